@@ -45,6 +45,24 @@ pub enum AgentRole {
     System,
 }
 
+impl std::str::FromStr for AgentRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "planner" => Ok(AgentRole::Planner),
+            "implementer" => Ok(AgentRole::Implementer),
+            "reviewer" => Ok(AgentRole::Reviewer),
+            "tester" => Ok(AgentRole::Tester),
+            "historian" => Ok(AgentRole::Historian),
+            "admin" => Ok(AgentRole::Admin),
+            "any" => Ok(AgentRole::Any),
+            "system" => Ok(AgentRole::System),
+            other => Err(format!("unknown agent role '{}'", other)),
+        }
+    }
+}
+
 /// Agent information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Agent {
@@ -3,6 +3,9 @@
 //! Extracted from `orchestrator.rs` to reduce god-object complexity.
 //! Holds cloned Arc references to shared resources and runs the main
 //! conversation loop (`run_main_loop`) plus tool execution (`execute_tool_calls`).
+//!
+//! Logs run inside a `session{session_id, task_id, round}` span (`round`
+//! follows the latest execution event); each tool call nests a `tool` span.
 
 use super::helpers::{
     MAX_CONVERSATION_MESSAGES, compact_preview, is_confirmation_permission_error,
@@ -11,21 +14,23 @@ use super::helpers::{
 use super::orchestrator::{AgentConfig, AgentResponse, ToolExecutor};
 use super::{
     AgentError, AgentExecutionEvent, AgentExecutionEventKind, AgentMessage, AgentSession,
-    AgentSessionExecutionEvent, AgentToolCall, AgentWorkflowStage, AnalysisResult,
-    ContextSnapshot, TaskVerifier, TodoExecutionScenario, VerificationResult, prompt_builder,
+    AgentSessionExecutionEvent, AgentToolCall, AgentWorkflowStage, AnalysisResult, ContextSnapshot,
+    TaskVerifier, TodoExecutionScenario, VerificationResult, prompt_builder,
     session_store::SessionStore,
 };
 use crate::TaskId;
 use crate::llm::provider::{
-    CompletionRequest, LlmProvider, Message, MessageRole, ToolCall as LlmToolCall,
-    ToolResult as LlmToolResult,
+    CompletionRequest, CompletionResponse, LlmProvider, Message, MessageRole,
+    ToolCall as LlmToolCall, ToolResult as LlmToolResult,
 };
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::{Mutex, broadcast};
-use tracing::{info, warn};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, info, info_span, instrument, warn};
 
 /// Accumulated token counts for the current session run.
+#[derive(Debug, Clone, Copy, Default)]
 struct SessionTokenTotals {
     prompt: u64,
     completion: u64,
@@ -42,6 +47,10 @@ pub(crate) struct ConversationRunner {
     config: AgentConfig,
     event_tx: broadcast::Sender<AgentSessionExecutionEvent>,
     store: Arc<Mutex<SessionStore>>,
+    /// Usage summed over every LLM call of this run
+    token_totals: std::sync::Mutex<SessionTokenTotals>,
+    /// Checked before every LLM and tool call
+    cancel: CancellationToken,
 }
 
 impl ConversationRunner {
@@ -60,9 +69,17 @@ impl ConversationRunner {
             config,
             event_tx,
             store,
+            token_totals: std::sync::Mutex::new(SessionTokenTotals::default()),
+            cancel: CancellationToken::new(),
         }
     }
 
+    /// Stop the run at the next LLM or tool call once `cancel` fires.
+    pub(crate) fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
     // ── event helpers ───────────────────────────────────────────────
 
     async fn emit_event(
@@ -71,9 +88,14 @@ impl ConversationRunner {
         execution_events: &mut Vec<AgentExecutionEvent>,
         event: AgentExecutionEvent,
     ) {
+        tracing::Span::current().record("round", event.round);
+        session_state.add_execution_event(event.clone());
+        execution_events.push(event.clone());
+        // Save first, so subscribers reading the session see the event they receive
+        self.save_session(session_state.clone()).await;
         if let Err(e) = self.event_tx.send(AgentSessionExecutionEvent {
             session_id: session_state.id.clone(),
-            event: event.clone(),
+            event,
         }) {
             tracing::warn!(
                 receivers = self.event_tx.receiver_count(),
@@ -81,9 +103,6 @@ impl ConversationRunner {
                 e
             );
         }
-        session_state.add_execution_event(event.clone());
-        execution_events.push(event);
-        self.save_session(session_state.clone()).await;
     }
 
     async fn emit_workflow_stage(
@@ -155,6 +174,156 @@ impl ConversationRunner {
         .await;
     }
 
+    // ── token budget ────────────────────────────────────────────────
+
+    fn session_token_totals(&self) -> SessionTokenTotals {
+        *self
+            .token_totals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record_usage(&self, usage: &crate::llm::provider::Usage) -> SessionTokenTotals {
+        let mut totals = self
+            .token_totals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        totals.prompt += usage.prompt_tokens as u64;
+        totals.completion += usage.completion_tokens as u64;
+        totals.total += usage.total_tokens as u64;
+        *totals
+    }
+
+    /// Refuse to issue another LLM call once `max_session_tokens` is spent.
+    ///
+    /// Emits a final `TokenUsage` event carrying the session totals so the
+    /// UI shows the spend that tripped the budget.
+    async fn ensure_token_budget(
+        &self,
+        session_state: &mut AgentSession,
+        execution_events: &mut Vec<AgentExecutionEvent>,
+        round: usize,
+    ) -> Result<(), AgentError> {
+        let Some(budget) = self.config.max_session_tokens else {
+            return Ok(());
+        };
+        let totals = self.session_token_totals();
+        if totals.total < budget {
+            return Ok(());
+        }
+
+        warn!(
+            "Session token budget exhausted: {} >= {}",
+            totals.total, budget
+        );
+        self.emit_event(
+            session_state,
+            execution_events,
+            AgentExecutionEvent {
+                kind: AgentExecutionEventKind::TokenUsage,
+                timestamp: chrono::Utc::now(),
+                message: format!(
+                    "token_usage: source=budget prompt=0 completion=0 total=0 | session_prompt_total={} session_completion_total={} session_total={} budget={}",
+                    totals.prompt, totals.completion, totals.total, budget
+                ),
+                round,
+                tool_name: None,
+                tool_call_id: None,
+                duration_ms: None,
+                is_error: true,
+                workflow_stage: None,
+                workflow_detail: None,
+                workflow_stage_index: None,
+                workflow_stage_total: None,
+            },
+        )
+        .await;
+        Err(AgentError::Other(format!(
+            "token_budget_exhausted: session used {} tokens, budget is {}",
+            totals.total, budget
+        )))
+    }
+
+    // ── cancellation ────────────────────────────────────────────────
+
+    /// Abort the run if cancellation was requested.
+    ///
+    /// In-flight calls are never interrupted; this runs between them. The
+    /// executor gets a chance to compensate partial side effects, then a
+    /// `session_cancelled` status is emitted, which also persists the
+    /// progress made so far.
+    async fn ensure_not_cancelled(
+        &self,
+        session_state: &mut AgentSession,
+        execution_events: &mut Vec<AgentExecutionEvent>,
+        round: usize,
+    ) -> Result<(), AgentError> {
+        if !self.cancel.is_cancelled() {
+            return Ok(());
+        }
+
+        warn!("Agent run cancelled at round {}", round);
+        if let Err(e) = self.tool_executor.compensate_cancelled().await {
+            warn!("Compensation after cancellation failed: {}", e);
+        }
+        self.emit_event(
+            session_state,
+            execution_events,
+            AgentExecutionEvent {
+                kind: AgentExecutionEventKind::SessionStatus,
+                timestamp: chrono::Utc::now(),
+                message: "session_cancelled".to_string(),
+                round,
+                tool_name: None,
+                tool_call_id: None,
+                duration_ms: None,
+                is_error: true,
+                workflow_stage: None,
+                workflow_detail: None,
+                workflow_stage_index: None,
+                workflow_stage_total: None,
+            },
+        )
+        .await;
+        Err(AgentError::Cancelled)
+    }
+
+    /// Issue an LLM call under the session token budget and record its usage.
+    async fn complete_within_budget(
+        &self,
+        request: &CompletionRequest,
+        session_state: &mut AgentSession,
+        execution_events: &mut Vec<AgentExecutionEvent>,
+        round: usize,
+    ) -> Result<CompletionResponse, AgentError> {
+        self.ensure_not_cancelled(session_state, execution_events, round)
+            .await?;
+        self.ensure_token_budget(session_state, execution_events, round)
+            .await?;
+
+        let response = self
+            .provider
+            .complete(request)
+            .await
+            .map_err(|e| AgentError::LlmError(e.to_string()))?;
+        let usage = response
+            .usage
+            .clone()
+            .unwrap_or_else(|| self.provider.estimate_tokens(request));
+        let usage_estimated = response.usage.is_none();
+        let totals = self.record_usage(&usage);
+        self.emit_token_usage(
+            session_state,
+            execution_events,
+            round,
+            usage,
+            &totals,
+            usage_estimated,
+        )
+        .await;
+        Ok(response)
+    }
+
     // ── delegation helpers ──────────────────────────────────────────
 
     async fn save_session(&self, session: AgentSession) {
@@ -168,6 +337,7 @@ impl ConversationRunner {
         active_task_id: Option<TaskId>,
         working_dir: Option<std::path::PathBuf>,
         working_memory: Option<crate::WorkingMemory>,
+        memory_context: Option<String>,
     ) -> Result<Vec<Message>, AgentError> {
         prompt_builder::build_messages(
             session,
@@ -175,6 +345,7 @@ impl ConversationRunner {
             active_task_id,
             working_dir,
             working_memory,
+            memory_context,
             &self.config.system_prompt_template,
             self.tool_executor.tool_schemas(),
         )
@@ -191,6 +362,15 @@ impl ConversationRunner {
     // ── main conversation loop ──────────────────────────────────────
 
     /// Run the non-streaming conversation loop.
+    #[instrument(
+        name = "session",
+        skip_all,
+        fields(
+            session_id = %session.id,
+            task_id = active_task_id.map(|id| id.to_string()),
+            round = tracing::field::Empty,
+        )
+    )]
     pub(crate) async fn run_main_loop(
         &self,
         session: AgentSession,
@@ -198,6 +378,7 @@ impl ConversationRunner {
         active_task_id: Option<TaskId>,
         working_dir: Option<std::path::PathBuf>,
         working_memory: Option<crate::WorkingMemory>,
+        memory_context: Option<String>,
     ) -> Result<AgentResponse, AgentError> {
         let mut messages = self
             .build_messages(
@@ -206,6 +387,7 @@ impl ConversationRunner {
                 active_task_id,
                 working_dir.clone(),
                 working_memory,
+                memory_context,
             )
             .await?;
         let mut session_state = session.clone();
@@ -220,23 +402,13 @@ impl ConversationRunner {
 
         if Self::todo_workflow_enabled() {
             return self
-                .run_todo_driven_main_loop(
-                    session,
-                    messages,
-                    session_state,
-                    active_task_id,
-                )
+                .run_todo_driven_main_loop(session, messages, session_state, active_task_id)
                 .await;
         }
 
         let mut tool_call_count = 0;
         let mut all_tool_calls: Vec<AgentToolCall> = Vec::new();
         let mut execution_events: Vec<AgentExecutionEvent> = Vec::new();
-        let mut session_token_totals = SessionTokenTotals {
-            prompt: 0,
-            completion: 0,
-            total: 0,
-        };
         self.emit_event(
             &mut session_state,
             &mut execution_events,
@@ -353,31 +525,18 @@ impl ConversationRunner {
                 } else {
                     Some(tool_schemas)
                 },
+                tool_choice: None,
             };
             let llm_started = Instant::now();
 
             let response = self
-                .provider
-                .complete(&llm_request)
-                .await
-                .map_err(|e| AgentError::LlmError(e.to_string()))?;
-            let usage = response
-                .usage
-                .clone()
-                .unwrap_or_else(|| self.provider.estimate_tokens(&llm_request));
-            let usage_estimated = response.usage.is_none();
-            session_token_totals.prompt += usage.prompt_tokens as u64;
-            session_token_totals.completion += usage.completion_tokens as u64;
-            session_token_totals.total += usage.total_tokens as u64;
-            self.emit_token_usage(
-                &mut session_state,
-                &mut execution_events,
-                round,
-                usage,
-                &session_token_totals,
-                usage_estimated,
-            )
-            .await;
+                .complete_within_budget(
+                    &llm_request,
+                    &mut session_state,
+                    &mut execution_events,
+                    round,
+                )
+                .await?;
 
             // 获取助手响应
             let assistant_message = response
@@ -509,6 +668,7 @@ impl ConversationRunner {
                         // We use `name` to carry tool_call_id for provider adapters.
                         name: Some(result.tool_call_id.clone()),
                         tool_calls: None,
+                        cacheable: false,
                     });
                     session_state.add_message(AgentMessage {
                         role: MessageRole::Tool,
@@ -610,6 +770,7 @@ impl ConversationRunner {
                     content: feedback,
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 });
                 session_state.add_message(AgentMessage {
                     role: MessageRole::System,
@@ -946,11 +1107,7 @@ impl ConversationRunner {
     /// Truncate messages when estimated tokens exceed threshold.
     /// Returns `true` if compression was applied.
     #[allow(dead_code)]
-    fn compress_context(
-        &self,
-        messages: &mut Vec<Message>,
-        snapshot: &ContextSnapshot,
-    ) -> bool {
+    fn compress_context(&self, messages: &mut Vec<Message>, snapshot: &ContextSnapshot) -> bool {
         if snapshot.estimated_tokens <= Self::CONTEXT_TOKEN_THRESHOLD {
             return false;
         }
@@ -987,6 +1144,7 @@ impl ConversationRunner {
             .to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let mut analysis_messages = messages.to_vec();
@@ -1003,13 +1161,12 @@ impl ConversationRunner {
             stop: None,
             stream: false,
             tools: None,
+            tool_choice: None,
         };
 
         let response = self
-            .provider
-            .complete(&request)
-            .await
-            .map_err(|e| AgentError::LlmError(e.to_string()))?;
+            .complete_within_budget(&request, session_state, execution_events, round)
+            .await?;
 
         let content = response
             .choices
@@ -1017,15 +1174,14 @@ impl ConversationRunner {
             .map(|c| c.message.content.as_str())
             .unwrap_or("");
 
-        let result: AnalysisResult = serde_json::from_str(content).unwrap_or_else(|_| {
-            AnalysisResult {
+        let result: AnalysisResult =
+            serde_json::from_str(content).unwrap_or_else(|_| AnalysisResult {
                 summary: content.to_string(),
                 affected_scope: vec![],
                 constraints: vec![],
                 scenario_hint: TodoExecutionScenario::Normal,
                 risks: vec![],
-            }
-        });
+            });
 
         self.emit_event(
             session_state,
@@ -1081,6 +1237,7 @@ impl ConversationRunner {
             ),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let mut planning_messages = messages.to_vec();
@@ -1097,13 +1254,12 @@ impl ConversationRunner {
             stop: None,
             stream: false,
             tools: None,
+            tool_choice: None,
         };
 
         let response = self
-            .provider
-            .complete(&request)
-            .await
-            .map_err(|e| AgentError::LlmError(e.to_string()))?;
+            .complete_within_budget(&request, session_state, execution_events, round)
+            .await?;
 
         let content = response
             .choices
@@ -1120,10 +1276,7 @@ impl ConversationRunner {
 
         // Fallback: must produce at least 1 TODO
         let todos = if todos.is_empty() {
-            vec![format!(
-                "Execute user request: {}",
-                analysis.summary
-            )]
+            vec![format!("Execute user request: {}", analysis.summary)]
         } else {
             todos
         };
@@ -1168,10 +1321,26 @@ impl ConversationRunner {
         }
         let lower = title.to_lowercase();
         let coding_keywords = [
-            "implement", "refactor", "fix", "add test", "write test", "unit test",
-            "write", "create", "build", "modify", "update code", "add function",
-            "add method", "extract", "move", "rename", "delete code", "remove code",
-            "bug", "patch",
+            "implement",
+            "refactor",
+            "fix",
+            "add test",
+            "write test",
+            "unit test",
+            "write",
+            "create",
+            "build",
+            "modify",
+            "update code",
+            "add function",
+            "add method",
+            "extract",
+            "move",
+            "rename",
+            "delete code",
+            "remove code",
+            "bug",
+            "patch",
         ];
         for kw in &coding_keywords {
             if lower.contains(kw) {
@@ -1199,6 +1368,7 @@ impl ConversationRunner {
             content: context_prompt.to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         });
 
         let mut round = round_start;
@@ -1209,7 +1379,10 @@ impl ConversationRunner {
             round += 1;
 
             if tool_call_count >= self.config.max_tool_calls {
-                warn!("Max tool calls exceeded in run_rounds_with_context: {}", tool_call_count);
+                warn!(
+                    "Max tool calls exceeded in run_rounds_with_context: {}",
+                    tool_call_count
+                );
                 return Ok((
                     format!(
                         "Reached maximum tool calls ({}) for this TODO.",
@@ -1237,13 +1410,12 @@ impl ConversationRunner {
                 } else {
                     Some(tool_schemas)
                 },
+                tool_choice: None,
             };
 
             let response = self
-                .provider
-                .complete(&request)
-                .await
-                .map_err(|e| AgentError::LlmError(e.to_string()))?;
+                .complete_within_budget(&request, session_state, execution_events, round)
+                .await?;
 
             let assistant_message = response
                 .choices
@@ -1281,6 +1453,7 @@ impl ConversationRunner {
                         content: sanitized,
                         name: Some(result.tool_call_id.clone()),
                         tool_calls: None,
+                        cacheable: false,
                     });
                 }
                 continue;
@@ -1616,6 +1789,8 @@ impl ConversationRunner {
         let mut results = Vec::new();
 
         for tool_call in tool_calls {
+            self.ensure_not_cancelled(session_state, execution_events, round)
+                .await?;
             let function = &tool_call.function;
             let tool_name = &function.name;
 
@@ -1649,6 +1824,7 @@ impl ConversationRunner {
             let tool_result = match self
                 .tool_executor
                 .execute_tool(tool_name, &function.arguments)
+                .instrument(info_span!("tool", tool = %tool_name, call_id = %tool_call.id))
                 .await
             {
                 Ok(content) => LlmToolResult {
@@ -1832,9 +2008,7 @@ impl ConversationRunner {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ai_agent::{
-        AnalysisResult, ContextSnapshot, TaskStorage, TodoExecutionScenario,
-    };
+    use crate::ai_agent::{AnalysisResult, ContextSnapshot, TaskStorage, TodoExecutionScenario};
     use crate::llm::provider::{
         Choice, CompletionResponse, ModelInfo, ModelPermission, ProviderConfig, ProviderType,
         StreamHandler, ToolCallFunction, Usage,
@@ -1920,6 +2094,8 @@ mod tests {
                 prompt_tokens: 1,
                 completion_tokens: 1,
                 total_tokens: 2,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }
         }
         async fn is_model_available(&self, _model: &str) -> bool {
@@ -2058,6 +2234,7 @@ mod tests {
 
         let tool_calls = vec![LlmToolCall {
             id: "call-1".to_string(),
+            index: None,
             function: ToolCallFunction {
                 name: "write".to_string(),
                 arguments: "{}".to_string(),
@@ -2108,6 +2285,7 @@ mod tests {
 
         let tool_calls = vec![LlmToolCall {
             id: "call-fail".to_string(),
+            index: None,
             function: ToolCallFunction {
                 name: "write".to_string(),
                 arguments: "{}".to_string(),
@@ -2138,6 +2316,7 @@ mod tests {
                     content: "Hello, world!".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2146,6 +2325,8 @@ mod tests {
                 prompt_tokens: 10,
                 completion_tokens: 5,
                 total_tokens: 15,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
 
@@ -2160,10 +2341,11 @@ mod tests {
             content: "Hi".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let result = runner
-            .run_main_loop(session, user_msg, None, None, None)
+            .run_main_loop(session, user_msg, None, None, None, None)
             .await
             .expect("should succeed");
 
@@ -2196,11 +2378,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![LlmToolCall {
                         id: "tc".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: "{}".to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -2230,10 +2414,11 @@ mod tests {
             content: "do stuff".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let result = runner
-            .run_main_loop(session, user_msg, None, None, None)
+            .run_main_loop(session, user_msg, None, None, None, None)
             .await
             .expect("should return max-exceeded response");
 
@@ -2264,11 +2449,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![LlmToolCall {
                         id: "tool-1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: r#"{"path":"test.txt"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -2288,6 +2475,7 @@ mod tests {
                     content: "Done writing.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2310,10 +2498,11 @@ mod tests {
             content: "write something".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let result = runner
-            .run_main_loop(session, user_msg, None, None, None)
+            .run_main_loop(session, user_msg, None, None, None, None)
             .await
             .expect("should succeed");
 
@@ -2335,16 +2524,21 @@ mod tests {
                 content: "Hello world, this is a test message".to_string(), // 34 chars
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             },
             Message {
                 role: MessageRole::Assistant,
                 content: "I understand".to_string(), // 12 chars
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             },
         ];
         let estimate = ConversationRunner::estimate_context_tokens(&messages);
-        assert!(estimate > 0, "estimate should be non-zero for non-empty messages");
+        assert!(
+            estimate > 0,
+            "estimate should be non-zero for non-empty messages"
+        );
         // 46 chars total → ~11 tokens at 4 chars/token; allow ±20%
         assert!(
             estimate >= 8 && estimate <= 16,
@@ -2371,6 +2565,7 @@ mod tests {
             content: "test".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
 
         let snapshot = runner.load_context(&messages);
@@ -2389,6 +2584,7 @@ mod tests {
             content: "Hello there, please analyze my code and refactor it".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
 
         let snapshot = runner.load_context(&messages);
@@ -2410,12 +2606,14 @@ mod tests {
                 content: "You are an assistant".to_string(),
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             },
             Message {
                 role: MessageRole::User,
                 content: "short".to_string(),
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             },
         ];
         let snapshot = ContextSnapshot {
@@ -2444,6 +2642,7 @@ mod tests {
             content: "You are an assistant".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         })
         .chain((0..80).map(|i| Message {
             role: if i % 2 == 0 {
@@ -2454,6 +2653,7 @@ mod tests {
             content: format!("Message {} with padding content for token count", i),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }))
         .collect();
         let snapshot = ContextSnapshot {
@@ -2490,6 +2690,7 @@ mod tests {
                     content: analysis_json.to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2498,6 +2699,8 @@ mod tests {
                 prompt_tokens: 100,
                 completion_tokens: 50,
                 total_tokens: 150,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -2509,6 +2712,7 @@ mod tests {
             content: "refactor auth module".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let mut session = AgentSession::new("analysis-test".to_string());
         let mut events = Vec::new();
@@ -2519,9 +2723,16 @@ mod tests {
             .expect("should parse analysis result");
         assert_eq!(result.summary, "User wants to refactor auth module");
         assert_eq!(result.affected_scope.len(), 2);
-        assert!(matches!(result.scenario_hint, TodoExecutionScenario::Coding));
+        assert!(matches!(
+            result.scenario_hint,
+            TodoExecutionScenario::Coding
+        ));
         // Should emit Analysis workflow stage
-        assert!(events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Analysis)));
+        assert!(
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Analysis))
+        );
     }
 
     #[tokio::test]
@@ -2545,6 +2756,7 @@ mod tests {
                     content: planning_json.to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2553,6 +2765,8 @@ mod tests {
                 prompt_tokens: 80,
                 completion_tokens: 30,
                 total_tokens: 110,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let analysis = AnalysisResult {
@@ -2571,6 +2785,7 @@ mod tests {
             content: "refactor auth module".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let mut session = AgentSession::new("planning-test".to_string());
         let mut events = Vec::new();
@@ -2586,7 +2801,11 @@ mod tests {
         assert_eq!(todos.len(), 3);
         assert_eq!(todos[0], "Extract JWT logic into jwt.rs");
         // Should emit Planning workflow stage
-        assert!(events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Planning)));
+        assert!(
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Planning))
+        );
         let planning_event = events
             .iter()
             .find(|e| e.kind == AgentExecutionEventKind::PlanningComplete)
@@ -2598,9 +2817,7 @@ mod tests {
             .trim();
         let parsed: serde_json::Value =
             serde_json::from_str(payload).expect("planning payload should be valid JSON");
-        let event_todos = parsed["todos"]
-            .as_array()
-            .expect("todos should be array");
+        let event_todos = parsed["todos"].as_array().expect("todos should be array");
         assert_eq!(event_todos.len(), 3);
     }
 
@@ -2619,6 +2836,7 @@ mod tests {
                     content: "I'm not sure what to do.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2627,6 +2845,8 @@ mod tests {
                 prompt_tokens: 50,
                 completion_tokens: 10,
                 total_tokens: 60,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let analysis = AnalysisResult {
@@ -2645,6 +2865,7 @@ mod tests {
             content: "what is this?".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let mut session = AgentSession::new("planning-fallback-test".to_string());
         let mut events = Vec::new();
@@ -2665,23 +2886,38 @@ mod tests {
     fn test_classify_scenario_coding_keywords() {
         // Titles containing file-change keywords → Coding
         assert_eq!(
-            ConversationRunner::classify_scenario("Implement auth middleware", &TodoExecutionScenario::Coding),
+            ConversationRunner::classify_scenario(
+                "Implement auth middleware",
+                &TodoExecutionScenario::Coding
+            ),
             TodoExecutionScenario::Coding,
         );
         assert_eq!(
-            ConversationRunner::classify_scenario("Refactor database module", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Refactor database module",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Coding,
         );
         assert_eq!(
-            ConversationRunner::classify_scenario("Fix bug in parser", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Fix bug in parser",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Coding,
         );
         assert_eq!(
-            ConversationRunner::classify_scenario("Add unit tests for auth", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Add unit tests for auth",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Coding,
         );
         assert_eq!(
-            ConversationRunner::classify_scenario("Write new handler function", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Write new handler function",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Coding,
         );
     }
@@ -2690,15 +2926,24 @@ mod tests {
     fn test_classify_scenario_normal_keywords() {
         // Non-code tasks → Normal
         assert_eq!(
-            ConversationRunner::classify_scenario("Update documentation", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Update documentation",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Normal,
         );
         assert_eq!(
-            ConversationRunner::classify_scenario("Research API options", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Research API options",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Normal,
         );
         assert_eq!(
-            ConversationRunner::classify_scenario("Configure CI pipeline", &TodoExecutionScenario::Normal),
+            ConversationRunner::classify_scenario(
+                "Configure CI pipeline",
+                &TodoExecutionScenario::Normal
+            ),
             TodoExecutionScenario::Normal,
         );
     }
@@ -2707,12 +2952,18 @@ mod tests {
     fn test_classify_scenario_respects_hint() {
         // When analysis says Coding, even normal-sounding title stays Coding
         assert_eq!(
-            ConversationRunner::classify_scenario("Update config settings", &TodoExecutionScenario::Coding),
+            ConversationRunner::classify_scenario(
+                "Update config settings",
+                &TodoExecutionScenario::Coding
+            ),
             TodoExecutionScenario::Coding,
         );
         // FastPath hint is preserved for short tasks
         assert_eq!(
-            ConversationRunner::classify_scenario("Quick question", &TodoExecutionScenario::FastPath),
+            ConversationRunner::classify_scenario(
+                "Quick question",
+                &TodoExecutionScenario::FastPath
+            ),
             TodoExecutionScenario::FastPath,
         );
     }
@@ -2731,6 +2982,7 @@ mod tests {
                     content: "Done with context".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2739,6 +2991,8 @@ mod tests {
                 prompt_tokens: 20,
                 completion_tokens: 10,
                 total_tokens: 30,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -2750,6 +3004,7 @@ mod tests {
             content: "do something".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let mut session = AgentSession::new("rounds-ctx".to_string());
         let mut events = Vec::new();
@@ -2784,6 +3039,7 @@ mod tests {
                     content: "Implemented the feature.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2792,6 +3048,8 @@ mod tests {
                 prompt_tokens: 50,
                 completion_tokens: 20,
                 total_tokens: 70,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -2803,6 +3061,7 @@ mod tests {
             content: "implement auth".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let analysis = AnalysisResult {
             summary: "Auth module".to_string(),
@@ -2829,16 +3088,22 @@ mod tests {
 
         // Must emit TodoExecutionStart and TodoExecutionEnd
         assert!(
-            events.iter().any(|e| e.kind == AgentExecutionEventKind::TodoExecutionStart),
+            events
+                .iter()
+                .any(|e| e.kind == AgentExecutionEventKind::TodoExecutionStart),
             "should emit TodoExecutionStart"
         );
         assert!(
-            events.iter().any(|e| e.kind == AgentExecutionEventKind::TodoExecutionEnd),
+            events
+                .iter()
+                .any(|e| e.kind == AgentExecutionEventKind::TodoExecutionEnd),
             "should emit TodoExecutionEnd"
         );
         // Executing workflow stage must be emitted
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Executing)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Executing)),
             "should emit Executing workflow stage"
         );
     }
@@ -2858,6 +3123,7 @@ mod tests {
                     content: "Written TDD code.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2866,6 +3132,8 @@ mod tests {
                 prompt_tokens: 60,
                 completion_tokens: 15,
                 total_tokens: 75,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -2877,6 +3145,7 @@ mod tests {
             content: "add auth tests".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let analysis = AnalysisResult {
             summary: "Auth tests".to_string(),
@@ -2925,6 +3194,7 @@ mod tests {
                     content: "All TODOs verified. No regressions found.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2933,6 +3203,8 @@ mod tests {
                 prompt_tokens: 40,
                 completion_tokens: 15,
                 total_tokens: 55,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -2944,11 +3216,9 @@ mod tests {
             content: "verify everything".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
-        let todos = vec![
-            "Implement auth".to_string(),
-            "Add tests".to_string(),
-        ];
+        let todos = vec!["Implement auth".to_string(), "Add tests".to_string()];
         let mut session = AgentSession::new("verify-test".to_string());
         let mut events = Vec::new();
 
@@ -2959,7 +3229,9 @@ mod tests {
 
         assert!(!result.is_empty(), "verification should return content");
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Verifying)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Verifying)),
             "should emit Verifying stage"
         );
     }
@@ -2978,6 +3250,7 @@ mod tests {
                     content: "Documentation updated. Memory entries saved.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -2986,6 +3259,8 @@ mod tests {
                 prompt_tokens: 30,
                 completion_tokens: 10,
                 total_tokens: 40,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -2997,6 +3272,7 @@ mod tests {
             content: "wrap up".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let todos = vec!["Done task".to_string()];
         let mut session = AgentSession::new("completion-test".to_string());
@@ -3009,7 +3285,9 @@ mod tests {
 
         assert!(!result.is_empty());
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Completing)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Completing)),
             "should emit Completing stage"
         );
     }
@@ -3025,9 +3303,11 @@ mod tests {
                 index: 0,
                 message: Message {
                     role: MessageRole::Assistant,
-                    content: "## Report\n- 3/3 TODOs completed (100%)\n- All tests pass".to_string(),
+                    content: "## Report\n- 3/3 TODOs completed (100%)\n- All tests pass"
+                        .to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -3036,6 +3316,8 @@ mod tests {
                 prompt_tokens: 50,
                 completion_tokens: 20,
                 total_tokens: 70,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let runner = make_runner(
@@ -3047,6 +3329,7 @@ mod tests {
             content: "generate report".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let todos = vec![
             "Task A".to_string(),
@@ -3071,11 +3354,15 @@ mod tests {
 
         assert!(!report.is_empty(), "report should not be empty");
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Reporting)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Reporting)),
             "should emit Reporting stage"
         );
         assert!(
-            events.iter().any(|e| e.kind == AgentExecutionEventKind::Report),
+            events
+                .iter()
+                .any(|e| e.kind == AgentExecutionEventKind::Report),
             "should emit Report event"
         );
     }
@@ -3099,6 +3386,7 @@ mod tests {
                         content: content.to_string(),
                         name: None,
                         tool_calls: None,
+                        cacheable: false,
                     },
                     finish_reason: Some("stop".to_string()),
                     logprobs: None,
@@ -3107,12 +3395,16 @@ mod tests {
                     prompt_tokens: 10,
                     completion_tokens: 5,
                     total_tokens: 15,
+                    cache_creation_tokens: 0,
+                    cache_read_tokens: 0,
                 }),
             }
         }
 
         let provider = Arc::new(ScriptedProvider::new(vec![
-            mk_resp(r#"{"summary":"Add logging","affected_scope":["runtime"],"constraints":[],"scenario_hint":"coding","risks":["low"]}"#),
+            mk_resp(
+                r#"{"summary":"Add logging","affected_scope":["runtime"],"constraints":[],"scenario_hint":"coding","risks":["low"]}"#,
+            ),
             mk_resp(r#"{"todos":["Add tracing to runtime","Write tests for tracing"]}"#),
             mk_resp("Added tracing::info! calls to runtime methods."),
             mk_resp("Wrote tests verifying tracing output."),
@@ -3126,6 +3418,7 @@ mod tests {
             content: "Add logging to the runtime".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }];
         let mut session = AgentSession::new("e2e-test".to_string());
         let mut events: Vec<AgentExecutionEvent> = Vec::new();
@@ -3147,7 +3440,9 @@ mod tests {
             .expect("analysis should succeed");
         assert_eq!(analysis.summary, "Add logging");
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Analysis)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Analysis)),
             "should emit Analysis stage"
         );
 
@@ -3158,7 +3453,9 @@ mod tests {
             .expect("planning should succeed");
         assert_eq!(todos.len(), 2);
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Planning)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Planning)),
             "should emit Planning stage"
         );
 
@@ -3178,11 +3475,15 @@ mod tests {
                 .expect("execute should succeed");
         }
         assert!(
-            events.iter().any(|e| e.kind == AgentExecutionEventKind::TodoExecutionStart),
+            events
+                .iter()
+                .any(|e| e.kind == AgentExecutionEventKind::TodoExecutionStart),
             "should emit TodoExecutionStart"
         );
         assert!(
-            events.iter().any(|e| e.kind == AgentExecutionEventKind::TodoExecutionEnd),
+            events
+                .iter()
+                .any(|e| e.kind == AgentExecutionEventKind::TodoExecutionEnd),
             "should emit TodoExecutionEnd"
         );
 
@@ -3193,7 +3494,9 @@ mod tests {
             .expect("verification should succeed");
         assert!(verification.contains("pass"));
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Verifying)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Verifying)),
             "should emit Verifying stage"
         );
 
@@ -3204,7 +3507,9 @@ mod tests {
             .expect("completion should succeed");
         assert!(!completion.is_empty());
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Completing)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Completing)),
             "should emit Completing stage"
         );
 
@@ -3222,19 +3527,21 @@ mod tests {
             .expect("report should succeed");
         assert!(report.contains("2/2"));
         assert!(
-            events.iter().any(|e| e.workflow_stage == Some(AgentWorkflowStage::Reporting)),
+            events
+                .iter()
+                .any(|e| e.workflow_stage == Some(AgentWorkflowStage::Reporting)),
             "should emit Reporting stage"
         );
         assert!(
-            events.iter().any(|e| e.kind == AgentExecutionEventKind::Report),
+            events
+                .iter()
+                .any(|e| e.kind == AgentExecutionEventKind::Report),
             "should emit Report event"
         );
 
         // Verify all stages that emit events were covered
-        let stages_emitted: std::collections::HashSet<_> = events
-            .iter()
-            .filter_map(|e| e.workflow_stage)
-            .collect();
+        let stages_emitted: std::collections::HashSet<_> =
+            events.iter().filter_map(|e| e.workflow_stage).collect();
         assert!(stages_emitted.contains(&AgentWorkflowStage::Analysis));
         assert!(stages_emitted.contains(&AgentWorkflowStage::Planning));
         assert!(stages_emitted.contains(&AgentWorkflowStage::Executing));
@@ -3258,6 +3565,7 @@ mod tests {
                         content: content.to_string(),
                         name: None,
                         tool_calls: None,
+                        cacheable: false,
                     },
                     finish_reason: Some("stop".to_string()),
                     logprobs: None,
@@ -3266,13 +3574,17 @@ mod tests {
                     prompt_tokens: 10,
                     completion_tokens: 5,
                     total_tokens: 15,
+                    cache_creation_tokens: 0,
+                    cache_read_tokens: 0,
                 }),
             }
         }
 
         let runner = make_runner(
             Arc::new(ScriptedProvider::new(vec![
-                mk_resp(r#"{"summary":"Fix auth","affected_scope":["auth.rs"],"constraints":[],"scenario_hint":"coding","risks":[]}"#),
+                mk_resp(
+                    r#"{"summary":"Fix auth","affected_scope":["auth.rs"],"constraints":[],"scenario_hint":"coding","risks":[]}"#,
+                ),
                 mk_resp(r#"{"todos":["Refactor auth flow"]}"#),
                 mk_resp("Refactored auth flow with tests."),
                 mk_resp("Verification passed."),
@@ -3288,10 +3600,11 @@ mod tests {
             content: "Fix auth flow".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let mut messages = runner
-            .build_messages(&session, &user_msg, None, None, None)
+            .build_messages(&session, &user_msg, None, None, None, None)
             .await
             .expect("messages");
         let mut session_state = session.clone();
@@ -3336,4 +3649,94 @@ mod tests {
             "should emit TodoStateChange for sidebar updates"
         );
     }
+
+    #[tokio::test]
+    async fn test_run_main_loop_stops_at_token_budget() {
+        // Every call reports 100 tokens and requests another tool call
+        let tool_response = || CompletionResponse {
+            id: "resp".to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "mock-model".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: MessageRole::Assistant,
+                    content: String::new(),
+                    name: None,
+                    tool_calls: Some(vec![LlmToolCall {
+                        id: "tc".to_string(),
+                        index: None,
+                        function: ToolCallFunction {
+                            name: "write".to_string(),
+                            arguments: "{}".to_string(),
+                        },
+                    }]),
+                    cacheable: false,
+                },
+                finish_reason: None,
+                logprobs: None,
+            }],
+            usage: Some(Usage {
+                prompt_tokens: 60,
+                completion_tokens: 40,
+                total_tokens: 100,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+            }),
+        };
+
+        let responses: Vec<_> = (0..10).map(|_| tool_response()).collect();
+        let config = AgentConfig {
+            max_session_tokens: Some(300),
+            ..AgentConfig::default()
+        };
+
+        let tools = Arc::new(MockToolExecutor::new());
+        let verifier = Arc::new(TaskVerifier::new(Arc::new(MockStorage)));
+        let (event_tx, mut event_rx) = broadcast::channel(256);
+        let runner = ConversationRunner::new(
+            Arc::new(ScriptedProvider::new(responses)),
+            tools.clone(),
+            verifier,
+            config,
+            event_tx,
+            Arc::new(Mutex::new(SessionStore::new())),
+        );
+
+        let session = AgentSession::new("budget-test".to_string());
+        let user_msg = Message {
+            role: MessageRole::User,
+            content: "do stuff".to_string(),
+            name: None,
+            tool_calls: None,
+            cacheable: false,
+        };
+
+        let err = runner
+            .run_main_loop(session, user_msg, None, None, None, None)
+            .await
+            .expect_err("budget should stop the loop");
+
+        assert!(
+            matches!(err, AgentError::Other(ref msg) if msg.contains("token_budget_exhausted")),
+            "unexpected error: {:?}",
+            err
+        );
+        // Three calls reach the budget exactly; the fourth is never issued
+        assert_eq!(tools.calls.lock().await.len(), 3);
+        assert_eq!(runner.session_token_totals().total, 300);
+
+        let mut usage_events = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            if let Some(usage) = event.event.token_usage_info() {
+                usage_events.push((usage, event.event.is_error));
+            }
+        }
+        assert_eq!(usage_events.len(), 4);
+        let (last, is_error) = usage_events.last().unwrap();
+        assert!(*is_error);
+        assert_eq!(last.source, "budget");
+        assert_eq!(last.session_total, 300);
+    }
 }
@@ -73,6 +73,7 @@ pub(crate) fn truncate_messages(messages: &mut Vec<Message>, max_messages: usize
         content: "[earlier conversation history omitted for context window management]".to_string(),
         name: None,
         tool_calls: None,
+        cacheable: false,
     };
 
     // Remove messages after system prompt, replace with single placeholder
@@ -152,6 +153,7 @@ mod tests {
             content: content.to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         }
     }
 
@@ -257,6 +259,7 @@ mod tests {
     fn test_summarize_tool_calls_single() {
         let calls = vec![ToolCall {
             id: "call_1".to_string(),
+            index: None,
             function: ToolCallFunction {
                 name: "read_file".to_string(),
                 arguments: r#"{"path":"src/main.rs"}"#.to_string(),
@@ -272,6 +275,7 @@ mod tests {
         let calls: Vec<ToolCall> = (0..5)
             .map(|i| ToolCall {
                 id: format!("call_{}", i),
+                index: None,
                 function: ToolCallFunction {
                     name: format!("tool_{}", i),
                     arguments: "{}".to_string(),
@@ -27,7 +27,10 @@ pub use orchestrator::{
     AgentConfig, AgentOrchestrator, AgentRequest, AgentResponse, StreamEvent, ToolExecutor,
 };
 
-pub use session::{AgentMessage, AgentSession, ProjectIdentity, SessionManager, SessionState};
+pub use session::{
+    AgentMessage, AgentSession, COMPACTION_SUMMARY_PREFIX, CompactionConfig, ProjectIdentity,
+    SessionManager, SessionState,
+};
 
 pub use verifier::{TaskStorage, TaskVerifier, VerificationError, VerificationResult};
 
@@ -138,6 +141,8 @@ pub enum AgentExecutionEventKind {
     TodoExecutionEnd,
     /// 执行报告
     Report,
+    /// 警告（如决策引擎修改了动作）
+    Warning,
 }
 
 /// Workflow 阶段（统一语义）
@@ -396,6 +401,7 @@ pub fn default_agent_config() -> AgentConfig {
         auto_verify: true,
         require_permission_for_dangerous: true,
         system_prompt_template: None,
+        max_session_tokens: None,
     }
 }
 
@@ -534,8 +540,18 @@ mod tests {
             (AgentWorkflowStage::Reporting, "reporting", 8),
         ];
         for (stage, expected_str, expected_index) in stages {
-            assert_eq!(stage.as_str(), expected_str, "as_str failed for {:?}", stage);
-            assert_eq!(stage.index(), expected_index, "index failed for {:?}", stage);
+            assert_eq!(
+                stage.as_str(),
+                expected_str,
+                "as_str failed for {:?}",
+                stage
+            );
+            assert_eq!(
+                stage.index(),
+                expected_index,
+                "index failed for {:?}",
+                stage
+            );
             let parsed = AgentWorkflowStage::parse(expected_str)
                 .unwrap_or_else(|| panic!("parse failed for {}", expected_str));
             assert_eq!(parsed, stage, "roundtrip failed for {}", expected_str);
@@ -13,22 +13,28 @@ use super::{
 };
 use crate::llm::provider::{
     CompletionRequest, LlmProvider, Message, MessageRole, ProviderError, StreamHandler,
+    ToolCallAccumulator,
 };
 use crate::{AgentRole, TaskId};
 use async_trait::async_trait;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Mutex, broadcast};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
-/// 流式响应处理器 - 使用 Mutex 包装内容
+/// 流式响应处理器 - 使用 Mutex 包装内容和工具调用
 struct StreamingHandler {
     content: Arc<Mutex<String>>,
+    tool_calls: Arc<Mutex<ToolCallAccumulator>>,
 }
 
 impl StreamingHandler {
-    fn new(content: Arc<Mutex<String>>) -> Self {
-        Self { content }
+    fn new(content: Arc<Mutex<String>>, tool_calls: Arc<Mutex<ToolCallAccumulator>>) -> Self {
+        Self {
+            content,
+            tool_calls,
+        }
     }
 }
 
@@ -46,6 +52,7 @@ impl StreamHandler for StreamingHandler {
                 content.push_str(&delta.content);
             }
         }
+        self.tool_calls.lock().await.push_chunk(chunk);
         Ok(())
     }
 
@@ -84,6 +91,9 @@ pub struct AgentConfig {
 
     /// 自定义系统提示词模板
     pub system_prompt_template: Option<String>,
+
+    /// 单次会话运行的 token 上限 (None 表示不限制)
+    pub max_session_tokens: Option<u64>,
 }
 
 impl Default for AgentConfig {
@@ -96,6 +106,7 @@ impl Default for AgentConfig {
             auto_verify: true,
             require_permission_for_dangerous: true,
             system_prompt_template: None,
+            max_session_tokens: None,
         }
     }
 }
@@ -121,6 +132,11 @@ impl AgentConfig {
                 self.timeout_secs
             )));
         }
+        if self.max_session_tokens == Some(0) {
+            return Err(AgentError::ConfigError(
+                "max_session_tokens must be greater than 0".to_string(),
+            ));
+        }
         Ok(())
     }
 }
@@ -145,6 +161,9 @@ pub struct AgentRequest {
 
     /// Optional working memory (Abstract + Raw + Hard)
     pub working_memory: Option<crate::WorkingMemory>,
+
+    /// Relevant long-term memory assembled by `ContextBuilder`
+    pub memory_context: Option<String>,
 }
 
 /// Agent 响应
@@ -210,6 +229,11 @@ pub trait ToolExecutor: Send + Sync {
         Ok(None)
     }
 
+    /// 运行被取消后补偿已产生的副作用 (例如执行 saga 回滚)，默认不处理
+    async fn compensate_cancelled(&self) -> Result<(), AgentError> {
+        Ok(())
+    }
+
     /// 获取可用工具列表
     fn list_tools(&self) -> Vec<String>;
 
@@ -261,7 +285,7 @@ impl AgentOrchestrator {
     }
 
     /// Build a per-request `ConversationRunner` from shared state.
-    fn runner(&self) -> super::conversation_runner::ConversationRunner {
+    fn runner(&self, cancel: CancellationToken) -> super::conversation_runner::ConversationRunner {
         super::conversation_runner::ConversationRunner::new(
             self.provider.clone(),
             self.tool_executor.clone(),
@@ -270,6 +294,7 @@ impl AgentOrchestrator {
             self.event_tx.clone(),
             self.store.clone(),
         )
+        .with_cancellation(cancel)
     }
 
     /// 订阅实时执行事件
@@ -277,8 +302,28 @@ impl AgentOrchestrator {
         self.event_tx.subscribe()
     }
 
+    /// 向实时订阅者广播对话之外（如 runtime executor）产生的执行事件
+    pub fn publish_execution_event(&self, session_id: &str, event: AgentExecutionEvent) {
+        let _ = self.event_tx.send(AgentSessionExecutionEvent {
+            session_id: session_id.to_string(),
+            event,
+        });
+    }
+
     /// 处理用户请求 (非流式)
     pub async fn process(&self, request: AgentRequest) -> Result<AgentResponse, AgentError> {
+        self.process_with_cancellation(request, CancellationToken::new())
+            .await
+    }
+
+    /// 处理用户请求，`cancel` 触发后在当前 LLM/工具调用结束时停止
+    ///
+    /// 返回 `AgentError::Cancelled`；已完成的进度保留在会话中。
+    pub async fn process_with_cancellation(
+        &self,
+        request: AgentRequest,
+        cancel: CancellationToken,
+    ) -> Result<AgentResponse, AgentError> {
         info!("Processing agent request: {}", request.user_input);
 
         let timeout = Duration::from_secs(self.config.timeout_secs);
@@ -301,16 +346,18 @@ impl AgentOrchestrator {
                 content: request.user_input.clone(),
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             };
 
             // 执行主循环
-            self.runner()
+            self.runner(cancel)
                 .run_main_loop(
                     session,
                     user_message,
                     request.active_task_id,
                     request.working_dir.clone(),
                     request.working_memory.clone(),
+                    request.memory_context.clone(),
                 )
                 .await
         };
@@ -351,6 +398,7 @@ impl AgentOrchestrator {
             content: request.user_input.clone(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let messages = self
@@ -360,6 +408,7 @@ impl AgentOrchestrator {
                 request.active_task_id,
                 request.working_dir.clone(),
                 request.working_memory.clone(),
+                request.memory_context.clone(),
             )
             .await?;
 
@@ -380,11 +429,14 @@ impl AgentOrchestrator {
             } else {
                 Some(tool_schemas)
             },
+            tool_choice: None,
         };
 
         // 创建流处理器
         let content = Arc::new(Mutex::new(String::new()));
-        let handler: Arc<dyn StreamHandler> = Arc::new(StreamingHandler::new(content.clone()));
+        let accumulator = Arc::new(Mutex::new(ToolCallAccumulator::new()));
+        let handler: Arc<dyn StreamHandler> =
+            Arc::new(StreamingHandler::new(content.clone(), accumulator.clone()));
 
         // 发送流式请求
         self.provider
@@ -396,6 +448,17 @@ impl AgentOrchestrator {
             let c = content.lock().await;
             c.clone()
         };
+        let tool_calls: Vec<AgentToolCall> = accumulator
+            .lock()
+            .await
+            .calls()
+            .into_iter()
+            .map(|tc| AgentToolCall {
+                name: tc.function.name,
+                arguments: tc.function.arguments,
+                id: tc.id,
+            })
+            .collect();
 
         let mut session_state = session.clone();
         session_state.add_message(AgentMessage {
@@ -410,7 +473,7 @@ impl AgentOrchestrator {
             role: MessageRole::Assistant,
             content: final_content.clone(),
             timestamp: chrono::Utc::now(),
-            tool_calls: None,
+            tool_calls: (!tool_calls.is_empty()).then(|| tool_calls.clone()),
             tool_results: None,
             tool_call_id: None,
         });
@@ -419,7 +482,7 @@ impl AgentOrchestrator {
         Ok(AgentResponse {
             session_id,
             content: final_content,
-            tool_calls: Vec::new(),
+            tool_calls,
             is_complete: true,
             needs_input: false,
             verification_result: None,
@@ -508,6 +571,7 @@ impl AgentOrchestrator {
         active_task_id: Option<TaskId>,
         working_dir: Option<std::path::PathBuf>,
         working_memory: Option<crate::WorkingMemory>,
+        memory_context: Option<String>,
     ) -> Result<Vec<Message>, AgentError> {
         prompt_builder::build_messages(
             session,
@@ -515,6 +579,7 @@ impl AgentOrchestrator {
             active_task_id,
             working_dir,
             working_memory,
+            memory_context,
             &self.config.system_prompt_template,
             self.tool_executor.tool_schemas(),
         )
@@ -553,6 +618,7 @@ mod tests {
             role: None,
             active_task_id: None,
             working_memory: None,
+            memory_context: None,
         };
 
         assert_eq!(request.user_input, "Create a new task");
@@ -696,6 +762,8 @@ mod tests {
                 prompt_tokens: 1,
                 completion_tokens: 1,
                 total_tokens: 2,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }
         }
 
@@ -902,11 +970,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "tool-1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: r#"{"path":"/tmp/test.txt","content":"x"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -926,6 +996,7 @@ mod tests {
                     content: "File updated.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -945,6 +1016,7 @@ mod tests {
                     content: "Continuing same session.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -974,6 +1046,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1009,6 +1082,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1031,6 +1105,151 @@ mod tests {
         assert!(replay.len() <= 3);
     }
 
+    /// Simulates Ctrl-C arriving while a tool call is in flight
+    struct CancellingToolExecutor {
+        cancel: CancellationToken,
+        compensations: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ToolExecutor for CancellingToolExecutor {
+        async fn execute_tool(&self, _name: &str, _arguments: &str) -> Result<String, AgentError> {
+            self.cancel.cancel();
+            Ok("partial".to_string())
+        }
+
+        async fn compensate_cancelled(&self) -> Result<(), AgentError> {
+            self.compensations
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn list_tools(&self) -> Vec<String> {
+            vec!["write".to_string()]
+        }
+    }
+
+    fn tool_call_response(id: &str) -> CompletionResponse {
+        CompletionResponse {
+            id: id.to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "mock-model".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: MessageRole::Assistant,
+                    content: String::new(),
+                    name: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: format!("{}-call", id),
+                        index: None,
+                        function: ToolCallFunction {
+                            name: "write".to_string(),
+                            arguments: "{}".to_string(),
+                        },
+                    }]),
+                    cacheable: false,
+                },
+                finish_reason: None,
+                logprobs: None,
+            }],
+            usage: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_between_rounds_stops_before_next_llm_call() {
+        let cancel = CancellationToken::new();
+        let provider = Arc::new(ScriptedProvider::new(vec![
+            tool_call_response("resp-1"),
+            tool_call_response("resp-2"),
+        ]));
+        let tool_executor = Arc::new(CancellingToolExecutor {
+            cancel: cancel.clone(),
+            compensations: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let orchestrator = AgentOrchestrator::new(
+            provider.clone(),
+            tool_executor.clone(),
+            Arc::new(TaskVerifier::new(Arc::new(MockStorage))),
+            AgentConfig::default(),
+        );
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            orchestrator.process_with_cancellation(
+                AgentRequest {
+                    user_input: "long task".to_string(),
+                    session_id: Some("cancel-session".to_string()),
+                    working_dir: None,
+                    role: None,
+                    active_task_id: None,
+                    working_memory: None,
+                    memory_context: None,
+                },
+                cancel,
+            ),
+        )
+        .await
+        .expect("cancelled run should exit promptly");
+
+        assert!(matches!(result, Err(AgentError::Cancelled)));
+        assert_eq!(provider.requests.lock().await.len(), 1);
+        assert_eq!(
+            tool_executor
+                .compensations
+                .load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+
+        // Partial progress (the finished tool round) stays in the session
+        let session = orchestrator
+            .session_snapshot("cancel-session")
+            .await
+            .unwrap();
+        assert!(
+            session
+                .messages
+                .iter()
+                .any(|m| m.role == MessageRole::Tool && m.content.contains("partial"))
+        );
+        let last = session.execution_events.last().unwrap();
+        assert_eq!(last.kind, AgentExecutionEventKind::SessionStatus);
+        assert_eq!(last.message, "session_cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_start_makes_no_llm_call() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let provider = Arc::new(ScriptedProvider::new(vec![tool_call_response("resp-1")]));
+        let orchestrator = AgentOrchestrator::new(
+            provider.clone(),
+            Arc::new(MockToolExecutor::new()),
+            Arc::new(TaskVerifier::new(Arc::new(MockStorage))),
+            AgentConfig::default(),
+        );
+
+        let result = orchestrator
+            .process_with_cancellation(
+                AgentRequest {
+                    user_input: "never runs".to_string(),
+                    session_id: None,
+                    working_dir: None,
+                    role: None,
+                    active_task_id: None,
+                    working_memory: None,
+                    memory_context: None,
+                },
+                cancel,
+            )
+            .await;
+
+        assert!(matches!(result, Err(AgentError::Cancelled)));
+        assert!(provider.requests.lock().await.is_empty());
+    }
+
     fn make_temp_project_path(prefix: &str) -> std::path::PathBuf {
         let millis = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -1198,6 +1417,7 @@ mod tests {
                     content: "First answer".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1216,6 +1436,7 @@ mod tests {
                     content: "Second answer after feedback".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1255,6 +1476,7 @@ mod tests {
                 role: None,
                 active_task_id: Some(task_id),
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1285,6 +1507,7 @@ mod tests {
                     content: "done".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1293,6 +1516,8 @@ mod tests {
                 prompt_tokens: 11,
                 completion_tokens: 7,
                 total_tokens: 18,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let provider = Arc::new(ScriptedProvider::new(vec![response]));
@@ -1309,6 +1534,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1360,6 +1586,7 @@ mod tests {
                     content: "done".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1380,6 +1607,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1411,11 +1639,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "tool-perm-1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: r#"{"path":"/tmp/test.txt","content":"x"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -1434,6 +1664,7 @@ mod tests {
                     content: "Cannot write without permission.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1458,6 +1689,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1484,11 +1716,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "tool-perm-approve-1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "git".to_string(),
                             arguments: r#"{"operation":"commit"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -1507,6 +1741,7 @@ mod tests {
                     content: "commit completed".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1531,6 +1766,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1567,11 +1803,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "tool-multi-1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: r#"{"path":"/tmp/test.txt","content":"x"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -1590,6 +1828,7 @@ mod tests {
                     content: "Need permission before writing.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1608,6 +1847,7 @@ mod tests {
                     content: "Second round status update.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1636,6 +1876,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1647,6 +1888,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1678,11 +1920,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "tool-e2e-1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: r#"{"path":"/tmp/e2e-a.txt","content":"x"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -1691,6 +1935,8 @@ mod tests {
                 prompt_tokens: 8,
                 completion_tokens: 3,
                 total_tokens: 11,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let second_response = CompletionResponse {
@@ -1705,6 +1951,7 @@ mod tests {
                     content: "Round one blocked by permission.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1713,6 +1960,8 @@ mod tests {
                 prompt_tokens: 7,
                 completion_tokens: 4,
                 total_tokens: 11,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let third_response = CompletionResponse {
@@ -1728,11 +1977,13 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "tool-e2e-2".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "write".to_string(),
                             arguments: r#"{"path":"/tmp/e2e-b.txt","content":"y"}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 finish_reason: None,
                 logprobs: None,
@@ -1741,6 +1992,8 @@ mod tests {
                 prompt_tokens: 9,
                 completion_tokens: 3,
                 total_tokens: 12,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
         let fourth_response = CompletionResponse {
@@ -1755,6 +2008,7 @@ mod tests {
                     content: "Round two blocked by permission as well.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1763,6 +2017,8 @@ mod tests {
                 prompt_tokens: 6,
                 completion_tokens: 4,
                 total_tokens: 10,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
             }),
         };
 
@@ -1788,6 +2044,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1799,6 +2056,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1877,6 +2135,7 @@ mod tests {
                     content: "done".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some("stop".to_string()),
                 logprobs: None,
@@ -1899,6 +2158,7 @@ mod tests {
                 role: None,
                 active_task_id: None,
                 working_memory: None,
+                memory_context: None,
             })
             .await
             .unwrap();
@@ -1985,10 +2245,11 @@ mod tests {
             content: "hello".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let messages = orchestrator
-            .build_messages(&session, &user_msg, None, None, None)
+            .build_messages(&session, &user_msg, None, None, None, None)
             .await
             .expect("build_messages should succeed");
 
@@ -2052,10 +2313,11 @@ mod tests {
             content: "hello".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let messages = orchestrator
-            .build_messages(&session, &user_msg, None, None, None)
+            .build_messages(&session, &user_msg, None, None, None, None)
             .await
             .expect("build_messages should succeed");
 
@@ -18,12 +18,14 @@ use tracing::warn;
 /// - System prompt construction (template or enhanced prompt)
 /// - History reconstruction with legacy tool_call_id recovery
 /// - Orphaned tool_use / tool_result cleanup
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn build_messages(
     session: &AgentSession,
     user_message: &Message,
     active_task_id: Option<TaskId>,
     working_dir: Option<std::path::PathBuf>,
     working_memory: Option<crate::WorkingMemory>,
+    memory_context: Option<String>,
     system_prompt_template: &Option<String>,
     tool_schemas: Vec<serde_json::Value>,
 ) -> Result<Vec<Message>, AgentError> {
@@ -40,6 +42,7 @@ pub(crate) fn build_messages(
         invariants: None,
         lineage: None,
         context_patterns: Vec::new(),
+        memory_context,
     };
 
     let system_prompt = if let Some(template) = system_prompt_template {
@@ -48,11 +51,13 @@ pub(crate) fn build_messages(
         build_enhanced_prompt(&prompt_context)
     };
 
+    // 系统提示在各轮之间不变：作为提示缓存断点
     messages.push(Message {
         role: MessageRole::System,
         content: system_prompt,
         name: None,
         tool_calls: None,
+        cacheable: true,
     });
 
     // 添加历史消息 (最近的 N 条)
@@ -65,6 +70,7 @@ pub(crate) fn build_messages(
             tcs.iter()
                 .map(|tc| ToolCall {
                     id: tc.id.clone(),
+                    index: None,
                     function: ToolCallFunction {
                         name: tc.name.clone(),
                         arguments: tc.arguments.clone(),
@@ -106,6 +112,7 @@ pub(crate) fn build_messages(
             content: msg.content.clone(),
             name,
             tool_calls,
+            cacheable: false,
         });
     }
 
@@ -239,14 +246,16 @@ mod tests {
             content: "hello".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let messages =
-            build_messages(&session, &user_msg, None, None, None, &None, vec![]).unwrap();
+            build_messages(&session, &user_msg, None, None, None, None, &None, vec![]).unwrap();
 
         // Should have system prompt + user message
         assert!(messages.len() >= 2);
         assert_eq!(messages[0].role, MessageRole::System);
+        assert!(messages[0].cacheable);
         assert_eq!(messages.last().unwrap().role, MessageRole::User);
         assert_eq!(messages.last().unwrap().content, "hello");
     }
@@ -259,15 +268,52 @@ mod tests {
             content: "hi".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let template = Some("Custom system prompt".to_string());
-        let messages =
-            build_messages(&session, &user_msg, None, None, None, &template, vec![]).unwrap();
+        let messages = build_messages(
+            &session,
+            &user_msg,
+            None,
+            None,
+            None,
+            None,
+            &template,
+            vec![],
+        )
+        .unwrap();
 
         assert_eq!(messages[0].content, "Custom system prompt");
     }
 
+    #[test]
+    fn test_build_messages_injects_memory_context() {
+        let session = AgentSession::new("test-session".to_string());
+        let user_msg = Message {
+            role: MessageRole::User,
+            content: "hi".to_string(),
+            name: None,
+            tool_calls: None,
+            cacheable: false,
+        };
+        let memory = "## Relevant memory\n- [Verified] cache keys include the target".to_string();
+
+        let messages = build_messages(
+            &session,
+            &user_msg,
+            None,
+            None,
+            None,
+            Some(memory.clone()),
+            &None,
+            vec![],
+        )
+        .unwrap();
+
+        assert!(messages[0].content.contains(&memory));
+    }
+
     #[test]
     fn test_build_messages_recovers_legacy_tool_call_id() {
         let mut session = AgentSession::new("legacy-session".to_string());
@@ -310,10 +356,11 @@ mod tests {
             content: "next".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let messages =
-            build_messages(&session, &user_msg, None, None, None, &None, vec![]).unwrap();
+            build_messages(&session, &user_msg, None, None, None, None, &None, vec![]).unwrap();
 
         let tool_msg = messages
             .iter()
@@ -351,10 +398,11 @@ mod tests {
             content: "hello".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let messages =
-            build_messages(&session, &user_msg, None, None, None, &None, vec![]).unwrap();
+            build_messages(&session, &user_msg, None, None, None, None, &None, vec![]).unwrap();
 
         assert!(
             !messages.iter().any(|m| m.role == MessageRole::Tool),
@@ -34,6 +34,9 @@ pub struct EnhancedPromptContext {
 
     /// 当前任务模式匹配
     pub context_patterns: Vec<String>,
+
+    /// ContextBuilder 组装的相关记忆
+    pub memory_context: Option<String>,
 }
 
 /// 提示词上下文
@@ -112,6 +115,11 @@ impl PromptBuilder {
             );
         }
 
+        if let Some(memory) = context.memory_context.as_ref().filter(|m| !m.is_empty()) {
+            prompt.push_str("\n\n");
+            prompt.push_str(memory);
+        }
+
         // 2. 注入 Working Memory
         if let Some(ref wm) = context.working_memory
             && wm.has_context()
@@ -339,6 +347,7 @@ mod tests {
             invariants: Some(invariant_injector),
             lineage: None,
             context_patterns: vec!["test".to_string()],
+            memory_context: None,
         };
 
         let prompt = build_enhanced_prompt(&context);
@@ -5,9 +5,10 @@
 //! - 跟踪对话历史
 //! - 记录工具调用统计
 
-use super::{AgentExecutionEvent, AgentToolCall};
+use super::{AgentError, AgentExecutionEvent, AgentToolCall};
 use crate::TaskId;
-use crate::llm::provider::MessageRole;
+use crate::llm::provider::token_counter::estimate_tokens;
+use crate::llm::provider::{CompletionRequest, LlmProvider, Message, MessageRole};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -156,6 +157,40 @@ pub struct AgentSession {
 
     /// Worktree root (git common dir parent when available)
     pub worktree: PathBuf,
+
+    /// 已被压缩进摘要的消息数（累计）
+    #[serde(default)]
+    pub compacted_messages: usize,
+}
+
+/// 压缩摘要消息的前缀
+pub const COMPACTION_SUMMARY_PREFIX: &str = "[Compacted conversation summary]";
+
+/// 上下文压缩配置
+#[derive(Debug, Clone)]
+pub struct CompactionConfig {
+    /// 对话历史估算 token 超过该值时触发压缩
+    pub token_threshold: u64,
+
+    /// 原样保留的最近消息数
+    pub keep_recent: usize,
+
+    /// 摘要使用的模型（为空时使用 provider 默认模型）
+    pub model: String,
+
+    /// 摘要输出的 token 上限
+    pub max_summary_tokens: u32,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            token_threshold: 60_000,
+            keep_recent: 12,
+            model: String::new(),
+            max_summary_tokens: 1024,
+        }
+    }
 }
 
 /// 会话状态
@@ -228,6 +263,7 @@ impl AgentSession {
             project_root: identity.project_root,
             working_dir: identity.working_dir,
             worktree: identity.worktree,
+            compacted_messages: 0,
         }
     }
 
@@ -278,6 +314,147 @@ impl AgentSession {
     pub fn is_expired(&self, timeout_secs: u64) -> bool {
         self.duration().num_seconds() >= timeout_secs as i64
     }
+
+    /// 估算对话历史的 token 数
+    pub fn estimated_tokens(&self) -> u64 {
+        self.messages
+            .iter()
+            .map(|m| estimate_tokens(&m.content, "cl100k_base") as u64)
+            .sum()
+    }
+
+    /// 压缩对话历史
+    ///
+    /// 将最近 `keep_recent` 条之前的消息（连同已有摘要）交给 provider 总结，
+    /// 替换为开头的一条 System 摘要消息。工具结果不参与摘要，不再被引用的
+    /// 工具结果直接丢弃。没有可压缩的消息时不做任何事，返回本次压缩的消息数。
+    pub async fn compact(
+        &mut self,
+        provider: &dyn LlmProvider,
+        config: &CompactionConfig,
+    ) -> Result<usize, AgentError> {
+        let Some((start, end)) = self.compaction_range(config.keep_recent) else {
+            return Ok(0);
+        };
+
+        let previous = (start == 1).then(|| self.messages[0].content.as_str());
+        let request = CompletionRequest {
+            model: if config.model.is_empty() {
+                provider.config().default_model.clone()
+            } else {
+                config.model.clone()
+            },
+            messages: vec![
+                Message {
+                    role: MessageRole::System,
+                    content: "Summarize the conversation below for an engineering agent that \
+                              will continue the work. Keep decisions, file paths, open problems \
+                              and the user's intent. Be concise."
+                        .to_string(),
+                    name: None,
+                    tool_calls: None,
+                    cacheable: false,
+                },
+                Message {
+                    role: MessageRole::User,
+                    content: compaction_transcript(previous, &self.messages[start..end]),
+                    name: None,
+                    tool_calls: None,
+                    cacheable: false,
+                },
+            ],
+            temperature: Some(0.0),
+            max_tokens: Some(config.max_summary_tokens),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+        };
+
+        let response = provider
+            .complete(&request)
+            .await
+            .map_err(|e| AgentError::LlmError(e.to_string()))?;
+        let summary = response
+            .choices
+            .first()
+            .map(|c| c.message.content.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| AgentError::LlmError("empty compaction summary".to_string()))?;
+
+        let compacted = end - start;
+        let summary_message = AgentMessage {
+            role: MessageRole::System,
+            content: format!("{}\n{}", COMPACTION_SUMMARY_PREFIX, summary),
+            timestamp: chrono::Utc::now(),
+            tool_calls: None,
+            tool_results: None,
+            tool_call_id: None,
+        };
+        self.messages.splice(0..end, [summary_message]);
+        self.drop_unreferenced_tool_results();
+        self.compacted_messages += compacted;
+        Ok(compacted)
+    }
+
+    /// 待压缩区间 `[start, end)`；`start` 跳过已有摘要，`end` 不拆开工具调用与结果
+    fn compaction_range(&self, keep_recent: usize) -> Option<(usize, usize)> {
+        let start = usize::from(self.messages.first().is_some_and(is_compaction_summary));
+        let mut end = self.messages.len().saturating_sub(keep_recent);
+        while end > start
+            && end < self.messages.len()
+            && self.messages[end].role == MessageRole::Tool
+        {
+            end -= 1;
+        }
+        (end > start).then_some((start, end))
+    }
+
+    fn drop_unreferenced_tool_results(&mut self) {
+        let referenced: std::collections::HashSet<String> = self
+            .messages
+            .iter()
+            .filter_map(|m| m.tool_calls.as_ref())
+            .flatten()
+            .map(|call| call.id.clone())
+            .collect();
+        self.messages.retain(|m| {
+            m.role != MessageRole::Tool
+                || m.tool_call_id
+                    .as_ref()
+                    .is_none_or(|id| referenced.contains(id))
+        });
+    }
+}
+
+fn is_compaction_summary(message: &AgentMessage) -> bool {
+    message.role == MessageRole::System && message.content.starts_with(COMPACTION_SUMMARY_PREFIX)
+}
+
+fn compaction_transcript(previous: Option<&str>, messages: &[AgentMessage]) -> String {
+    let mut lines = Vec::new();
+    if let Some(previous) = previous {
+        lines.push(format!("Earlier summary:\n{}", previous));
+    }
+    for message in messages {
+        let role = match message.role {
+            MessageRole::System => "system",
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+            MessageRole::Tool => continue,
+        };
+        if !message.content.trim().is_empty() {
+            lines.push(format!("{}: {}", role, message.content.trim()));
+        }
+        if let Some(calls) = &message.tool_calls {
+            let names: Vec<&str> = calls.iter().map(|c| c.name.as_str()).collect();
+            lines.push(format!("{} called tools: {}", role, names.join(", ")));
+        }
+    }
+    lines.join("\n\n")
 }
 
 /// Session Manager
@@ -285,6 +462,7 @@ impl AgentSession {
 pub struct SessionManager {
     sessions: HashMap<String, AgentSession>,
     default_timeout_secs: u64,
+    compaction: CompactionConfig,
 }
 
 impl SessionManager {
@@ -293,9 +471,16 @@ impl SessionManager {
         Self {
             sessions: HashMap::new(),
             default_timeout_secs: 3600, // 1 hour
+            compaction: CompactionConfig::default(),
         }
     }
 
+    /// 设置上下文压缩配置
+    pub fn with_compaction(mut self, config: CompactionConfig) -> Self {
+        self.compaction = config;
+        self
+    }
+
     /// 创建新会话
     pub fn create_session(&mut self) -> AgentSession {
         let id = ulid::Ulid::new().to_string();
@@ -344,6 +529,22 @@ impl SessionManager {
     pub fn remove_session(&mut self, id: &str) -> Option<AgentSession> {
         self.sessions.remove(id)
     }
+
+    /// 对话历史超过 token 阈值时压缩会话，返回本次压缩的消息数
+    pub async fn compact_if_needed(
+        &mut self,
+        id: &str,
+        provider: &dyn LlmProvider,
+    ) -> Result<usize, AgentError> {
+        let session = self
+            .sessions
+            .get_mut(id)
+            .ok_or_else(|| AgentError::SessionNotFound(id.to_string()))?;
+        if session.estimated_tokens() < self.compaction.token_threshold {
+            return Ok(0);
+        }
+        session.compact(provider, &self.compaction).await
+    }
 }
 
 impl Default for SessionManager {
@@ -355,8 +556,141 @@ impl Default for SessionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm::provider::{
+        Choice, CompletionResponse, ModelInfo, ProviderConfig, ProviderError, ProviderType,
+        StreamHandler, Usage,
+    };
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    struct FakeSummarizer {
+        config: ProviderConfig,
+        calls: AtomicUsize,
+    }
+
+    impl FakeSummarizer {
+        fn new() -> Self {
+            Self {
+                config: ProviderConfig {
+                    name: "fake".to_string(),
+                    provider_type: ProviderType::OpenAi,
+                    api_key: "test".to_string(),
+                    base_url: None,
+                    organization: None,
+                    default_model: "fake-model".to_string(),
+                    models: vec!["fake-model".to_string()],
+                    timeout_ms: 1000,
+                    max_retries: 1,
+                },
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LlmProvider for FakeSummarizer {
+        fn provider_type(&self) -> ProviderType {
+            ProviderType::OpenAi
+        }
+
+        fn name(&self) -> &str {
+            "fake-summarizer"
+        }
+
+        async fn list_models(&self) -> Result<Vec<ModelInfo>, ProviderError> {
+            Ok(Vec::new())
+        }
+
+        async fn complete(
+            &self,
+            request: &CompletionRequest,
+        ) -> Result<CompletionResponse, ProviderError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            assert!(!request.messages[1].content.contains("tool output"));
+            Ok(CompletionResponse {
+                id: format!("summary-{}", n),
+                object: "chat.completion".to_string(),
+                created: 0,
+                model: request.model.clone(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: Message {
+                        role: MessageRole::Assistant,
+                        content: format!("summary #{}", n),
+                        name: None,
+                        tool_calls: None,
+                        cacheable: false,
+                    },
+                    finish_reason: Some("stop".to_string()),
+                    logprobs: None,
+                }],
+                usage: None,
+            })
+        }
+
+        async fn complete_streaming(
+            &self,
+            _request: &CompletionRequest,
+            _handler: &Arc<dyn StreamHandler>,
+        ) -> Result<(), ProviderError> {
+            Ok(())
+        }
+
+        fn estimate_tokens(&self, _request: &CompletionRequest) -> Usage {
+            Usage {
+                prompt_tokens: 1,
+                completion_tokens: 1,
+                total_tokens: 2,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+            }
+        }
+
+        async fn is_model_available(&self, _model: &str) -> bool {
+            true
+        }
+
+        fn config(&self) -> &ProviderConfig {
+            &self.config
+        }
+    }
+
+    fn message(role: MessageRole, content: &str) -> AgentMessage {
+        AgentMessage {
+            role,
+            content: content.to_string(),
+            timestamp: chrono::Utc::now(),
+            tool_calls: None,
+            tool_results: None,
+            tool_call_id: None,
+        }
+    }
+
+    fn tool_turn(id: &str) -> [AgentMessage; 2] {
+        let mut call = message(MessageRole::Assistant, "");
+        call.tool_calls = Some(vec![AgentToolCall {
+            name: "read".to_string(),
+            arguments: "{}".to_string(),
+            id: id.to_string(),
+        }]);
+        let mut result = message(MessageRole::Tool, "tool output");
+        result.tool_call_id = Some(id.to_string());
+        [call, result]
+    }
+
+    fn long_session() -> AgentSession {
+        let mut session = AgentSession::new("compact".to_string());
+        for i in 0..4 {
+            session.add_message(message(MessageRole::User, &format!("question {}", i)));
+            for m in tool_turn(&format!("call-{}", i)) {
+                session.add_message(m);
+            }
+            session.add_message(message(MessageRole::Assistant, &format!("answer {}", i)));
+        }
+        session
+    }
+
     #[test]
     fn test_agent_session_new() {
         let session = AgentSession::new("test-session".to_string());
@@ -511,4 +845,82 @@ mod tests {
         manager.cleanup_expired();
         assert!(manager.get_session(&id).is_none());
     }
+
+    #[tokio::test]
+    async fn test_compact_replaces_old_messages_with_summary() {
+        let provider = FakeSummarizer::new();
+        let mut session = long_session();
+        let recent: Vec<String> = session.messages[12..]
+            .iter()
+            .map(|m| m.content.clone())
+            .collect();
+        let config = CompactionConfig {
+            keep_recent: 4,
+            ..CompactionConfig::default()
+        };
+
+        let compacted = session.compact(&provider, &config).await.unwrap();
+        assert_eq!(compacted, 12);
+        assert_eq!(session.compacted_messages, 12);
+        assert_eq!(session.messages.len(), 5);
+        assert!(
+            session.messages[0]
+                .content
+                .starts_with(COMPACTION_SUMMARY_PREFIX)
+        );
+        assert!(session.messages[0].content.contains("summary #1"));
+        let kept: Vec<String> = session.messages[1..]
+            .iter()
+            .map(|m| m.content.clone())
+            .collect();
+        assert_eq!(kept, recent);
+
+        // 没有新消息时再次压缩是空操作
+        assert_eq!(session.compact(&provider, &config).await.unwrap(), 0);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(session.messages.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_compact_keeps_tool_results_with_their_calls() {
+        let provider = FakeSummarizer::new();
+        let mut session = long_session();
+        let config = CompactionConfig {
+            // 边界落在 call-3 的工具结果上，应整体保留该工具调用
+            keep_recent: 2,
+            ..CompactionConfig::default()
+        };
+
+        session.compact(&provider, &config).await.unwrap();
+        assert_eq!(session.messages.len(), 4);
+        assert_eq!(session.messages[2].role, MessageRole::Tool);
+        assert_eq!(session.messages[2].tool_call_id.as_deref(), Some("call-3"));
+        assert_eq!(session.compacted_messages, 13);
+    }
+
+    #[tokio::test]
+    async fn test_session_manager_compacts_over_threshold() {
+        let provider = FakeSummarizer::new();
+        let mut manager = SessionManager::new().with_compaction(CompactionConfig {
+            token_threshold: 1_000,
+            keep_recent: 4,
+            ..CompactionConfig::default()
+        });
+        let id = manager.create_session().id;
+        for m in long_session().messages {
+            manager.get_session_mut(&id).unwrap().add_message(m);
+        }
+
+        assert_eq!(manager.compact_if_needed(&id, &provider).await.unwrap(), 0);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+
+        manager
+            .get_session_mut(&id)
+            .unwrap()
+            .add_message(message(MessageRole::User, &"x".repeat(8_000)));
+        assert_eq!(manager.compact_if_needed(&id, &provider).await.unwrap(), 13);
+        let session = manager.get_session(&id).unwrap();
+        assert_eq!(session.messages.len(), 5);
+        assert_eq!(session.compacted_messages, 13);
+    }
 }
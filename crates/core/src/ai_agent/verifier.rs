@@ -386,6 +386,7 @@ impl TaskVerifier {
                 version: 1,
                 modified_at: Some(chrono::Utc::now()),
                 tags: vec!["gold-memory".to_string(), "invariants".to_string()],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::system(), MemoryStability::Canonical),
         };
@@ -652,7 +653,7 @@ struct MigrationAuditV2 {
 /// 格式化操作描述
 fn format_action(action: &Action) -> String {
     match action {
-        Action::ReadFile { path } => {
+        Action::ReadFile { path, .. } => {
             format!("read file: {}", path.display())
         }
         Action::WriteFile { path, .. } => {
@@ -664,6 +665,9 @@ fn format_action(action: &Action) -> String {
         Action::DeleteFile { path } => {
             format!("delete file: {}", path.display())
         }
+        Action::MoveFile { from, to } => {
+            format!("move file: {} -> {}", from.display(), to.display())
+        }
         Action::RunCommand { command, args } => {
             format!("run command: {} {}", command, args.join(" "))
         }
@@ -814,6 +818,7 @@ mod tests {
     fn test_format_action() {
         let action = Action::ReadFile {
             path: std::path::PathBuf::from("test.rs"),
+            range: None,
         };
         let formatted = format_action(&action);
         assert!(formatted.contains("read file"));
@@ -1035,6 +1040,7 @@ mod tests {
                 version: 1,
                 modified_at: Some(chrono::Utc::now()),
                 tags: vec!["gold-memory".to_string()],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::system(), MemoryStability::Canonical),
         };
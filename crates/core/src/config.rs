@@ -9,7 +9,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 // Re-export from llm/provider
@@ -48,6 +48,9 @@ pub struct NdcConfig {
     /// Permanently approved security permissions (e.g. "shell_high_risk", "git_commit")
     #[serde(default)]
     pub approved_permissions: Vec<String>,
+    /// Extra redaction regexes, merged with the built-in secret patterns
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +63,8 @@ struct YamlNdcConfig {
     pub agents: Vec<YamlAgentProfile>,
     #[serde(default)]
     pub approved_permissions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redaction_patterns: Vec<String>,
 }
 
 impl From<YamlNdcConfig> for NdcConfig {
@@ -71,6 +76,7 @@ impl From<YamlNdcConfig> for NdcConfig {
             storage: yaml.storage,
             agents: yaml.agents,
             approved_permissions: yaml.approved_permissions,
+            redaction_patterns: yaml.redaction_patterns,
         }
     }
 }
@@ -84,6 +90,7 @@ impl From<NdcConfig> for YamlNdcConfig {
             storage: config.storage,
             agents: config.agents,
             approved_permissions: config.approved_permissions,
+            redaction_patterns: config.redaction_patterns,
         }
     }
 }
@@ -97,12 +104,13 @@ impl Default for NdcConfig {
             storage: Some(YamlStorageConfig::default()),
             agents: Vec::new(),
             approved_permissions: Vec::new(),
+            redaction_patterns: Vec::new(),
         }
     }
 }
 
 /// LLM 配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct YamlLlmConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -121,6 +129,9 @@ pub struct YamlLlmConfig {
     pub timeout: u64,
     #[serde(default)]
     pub providers: HashMap<String, YamlProviderConfig>,
+    /// Per-model rates overriding the built-in pricing table
+    #[serde(default)]
+    pub pricing: HashMap<String, crate::llm::provider::ModelRate>,
 }
 
 fn default_true() -> bool {
@@ -155,6 +166,7 @@ impl Default for YamlLlmConfig {
             max_tokens: default_max_tokens(),
             timeout: default_timeout(),
             providers: HashMap::new(),
+            pricing: HashMap::new(),
         }
     }
 }
@@ -185,7 +197,7 @@ impl YamlLlmConfig {
 }
 
 /// Provider 配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct YamlProviderConfig {
     pub name: String,
     #[serde(rename = "type")]
@@ -279,6 +291,23 @@ pub struct YamlRuntimeConfig {
     /// Discovery failure strategy: "degrade" (default) or "block"
     #[serde(default = "default_discovery_failure_mode")]
     pub discovery_failure_mode: String,
+    /// Shell command per quality check (test/lint/typecheck/build/security/custom:<name>)
+    #[serde(default)]
+    pub quality_commands: HashMap<String, String>,
+    /// Answer type checks from LSP diagnostics on the changed files
+    #[serde(default)]
+    pub lsp_gate: Option<YamlLspGateConfig>,
+}
+
+/// LSP-backed type checks for the quality gate
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct YamlLspGateConfig {
+    /// Warnings fail the type check, not just errors
+    #[serde(default)]
+    pub fail_on_warnings: bool,
+    /// Language → availability check command, overriding the built-in one
+    #[serde(default)]
+    pub servers: HashMap<String, Vec<String>>,
 }
 
 fn default_max_concurrent() -> usize {
@@ -299,12 +328,14 @@ impl Default for YamlRuntimeConfig {
             working_dir: None,
             quality_gates: None,
             discovery_failure_mode: default_discovery_failure_mode(),
+            quality_commands: HashMap::new(),
+            lsp_gate: None,
         }
     }
 }
 
 /// Storage 配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct YamlStorageConfig {
     #[serde(default = "default_storage_type")]
     pub storage_type: String,
@@ -429,21 +460,42 @@ impl NdcConfigLoader {
         }
     }
 
+    /// Config file path of every layer, in merge order.
+    pub fn config_paths(&self) -> Vec<PathBuf> {
+        self.layers
+            .iter()
+            .map(|layer| layer.path().join("config.yaml"))
+            .collect()
+    }
+
     pub fn load(&mut self) -> Result<&NdcConfig, ConfigError> {
-        // Clone layers to avoid borrow conflicts
-        let layers = self.layers.clone();
-        for layer in layers {
-            let path = layer.path().join("config.yaml");
+        let paths = self.config_paths();
+        self.load_from_paths(&paths)
+    }
+
+    /// Load and merge explicit config files in order.
+    /// (Testable variant of `load` that accepts explicit paths.)
+    pub fn load_from_paths(&mut self, paths: &[PathBuf]) -> Result<&NdcConfig, ConfigError> {
+        self.load_unvalidated(paths)?;
+        self.validate_config()?;
+        crate::redaction::add_custom_patterns(&self.config.redaction_patterns)
+            .map_err(|e| ConfigError::ValidationError(e.to_string()))?;
+        Ok(&self.config)
+    }
+
+    /// Parse, merge and apply env overrides without validating.
+    /// (Used by `ndc config validate` to report every problem at once.)
+    pub fn load_unvalidated(&mut self, paths: &[PathBuf]) -> Result<&NdcConfig, ConfigError> {
+        for path in paths {
             if path.exists() {
-                let content = std::fs::read_to_string(&path)
-                    .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| ConfigError::ParseError(format!("{}: {}", path.display(), e)))?;
                 let config: NdcConfig = serde_yaml::from_str(&content)
-                    .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+                    .map_err(|e| ConfigError::ParseError(format!("{}: {}", path.display(), e)))?;
                 self.merge(config);
             }
         }
         self.apply_env_overrides();
-        self.validate_config()?;
         Ok(&self.config)
     }
 
@@ -479,6 +531,11 @@ impl NdcConfigLoader {
                 self.config.approved_permissions.push(perm);
             }
         }
+        for pattern in other.redaction_patterns {
+            if !self.config.redaction_patterns.contains(&pattern) {
+                self.config.redaction_patterns.push(pattern);
+            }
+        }
     }
 
     fn apply_env_overrides(&mut self) {
@@ -533,6 +590,15 @@ impl NdcConfigLoader {
                 .get_or_insert_with(YamlRuntimeConfig::default);
             runtime.discovery_failure_mode = v;
         }
+
+        // Redaction 配置 (追加到配置文件中的模式之后)
+        if let Ok(v) = env::var(crate::redaction::REDACTION_PATTERNS_ENV) {
+            for pattern in crate::redaction::parse_pattern_list(&v) {
+                if !self.config.redaction_patterns.contains(&pattern) {
+                    self.config.redaction_patterns.push(pattern);
+                }
+            }
+        }
     }
 
     /// Save provider and model preference to the user-level config file.
@@ -566,6 +632,7 @@ impl NdcConfigLoader {
                 storage: None,
                 agents: Vec::new(),
                 approved_permissions: Vec::new(),
+                redaction_patterns: Vec::new(),
             }
         };
 
@@ -610,6 +677,7 @@ impl NdcConfigLoader {
                 storage: None,
                 agents: Vec::new(),
                 approved_permissions: Vec::new(),
+                redaction_patterns: Vec::new(),
             }
         };
 
@@ -691,6 +759,301 @@ fn parse_env_ref(value: &str) -> Option<String> {
     }
 }
 
+// ============================================================================
+// 配置校验报告 (ndc config validate)
+// ============================================================================
+
+/// 校验问题等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSeverity {
+    Error,
+    Warning,
+}
+
+/// 单条校验问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub severity: ConfigSeverity,
+    /// 出问题的配置项，如 `llm.api_key`
+    pub field: String,
+    pub message: String,
+}
+
+/// 配置校验报告
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    pub fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.push(ConfigSeverity::Error, field, message);
+    }
+
+    pub fn warning(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.push(ConfigSeverity::Warning, field, message);
+    }
+
+    fn push(
+        &mut self,
+        severity: ConfigSeverity,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(ConfigIssue {
+            severity,
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == ConfigSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == ConfigSeverity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// 检查存储目录可写：已存在时写入探测文件，不存在时检查最近的已存在祖先目录
+    pub fn check_storage_dir(&mut self, field: &str, path: &Path) {
+        if let Err(message) = probe_writable_dir(path) {
+            self.error(field, format!("{}: {}", path.display(), message));
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for issue in self.errors().chain(self.warnings()) {
+            let label = match issue.severity {
+                ConfigSeverity::Error => "error",
+                ConfigSeverity::Warning => "warning",
+            };
+            writeln!(f, "{}: {}: {}", label, issue.field, issue.message)?;
+        }
+        write!(
+            f,
+            "{} error(s), {} warning(s)",
+            self.errors().count(),
+            self.warnings().count()
+        )
+    }
+}
+
+/// 已知 provider 名称（含 MiniMax 别名）
+const KNOWN_PROVIDERS: &[&str] = &[
+    "openai",
+    "anthropic",
+    "azure",
+    "ollama",
+    "local",
+    "minimax",
+    "minimax-coding-plan",
+    "minimax-cn",
+    "minimax-cn-coding-plan",
+    "openrouter",
+];
+
+/// 不需要 API key 的本地 provider
+const KEYLESS_PROVIDERS: &[&str] = &["ollama", "local"];
+
+fn probe_writable_dir(path: &Path) -> Result<(), String> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("."));
+    if !existing.is_dir() {
+        return Err(format!("{} is not a directory", existing.display()));
+    }
+    let probe = existing.join(format!(".ndc-write-probe-{}", std::process::id()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("{} is not writable: {}", existing.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+impl NdcConfig {
+    /// 静态校验配置（不访问网络），API key 从进程环境变量补全
+    pub fn check(&self) -> ConfigReport {
+        self.check_with_env(|name| env::var(name).ok())
+    }
+
+    /// `check` 的可测试版本，`env` 用于查找环境变量
+    pub fn check_with_env(&self, env: impl Fn(&str) -> Option<String>) -> ConfigReport {
+        let mut report = ConfigReport::default();
+
+        if let Some(llm) = self.llm.as_ref().filter(|llm| llm.enabled) {
+            if let Err(ConfigError::ValidationError(e)) = llm.validate() {
+                report.error("llm", e);
+            }
+            check_provider_name(&mut report, "llm.provider", &llm.provider);
+            check_model_name(&mut report, "llm.model", &llm.provider, &llm.model);
+
+            let override_cfg = llm.providers.get(&llm.provider);
+            let configured = override_cfg
+                .and_then(|p| p.api_key.as_deref())
+                .or(llm.api_key.as_deref());
+            let env_key = provider_env_key(&llm.provider);
+            let from_env = env(&env_key).or_else(|| env("NDC_LLM_API_KEY"));
+            match configured {
+                Some(value) => check_env_ref(&mut report, "llm.api_key", value, &env),
+                None if from_env.is_some() => {}
+                None if KEYLESS_PROVIDERS.contains(&llm.provider.as_str()) => {}
+                None => report.error(
+                    "llm.api_key",
+                    format!(
+                        "no API key for provider '{}' (set llm.api_key or {})",
+                        llm.provider, env_key
+                    ),
+                ),
+            }
+
+            for (name, provider) in &llm.providers {
+                let field = format!("llm.providers.{}", name);
+                check_provider_name(
+                    &mut report,
+                    &format!("{}.type", field),
+                    &provider.provider_type,
+                );
+                if let Some(model) = &provider.model {
+                    check_model_name(&mut report, &format!("{}.model", field), name, model);
+                }
+                if let Some(key) = &provider.api_key {
+                    check_env_ref(&mut report, &format!("{}.api_key", field), key, &env);
+                }
+            }
+        }
+
+        if let Some(repl) = &self.repl
+            && let Err(ConfigError::ValidationError(e)) = repl.validate()
+        {
+            report.error("repl", e);
+        }
+
+        if let Some(runtime) = &self.runtime {
+            if runtime.max_concurrent_tasks == 0 {
+                report.error("runtime.max_concurrent_tasks", "must be at least 1");
+            }
+            if !matches!(runtime.discovery_failure_mode.as_str(), "degrade" | "block") {
+                report.error(
+                    "runtime.discovery_failure_mode",
+                    format!(
+                        "must be 'degrade' or 'block', got '{}'",
+                        runtime.discovery_failure_mode
+                    ),
+                );
+            }
+            if let Some(dir) = &runtime.working_dir
+                && !dir.is_dir()
+            {
+                report.warning(
+                    "runtime.working_dir",
+                    format!("{} does not exist", dir.display()),
+                );
+            }
+        }
+
+        if let Some(storage) = &self.storage {
+            match storage.storage_type.as_str() {
+                "memory" => {}
+                "sqlite" if storage.in_memory => {}
+                "sqlite" => match &storage.db_path {
+                    Some(db_path) => {
+                        let dir = db_path.parent().filter(|p| !p.as_os_str().is_empty());
+                        report.check_storage_dir("storage.db_path", dir.unwrap_or(Path::new(".")));
+                    }
+                    None => report.error("storage.db_path", "required when storage_type is sqlite"),
+                },
+                other => report.error(
+                    "storage.storage_type",
+                    format!("must be 'memory' or 'sqlite', got '{}'", other),
+                ),
+            }
+        }
+
+        for (i, agent) in self.agents.iter().enumerate() {
+            if let Some(provider) = &agent.provider
+                && !KNOWN_PROVIDERS.contains(&provider.as_str())
+            {
+                report.warning(
+                    format!("agents[{}].provider", i),
+                    format!("unknown provider '{}' for agent '{}'", provider, agent.name),
+                );
+            }
+        }
+
+        if let Err(e) = crate::redaction::compile_patterns(&self.redaction_patterns) {
+            report.error("redaction_patterns", e.to_string());
+        }
+
+        report
+    }
+}
+
+/// `NDC_<PROVIDER>_API_KEY`，MiniMax 别名共用 `NDC_MINIMAX_API_KEY`
+fn provider_env_key(provider: &str) -> String {
+    let key = if provider.starts_with("minimax") {
+        "minimax"
+    } else {
+        provider
+    };
+    format!("NDC_{}_API_KEY", key.to_uppercase().replace('-', "_"))
+}
+
+fn check_provider_name(report: &mut ConfigReport, field: &str, provider: &str) {
+    if !KNOWN_PROVIDERS.contains(&provider.to_lowercase().as_str()) {
+        report.error(
+            field,
+            format!(
+                "unknown provider '{}' (expected one of: {})",
+                provider,
+                KNOWN_PROVIDERS.join(", ")
+            ),
+        );
+    }
+}
+
+fn check_model_name(report: &mut ConfigReport, field: &str, provider: &str, model: &str) {
+    if model.trim().is_empty() {
+        report.error(field, "model name is empty");
+    } else if model.chars().any(char::is_whitespace) {
+        report.error(field, format!("model name '{}' contains whitespace", model));
+    } else if provider == "openrouter" && !model.contains('/') {
+        report.warning(
+            field,
+            format!(
+                "OpenRouter models are named '<vendor>/<model>', got '{}'",
+                model
+            ),
+        );
+    }
+}
+
+fn check_env_ref(
+    report: &mut ConfigReport,
+    field: &str,
+    value: &str,
+    env: &impl Fn(&str) -> Option<String>,
+) {
+    if let Some(var) = value.strip_prefix("env://") {
+        if env(var).is_none() {
+            report.error(field, format!("environment variable {} is not set", var));
+        }
+    } else if value.trim().is_empty() {
+        report.error(field, "API key is empty");
+    }
+}
+
 // ============================================================================
 // Agent Configuration System
 // ============================================================================
@@ -1022,6 +1385,55 @@ repl:
         assert_eq!(llm.model, "claude-sonnet-4-5-20250929");
     }
 
+    #[test]
+    fn test_load_from_paths_merges_in_order_and_validates() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.yaml");
+        let project = dir.path().join("project.yaml");
+        std::fs::write(&base, "repl:\n  max_history: 10\n").unwrap();
+        std::fs::write(&project, "repl:\n  max_history: 20\n").unwrap();
+
+        let mut loader = NdcConfigLoader::new();
+        let config = loader
+            .load_from_paths(&[
+                base.clone(),
+                project.clone(),
+                dir.path().join("missing.yaml"),
+            ])
+            .unwrap();
+        assert_eq!(config.repl.as_ref().unwrap().max_history, 20);
+
+        std::fs::write(&project, "repl:\n  max_history: 0\n").unwrap();
+        let mut loader = NdcConfigLoader::new();
+        assert!(matches!(
+            loader.load_from_paths(&[base, project]),
+            Err(ConfigError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_registers_redaction_patterns_and_rejects_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "redaction_patterns:\n  - 'ACME-[0-9]{4}'\n").unwrap();
+
+        let mut loader = NdcConfigLoader::new();
+        let config = loader.load_from_paths(std::slice::from_ref(&path)).unwrap();
+        assert_eq!(config.redaction_patterns, vec!["ACME-[0-9]{4}"]);
+        assert_eq!(
+            crate::redaction::sanitize_text(
+                "ticket ACME-1234 password=hunter2",
+                crate::redaction::RedactionMode::Basic
+            ),
+            "ticket [REDACTED] password=[REDACTED]"
+        );
+
+        std::fs::write(&path, "redaction_patterns:\n  - 'ACME-[0-9'\n").unwrap();
+        let mut loader = NdcConfigLoader::new();
+        let err = loader.load_from_paths(&[path]).unwrap_err();
+        assert!(matches!(&err, ConfigError::ValidationError(m) if m.contains("ACME-[0-9")));
+    }
+
     #[test]
     fn test_save_approved_permission_creates_new_entry() {
         let dir = tempfile::tempdir().unwrap();
@@ -1066,4 +1478,73 @@ repl:
         assert_eq!(config.approved_permissions, vec!["git_commit"]);
         assert_eq!(config.llm.unwrap().provider, "openai");
     }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_check_valid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "llm:\n  provider: anthropic\n  model: claude-sonnet-4\n  api_key: env://TEST_KEY\n\
+             storage:\n  storage_type: sqlite\n  in_memory: false\n  db_path: {}\n",
+            dir.path().join("ndc.db").display()
+        );
+        let config: NdcConfig = serde_yaml::from_str(&yaml).unwrap();
+
+        let report = config.check_with_env(|name| (name == "TEST_KEY").then(|| "k".to_string()));
+        assert!(report.issues.is_empty(), "{}", report);
+        assert!(
+            NdcConfig::default()
+                .check_with_env(no_env)
+                .issues
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_check_reports_missing_api_key() {
+        let config: NdcConfig =
+            serde_yaml::from_str("llm:\n  provider: openai\n  model: gpt 4o\n").unwrap();
+
+        let report = config.check_with_env(no_env);
+        assert!(report.has_errors());
+        let fields: Vec<&str> = report.errors().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, vec!["llm.model", "llm.api_key"]);
+        assert!(report.to_string().contains("NDC_OPENAI_API_KEY"));
+
+        // 环境变量提供的 key 同样有效；本地 provider 不需要 key
+        let report =
+            config.check_with_env(|name| (name == "NDC_OPENAI_API_KEY").then(|| "k".to_string()));
+        assert_eq!(report.errors().count(), 1);
+        let ollama: NdcConfig =
+            serde_yaml::from_str("llm:\n  provider: ollama\n  model: llama3\n").unwrap();
+        assert!(!ollama.check_with_env(no_env).has_errors());
+    }
+
+    #[test]
+    fn test_check_reports_unusable_storage_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("not-a-dir");
+        std::fs::write(&file, "x").unwrap();
+
+        let mut report = ConfigReport::default();
+        report.check_storage_dir("storage_path", &file.join("storage"));
+        report.check_storage_dir("storage_path", &dir.path().join("new/storage"));
+        let errors: Vec<&ConfigIssue> = report.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("is not a directory"));
+
+        let config = NdcConfig {
+            storage: Some(YamlStorageConfig {
+                storage_type: "sqlite".to_string(),
+                db_path: Some(file.join("ndc.db")),
+                in_memory: false,
+            }),
+            ..NdcConfig::default()
+        };
+        let report = config.check_with_env(no_env);
+        assert_eq!(report.errors().next().unwrap().field, "storage.db_path");
+    }
 }
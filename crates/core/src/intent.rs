@@ -56,8 +56,12 @@ pub struct Intent {
 /// Action - 提议的动作
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Action {
-    /// 读文件
-    ReadFile { path: PathBuf },
+    /// 读文件（可选只读一段字节）
+    ReadFile {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<ByteRange>,
+    },
 
     /// 写文件
     WriteFile { path: PathBuf, content: String },
@@ -68,6 +72,9 @@ pub enum Action {
     /// 删除文件
     DeleteFile { path: PathBuf },
 
+    /// 移动/重命名文件
+    MoveFile { from: PathBuf, to: PathBuf },
+
     /// 执行命令
     RunCommand { command: String, args: Vec<String> },
 
@@ -111,6 +118,13 @@ pub enum Action {
     },
 }
 
+/// 字节范围 `[start, end)`；`end` 为空表示读到文件末尾
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GitOp {
     Status,
@@ -206,6 +220,7 @@ pub enum InteractionType {
 /// - ReadFile(src/) → Normal 权限
 /// - WriteFile(src/) → Normal 权限
 /// - WriteFile(Cargo.toml) → Elevated 权限
+/// - MoveFile(src/) → Elevated 权限
 /// - DeleteFile(any) → High 权限
 /// - RunCommand(git reset --hard) → Critical 权限
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -304,6 +319,53 @@ pub enum Verdict {
     },
 }
 
+/// 人类对 `Verdict::RequireHuman` 的答复
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HumanDecision {
+    /// 批准原动作
+    Approve,
+
+    /// 改为执行 `HumanContext.alternatives` 中的第 n 个（从 0 开始）
+    Alternative(usize),
+
+    /// 全部拒绝
+    Deny,
+}
+
+impl Verdict {
+    /// 按人类答复结算 `RequireHuman`：批准执行原动作，选择替代方案则执行该方案，
+    /// 拒绝（或选择不存在的方案）则为 `Deny`。其他裁决原样返回。
+    pub fn resolve_human(self, decision: HumanDecision) -> Verdict {
+        let Verdict::RequireHuman {
+            action, context, ..
+        } = self
+        else {
+            return self;
+        };
+        let allow = |action| Verdict::Allow {
+            action,
+            privilege: context.required_privilege,
+            conditions: vec![],
+        };
+        match decision {
+            HumanDecision::Approve => allow(action),
+            HumanDecision::Alternative(index) => match context.alternatives.get(index) {
+                Some(alternative) => allow(alternative.clone()),
+                None => Verdict::Deny {
+                    action,
+                    reason: format!("no alternative #{}", index + 1),
+                    error_code: ErrorCode::InvalidAction,
+                },
+            },
+            HumanDecision::Deny => Verdict::Deny {
+                action,
+                reason: "Declined by human".to_string(),
+                error_code: ErrorCode::Unauthorized,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Condition {
     pub condition_type: ConditionType,
@@ -327,6 +389,9 @@ pub enum ConditionType {
     /// 需要特定权限
     RequirePrivilege(PrivilegeLevel),
 
+    /// 写入/删除前必须备份目标文件，保证可回滚
+    RequireBackup,
+
     /// 自定义条件
     Custom(String),
 }
@@ -353,6 +418,9 @@ pub enum ErrorCode {
         required: PrivilegeLevel,
         granted: PrivilegeLevel,
     },
+
+    /// 超出频率限制
+    RateLimited,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -383,6 +451,11 @@ pub enum InformationSource {
     Human,
     Memory,
     ExternalAPI,
+    /// 批量评估中未通过的前置 Intent
+    Prerequisite {
+        index: usize,
+        intent_id: IntentId,
+    },
 }
 
 // 类型别名
@@ -7,9 +7,11 @@
 //! - Memory: 记忆与稳定性
 //! - TODO: 任务追踪
 //! - LLM: 集成与分解
+//! - Clock: 可注入的时间源
 
 mod agent;
 mod ai_agent;
+mod clock;
 mod config;
 mod intent;
 pub mod llm;
@@ -20,6 +22,7 @@ mod todo;
 
 pub use agent::*;
 pub use ai_agent::*;
+pub use clock::{Clock, MockClock, SharedClock, SystemClock, system_clock};
 pub use intent::*;
 pub use llm::*;
 pub use memory::*;
@@ -31,7 +34,10 @@ pub use config::{
     AgentProfile,
     AgentRoleSelector,
     ConfigError,
+    ConfigIssue,
     ConfigLayer,
+    ConfigReport,
+    ConfigSeverity,
     NdcConfig,
     NdcConfigLoader,
     PermissionRule,
@@ -39,6 +45,7 @@ pub use config::{
     ToolPermissions,
     YamlAgentProfile,
     YamlLlmConfig,
+    YamlLspGateConfig,
     YamlProviderConfig,
     YamlReplConfig,
     YamlRuntimeConfig,
@@ -142,6 +149,7 @@ mod tests {
             step_id: 1,
             action: Action::ReadFile {
                 path: PathBuf::from("test.rs"),
+                range: None,
             },
             status: StepStatus::Completed,
             result: Some(ActionResult {
@@ -201,6 +209,7 @@ mod tests {
             agent_role: AgentRole::Implementer,
             proposed_action: Action::ReadFile {
                 path: PathBuf::from("src/main.rs"),
+                range: None,
             },
             effects: vec![],
             reasoning: "Reading main file".to_string(),
@@ -217,6 +226,7 @@ mod tests {
         let actions = vec![
             Action::ReadFile {
                 path: PathBuf::from("test.rs"),
+                range: None,
             },
             Action::WriteFile {
                 path: PathBuf::from("test.rs"),
@@ -257,6 +267,7 @@ mod tests {
         let verdict = Verdict::Allow {
             action: Action::ReadFile {
                 path: PathBuf::from("test.rs"),
+                range: None,
             },
             privilege: PrivilegeLevel::Normal,
             conditions: vec![],
@@ -472,6 +483,7 @@ mod tests {
             source_task: None,
             min_stability: None,
             max_stability: None,
+            ..Default::default()
         };
 
         assert_eq!(query.query, Some("test query".to_string()));
@@ -546,6 +558,7 @@ mod tests {
                 version: 1,
                 modified_at: None,
                 tags: vec![],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::new(), MemoryStability::Ephemeral),
         };
@@ -571,6 +584,7 @@ mod tests {
                 version: 1,
                 modified_at: None,
                 tags: vec![],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::new(), MemoryStability::Derived),
         };
@@ -583,6 +597,96 @@ mod tests {
         assert_eq!(scored.score, 0.95);
     }
 
+    fn memory_with_embedding(embedding: Vec<f32>) -> MemoryEntry {
+        MemoryEntry {
+            id: MemoryId::new(),
+            content: MemoryContent::General {
+                text: "test".to_string(),
+                metadata: "".to_string(),
+            },
+            embedding,
+            relations: vec![],
+            metadata: MemoryMetadata {
+                stability: MemoryStability::Derived,
+                created_at: chrono::Utc::now(),
+                created_by: AgentId::new(),
+                source_task: TaskId::new(),
+                version: 1,
+                modified_at: None,
+                tags: vec![],
+                expires_at: None,
+            },
+            access_control: AccessControl::new(AgentId::new(), MemoryStability::Derived),
+        }
+    }
+
+    #[test]
+    fn test_memory_query_matches() {
+        let mut entry = memory_with_embedding(vec![]);
+        entry.content = MemoryContent::Code(CodeKnowledge {
+            file_path: "src/lib.rs".to_string(),
+            language: "rust".to_string(),
+            summary: "Retry loop for HTTP calls".to_string(),
+            functions: vec![],
+        });
+        entry.metadata.stability = MemoryStability::Verified;
+        entry.metadata.tags = vec!["network".to_string(), "retry".to_string()];
+
+        assert!(MemoryQuery::default().matches(&entry));
+        let query = |f: fn(&mut MemoryQuery)| {
+            let mut q = MemoryQuery::default();
+            f(&mut q);
+            q.matches(&entry)
+        };
+        assert!(query(|q| q.query = Some("http".to_string())));
+        assert!(!query(|q| q.query = Some("database".to_string())));
+        assert!(query(|q| q.min_stability = Some(MemoryStability::Derived)));
+        assert!(!query(
+            |q| q.min_stability = Some(MemoryStability::Canonical)
+        ));
+        assert!(!query(|q| q.stability = Some(MemoryStability::Derived)));
+        assert!(query(|q| q.memory_type = Some("CodeKnowledge".to_string())));
+        assert!(query(|q| q.memory_type = Some("code".to_string())));
+        assert!(!query(|q| q.memory_type = Some("decision".to_string())));
+        assert!(query(|q| q.tags = vec!["Network".to_string()]));
+        assert!(!query(
+            |q| q.tags = vec!["network".to_string(), "db".to_string()]
+        ));
+
+        assert_eq!(
+            "Verified".parse::<MemoryStability>(),
+            Ok(MemoryStability::Verified)
+        );
+        assert!("solid".parse::<MemoryStability>().is_err());
+    }
+
+    #[test]
+    fn test_cosine_similarity_bounds() {
+        assert!((cosine_similarity(&[0.3, 0.4, 0.5], &[0.3, 0.4, 0.5]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[-1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_search_cosine_ranks_exact_match_first() {
+        let exact = memory_with_embedding(vec![0.2, 0.7, 0.1]);
+        let orthogonal = memory_with_embedding(vec![0.7, -0.2, 0.0]);
+        let unembedded = memory_with_embedding(vec![]);
+        let memories = vec![orthogonal.clone(), unembedded, exact.clone()];
+
+        let results = search_cosine(&memories, &[0.2, 0.7, 0.1], 10);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].memory.id, exact.id);
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(results[1].memory.id, orthogonal.id);
+        assert!(results[1].score.abs() < 1e-6);
+
+        assert_eq!(search_cosine(&memories, &[0.2, 0.7, 0.1], 1).len(), 1);
+    }
+
     // ===== Serialization Tests =====
 
     #[test]
@@ -637,6 +741,7 @@ mod tests {
                 version: 1,
                 modified_at: None,
                 tags: vec!["core".to_string()],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::new(), MemoryStability::Verified),
         };
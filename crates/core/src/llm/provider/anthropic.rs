@@ -3,6 +3,8 @@
 //! Supports:
 //! - Claude API (Messages API)
 //! - Claude 2, 3, 3.5, 4 series
+//! - Prompt caching: messages marked `cacheable` become `cache_control` breakpoints,
+//!   plus one on the tool definitions when any message is marked
 
 use super::*;
 use futures_util::StreamExt;
@@ -11,12 +13,16 @@ use reqwest::{Client, StatusCode};
 /// Anthropic API version
 const ANTHROPIC_API_VERSION: &str = "2023-06-01";
 
+/// Anthropic rejects requests with more `cache_control` breakpoints than this
+const MAX_CACHE_BREAKPOINTS: usize = 4;
+
 /// Anthropic Provider
 #[derive(Clone)]
 pub struct AnthropicProvider {
     config: ProviderConfig,
     client: Client,
     token_counter: Arc<dyn TokenCounter>,
+    retry_policy: RetryPolicy,
 }
 
 impl std::fmt::Debug for AnthropicProvider {
@@ -35,14 +41,22 @@ impl AnthropicProvider {
             .timeout(std::time::Duration::from_millis(config.timeout_ms))
             .build()
             .expect("Failed to create HTTP client");
+        let retry_policy = RetryPolicy::from_config(&config);
 
         Self {
             config,
             client,
             token_counter,
+            retry_policy,
         }
     }
 
+    /// Override the retry policy derived from `ProviderConfig.max_retries`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Get base URL for API calls
     fn get_base_url(&self) -> String {
         if let Some(url) = &self.config.base_url {
@@ -164,6 +178,7 @@ impl AnthropicProvider {
 
                         Some(ToolCall {
                             id,
+                            index: None,
                             function: ToolCallFunction { name, arguments },
                         })
                     })
@@ -172,6 +187,120 @@ impl AnthropicProvider {
             .unwrap_or_default()
     }
 
+    /// Tool definitions; `cache` puts a breakpoint on the last one
+    fn apply_tools(body: &mut serde_json::Value, request: &CompletionRequest, cache: bool) {
+        let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty()) else {
+            return;
+        };
+        let mut mapped_tools: Vec<serde_json::Value> = tools
+            .iter()
+            .filter_map(Self::map_openai_tool_to_anthropic)
+            .collect();
+        let Some(last) = mapped_tools.last_mut() else {
+            return;
+        };
+        if cache {
+            last["cache_control"] = serde_json::json!({ "type": "ephemeral" });
+        }
+        body["tools"] = serde_json::json!(mapped_tools);
+        if let Some(choice) = &request.tool_choice {
+            body["tool_choice"] = choice.to_anthropic();
+        }
+    }
+
+    /// Request body for the Messages API
+    fn request_body(&self, request: &CompletionRequest) -> serde_json::Value {
+        let mut messages = self.serialize_messages_for_anthropic(request);
+        let system = request
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::System);
+
+        // Breakpoints in prompt order (tools, system, messages); extras beyond
+        // the limit are dropped
+        let mut breakpoints = MAX_CACHE_BREAKPOINTS;
+        let mut take_breakpoint = |cacheable: bool| {
+            let take = cacheable && breakpoints > 0;
+            breakpoints -= usize::from(take);
+            take
+        };
+        let cache_tools = take_breakpoint(
+            request.messages.iter().any(|m| m.cacheable)
+                && request.tools.as_ref().is_some_and(|t| !t.is_empty()),
+        );
+        let system = system.map(|m| match take_breakpoint(m.cacheable) {
+            true => Self::with_cache_control(serde_json::json!(m.content)),
+            false => serde_json::json!(m.content),
+        });
+        let conversation = request
+            .messages
+            .iter()
+            .filter(|m| m.role != MessageRole::System);
+        for (message, serialized) in conversation.zip(messages.iter_mut()) {
+            if take_breakpoint(message.cacheable) {
+                serialized["content"] = Self::with_cache_control(serialized["content"].take());
+            }
+        }
+        let marked = request.messages.iter().filter(|m| m.cacheable).count();
+        if marked + usize::from(cache_tools) > MAX_CACHE_BREAKPOINTS {
+            tracing::warn!(
+                marked,
+                max = MAX_CACHE_BREAKPOINTS,
+                "Too many cacheable messages; extra cache breakpoints ignored"
+            );
+        }
+
+        let mut body = serde_json::json!({
+            "model": self.map_model_name(&request.model),
+            "messages": messages,
+            "max_tokens": request.max_tokens.unwrap_or(1024),
+            "temperature": request.temperature.unwrap_or(1.0),
+        });
+
+        if let Some(sys) = system {
+            body["system"] = sys;
+        }
+
+        if let Some(stop) = &request.stop {
+            body["stop_sequences"] = serde_json::json!(stop);
+        }
+
+        Self::apply_tools(&mut body, request, cache_tools);
+        body
+    }
+
+    /// Content as blocks, with an ephemeral `cache_control` on the last block
+    fn with_cache_control(content: serde_json::Value) -> serde_json::Value {
+        let mut blocks = match content {
+            serde_json::Value::Array(blocks) => blocks,
+            serde_json::Value::String(text) if !text.is_empty() => {
+                vec![serde_json::json!({ "type": "text", "text": text })]
+            }
+            // Nothing to cache (e.g. an empty assistant turn)
+            other => return other,
+        };
+        if let Some(last) = blocks.last_mut() {
+            last["cache_control"] = serde_json::json!({ "type": "ephemeral" });
+        }
+        serde_json::Value::Array(blocks)
+    }
+
+    /// Usage from a Messages API `usage` object; `input_tokens` excludes cached tokens
+    fn parse_usage(usage: &serde_json::Value) -> Usage {
+        let tokens = |key: &str| usage[key].as_u64().unwrap_or(0) as u32;
+        let cache_creation_tokens = tokens("cache_creation_input_tokens");
+        let cache_read_tokens = tokens("cache_read_input_tokens");
+        let prompt_tokens = tokens("input_tokens") + cache_creation_tokens + cache_read_tokens;
+        let completion_tokens = tokens("output_tokens");
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            cache_creation_tokens,
+            cache_read_tokens,
+        }
+    }
+
     fn map_openai_tool_to_anthropic(tool: &serde_json::Value) -> Option<serde_json::Value> {
         let function = tool.get("function")?;
         let name = function.get("name")?.as_str()?;
@@ -310,49 +439,16 @@ impl LlmProvider for AnthropicProvider {
             });
         }
 
-        let messages = self.serialize_messages_for_anthropic(request);
-
-        // Extract system message
-        let system = request
-            .messages
-            .iter()
-            .find(|m| m.role == MessageRole::System)
-            .map(|m| m.content.clone());
-
-        // Build body
-        let mut body = serde_json::json!({
-            "model": self.map_model_name(&request.model),
-            "messages": messages,
-            "max_tokens": request.max_tokens.unwrap_or(1024),
-            "temperature": request.temperature.unwrap_or(1.0),
-        });
-
-        if let Some(sys) = system {
-            body["system"] = serde_json::json!(sys);
-        }
-
-        if let Some(stop) = &request.stop {
-            body["stop_sequences"] = serde_json::json!(stop);
-        }
+        let body = self.request_body(request);
 
-        if let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty()) {
-            let mapped_tools: Vec<serde_json::Value> = tools
-                .iter()
-                .filter_map(Self::map_openai_tool_to_anthropic)
-                .collect();
-            if !mapped_tools.is_empty() {
-                body["tools"] = serde_json::json!(mapped_tools);
-            }
-        }
-
-        let response = self
-            .client
-            .post(&url)
-            .headers(self.get_headers()?)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?;
+        let response = send_with_retry(
+            &self.retry_policy,
+            self.client
+                .post(&url)
+                .headers(self.get_headers()?)
+                .json(&body),
+        )
+        .await?;
 
         let status = response.status();
         if status == StatusCode::UNAUTHORIZED {
@@ -360,7 +456,9 @@ impl LlmProvider for AnthropicProvider {
                 message: "Invalid API key".to_string(),
             });
         } else if status == StatusCode::TOO_MANY_REQUESTS {
-            return Err(ProviderError::RateLimited { retry_after: 60 });
+            return Err(ProviderError::RateLimited {
+                retry_after: retry_after_secs(response.headers()).unwrap_or(60),
+            });
         } else if status == StatusCode::BAD_REQUEST {
             let error: serde_json::Value = response
                 .json()
@@ -419,16 +517,12 @@ impl LlmProvider for AnthropicProvider {
                     } else {
                         Some(tool_calls)
                     },
+                    cacheable: false,
                 },
                 finish_reason: data["stop_reason"].as_str().map(|s| s.to_string()),
                 logprobs: None,
             }],
-            usage: Some(Usage {
-                prompt_tokens: data["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32,
-                completion_tokens: data["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32,
-                total_tokens: data["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32
-                    + data["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32,
-            }),
+            usage: Some(Self::parse_usage(&data["usage"])),
         };
 
         Ok(response)
@@ -441,29 +535,24 @@ impl LlmProvider for AnthropicProvider {
     ) -> Result<(), ProviderError> {
         let url = format!("{}/messages", self.get_base_url());
 
-        let body = serde_json::json!({
-            "model": self.map_model_name(&request.model),
-            "messages": request.messages.iter().map(|m| serde_json::json!({
-                "role": match m.role {
-                    MessageRole::User => "user",
-                    MessageRole::Assistant => "assistant",
-                    _ => "user",
-                },
-                "content": m.content,
-            })).collect::<Vec<_>>(),
-            "max_tokens": request.max_tokens.unwrap_or(1024),
-            "stream": true,
-        });
-
-        let mut stream = self
-            .client
-            .post(&url)
-            .headers(self.get_headers()?)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?
-            .bytes_stream();
+        let mut body = self.request_body(request);
+        body["stream"] = serde_json::json!(true);
+
+        let response = send_with_retry(
+            &self.retry_policy,
+            self.client
+                .post(&url)
+                .headers(self.get_headers()?)
+                .json(&body),
+        )
+        .await?;
+        if !response.status().is_success() {
+            return Err(map_provider_error(
+                response.error_for_status().unwrap_err(),
+                "anthropic",
+            ));
+        }
+        let mut stream = response.bytes_stream();
 
         let full_response: Option<CompletionResponse> = None;
 
@@ -504,6 +593,7 @@ impl LlmProvider for AnthropicProvider {
                                     content: content.to_string(),
                                     name: None,
                                     tool_calls: None,
+                                    cacheable: false,
                                 }),
                                 finish_reason: None,
                             }],
@@ -529,6 +619,8 @@ impl LlmProvider for AnthropicProvider {
             prompt_tokens: prompt_tokens as u32,
             completion_tokens: completion_tokens as u32,
             total_tokens: (prompt_tokens + completion_tokens) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         }
     }
 
@@ -633,6 +725,44 @@ mod tests {
         assert_eq!(mapped["input_schema"]["type"], "object");
     }
 
+    #[test]
+    fn test_apply_tools_maps_tool_choice() {
+        let request = |tool_choice| CompletionRequest {
+            model: "claude-sonnet-4".to_string(),
+            messages: vec![],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: Some(vec![serde_json::json!({
+                "type": "function",
+                "function": { "name": "read", "parameters": { "type": "object" } }
+            })]),
+            tool_choice,
+        };
+
+        let mut body = serde_json::json!({});
+        AnthropicProvider::apply_tools(&mut body, &request(None), false);
+        assert_eq!(body["tools"][0]["name"], "read");
+        assert!(body.get("tool_choice").is_none());
+
+        AnthropicProvider::apply_tools(
+            &mut body,
+            &request(Some(ToolChoice::function("read"))),
+            false,
+        );
+        assert_eq!(
+            body["tool_choice"],
+            serde_json::json!({"type": "tool", "name": "read"})
+        );
+
+        AnthropicProvider::apply_tools(&mut body, &request(Some(ToolChoice::None)), false);
+        assert_eq!(body["tool_choice"], serde_json::json!({"type": "none"}));
+    }
+
     #[test]
     fn test_serialize_messages_includes_tool_result_block() {
         let config = create_anthropic_config("anthropic", "test-key", "claude-sonnet-4");
@@ -645,6 +775,7 @@ mod tests {
                     content: "system".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 Message {
                     role: MessageRole::Assistant,
@@ -652,17 +783,20 @@ mod tests {
                     name: None,
                     tool_calls: Some(vec![ToolCall {
                         id: "toolu_1".to_string(),
+                        index: None,
                         function: ToolCallFunction {
                             name: "list".to_string(),
                             arguments: r#"{"path":"."}"#.to_string(),
                         },
                     }]),
+                    cacheable: false,
                 },
                 Message {
                     role: MessageRole::Tool,
                     content: "[]".to_string(),
                     name: Some("toolu_1".to_string()),
                     tool_calls: None,
+                    cacheable: false,
                 },
             ],
             temperature: Some(0.1),
@@ -673,6 +807,7 @@ mod tests {
             stop: None,
             stream: false,
             tools: None,
+            tool_choice: None,
         };
 
         let mapped = provider.serialize_messages_for_anthropic(&request);
@@ -684,6 +819,122 @@ mod tests {
         assert_eq!(mapped[1]["content"][0]["tool_use_id"], "toolu_1");
     }
 
+    #[test]
+    fn test_request_body_marks_cacheable_messages() {
+        let config = create_anthropic_config("anthropic", "test-key", "claude-sonnet-4");
+        let provider = AnthropicProvider::new(config, Arc::new(SimpleTokenCounter::new()));
+        let message = |role, content: &str| Message {
+            role,
+            content: content.to_string(),
+            name: None,
+            tool_calls: None,
+            cacheable: false,
+        };
+        let request = CompletionRequest {
+            model: "claude-sonnet-4".to_string(),
+            messages: vec![
+                message(MessageRole::System, "You are a coding agent.").mark_cacheable(),
+                message(MessageRole::User, "[memory] project uses tokio").mark_cacheable(),
+                message(MessageRole::Assistant, "noted"),
+                message(MessageRole::User, "fix the build"),
+            ],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: Some(
+                ["read", "grep"]
+                    .iter()
+                    .map(|name| {
+                        serde_json::json!({
+                            "type": "function",
+                            "function": { "name": name, "parameters": {"type": "object"} }
+                        })
+                    })
+                    .collect(),
+            ),
+            tool_choice: None,
+        };
+
+        let body = provider.request_body(&request);
+        assert_eq!(
+            body["system"],
+            serde_json::json!([{
+                "type": "text",
+                "text": "You are a coding agent.",
+                "cache_control": {"type": "ephemeral"}
+            }])
+        );
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(
+            messages[0]["content"],
+            serde_json::json!([{
+                "type": "text",
+                "text": "[memory] project uses tokio",
+                "cache_control": {"type": "ephemeral"}
+            }])
+        );
+        // Unmarked messages are sent unchanged
+        assert!(!messages[1].to_string().contains("cache_control"));
+        assert_eq!(messages[2]["content"], "fix the build");
+        // Tool definitions precede the system prompt; the last one is a breakpoint
+        let tools = body["tools"].as_array().unwrap();
+        assert!(tools[0].get("cache_control").is_none());
+        assert_eq!(
+            tools[1]["cache_control"],
+            serde_json::json!({"type": "ephemeral"})
+        );
+
+        // Without hints the system prompt stays a plain string
+        let mut plain = request.clone();
+        plain.messages.iter_mut().for_each(|m| m.cacheable = false);
+        let body = provider.request_body(&plain);
+        assert_eq!(body["system"], "You are a coding agent.");
+        assert!(!body.to_string().contains("cache_control"));
+    }
+
+    #[test]
+    fn test_parse_usage_includes_cache_tokens() {
+        // Recorded Messages API response on a cache hit
+        let data: serde_json::Value = serde_json::from_str(
+            r#"{
+                "id": "msg_01",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4",
+                "content": [{"type": "text", "text": "ok"}],
+                "stop_reason": "end_turn",
+                "usage": {
+                    "input_tokens": 21,
+                    "cache_creation_input_tokens": 188,
+                    "cache_read_input_tokens": 1800,
+                    "output_tokens": 393
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let usage = AnthropicProvider::parse_usage(&data["usage"]);
+        assert_eq!(usage.cache_creation_tokens, 188);
+        assert_eq!(usage.cache_read_tokens, 1800);
+        assert_eq!(usage.prompt_tokens, 21 + 188 + 1800);
+        assert_eq!(usage.completion_tokens, 393);
+        assert_eq!(usage.total_tokens, 21 + 188 + 1800 + 393);
+
+        let usage = AnthropicProvider::parse_usage(&serde_json::json!({
+            "input_tokens": 5,
+            "output_tokens": 2
+        }));
+        assert_eq!(
+            (usage.cache_creation_tokens, usage.cache_read_tokens),
+            (0, 0)
+        );
+        assert_eq!(usage.total_tokens, 7);
+    }
+
     #[test]
     fn test_get_headers_returns_error_on_invalid_api_key() {
         // API key with newline characters should NOT panic — must return InvalidConfig error
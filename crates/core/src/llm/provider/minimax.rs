@@ -21,6 +21,7 @@ pub struct MiniMaxProvider {
     config: ProviderConfig,
     client: Client,
     token_counter: Arc<dyn TokenCounter>,
+    retry_policy: RetryPolicy,
     group_id: Option<String>,
 }
 
@@ -41,6 +42,7 @@ impl MiniMaxProvider {
             .timeout(std::time::Duration::from_millis(config.timeout_ms))
             .build()
             .expect("Failed to create HTTP client");
+        let retry_policy = RetryPolicy::from_config(&config);
 
         // Extract group_id from organization field if provided
         let group_id = config.organization.clone();
@@ -49,10 +51,17 @@ impl MiniMaxProvider {
             config,
             client,
             token_counter,
+            retry_policy,
             group_id,
         }
     }
 
+    /// Override the retry policy derived from `ProviderConfig.max_retries`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// Create MiniMax provider with group_id
     pub fn with_group_id(
         config: ProviderConfig,
@@ -181,21 +190,8 @@ impl MiniMaxProvider {
         &self,
         response_value: serde_json::Value,
     ) -> Result<CompletionResponse, ProviderError> {
-        if let Some(base_resp) = response_value.get("base_resp") {
-            let status_code = base_resp
-                .get("status_code")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
-            if status_code != 0 {
-                let status_msg = base_resp
-                    .get("status_msg")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown minimax error");
-                return Err(ProviderError::Api {
-                    message: format!("MiniMax base_resp error {}: {}", status_code, status_msg),
-                    status_code: None,
-                });
-            }
+        if let Some(err) = base_resp_error(&response_value) {
+            return Err(err);
         }
 
         let first_choice = response_value
@@ -225,6 +221,8 @@ impl MiniMaxProvider {
             prompt_tokens: u["prompt_tokens"].as_u64().unwrap_or(0) as u32,
             completion_tokens: u["completion_tokens"].as_u64().unwrap_or(0) as u32,
             total_tokens: u["total_tokens"].as_u64().unwrap_or(0) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         });
 
         let finish_reason = first_choice
@@ -255,6 +253,7 @@ impl MiniMaxProvider {
                     content: text.to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 finish_reason: Some(finish_reason),
                 logprobs: None,
@@ -372,11 +371,7 @@ impl LlmProvider for MiniMaxProvider {
             req_builder = req_builder.query(&[("GroupId", group_id)]);
         }
 
-        let response = req_builder
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?;
+        let response = send_with_retry(&self.retry_policy, req_builder.json(&body)).await?;
 
         let status = response.status();
 
@@ -387,7 +382,9 @@ impl LlmProvider for MiniMaxProvider {
         }
 
         if status.as_u16() == 429 {
-            return Err(ProviderError::RateLimited { retry_after: 60 });
+            return Err(ProviderError::RateLimited {
+                retry_after: retry_after_secs(response.headers()).unwrap_or(60),
+            });
         }
 
         if !status.is_success() {
@@ -442,11 +439,7 @@ impl LlmProvider for MiniMaxProvider {
             req_builder = req_builder.query(&[("GroupId", group_id)]);
         }
 
-        let response = req_builder
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?;
+        let response = send_with_retry(&self.retry_policy, req_builder.json(&body)).await?;
 
         let status = response.status();
 
@@ -468,102 +461,16 @@ impl LlmProvider for MiniMaxProvider {
         }
 
         let mut stream = response.bytes_stream();
-
-        let mut full_response: Option<CompletionResponse> = None;
-        let mut buffer = String::new();
+        let mut sse = MiniMaxStream::new(self.config.default_model.clone());
 
         while let Some(chunk_result) = stream.next().await {
             let chunk = chunk_result.map_err(|e| ProviderError::Network { source: e })?;
-            let text = std::str::from_utf8(&chunk).map_err(|e| ProviderError::Api {
-                message: e.to_string(),
-                status_code: None,
-            })?;
-
-            buffer.push_str(text);
-
-            // MiniMax SSE format: data: {...}
-            for line in buffer.lines() {
-                if let Some(data) = line.strip_prefix("data: ") {
-                    if data == "[DONE]" {
-                        if let Some(ref response) = full_response {
-                            handler.on_complete(response).await?;
-                        }
-                        return Ok(());
-                    }
-
-                    if let Ok(value) = serde_json::from_str::<serde_json::Value>(data) {
-                        let chunk_text = value["choices"]
-                            .get(0)
-                            .and_then(|c| c.get("text"))
-                            .and_then(|t| t.as_str())
-                            .or_else(|| {
-                                value["choices"]
-                                    .get(0)
-                                    .and_then(|c| c.get("delta"))
-                                    .and_then(|d| d.get("content"))
-                                    .and_then(|v| v.as_str())
-                            })
-                            .or_else(|| value.get("reply").and_then(|v| v.as_str()))
-                            .unwrap_or("");
-
-                        if !chunk_text.is_empty() {
-                            let stream_chunk = StreamChunk {
-                                id: value["id"].as_str().unwrap_or("").to_string(),
-                                object: "chat.completion.chunk".to_string(),
-                                created: value["created"].as_u64().unwrap_or(0),
-                                model: self.config.default_model.clone(),
-                                choices: vec![StreamChoice {
-                                    index: 0,
-                                    delta: Some(Message {
-                                        role: MessageRole::Assistant,
-                                        content: chunk_text.to_string(),
-                                        name: None,
-                                        tool_calls: None,
-                                    }),
-                                    finish_reason: None,
-                                }],
-                            };
-
-                            handler.on_chunk(&stream_chunk).await?;
-
-                            // Build full response progressively
-                            if full_response.is_none() {
-                                full_response = Some(CompletionResponse {
-                                    id: value["id"].as_str().unwrap_or("").to_string(),
-                                    object: "chat.completion".to_string(),
-                                    created: value["created"].as_u64().unwrap_or(0),
-                                    model: self.config.default_model.clone(),
-                                    choices: vec![Choice {
-                                        index: 0,
-                                        message: Message {
-                                            role: MessageRole::Assistant,
-                                            content: chunk_text.to_string(),
-                                            name: None,
-                                            tool_calls: None,
-                                        },
-                                        finish_reason: None,
-                                        logprobs: None,
-                                    }],
-                                    usage: None,
-                                });
-                            } else if let Some(ref mut resp) = full_response {
-                                // Append content
-                                if let Some(choice) = resp.choices.first_mut() {
-                                    choice.message.content.push_str(chunk_text);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Keep remaining incomplete line
-            if let Some(last_line_break) = buffer.rfind('\n') {
-                buffer = buffer[last_line_break + 1..].to_string();
+            if sse.feed(&chunk, handler).await? {
+                break;
             }
         }
 
-        Ok(())
+        sse.finish(handler).await
     }
 
     fn estimate_tokens(&self, request: &CompletionRequest) -> Usage {
@@ -577,6 +484,8 @@ impl LlmProvider for MiniMaxProvider {
             prompt_tokens: prompt_tokens as u32,
             completion_tokens: completion_tokens as u32,
             total_tokens: (prompt_tokens + completion_tokens) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         }
     }
 
@@ -593,6 +502,215 @@ impl LlmProvider for MiniMaxProvider {
     }
 }
 
+/// `base_resp` envelope error, if the status code is non-zero
+fn base_resp_error(value: &serde_json::Value) -> Option<ProviderError> {
+    let base_resp = value.get("base_resp")?;
+    let status_code = base_resp
+        .get("status_code")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    if status_code == 0 {
+        return None;
+    }
+    let status_msg = base_resp
+        .get("status_msg")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown minimax error");
+    Some(ProviderError::Api {
+        message: format!("MiniMax base_resp error {}: {}", status_code, status_msg),
+        status_code: None,
+    })
+}
+
+/// MiniMax SSE stream accumulator
+///
+/// Splits the body into `data:` lines, forwards content deltas as
+/// `StreamChunk`s and builds the final `CompletionResponse`. MiniMax closes
+/// the stream with a chunk carrying the whole `message` plus `usage`; its
+/// content is only used when no deltas were seen, so it is never duplicated.
+struct MiniMaxStream {
+    buffer: Vec<u8>,
+    id: String,
+    created: u64,
+    model: String,
+    content: String,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    started: bool,
+}
+
+impl MiniMaxStream {
+    fn new(model: String) -> Self {
+        Self {
+            buffer: Vec::new(),
+            id: String::new(),
+            created: 0,
+            model,
+            content: String::new(),
+            finish_reason: None,
+            usage: None,
+            started: false,
+        }
+    }
+
+    /// Feed raw body bytes; returns true once `[DONE]` is seen
+    async fn feed(
+        &mut self,
+        bytes: &[u8],
+        handler: &Arc<dyn StreamHandler>,
+    ) -> Result<bool, ProviderError> {
+        self.buffer.extend_from_slice(bytes);
+        // Only complete lines are decoded, so multi-byte characters split
+        // across network chunks stay intact
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            match self.parse_line(line.trim_end())? {
+                SseLine::Done => {
+                    self.buffer.clear();
+                    return Ok(true);
+                }
+                SseLine::Chunk(chunk) => handler.on_chunk(&chunk).await?,
+                SseLine::Skip => {}
+            }
+        }
+        Ok(false)
+    }
+
+    /// Flush any unterminated last line and report the final response
+    async fn finish(mut self, handler: &Arc<dyn StreamHandler>) -> Result<(), ProviderError> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            if let SseLine::Chunk(chunk) = self.parse_line(line.trim_end())? {
+                handler.on_chunk(&chunk).await?;
+            }
+        }
+        if let Some(response) = self.response() {
+            handler.on_complete(&response).await?;
+        }
+        Ok(())
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<SseLine, ProviderError> {
+        let Some(data) = line.strip_prefix("data:") else {
+            // Blank separators, `event:`/`id:` fields and `:` comments
+            return Ok(SseLine::Skip);
+        };
+        let data = data.trim();
+        if data == "[DONE]" {
+            return Ok(SseLine::Done);
+        }
+        let value = match serde_json::from_str::<serde_json::Value>(data) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!(error = %e, line = %data, "Skipping malformed MiniMax SSE line");
+                return Ok(SseLine::Skip);
+            }
+        };
+        if let Some(err) = base_resp_error(&value) {
+            return Err(err);
+        }
+
+        if !self.started {
+            self.started = true;
+            self.id = value["id"]
+                .as_str()
+                .or_else(|| value["request_id"].as_str())
+                .unwrap_or("minimax-unknown")
+                .to_string();
+            self.created = value["created"].as_u64().unwrap_or(0);
+            if let Some(model) = value["model"].as_str() {
+                self.model = model.to_string();
+            }
+        }
+        if let Some(usage) = value.get("usage").filter(|u| u.is_object()) {
+            let prompt_tokens = usage["prompt_tokens"].as_u64().unwrap_or(0) as u32;
+            let completion_tokens = usage["completion_tokens"].as_u64().unwrap_or(0) as u32;
+            self.usage = Some(Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: usage["total_tokens"]
+                    .as_u64()
+                    .map_or(prompt_tokens + completion_tokens, |t| t as u32),
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+            });
+        }
+
+        let choice = &value["choices"][0];
+        let finish_reason = choice["finish_reason"].as_str().map(str::to_string);
+        if finish_reason.is_some() {
+            self.finish_reason = finish_reason.clone();
+        }
+        let delta = choice["delta"]["content"]
+            .as_str()
+            .or_else(|| choice["text"].as_str())
+            .or_else(|| value["reply"].as_str());
+        let text = match delta {
+            Some(text) => text.to_string(),
+            // Aggregated final message: only new if nothing was streamed
+            None if self.content.is_empty() => choice["message"]["content"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            None => String::new(),
+        };
+        if text.is_empty() && finish_reason.is_none() {
+            return Ok(SseLine::Skip);
+        }
+        self.content.push_str(&text);
+
+        Ok(SseLine::Chunk(StreamChunk {
+            id: self.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model.clone(),
+            choices: vec![StreamChoice {
+                index: 0,
+                delta: (!text.is_empty()).then_some(Message {
+                    role: MessageRole::Assistant,
+                    content: text,
+                    name: None,
+                    tool_calls: None,
+                    cacheable: false,
+                }),
+                finish_reason,
+            }],
+        }))
+    }
+
+    fn response(self) -> Option<CompletionResponse> {
+        if !self.started {
+            return None;
+        }
+        Some(CompletionResponse {
+            id: self.id,
+            object: "chat.completion".to_string(),
+            created: self.created,
+            model: self.model,
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: MessageRole::Assistant,
+                    content: self.content,
+                    name: None,
+                    tool_calls: None,
+                    cacheable: false,
+                },
+                finish_reason: Some(self.finish_reason.unwrap_or_else(|| "stop".to_string())),
+                logprobs: None,
+            }],
+            usage: self.usage,
+        })
+    }
+}
+
+enum SseLine {
+    Chunk(StreamChunk),
+    Done,
+    Skip,
+}
+
 /// Create a MiniMax provider configuration
 pub fn create_minimax_config(
     api_key: String,
@@ -770,12 +888,14 @@ mod tests {
                     content: "you are helpful".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 Message {
                     role: MessageRole::User,
                     content: "hello".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
             ],
             temperature: Some(0.3),
@@ -786,6 +906,7 @@ mod tests {
             stop: None,
             stream: false,
             tools: None,
+            tool_choice: None,
         };
 
         let body = provider.build_request_body(&request);
@@ -796,4 +917,100 @@ mod tests {
         assert!(text.contains("you are helpful"));
         assert_eq!(messages[1]["role"].as_str(), Some("user"));
     }
+
+    #[derive(Default)]
+    struct CollectingHandler {
+        chunks: std::sync::Mutex<Vec<StreamChunk>>,
+        completed: std::sync::Mutex<Vec<CompletionResponse>>,
+    }
+
+    #[async_trait::async_trait]
+    impl StreamHandler for CollectingHandler {
+        async fn on_chunk(&self, chunk: &StreamChunk) -> Result<(), ProviderError> {
+            self.chunks.lock().unwrap().push(chunk.clone());
+            Ok(())
+        }
+
+        async fn on_complete(&self, response: &CompletionResponse) -> Result<(), ProviderError> {
+            self.completed.lock().unwrap().push(response.clone());
+            Ok(())
+        }
+
+        async fn on_error(&self, _error: &ProviderError) {}
+    }
+
+    /// Body recorded from `text/chatcompletion_v2` with `stream: true`
+    const RECORDED_SSE: &str = concat!(
+        "data: {\"id\":\"03f2a1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"你好\",\"role\":\"assistant\"}}],\"created\":1718000000,\"model\":\"abab6.5s-chat\",\"object\":\"chat.completion.chunk\"}\n\n",
+        ": keep-alive\n\n",
+        "data: {not json}\n\n",
+        "data: {\"id\":\"03f2a1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\", world\",\"role\":\"assistant\"}}],\"created\":1718000000,\"model\":\"abab6.5s-chat\",\"object\":\"chat.completion.chunk\"}\n\n",
+        "data: {\"id\":\"03f2a1\",\"choices\":[{\"finish_reason\":\"stop\",\"index\":0,\"message\":{\"content\":\"你好, world\",\"role\":\"assistant\"}}],\"created\":1718000000,\"model\":\"abab6.5s-chat\",\"object\":\"chat.completion\",\"usage\":{\"total_tokens\":21,\"prompt_tokens\":17,\"completion_tokens\":4},\"base_resp\":{\"status_code\":0,\"status_msg\":\"\"}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    #[tokio::test]
+    async fn test_stream_parses_recorded_sse_body() {
+        let collector = Arc::new(CollectingHandler::default());
+        let handler: Arc<dyn StreamHandler> = collector.clone();
+        let mut sse = MiniMaxStream::new("abab6.5s-chat".to_string());
+
+        // Network chunks split mid-line and mid-character
+        let body = RECORDED_SSE.as_bytes();
+        let mut done = false;
+        for piece in body.chunks(7) {
+            done = sse.feed(piece, &handler).await.unwrap();
+            if done {
+                break;
+            }
+        }
+        assert!(done);
+        sse.finish(&handler).await.unwrap();
+
+        let chunks = collector.chunks.lock().unwrap();
+        let deltas: Vec<_> = chunks
+            .iter()
+            .map(|c| c.choices[0].delta.as_ref().map(|m| m.content.as_str()))
+            .collect();
+        assert_eq!(deltas, vec![Some("你好"), Some(", world"), None]);
+        assert_eq!(chunks[2].choices[0].finish_reason.as_deref(), Some("stop"));
+        assert_eq!(chunks[0].id, "03f2a1");
+
+        let completed = collector.completed.lock().unwrap();
+        assert_eq!(completed.len(), 1);
+        let response = &completed[0];
+        assert_eq!(response.choices[0].message.content, "你好, world");
+        assert_eq!(response.choices[0].finish_reason.as_deref(), Some("stop"));
+        let usage = response.usage.as_ref().unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (17, 4, 21)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_without_done_and_base_resp_error() {
+        // Final aggregated message only, no trailing newline or [DONE]
+        let collector = Arc::new(CollectingHandler::default());
+        let handler: Arc<dyn StreamHandler> = collector.clone();
+        let mut sse = MiniMaxStream::new("abab6.5s-chat".to_string());
+        let body = "data: {\"id\":\"x\",\"choices\":[{\"finish_reason\":\"length\",\"message\":{\"content\":\"whole\"}}]}";
+        assert!(!sse.feed(body.as_bytes(), &handler).await.unwrap());
+        sse.finish(&handler).await.unwrap();
+        let completed = collector.completed.lock().unwrap().clone();
+        assert_eq!(completed[0].choices[0].message.content, "whole");
+        assert_eq!(
+            completed[0].choices[0].finish_reason.as_deref(),
+            Some("length")
+        );
+
+        let mut sse = MiniMaxStream::new("abab6.5s-chat".to_string());
+        let body = "data: {\"base_resp\":{\"status_code\":1008,\"status_msg\":\"insufficient balance\"}}\n";
+        let err = sse.feed(body.as_bytes(), &handler).await.unwrap_err();
+        assert!(err.to_string().contains("insufficient balance"));
+    }
 }
@@ -6,23 +6,33 @@
 //! - Request/Response handling
 //! - Token counting
 //! - Model registry
+//! - Retry with exponential backoff
+//! - Model list caching
 
 pub mod anthropic;
 pub mod minimax;
+pub mod ollama;
 pub mod openai;
 pub mod openrouter;
+pub mod pricing;
 pub mod token_counter;
+pub mod tool_call_accumulator;
 
 pub use anthropic::{AnthropicProvider, create_anthropic_config};
 pub use minimax::{MiniMaxProvider, create_minimax_config};
+pub use ollama::{OllamaProvider, create_ollama_config};
 pub use openai::{OpenAiProvider, create_azure_config, create_openai_config};
 pub use openrouter::{OpenRouterProvider, create_openrouter_config};
+pub use pricing::{CostEstimate, ModelPricing, ModelRate};
 pub use token_counter::{SimpleTokenCounter, TokenCountError};
+pub use tool_call_accumulator::ToolCallAccumulator;
 
 use serde::de::Deserializer;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Provider-specific errors
@@ -75,11 +85,29 @@ pub enum MessageRole {
 /// A single message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
+    /// Streamed deltas after the first omit the role
+    #[serde(default = "assistant_role")]
     pub role: MessageRole,
     #[serde(default, deserialize_with = "deserialize_message_content")]
     pub content: String,
     pub name: Option<String>,
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// Opt-in prompt caching breakpoint: the prompt up to and including this
+    /// message may be cached (Anthropic `cache_control`); other providers ignore it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cacheable: bool,
+}
+
+impl Message {
+    /// Mark the message as a prompt caching breakpoint
+    pub fn mark_cacheable(mut self) -> Self {
+        self.cacheable = true;
+        self
+    }
+}
+
+fn assistant_role() -> MessageRole {
+    MessageRole::Assistant
 }
 
 fn deserialize_message_content<'de, D>(deserializer: D) -> Result<String, D::Error>
@@ -93,14 +121,22 @@ where
 /// Tool call request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
+    /// Only the first streamed fragment of a call carries the id
+    #[serde(default)]
     pub id: String,
+    /// Position of the call in a streamed response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    #[serde(default)]
     pub function: ToolCallFunction,
 }
 
 /// Tool call function
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolCallFunction {
+    #[serde(default)]
     pub name: String,
+    #[serde(default)]
     pub arguments: String,
 }
 
@@ -125,6 +161,49 @@ pub struct CompletionRequest {
     pub stop: Option<Vec<String>>,
     pub stream: bool,
     pub tools: Option<Vec<serde_json::Value>>,
+    /// 工具选择策略；None 时由 provider 默认为 auto
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+}
+
+/// Tool choice - 控制模型是否/调用哪个工具
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ToolChoice {
+    /// 由模型决定
+    Auto,
+    /// 禁止调用工具
+    None,
+    /// 强制调用指定工具
+    Function { name: String },
+}
+
+impl ToolChoice {
+    /// 强制调用指定工具
+    pub fn function(name: impl Into<String>) -> Self {
+        Self::Function { name: name.into() }
+    }
+
+    /// OpenAI Chat Completions 格式
+    pub fn to_openai(&self) -> serde_json::Value {
+        match self {
+            Self::Auto => serde_json::json!("auto"),
+            Self::None => serde_json::json!("none"),
+            Self::Function { name } => serde_json::json!({
+                "type": "function",
+                "function": { "name": name }
+            }),
+        }
+    }
+
+    /// Anthropic Messages 格式
+    pub fn to_anthropic(&self) -> serde_json::Value {
+        match self {
+            Self::Auto => serde_json::json!({ "type": "auto" }),
+            Self::None => serde_json::json!({ "type": "none" }),
+            Self::Function { name } => serde_json::json!({ "type": "tool", "name": name }),
+        }
+    }
 }
 
 /// Response from LLM
@@ -147,12 +226,65 @@ pub struct Choice {
     pub logprobs: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl Choice {
+    /// `finish_reason` normalized across providers; the raw string stays in
+    /// `finish_reason`
+    pub fn normalized_finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason.as_deref().map(FinishReason::from_raw)
+    }
+
+    /// Whether generation stopped at the token limit
+    pub fn is_truncated(&self) -> bool {
+        self.normalized_finish_reason() == Some(FinishReason::Length)
+    }
+}
+
+/// Why generation stopped, normalized across providers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinishReason {
+    /// Natural end of turn or a stop sequence
+    Stop,
+    /// Token limit reached; the output is truncated
+    Length,
+    /// The model is calling tools
+    ToolCalls,
+    /// Output withheld by a safety filter or refused
+    ContentFilter,
+    /// Provider-specific value with no normalized equivalent
+    Other(String),
+}
+
+impl FinishReason {
+    /// Map a provider's raw finish reason (case-insensitive)
+    ///
+    /// - OpenAI / OpenRouter / MiniMax: `stop`, `length`, `tool_calls`,
+    ///   `function_call`, `content_filter`
+    /// - Anthropic `stop_reason`: `end_turn`, `stop_sequence`, `max_tokens`,
+    ///   `tool_use`, `refusal`
+    pub fn from_raw(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "stop" | "end_turn" | "stop_sequence" => Self::Stop,
+            "length" | "max_tokens" => Self::Length,
+            "tool_calls" | "function_call" | "tool_use" => Self::ToolCalls,
+            "content_filter" | "refusal" | "safety" => Self::ContentFilter,
+            _ => Self::Other(raw.to_string()),
+        }
+    }
+}
+
 /// Token usage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Usage {
+    /// Includes cache creation and cache read tokens
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    /// Prompt tokens written to the provider's prompt cache
+    #[serde(default)]
+    pub cache_creation_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u32,
 }
 
 /// Stream chunk
@@ -173,6 +305,13 @@ pub struct StreamChoice {
     pub finish_reason: Option<String>,
 }
 
+impl StreamChoice {
+    /// `finish_reason` normalized across providers
+    pub fn normalized_finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason.as_deref().map(FinishReason::from_raw)
+    }
+}
+
 /// Model information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
@@ -318,9 +457,35 @@ pub trait LlmProvider: Send + Sync {
     /// Get token usage for a request
     fn estimate_tokens(&self, request: &CompletionRequest) -> Usage;
 
+    /// Estimate the dollar cost of a request
+    ///
+    /// Uses `usage` when known, otherwise `estimate_tokens`. Returns `None`
+    /// when the model has no entry in `pricing`.
+    fn estimate_cost(
+        &self,
+        request: &CompletionRequest,
+        usage: Option<&Usage>,
+        pricing: &ModelPricing,
+    ) -> Option<CostEstimate> {
+        let model = if request.model.is_empty() {
+            &self.config().default_model
+        } else {
+            &request.model
+        };
+        match usage {
+            Some(usage) => pricing.estimate(model, usage),
+            None => pricing.estimate(model, &self.estimate_tokens(request)),
+        }
+    }
+
     /// Check if model is available
     async fn is_model_available(&self, model: &str) -> bool;
 
+    /// Re-fetch the model list, bypassing any cached result
+    async fn refresh_models(&self) -> Result<Vec<ModelInfo>, ProviderError> {
+        self.list_models().await
+    }
+
     /// Get configuration
     fn config(&self) -> &ProviderConfig;
 }
@@ -371,6 +536,229 @@ pub fn map_provider_error(error: reqwest::Error, _provider: &str) -> ProviderErr
     }
 }
 
+/// Retry policy shared by all providers
+///
+/// Retries rate limiting (429), transient server errors (5xx) and network
+/// timeouts/connection failures. Other failures return immediately.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Policy using `ProviderConfig.max_retries` and default delays
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Backoff before retry number `attempt` (0-based)
+    ///
+    /// The server's `Retry-After` seeds the delay when it is longer than the
+    /// base delay; the result is doubled per attempt, capped at `max_delay`,
+    /// and up to 25% jitter is added.
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let seed = retry_after.unwrap_or_default().max(self.base_delay);
+        let delay = seed
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let jitter_range = delay.as_millis() as u64 / 4;
+        let jitter = if jitter_range == 0 {
+            0
+        } else {
+            random_u64() % (jitter_range + 1)
+        };
+        delay + Duration::from_millis(jitter)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+fn random_u64() -> u64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.finish()
+}
+
+/// Short-lived cache of `list_models` results
+///
+/// A successful fetch is reused for `ttl`. A failed fetch is only remembered
+/// for `failure_cooldown`, after which the next lookup goes to the network
+/// again. Clones share the same cache.
+#[derive(Debug, Clone)]
+pub struct ModelCache {
+    ttl: Duration,
+    failure_cooldown: Duration,
+    state: Arc<std::sync::Mutex<Option<CachedModels>>>,
+}
+
+#[derive(Debug, Clone)]
+enum CachedModels {
+    Fetched {
+        models: Arc<Vec<ModelInfo>>,
+        at: std::time::Instant,
+    },
+    Failed {
+        at: std::time::Instant,
+    },
+}
+
+impl ModelCache {
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+    pub const DEFAULT_FAILURE_COOLDOWN: Duration = Duration::from_secs(10);
+
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            failure_cooldown: Self::DEFAULT_FAILURE_COOLDOWN.min(ttl),
+            state: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    pub fn with_failure_cooldown(mut self, failure_cooldown: Duration) -> Self {
+        self.failure_cooldown = failure_cooldown;
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Forget the cached result so the next lookup fetches
+    pub fn invalidate(&self) {
+        *self.lock() = None;
+    }
+
+    /// Cached models, calling `fetch` when missing or stale
+    ///
+    /// Returns `None` while the last fetch failed and the cooldown is running.
+    pub async fn get_or_fetch<F, Fut>(&self, fetch: F) -> Option<Arc<Vec<ModelInfo>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<ModelInfo>, ProviderError>>,
+    {
+        match self.lock().as_ref() {
+            Some(CachedModels::Fetched { models, at }) if at.elapsed() < self.ttl => {
+                return Some(models.clone());
+            }
+            Some(CachedModels::Failed { at }) if at.elapsed() < self.failure_cooldown => {
+                return None;
+            }
+            _ => {}
+        }
+        self.store(fetch().await).ok()
+    }
+
+    /// Record a fetch result, returning the models on success
+    pub fn store(
+        &self,
+        result: Result<Vec<ModelInfo>, ProviderError>,
+    ) -> Result<Arc<Vec<ModelInfo>>, ProviderError> {
+        let at = std::time::Instant::now();
+        match result {
+            Ok(models) => {
+                let models = Arc::new(models);
+                *self.lock() = Some(CachedModels::Fetched {
+                    models: models.clone(),
+                    at,
+                });
+                Ok(models)
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, "model list fetch failed, cooling down");
+                *self.lock() = Some(CachedModels::Failed { at });
+                Err(e)
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<CachedModels>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for ModelCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TTL)
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds
+pub fn retry_after_secs(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Send a request, retrying 429/5xx responses and transient network errors
+///
+/// The final response is returned as-is (including error statuses) so each
+/// provider keeps its own status-to-error mapping.
+pub async fn send_with_retry(
+    policy: &RetryPolicy,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, ProviderError> {
+    let mut attempt = 0;
+    loop {
+        let Some(this_try) = request.try_clone() else {
+            // Streaming bodies cannot be replayed; send once.
+            return request
+                .send()
+                .await
+                .map_err(|e| ProviderError::Network { source: e });
+        };
+
+        let retry_after = match this_try.send().await {
+            Ok(response) => {
+                let status = response.status();
+                let retryable =
+                    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if !retryable || attempt >= policy.max_retries {
+                    return Ok(response);
+                }
+                retry_after_secs(response.headers()).map(Duration::from_secs)
+            }
+            Err(e) => {
+                if !(e.is_timeout() || e.is_connect()) || attempt >= policy.max_retries {
+                    return Err(ProviderError::Network { source: e });
+                }
+                None
+            }
+        };
+
+        let delay = policy.backoff(attempt, retry_after);
+        tracing::warn!(
+            attempt = attempt + 1,
+            max_retries = policy.max_retries,
+            delay_ms = delay.as_millis() as u64,
+            "LLM request failed with a retryable error, backing off"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,6 +791,7 @@ mod tests {
             content: "Hello!".to_string(),
             name: None,
             tool_calls: None,
+            cacheable: false,
         };
 
         let json = serde_json::to_string(&message).unwrap();
@@ -412,6 +801,56 @@ mod tests {
         assert_eq!(parsed.content, "Hello!");
     }
 
+    #[test]
+    fn test_finish_reason_normalization() {
+        use FinishReason::*;
+        let cases = [
+            // OpenAI / OpenRouter / MiniMax
+            ("stop", Stop),
+            ("length", Length),
+            ("tool_calls", ToolCalls),
+            ("function_call", ToolCalls),
+            ("content_filter", ContentFilter),
+            // Anthropic
+            ("end_turn", Stop),
+            ("stop_sequence", Stop),
+            ("max_tokens", Length),
+            ("tool_use", ToolCalls),
+            ("refusal", ContentFilter),
+            // Upper-case variants (e.g. Gemini via OpenRouter)
+            ("MAX_TOKENS", Length),
+            ("SAFETY", ContentFilter),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(FinishReason::from_raw(raw), expected, "{}", raw);
+        }
+        assert_eq!(
+            FinishReason::from_raw("pause_turn"),
+            Other("pause_turn".to_string())
+        );
+
+        let choice = Choice {
+            index: 0,
+            message: Message {
+                role: MessageRole::Assistant,
+                content: "partial".to_string(),
+                name: None,
+                tool_calls: None,
+                cacheable: false,
+            },
+            finish_reason: Some("max_tokens".to_string()),
+            logprobs: None,
+        };
+        assert!(choice.is_truncated());
+        assert_eq!(choice.finish_reason.as_deref(), Some("max_tokens"));
+        let stream_choice = StreamChoice {
+            index: 0,
+            delta: None,
+            finish_reason: None,
+        };
+        assert_eq!(stream_choice.normalized_finish_reason(), None);
+    }
+
     #[test]
     fn test_completion_request_serde() {
         let request = CompletionRequest {
@@ -422,12 +861,14 @@ mod tests {
                     content: "You are a helpful assistant.".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
                 Message {
                     role: MessageRole::User,
                     content: "Hello!".to_string(),
                     name: None,
                     tool_calls: None,
+                    cacheable: false,
                 },
             ],
             temperature: Some(0.7),
@@ -438,6 +879,7 @@ mod tests {
             stop: None,
             stream: false,
             tools: None,
+            tool_choice: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -463,6 +905,8 @@ mod tests {
             prompt_tokens: 10,
             completion_tokens: 50,
             total_tokens: 60,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         };
 
         let json = serde_json::to_string(&usage).unwrap();
@@ -477,6 +921,7 @@ mod tests {
     fn test_tool_call_serde() {
         let tool_call = ToolCall {
             id: "call-1".to_string(),
+            index: None,
             function: ToolCallFunction {
                 name: "search".to_string(),
                 arguments: r#"{"query": "test"}"#.to_string(),
@@ -515,4 +960,236 @@ mod tests {
         let parsed: Test = serde_json::from_str(json).unwrap();
         assert_eq!(parsed.role, MessageRole::User);
     }
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve `statuses` in order (last one repeats), counting requests
+    async fn mock_llm_server(statuses: Vec<u16>, hits: Arc<AtomicUsize>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let n = hits.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[n.min(statuses.len() - 1)];
+
+                // Drain the request so closing the socket does not reset it.
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let read = socket.read(&mut buf).await.unwrap_or(0);
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(header_end) = text.find("\r\n\r\n") {
+                        let length = text[..header_end]
+                            .lines()
+                            .find_map(|l| {
+                                l.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= header_end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+
+                let body = if status == 200 && request.starts_with(b"GET") {
+                    serde_json::json!({
+                        "object": "list",
+                        "data": [{
+                            "id": "ft:custom-model",
+                            "object": "model",
+                            "created": 0,
+                            "owned_by": "org",
+                            "permission": []
+                        }]
+                    })
+                    .to_string()
+                } else if status == 200 {
+                    serde_json::json!({
+                        "id": "chatcmpl-1",
+                        "object": "chat.completion",
+                        "created": 0,
+                        "model": "gpt-4",
+                        "choices": [{
+                            "index": 0,
+                            "message": { "role": "assistant", "content": "hi" },
+                            "finish_reason": "stop"
+                        }]
+                    })
+                    .to_string()
+                } else {
+                    "{}".to_string()
+                };
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nRetry-After: 0\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn test_provider(base_url: String, max_retries: u32) -> OpenAiProvider {
+        let mut config = create_openai_config("test", "sk-test", "gpt-4");
+        config.base_url = Some(base_url);
+        OpenAiProvider::new(config, Arc::new(SimpleTokenCounter::new())).with_retry_policy(
+            RetryPolicy {
+                max_retries,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(10),
+            },
+        )
+    }
+
+    fn test_request() -> CompletionRequest {
+        CompletionRequest {
+            model: "gpt-4".to_string(),
+            messages: vec![Message {
+                role: MessageRole::User,
+                content: "Hello!".to_string(),
+                name: None,
+                tool_calls: None,
+                cacheable: false,
+            }],
+            temperature: None,
+            max_tokens: Some(16),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_retries_rate_limit_then_succeeds() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = mock_llm_server(vec![429, 429, 200], hits.clone()).await;
+
+        let response = test_provider(base_url, 3)
+            .complete(&test_request())
+            .await
+            .unwrap();
+
+        assert_eq!(response.choices[0].message.content, "hi");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_complete_gives_up_after_max_retries() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = mock_llm_server(vec![503], hits.clone()).await;
+
+        let result = test_provider(base_url, 2).complete(&test_request()).await;
+
+        assert!(matches!(
+            result,
+            Err(ProviderError::Api {
+                status_code: Some(503),
+                ..
+            })
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_complete_does_not_retry_auth_errors() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = mock_llm_server(vec![401, 200], hits.clone()).await;
+
+        let result = test_provider(base_url, 3).complete(&test_request()).await;
+
+        assert!(matches!(result, Err(ProviderError::Auth { .. })));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_is_seeded_by_retry_after_and_capped() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        };
+
+        let first = policy.backoff(0, None);
+        assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(125));
+
+        let third = policy.backoff(2, None);
+        assert!(third >= Duration::from_millis(400) && third <= Duration::from_millis(500));
+
+        let seeded = policy.backoff(0, Some(Duration::from_secs(2)));
+        assert!(seeded >= Duration::from_secs(2) && seeded <= Duration::from_millis(2500));
+
+        let capped = policy.backoff(10, Some(Duration::from_secs(5)));
+        assert!(capped >= Duration::from_secs(10) && capped <= Duration::from_millis(12500));
+    }
+
+    #[tokio::test]
+    async fn test_model_cache_suppresses_redundant_fetches() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = mock_llm_server(vec![200], hits.clone()).await;
+        let provider =
+            test_provider(base_url, 0).with_model_cache(ModelCache::new(Duration::from_secs(60)));
+
+        for _ in 0..3 {
+            assert!(provider.is_model_available("ft:custom-model").await);
+        }
+        assert!(!provider.is_model_available("ft:other").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Configured models never need the network
+        assert!(provider.is_model_available("gpt-4").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let models = provider.refresh_models().await.unwrap();
+        assert_eq!(models[0].id, "ft:custom-model");
+        assert!(provider.is_model_available("ft:custom-model").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_model_cache_refetches_after_ttl() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = mock_llm_server(vec![200], hits.clone()).await;
+        let provider =
+            test_provider(base_url, 0).with_model_cache(ModelCache::new(Duration::from_millis(30)));
+
+        assert!(provider.is_model_available("ft:custom-model").await);
+        assert!(provider.is_model_available("ft:custom-model").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(provider.is_model_available("ft:custom-model").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_retries_after_cooldown() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = mock_llm_server(vec![500, 200], hits.clone()).await;
+        let provider = test_provider(base_url, 0).with_model_cache(
+            ModelCache::new(Duration::from_secs(60))
+                .with_failure_cooldown(Duration::from_millis(30)),
+        );
+
+        assert!(!provider.is_model_available("ft:custom-model").await);
+        assert!(!provider.is_model_available("ft:custom-model").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(provider.is_model_available("ft:custom-model").await);
+        assert!(provider.is_model_available("ft:custom-model").await);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,764 @@
+//! Ollama Provider Implementation
+//!
+//! Supports:
+//! - Ollama native chat API (`POST /api/chat`)
+//! - Streaming responses (newline-delimited JSON)
+//! - Model list API (`GET /api/tags`)
+//! - Tool calling; models without tool support are retried without tools
+//!
+//! API Documentation: https://github.com/ollama/ollama/blob/main/docs/api.md
+
+use super::*;
+use futures_util::StreamExt;
+use reqwest::{Client, StatusCode};
+use std::sync::Arc;
+
+/// Default local Ollama host
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Ollama Provider
+#[derive(Clone)]
+pub struct OllamaProvider {
+    config: ProviderConfig,
+    client: Client,
+    token_counter: Arc<dyn TokenCounter>,
+    retry_policy: RetryPolicy,
+    model_cache: ModelCache,
+}
+
+impl std::fmt::Debug for OllamaProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OllamaProvider")
+            .field("name", &self.config.name)
+            .field("base_url", &self.get_base_url())
+            .field("default_model", &self.config.default_model)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OllamaProvider {
+    /// Create a new Ollama provider
+    pub fn new(config: ProviderConfig, token_counter: Arc<dyn TokenCounter>) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_millis(config.timeout_ms))
+            .build()
+            .expect("Failed to create HTTP client");
+        let retry_policy = RetryPolicy::from_config(&config);
+
+        Self {
+            config,
+            client,
+            token_counter,
+            retry_policy,
+            model_cache: ModelCache::default(),
+        }
+    }
+
+    /// Override the retry policy derived from `ProviderConfig.max_retries`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Replace the model list cache (e.g. to change its TTL)
+    pub fn with_model_cache(mut self, model_cache: ModelCache) -> Self {
+        self.model_cache = model_cache;
+        self
+    }
+
+    /// Host root; an OpenAI-compatible `/v1` suffix is dropped
+    fn get_base_url(&self) -> String {
+        let url = self
+            .config
+            .base_url
+            .as_deref()
+            .unwrap_or(OLLAMA_BASE_URL)
+            .trim_end_matches('/');
+        url.strip_suffix("/v1").unwrap_or(url).to_string()
+    }
+
+    fn model<'a>(&'a self, request: &'a CompletionRequest) -> &'a str {
+        if request.model.is_empty() {
+            &self.config.default_model
+        } else {
+            &request.model
+        }
+    }
+
+    fn serialize_messages(request: &CompletionRequest) -> Vec<serde_json::Value> {
+        request
+            .messages
+            .iter()
+            .map(|m| {
+                let mut msg = serde_json::json!({
+                    "role": m.role,
+                    "content": m.content,
+                });
+                if let Some(calls) = m.tool_calls.as_ref().filter(|c| !c.is_empty()) {
+                    // Ollama takes arguments as an object, not a JSON string
+                    msg["tool_calls"] = calls
+                        .iter()
+                        .map(|call| {
+                            let arguments = serde_json::from_str(&call.function.arguments)
+                                .unwrap_or_else(|_| serde_json::json!({}));
+                            serde_json::json!({
+                                "function": {
+                                    "name": call.function.name,
+                                    "arguments": arguments,
+                                }
+                            })
+                        })
+                        .collect();
+                }
+                msg
+            })
+            .collect()
+    }
+
+    fn build_body(&self, request: &CompletionRequest, stream: bool) -> serde_json::Value {
+        let mut options = serde_json::json!({
+            "temperature": request.temperature.unwrap_or(0.7),
+        });
+        if let Some(max_tokens) = request.max_tokens {
+            options["num_predict"] = serde_json::json!(max_tokens);
+        }
+        if let Some(top_p) = request.top_p {
+            options["top_p"] = serde_json::json!(top_p);
+        }
+        if let Some(stop) = &request.stop {
+            options["stop"] = serde_json::json!(stop);
+        }
+
+        let mut body = serde_json::json!({
+            "model": self.model(request),
+            "messages": Self::serialize_messages(request),
+            "stream": stream,
+            "options": options,
+        });
+        // Ollama has no tool_choice; `None` means no tools at all
+        if request.tool_choice != Some(ToolChoice::None)
+            && let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty())
+        {
+            body["tools"] = serde_json::json!(tools);
+        }
+        body
+    }
+
+    /// POST `/api/chat`, retrying once without tools when the model lacks
+    /// tool support
+    async fn post_chat(
+        &self,
+        mut body: serde_json::Value,
+    ) -> Result<reqwest::Response, ProviderError> {
+        let url = format!("{}/api/chat", self.get_base_url());
+        loop {
+            let response =
+                send_with_retry(&self.retry_policy, self.client.post(&url).json(&body)).await?;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            let message = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v["error"].as_str().map(String::from))
+                .unwrap_or_else(|| format!("API returned status {}", status));
+
+            if status == StatusCode::BAD_REQUEST
+                && message.contains("does not support tools")
+                && body
+                    .as_object_mut()
+                    .is_some_and(|b| b.remove("tools").is_some())
+            {
+                tracing::warn!(model = %body["model"], "Model does not support tools, retrying without them");
+                continue;
+            }
+            return Err(match status {
+                StatusCode::NOT_FOUND => ProviderError::ModelNotFound {
+                    model: body["model"].as_str().unwrap_or_default().to_string(),
+                },
+                StatusCode::BAD_REQUEST => ProviderError::InvalidRequest { message },
+                StatusCode::TOO_MANY_REQUESTS => ProviderError::RateLimited { retry_after: 60 },
+                _ => ProviderError::Api {
+                    message,
+                    status_code: Some(status.as_u16()),
+                },
+            });
+        }
+    }
+}
+
+/// Tool calls of an Ollama message, with generated ids (Ollama has none)
+fn parse_tool_calls(message: &serde_json::Value) -> Option<Vec<ToolCall>> {
+    let calls: Vec<ToolCall> = message["tool_calls"]
+        .as_array()?
+        .iter()
+        .enumerate()
+        .map(|(index, call)| ToolCall {
+            id: format!("call_{}", index),
+            index: Some(index),
+            function: ToolCallFunction {
+                name: call["function"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                arguments: match &call["function"]["arguments"] {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Null => "{}".to_string(),
+                    other => other.to_string(),
+                },
+            },
+        })
+        .collect();
+    (!calls.is_empty()).then_some(calls)
+}
+
+/// Finish reason of a `done` line: `tool_calls` when tools were called,
+/// otherwise Ollama's `done_reason`
+fn finish_reason(line: &serde_json::Value, has_tool_calls: bool) -> String {
+    if has_tool_calls {
+        return "tool_calls".to_string();
+    }
+    line["done_reason"].as_str().unwrap_or("stop").to_string()
+}
+
+fn parse_usage(line: &serde_json::Value) -> Usage {
+    let prompt_tokens = line["prompt_eval_count"].as_u64().unwrap_or(0) as u32;
+    let completion_tokens = line["eval_count"].as_u64().unwrap_or(0) as u32;
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+        cache_creation_tokens: 0,
+        cache_read_tokens: 0,
+    }
+}
+
+/// Unix seconds of an RFC 3339 timestamp (0 when missing)
+fn parse_created(value: &serde_json::Value) -> u64 {
+    value
+        .as_str()
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map_or(0, |t| t.timestamp().max(0) as u64)
+}
+
+fn stream_error(line: &serde_json::Value) -> Option<ProviderError> {
+    line["error"].as_str().map(|message| ProviderError::Api {
+        message: message.to_string(),
+        status_code: None,
+    })
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for OllamaProvider {
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Ollama
+    }
+
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, ProviderError> {
+        let url = format!("{}/api/tags", self.get_base_url());
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ProviderError::Network { source: e })?;
+
+        if !response.status().is_success() {
+            return Err(map_provider_error(
+                response.error_for_status().unwrap_err(),
+                "ollama",
+            ));
+        }
+
+        let data: serde_json::Value = response.json().await.map_err(|e| ProviderError::Api {
+            message: e.to_string(),
+            status_code: None,
+        })?;
+
+        let models = data["models"]
+            .as_array()
+            .ok_or_else(|| ProviderError::Api {
+                message: "Failed to parse models: missing 'models'".to_string(),
+                status_code: None,
+            })?
+            .iter()
+            .filter_map(|m| {
+                Some(ModelInfo {
+                    id: m["name"]
+                        .as_str()
+                        .or_else(|| m["model"].as_str())?
+                        .to_string(),
+                    object: "model".to_string(),
+                    created: parse_created(&m["modified_at"]),
+                    owned_by: "ollama".to_string(),
+                    permission: Vec::new(),
+                })
+            })
+            .collect();
+
+        Ok(models)
+    }
+
+    async fn complete(
+        &self,
+        request: &CompletionRequest,
+    ) -> Result<CompletionResponse, ProviderError> {
+        let response = self.post_chat(self.build_body(request, false)).await?;
+
+        let data: serde_json::Value = response.json().await.map_err(|e| ProviderError::Api {
+            message: format!("Failed to parse response: {}", e),
+            status_code: None,
+        })?;
+        if let Some(error) = stream_error(&data) {
+            return Err(error);
+        }
+
+        let tool_calls = parse_tool_calls(&data["message"]);
+        let created = parse_created(&data["created_at"]);
+        Ok(CompletionResponse {
+            id: format!("ollama-{}", created),
+            object: "chat.completion".to_string(),
+            created,
+            model: data["model"]
+                .as_str()
+                .unwrap_or(self.model(request))
+                .to_string(),
+            choices: vec![Choice {
+                index: 0,
+                finish_reason: Some(finish_reason(&data, tool_calls.is_some())),
+                message: Message {
+                    role: MessageRole::Assistant,
+                    content: data["message"]["content"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    name: None,
+                    tool_calls,
+                    cacheable: false,
+                },
+                logprobs: None,
+            }],
+            usage: Some(parse_usage(&data)),
+        })
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        handler: &Arc<dyn StreamHandler>,
+    ) -> Result<(), ProviderError> {
+        let response = self.post_chat(self.build_body(request, true)).await?;
+        let mut stream = response.bytes_stream();
+
+        let model = self.model(request).to_string();
+        let mut buffer = Vec::new();
+        let mut content = String::new();
+        let mut tool_calls: Vec<ToolCall> = Vec::new();
+        let mut created = 0;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| ProviderError::Network { source: e })?;
+            buffer.extend_from_slice(&chunk);
+
+            // A JSON line may be split across network chunks
+            while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                let line = std::str::from_utf8(&line).map_err(|e| ProviderError::Api {
+                    message: e.to_string(),
+                    status_code: None,
+                })?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let value: serde_json::Value =
+                    serde_json::from_str(line).map_err(|e| ProviderError::Api {
+                        message: format!("Invalid stream line: {}", e),
+                        status_code: None,
+                    })?;
+                if let Some(error) = stream_error(&value) {
+                    handler.on_error(&error).await;
+                    return Err(error);
+                }
+                if created == 0 {
+                    created = parse_created(&value["created_at"]);
+                }
+
+                let delta_text = value["message"]["content"].as_str().unwrap_or_default();
+                content.push_str(delta_text);
+                let mut delta_calls = parse_tool_calls(&value["message"]);
+                if let Some(calls) = delta_calls.as_mut() {
+                    for call in calls.iter_mut() {
+                        let index = tool_calls.len();
+                        call.id = format!("call_{}", index);
+                        call.index = Some(index);
+                        tool_calls.push(call.clone());
+                    }
+                }
+
+                let done = value["done"].as_bool().unwrap_or(false);
+                let chunk = StreamChunk {
+                    id: format!("ollama-{}", created),
+                    object: "chat.completion.chunk".to_string(),
+                    created,
+                    model: model.clone(),
+                    choices: vec![StreamChoice {
+                        index: 0,
+                        delta: Some(Message {
+                            role: MessageRole::Assistant,
+                            content: delta_text.to_string(),
+                            name: None,
+                            tool_calls: delta_calls,
+                            cacheable: false,
+                        }),
+                        finish_reason: done.then(|| finish_reason(&value, !tool_calls.is_empty())),
+                    }],
+                };
+                handler.on_chunk(&chunk).await?;
+
+                if done {
+                    let response = CompletionResponse {
+                        id: format!("ollama-{}", created),
+                        object: "chat.completion".to_string(),
+                        created,
+                        model: model.clone(),
+                        choices: vec![Choice {
+                            index: 0,
+                            finish_reason: Some(finish_reason(&value, !tool_calls.is_empty())),
+                            message: Message {
+                                role: MessageRole::Assistant,
+                                content: std::mem::take(&mut content),
+                                name: None,
+                                tool_calls: (!tool_calls.is_empty())
+                                    .then(|| std::mem::take(&mut tool_calls)),
+                                cacheable: false,
+                            },
+                            logprobs: None,
+                        }],
+                        usage: Some(parse_usage(&value)),
+                    };
+                    handler.on_complete(&response).await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn estimate_tokens(&self, request: &CompletionRequest) -> Usage {
+        let prompt_tokens = self
+            .token_counter
+            .count_messages(&request.messages, &request.model);
+        let completion_tokens = request.max_tokens.unwrap_or(1024) as usize;
+        Usage {
+            prompt_tokens: prompt_tokens as u32,
+            completion_tokens: completion_tokens as u32,
+            total_tokens: (prompt_tokens + completion_tokens) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+        }
+    }
+
+    async fn is_model_available(&self, model: &str) -> bool {
+        if self.config.models.iter().any(|m| m == model) {
+            return true;
+        }
+        self.model_cache
+            .get_or_fetch(|| self.list_models())
+            .await
+            .is_some_and(|models| {
+                models
+                    .iter()
+                    .any(|m| m.id == model || m.id == format!("{}:latest", model))
+            })
+    }
+
+    async fn refresh_models(&self) -> Result<Vec<ModelInfo>, ProviderError> {
+        self.model_cache.invalidate();
+        let models = self.model_cache.store(self.list_models().await)?;
+        Ok(models.as_ref().clone())
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+}
+
+/// Create a basic Ollama configuration (`base_url` defaults to the local host)
+pub fn create_ollama_config(
+    name: &str,
+    base_url: Option<&str>,
+    default_model: &str,
+) -> ProviderConfig {
+    ProviderConfig {
+        name: name.to_string(),
+        provider_type: ProviderType::Ollama,
+        api_key: String::new(),
+        base_url: Some(base_url.unwrap_or(OLLAMA_BASE_URL).to_string()),
+        organization: None,
+        default_model: default_model.to_string(),
+        models: vec![default_model.to_string()],
+        timeout_ms: 300000,
+        max_retries: 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::Mutex;
+
+    /// Answer requests with `responses` in order (last one repeats),
+    /// recording each request's first line and body
+    async fn mock_ollama(responses: Vec<(u16, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            let mut served = 0;
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let (head, body) = loop {
+                    let read = socket.read(&mut buf).await.unwrap_or(0);
+                    request.extend_from_slice(&buf[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some(header_end) = text.find("\r\n\r\n") {
+                        let length = text[..header_end]
+                            .lines()
+                            .find_map(|l| {
+                                l.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        if read == 0 || request.len() >= header_end + 4 + length {
+                            let head = text.lines().next().unwrap_or_default().to_string();
+                            break (head, text[header_end + 4..].to_string());
+                        }
+                    } else if read == 0 {
+                        break (String::new(), String::new());
+                    }
+                };
+                recorded.lock().await.push(format!("{}\n{}", head, body));
+
+                let (status, body) = &responses[served.min(responses.len() - 1)];
+                served += 1;
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Type: application/x-ndjson\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+
+    fn provider(base_url: &str) -> OllamaProvider {
+        let config = create_ollama_config("ollama", Some(base_url), "llama3.2");
+        OllamaProvider::new(config, Arc::new(SimpleTokenCounter::new()))
+    }
+
+    fn request(tools: Option<Vec<serde_json::Value>>) -> CompletionRequest {
+        CompletionRequest {
+            model: "llama3.2".to_string(),
+            messages: vec![Message {
+                role: MessageRole::User,
+                content: "list the files".to_string(),
+                name: None,
+                tool_calls: None,
+                cacheable: false,
+            }],
+            temperature: Some(0.1),
+            max_tokens: Some(256),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools,
+            tool_choice: None,
+        }
+    }
+
+    fn list_tool() -> serde_json::Value {
+        serde_json::json!({
+            "type": "function",
+            "function": { "name": "list", "parameters": { "type": "object" } }
+        })
+    }
+
+    /// Body of a recorded request
+    fn body(recorded: &str) -> serde_json::Value {
+        serde_json::from_str(recorded.split_once('\n').unwrap().1).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_complete_maps_tool_calls_and_usage() {
+        let reply = serde_json::json!({
+            "model": "llama3.2",
+            "created_at": "2024-07-22T20:33:28.123Z",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [{ "function": { "name": "list", "arguments": { "path": "src" } } }]
+            },
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 26,
+            "eval_count": 12
+        });
+        let (base_url, requests) = mock_ollama(vec![(200, reply.to_string())]).await;
+
+        // A trailing `/v1` (OpenAI-compatible URL) still reaches the native API
+        let response = provider(&format!("{}/v1/", base_url))
+            .complete(&request(Some(vec![list_tool()])))
+            .await
+            .unwrap();
+
+        let choice = &response.choices[0];
+        assert_eq!(
+            choice.normalized_finish_reason(),
+            Some(FinishReason::ToolCalls)
+        );
+        let calls = choice.message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_0");
+        assert_eq!(calls[0].function.name, "list");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&calls[0].function.arguments).unwrap(),
+            serde_json::json!({ "path": "src" })
+        );
+        let usage = response.usage.unwrap();
+        assert_eq!((usage.prompt_tokens, usage.completion_tokens), (26, 12));
+        assert_eq!(response.created, 1721680408);
+
+        let requests = requests.lock().await;
+        assert!(requests[0].starts_with("POST /api/chat "));
+        let sent = body(&requests[0]);
+        assert_eq!(sent["stream"], false);
+        assert_eq!(sent["options"]["num_predict"], 256);
+        assert_eq!(sent["tools"][0]["function"]["name"], "list");
+    }
+
+    #[derive(Default)]
+    struct Collect {
+        chunks: std::sync::Mutex<Vec<StreamChunk>>,
+        complete: std::sync::Mutex<Option<CompletionResponse>>,
+    }
+
+    #[async_trait::async_trait]
+    impl StreamHandler for Collect {
+        async fn on_chunk(&self, chunk: &StreamChunk) -> Result<(), ProviderError> {
+            self.chunks.lock().unwrap().push(chunk.clone());
+            Ok(())
+        }
+
+        async fn on_complete(&self, response: &CompletionResponse) -> Result<(), ProviderError> {
+            *self.complete.lock().unwrap() = Some(response.clone());
+            Ok(())
+        }
+
+        async fn on_error(&self, _error: &ProviderError) {}
+    }
+
+    #[tokio::test]
+    async fn test_complete_streaming_parses_ndjson() {
+        let lines = [
+            serde_json::json!({"model": "llama3.2", "created_at": "2024-07-22T20:33:28Z", "message": {"role": "assistant", "content": "Hel"}, "done": false}),
+            serde_json::json!({"model": "llama3.2", "created_at": "2024-07-22T20:33:28Z", "message": {"role": "assistant", "content": "lo"}, "done": false}),
+            serde_json::json!({"model": "llama3.2", "created_at": "2024-07-22T20:33:29Z", "message": {"role": "assistant", "content": ""}, "done": true, "done_reason": "length", "prompt_eval_count": 5, "eval_count": 2}),
+        ];
+        let ndjson: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        let (base_url, requests) = mock_ollama(vec![(200, ndjson)]).await;
+
+        let collect = Arc::new(Collect::default());
+        let handler: Arc<dyn StreamHandler> = collect.clone();
+        provider(&base_url)
+            .complete_streaming(&request(None), &handler)
+            .await
+            .unwrap();
+
+        let chunks = collect.chunks.lock().unwrap().clone();
+        let text: String = chunks
+            .iter()
+            .filter_map(|c| c.choices[0].delta.as_ref())
+            .map(|d| d.content.as_str())
+            .collect();
+        assert_eq!(text, "Hello");
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].choices[0].finish_reason.is_none());
+        assert_eq!(
+            chunks[2].choices[0].normalized_finish_reason(),
+            Some(FinishReason::Length)
+        );
+
+        let complete = collect.complete.lock().unwrap().clone().unwrap();
+        assert_eq!(complete.choices[0].message.content, "Hello");
+        assert!(complete.choices[0].is_truncated());
+        assert_eq!(complete.usage.unwrap().total_tokens, 7);
+        assert_eq!(body(&requests.lock().await[0])["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_tools_dropped_for_models_without_support() {
+        let reply = serde_json::json!({
+            "model": "gemma",
+            "message": {"role": "assistant", "content": "no tools here"},
+            "done": true,
+            "done_reason": "stop"
+        });
+        let (base_url, requests) = mock_ollama(vec![
+            (
+                400,
+                serde_json::json!({"error": "registry.ollama.ai/library/gemma does not support tools"})
+                    .to_string(),
+            ),
+            (200, reply.to_string()),
+        ])
+        .await;
+
+        let response = provider(&base_url)
+            .complete(&request(Some(vec![list_tool()])))
+            .await
+            .unwrap();
+        assert_eq!(response.choices[0].message.content, "no tools here");
+
+        let requests = requests.lock().await;
+        assert_eq!(requests.len(), 2);
+        assert!(body(&requests[0]).get("tools").is_some());
+        assert!(body(&requests[1]).get("tools").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_models_uses_tags_endpoint() {
+        let tags = serde_json::json!({
+            "models": [
+                {"name": "llama3.2:latest", "modified_at": "2024-07-22T20:33:28Z", "size": 1},
+                {"name": "qwen2.5:7b", "size": 2}
+            ]
+        });
+        let (base_url, requests) = mock_ollama(vec![(200, tags.to_string())]).await;
+        let provider = provider(&base_url);
+
+        let models = provider.list_models().await.unwrap();
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["llama3.2:latest", "qwen2.5:7b"]);
+        assert!(requests.lock().await[0].starts_with("GET /api/tags "));
+        assert!(provider.is_model_available("qwen2.5:7b").await);
+        assert!(provider.is_model_available("llama3.2").await);
+        assert!(!provider.is_model_available("mistral").await);
+    }
+}
@@ -19,6 +19,8 @@ pub struct OpenAiProvider {
     config: ProviderConfig,
     client: Client,
     token_counter: Arc<dyn TokenCounter>,
+    retry_policy: RetryPolicy,
+    model_cache: ModelCache,
 }
 
 impl std::fmt::Debug for OpenAiProvider {
@@ -37,14 +39,29 @@ impl OpenAiProvider {
             .timeout(std::time::Duration::from_millis(config.timeout_ms))
             .build()
             .expect("Failed to create HTTP client");
+        let retry_policy = RetryPolicy::from_config(&config);
 
         Self {
             config,
             client,
             token_counter,
+            retry_policy,
+            model_cache: ModelCache::default(),
         }
     }
 
+    /// Override the retry policy derived from `ProviderConfig.max_retries`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Replace the model list cache (e.g. to change its TTL)
+    pub fn with_model_cache(mut self, model_cache: ModelCache) -> Self {
+        self.model_cache = model_cache;
+        self
+    }
+
     /// Get base URL for API calls
     fn get_base_url(&self) -> String {
         if let Some(url) = &self.config.base_url {
@@ -97,7 +114,8 @@ impl OpenAiProvider {
     fn apply_tools(&self, body: &mut serde_json::Value, request: &CompletionRequest) {
         if let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty()) {
             body["tools"] = serde_json::json!(tools);
-            body["tool_choice"] = serde_json::json!("auto");
+            let choice = request.tool_choice.as_ref().unwrap_or(&ToolChoice::Auto);
+            body["tool_choice"] = choice.to_openai();
         }
     }
 }
@@ -172,15 +190,15 @@ impl LlmProvider for OpenAiProvider {
         });
         self.apply_tools(&mut body, request);
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", self.get_auth_header())
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?;
+        let response = send_with_retry(
+            &self.retry_policy,
+            self.client
+                .post(&url)
+                .header("Authorization", self.get_auth_header())
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await?;
 
         let status = response.status();
         if status == StatusCode::UNAUTHORIZED {
@@ -188,7 +206,9 @@ impl LlmProvider for OpenAiProvider {
                 message: "Invalid API key".to_string(),
             });
         } else if status == StatusCode::TOO_MANY_REQUESTS {
-            return Err(ProviderError::RateLimited { retry_after: 60 });
+            return Err(ProviderError::RateLimited {
+                retry_after: retry_after_secs(response.headers()).unwrap_or(60),
+            });
         } else if status == StatusCode::BAD_REQUEST {
             let error: serde_json::Value = response
                 .json()
@@ -231,16 +251,22 @@ impl LlmProvider for OpenAiProvider {
         });
         self.apply_tools(&mut body, request);
 
-        let mut stream = self
-            .client
-            .post(&url)
-            .header("Authorization", self.get_auth_header())
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?
-            .bytes_stream();
+        let response = send_with_retry(
+            &self.retry_policy,
+            self.client
+                .post(&url)
+                .header("Authorization", self.get_auth_header())
+                .header("Content-Type", "application/json")
+                .json(&body),
+        )
+        .await?;
+        if !response.status().is_success() {
+            return Err(map_provider_error(
+                response.error_for_status().unwrap_err(),
+                "openai",
+            ));
+        }
+        let mut stream = response.bytes_stream();
 
         let mut full_response: Option<CompletionResponse> = None;
 
@@ -290,12 +316,25 @@ impl LlmProvider for OpenAiProvider {
             prompt_tokens: prompt_tokens as u32,
             completion_tokens: completion_tokens as u32,
             total_tokens: (prompt_tokens + completion_tokens) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         }
     }
 
     async fn is_model_available(&self, model: &str) -> bool {
-        // Simple check - in production, verify against model list
-        self.config.models.contains(&model.to_string())
+        if self.config.models.iter().any(|m| m == model) {
+            return true;
+        }
+        self.model_cache
+            .get_or_fetch(|| self.list_models())
+            .await
+            .is_some_and(|models| models.iter().any(|m| m.id == model))
+    }
+
+    async fn refresh_models(&self) -> Result<Vec<ModelInfo>, ProviderError> {
+        self.model_cache.invalidate();
+        let models = self.model_cache.store(self.list_models().await)?;
+        Ok(models.as_ref().clone())
     }
 
     fn config(&self) -> &ProviderConfig {
@@ -346,3 +385,54 @@ pub fn create_azure_config(
         max_retries: 3,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_request(tool_choice: Option<ToolChoice>) -> CompletionRequest {
+        CompletionRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: Some(vec![serde_json::json!({
+                "type": "function",
+                "function": { "name": "read", "parameters": { "type": "object" } }
+            })]),
+            tool_choice,
+        }
+    }
+
+    #[test]
+    fn test_apply_tools_maps_tool_choice() {
+        let config = create_openai_config("openai", "test-key", "gpt-4o");
+        let provider = OpenAiProvider::new(config, Arc::new(SimpleTokenCounter::new()));
+
+        let cases = [
+            (None, serde_json::json!("auto")),
+            (Some(ToolChoice::None), serde_json::json!("none")),
+            (
+                Some(ToolChoice::function("read")),
+                serde_json::json!({"type": "function", "function": {"name": "read"}}),
+            ),
+        ];
+        for (choice, expected) in cases {
+            let mut body = serde_json::json!({});
+            provider.apply_tools(&mut body, &tool_request(choice));
+            assert_eq!(body["tool_choice"], expected);
+        }
+
+        // 没有工具时不发送 tool_choice
+        let mut request = tool_request(Some(ToolChoice::function("read")));
+        request.tools = None;
+        let mut body = serde_json::json!({});
+        provider.apply_tools(&mut body, &request);
+        assert!(body.get("tool_choice").is_none());
+    }
+}
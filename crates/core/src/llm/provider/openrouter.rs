@@ -22,6 +22,8 @@ pub struct OpenRouterProvider {
     config: ProviderConfig,
     client: Client,
     token_counter: Arc<dyn TokenCounter>,
+    retry_policy: RetryPolicy,
+    model_cache: ModelCache,
     site_url: Option<String>,
     app_name: Option<String>,
 }
@@ -44,16 +46,31 @@ impl OpenRouterProvider {
             .timeout(std::time::Duration::from_millis(config.timeout_ms))
             .build()
             .expect("Failed to create HTTP client");
+        let retry_policy = RetryPolicy::from_config(&config);
 
         Self {
             config,
             client,
             token_counter,
+            retry_policy,
+            model_cache: ModelCache::default(),
             site_url: None,
             app_name: None,
         }
     }
 
+    /// Override the retry policy derived from `ProviderConfig.max_retries`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Replace the model list cache (e.g. to change its TTL)
+    pub fn with_model_cache(mut self, model_cache: ModelCache) -> Self {
+        self.model_cache = model_cache;
+        self
+    }
+
     /// Create OpenRouter provider with site info
     pub fn with_site_info(
         config: ProviderConfig,
@@ -136,10 +153,30 @@ impl OpenRouterProvider {
     fn apply_tools(&self, body: &mut serde_json::Value, request: &CompletionRequest) {
         if let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty()) {
             body["tools"] = serde_json::json!(tools);
-            body["tool_choice"] = serde_json::json!("auto");
+            let choice = match request.tool_choice.clone().unwrap_or(ToolChoice::Auto) {
+                ToolChoice::Function { name }
+                    if !Self::supports_forced_tool_choice(&request.model) =>
+                {
+                    tracing::warn!(
+                        model = %request.model,
+                        tool = %name,
+                        "Model cannot force a specific tool, falling back to tool_choice=auto"
+                    );
+                    ToolChoice::Auto
+                }
+                choice => choice,
+            };
+            body["tool_choice"] = choice.to_openai();
         }
     }
 
+    /// 上游是否支持强制调用指定工具（OpenAI / Anthropic 模型）
+    fn supports_forced_tool_choice(model: &str) -> bool {
+        ["openai/", "anthropic/"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix))
+    }
+
     /// Parse OpenRouter response to CompletionResponse
     fn parse_response(
         &self,
@@ -182,6 +219,8 @@ impl OpenRouterProvider {
             prompt_tokens: u["prompt_tokens"].as_u64().unwrap_or(0) as u32,
             completion_tokens: u["completion_tokens"].as_u64().unwrap_or(0) as u32,
             total_tokens: u["total_tokens"].as_u64().unwrap_or(0) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         });
 
         let finish_reason = first_choice
@@ -220,6 +259,7 @@ impl OpenRouterProvider {
                     content: content.to_string(),
                     name: None,
                     tool_calls,
+                    cacheable: false,
                 },
                 finish_reason: Some(finish_reason),
                 logprobs: None,
@@ -336,11 +376,7 @@ impl LlmProvider for OpenRouterProvider {
             req_builder = req_builder.header(key, value);
         }
 
-        let response = req_builder
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?;
+        let response = send_with_retry(&self.retry_policy, req_builder.json(&body)).await?;
 
         let status = response.status();
 
@@ -351,7 +387,9 @@ impl LlmProvider for OpenRouterProvider {
         }
 
         if status.as_u16() == 429 {
-            return Err(ProviderError::RateLimited { retry_after: 60 });
+            return Err(ProviderError::RateLimited {
+                retry_after: retry_after_secs(response.headers()).unwrap_or(60),
+            });
         }
 
         if !status.is_success() {
@@ -406,11 +444,7 @@ impl LlmProvider for OpenRouterProvider {
             req_builder = req_builder.header(key, value);
         }
 
-        let response = req_builder
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ProviderError::Network { source: e })?;
+        let response = send_with_retry(&self.retry_policy, req_builder.json(&body)).await?;
 
         let status = response.status();
 
@@ -500,6 +534,7 @@ impl LlmProvider for OpenRouterProvider {
                                             content: chunk_content.to_string(),
                                             name: None,
                                             tool_calls: None,
+                                            cacheable: false,
                                         }),
                                         finish_reason: None,
                                     }],
@@ -533,6 +568,7 @@ impl LlmProvider for OpenRouterProvider {
                                                 content: chunk_content.to_string(),
                                                 name: None,
                                                 tool_calls: None,
+                                                cacheable: false,
                                             },
                                             finish_reason: None,
                                             logprobs: None,
@@ -571,12 +607,14 @@ impl LlmProvider for OpenRouterProvider {
             prompt_tokens: prompt_tokens as u32,
             completion_tokens: completion_tokens as u32,
             total_tokens: (prompt_tokens + completion_tokens) as u32,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
         }
     }
 
     async fn is_model_available(&self, model: &str) -> bool {
-        // Try to fetch from models list
-        if let Ok(models) = self.list_models().await {
+        // Try the (cached) models list
+        if let Some(models) = self.model_cache.get_or_fetch(|| self.list_models()).await {
             return models.iter().any(|m| m.id == model);
         }
 
@@ -584,6 +622,12 @@ impl LlmProvider for OpenRouterProvider {
         model.contains('/') || model.contains("-")
     }
 
+    async fn refresh_models(&self) -> Result<Vec<ModelInfo>, ProviderError> {
+        self.model_cache.invalidate();
+        let models = self.model_cache.store(self.list_models().await)?;
+        Ok(models.as_ref().clone())
+    }
+
     fn config(&self) -> &ProviderConfig {
         &self.config
     }
@@ -665,4 +709,46 @@ mod tests {
         assert_eq!(provider.site_url, Some("https://example.com".to_string()));
         assert_eq!(provider.app_name, Some("TestApp".to_string()));
     }
+
+    #[test]
+    fn test_apply_tools_degrades_forced_choice_to_auto() {
+        let config = create_openrouter_config("test-key".to_string(), None, None, None);
+        let provider = OpenRouterProvider::new(config, Arc::new(SimpleTokenCounter::new()));
+        let request = |model: &str, tool_choice| CompletionRequest {
+            model: model.to_string(),
+            messages: vec![],
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: Some(vec![serde_json::json!({
+                "type": "function",
+                "function": { "name": "read", "parameters": { "type": "object" } }
+            })]),
+            tool_choice,
+        };
+
+        let mut body = serde_json::json!({});
+        let forced = Some(ToolChoice::function("read"));
+        provider.apply_tools(
+            &mut body,
+            &request("anthropic/claude-3-haiku", forced.clone()),
+        );
+        assert_eq!(
+            body["tool_choice"],
+            serde_json::json!({"type": "function", "function": {"name": "read"}})
+        );
+
+        provider.apply_tools(&mut body, &request("meta-llama/llama-3-70b", forced));
+        assert_eq!(body["tool_choice"], serde_json::json!("auto"));
+
+        provider.apply_tools(
+            &mut body,
+            &request("meta-llama/llama-3-70b", Some(ToolChoice::None)),
+        );
+        assert_eq!(body["tool_choice"], serde_json::json!("none"));
+    }
 }
@@ -0,0 +1,234 @@
+//! Model pricing and cost estimation
+//!
+//! Rates are USD per 1K tokens, keyed by model id. Built-in defaults cover the
+//! common OpenAI/Anthropic/MiniMax models; `llm.pricing` in the config adds or
+//! overrides entries so prices can be kept current without recompiling.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::Usage;
+
+/// Per-model token rates (USD per 1K tokens)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelRate {
+    pub input_per_1k: f64,
+    pub output_per_1k: f64,
+}
+
+impl ModelRate {
+    pub const fn new(input_per_1k: f64, output_per_1k: f64) -> Self {
+        Self {
+            input_per_1k,
+            output_per_1k,
+        }
+    }
+}
+
+/// Estimated cost of a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub input_cost: f64,
+    pub output_cost: f64,
+    /// input_cost + output_cost, in USD
+    pub total_cost: f64,
+}
+
+/// Built-in rates as of early 2025
+const BUILTIN_RATES: &[(&str, ModelRate)] = &[
+    ("gpt-4o", ModelRate::new(0.0025, 0.01)),
+    ("gpt-4o-mini", ModelRate::new(0.00015, 0.0006)),
+    ("gpt-4-turbo", ModelRate::new(0.01, 0.03)),
+    ("gpt-4", ModelRate::new(0.03, 0.06)),
+    ("gpt-3.5-turbo", ModelRate::new(0.0005, 0.0015)),
+    ("claude-opus-4-20250514", ModelRate::new(0.015, 0.075)),
+    ("claude-sonnet-4-20250514", ModelRate::new(0.003, 0.015)),
+    ("claude-3-5-sonnet-20241022", ModelRate::new(0.003, 0.015)),
+    ("claude-3-5-haiku-20241022", ModelRate::new(0.0008, 0.004)),
+    ("claude-3-opus-20240229", ModelRate::new(0.015, 0.075)),
+    ("claude-3-haiku-20240307", ModelRate::new(0.00025, 0.00125)),
+    ("abab6.5s-chat", ModelRate::new(0.001, 0.001)),
+    ("abab6.5-chat", ModelRate::new(0.004, 0.004)),
+    ("abab5.5-chat", ModelRate::new(0.002, 0.002)),
+];
+
+/// Pricing table keyed by model id
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    rates: HashMap<String, ModelRate>,
+}
+
+impl ModelPricing {
+    /// Empty table; every lookup returns `None`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table with the built-in default rates
+    pub fn builtin() -> Self {
+        Self {
+            rates: BUILTIN_RATES
+                .iter()
+                .map(|(model, rate)| (model.to_string(), *rate))
+                .collect(),
+        }
+    }
+
+    /// Built-in rates overridden by `llm.pricing` from config
+    pub fn from_config(config: &crate::NdcConfig) -> Self {
+        let mut pricing = Self::builtin();
+        if let Some(llm) = config.llm.as_ref() {
+            pricing.merge(llm.pricing.clone());
+        }
+        pricing
+    }
+
+    pub fn with_rate(mut self, model: impl Into<String>, rate: ModelRate) -> Self {
+        self.rates.insert(model.into(), rate);
+        self
+    }
+
+    /// Add or replace rates
+    pub fn merge(&mut self, rates: HashMap<String, ModelRate>) {
+        self.rates.extend(rates);
+    }
+
+    /// Rate for a model; a `provider/model` id falls back to the bare model
+    pub fn rate(&self, model: &str) -> Option<ModelRate> {
+        self.rates.get(model).copied().or_else(|| {
+            model
+                .rsplit_once('/')
+                .and_then(|(_, bare)| self.rates.get(bare).copied())
+        })
+    }
+
+    /// Cost of `usage` on `model`; `None` for unknown models
+    pub fn estimate(&self, model: &str, usage: &Usage) -> Option<CostEstimate> {
+        let rate = self.rate(model)?;
+        let input_cost = usage.prompt_tokens as f64 / 1000.0 * rate.input_per_1k;
+        let output_cost = usage.completion_tokens as f64 / 1000.0 * rate.output_per_1k;
+        Some(CostEstimate {
+            model: model.to_string(),
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            input_cost,
+            output_cost,
+            total_cost: input_cost + output_cost,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: u32, completion: u32) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn test_estimate_for_known_model() {
+        let estimate = ModelPricing::builtin()
+            .estimate("gpt-4o", &usage(2000, 500))
+            .unwrap();
+
+        assert!((estimate.input_cost - 0.005).abs() < 1e-12);
+        assert!((estimate.output_cost - 0.005).abs() < 1e-12);
+        assert!((estimate.total_cost - 0.01).abs() < 1e-12);
+        assert_eq!(estimate.prompt_tokens, 2000);
+    }
+
+    #[test]
+    fn test_unknown_model_returns_none() {
+        let pricing = ModelPricing::builtin();
+        assert!(
+            pricing
+                .estimate("mystery-model-9000", &usage(10, 10))
+                .is_none()
+        );
+        assert!(
+            ModelPricing::new()
+                .estimate("gpt-4o", &usage(10, 10))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_config_overrides_and_provider_prefix() {
+        let mut config = crate::NdcConfig::default();
+        let mut llm = crate::YamlLlmConfig::default();
+        llm.pricing
+            .insert("gpt-4o".to_string(), ModelRate::new(1.0, 2.0));
+        llm.pricing
+            .insert("local-llama".to_string(), ModelRate::new(0.0, 0.0));
+        config.llm = Some(llm);
+
+        let pricing = ModelPricing::from_config(&config);
+        let estimate = pricing
+            .estimate("openai/gpt-4o", &usage(1000, 1000))
+            .unwrap();
+        assert!((estimate.total_cost - 3.0).abs() < 1e-12);
+        assert_eq!(pricing.rate("local-llama"), Some(ModelRate::new(0.0, 0.0)));
+        assert!(pricing.rate("claude-3-haiku-20240307").is_some());
+    }
+
+    #[test]
+    fn test_provider_estimate_cost_uses_estimated_tokens() {
+        use crate::llm::provider::{
+            CompletionRequest, LlmProvider, Message, MessageRole, OpenAiProvider,
+            SimpleTokenCounter, create_openai_config,
+        };
+        use std::sync::Arc;
+
+        let provider = OpenAiProvider::new(
+            create_openai_config("openai", "sk-test", "gpt-4o"),
+            Arc::new(SimpleTokenCounter::new()),
+        );
+        let request = CompletionRequest {
+            model: String::new(),
+            messages: vec![Message {
+                role: MessageRole::User,
+                content: "hello there".to_string(),
+                name: None,
+                tool_calls: None,
+                cacheable: false,
+            }],
+            temperature: None,
+            max_tokens: Some(1000),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+        };
+        let pricing = ModelPricing::builtin();
+
+        let estimate = provider.estimate_cost(&request, None, &pricing).unwrap();
+        assert_eq!(estimate.model, "gpt-4o");
+        assert_eq!(estimate.completion_tokens, 1000);
+        assert!((estimate.output_cost - 0.01).abs() < 1e-12);
+        assert!(estimate.total_cost > estimate.output_cost);
+
+        let actual = provider
+            .estimate_cost(&request, Some(&usage(0, 100)), &pricing)
+            .unwrap();
+        assert!((actual.total_cost - 0.001).abs() < 1e-12);
+
+        let unknown = CompletionRequest {
+            model: "mystery".to_string(),
+            ..request
+        };
+        assert!(provider.estimate_cost(&unknown, None, &pricing).is_none());
+    }
+}
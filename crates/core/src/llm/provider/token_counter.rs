@@ -157,12 +157,14 @@ mod tests {
                 content: "You are a helpful assistant.".to_string(),
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             },
             Message {
                 role: MessageRole::User,
                 content: "Hello!".to_string(),
                 name: None,
                 tool_calls: None,
+                cacheable: false,
             },
         ];
 
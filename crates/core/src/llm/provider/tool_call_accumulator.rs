@@ -0,0 +1,199 @@
+//! Tool Call Accumulator - rebuild tool calls from streamed deltas
+//!
+//! Streaming APIs send a tool call in fragments: the first carries the id and
+//! function name, later ones append pieces of the JSON argument string.
+//! Several calls can be interleaved, distinguished by `index`. The accumulator
+//! merges fragments from any provider into complete `ToolCall`s.
+
+use super::{FinishReason, StreamChunk, ToolCall, ToolCallFunction};
+
+#[derive(Debug, Clone, Default)]
+struct PartialToolCall {
+    index: Option<usize>,
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Merges streamed `ToolCall` fragments into complete calls
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallAccumulator {
+    /// Calls in order of first appearance
+    calls: Vec<PartialToolCall>,
+    finished: bool,
+}
+
+impl ToolCallAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge every tool-call fragment in a chunk
+    ///
+    /// A `FinishReason::ToolCalls` finish marks the calls as complete.
+    pub fn push_chunk(&mut self, chunk: &StreamChunk) {
+        for choice in &chunk.choices {
+            if let Some(calls) = choice.delta.as_ref().and_then(|d| d.tool_calls.as_ref()) {
+                for fragment in calls {
+                    self.push(fragment);
+                }
+            }
+            if choice.normalized_finish_reason() == Some(FinishReason::ToolCalls) {
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Merge one fragment
+    ///
+    /// Fragments are matched by `index`, then by `id`. A fragment with neither
+    /// continues the most recent call.
+    pub fn push(&mut self, fragment: &ToolCall) {
+        let position = match fragment.index {
+            Some(index) => self.calls.iter().position(|c| c.index == Some(index)),
+            None if !fragment.id.is_empty() => self.calls.iter().position(|c| c.id == fragment.id),
+            None => self.calls.len().checked_sub(1),
+        };
+        let call = match position {
+            Some(position) => &mut self.calls[position],
+            None => {
+                self.calls.push(PartialToolCall {
+                    index: fragment.index,
+                    ..PartialToolCall::default()
+                });
+                self.calls.last_mut().expect("just pushed")
+            }
+        };
+
+        if call.id.is_empty() {
+            call.id.clone_from(&fragment.id);
+        }
+        if call.name.is_empty() {
+            call.name.clone_from(&fragment.function.name);
+        }
+        call.arguments.push_str(&fragment.function.arguments);
+    }
+
+    /// Whether a `finish_reason` of `tool_calls` has been seen
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Calls accumulated so far; arguments may still be partial
+    pub fn calls(&self) -> Vec<ToolCall> {
+        self.calls
+            .iter()
+            .map(|call| ToolCall {
+                id: call.id.clone(),
+                index: call.index,
+                function: ToolCallFunction {
+                    name: call.name.clone(),
+                    // Calls without arguments still need a valid JSON object
+                    arguments: if call.arguments.trim().is_empty() {
+                        "{}".to_string()
+                    } else {
+                        call.arguments.clone()
+                    },
+                },
+            })
+            .collect()
+    }
+
+    /// Complete calls, or `None` until the stream reports `tool_calls`
+    pub fn finish(&self) -> Option<Vec<ToolCall>> {
+        self.finished.then(|| self.calls())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(data: serde_json::Value) -> StreamChunk {
+        serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": "gpt-4o",
+            "choices": [data]
+        }))
+        .unwrap()
+    }
+
+    fn delta(calls: serde_json::Value) -> StreamChunk {
+        chunk(serde_json::json!({
+            "index": 0,
+            "delta": { "tool_calls": calls },
+            "finish_reason": null
+        }))
+    }
+
+    #[test]
+    fn test_interleaved_fragments_rebuild_valid_json() {
+        let mut acc = ToolCallAccumulator::new();
+        acc.push_chunk(&chunk(serde_json::json!({
+            "index": 0,
+            "delta": { "role": "assistant", "content": null },
+            "finish_reason": null
+        })));
+        acc.push_chunk(&delta(serde_json::json!([
+            { "index": 0, "id": "call_a", "type": "function",
+              "function": { "name": "read", "arguments": "" } }
+        ])));
+        acc.push_chunk(&delta(serde_json::json!([
+            { "index": 0, "function": { "arguments": "{\"pa" } },
+            { "index": 1, "id": "call_b", "type": "function",
+              "function": { "name": "grep", "arguments": "{\"pattern\":" } }
+        ])));
+        acc.push_chunk(&delta(serde_json::json!([
+            { "index": 1, "function": { "arguments": " \"fn main\"}" } },
+            { "index": 0, "function": { "arguments": "th\": \"src/lib.rs\"}" } }
+        ])));
+        assert!(acc.finish().is_none());
+
+        acc.push_chunk(&chunk(serde_json::json!({
+            "index": 0,
+            "delta": {},
+            "finish_reason": "tool_calls"
+        })));
+        let calls = acc.finish().unwrap();
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_a");
+        assert_eq!(calls[0].function.name, "read");
+        let args: serde_json::Value = serde_json::from_str(&calls[0].function.arguments).unwrap();
+        assert_eq!(args["path"], "src/lib.rs");
+        assert_eq!(calls[1].id, "call_b");
+        let args: serde_json::Value = serde_json::from_str(&calls[1].function.arguments).unwrap();
+        assert_eq!(args["pattern"], "fn main");
+    }
+
+    #[test]
+    fn test_fragments_without_index() {
+        let mut acc = ToolCallAccumulator::new();
+        let fragment = |id: &str, name: &str, args: &str| ToolCall {
+            id: id.to_string(),
+            index: None,
+            function: ToolCallFunction {
+                name: name.to_string(),
+                arguments: args.to_string(),
+            },
+        };
+
+        acc.push(&fragment("toolu_1", "list", ""));
+        acc.push(&fragment("", "", "{\"path\""));
+        acc.push(&fragment("", "", ": \".\"}"));
+        acc.push(&fragment("toolu_2", "status", ""));
+
+        let calls = acc.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].function.arguments, "{\"path\": \".\"}");
+        assert!(serde_json::from_str::<serde_json::Value>(&calls[0].function.arguments).is_ok());
+        assert_eq!(calls[1].function.arguments, "{}");
+        assert!(!acc.is_finished());
+    }
+}
@@ -1,8 +1,22 @@
 //! Memory types and stability levels
 
+mod context_builder;
+mod embedding;
 mod invariant;
+mod simhash;
+mod vector_search;
 mod working_memory;
 
+pub use context_builder::{
+    BuiltContext, ContextBuilder, ContextConfig, DEFAULT_CONTEXT_TOKENS, render_memory,
+};
+pub use embedding::{
+    DEFAULT_EMBEDDING_DIMENSIONS, DEFAULT_EMBEDDING_MODEL, EmbedError, Embedder, HashEmbedder,
+    OpenAiEmbedder, check_dimensions, embedder_from_env,
+};
+pub use simhash::SimHashIndex;
+pub use vector_search::{SearchMode, VectorSearch};
+
 // Re-export working memory types, excluding duplicates with invariant module
 pub use working_memory::{
     AbstractHistory, ApiKind, ApiSurface, FailurePattern, LlmContext, RawCurrent, StepContext,
@@ -49,6 +63,23 @@ pub enum MemoryStability {
     Canonical = 3,
 }
 
+impl std::str::FromStr for MemoryStability {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ephemeral" | "0" => Ok(MemoryStability::Ephemeral),
+            "derived" | "1" => Ok(MemoryStability::Derived),
+            "verified" | "2" => Ok(MemoryStability::Verified),
+            "canonical" | "3" => Ok(MemoryStability::Canonical),
+            other => Err(format!(
+                "unknown stability '{}' (expected ephemeral, derived, verified or canonical)",
+                other
+            )),
+        }
+    }
+}
+
 /// Memory content types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MemoryContent {
@@ -61,6 +92,49 @@ pub enum MemoryContent {
     General { text: String, metadata: String },
 }
 
+impl MemoryContent {
+    /// One-line description of the content
+    pub fn summary(&self) -> String {
+        match self {
+            MemoryContent::Code(code) => format!("{}: {}", code.file_path, code.summary),
+            MemoryContent::ProjectStructure(structure) => format!(
+                "project {} (dirs: {}; key files: {})",
+                structure.root_path,
+                structure.directories.join(", "),
+                structure.important_files.join(", ")
+            ),
+            MemoryContent::ApiDocumentation(api) => {
+                format!("{} {} -> {}", api.method, api.endpoint, api.return_type)
+            }
+            MemoryContent::Decision(decision) => {
+                format!("decision: {} ({})", decision.decision, decision.rationale)
+            }
+            MemoryContent::ErrorSolution(fix) => {
+                format!("error: {} -> fix: {}", fix.error, fix.solution)
+            }
+            MemoryContent::TestResult(test) => format!(
+                "test {} {}",
+                test.test_name,
+                if test.passed { "passed" } else { "failed" }
+            ),
+            MemoryContent::General { text, .. } => text.clone(),
+        }
+    }
+
+    /// Type name used by `MemoryQuery::memory_type`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            MemoryContent::Code(_) => "code",
+            MemoryContent::ProjectStructure(_) => "project_structure",
+            MemoryContent::ApiDocumentation(_) => "api_documentation",
+            MemoryContent::Decision(_) => "decision",
+            MemoryContent::ErrorSolution(_) => "error_solution",
+            MemoryContent::TestResult(_) => "test_result",
+            MemoryContent::General { .. } => "general",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeKnowledge {
     pub file_path: String,
@@ -148,6 +222,19 @@ pub struct MemoryMetadata {
     pub version: u64,
     pub modified_at: Option<DateTime<Utc>>,
     pub tags: Vec<String>,
+    /// Expiry time (`created_at + ttl`); only honoured for Ephemeral memories
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl MemoryMetadata {
+    /// Whether the memory has expired at `now`
+    ///
+    /// Only Ephemeral memories expire; higher stability levels are kept forever.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.stability == MemoryStability::Ephemeral
+            && self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
 }
 
 // Use TaskId from task module (re-exported from lib.rs)
@@ -231,6 +318,16 @@ impl MemoryEntry {
     pub fn id(&self) -> MemoryId {
         self.id
     }
+
+    /// Set expiry to `created_at + ttl`
+    pub fn with_ttl(mut self, ttl: chrono::Duration) -> Self {
+        self.metadata.expires_at = Some(self.metadata.created_at + ttl);
+        self
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.metadata.is_expired(now)
+    }
 }
 
 /// Memory query for filtering memories
@@ -256,6 +353,160 @@ pub struct MemoryQuery {
 
     /// Filter by maximum stability (inclusive)
     pub max_stability: Option<MemoryStability>,
+
+    /// Created at or after this time (inclusive)
+    #[serde(default)]
+    pub created_after: Option<DateTime<Utc>>,
+
+    /// Created before this time (exclusive)
+    #[serde(default)]
+    pub created_before: Option<DateTime<Utc>>,
+
+    /// Result ordering
+    #[serde(default)]
+    pub sort_by: MemorySort,
+}
+
+/// Ordering of memory query results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemorySort {
+    /// Best text match first, then most stable and newest
+    #[default]
+    Relevance,
+    /// Newest first
+    Newest,
+    /// Oldest first
+    Oldest,
+    /// Most stable first, newest first within a level
+    Stability,
+}
+
+impl MemoryQuery {
+    /// Whether the entry passes every filter that is set
+    ///
+    /// Text matches the content summary or a tag, case-insensitively. Type
+    /// names ignore case, `_` and `-`, and also accept the content struct
+    /// name (e.g. `CodeKnowledge`). All listed tags must be present.
+    pub fn matches(&self, entry: &MemoryEntry) -> bool {
+        let stability = entry.metadata.stability;
+        if self.stability.is_some_and(|s| s != stability)
+            || self.min_stability.is_some_and(|s| stability < s)
+            || self.max_stability.is_some_and(|s| stability > s)
+        {
+            return false;
+        }
+        if self
+            .source_task
+            .is_some_and(|task| task != entry.metadata.source_task)
+        {
+            return false;
+        }
+        let created_at = entry.metadata.created_at;
+        if self.created_after.is_some_and(|after| created_at < after)
+            || self
+                .created_before
+                .is_some_and(|before| created_at >= before)
+        {
+            return false;
+        }
+        if let Some(memory_type) = &self.memory_type {
+            let wanted = normalize_type_name(memory_type);
+            let aliases = match &entry.content {
+                MemoryContent::Code(_) => "codeknowledge",
+                MemoryContent::ApiDocumentation(_) => "apidoc",
+                MemoryContent::Decision(_) => "decisionrecord",
+                _ => "",
+            };
+            if wanted != normalize_type_name(entry.content.type_name()) && wanted != aliases {
+                return false;
+            }
+        }
+        if !self.tags.iter().all(|tag| {
+            entry
+                .metadata
+                .tags
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(tag))
+        }) {
+            return false;
+        }
+        match self.query.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => {
+                let text = text.to_lowercase();
+                entry.content.summary().to_lowercase().contains(&text)
+                    || entry
+                        .metadata
+                        .tags
+                        .iter()
+                        .any(|t| t.to_lowercase().contains(&text))
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether any filter besides the text query is set
+    pub fn has_filters(&self) -> bool {
+        self.stability.is_some()
+            || self.memory_type.is_some()
+            || !self.tags.is_empty()
+            || self.source_task.is_some()
+            || self.min_stability.is_some()
+            || self.max_stability.is_some()
+            || self.created_after.is_some()
+            || self.created_before.is_some()
+    }
+
+    /// Order matching entries according to `sort_by`
+    pub fn sort(&self, entries: &mut [MemoryEntry]) {
+        let by_stability = |a: &MemoryEntry, b: &MemoryEntry| {
+            b.metadata
+                .stability
+                .cmp(&a.metadata.stability)
+                .then(b.metadata.created_at.cmp(&a.metadata.created_at))
+        };
+        match self.sort_by {
+            MemorySort::Relevance => entries.sort_by(|a, b| {
+                self.text_score(b)
+                    .cmp(&self.text_score(a))
+                    .then_with(|| by_stability(a, b))
+            }),
+            MemorySort::Newest => entries.sort_by_key(|e| std::cmp::Reverse(e.metadata.created_at)),
+            MemorySort::Oldest => entries.sort_by_key(|e| e.metadata.created_at),
+            MemorySort::Stability => entries.sort_by(by_stability),
+        }
+    }
+
+    /// 2 when the text matches the content, 1 for a tag-only match
+    fn text_score(&self, entry: &MemoryEntry) -> u8 {
+        let Some(text) = self
+            .query
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        else {
+            return 0;
+        };
+        let text = text.to_lowercase();
+        if entry.content.summary().to_lowercase().contains(&text) {
+            2
+        } else if entry
+            .metadata
+            .tags
+            .iter()
+            .any(|t| t.to_lowercase().contains(&text))
+        {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+fn normalize_type_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
 }
 
 /// A memory with a similarity score (for vector search results)
@@ -265,5 +516,53 @@ pub struct ScoredMemory {
     pub score: f32,
 }
 
+/// Cosine similarity mapped to 0.0–1.0
+///
+/// Negative similarity is clamped to 0.0 so an orthogonal or opposite
+/// vector scores 0.0 and an identical direction scores 1.0. Mismatched
+/// dimensions or zero vectors score 0.0.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    (dot / (norm_a.sqrt() * norm_b.sqrt())).clamp(0.0, 1.0)
+}
+
+/// Exact cosine search over stored embeddings, best match first
+///
+/// Memories without an embedding are skipped; only the `top_k` hits are cloned.
+pub fn search_cosine<'a>(
+    memories: impl IntoIterator<Item = &'a MemoryEntry>,
+    query: &[f32],
+    top_k: usize,
+) -> Vec<ScoredMemory> {
+    let mut scored: Vec<(f32, &MemoryEntry)> = memories
+        .into_iter()
+        .filter(|memory| !memory.embedding.is_empty())
+        .map(|memory| (cosine_similarity(&memory.embedding, query), memory))
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(top_k);
+    scored
+        .into_iter()
+        .map(|(score, memory)| ScoredMemory {
+            score,
+            memory: memory.clone(),
+        })
+        .collect()
+}
+
 /// Type alias for Memory (used by persistence layer)
 pub type Memory = MemoryEntry;
@@ -0,0 +1,250 @@
+//! Context Builder - token-budgeted memory context
+//!
+//! Assembles scored memories into a prompt section without exceeding a
+//! token budget. Candidates are ranked by stability first
+//! (Canonical > Verified > Derived > Ephemeral), then by score, and are
+//! added greedily until the next entry no longer fits. Lower-stability
+//! memories are therefore the first to be dropped when space is tight.
+
+use std::sync::Arc;
+
+use super::{MemoryEntry, ScoredMemory};
+use crate::llm::provider::TokenCounter;
+
+/// Header placed above the assembled memories
+const CONTEXT_HEADER: &str = "## Relevant memory";
+
+/// Context budget used when none is configured, in tokens
+pub const DEFAULT_CONTEXT_TOKENS: usize = 2000;
+
+/// Context assembly configuration
+#[derive(Clone)]
+pub struct ContextConfig {
+    /// Upper bound for the assembled context, in tokens
+    pub max_tokens: usize,
+    /// Model name passed to the token counter
+    pub model: String,
+    pub token_counter: Arc<dyn TokenCounter>,
+}
+
+impl ContextConfig {
+    pub fn new(max_tokens: usize, token_counter: Arc<dyn TokenCounter>) -> Self {
+        Self {
+            max_tokens,
+            model: "default".to_string(),
+            token_counter,
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+}
+
+impl std::fmt::Debug for ContextConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextConfig")
+            .field("max_tokens", &self.max_tokens)
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+/// Result of `ContextBuilder::build`
+#[derive(Debug, Clone, Default)]
+pub struct BuiltContext {
+    /// Assembled context text (empty when nothing fits)
+    pub text: String,
+    /// Token count of `text`
+    pub tokens: usize,
+    /// Memories included, in the order they appear in `text`
+    pub included: Vec<ScoredMemory>,
+    /// Memories left out because the budget was exhausted
+    pub dropped: Vec<ScoredMemory>,
+}
+
+/// Builds token-budgeted memory context
+#[derive(Debug, Clone)]
+pub struct ContextBuilder {
+    config: ContextConfig,
+}
+
+impl ContextBuilder {
+    pub fn new(config: ContextConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &ContextConfig {
+        &self.config
+    }
+
+    /// Assemble memories into context, stopping at the first one that does not fit
+    pub fn build(&self, memories: Vec<ScoredMemory>) -> BuiltContext {
+        let mut candidates = memories;
+        candidates.sort_by(|a, b| {
+            b.memory
+                .metadata
+                .stability
+                .cmp(&a.memory.metadata.stability)
+                .then_with(|| b.score.total_cmp(&a.score))
+        });
+
+        let mut built = BuiltContext::default();
+        let mut text = CONTEXT_HEADER.to_string();
+        let mut remaining = candidates.into_iter();
+
+        for candidate in remaining.by_ref() {
+            let next = format!("{}\n{}", text, render_memory(&candidate.memory));
+            let tokens = self.count(&next);
+            if tokens > self.config.max_tokens {
+                built.dropped.push(candidate);
+                break;
+            }
+            text = next;
+            built.tokens = tokens;
+            built.included.push(candidate);
+        }
+        built.dropped.extend(remaining);
+
+        if !built.included.is_empty() {
+            built.text = text;
+        }
+        built
+    }
+
+    /// Tokens taken by a memory's own context line
+    pub fn memory_tokens(&self, memory: &MemoryEntry) -> usize {
+        self.count(&render_memory(memory))
+    }
+
+    fn count(&self, text: &str) -> usize {
+        self.config
+            .token_counter
+            .count_text(text, &self.config.model)
+    }
+}
+
+/// Render a memory as a single context line, tagged with its stability
+pub fn render_memory(memory: &MemoryEntry) -> String {
+    let body = memory.content.summary();
+    format!("- [{:?}] {}", memory.metadata.stability, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::provider::SimpleTokenCounter;
+    use crate::{
+        AccessControl, AgentId, MemoryContent, MemoryId, MemoryMetadata, MemoryStability, TaskId,
+    };
+
+    fn scored(text: &str, stability: MemoryStability, score: f32) -> ScoredMemory {
+        let agent = AgentId::new();
+        ScoredMemory {
+            memory: MemoryEntry {
+                id: MemoryId::new(),
+                content: MemoryContent::General {
+                    text: text.to_string(),
+                    metadata: String::new(),
+                },
+                embedding: vec![],
+                relations: vec![],
+                metadata: MemoryMetadata {
+                    stability,
+                    created_at: chrono::Utc::now(),
+                    created_by: agent,
+                    source_task: TaskId::new(),
+                    version: 1,
+                    modified_at: None,
+                    tags: vec![],
+                    expires_at: None,
+                },
+                access_control: AccessControl::new(agent, stability),
+            },
+            score,
+        }
+    }
+
+    fn builder(max_tokens: usize) -> ContextBuilder {
+        ContextBuilder::new(ContextConfig::new(
+            max_tokens,
+            Arc::new(SimpleTokenCounter::new()),
+        ))
+    }
+
+    fn texts(memories: &[ScoredMemory]) -> Vec<String> {
+        memories
+            .iter()
+            .map(|m| match &m.memory.content {
+                MemoryContent::General { text, .. } => text.clone(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_context_never_exceeds_budget() {
+        let memories: Vec<_> = (0..20)
+            .map(|i| {
+                scored(
+                    &format!("fact number {} about the build system", i),
+                    MemoryStability::Verified,
+                    i as f32 / 20.0,
+                )
+            })
+            .collect();
+        let counter = SimpleTokenCounter::new();
+
+        for budget in [0, 5, 20, 50, 120, 10_000] {
+            let built = builder(budget).build(memories.clone());
+            assert!(built.tokens <= budget, "budget {} exceeded", budget);
+            assert!(counter.count_text(&built.text, "default") <= budget.max(1));
+            assert_eq!(built.included.len() + built.dropped.len(), 20);
+        }
+
+        let built = builder(10_000).build(memories);
+        assert!(built.dropped.is_empty());
+    }
+
+    #[test]
+    fn test_highest_scores_win_within_a_stability_level() {
+        let memories = vec![
+            scored("low", MemoryStability::Derived, 0.1),
+            scored("high", MemoryStability::Derived, 0.9),
+            scored("mid", MemoryStability::Derived, 0.5),
+        ];
+
+        let built = builder(14).build(memories);
+
+        assert_eq!(texts(&built.included), vec!["high", "mid"]);
+        assert_eq!(texts(&built.dropped), vec!["low"]);
+    }
+
+    #[test]
+    fn test_lower_stability_dropped_first() {
+        let memories = vec![
+            scored("scratch note", MemoryStability::Ephemeral, 0.99),
+            scored("inferred rule", MemoryStability::Derived, 0.9),
+            scored("tested fact", MemoryStability::Verified, 0.2),
+            scored("system truth", MemoryStability::Canonical, 0.1),
+        ];
+
+        let built = builder(20).build(memories);
+
+        assert_eq!(texts(&built.included), vec!["system truth", "tested fact"]);
+        assert_eq!(texts(&built.dropped), vec!["inferred rule", "scratch note"]);
+        assert!(built.text.starts_with(CONTEXT_HEADER));
+        assert!(built.text.contains("- [Canonical] system truth"));
+    }
+
+    #[test]
+    fn test_empty_context_when_nothing_fits() {
+        let built = builder(1).build(vec![scored("x", MemoryStability::Canonical, 1.0)]);
+
+        assert!(built.text.is_empty());
+        assert_eq!(built.tokens, 0);
+        assert!(built.included.is_empty());
+        assert_eq!(built.dropped.len(), 1);
+    }
+}
@@ -0,0 +1,257 @@
+//! Embedding - text to vector generation for memory retrieval
+//!
+//! `Embedder` turns memory text into the vectors `search_cosine` ranks on.
+//! - `OpenAiEmbedder`: any OpenAI-compatible `/embeddings` endpoint
+//! - `HashEmbedder`: local deterministic fallback (feature hashing), no network
+//!
+//! `embedder_from_env` picks the remote embedder when `NDC_EMBEDDING_API_KEY`
+//! is set, otherwise the local one.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Default model for `OpenAiEmbedder`
+pub const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+/// Default vector size (matches `text-embedding-3-small`)
+pub const DEFAULT_EMBEDDING_DIMENSIONS: usize = 1536;
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+#[derive(Debug, Error)]
+pub enum EmbedError {
+    #[error("embedding request failed: {0}")]
+    Request(String),
+
+    #[error("embedding API error ({status}): {message}")]
+    Api { status: u16, message: String },
+
+    #[error("invalid embedding response: {0}")]
+    InvalidResponse(String),
+
+    #[error("embedding dimension mismatch: expected {expected}, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+}
+
+/// Generates embedding vectors of a fixed size
+#[async_trait]
+pub trait Embedder: Send + Sync + std::fmt::Debug {
+    /// Length of every vector this embedder returns
+    fn dimensions(&self) -> usize;
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbedError>;
+}
+
+/// `Err(DimensionMismatch)` unless `vector` has `expected` components
+pub fn check_dimensions(expected: usize, vector: &[f32]) -> Result<(), EmbedError> {
+    if vector.len() == expected {
+        Ok(())
+    } else {
+        Err(EmbedError::DimensionMismatch {
+            expected,
+            actual: vector.len(),
+        })
+    }
+}
+
+/// Embedder for OpenAI-compatible `POST {base_url}/embeddings` endpoints
+#[derive(Debug, Clone)]
+pub struct OpenAiEmbedder {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    dimensions: usize,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>, dimensions: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: api_key.into(),
+            model: model.into(),
+            dimensions,
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+}
+
+/// First vector of an `/embeddings` response body
+fn parse_embedding_response(body: &serde_json::Value) -> Result<Vec<f32>, EmbedError> {
+    let values = body
+        .pointer("/data/0/embedding")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| EmbedError::InvalidResponse("missing data[0].embedding".to_string()))?;
+    values
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .map(|f| f as f32)
+                .ok_or_else(|| EmbedError::InvalidResponse(format!("non-numeric component {}", v)))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Embedder for OpenAiEmbedder {
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbedError> {
+        let response = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "model": self.model,
+                "input": text,
+                "dimensions": self.dimensions,
+            }))
+            .send()
+            .await
+            .map_err(|e| EmbedError::Request(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(EmbedError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| EmbedError::InvalidResponse(e.to_string()))?;
+        let vector = parse_embedding_response(&body)?;
+        check_dimensions(self.dimensions, &vector)?;
+        Ok(vector)
+    }
+}
+
+/// Local deterministic embedder: signed feature hashing of lowercase word
+/// tokens, L2-normalized. Texts sharing words get similar vectors.
+#[derive(Debug, Clone, Copy)]
+pub struct HashEmbedder {
+    dimensions: usize,
+}
+
+impl HashEmbedder {
+    /// Panics when `dimensions` is 0
+    pub fn new(dimensions: usize) -> Self {
+        assert!(dimensions > 0, "embedding dimensions must be positive");
+        Self { dimensions }
+    }
+
+    fn vector(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        for token in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+        {
+            let hash = fnv1a(&token.to_lowercase());
+            let index = (hash % self.dimensions as u64) as usize;
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[index] += sign;
+        }
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(DEFAULT_EMBEDDING_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbedError> {
+        Ok(self.vector(text))
+    }
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Embedder configured by the environment:
+/// `NDC_EMBEDDING_API_KEY` (+ optional `NDC_EMBEDDING_MODEL`,
+/// `NDC_EMBEDDING_BASE_URL`, `NDC_EMBEDDING_DIMENSIONS`) selects
+/// `OpenAiEmbedder`; otherwise `HashEmbedder`
+pub fn embedder_from_env() -> Arc<dyn Embedder> {
+    let dimensions = std::env::var("NDC_EMBEDDING_DIMENSIONS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|d| *d > 0)
+        .unwrap_or(DEFAULT_EMBEDDING_DIMENSIONS);
+    match std::env::var("NDC_EMBEDDING_API_KEY") {
+        Ok(api_key) if !api_key.trim().is_empty() => {
+            let model = std::env::var("NDC_EMBEDDING_MODEL")
+                .unwrap_or_else(|_| DEFAULT_EMBEDDING_MODEL.to_string());
+            let mut embedder = OpenAiEmbedder::new(api_key, model, dimensions);
+            if let Ok(base_url) = std::env::var("NDC_EMBEDDING_BASE_URL") {
+                embedder = embedder.with_base_url(base_url);
+            }
+            Arc::new(embedder)
+        }
+        _ => Arc::new(HashEmbedder::new(dimensions)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::cosine_similarity;
+
+    #[tokio::test]
+    async fn test_hash_embedder_is_deterministic_and_normalized() {
+        let embedder = HashEmbedder::new(64);
+        let a = embedder.embed("Fix the login timeout bug").await.unwrap();
+        let b = embedder.embed("fix the LOGIN timeout bug").await.unwrap();
+        let other = embedder.embed("render markdown tables").await.unwrap();
+
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, b);
+        let norm = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(cosine_similarity(&a, &b) > cosine_similarity(&a, &other));
+        assert!(embedder.embed("").await.unwrap().iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_parse_embedding_response() {
+        let body = serde_json::json!({"data": [{"embedding": [0.5, -1.0, 2]}]});
+        assert_eq!(
+            parse_embedding_response(&body).unwrap(),
+            vec![0.5, -1.0, 2.0]
+        );
+        assert!(matches!(
+            parse_embedding_response(&serde_json::json!({"data": []})),
+            Err(EmbedError::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            check_dimensions(4, &[0.0; 3]),
+            Err(EmbedError::DimensionMismatch {
+                expected: 4,
+                actual: 3
+            })
+        ));
+    }
+}
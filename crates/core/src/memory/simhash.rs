@@ -0,0 +1,249 @@
+//! SimHash Index - approximate nearest-neighbour lookup over embeddings
+//!
+//! Each embedding is reduced to a 64-bit SimHash (signs against fixed random
+//! hyperplanes). The hash is split into bands; entries sharing any band with the
+//! query are candidates, and candidates are ranked by exact cosine similarity.
+//! Entries can be added, updated and removed incrementally; buckets are kept
+//! consistent so removed ids never come back from a search.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{MemoryId, cosine_similarity};
+
+const HASH_BITS: usize = 64;
+const BAND_BITS: usize = 8;
+const BANDS: usize = HASH_BITS / BAND_BITS;
+/// Fixed seed so hashes are stable across runs
+const PLANE_SEED: u64 = 0x5EED_0F51_A54A_5400;
+
+#[derive(Debug, Clone)]
+struct IndexedEntry {
+    hash: u64,
+    embedding: Vec<f32>,
+}
+
+/// SimHash index keyed by memory id
+#[derive(Debug, Clone)]
+pub struct SimHashIndex {
+    dims: usize,
+    planes: Vec<Vec<f32>>,
+    entries: HashMap<MemoryId, IndexedEntry>,
+    /// (band index, band value) -> ids
+    buckets: HashMap<(usize, u8), HashSet<MemoryId>>,
+}
+
+impl SimHashIndex {
+    /// Create an index for embeddings of `dims` dimensions
+    pub fn new(dims: usize) -> Self {
+        let mut state = PLANE_SEED;
+        let planes = (0..HASH_BITS)
+            .map(|_| (0..dims).map(|_| next_unit(&mut state)).collect())
+            .collect();
+        Self {
+            dims,
+            planes,
+            entries: HashMap::new(),
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, id: &MemoryId) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    /// Index an embedding; re-adding an id replaces its previous entry
+    pub fn add(&mut self, id: MemoryId, embedding: &[f32]) {
+        self.remove(&id);
+        let hash = self.hash(embedding);
+        for band in 0..BANDS {
+            self.buckets
+                .entry((band, band_value(hash, band)))
+                .or_default()
+                .insert(id);
+        }
+        self.entries.insert(
+            id,
+            IndexedEntry {
+                hash,
+                embedding: embedding.to_vec(),
+            },
+        );
+    }
+
+    /// Drop an entry; returns whether it was indexed
+    pub fn remove(&mut self, id: &MemoryId) -> bool {
+        let Some(entry) = self.entries.remove(id) else {
+            return false;
+        };
+        for band in 0..BANDS {
+            let key = (band, band_value(entry.hash, band));
+            if let Some(bucket) = self.buckets.get_mut(&key) {
+                bucket.remove(id);
+                if bucket.is_empty() {
+                    self.buckets.remove(&key);
+                }
+            }
+        }
+        true
+    }
+
+    /// Re-index an existing entry with a new embedding; false if the id is unknown
+    pub fn update(&mut self, id: &MemoryId, new_embedding: &[f32]) -> bool {
+        if !self.entries.contains_key(id) {
+            return false;
+        }
+        self.add(*id, new_embedding);
+        true
+    }
+
+    /// Candidates sharing a band with the query, best cosine match first
+    pub fn search(&self, query: &[f32], top_k: usize) -> Vec<(MemoryId, f32)> {
+        let hash = self.hash(query);
+        rank(
+            self.candidates(hash)
+                .map(|(id, entry)| (id, cosine_similarity(&entry.embedding, query))),
+            top_k,
+        )
+    }
+
+    /// Candidates sharing a band with the query, scored by Hamming similarity
+    ///
+    /// Score is `1 - distance / 64`, so an identical hash scores 1.0.
+    pub fn search_hamming(&self, query: &[f32], top_k: usize) -> Vec<(MemoryId, f32)> {
+        let hash = self.hash(query);
+        rank(
+            self.candidates(hash).map(|(id, entry)| {
+                let distance = (entry.hash ^ hash).count_ones();
+                (id, 1.0 - distance as f32 / HASH_BITS as f32)
+            }),
+            top_k,
+        )
+    }
+
+    fn candidates(&self, hash: u64) -> impl Iterator<Item = (MemoryId, &IndexedEntry)> {
+        let ids: HashSet<&MemoryId> = (0..BANDS)
+            .filter_map(|band| self.buckets.get(&(band, band_value(hash, band))))
+            .flatten()
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| Some((*id, self.entries.get(id)?)))
+    }
+
+    /// Hamming distance between the SimHashes of two indexed entries
+    pub fn distance(&self, a: &MemoryId, b: &MemoryId) -> Option<u32> {
+        let a = self.entries.get(a)?;
+        let b = self.entries.get(b)?;
+        Some((a.hash ^ b.hash).count_ones())
+    }
+
+    fn hash(&self, embedding: &[f32]) -> u64 {
+        self.planes
+            .iter()
+            .enumerate()
+            .fold(0u64, |hash, (bit, plane)| {
+                let dot: f32 = plane.iter().zip(embedding).map(|(p, x)| p * x).sum();
+                if dot >= 0.0 { hash | (1 << bit) } else { hash }
+            })
+    }
+}
+
+fn rank(scored: impl Iterator<Item = (MemoryId, f32)>, top_k: usize) -> Vec<(MemoryId, f32)> {
+    let mut scored: Vec<(MemoryId, f32)> = scored.collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(top_k);
+    scored
+}
+
+fn band_value(hash: u64, band: usize) -> u8 {
+    (hash >> (band * BAND_BITS)) as u8
+}
+
+/// splitmix64 step mapped to [-1, 1)
+fn next_unit(state: &mut u64) -> f32 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_of_three() -> (SimHashIndex, [MemoryId; 3]) {
+        let mut index = SimHashIndex::new(4);
+        let ids = [MemoryId::new(), MemoryId::new(), MemoryId::new()];
+        index.add(ids[0], &[1.0, 0.0, 0.0, 0.0]);
+        index.add(ids[1], &[0.95, 0.05, 0.0, 0.0]);
+        index.add(ids[2], &[0.9, 0.0, 0.1, 0.0]);
+        (index, ids)
+    }
+
+    #[test]
+    fn test_remove_drops_entry_from_search() {
+        let (mut index, ids) = index_of_three();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.search(&[1.0, 0.0, 0.0, 0.0], 10).len(), 3);
+
+        assert!(index.remove(&ids[0]));
+        assert!(!index.remove(&ids[0]));
+        assert_eq!(index.len(), 2);
+
+        let results = index.search(&[1.0, 0.0, 0.0, 0.0], 10);
+        assert!(results.iter().all(|(id, _)| *id != ids[0]));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, ids[1]);
+        assert!(results[0].1 > 0.99);
+        assert!(results[1].1 > 0.99 && results[1].1 < results[0].1);
+    }
+
+    #[test]
+    fn test_update_rebuckets_entry() {
+        let (mut index, ids) = index_of_three();
+
+        assert!(index.update(&ids[2], &[-1.0, 0.0, 0.0, 0.0]));
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.distance(&ids[0], &ids[2]), Some(64));
+        let results = index.search(&[1.0, 0.0, 0.0, 0.0], 10);
+        assert!(results.iter().all(|(id, _)| *id != ids[2]));
+
+        assert!(!index.update(&MemoryId::new(), &[1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_search_hamming_scores_identical_hash_as_one() {
+        let (index, ids) = index_of_three();
+
+        let results = index.search_hamming(&[1.0, 0.0, 0.0, 0.0], 10);
+        assert_eq!(results.len(), 3);
+        let exact = results.iter().find(|(id, _)| *id == ids[0]).unwrap();
+        assert_eq!(exact.1, 1.0);
+        assert!(results.iter().all(|(_, score)| (0.0..=1.0).contains(score)));
+        assert_eq!(index.search_hamming(&[1.0, 0.0, 0.0, 0.0], 1).len(), 1);
+    }
+
+    #[test]
+    fn test_removing_everything_leaves_no_buckets() {
+        let (mut index, ids) = index_of_three();
+        for id in &ids {
+            index.remove(id);
+        }
+        assert!(index.is_empty());
+        assert!(index.buckets.is_empty());
+        assert!(index.search(&[1.0, 0.0, 0.0, 0.0], 10).is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! Vector Search - memory lookup by embedding in a selectable mode
+//!
+//! - `Cosine`: exact cosine over every stored embedding
+//! - `SimHash`: entries sharing a SimHash band with the query, scored by
+//!   Hamming similarity (fast, approximate)
+//! - `Hybrid`: the same SimHash candidates, reranked by exact cosine
+//!
+//! All modes score in 0.0–1.0 so results stay comparable.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{MemoryEntry, MemoryId, ScoredMemory, SimHashIndex, search_cosine};
+
+/// How stored embeddings are matched against a query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchMode {
+    /// SimHash candidates scored by Hamming similarity
+    SimHash,
+    /// Exact cosine over every embedding
+    #[default]
+    Cosine,
+    /// SimHash candidates reranked by exact cosine
+    Hybrid,
+}
+
+/// Embedded memories plus a SimHash index over them
+#[derive(Debug, Clone)]
+pub struct VectorSearch {
+    index: SimHashIndex,
+    memories: HashMap<MemoryId, MemoryEntry>,
+}
+
+impl VectorSearch {
+    /// Empty search over embeddings of `dims` dimensions
+    pub fn new(dims: usize) -> Self {
+        Self {
+            index: SimHashIndex::new(dims),
+            memories: HashMap::new(),
+        }
+    }
+
+    /// Index every memory whose embedding has `dims` dimensions
+    pub fn from_memories(memories: impl IntoIterator<Item = MemoryEntry>, dims: usize) -> Self {
+        let mut search = Self::new(dims);
+        for memory in memories {
+            search.insert(memory);
+        }
+        search
+    }
+
+    pub fn dims(&self) -> usize {
+        self.index.dims()
+    }
+
+    pub fn len(&self) -> usize {
+        self.memories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.memories.is_empty()
+    }
+
+    /// Add or replace a memory; false (and any previous entry dropped) if
+    /// its embedding has the wrong size
+    pub fn insert(&mut self, memory: MemoryEntry) -> bool {
+        if memory.embedding.len() != self.dims() {
+            self.remove(&memory.id);
+            return false;
+        }
+        self.index.add(memory.id, &memory.embedding);
+        self.memories.insert(memory.id, memory);
+        true
+    }
+
+    pub fn remove(&mut self, id: &MemoryId) -> Option<MemoryEntry> {
+        self.index.remove(id);
+        self.memories.remove(id)
+    }
+
+    /// Exact cosine search, best match first
+    pub fn search_cosine(&self, query: &[f32], top_k: usize) -> Vec<ScoredMemory> {
+        search_cosine(self.memories.values(), query, top_k)
+    }
+
+    /// The `top_k` best matches for `query` under `mode`, best first
+    pub fn search(&self, query: &[f32], top_k: usize, mode: SearchMode) -> Vec<ScoredMemory> {
+        let hits = match mode {
+            SearchMode::Cosine => return self.search_cosine(query, top_k),
+            SearchMode::SimHash => self.index.search_hamming(query, top_k),
+            SearchMode::Hybrid => self.index.search(query, top_k),
+        };
+        hits.into_iter()
+            .filter_map(|(id, score)| {
+                Some(ScoredMemory {
+                    memory: self.memories.get(&id)?.clone(),
+                    score,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{AccessControl, MemoryContent, MemoryMetadata, MemoryStability};
+    use crate::{AgentId, TaskId};
+
+    fn memory(embedding: Vec<f32>) -> MemoryEntry {
+        MemoryEntry {
+            id: MemoryId::new(),
+            content: MemoryContent::General {
+                text: "test".to_string(),
+                metadata: "".to_string(),
+            },
+            embedding,
+            relations: vec![],
+            metadata: MemoryMetadata {
+                stability: MemoryStability::Derived,
+                created_at: chrono::Utc::now(),
+                created_by: AgentId::new(),
+                source_task: TaskId::new(),
+                version: 1,
+                modified_at: None,
+                tags: vec![],
+                expires_at: None,
+            },
+            access_control: AccessControl::new(AgentId::new(), MemoryStability::Derived),
+        }
+    }
+
+    /// exact, near, orthogonal
+    fn search_of_three() -> (VectorSearch, [MemoryId; 3]) {
+        let entries = [
+            memory(vec![1.0, 0.0, 0.0, 0.0]),
+            memory(vec![0.95, 0.05, 0.0, 0.0]),
+            memory(vec![0.0, 1.0, 0.0, 0.0]),
+        ];
+        let ids = [entries[0].id, entries[1].id, entries[2].id];
+        (VectorSearch::from_memories(entries, 4), ids)
+    }
+
+    const QUERY: [f32; 4] = [1.0, 0.0, 0.0, 0.0];
+
+    #[test]
+    fn test_cosine_mode_scores_exact_and_orthogonal() {
+        let (search, ids) = search_of_three();
+
+        let results = search.search(&QUERY, 10, SearchMode::Cosine);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].memory.id, ids[0]);
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(results[2].memory.id, ids[2]);
+        assert!(results[2].score.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_simhash_mode_scores_by_hamming_similarity() {
+        let (search, ids) = search_of_three();
+
+        let results = search.search(&QUERY, 10, SearchMode::SimHash);
+        let exact = results.iter().find(|r| r.memory.id == ids[0]).unwrap();
+        assert_eq!(exact.score, 1.0);
+        assert_eq!(results[0].score, 1.0);
+        assert!(results.iter().all(|r| (0.0..=1.0).contains(&r.score)));
+    }
+
+    #[test]
+    fn test_hybrid_mode_reranks_candidates_by_cosine() {
+        let (search, ids) = search_of_three();
+
+        let results = search.search(&QUERY, 10, SearchMode::Hybrid);
+        assert_eq!(results[0].memory.id, ids[0]);
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(results[1].memory.id, ids[1]);
+        assert!(results[1].score > 0.99 && results[1].score < results[0].score);
+        // Hybrid scores match the exact cosine ones for every candidate
+        let exact = search.search_cosine(&QUERY, 10);
+        for hit in &results {
+            let cosine = exact.iter().find(|e| e.memory.id == hit.memory.id).unwrap();
+            assert!((hit.score - cosine.score).abs() < 1e-6);
+        }
+        assert_eq!(search.search(&QUERY, 1, SearchMode::Hybrid).len(), 1);
+    }
+
+    #[test]
+    fn test_insert_rejects_wrong_dimensions_and_remove_unindexes() {
+        let (mut search, ids) = search_of_three();
+        assert!(!search.insert(memory(vec![1.0, 0.0])));
+        assert_eq!(search.len(), 3);
+
+        assert!(search.remove(&ids[0]).is_some());
+        for mode in [SearchMode::SimHash, SearchMode::Cosine, SearchMode::Hybrid] {
+            let results = search.search(&QUERY, 10, mode);
+            assert!(results.iter().all(|r| r.memory.id != ids[0]));
+        }
+    }
+}
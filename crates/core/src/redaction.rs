@@ -1,4 +1,19 @@
-use std::sync::OnceLock;
+use std::sync::{OnceLock, RwLock};
+
+/// Placeholder substituted for matches of custom patterns
+pub const REDACTED_PLACEHOLDER: &str = "[REDACTED]";
+
+/// Env var with extra patterns: a JSON array, or one regex per line
+pub const REDACTION_PATTERNS_ENV: &str = "NDC_REDACTION_PATTERNS";
+
+/// A user-supplied redaction regex that failed to compile
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid redaction pattern #{index} `{pattern}`: {message}")]
+pub struct InvalidRedactionPattern {
+    pub index: usize,
+    pub pattern: String,
+    pub message: String,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RedactionMode {
@@ -26,7 +41,66 @@ impl RedactionMode {
     }
 }
 
+/// Split a pattern list from config/env: a JSON array or one regex per line
+pub fn parse_pattern_list(raw: &str) -> Vec<String> {
+    let raw = raw.trim();
+    if raw.starts_with('[')
+        && let Ok(patterns) = serde_json::from_str::<Vec<String>>(raw)
+    {
+        return patterns;
+    }
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Compile every pattern, failing on the first invalid one
+pub fn compile_patterns(patterns: &[String]) -> Result<Vec<regex::Regex>, InvalidRedactionPattern> {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(index, pattern)| {
+            regex::Regex::new(pattern).map_err(|e| InvalidRedactionPattern {
+                index,
+                pattern: pattern.clone(),
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn custom_patterns() -> &'static RwLock<Vec<regex::Regex>> {
+    static CUSTOM: OnceLock<RwLock<Vec<regex::Regex>>> = OnceLock::new();
+    CUSTOM.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Register custom patterns applied by `sanitize_text` alongside the built-ins
+///
+/// Already registered patterns are skipped. Nothing is registered if any
+/// pattern is invalid.
+pub fn add_custom_patterns(patterns: &[String]) -> Result<(), InvalidRedactionPattern> {
+    let compiled = compile_patterns(patterns)?;
+    let mut custom = custom_patterns()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    for pattern in compiled {
+        if !custom.iter().any(|p| p.as_str() == pattern.as_str()) {
+            custom.push(pattern);
+        }
+    }
+    Ok(())
+}
+
 pub fn sanitize_text(input: &str, mode: RedactionMode) -> String {
+    let custom = custom_patterns()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    sanitize_with(input, mode, &custom)
+}
+
+fn sanitize_with(input: &str, mode: RedactionMode, custom: &[regex::Regex]) -> String {
     if mode == RedactionMode::Off {
         return input.to_string();
     }
@@ -54,6 +128,9 @@ pub fn sanitize_text(input: &str, mode: RedactionMode) -> String {
     });
 
     let mut out = input.to_string();
+    for pattern in custom {
+        out = pattern.replace_all(&out, REDACTED_PLACEHOLDER).to_string();
+    }
     out = secret_assign.replace_all(&out, "$1=[REDACTED]").to_string();
     out = bearer.replace_all(&out, "Bearer [REDACTED]").to_string();
     out = openai_key.replace_all(&out, "sk-[REDACTED]").to_string();
@@ -103,4 +180,32 @@ mod tests {
         assert!(!out.contains("/tmp/a/b.txt"));
         assert!(!out.contains("/var/log/syslog"));
     }
+
+    #[test]
+    fn test_custom_pattern_applies_alongside_builtins() {
+        let custom =
+            compile_patterns(&[r"EMP-\d{6}".to_string(), r"itk_[a-z0-9]{12}".to_string()]).unwrap();
+        let input = "EMP-123456 used itk_abcdef123456 with token=abc";
+        let out = sanitize_with(input, RedactionMode::Basic, &custom);
+        assert_eq!(out, "[REDACTED] used [REDACTED] with token=[REDACTED]");
+        assert_eq!(sanitize_with(input, RedactionMode::Off, &custom), input);
+    }
+
+    #[test]
+    fn test_add_custom_patterns_reports_invalid_regex() {
+        let err = add_custom_patterns(&["ok".to_string(), "(unclosed".to_string()]).unwrap_err();
+        assert_eq!(err.index, 1);
+        assert_eq!(err.pattern, "(unclosed");
+        assert_eq!(sanitize_text("ok", RedactionMode::Basic), "ok");
+
+        add_custom_patterns(&[r"CUSTOM-ZZ\d+".to_string()]).unwrap();
+        let out = sanitize_text("id CUSTOM-ZZ42 Bearer abc", RedactionMode::Basic);
+        assert_eq!(out, "id [REDACTED] Bearer [REDACTED]");
+    }
+
+    #[test]
+    fn test_parse_pattern_list() {
+        assert_eq!(parse_pattern_list(r#"["a\\d", "b"]"#), vec![r"a\d", "b"]);
+        assert_eq!(parse_pattern_list("a\n\n b \n"), vec!["a", "b"]);
+    }
 }
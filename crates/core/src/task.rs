@@ -41,6 +41,26 @@ impl fmt::Display for TaskState {
     }
 }
 
+impl std::str::FromStr for TaskState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pending" => Ok(TaskState::Pending),
+            "preparing" => Ok(TaskState::Preparing),
+            "in_progress" | "in-progress" | "inprogress" => Ok(TaskState::InProgress),
+            "awaiting_verification" | "awaiting-verification" | "awaitingverification" => {
+                Ok(TaskState::AwaitingVerification)
+            }
+            "blocked" => Ok(TaskState::Blocked),
+            "completed" => Ok(TaskState::Completed),
+            "failed" => Ok(TaskState::Failed),
+            "cancelled" | "canceled" => Ok(TaskState::Cancelled),
+            other => Err(format!("unknown task state '{}'", other)),
+        }
+    }
+}
+
 /// Git Worktree 快照（使用 git worktree 实现精确回滚）
 ///
 /// 工作原理：
@@ -156,6 +176,10 @@ pub struct Task {
     #[serde(default)]
     pub lightweight_snapshots: Vec<LightweightSnapshot>,
 
+    /// 前置任务：全部完成后才能执行（见 `plan_execution_order`）
+    #[serde(default)]
+    pub depends_on: Vec<TaskId>,
+
     /// 元数据
     pub metadata: TaskMetadata,
 }
@@ -215,7 +239,7 @@ pub struct QualityCheck {
     pub pass_condition: PassCondition,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QualityCheckType {
     Test,
     Lint,
@@ -280,6 +304,7 @@ impl Task {
             quality_gate: None,
             snapshots: vec![],
             lightweight_snapshots: vec![],
+            depends_on: vec![],
             metadata: TaskMetadata::default(),
         }
     }
@@ -298,6 +323,7 @@ impl Task {
             quality_gate: None,
             snapshots: vec![],
             lightweight_snapshots: vec![],
+            depends_on: vec![],
             metadata: TaskMetadata {
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
@@ -399,11 +425,17 @@ impl Task {
             state: TaskState::Pending,
             title,
             description,
-            allowed_transitions: vec![TaskState::Preparing, TaskState::InProgress, TaskState::Completed, TaskState::Cancelled],
+            allowed_transitions: vec![
+                TaskState::Preparing,
+                TaskState::InProgress,
+                TaskState::Completed,
+                TaskState::Cancelled,
+            ],
             steps: vec![],
             quality_gate: None,
             snapshots: vec![],
             lightweight_snapshots: vec![],
+            depends_on: vec![],
             metadata: TaskMetadata {
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
@@ -499,6 +531,12 @@ pub enum WorkEvent {
     Unblocked,
     Completed,
     Failed,
+    /// 决策引擎修改了动作（`Verdict::Modify`），记录原动作与原因
+    ActionModified {
+        original: Action,
+        reason: String,
+        warnings: Vec<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -522,6 +560,66 @@ pub enum TransitionError {
     NotAllowed { from: TaskState, to: TaskState },
 }
 
+/// 任务依赖存在环
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("任务依赖存在环: {}", tasks.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "))]
+pub struct CycleError {
+    /// 处于环上（或依赖环）而无法排序的任务
+    pub tasks: Vec<TaskId>,
+}
+
+/// 按依赖拓扑排序待执行任务
+///
+/// 已完成的任务不参与排序；依赖已完成或不在 `tasks` 中的任务视为已满足。
+/// 同时就绪的任务保持输入顺序。
+pub fn plan_execution_order(tasks: &[Task]) -> Result<Vec<TaskId>, CycleError> {
+    let pending: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.state != TaskState::Completed)
+        .collect();
+    let index: std::collections::HashMap<TaskId, usize> =
+        pending.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+
+    let mut unmet = vec![0usize; pending.len()];
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); pending.len()];
+    for (i, task) in pending.iter().enumerate() {
+        let mut deps: Vec<usize> = task
+            .depends_on
+            .iter()
+            .filter_map(|dep| index.get(dep).copied())
+            .collect();
+        deps.sort_unstable();
+        deps.dedup();
+        unmet[i] = deps.len();
+        for dep in deps {
+            dependents[dep].push(i);
+        }
+    }
+
+    let mut ready: std::collections::BTreeSet<usize> =
+        (0..pending.len()).filter(|i| unmet[*i] == 0).collect();
+    let mut order = Vec::with_capacity(pending.len());
+    while let Some(i) = ready.pop_first() {
+        order.push(pending[i].id);
+        for &dependent in &dependents[i] {
+            unmet[dependent] -= 1;
+            if unmet[dependent] == 0 {
+                ready.insert(dependent);
+            }
+        }
+    }
+
+    if order.len() < pending.len() {
+        return Err(CycleError {
+            tasks: (0..pending.len())
+                .filter(|i| unmet[*i] > 0)
+                .map(|i| pending[i].id)
+                .collect(),
+        });
+    }
+    Ok(order)
+}
+
 // 类型别名
 pub type TaskId = ulid::Ulid;
 pub type SnapshotId = ulid::Ulid;
@@ -680,7 +778,11 @@ mod tests {
 
     #[test]
     fn test_mark_in_progress_from_terminal_errors() {
-        for state in [TaskState::Completed, TaskState::Failed, TaskState::Cancelled] {
+        for state in [
+            TaskState::Completed,
+            TaskState::Failed,
+            TaskState::Cancelled,
+        ] {
             let mut task = Task::new_todo("t".into(), "d".into(), "p", "s");
             task.state = state.clone();
             assert!(
@@ -690,4 +792,54 @@ mod tests {
             );
         }
     }
+
+    fn task(title: &str, depends_on: &[&Task]) -> Task {
+        let mut task = Task::new(title.to_string(), String::new(), AgentRole::Implementer);
+        task.depends_on = depends_on.iter().map(|t| t.id).collect();
+        task
+    }
+
+    #[test]
+    fn test_plan_execution_order_linear_chain() {
+        let a = task("a", &[]);
+        let b = task("b", &[&a]);
+        let mut c = task("c", &[&b]);
+        c.depends_on.push(a.id);
+        let order = plan_execution_order(&[c.clone(), b.clone(), a.clone()]).unwrap();
+        assert_eq!(order, vec![a.id, b.id, c.id]);
+
+        // Completed prerequisites are already satisfied
+        let mut done = a.clone();
+        done.state = TaskState::Completed;
+        let order = plan_execution_order(&[c.clone(), b.clone(), done]).unwrap();
+        assert_eq!(order, vec![b.id, c.id]);
+    }
+
+    #[test]
+    fn test_plan_execution_order_diamond() {
+        // a <- b, a <- c, {b, c} <- d
+        let a = task("a", &[]);
+        let b = task("b", &[&a]);
+        let c = task("c", &[&a]);
+        let d = task("d", &[&b, &c]);
+        let order = plan_execution_order(&[d.clone(), c.clone(), b.clone(), a.clone()]).unwrap();
+        assert_eq!(order.len(), 4);
+        let pos = |id: TaskId| order.iter().position(|t| *t == id).unwrap();
+        assert_eq!(pos(a.id), 0);
+        assert!(pos(b.id) < pos(d.id));
+        assert!(pos(c.id) < pos(d.id));
+        assert_eq!(pos(d.id), 3);
+    }
+
+    #[test]
+    fn test_plan_execution_order_detects_cycle() {
+        let a = task("a", &[]);
+        let mut b = task("b", &[&a]);
+        let c = task("c", &[&b]);
+        b.depends_on.push(c.id);
+        let free = task("free", &[]);
+        let err = plan_execution_order(&[a.clone(), b.clone(), c.clone(), free]).unwrap_err();
+        assert_eq!(err.tasks, vec![b.id, c.id]);
+        assert!(err.to_string().contains(&b.id.to_string()));
+    }
 }
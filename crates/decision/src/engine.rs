@@ -4,6 +4,7 @@
 //! - 评估 Intent 并返回 Verdict
 //! - 执行约束校验
 //! - 权限等级判定
+//! - 按角色/Agent 限流
 //!
 //! 设计原则：
 //! - 同步阻塞：没有 Verdict，任何动作不能 commit
@@ -11,11 +12,15 @@
 
 use async_trait::async_trait;
 use ndc_core::{
-    Action, AgentRole, Condition, ConditionType, ErrorCode, HumanContext, Intent, PrivilegeLevel,
-    Verdict,
+    Action, AgentRole, Condition, ConditionType, ErrorCode, HumanContext, InformationRequirement,
+    InformationSource, Intent, IntentId, PrivilegeLevel, RiskLevel, Verdict,
 };
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::rate_limit::RateLimiter;
 
 /// 决策引擎 Trait
 #[async_trait]
@@ -26,6 +31,13 @@ pub trait DecisionEngine: Send + Sync {
     /// 批量评估
     async fn evaluate_batch(&self, intents: Vec<Intent>) -> Vec<Verdict>;
 
+    /// 带依赖的批量评估（见 `IntentDependency`）
+    async fn evaluate_batch_with_dependencies(
+        &self,
+        intents: Vec<Intent>,
+        dependencies: &[IntentDependency],
+    ) -> Vec<Verdict>;
+
     /// 注册校验器
     fn register_validator(&mut self, validator: Arc<dyn Validator>);
 
@@ -56,6 +68,25 @@ pub enum ValidationResult {
     Defer(Vec<ndc_core::InformationRequirement>, Option<u64>),
 }
 
+/// 批量评估中的依赖（按下标）
+///
+/// 严格模式下，前置 Intent 被拒绝（或自身被跳过）时，依赖它的 Intent
+/// 不再评估，直接返回指向该前置的 `Verdict::Defer`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntentDependency {
+    pub dependent: usize,
+    pub prerequisite: usize,
+}
+
+impl IntentDependency {
+    pub fn new(dependent: usize, prerequisite: usize) -> Self {
+        Self {
+            dependent,
+            prerequisite,
+        }
+    }
+}
+
 /// 策略状态
 #[derive(Debug, Clone, Default)]
 pub struct PolicyState {
@@ -81,6 +112,9 @@ pub struct PolicyState {
     pub denied_intents: u32,
 }
 
+/// 波动度达到该值的路径，风险等级上调一级（与 discovery 的默认 risk_threshold 一致）
+pub const HIGH_VOLATILITY_THRESHOLD: f64 = 0.7;
+
 /// 决策引擎实现
 pub struct BasicDecisionEngine {
     /// 校验器列表（按优先级排序）
@@ -91,6 +125,18 @@ pub struct BasicDecisionEngine {
 
     /// 角色权限映射
     role_privileges: HashMap<AgentRole, PrivilegeLevel>,
+
+    /// 可选的 Intent 限流器
+    rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// 项目根目录，用于解析相对路径（判断文件是否已存在）
+    project_root: Option<PathBuf>,
+
+    /// discovery 给出的路径波动度（0-1），用于风险评估
+    volatility: HashMap<PathBuf, f64>,
+
+    /// 人类介入次数（evaluate 只持有 &self）
+    human_interventions: AtomicU32,
 }
 
 impl BasicDecisionEngine {
@@ -100,6 +146,10 @@ impl BasicDecisionEngine {
             validators: Vec::new(),
             policy_state: PolicyState::default(),
             role_privileges: HashMap::new(),
+            rate_limiter: None,
+            project_root: None,
+            volatility: HashMap::new(),
+            human_interventions: AtomicU32::new(0),
         };
 
         // 初始化默认角色权限
@@ -108,6 +158,42 @@ impl BasicDecisionEngine {
         engine
     }
 
+    /// 启用限流
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+        self
+    }
+
+    /// 启用严格模式
+    pub fn with_strict_mode(mut self, strict: bool) -> Self {
+        self.policy_state.strict_mode = strict;
+        self
+    }
+
+    /// 设置项目根目录（相对路径据此判断文件是否存在）
+    pub fn with_project_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.project_root = Some(root.into());
+        self
+    }
+
+    /// 高风险（High/Critical）动作需要人类确认
+    pub fn with_human_review_for_high_risk(mut self, required: bool) -> Self {
+        self.policy_state.require_human_for_high_risk = required;
+        self
+    }
+
+    /// 记录路径的波动度（来自 discovery 的 HighVolatilityModule）；
+    /// 目录对其下所有文件生效
+    pub fn with_path_volatility(mut self, path: impl Into<PathBuf>, score: f64) -> Self {
+        self.volatility.insert(path.into(), score);
+        self
+    }
+
+    /// 限流器（用于诊断）
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_deref()
+    }
+
     /// 初始化默认角色权限
     fn init_default_privileges(&mut self) {
         self.role_privileges
@@ -134,6 +220,17 @@ impl Default for BasicDecisionEngine {
 #[async_trait]
 impl DecisionEngine for BasicDecisionEngine {
     async fn evaluate(&self, intent: Intent) -> Verdict {
+        // 0. 限流（批量评估时额度跨整个批次累计）
+        if let Some(limiter) = &self.rate_limiter
+            && let ValidationResult::Deny(reason) = limiter.check(&intent)
+        {
+            return Verdict::Deny {
+                action: intent.proposed_action,
+                reason,
+                error_code: ErrorCode::RateLimited,
+            };
+        }
+
         // 1. 计算所需权限等级
         let required_privilege = self.calculate_required_privilege(&intent);
 
@@ -198,10 +295,34 @@ impl DecisionEngine for BasicDecisionEngine {
             };
         }
 
-        // 5. 构建附加条件
+        // 5. 高风险动作交给人类确认
+        let risk_level = self.assess_risk(&intent.proposed_action, required_privilege);
+        if self.policy_state.require_human_for_high_risk
+            && matches!(risk_level, RiskLevel::High | RiskLevel::Critical)
+        {
+            self.human_interventions.fetch_add(1, Ordering::Relaxed);
+            return Verdict::RequireHuman {
+                question: format!(
+                    "Approve {:?}-risk action {}?",
+                    risk_level,
+                    Self::describe_action(&intent.proposed_action)
+                ),
+                context: HumanContext {
+                    task_id: intent.task_id,
+                    affected_files: Self::affected_files(&intent.proposed_action),
+                    risk_level,
+                    alternatives: vec![],
+                    required_privilege,
+                },
+                action: intent.proposed_action,
+                timeout: Some(300),
+            };
+        }
+
+        // 6. 构建附加条件
         let conditions = self.build_conditions(&intent);
 
-        // 6. 返回 Allow Verdict
+        // 7. 返回 Allow Verdict
         Verdict::Allow {
             action: intent.proposed_action,
             privilege: granted_privilege,
@@ -210,11 +331,92 @@ impl DecisionEngine for BasicDecisionEngine {
     }
 
     async fn evaluate_batch(&self, intents: Vec<Intent>) -> Vec<Verdict> {
-        let mut results = Vec::with_capacity(intents.len());
-        for intent in intents {
-            results.push(self.evaluate(intent).await);
+        self.evaluate_batch_with_dependencies(intents, &[]).await
+    }
+
+    async fn evaluate_batch_with_dependencies(
+        &self,
+        intents: Vec<Intent>,
+        dependencies: &[IntentDependency],
+    ) -> Vec<Verdict> {
+        let count = intents.len();
+        let ids: Vec<IntentId> = intents.iter().map(|intent| intent.id).collect();
+        let mut prerequisites: Vec<Vec<usize>> = vec![Vec::new(); count];
+        if self.policy_state.strict_mode {
+            for dep in dependencies {
+                if dep.dependent < count && dep.dependent != dep.prerequisite {
+                    prerequisites[dep.dependent].push(dep.prerequisite);
+                }
+            }
         }
-        results
+
+        // 按依赖分波评估；同一波内的 Intent 并行评估（按下标顺序开始）
+        // 没有 Verdict 的下标即为待评估，Intent 始终保留在 `intents` 中
+        let mut verdicts: Vec<Option<Verdict>> = vec![None; count];
+        loop {
+            let mut wave = Vec::new();
+            let mut skipped = false;
+            for index in 0..count {
+                if verdicts[index].is_some() {
+                    continue;
+                }
+                let mut ready = true;
+                let mut blocker = None;
+                for &pre in &prerequisites[index] {
+                    match verdicts.get(pre) {
+                        None => blocker = Some((pre, "does not exist".to_string())),
+                        Some(None) => ready = false,
+                        Some(Some(verdict)) => {
+                            blocker = Self::blocking_reason(verdict).map(|reason| (pre, reason))
+                        }
+                    }
+                    if blocker.is_some() {
+                        break;
+                    }
+                }
+                if let Some((pre, reason)) = blocker {
+                    let intent = intents[index].clone();
+                    let pre_id = ids.get(pre).copied().unwrap_or(intent.id);
+                    verdicts[index] =
+                        Some(Self::defer_on_prerequisite(intent, pre, pre_id, &reason));
+                    skipped = true;
+                } else if ready {
+                    wave.push(index);
+                }
+            }
+
+            if wave.is_empty() {
+                if skipped {
+                    continue;
+                }
+                break;
+            }
+            let results =
+                futures::future::join_all(wave.iter().map(|&i| self.evaluate(intents[i].clone())))
+                    .await;
+            for (index, verdict) in wave.into_iter().zip(results) {
+                verdicts[index] = Some(verdict);
+            }
+        }
+
+        // 剩余的 Intent 处于依赖环中，无法评估
+        verdicts
+            .into_iter()
+            .zip(intents)
+            .enumerate()
+            .map(|(index, resolved)| match resolved {
+                (Some(verdict), _) => verdict,
+                (None, intent) => {
+                    let pre = prerequisites[index][0];
+                    Self::defer_on_prerequisite(
+                        intent,
+                        pre,
+                        ids[pre],
+                        "is part of a dependency cycle",
+                    )
+                }
+            })
+            .collect()
     }
 
     fn register_validator(&mut self, validator: Arc<dyn Validator>) {
@@ -223,11 +425,45 @@ impl DecisionEngine for BasicDecisionEngine {
     }
 
     fn policy_state(&self) -> PolicyState {
-        self.policy_state.clone()
+        PolicyState {
+            human_interventions: self.human_interventions.load(Ordering::Relaxed),
+            ..self.policy_state.clone()
+        }
     }
 }
 
 impl BasicDecisionEngine {
+    /// 前置 Verdict 是否阻止依赖项评估（被拒绝或被延迟）
+    fn blocking_reason(verdict: &Verdict) -> Option<String> {
+        match verdict {
+            Verdict::Deny { reason, .. } => Some(format!("was denied: {}", reason)),
+            Verdict::Defer { .. } => Some("was deferred".to_string()),
+            _ => None,
+        }
+    }
+
+    fn defer_on_prerequisite(
+        intent: Intent,
+        prerequisite: usize,
+        prerequisite_id: IntentId,
+        reason: &str,
+    ) -> Verdict {
+        Verdict::Defer {
+            action: intent.proposed_action,
+            required_info: vec![InformationRequirement {
+                description: format!(
+                    "Skipped: prerequisite intent #{} ({}) {}",
+                    prerequisite, prerequisite_id.0, reason
+                ),
+                source: InformationSource::Prerequisite {
+                    index: prerequisite,
+                    intent_id: prerequisite_id,
+                },
+            }],
+            retry_after: None,
+        }
+    }
+
     /// 计算所需权限等级
     fn calculate_required_privilege(&self, intent: &Intent) -> PrivilegeLevel {
         match &intent.proposed_action {
@@ -247,6 +483,13 @@ impl BasicDecisionEngine {
                 }
             }
             Action::DeleteFile { .. } => PrivilegeLevel::High,
+            Action::MoveFile { from, to } => {
+                if Self::is_config_file(from) || Self::is_config_file(to) {
+                    PrivilegeLevel::High
+                } else {
+                    PrivilegeLevel::Elevated
+                }
+            }
             Action::RunCommand { command, .. } => {
                 if Self::is_dangerous_command(command) {
                     PrivilegeLevel::Critical
@@ -273,6 +516,68 @@ impl BasicDecisionEngine {
         }
     }
 
+    /// 评估风险：以所需权限为基线，删除至少为 High，
+    /// 触及高波动路径时上调一级
+    fn assess_risk(&self, action: &Action, required: PrivilegeLevel) -> RiskLevel {
+        let mut risk = match required {
+            PrivilegeLevel::Normal => RiskLevel::Low,
+            PrivilegeLevel::Elevated => RiskLevel::Medium,
+            PrivilegeLevel::High => RiskLevel::High,
+            PrivilegeLevel::Critical => RiskLevel::Critical,
+        };
+        if matches!(action, Action::DeleteFile { .. }) && risk != RiskLevel::Critical {
+            risk = RiskLevel::High;
+        }
+        let volatile = Self::affected_files(action)
+            .iter()
+            .any(|path| self.path_volatility(path) >= HIGH_VOLATILITY_THRESHOLD);
+        // 只读动作不因波动度升级
+        if volatile && !matches!(action, Action::ReadFile { .. }) {
+            risk = match risk {
+                RiskLevel::Low => RiskLevel::Medium,
+                RiskLevel::Medium => RiskLevel::High,
+                _ => RiskLevel::Critical,
+            };
+        }
+        risk
+    }
+
+    /// 路径（或其所在目录）的最大波动度
+    fn path_volatility(&self, path: &Path) -> f64 {
+        self.volatility
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix))
+            .map(|(_, score)| *score)
+            .fold(0.0, f64::max)
+    }
+
+    /// 动作涉及的文件
+    fn affected_files(action: &Action) -> Vec<PathBuf> {
+        match action {
+            Action::ReadFile { path, .. }
+            | Action::WriteFile { path, .. }
+            | Action::CreateFile { path }
+            | Action::DeleteFile { path } => vec![path.clone()],
+            Action::MoveFile { from, to } => vec![from.clone(), to.clone()],
+            _ => vec![],
+        }
+    }
+
+    /// 用于人类确认问题的简短动作描述
+    fn describe_action(action: &Action) -> String {
+        match action {
+            Action::DeleteFile { path } => format!("delete {}", path.display()),
+            Action::WriteFile { path, .. } => format!("write {}", path.display()),
+            Action::MoveFile { from, to } => {
+                format!("move {} -> {}", from.display(), to.display())
+            }
+            Action::RunCommand { command, args } if args.is_empty() => format!("`{}`", command),
+            Action::RunCommand { command, args } => format!("`{} {}`", command, args.join(" ")),
+            Action::Git { operation } => format!("git {:?}", operation),
+            other => format!("{:?}", other),
+        }
+    }
+
     /// 判断是否为配置文件
     fn is_config_file(path: &std::path::Path) -> bool {
         let path_str = path.to_string_lossy();
@@ -303,7 +608,10 @@ impl BasicDecisionEngine {
 
         // 代码修改需要测试
         match intent.proposed_action {
-            Action::WriteFile { .. } | Action::CreateFile { .. } | Action::DeleteFile { .. } => {
+            Action::WriteFile { .. }
+            | Action::CreateFile { .. }
+            | Action::DeleteFile { .. }
+            | Action::MoveFile { .. } => {
                 conditions.push(Condition {
                     condition_type: ConditionType::MustPassTests,
                     description: "Code changes must pass tests".to_string(),
@@ -312,6 +620,27 @@ impl BasicDecisionEngine {
             _ => {}
         }
 
+        // 破坏性写入需要先备份，保证可回滚
+        let needs_backup = match &intent.proposed_action {
+            Action::DeleteFile { .. } => true,
+            Action::WriteFile { path, .. } => self.resolve_path(path).is_file(),
+            _ => false,
+        };
+        if needs_backup {
+            conditions.push(Condition {
+                condition_type: ConditionType::RequireBackup,
+                description: "Target file must be backed up before it is changed".to_string(),
+            });
+        }
+
         conditions
     }
+
+    /// 相对路径按项目根目录解析
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        match &self.project_root {
+            Some(root) => root.join(path),
+            None => path.to_path_buf(),
+        }
+    }
 }
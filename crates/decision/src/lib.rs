@@ -3,9 +3,12 @@
 // Decision & Policy Engine implementation
 
 pub mod engine;
+pub mod rate_limit;
 pub mod validators;
 
 pub use engine::*;
+pub use rate_limit::{RateLimitKey, RateLimitScope, RateLimitUsage, RateLimiter};
+pub use validators::{PathAllowlistValidator, PermissionValidator};
 
 #[cfg(test)]
 mod tests {
@@ -41,6 +44,7 @@ mod tests {
             agent_role: AgentRole::Historian,
             proposed_action: Action::ReadFile {
                 path: PathBuf::from("src/main.rs"),
+                range: None,
             },
             effects: vec![],
             reasoning: "Reading source file".to_string(),
@@ -224,6 +228,7 @@ mod tests {
                 agent_role: AgentRole::Historian,
                 proposed_action: Action::ReadFile {
                     path: PathBuf::from("test.rs"),
+                    range: None,
                 },
                 effects: vec![],
                 reasoning: "Reading".to_string(),
@@ -293,6 +298,7 @@ mod tests {
             agent_role: AgentRole::Historian,
             proposed_action: Action::ReadFile {
                 path: PathBuf::from("test.rs"),
+                range: None,
             },
             effects: vec![],
             reasoning: "Reading".to_string(),
@@ -340,6 +346,7 @@ mod tests {
             agent_role: AgentRole::Admin,
             proposed_action: Action::ReadFile {
                 path: PathBuf::from("test.rs"),
+                range: None,
             },
             effects: vec![],
             reasoning: "Admin reading".to_string(),
@@ -377,6 +384,7 @@ mod tests {
             ValidationResult::Modify(
                 Action::ReadFile {
                     path: PathBuf::from("test.rs"),
+                    range: None,
                 },
                 "modified".to_string(),
                 vec!["warning1".to_string()],
@@ -427,6 +435,47 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_destructive_writes_require_backup() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("existing.rs"), "fn a() {}").unwrap();
+        let engine = BasicDecisionEngine::new().with_project_root(temp_dir.path());
+
+        let intent = |action: Action| Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Admin,
+            proposed_action: action,
+            effects: vec![],
+            reasoning: "Changing files".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        };
+        let requires_backup = |verdict: ndc_core::Verdict| match verdict {
+            ndc_core::Verdict::Allow { conditions, .. } => conditions
+                .iter()
+                .any(|c| c.condition_type == ndc_core::ConditionType::RequireBackup),
+            other => panic!("Expected Allow verdict, got {:?}", other),
+        };
+
+        let overwrite = Action::WriteFile {
+            path: PathBuf::from("existing.rs"),
+            content: "fn b() {}".to_string(),
+        };
+        assert!(requires_backup(engine.evaluate(intent(overwrite)).await));
+
+        let delete = Action::DeleteFile {
+            path: PathBuf::from("existing.rs"),
+        };
+        assert!(requires_backup(engine.evaluate(intent(delete)).await));
+
+        let new_file = Action::WriteFile {
+            path: PathBuf::from("brand_new.rs"),
+            content: "fn c() {}".to_string(),
+        };
+        assert!(!requires_backup(engine.evaluate(intent(new_file)).await));
+    }
+
     #[tokio::test]
     async fn test_create_task_allowed() {
         let engine = BasicDecisionEngine::new();
@@ -496,6 +545,7 @@ mod tests {
                 ValidationResult::Modify(
                     Action::ReadFile {
                         path: PathBuf::from("modified.rs"),
+                        range: None,
                     },
                     "Modified for safety".to_string(),
                     vec!["Warning: path changed".to_string()],
@@ -519,6 +569,7 @@ mod tests {
             agent_role: AgentRole::Historian,
             proposed_action: Action::ReadFile {
                 path: PathBuf::from("original.rs"),
+                range: None,
             },
             effects: vec![],
             reasoning: "Original".to_string(),
@@ -535,7 +586,7 @@ mod tests {
                 ..
             } => {
                 match modified_action {
-                    Action::ReadFile { path } => {
+                    Action::ReadFile { path, .. } => {
                         assert_eq!(path, PathBuf::from("modified.rs"));
                     }
                     _ => panic!("Expected modified ReadFile action"),
@@ -546,6 +597,103 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_require_human_alternatives_substitute_action() {
+        // Validator offering two safer variants of a delete
+        struct AlternativesValidator;
+
+        #[async_trait::async_trait]
+        impl Validator for AlternativesValidator {
+            async fn validate(&self, intent: &Intent, _policy: &PolicyState) -> ValidationResult {
+                ValidationResult::RequireHuman(
+                    "Delete target/ ?".to_string(),
+                    ndc_core::HumanContext {
+                        task_id: intent.task_id,
+                        affected_files: vec![PathBuf::from("target")],
+                        risk_level: ndc_core::RiskLevel::High,
+                        alternatives: vec![
+                            Action::MoveFile {
+                                from: PathBuf::from("target"),
+                                to: PathBuf::from("target.bak"),
+                            },
+                            Action::RunCommand {
+                                command: "cargo".to_string(),
+                                args: vec!["clean".to_string()],
+                            },
+                        ],
+                        required_privilege: PrivilegeLevel::Elevated,
+                    },
+                )
+            }
+
+            fn name(&self) -> &str {
+                "alternatives_validator"
+            }
+            fn priority(&self) -> u32 {
+                1
+            }
+        }
+
+        let mut engine = BasicDecisionEngine::new();
+        engine.register_validator(Arc::new(AlternativesValidator));
+        let intent = Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Implementer,
+            proposed_action: Action::DeleteFile {
+                path: PathBuf::from("target"),
+            },
+            effects: vec![],
+            reasoning: "Clean up".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        };
+
+        let verdict = engine.evaluate(intent).await;
+        let ndc_core::Verdict::RequireHuman { context, .. } = &verdict else {
+            panic!("Expected RequireHuman verdict, got {:?}", verdict);
+        };
+        assert_eq!(context.alternatives.len(), 2);
+
+        match verdict
+            .clone()
+            .resolve_human(ndc_core::HumanDecision::Alternative(1))
+        {
+            ndc_core::Verdict::Allow {
+                action: Action::RunCommand { command, args },
+                privilege,
+                ..
+            } => {
+                assert_eq!(command, "cargo");
+                assert_eq!(args, vec!["clean".to_string()]);
+                assert_eq!(privilege, PrivilegeLevel::Elevated);
+            }
+            other => panic!("Expected the chosen alternative, got {:?}", other),
+        }
+        assert!(matches!(
+            verdict
+                .clone()
+                .resolve_human(ndc_core::HumanDecision::Approve),
+            ndc_core::Verdict::Allow {
+                action: Action::DeleteFile { .. },
+                ..
+            }
+        ));
+        assert!(matches!(
+            verdict
+                .clone()
+                .resolve_human(ndc_core::HumanDecision::Alternative(2)),
+            ndc_core::Verdict::Deny { .. }
+        ));
+        assert!(matches!(
+            verdict.resolve_human(ndc_core::HumanDecision::Deny),
+            ndc_core::Verdict::Deny {
+                action: Action::DeleteFile { .. },
+                ..
+            }
+        ));
+    }
+
     #[tokio::test]
     async fn test_verdict_defer() {
         struct DeferValidator;
@@ -579,6 +727,7 @@ mod tests {
             agent_role: AgentRole::Historian,
             proposed_action: Action::ReadFile {
                 path: PathBuf::from("test.rs"),
+                range: None,
             },
             effects: vec![],
             reasoning: "Test".to_string(),
@@ -660,4 +809,386 @@ mod tests {
             _ => panic!("Expected Deny verdict for Historian saving knowledge"),
         }
     }
+
+    fn move_intent(role: AgentRole, from: &str, to: &str) -> Intent {
+        Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: role,
+            proposed_action: Action::MoveFile {
+                from: PathBuf::from(from),
+                to: PathBuf::from(to),
+            },
+            effects: vec![],
+            reasoning: "Renaming module".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_move_file_requires_elevated() {
+        let engine = BasicDecisionEngine::new();
+
+        let verdict = engine
+            .evaluate(move_intent(
+                AgentRole::Implementer,
+                "src/old.rs",
+                "src/new.rs",
+            ))
+            .await;
+        match verdict {
+            ndc_core::Verdict::Allow { privilege, .. } => {
+                assert_eq!(privilege, PrivilegeLevel::Elevated);
+            }
+            _ => panic!("Expected Allow verdict for Implementer move"),
+        }
+
+        let verdict = engine
+            .evaluate(move_intent(
+                AgentRole::Historian,
+                "src/old.rs",
+                "src/new.rs",
+            ))
+            .await;
+        assert!(matches!(verdict, ndc_core::Verdict::Deny { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_move_config_file_requires_high() {
+        let engine = BasicDecisionEngine::new();
+
+        for (from, to) in [
+            ("Cargo.toml", "Cargo.toml.bak"),
+            ("notes.md", "config/app.toml"),
+        ] {
+            let verdict = engine
+                .evaluate(move_intent(AgentRole::Implementer, from, to))
+                .await;
+            match verdict {
+                ndc_core::Verdict::Deny { error_code, .. } => match error_code {
+                    ndc_core::ErrorCode::InsufficientPrivilege { required, .. } => {
+                        assert_eq!(required, PrivilegeLevel::High);
+                    }
+                    _ => panic!("Expected InsufficientPrivilege error code"),
+                },
+                _ => panic!("Expected Deny verdict moving {} -> {}", from, to),
+            }
+        }
+
+        let verdict = engine
+            .evaluate(move_intent(
+                AgentRole::Admin,
+                "Cargo.toml",
+                "Cargo.toml.bak",
+            ))
+            .await;
+        assert!(matches!(verdict, ndc_core::Verdict::Allow { .. }));
+    }
+
+    // ===== Rate Limiting Tests =====
+
+    fn write_intent(role: AgentRole, agent: AgentId) -> Intent {
+        Intent {
+            id: ndc_core::IntentId::new(),
+            agent,
+            agent_role: role,
+            proposed_action: Action::WriteFile {
+                path: PathBuf::from("src/lib.rs"),
+                content: "// edit".to_string(),
+            },
+            effects: vec![],
+            reasoning: "Editing".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn is_rate_limited(verdict: &ndc_core::Verdict) -> bool {
+        matches!(
+            verdict,
+            ndc_core::Verdict::Deny {
+                error_code: ndc_core::ErrorCode::RateLimited,
+                reason,
+                ..
+            } if reason.contains("Rate limited")
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_denies_over_limit_and_resets() {
+        let engine = BasicDecisionEngine::new()
+            .with_rate_limiter(RateLimiter::new(3, std::time::Duration::from_millis(80)));
+        let agent = AgentId::new();
+
+        for _ in 0..3 {
+            let verdict = engine
+                .evaluate(write_intent(AgentRole::Implementer, agent))
+                .await;
+            assert!(matches!(verdict, ndc_core::Verdict::Allow { .. }));
+        }
+        let verdict = engine
+            .evaluate(write_intent(AgentRole::Implementer, agent))
+            .await;
+        assert!(is_rate_limited(&verdict));
+
+        // Other roles have their own budget
+        let verdict = engine
+            .evaluate(write_intent(AgentRole::Admin, AgentId::new()))
+            .await;
+        assert!(matches!(verdict, ndc_core::Verdict::Allow { .. }));
+
+        let usage = engine.rate_limiter().unwrap().usage();
+        let implementer = usage
+            .iter()
+            .find(|u| u.key == RateLimitKey::Role(AgentRole::Implementer))
+            .unwrap();
+        assert_eq!((implementer.used, implementer.remaining), (3, 0));
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let verdict = engine
+            .evaluate(write_intent(AgentRole::Implementer, agent))
+            .await;
+        assert!(matches!(verdict, ndc_core::Verdict::Allow { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_applies_across_batch() {
+        let engine = BasicDecisionEngine::new().with_rate_limiter(
+            RateLimiter::new(10, std::time::Duration::from_secs(60))
+                .with_scope(RateLimitScope::PerAgent)
+                .with_role_limit(AgentRole::Implementer, 2),
+        );
+        let flooder = AgentId::new();
+        let other = AgentId::new();
+
+        let verdicts = engine
+            .evaluate_batch(vec![
+                write_intent(AgentRole::Implementer, flooder),
+                write_intent(AgentRole::Implementer, flooder),
+                write_intent(AgentRole::Implementer, other),
+                write_intent(AgentRole::Implementer, flooder),
+            ])
+            .await;
+
+        let limited: Vec<bool> = verdicts.iter().map(is_rate_limited).collect();
+        assert_eq!(limited, vec![false, false, false, true]);
+        assert_eq!(engine.rate_limiter().unwrap().usage().len(), 2);
+    }
+
+    // ===== Batch Dependency Tests =====
+
+    /// Denies every file creation
+    struct DenyCreateValidator;
+
+    #[async_trait::async_trait]
+    impl Validator for DenyCreateValidator {
+        async fn validate(&self, intent: &Intent, _policy: &PolicyState) -> ValidationResult {
+            match intent.proposed_action {
+                Action::CreateFile { .. } => ValidationResult::Deny("create blocked".to_string()),
+                _ => ValidationResult::Allow,
+            }
+        }
+
+        fn name(&self) -> &str {
+            "deny_create"
+        }
+
+        fn priority(&self) -> u32 {
+            1
+        }
+    }
+
+    fn create_intent() -> Intent {
+        Intent {
+            proposed_action: Action::CreateFile {
+                path: PathBuf::from("src/new.rs"),
+            },
+            ..write_intent(AgentRole::Implementer, AgentId::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strict_batch_defers_write_after_denied_create() {
+        let mut engine = BasicDecisionEngine::new().with_strict_mode(true);
+        engine.register_validator(Arc::new(DenyCreateValidator));
+        let create = create_intent();
+        let create_id = create.id;
+
+        let verdicts = engine
+            .evaluate_batch_with_dependencies(
+                vec![
+                    create,
+                    write_intent(AgentRole::Implementer, AgentId::new()),
+                    write_intent(AgentRole::Implementer, AgentId::new()),
+                ],
+                &[IntentDependency::new(1, 0)],
+            )
+            .await;
+
+        assert!(matches!(verdicts[0], ndc_core::Verdict::Deny { .. }));
+        match &verdicts[1] {
+            ndc_core::Verdict::Defer {
+                action,
+                required_info,
+                ..
+            } => {
+                assert!(matches!(action, Action::WriteFile { .. }));
+                assert!(required_info[0].description.contains("#0"));
+                assert!(required_info[0].description.contains("create blocked"));
+                assert!(matches!(
+                    required_info[0].source,
+                    ndc_core::InformationSource::Prerequisite { index: 0, intent_id }
+                        if intent_id == create_id
+                ));
+            }
+            other => panic!("Expected Defer for dependent write, got {:?}", other),
+        }
+        // The independent write is still evaluated
+        assert!(matches!(verdicts[2], ndc_core::Verdict::Allow { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_batch_dependencies_chain_and_non_strict_mode() {
+        let strict = {
+            let mut engine = BasicDecisionEngine::new().with_strict_mode(true);
+            engine.register_validator(Arc::new(DenyCreateValidator));
+            engine
+        };
+        let chain = [IntentDependency::new(1, 0), IntentDependency::new(2, 1)];
+        let intents = || {
+            vec![
+                create_intent(),
+                write_intent(AgentRole::Implementer, AgentId::new()),
+                write_intent(AgentRole::Implementer, AgentId::new()),
+            ]
+        };
+
+        // The skip propagates down the chain
+        let verdicts = strict
+            .evaluate_batch_with_dependencies(intents(), &chain)
+            .await;
+        assert!(matches!(verdicts[2], ndc_core::Verdict::Defer { .. }));
+
+        // Without strict mode every intent is evaluated on its own
+        let mut lenient = BasicDecisionEngine::new();
+        lenient.register_validator(Arc::new(DenyCreateValidator));
+        let verdicts = lenient
+            .evaluate_batch_with_dependencies(intents(), &chain)
+            .await;
+        assert!(matches!(verdicts[0], ndc_core::Verdict::Deny { .. }));
+        assert!(matches!(verdicts[1], ndc_core::Verdict::Allow { .. }));
+        assert!(matches!(verdicts[2], ndc_core::Verdict::Allow { .. }));
+    }
+
+    fn action_intent(role: AgentRole, action: Action) -> Intent {
+        Intent {
+            proposed_action: action,
+            ..write_intent(role, AgentId::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_high_risk_delete_requires_human_review() {
+        let engine = BasicDecisionEngine::new().with_human_review_for_high_risk(true);
+
+        let delete = action_intent(
+            AgentRole::Admin,
+            Action::DeleteFile {
+                path: PathBuf::from("src/old.rs"),
+            },
+        );
+        match engine.evaluate(delete).await {
+            ndc_core::Verdict::RequireHuman {
+                action, context, ..
+            } => {
+                assert!(matches!(action, Action::DeleteFile { .. }));
+                assert_eq!(context.affected_files, vec![PathBuf::from("src/old.rs")]);
+                assert_eq!(context.risk_level, ndc_core::RiskLevel::High);
+                assert_eq!(context.required_privilege, PrivilegeLevel::High);
+            }
+            other => panic!("Expected RequireHuman for delete, got {:?}", other),
+        }
+
+        let read = action_intent(
+            AgentRole::Admin,
+            Action::ReadFile {
+                path: PathBuf::from("src/old.rs"),
+                range: None,
+            },
+        );
+        assert!(matches!(
+            engine.evaluate(read).await,
+            ndc_core::Verdict::Allow { .. }
+        ));
+        assert_eq!(engine.policy_state().human_interventions, 1);
+
+        // Without the policy the delete is allowed as before
+        let lenient = BasicDecisionEngine::new();
+        let delete = action_intent(
+            AgentRole::Admin,
+            Action::DeleteFile {
+                path: PathBuf::from("src/old.rs"),
+            },
+        );
+        assert!(matches!(
+            lenient.evaluate(delete).await,
+            ndc_core::Verdict::Allow { .. }
+        ));
+        assert_eq!(lenient.policy_state().human_interventions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_volatile_paths_raise_risk() {
+        let engine = BasicDecisionEngine::new()
+            .with_human_review_for_high_risk(true)
+            .with_path_volatility("src/core", 0.9);
+        let move_file = |to: &str| {
+            action_intent(
+                AgentRole::Implementer,
+                Action::MoveFile {
+                    from: PathBuf::from("src/a.rs"),
+                    to: PathBuf::from(to),
+                },
+            )
+        };
+
+        // An Elevated (Medium risk) move becomes High inside a volatile module
+        assert!(matches!(
+            engine.evaluate(move_file("src/b.rs")).await,
+            ndc_core::Verdict::Allow { .. }
+        ));
+        match engine.evaluate(move_file("src/core/b.rs")).await {
+            ndc_core::Verdict::RequireHuman { context, .. } => {
+                assert_eq!(context.risk_level, ndc_core::RiskLevel::High);
+                assert_eq!(context.affected_files.len(), 2);
+            }
+            other => panic!("Expected RequireHuman for volatile move, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ranged_read_is_normal_privilege() {
+        let engine = BasicDecisionEngine::new().with_human_review_for_high_risk(true);
+        let read = action_intent(
+            AgentRole::Historian,
+            Action::ReadFile {
+                path: PathBuf::from("assets/logo.png"),
+                range: Some(ndc_core::ByteRange {
+                    start: 0,
+                    end: Some(4096),
+                }),
+            },
+        );
+
+        match engine.evaluate(read).await {
+            ndc_core::Verdict::Allow {
+                action, privilege, ..
+            } => {
+                assert!(matches!(action, Action::ReadFile { range: Some(_), .. }));
+                assert_eq!(privilege, PrivilegeLevel::Normal);
+            }
+            other => panic!("Expected Allow for ranged read, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,172 @@
+//! Rate Limiter - 按角色或 Agent 限制 Intent 频率
+//!
+//! 固定窗口计数：每个 key 在 `window` 内最多放行 `max_intents` 个 Intent，
+//! 超出后返回 `ValidationResult::Deny`，窗口到期后计数清零。
+
+use crate::engine::ValidationResult;
+use ndc_core::{AgentId, AgentRole, Intent};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 限流维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateLimitScope {
+    /// 同一角色的所有 Agent 共享额度
+    #[default]
+    PerRole,
+    /// 每个 Agent 独立额度
+    PerAgent,
+}
+
+/// 限流计数的 key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    Role(AgentRole),
+    Agent(AgentId),
+}
+
+/// 某个 key 当前窗口的使用情况
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitUsage {
+    pub key: RateLimitKey,
+    pub used: u32,
+    pub remaining: u32,
+    /// 距窗口重置的时间
+    pub resets_in: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WindowState {
+    started: Instant,
+    count: u32,
+    limit: u32,
+}
+
+/// Intent 限流器
+#[derive(Debug)]
+pub struct RateLimiter {
+    max_intents: u32,
+    window: Duration,
+    scope: RateLimitScope,
+    /// 按角色覆盖的额度
+    role_limits: HashMap<AgentRole, u32>,
+    windows: Mutex<HashMap<RateLimitKey, WindowState>>,
+}
+
+impl RateLimiter {
+    /// 每个窗口最多 `max_intents` 个 Intent（默认按角色）
+    pub fn new(max_intents: u32, window: Duration) -> Self {
+        Self {
+            max_intents,
+            window,
+            scope: RateLimitScope::default(),
+            role_limits: HashMap::new(),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_scope(mut self, scope: RateLimitScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// 为某个角色单独设置额度
+    pub fn with_role_limit(mut self, role: AgentRole, max_intents: u32) -> Self {
+        self.role_limits.insert(role, max_intents);
+        self
+    }
+
+    pub fn scope(&self) -> RateLimitScope {
+        self.scope
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// 检查并计入一个 Intent；被拒绝的 Intent 不占用额度
+    pub fn check(&self, intent: &Intent) -> ValidationResult {
+        let key = self.key_for(intent);
+        let limit = self.limit_for(intent.agent_role);
+        let now = Instant::now();
+
+        let mut windows = self.lock();
+        let state = windows.entry(key).or_insert(WindowState {
+            started: now,
+            count: 0,
+            limit,
+        });
+        if now.duration_since(state.started) >= self.window {
+            state.started = now;
+            state.count = 0;
+        }
+        state.limit = limit;
+
+        if state.count >= limit {
+            let resets_in = self
+                .window
+                .saturating_sub(now.duration_since(state.started));
+            return ValidationResult::Deny(format!(
+                "Rate limited: {} exceeded {} intents per {:?} (resets in {:?})",
+                describe_key(&key),
+                limit,
+                self.window,
+                resets_in
+            ));
+        }
+
+        state.count += 1;
+        ValidationResult::Allow
+    }
+
+    /// 当前各 key 的使用情况（已过期的窗口不返回）
+    pub fn usage(&self) -> Vec<RateLimitUsage> {
+        let now = Instant::now();
+        self.lock()
+            .iter()
+            .filter_map(|(key, state)| {
+                let elapsed = now.duration_since(state.started);
+                if elapsed >= self.window {
+                    return None;
+                }
+                Some(RateLimitUsage {
+                    key: *key,
+                    used: state.count,
+                    remaining: state.limit.saturating_sub(state.count),
+                    resets_in: self.window - elapsed,
+                })
+            })
+            .collect()
+    }
+
+    /// 清空所有计数
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn key_for(&self, intent: &Intent) -> RateLimitKey {
+        match self.scope {
+            RateLimitScope::PerRole => RateLimitKey::Role(intent.agent_role),
+            RateLimitScope::PerAgent => RateLimitKey::Agent(intent.agent),
+        }
+    }
+
+    fn limit_for(&self, role: AgentRole) -> u32 {
+        self.role_limits
+            .get(&role)
+            .copied()
+            .unwrap_or(self.max_intents)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<RateLimitKey, WindowState>> {
+        self.windows.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn describe_key(key: &RateLimitKey) -> String {
+    match key {
+        RateLimitKey::Role(role) => format!("role {:?}", role),
+        RateLimitKey::Agent(agent) => format!("agent {}", agent.0),
+    }
+}
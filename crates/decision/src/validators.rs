@@ -4,10 +4,13 @@
 //! - PermissionValidator: 确保 Agent 有执行权限
 //! - SecurityPolicyValidator: 防止危险操作
 //! - DependencyValidator: 确保前置条件满足
+//! - PathAllowlistValidator: 限制文件写操作只能落在允许的路径内
 
 use crate::engine::{PolicyState, ValidationResult};
 use async_trait::async_trait;
+use glob::{MatchOptions, Pattern, PatternError};
 use ndc_core::{AgentRole, Intent};
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
 /// 校验器 Trait
@@ -55,15 +58,19 @@ impl Validator for PermissionValidator {
         // 检查角色是否有权限执行此操作
         match &intent.proposed_action {
             // 删除文件只有 Human 或特定角色可以做
-            ndc_core::Action::DeleteFile { .. } => {
+            ndc_core::Action::DeleteFile { path } => {
                 if intent.agent_role != AgentRole::Admin {
                     return ValidationResult::RequireHuman(
                         "Delete file operation requires human approval".to_string(),
                         ndc_core::HumanContext {
                             task_id: intent.task_id,
-                            affected_files: vec![],
+                            affected_files: vec![path.clone()],
                             risk_level: ndc_core::RiskLevel::Critical,
-                            alternatives: vec![],
+                            // 可恢复的删除：改名为 .bak
+                            alternatives: vec![ndc_core::Action::MoveFile {
+                                from: path.clone(),
+                                to: with_suffix(path, ".bak"),
+                            }],
                             required_privilege: ndc_core::PrivilegeLevel::Critical,
                         },
                     );
@@ -71,9 +78,11 @@ impl Validator for PermissionValidator {
             }
 
             // 修改系统配置需要提升权限
-            ndc_core::Action::WriteFile { path, .. } => {
-                let is_config = path.to_string_lossy().contains("Cargo.toml")
-                    || path.to_string_lossy().contains("package.json");
+            ndc_core::Action::WriteFile { path, content } => {
+                // 只看文件名：`.proposed` 替代方案本身不应再次触发确认
+                let is_config = path
+                    .file_name()
+                    .is_some_and(|name| name == "Cargo.toml" || name == "package.json");
                 if is_config && intent.agent_role != AgentRole::Admin {
                     return ValidationResult::RequireHuman(
                         "System configuration modification requires human approval".to_string(),
@@ -81,7 +90,11 @@ impl Validator for PermissionValidator {
                             task_id: intent.task_id,
                             affected_files: vec![path.clone()],
                             risk_level: ndc_core::RiskLevel::Medium,
-                            alternatives: vec![],
+                            // 写到旁边的 .proposed 文件，由人类审阅后合并
+                            alternatives: vec![ndc_core::Action::WriteFile {
+                                path: with_suffix(path, ".proposed"),
+                                content: content.clone(),
+                            }],
                             required_privilege: ndc_core::PrivilegeLevel::Elevated,
                         },
                     );
@@ -105,6 +118,29 @@ impl Validator for PermissionValidator {
     }
 }
 
+/// 决策引擎中的 PermissionValidator，使其替代方案能进入 REPL/TUI 的确认流程
+#[async_trait]
+impl crate::engine::Validator for PermissionValidator {
+    async fn validate(&self, intent: &Intent, policy: &PolicyState) -> ValidationResult {
+        Validator::validate(self, intent, policy).await
+    }
+
+    fn name(&self) -> &str {
+        "permission"
+    }
+
+    fn priority(&self) -> u32 {
+        20
+    }
+}
+
+/// `path` with `suffix` appended to its file name (`Cargo.toml` → `Cargo.toml.bak`)
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
 /// 安全策略校验器
 #[derive(Debug, Default)]
 pub struct SecurityPolicyValidator;
@@ -172,6 +208,146 @@ impl Validator for DependencyValidator {
     }
 }
 
+/// 路径白名单校验器
+///
+/// 对 WriteFile / CreateFile / DeleteFile / MoveFile（两端）生效：目标路径先相对 `root` 解析，
+/// 消除 `..` 并跟随已存在的符号链接，然后用相对 `root` 的路径匹配 glob 模式。
+/// 落在 `root` 之外或不匹配任何模式的路径一律拒绝。
+#[derive(Debug)]
+pub struct PathAllowlistValidator {
+    root: PathBuf,
+    patterns: Vec<Pattern>,
+    priority: u32,
+}
+
+impl PathAllowlistValidator {
+    /// 默认优先级，早于其他内置校验和权限检查执行
+    pub const DEFAULT_PRIORITY: u32 = 0;
+
+    /// 使用项目根目录和 glob 模式（如 `src/**/*.rs`）创建
+    pub fn new<I, S>(root: impl Into<PathBuf>, patterns: I) -> Result<Self, PatternError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let root = root.into();
+        let root = root
+            .canonicalize()
+            .unwrap_or_else(|_| normalize_lexically(&root));
+        let patterns = patterns
+            .into_iter()
+            .map(|p| Pattern::new(p.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            root,
+            patterns,
+            priority: Self::DEFAULT_PRIORITY,
+        })
+    }
+
+    /// 设置优先级（数字越小越先执行）
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 判断路径是否在白名单内
+    pub fn is_allowed(&self, path: &Path) -> bool {
+        let Some(relative) = self.resolve_relative(path) else {
+            return false;
+        };
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path_with(&relative, options))
+    }
+
+    /// 解析为相对 `root` 的路径；逃逸出 `root` 时返回 None
+    fn resolve_relative(&self, path: &Path) -> Option<PathBuf> {
+        let joined = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        };
+        let resolved = resolve_symlinks(&normalize_lexically(&joined));
+        resolved
+            .strip_prefix(&self.root)
+            .ok()
+            .map(Path::to_path_buf)
+    }
+}
+
+#[async_trait]
+impl crate::engine::Validator for PathAllowlistValidator {
+    async fn validate(&self, intent: &Intent, _policy: &PolicyState) -> ValidationResult {
+        let paths = match &intent.proposed_action {
+            ndc_core::Action::WriteFile { path, .. }
+            | ndc_core::Action::CreateFile { path }
+            | ndc_core::Action::DeleteFile { path } => vec![path],
+            ndc_core::Action::MoveFile { from, to } => vec![from, to],
+            _ => return ValidationResult::Allow,
+        };
+
+        match paths.into_iter().find(|path| !self.is_allowed(path)) {
+            None => ValidationResult::Allow,
+            Some(path) => ValidationResult::Deny(format!(
+                "Path '{}' is outside the write allowlist",
+                path.display()
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "path_allowlist"
+    }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+}
+
+/// 纯字面地消除 `.` 和 `..`（不访问文件系统）
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// 规范化最深的已存在祖先目录，使符号链接逃逸可见；不存在的尾部原样拼接
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut tail = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return tail
+                .iter()
+                .rev()
+                .fold(canonical, |acc: PathBuf, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                tail.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
 /// 校验器注册表
 #[derive(Debug, Default)]
 pub struct ValidatorRegistry {
@@ -193,3 +369,110 @@ impl ValidatorRegistry {
         self.validators.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Validator as _;
+    use ndc_core::{Action, AgentId};
+    use tempfile::TempDir;
+
+    fn write_intent(path: &str) -> Intent {
+        Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Implementer,
+            proposed_action: Action::WriteFile {
+                path: PathBuf::from(path),
+                content: String::new(),
+            },
+            effects: vec![],
+            reasoning: "Writing".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn allowlist(root: &Path) -> PathAllowlistValidator {
+        PathAllowlistValidator::new(root, ["src/**/*.rs", "Cargo.toml"]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_path_allowlist_glob_matching() {
+        let dir = TempDir::new().unwrap();
+        let validator = allowlist(dir.path());
+        let policy = PolicyState::default();
+
+        for allowed in [
+            "src/lib.rs",
+            "src/a/b/mod.rs",
+            "./src/main.rs",
+            "Cargo.toml",
+        ] {
+            let result = validator.validate(&write_intent(allowed), &policy).await;
+            assert!(matches!(result, ValidationResult::Allow), "{}", allowed);
+        }
+        for denied in ["src/notes.md", "tests/it.rs", "build.rs"] {
+            let result = validator.validate(&write_intent(denied), &policy).await;
+            assert!(matches!(result, ValidationResult::Deny(_)), "{}", denied);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_path_allowlist_rejects_relative_escape() {
+        let dir = TempDir::new().unwrap();
+        let validator = allowlist(dir.path());
+        let policy = PolicyState::default();
+
+        let result = validator
+            .validate(&write_intent("src/../../etc/passwd"), &policy)
+            .await;
+        assert!(matches!(result, ValidationResult::Deny(_)));
+        // `..` that stays inside the root is resolved before matching.
+        assert!(validator.is_allowed(Path::new("src/x/../lib.rs")));
+    }
+
+    #[tokio::test]
+    async fn test_path_allowlist_absolute_paths() {
+        let dir = TempDir::new().unwrap();
+        let validator = allowlist(dir.path());
+
+        let inside = dir.path().join("src/lib.rs");
+        assert!(validator.is_allowed(&inside));
+        assert!(!validator.is_allowed(Path::new("/etc/passwd")));
+        assert!(!validator.is_allowed(&dir.path().join("../src/lib.rs")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_allowlist_rejects_symlink_escape() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("src/link")).unwrap();
+
+        let validator = allowlist(dir.path());
+        assert!(!validator.is_allowed(Path::new("src/link/evil.rs")));
+    }
+
+    #[tokio::test]
+    async fn test_path_allowlist_ignores_other_actions_and_priority() {
+        let dir = TempDir::new().unwrap();
+        let validator = allowlist(dir.path()).with_priority(7);
+        assert_eq!(validator.priority(), 7);
+
+        let mut intent = write_intent("ignored");
+        intent.proposed_action = Action::ReadFile {
+            path: PathBuf::from("/etc/passwd"),
+            range: None,
+        };
+        let result = validator.validate(&intent, &PolicyState::default()).await;
+        assert!(matches!(result, ValidationResult::Allow));
+
+        intent.proposed_action = Action::DeleteFile {
+            path: PathBuf::from("/etc/passwd"),
+        };
+        let result = validator.validate(&intent, &PolicyState::default()).await;
+        assert!(matches!(result, ValidationResult::Deny(_)));
+    }
+}
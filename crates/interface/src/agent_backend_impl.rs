@@ -7,8 +7,11 @@
 use std::path::PathBuf;
 
 use async_trait::async_trait;
-use ndc_core::{AgentExecutionEvent, AgentResponse, AgentSessionExecutionEvent, ModelInfo, Task, TaskState};
+use ndc_core::{
+    AgentExecutionEvent, AgentResponse, AgentSessionExecutionEvent, ModelInfo, Task, TaskState,
+};
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 use ndc_tui::{
     AgentBackend, AgentStatus, ProjectCandidate, ProjectSwitchInfo, TodoItem, TodoState,
@@ -66,6 +69,14 @@ impl AgentBackend for AgentModeManager {
         Ok(self.process_input(input).await?)
     }
 
+    async fn process_input_with_cancellation(
+        &self,
+        input: &str,
+        cancel: CancellationToken,
+    ) -> anyhow::Result<AgentResponse> {
+        Ok(self.process_input_with_cancellation(input, cancel).await?)
+    }
+
     async fn switch_provider(&self, provider: &str, model: Option<&str>) -> anyhow::Result<()> {
         Ok(self.switch_provider(provider, model).await?)
     }
@@ -133,6 +144,7 @@ impl AgentBackend for AgentModeManager {
                 let tui_req = TuiPermissionRequest {
                     description: req.description,
                     permission_key: req.permission_key,
+                    alternatives: req.alternatives,
                     response_tx: req.response_tx,
                 };
                 if tx.send(tui_req).await.is_err() {
@@ -210,10 +222,7 @@ impl AgentBackend for AgentModeManager {
         })
     }
 
-    async fn create_todos(
-        &self,
-        items: Vec<(String, String)>,
-    ) -> anyhow::Result<Vec<TodoItem>> {
+    async fn create_todos(&self, items: Vec<(String, String)>) -> anyhow::Result<Vec<TodoItem>> {
         let status = self.status().await;
         let project_id = status.project_id.unwrap_or_default();
         let session_id = status.session_id.unwrap_or_default();
@@ -236,11 +245,7 @@ impl AgentBackend for AgentModeManager {
         Ok(result)
     }
 
-    async fn update_todo_state(
-        &self,
-        index: usize,
-        state: TodoState,
-    ) -> anyhow::Result<()> {
+    async fn update_todo_state(&self, index: usize, state: TodoState) -> anyhow::Result<()> {
         let todos = self.list_session_todos().await?;
         let item = todos
             .iter()
@@ -16,23 +16,28 @@ use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{Mutex, broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
 use ndc_core::{
     AbstractHistory, AgentConfig, AgentError, AgentOrchestrator, AgentRequest, AgentResponse,
-    AgentRole, ApiSurface, FailurePattern, InvariantPriority, LlmProvider, ModelInfo,
-    NdcConfigLoader, ProviderType, RawCurrent, StepContext, SubTaskId, TaskId, TaskStorage,
-    TaskVerifier, TrajectoryState, VersionedInvariant, WorkingMemory,
+    AgentRole, ApiSurface, ContextBuilder, FailurePattern, InvariantPriority, LlmProvider,
+    ModelInfo, NdcConfig, NdcConfigLoader, RawCurrent, StepContext, SubTaskId, TaskId, TaskStorage,
+    TaskVerifier, TrajectoryState, VersionedInvariant, WorkingMemory, YamlLlmConfig,
+};
+use ndc_runtime::{
+    ExecutionContext, Executor, SharedStorage, WorkflowEngine,
+    tools::{RoleToolPolicy, ToolRegistry},
 };
-use ndc_runtime::{Executor, SharedStorage, tools::ToolRegistry};
 
-use crate::provider_config::{create_provider_config, is_minimax_family};
+use crate::provider_config::{create_provider, is_minimax_family};
 
 use crate::project_index::{
     ProjectIndexStore, build_project_scoped_session_id, canonicalize_existing_dir,
     discover_project_directories,
 };
 
+use crate::daemon::{SessionWorkspace, SessionWorkspaces};
 use crate::session_archive::SessionArchiveStore;
 
 /// Runtime storage adapter - 给 TaskVerifier 使用同一份任务存储
@@ -111,6 +116,15 @@ pub struct AgentModeConfig {
 
     /// 权限规则: 操作 -> allow/ask/deny
     pub permissions: HashMap<String, PermissionRule>,
+
+    /// 按角色限定暴露给 LLM 的工具
+    pub role_tools: RoleToolPolicy,
+
+    /// 永久批准的安全权限（配置项 `approved_permissions`）
+    pub approved_permissions: Vec<String>,
+
+    /// 会话角色，决定暴露给 LLM 的工具
+    pub role: AgentRole,
 }
 
 pub use crate::permission_engine::PermissionRule;
@@ -141,21 +155,55 @@ impl Default for AgentModeConfig {
             enable_streaming: true,
             auto_verify: true,
             permissions,
+            role_tools: RoleToolPolicy::default(),
+            approved_permissions: Vec::new(),
+            role: AgentRole::Implementer,
         };
 
         // Prefer configured provider/model when available.
         let mut loader = NdcConfigLoader::new();
-        if loader.load().is_ok()
-            && let Some(llm) = loader.config().llm.as_ref()
-        {
-            config.provider = llm.provider.clone();
-            config.model = llm.model.clone();
+        if loader.load().is_ok() {
+            config.apply_ndc_config(loader.config());
         }
 
         config
     }
 }
 
+impl AgentModeConfig {
+    /// 用配置文件中的 LLM 设置、已批准权限和同名 agent 的工具权限规则覆盖当前值
+    pub fn apply_ndc_config(&mut self, ndc_config: &NdcConfig) {
+        if let Some(llm) = ndc_config.llm.as_ref() {
+            self.provider = llm.provider.clone();
+            self.model = llm.model.clone();
+            self.temperature = llm.temperature;
+        }
+        self.approved_permissions = ndc_config.approved_permissions.clone();
+
+        let tool_permissions = ndc_config
+            .agents
+            .iter()
+            .find(|profile| profile.name == self.agent_name)
+            .and_then(|profile| profile.tool_permissions.as_ref());
+        if let Some(tool_permissions) = tool_permissions {
+            let rules = std::iter::once(("*", &tool_permissions.default)).chain(
+                tool_permissions
+                    .tools
+                    .iter()
+                    .map(|(tool, rule)| (tool.as_str(), rule)),
+            );
+            for (tool, rule) in rules {
+                match PermissionRule::parse(rule) {
+                    Some(rule) => {
+                        self.permissions.insert(tool.to_string(), rule);
+                    }
+                    None => tracing::warn!(tool, rule = %rule, "Ignoring unknown permission rule"),
+                }
+            }
+        }
+    }
+}
+
 /// Agent REPL 模式状态
 #[derive(Debug, Clone, Default)]
 pub struct AgentModeState {
@@ -182,6 +230,9 @@ pub struct AgentModeState {
 
     /// 当前工作树根目录
     pub worktree: Option<PathBuf>,
+
+    /// 最近一次热加载应用的 LLM 配置
+    pub applied_llm: Option<YamlLlmConfig>,
 }
 
 /// Agent REPL 模式管理器
@@ -195,8 +246,8 @@ pub struct AgentModeManager {
     /// Runtime Executor (保留供未来使用)
     _executor: Arc<Executor>,
 
-    /// Tool Registry
-    tool_registry: Arc<ToolRegistry>,
+    /// Tool Registry（热加载 MCP 配置时整体替换）
+    tool_registry: std::sync::RwLock<Arc<ToolRegistry>>,
 
     /// Runtime working directory shared with tool executor.
     runtime_working_dir: Arc<Mutex<Option<PathBuf>>>,
@@ -209,6 +260,15 @@ pub struct AgentModeManager {
 
     /// Channel sender for TUI permission prompts.
     permission_tx: Arc<Mutex<Option<mpsc::Sender<PermissionRequest>>>>,
+
+    /// Per-session workspaces configured on the daemon.
+    session_workspaces: Option<SessionWorkspaces>,
+
+    /// Workspace of the turn in progress, shared with the tool executor.
+    active_workspace: Arc<Mutex<Option<SessionWorkspace>>>,
+
+    /// File changes of the turn in progress, undone if it is cancelled.
+    run_saga: RunSaga,
 }
 
 impl AgentModeManager {
@@ -217,20 +277,52 @@ impl AgentModeManager {
         Self {
             state: Arc::new(Mutex::new(AgentModeState::default())),
             orchestrator: Arc::new(Mutex::new(None)),
+            run_saga: RunSaga::new(executor.clone()),
             _executor: executor,
-            tool_registry,
+            tool_registry: std::sync::RwLock::new(tool_registry),
             runtime_working_dir: Arc::new(Mutex::new(None)),
             project_index: Arc::new(Mutex::new(ProjectIndexStore::load_default())),
             session_archive: Arc::new(Mutex::new(SessionArchiveStore::load_default())),
             permission_tx: Arc::new(Mutex::new(None)),
+            session_workspaces: None,
+            active_workspace: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Run turns of sessions that have a workspace inside that workspace.
+    pub fn with_session_workspaces(mut self, workspaces: SessionWorkspaces) -> Self {
+        self.session_workspaces = Some(workspaces);
+        self
+    }
+
+    /// Keep the session archive in `path` instead of the user's default file.
+    pub fn with_session_archive_file(mut self, path: PathBuf) -> Self {
+        self.session_archive = Arc::new(Mutex::new(SessionArchiveStore::load_from(path)));
+        self
+    }
+
+    fn session_workspace(&self, session_id: Option<&str>) -> Option<SessionWorkspace> {
+        let workspaces = self.session_workspaces.as_ref()?;
+        workspaces
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session_id?)
+            .cloned()
+    }
+
     /// Set the TUI permission channel for interactive confirmation.
     pub async fn set_permission_channel(&self, tx: mpsc::Sender<PermissionRequest>) {
         *self.permission_tx.lock().await = Some(tx);
     }
 
+    /// 当前工具注册表
+    pub fn tool_registry(&self) -> Arc<ToolRegistry> {
+        self.tool_registry
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
     /// Access the shared storage backend.
     pub fn storage(&self) -> SharedStorage {
         self._executor.context().storage.clone()
@@ -245,13 +337,20 @@ impl AgentModeManager {
 
         // 创建 Agent Orchestrator
         let mut executor = ReplToolExecutor::new(
-            self.tool_registry.clone(),
+            self.tool_registry(),
             config.permissions.clone(),
             self.runtime_working_dir.clone(),
-        );
+        )
+        .with_role_scope(config.role, config.role_tools.clone())
+        .with_workspace(self.active_workspace.clone())
+        .with_run_saga(self.run_saga.clone())
+        .with_approved_permissions(config.approved_permissions.clone());
         if let Some(tx) = self.permission_tx.lock().await.clone() {
             executor = executor.with_permission_channel(tx);
         }
+        if let Some(engine) = self._executor.context().decision_engine.clone() {
+            executor = executor.with_decision_engine(engine);
+        }
         let tool_executor = Arc::new(executor);
         let provider = self.create_provider(&config.provider, &config.model)?;
 
@@ -272,6 +371,20 @@ impl AgentModeManager {
 
         let orchestrator = AgentOrchestrator::new(provider, tool_executor, verifier, agent_config);
         self.hydrate_orchestrator_sessions(&orchestrator).await;
+        tokio::spawn(forward_workflow_stages(
+            orchestrator.subscribe_execution_events(),
+            self._executor.context().workflow_engine.clone(),
+        ));
+        tokio::spawn(persist_session_events(
+            orchestrator.subscribe_execution_events(),
+            self.orchestrator.clone(),
+            self.session_archive.clone(),
+        ));
+        tokio::spawn(forward_runtime_warnings(
+            self.subscribe_runtime_warnings(),
+            self.state.clone(),
+            orchestrator.clone(),
+        ));
 
         let restored_session_id = orchestrator
             .latest_session_id_for_project(detected_identity.project_id.as_str())
@@ -303,6 +416,24 @@ impl AgentModeManager {
         Ok(())
     }
 
+    /// Warnings of the executor's `VerdictModified` events; replaces the
+    /// listener of an earlier `enable`
+    fn subscribe_runtime_warnings(&self) -> mpsc::UnboundedReceiver<ndc_core::AgentExecutionEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let events = &self._executor.context().events;
+        events.off(RUNTIME_WARNINGS_LISTENER);
+        events.on(
+            RUNTIME_WARNINGS_LISTENER.to_string(),
+            vec![ndc_runtime::EventType::VerdictModified],
+            move |event| {
+                for warning in event.warning_events() {
+                    let _ = tx.send(warning);
+                }
+            },
+        );
+        rx
+    }
+
     /// 禁用 Agent 模式
     pub async fn disable(&self) {
         let mut state = self.state.lock().await;
@@ -331,6 +462,16 @@ impl AgentModeManager {
 
     /// 处理用户输入 (非流式)
     pub async fn process_input(&self, input: &str) -> Result<AgentResponse, AgentError> {
+        self.process_input_with_cancellation(input, CancellationToken::new())
+            .await
+    }
+
+    /// 处理用户输入；`cancel` 触发后在当前 LLM/工具调用结束时停止，并撤销本轮的文件改动
+    pub async fn process_input_with_cancellation(
+        &self,
+        input: &str,
+        cancel: CancellationToken,
+    ) -> Result<AgentResponse, AgentError> {
         let state = self.state.lock().await;
 
         if !state.enabled {
@@ -340,11 +481,23 @@ impl AgentModeManager {
         }
 
         let session_id = state.session_id.clone();
-        let working_dir = state.working_dir.clone();
+        let mut working_dir = state.working_dir.clone();
         let active_task_id = state.active_task_id;
+        let model = state.config.model.clone();
+        let role = state.config.role;
 
         drop(state);
 
+        // 会话在 daemon 上配置了工作区时，本轮的工具调用限定在其中
+        let workspace = self.session_workspace(session_id.as_deref());
+        if let Some(workspace) = &workspace {
+            working_dir = Some(workspace.project_root().to_path_buf());
+        }
+        *self.active_workspace.lock().await = workspace;
+        self.run_saga
+            .reset(session_id.as_deref().unwrap_or_default())
+            .await;
+
         let orchestrator = {
             let orch = self.orchestrator.lock().await;
             orch.as_ref().cloned().ok_or_else(|| {
@@ -356,12 +509,15 @@ impl AgentModeManager {
             user_input: input.to_string(),
             session_id,
             working_dir,
-            role: Some(AgentRole::Implementer),
+            role: Some(role),
             active_task_id,
             working_memory: self.build_working_memory(active_task_id).await,
+            memory_context: self.build_memory_context(input, &model).await,
         };
 
-        let response = orchestrator.process(request).await?;
+        let response = orchestrator
+            .process_with_cancellation(request, cancel)
+            .await?;
         let identity = {
             let state = self.state.lock().await;
             let Some(project_id) = state.project_id.clone() else {
@@ -768,25 +924,7 @@ impl AgentModeManager {
     }
 
     async fn persist_session_snapshot(&self, session_id: &str) {
-        let orchestrator = {
-            let orch = self.orchestrator.lock().await;
-            orch.as_ref().cloned()
-        };
-        let Some(orchestrator) = orchestrator else {
-            return;
-        };
-        let Some(session) = orchestrator.session_snapshot(session_id).await else {
-            return;
-        };
-        let mut store = self.session_archive.lock().await;
-        store.upsert(&session);
-        if let Err(err) = store.save() {
-            debug!(
-                error = %err,
-                path = %store.path.display(),
-                "failed to persist session archive"
-            );
-        }
+        persist_session(&self.orchestrator, &self.session_archive, session_id).await;
     }
 
     async fn remember_project_identity(
@@ -892,6 +1030,28 @@ impl AgentModeManager {
         Ok((session_id, orchestrator.subscribe_execution_events()))
     }
 
+    /// 与输入相关的长期记忆，组装方式与 `ndc context` 预览一致
+    pub(crate) async fn build_memory_context(&self, input: &str, model: &str) -> Option<String> {
+        let storage = self._executor.context().storage.clone();
+        let builder = ContextBuilder::new(crate::cli::run_context_config(model));
+        let embedder = ndc_core::embedder_from_env();
+        match crate::cli::assemble_context(
+            storage.as_ref(),
+            embedder.as_ref(),
+            &builder,
+            input,
+            crate::cli::DEFAULT_CONTEXT_TOP_K,
+        )
+        .await
+        {
+            Ok(built) => Some(built.text).filter(|text| !text.is_empty()),
+            Err(e) => {
+                debug!("Memory context unavailable: {}", e);
+                None
+            }
+        }
+    }
+
     async fn build_working_memory(&self, active_task_id: Option<TaskId>) -> Option<WorkingMemory> {
         let task_id = active_task_id?;
         let storage = self._executor.context().storage.clone();
@@ -983,12 +1143,16 @@ impl AgentModeManager {
 
     fn extract_action_path_strings(action: &ndc_core::Action, out: &mut Vec<String>) {
         match action {
-            ndc_core::Action::ReadFile { path }
+            ndc_core::Action::ReadFile { path, .. }
             | ndc_core::Action::WriteFile { path, .. }
             | ndc_core::Action::CreateFile { path }
             | ndc_core::Action::DeleteFile { path } => {
                 out.push(path.display().to_string());
             }
+            ndc_core::Action::MoveFile { from, to } => {
+                out.push(from.display().to_string());
+                out.push(to.display().to_string());
+            }
             _ => {}
         }
     }
@@ -1215,56 +1379,100 @@ impl AgentModeManager {
         Ok(())
     }
 
-    /// 创建 LLM Provider
-    fn create_provider(
+    /// 应用热加载的配置（不回写配置文件）
+    ///
+    /// 任一 LLM 设置（含 api_key、base_url、providers 覆盖）、已批准权限、
+    /// 工具权限规则变化或传入新的工具注册表（MCP 服务器变化）时重建
+    /// orchestrator。新的 orchestrator 只影响之后的 turn；进行中的 turn
+    /// 继续使用已克隆的旧实例。当前会话在重建后恢复。
+    pub async fn apply_reloaded_config(
         &self,
-        provider_name: &str,
-        model: &str,
-    ) -> Result<Arc<dyn LlmProvider>, AgentError> {
-        use ndc_core::llm::provider::{
-            AnthropicProvider, OpenAiProvider, OpenRouterProvider, SimpleTokenCounter, TokenCounter,
+        ndc_config: &NdcConfig,
+        tool_registry: Option<Arc<ToolRegistry>>,
+    ) -> Result<(), AgentError> {
+        let (was_enabled, session_id, config) = {
+            let mut state = self.state.lock().await;
+            let mut next = state.config.clone();
+            next.apply_ndc_config(ndc_config);
+            let unchanged = state.applied_llm.as_ref() == ndc_config.llm.as_ref()
+                && next.provider == state.config.provider
+                && next.model == state.config.model
+                && next.temperature == state.config.temperature
+                && next.approved_permissions == state.config.approved_permissions
+                && next.permissions == state.config.permissions
+                && tool_registry.is_none();
+            if unchanged {
+                return Ok(());
+            }
+            if let Some(tool_registry) = tool_registry {
+                *self
+                    .tool_registry
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner()) = tool_registry;
+            }
+            state.applied_llm = ndc_config.llm.clone();
+            state.config = next;
+            (
+                state.enabled,
+                state.session_id.clone(),
+                state.config.clone(),
+            )
         };
 
-        // 根据 provider 名称创建相应的 Provider
-        let provider_type: ProviderType = if is_minimax_family(provider_name) {
-            ProviderType::MiniMax
-        } else {
-            provider_name.to_string().into()
-        };
-        let token_counter: Arc<dyn TokenCounter> = Arc::new(SimpleTokenCounter::new());
+        if was_enabled {
+            self.reenable(config.clone(), session_id).await?;
+        }
 
-        match provider_type {
-            ProviderType::OpenAi => {
-                let config = create_provider_config(provider_name, model);
-                let provider = OpenAiProvider::new(config, token_counter);
-                Ok(Arc::new(provider))
-            }
-            ProviderType::Anthropic => {
-                let config = create_provider_config(provider_name, model);
-                let provider = AnthropicProvider::new(config, token_counter);
-                Ok(Arc::new(provider))
-            }
-            ProviderType::MiniMax => {
-                let config = create_provider_config(provider_name, model);
-                // Align with OpenCode: use Anthropic-compatible MiniMax endpoint.
-                let provider = AnthropicProvider::new(config, token_counter);
-                Ok(Arc::new(provider))
-            }
-            ProviderType::OpenRouter => {
-                let config = create_provider_config(provider_name, model);
-                let provider = OpenRouterProvider::new(config, token_counter);
-                Ok(Arc::new(provider))
-            }
-            ProviderType::Ollama => {
-                let config = create_provider_config(provider_name, model);
-                let provider = OpenAiProvider::new(config, token_counter);
-                Ok(Arc::new(provider))
+        info!(provider = %config.provider, model = %config.model, "Provider config reloaded");
+        Ok(())
+    }
+
+    /// 切换会话角色，之后的 turn 只暴露该角色可调用的工具
+    pub async fn set_role(&self, role: AgentRole) -> Result<(), AgentError> {
+        let (was_enabled, session_id, config) = {
+            let mut state = self.state.lock().await;
+            if state.config.role == role {
+                return Ok(());
             }
-            _ => Err(AgentError::InvalidRequest(format!(
-                "Provider '{}' is not supported. Supported: openai, anthropic, minimax, openrouter, ollama",
-                provider_name
-            ))),
+            state.config.role = role;
+            (
+                state.enabled,
+                state.session_id.clone(),
+                state.config.clone(),
+            )
+        };
+
+        if was_enabled {
+            self.reenable(config, session_id).await?;
         }
+
+        info!(role = ?role, "Agent role switched");
+        Ok(())
+    }
+
+    /// 用新配置重建 orchestrator 并恢复当前会话
+    async fn reenable(
+        &self,
+        config: AgentModeConfig,
+        session_id: Option<String>,
+    ) -> Result<(), AgentError> {
+        self.disable().await;
+        self.enable(config).await?;
+        if let Some(session_id) = session_id
+            && let Err(e) = self.use_session(&session_id, false).await
+        {
+            tracing::warn!(session_id = %session_id, "Failed to restore session after agent rebuild: {e}");
+        }
+        Ok(())
+    }
+
+    /// 创建 LLM Provider
+    fn create_provider(
+        &self,
+        provider_name: &str,
+        model: &str,
+    ) -> Result<Arc<dyn LlmProvider>, AgentError> {
+        create_provider(provider_name, model)
     }
 }
 
@@ -1301,7 +1509,7 @@ pub struct ProjectSwitchOutcome {
 }
 
 // PermissionRequest and ReplToolExecutor are defined in permission_engine module
-pub use crate::permission_engine::{PermissionRequest, ReplToolExecutor};
+pub use crate::permission_engine::{PermissionRequest, ReplToolExecutor, RunSaga};
 
 /// 显示 Agent 状态
 pub fn show_agent_status(status: AgentModeStatus) {
@@ -1386,6 +1594,116 @@ pub async fn handle_agent_command(
     }
 }
 
+/// Agent 工具注册表：内置工具加上用户/项目配置目录中 `mcp.yaml` 所列 MCP 服务器的工具
+pub(crate) async fn agent_tool_registry(context: &ExecutionContext) -> Arc<ToolRegistry> {
+    let config_dirs = [
+        ndc_core::ConfigLayer::User.path(),
+        ndc_core::ConfigLayer::Project.path(),
+    ];
+    Arc::new(
+        ndc_runtime::create_tool_registry_with_mcp(
+            context.storage.clone(),
+            context.lock_manager.clone(),
+            &config_dirs,
+        )
+        .await,
+    )
+}
+
+/// 把会话快照写入归档
+///
+/// 先锁归档再取快照，写入顺序与快照顺序一致，较旧的快照不会覆盖较新的。
+async fn persist_session(
+    orchestrator: &Mutex<Option<AgentOrchestrator>>,
+    archive: &Mutex<SessionArchiveStore>,
+    session_id: &str,
+) {
+    let mut store = archive.lock().await;
+    let orchestrator = orchestrator.lock().await.as_ref().cloned();
+    let Some(orchestrator) = orchestrator else {
+        return;
+    };
+    let Some(session) = orchestrator.session_snapshot(session_id).await else {
+        return;
+    };
+    store.upsert(&session);
+    if let Err(err) = store.save() {
+        debug!(
+            error = %err,
+            path = %store.path.display(),
+            "failed to persist session archive"
+        );
+    }
+}
+
+/// 每个执行事件后持久化其会话，另一进程中的 `ndc logs --follow` 可以实时读到；
+/// 已排队的事件合并为一次写入
+async fn persist_session_events(
+    mut events: broadcast::Receiver<ndc_core::AgentSessionExecutionEvent>,
+    orchestrator: Arc<Mutex<Option<AgentOrchestrator>>>,
+    archive: Arc<Mutex<SessionArchiveStore>>,
+) {
+    loop {
+        let mut session_ids = std::collections::BTreeSet::new();
+        match events.recv().await {
+            Ok(event) => session_ids.insert(event.session_id),
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        while let Ok(event) = events.try_recv() {
+            session_ids.insert(event.session_id);
+        }
+        for session_id in session_ids {
+            persist_session(&orchestrator, &archive, &session_id).await;
+        }
+    }
+}
+
+/// Executor event listener that feeds runtime warnings to the agent's event stream
+const RUNTIME_WARNINGS_LISTENER: &str = "agent-mode-runtime-warnings";
+
+/// 将 runtime 警告转发到当前会话的实时事件流（TUI 渲染为 WarningNote）
+async fn forward_runtime_warnings(
+    mut warnings: mpsc::UnboundedReceiver<ndc_core::AgentExecutionEvent>,
+    state: Arc<Mutex<AgentModeState>>,
+    orchestrator: AgentOrchestrator,
+) {
+    while let Some(warning) = warnings.recv().await {
+        let session_id = state.lock().await.session_id.clone();
+        if let Some(session_id) = session_id {
+            orchestrator.publish_execution_event(&session_id, warning);
+        }
+    }
+}
+
+/// 将执行事件中的阶段切换转交 `WorkflowEngine` 计时（通知其 listener），
+/// 直到 orchestrator 的事件通道关闭
+async fn forward_workflow_stages(
+    mut events: broadcast::Receiver<ndc_core::AgentSessionExecutionEvent>,
+    engine: Arc<WorkflowEngine>,
+) {
+    use ndc_core::AgentExecutionEventKind;
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event.event,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        match (event.kind, event.workflow_stage) {
+            (AgentExecutionEventKind::WorkflowStage, Some(stage)) => {
+                engine.enter_stage(stage).await
+            }
+            // 会话空闲/取消：本轮运行结束
+            (AgentExecutionEventKind::SessionStatus, _) if event.message != "session_running" => {
+                engine.finish_stage().await
+            }
+            _ => {}
+        }
+    }
+    engine.finish_stage().await;
+}
+
 /// 显示 Agent 命令帮助
 fn show_agent_help() {
     println!("\n┌─────────────────────────────────────────────────────────────────┐");
@@ -1421,6 +1739,180 @@ mod tests {
             .unwrap_or_else(|poison| poison.into_inner())
     }
 
+    fn stage_event(
+        kind: AgentExecutionEventKind,
+        message: &str,
+        stage: Option<ndc_core::AgentWorkflowStage>,
+    ) -> ndc_core::AgentSessionExecutionEvent {
+        ndc_core::AgentSessionExecutionEvent {
+            session_id: "s".to_string(),
+            event: AgentExecutionEvent {
+                kind,
+                timestamp: chrono::Utc::now(),
+                message: message.to_string(),
+                round: 1,
+                tool_name: None,
+                tool_call_id: None,
+                duration_ms: None,
+                is_error: false,
+                workflow_stage: stage,
+                workflow_detail: None,
+                workflow_stage_index: None,
+                workflow_stage_total: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workflow_stage_events_drive_engine_timing() {
+        use ndc_core::AgentWorkflowStage::{Executing, Planning};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        #[async_trait::async_trait]
+        impl ndc_runtime::WorkflowListener for Recorder {
+            async fn on_stage_enter(&self, stage: ndc_core::AgentWorkflowStage) {
+                self.0.lock().unwrap().push(format!("enter {}", stage));
+            }
+
+            async fn on_stage_exit(
+                &self,
+                stage: ndc_core::AgentWorkflowStage,
+                _duration: std::time::Duration,
+            ) {
+                self.0.lock().unwrap().push(format!("exit {}", stage));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut engine = WorkflowEngine::new();
+        engine.register_listener(recorder.clone());
+        let (tx, rx) = broadcast::channel(16);
+        let forward = tokio::spawn(forward_workflow_stages(rx, Arc::new(engine)));
+
+        for event in [
+            stage_event(
+                AgentExecutionEventKind::SessionStatus,
+                "session_running",
+                None,
+            ),
+            stage_event(AgentExecutionEventKind::WorkflowStage, "", Some(Planning)),
+            stage_event(AgentExecutionEventKind::Text, "hi", None),
+            stage_event(AgentExecutionEventKind::WorkflowStage, "", Some(Executing)),
+            stage_event(AgentExecutionEventKind::SessionStatus, "session_idle", None),
+        ] {
+            tx.send(event).unwrap();
+        }
+        drop(tx);
+        forward.await.unwrap();
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                "enter planning",
+                "exit planning",
+                "enter executing",
+                "exit executing"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_events_reach_the_archive_during_the_run() {
+        use crate::logs::{FollowEnd, archive_events, follow};
+        use std::time::Duration;
+
+        let temp = TempDir::new().expect("temp dir");
+        let archive_path = temp.path().join("session_archive.json");
+        let context = ExecutionContext::default();
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(
+            context.storage.clone(),
+            None,
+        ));
+        let manager = AgentModeManager::new(Arc::new(Executor::new(context)), tool_registry)
+            .with_session_archive_file(archive_path.clone());
+        manager.enable(AgentModeConfig::default()).await.unwrap();
+        let orchestrator = manager.orchestrator.lock().await.clone().unwrap();
+
+        let (tx, rx) = broadcast::channel(16);
+        tokio::spawn(persist_session_events(
+            rx,
+            manager.orchestrator.clone(),
+            manager.session_archive.clone(),
+        ));
+        // `ndc logs --follow` in another process only sees the archive file
+        let follower = tokio::spawn(async move {
+            let events = archive_events(archive_path, "s".to_string(), 0, Duration::from_millis(5));
+            let mut out = Vec::new();
+            let end = follow(events, &mut out, crate::cli::OutputFormat::Pretty)
+                .await
+                .unwrap();
+            (end, String::from_utf8(out).unwrap())
+        });
+
+        let mut session = ndc_core::AgentSession::new("s".to_string());
+        for event in [
+            stage_event(AgentExecutionEventKind::StepStart, "llm_round_start", None),
+            stage_event(AgentExecutionEventKind::Text, "working", None),
+            stage_event(AgentExecutionEventKind::SessionStatus, "session_idle", None),
+        ] {
+            session.add_execution_event(event.event.clone());
+            orchestrator.upsert_session_snapshot(session.clone()).await;
+            tx.send(event).unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let (end, out) = tokio::time::timeout(Duration::from_secs(5), follower)
+            .await
+            .expect("events should be archived as they happen")
+            .unwrap();
+        assert_eq!(end, FollowEnd::Terminal);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", out);
+        assert!(lines[1].contains("working"), "{}", out);
+    }
+
+    #[tokio::test]
+    async fn test_verdict_warnings_reach_the_execution_event_stream() {
+        let temp = TempDir::new().expect("temp dir");
+        let context = ExecutionContext::default();
+        let events = context.events.clone();
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(
+            context.storage.clone(),
+            None,
+        ));
+        let manager = AgentModeManager::new(Arc::new(Executor::new(context)), tool_registry)
+            .with_session_archive_file(temp.path().join("session_archive.json"));
+        manager.enable(AgentModeConfig::default()).await.unwrap();
+        // Enabling twice keeps a single listener
+        manager.enable(AgentModeConfig::default()).await.unwrap();
+        let (session_id, mut rx) = manager.subscribe_execution_events().await.unwrap();
+
+        events.emit(&ndc_runtime::Event {
+            id: ndc_runtime::EventId::default(),
+            event_type: ndc_runtime::EventType::VerdictModified,
+            data: ndc_runtime::EventData::Verdict {
+                reason: "sandboxed".to_string(),
+                warnings: vec!["path changed to a sandboxed copy".to_string()],
+            },
+            task_id: None,
+            step_id: None,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        });
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("warning should be forwarded")
+            .unwrap();
+        assert_eq!(event.session_id, session_id);
+        assert_eq!(event.event.kind, AgentExecutionEventKind::Warning);
+        assert_eq!(event.event.message, "path changed to a sandboxed copy");
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn test_agent_mode_config_default() {
         let config = AgentModeConfig::default();
@@ -1464,7 +1956,7 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         manager.enable(AgentModeConfig::default()).await.unwrap();
@@ -1477,7 +1969,7 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         manager.enable(AgentModeConfig::default()).await.unwrap();
@@ -1494,7 +1986,7 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         manager.enable(AgentModeConfig::default()).await.unwrap();
@@ -1507,12 +1999,104 @@ mod tests {
         assert_ne!(before, after);
     }
 
+    #[test]
+    fn test_apply_ndc_config_applies_matching_profile_tool_permissions() {
+        let ndc_config: NdcConfig = serde_json::from_value(serde_json::json!({
+            "agents": [
+                {"name": "other", "tool_permissions": {"default": "deny"}},
+                {"name": "build", "tool_permissions": {
+                    "default": "allow",
+                    "tools": {"file_write": "Deny", "network": "sometimes"}
+                }}
+            ]
+        }))
+        .unwrap();
+        let mut config = AgentModeConfig {
+            agent_name: "build".to_string(),
+            ..AgentModeConfig::default()
+        };
+        config.apply_ndc_config(&ndc_config);
+
+        assert_eq!(config.permissions.get("*"), Some(&PermissionRule::Allow));
+        assert_eq!(
+            config.permissions.get("file_write"),
+            Some(&PermissionRule::Deny)
+        );
+        // Unknown rules are ignored, keeping the previous one
+        assert_eq!(
+            config.permissions.get("network"),
+            Some(&PermissionRule::Ask)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_reloaded_config_compares_whole_llm_config() {
+        let context = ExecutionContext::default();
+        let storage = context.storage.clone();
+        let executor = Arc::new(Executor::new(context));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
+        let manager = AgentModeManager::new(executor, tool_registry);
+
+        manager
+            .enable(AgentModeConfig {
+                provider: "ollama".to_string(),
+                model: "llama3.2".to_string(),
+                ..AgentModeConfig::default()
+            })
+            .await
+            .unwrap();
+
+        let reloaded = |base_url: &str| -> NdcConfig {
+            serde_json::from_value(serde_json::json!({
+                "llm": {"provider": "ollama", "model": "llama3.2", "base_url": base_url},
+                "approved_permissions": ["git_commit"],
+            }))
+            .unwrap()
+        };
+
+        // Same provider/model, but base_url and approved permissions are new
+        let first = reloaded("http://localhost:11434");
+        manager.apply_reloaded_config(&first, None).await.unwrap();
+        {
+            let state = manager.state.lock().await;
+            assert_eq!(state.applied_llm, first.llm);
+            assert_eq!(state.config.approved_permissions, vec!["git_commit"]);
+        }
+
+        let second = reloaded("http://gpu-box:11434");
+        manager.apply_reloaded_config(&second, None).await.unwrap();
+        assert_eq!(manager.state.lock().await.applied_llm, second.llm);
+
+        let status = manager.status().await;
+        assert!(status.enabled);
+        assert_eq!(status.provider, "ollama");
+    }
+
+    #[tokio::test]
+    async fn test_set_role_rebuilds_agent_for_session_role() {
+        let context = ExecutionContext::default();
+        let storage = context.storage.clone();
+        let executor = Arc::new(Executor::new(context));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
+        let manager = AgentModeManager::new(executor, tool_registry);
+
+        manager.enable(AgentModeConfig::default()).await.unwrap();
+        assert_eq!(
+            manager.state.lock().await.config.role,
+            AgentRole::Implementer
+        );
+
+        manager.set_role(AgentRole::Historian).await.unwrap();
+        assert_eq!(manager.state.lock().await.config.role, AgentRole::Historian);
+        assert!(manager.status().await.enabled);
+    }
+
     #[tokio::test]
     async fn test_resume_latest_project_session_without_history_returns_not_found() {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         manager.enable(AgentModeConfig::default()).await.unwrap();
@@ -1525,7 +2109,7 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         manager.enable(AgentModeConfig::default()).await.unwrap();
@@ -1538,7 +2122,7 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         manager.enable(AgentModeConfig::default()).await.unwrap();
@@ -1611,7 +2195,7 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage, None));
         let manager = AgentModeManager::new(executor, tool_registry);
         let known_roots = {
             let store = manager.project_index.lock().await;
@@ -1692,7 +2276,7 @@ mod tests {
         context_a.project_root = project_root.clone();
         let storage_a = context_a.storage.clone();
         let executor_a = Arc::new(Executor::new(context_a));
-        let tool_registry_a = Arc::new(create_default_tool_registry_with_storage(storage_a));
+        let tool_registry_a = Arc::new(create_default_tool_registry_with_storage(storage_a, None));
         let manager_a = AgentModeManager::new(executor_a, tool_registry_a);
         manager_a.enable(AgentModeConfig::default()).await.unwrap();
 
@@ -1732,7 +2316,7 @@ mod tests {
         context_b.project_root = project_root.clone();
         let storage_b = context_b.storage.clone();
         let executor_b = Arc::new(Executor::new(context_b));
-        let tool_registry_b = Arc::new(create_default_tool_registry_with_storage(storage_b));
+        let tool_registry_b = Arc::new(create_default_tool_registry_with_storage(storage_b, None));
         let manager_b = AgentModeManager::new(executor_b, tool_registry_b);
         manager_b.enable(AgentModeConfig::default()).await.unwrap();
 
@@ -1758,7 +2342,10 @@ mod tests {
         let context = ExecutionContext::default();
         let storage = context.storage.clone();
         let executor = Arc::new(Executor::new(context));
-        let tool_registry = Arc::new(create_default_tool_registry_with_storage(storage.clone()));
+        let tool_registry = Arc::new(create_default_tool_registry_with_storage(
+            storage.clone(),
+            None,
+        ));
         let manager = AgentModeManager::new(executor, tool_registry);
 
         let mut task = Task::new(
@@ -1778,6 +2365,7 @@ mod tests {
             step_id: 1,
             action: Action::ReadFile {
                 path: PathBuf::from("/tmp/test.txt"),
+                range: None,
             },
             status: ndc_core::StepStatus::Failed,
             result: Some(ndc_core::ActionResult {
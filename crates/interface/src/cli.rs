@@ -9,18 +9,34 @@
 //! - ndc run "message"  - Run AI with a message (one-shot or REPL)
 //! - ndc repl           - Start interactive REPL
 //! - ndc daemon         - Start background daemon
+//! - ndc discovery watch - Live volatility heatmap while committing
+//! - ndc config validate - Check the merged config and report problems
+//! - ndc logs [--follow] - Print (or tail) a session's execution events
+//! - ndc verify [--check test,lint] - Run the quality gate standalone (for CI)
+//! - ndc rollback TASK_ID [--dry-run] - Undo a task's completed steps
+//! - ndc context "query" - Preview the memory context assembled for a query
+//! - ndc memory export|import - Move the knowledge base as JSON Lines
+//! - ndc list [--since 24h] - Read-only listing of stored tasks
 //!
 //! Removed Commands (now AI internal workflow):
-//! - create, list, status, logs, run, rollback (use natural language instead)
+//! - create, status, run (use natural language instead)
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
-use tracing::info;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
 
-use ndc_core::AgentRole;
-use ndc_runtime::{ExecutionContext, Executor, MemoryStorage};
+use ndc_core::{
+    AgentRole, ConfigReport, ContextBuilder, ContextConfig, Embedder, MemoryQuery, MemoryStability,
+    NdcConfigLoader, TaskState,
+};
+use ndc_runtime::{
+    ExecutionContext, Executor, HeatmapConfig, HeatmapWatcher, MemoryStorage, TaskFilter,
+};
 
 use crate::agent_mode::{AgentModeConfig, AgentModeManager};
 
@@ -35,6 +51,15 @@ pub enum CliError {
 
     #[error("Agent error: {0}")]
     AgentError(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Execution failed: {0}")]
+    ExecutionFailed(String),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
 }
 
 /// CLI Configuration
@@ -51,6 +76,9 @@ pub struct CliConfig {
 
     /// Output format
     pub output_format: OutputFormat,
+
+    /// Store behind tasks, memories, sagas and audit entries
+    pub storage_backend: StorageBackend,
 }
 
 impl Default for CliConfig {
@@ -60,6 +88,53 @@ impl Default for CliConfig {
             storage_path: PathBuf::from(".ndc/storage"),
             verbose: false,
             output_format: OutputFormat::Pretty,
+            storage_backend: StorageBackend::JsonLog,
+        }
+    }
+}
+
+impl CliConfig {
+    /// File or directory holding the store
+    pub fn store_path(&self) -> &Path {
+        match &self.storage_backend {
+            StorageBackend::JsonLog => &self.storage_path,
+            StorageBackend::Sqlite { db_path } => db_path,
+        }
+    }
+}
+
+/// Persistent store used by CLI commands
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum StorageBackend {
+    /// Append-only JSON log under `--storage`
+    #[default]
+    JsonLog,
+    /// SQLite database (needs the `sqlite` feature)
+    Sqlite { db_path: PathBuf },
+}
+
+impl StorageBackend {
+    /// Backend selected by the `storage` config section
+    ///
+    /// The CLI store always lives on disk, so `memory` (the config default)
+    /// maps to the JSON log and `sqlite` without `db_path` to `ndc.db` under
+    /// `storage_path`.
+    pub fn from_config(storage: Option<&ndc_core::YamlStorageConfig>, storage_path: &Path) -> Self {
+        match storage {
+            Some(storage) if storage.storage_type == "sqlite" => StorageBackend::Sqlite {
+                db_path: storage
+                    .db_path
+                    .clone()
+                    .unwrap_or_else(|| storage_path.join("ndc.db")),
+            },
+            _ => StorageBackend::JsonLog,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StorageBackend::JsonLog => "json_log",
+            StorageBackend::Sqlite { .. } => "sqlite",
         }
     }
 }
@@ -69,6 +144,67 @@ pub enum OutputFormat {
     Pretty,
     Json,
     Minimal,
+    /// Newline-delimited JSON: one self-contained object per line
+    Jsonl,
+}
+
+/// Write `items` as JSON Lines, one compact object per line
+///
+/// Each line is independently parseable, so large result sets can be
+/// streamed into downstream tools without buffering a JSON array.
+pub fn write_jsonl<T, W>(out: &mut W, items: &[T]) -> std::io::Result<()>
+where
+    T: serde::Serialize,
+    W: std::io::Write + ?Sized,
+{
+    for item in items {
+        serde_json::to_writer(&mut *out, item)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Single memory search hit, as emitted by `ndc search --output json|jsonl`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub score: f32,
+    pub summary: String,
+}
+
+/// Task row emitted by `ndc list`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TaskSummary {
+    pub id: String,
+    pub title: String,
+    pub state: TaskState,
+    pub created_by: AgentRole,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Memory placed in (or left out of) a context preview
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PreviewMemory {
+    pub id: String,
+    pub stability: MemoryStability,
+    pub score: f32,
+    /// Tokens of the memory's own context line
+    pub tokens: usize,
+}
+
+/// Result of `ndc context`: what `ContextBuilder` would put in the prompt
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ContextPreview {
+    pub query: String,
+    pub model: String,
+    pub max_tokens: usize,
+    /// Tokens of the assembled context section
+    pub prompt_tokens: usize,
+    /// Included memories, in prompt order
+    pub included: Vec<PreviewMemory>,
+    /// Memories that did not fit the budget
+    pub dropped: Vec<PreviewMemory>,
 }
 
 /// NDC CLI
@@ -93,7 +229,7 @@ pub(crate) struct Cli {
     output: Option<OutputFormat>,
 
     #[command(subcommand)]
-    command: Commands,
+    pub(crate) command: Commands,
 }
 
 #[derive(Subcommand, Debug)]
@@ -112,6 +248,219 @@ pub(crate) enum Commands {
 
     /// Show system status
     StatusSystem,
+
+    /// Discovery phase tools (volatility heatmap)
+    Discovery(DiscoveryArgs),
+
+    /// Configuration tools
+    Config(ConfigArgs),
+
+    /// Print a session's execution events
+    Logs(LogsArgs),
+
+    /// Run the quality gate against the project (exits non-zero on failure)
+    Verify(VerifyArgs),
+
+    /// Undo a task's completed steps (refuses when an undo would fail)
+    Rollback(RollbackArgs),
+
+    /// Preview the memory context assembled for a query (no LLM call)
+    Context(ContextArgs),
+
+    /// Export or import the memory store
+    Memory(MemoryArgs),
+
+    /// List stored tasks, most recently updated first
+    List(ListArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ListArgs {
+    /// Only tasks created or modified within this window: 30m, 24h, 2d, 1w
+    #[arg(long, value_name = "DURATION")]
+    pub since: Option<String>,
+
+    /// Only tasks in this state (pending, in_progress, completed, ...)
+    #[arg(long)]
+    pub state: Option<TaskState>,
+
+    /// Only tasks created by this role (planner, implementer, ...)
+    #[arg(long)]
+    pub role: Option<AgentRole>,
+}
+
+impl ListArgs {
+    /// Storage filter for these arguments, with `--since` measured back from `now`
+    pub(crate) fn to_filter(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<TaskFilter, CliError> {
+        let active_since = match self.since.as_deref() {
+            Some(since) => Some(now.checked_sub_signed(parse_since(since)?).ok_or_else(|| {
+                CliError::InvalidArgument(format!("--since '{}' reaches too far back", since))
+            })?),
+            None => None,
+        };
+        Ok(TaskFilter {
+            state: self.state.clone(),
+            created_by: self.role,
+            active_since,
+            ..TaskFilter::default()
+        })
+    }
+}
+
+/// Parse a `--since` window: a positive integer followed by s, m, h, d or w
+pub(crate) fn parse_since(value: &str) -> Result<chrono::Duration, CliError> {
+    let invalid = || {
+        CliError::InvalidArgument(format!(
+            "invalid duration '{}' (expected e.g. 30m, 24h, 2d, 1w)",
+            value
+        ))
+    };
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let amount: i64 = value[..split].parse().map_err(|_| invalid())?;
+    if amount == 0 {
+        return Err(invalid());
+    }
+    let window = match &value[split..] {
+        "s" => chrono::Duration::try_seconds(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => None,
+    };
+    window.ok_or_else(invalid)
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct MemoryArgs {
+    #[command(subcommand)]
+    pub command: MemoryCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum MemoryCommands {
+    /// Write every memory to a JSON Lines file
+    Export(MemoryExportArgs),
+    /// Load memories from a JSON Lines file written by `memory export`
+    Import(MemoryImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct MemoryExportArgs {
+    #[arg(long, value_name = "FILE")]
+    pub out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct MemoryImportArgs {
+    #[arg(long = "in", value_name = "FILE")]
+    pub input: PathBuf,
+
+    /// Keep existing memories and skip imported ids already present (default)
+    #[arg(long, conflicts_with = "replace")]
+    pub merge: bool,
+
+    /// Delete every existing memory before importing
+    #[arg(long)]
+    pub replace: bool,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ContextArgs {
+    /// Query to assemble context for
+    pub query: String,
+
+    /// Context budget in tokens (default: the one agent runs use)
+    #[arg(long)]
+    pub max_tokens: Option<usize>,
+
+    /// Memories retrieved before the budget is applied
+    #[arg(long, default_value_t = DEFAULT_CONTEXT_TOP_K)]
+    pub top_k: usize,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct RollbackArgs {
+    /// Task whose saga to roll back
+    pub task: String,
+
+    /// Only check that every undo can be performed, and print the problems
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct VerifyArgs {
+    /// Checks to run, comma-separated: test, lint, typecheck, build, security,
+    /// custom:<name> (default: runtime.quality_gates, else typecheck,lint,test)
+    #[arg(long, value_delimiter = ',', value_name = "CHECKS")]
+    pub check: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct LogsArgs {
+    /// Session id, or id of a task in the session (default: latest session)
+    pub id: Option<String>,
+
+    /// Keep printing new events until the run finishes
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Read events from a gRPC daemon instead of the local session archive
+    #[arg(long, value_name = "ADDR")]
+    pub daemon: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ConfigCommands {
+    /// Load the merged config and report errors and warnings
+    Validate(ValidateArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ValidateArgs {
+    /// Also ping the configured providers (network calls)
+    #[arg(long)]
+    pub check_connectivity: bool,
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct DiscoveryArgs {
+    #[command(subcommand)]
+    pub command: DiscoveryCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum DiscoveryCommands {
+    /// Watch git refs and print the hottest modules after each commit
+    Watch(WatchArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct WatchArgs {
+    /// Number of modules to print per update
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Quiet period (ms) after a ref change before updating; merges rapid commits
+    #[arg(long, default_value_t = 500)]
+    pub debounce_ms: u64,
+
+    /// Look-back period in days
+    #[arg(long, default_value_t = 7)]
+    pub lookback_days: u32,
 }
 
 #[derive(Args, Debug)]
@@ -140,9 +489,21 @@ pub(crate) struct RunArgs {
     #[arg(short = 'a', long)]
     pub agent: Option<String>,
 
+    /// Session role; limits the tools exposed to the model (default: implementer)
+    #[arg(long, value_name = "ROLE")]
+    pub role: Option<ndc_core::AgentRole>,
+
     /// Non-interactive mode (no REPL)
     #[arg(long)]
     pub one_shot: bool,
+
+    /// Resume a task from its last checkpoint, skipping completed steps
+    #[arg(long, value_name = "TASK_ID")]
+    pub resume: Option<String>,
+
+    /// Run a task after its unmet dependencies, in dependency order
+    #[arg(long, value_name = "TASK_ID", conflicts_with = "resume")]
+    pub task: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -150,6 +511,10 @@ pub(crate) struct ReplArgs {
     /// History file path
     #[arg(long)]
     pub history: Option<PathBuf>,
+
+    /// Reload a saved transcript (stored next to the history file)
+    #[arg(long, value_name = "ID")]
+    pub resume_session: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -161,53 +526,123 @@ pub(crate) struct DaemonArgs {
 
 #[derive(Args, Debug)]
 pub(crate) struct SearchArgs {
-    /// Search query
-    pub query: String,
+    /// Search query; may be omitted when filters are given
+    pub query: Option<String>,
+
+    /// Only memories at exactly this stability
+    #[arg(long, value_name = "LEVEL")]
+    pub stability: Option<MemoryStability>,
+
+    /// Only memories at or above this stability
+    #[arg(long, value_name = "LEVEL")]
+    pub min_stability: Option<MemoryStability>,
+
+    /// Require a tag (repeatable; all must match)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Memory type (code, decision, error_solution, ...)
+    #[arg(long = "type", value_name = "TYPE")]
+    pub memory_type: Option<String>,
+}
+
+impl SearchArgs {
+    pub(crate) fn to_query(&self) -> MemoryQuery {
+        MemoryQuery {
+            query: self
+                .query
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty())
+                .map(str::to_string),
+            stability: self.stability,
+            memory_type: self.memory_type.clone(),
+            tags: self.tags.clone(),
+            source_task: None,
+            min_stability: self.min_stability,
+            max_stability: None,
+            ..Default::default()
+        }
+    }
 }
 
 /// Parse CLI arguments and execute commands
 pub async fn run() -> Result<(), CliError> {
     let cli = Cli::parse();
+    crate::logging::init_logging(cli.verbose);
+    dispatch(cli, &mut std::io::stdout()).await
+}
 
+/// Execute a parsed command line; report commands write their output to `out`
+pub(crate) async fn dispatch(
+    cli: Cli,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
     // Build config from args
+    let storage_path = cli.storage.unwrap_or_else(|| PathBuf::from(".ndc/storage"));
+    let mut loader = NdcConfigLoader::new();
+    let storage_backend = match loader.load() {
+        Ok(loaded) => StorageBackend::from_config(loaded.storage.as_ref(), &storage_path),
+        Err(_) => StorageBackend::JsonLog,
+    };
     let config = CliConfig {
         project_root: cli.project_root.unwrap_or_else(|| PathBuf::from(".")),
-        storage_path: cli.storage.unwrap_or_else(|| PathBuf::from(".ndc/storage")),
+        storage_path,
         verbose: cli.verbose,
         output_format: cli.output.unwrap_or(OutputFormat::Pretty),
+        storage_backend,
     };
 
-    if config.verbose {
-        tracing_subscriber::fmt::init();
-    }
-
     match cli.command {
         Commands::Run(args) => cmd_run(args, &config).await,
         Commands::Repl(args) => cmd_repl(args, &config).await,
         Commands::Daemon(args) => cmd_daemon(args, &config).await,
-        Commands::Search(args) => cmd_search(args).await,
-        Commands::StatusSystem => cmd_status_system().await,
+        Commands::Search(args) => cmd_search(args, &config, out).await,
+        Commands::StatusSystem => cmd_status_system(&config, out).await,
+        Commands::Discovery(args) => match args.command {
+            DiscoveryCommands::Watch(args) => cmd_discovery_watch(args, &config).await,
+        },
+        Commands::Config(args) => match args.command {
+            ConfigCommands::Validate(args) => cmd_config_validate(args, &config).await,
+        },
+        Commands::Logs(args) => cmd_logs(args, &config, out).await,
+        Commands::Verify(args) => cmd_verify(args, &config).await,
+        Commands::Rollback(args) => cmd_rollback(args, &config).await,
+        Commands::Context(args) => cmd_context(args, &config, out).await,
+        Commands::Memory(args) => match args.command {
+            MemoryCommands::Export(args) => cmd_memory_export(args, &config).await,
+            MemoryCommands::Import(args) => cmd_memory_import(args, &config).await,
+        },
+        Commands::List(args) => cmd_list(args, &config, out).await,
     }
 }
 
 async fn cmd_run(args: RunArgs, config: &CliConfig) -> Result<(), CliError> {
     // Initialize executor for tool access
-    let context = create_execution_context(config);
+    let context = create_execution_context(config).await?;
     let executor = Arc::new(Executor::new(context));
 
+    if let Some(task_id) = args.resume {
+        return cmd_resume(&executor, &task_id, config).await;
+    }
+    if let Some(task_id) = args.task {
+        return cmd_run_task(&executor, &task_id, config).await;
+    }
+
     if let Some(msg) = args.message {
         // One-shot mode: send message to AI and exit
         info!("Running one-shot: {}", msg);
 
-        let tool_registry = Arc::new(ndc_runtime::create_default_tool_registry_with_storage(
-            executor.context().storage.clone(),
-        ));
+        let tool_registry = crate::agent_mode::agent_tool_registry(executor.context()).await;
         let manager = AgentModeManager::new(executor, tool_registry);
 
         let mut agent_config = AgentModeConfig::default();
         if let Some(agent_name) = args.agent.as_ref() {
             agent_config.agent_name = agent_name.clone();
         }
+        if let Some(role) = args.role {
+            agent_config.role = role;
+        }
         if let Some(model_spec) = args.model.as_ref() {
             let (provider, model) = parse_model_spec(model_spec);
             agent_config.provider = provider.to_string();
@@ -233,10 +668,17 @@ async fn cmd_run(args: RunArgs, config: &CliConfig) -> Result<(), CliError> {
                 .map_err(|e| CliError::AgentError(e.to_string()))?;
         }
 
-        let response = manager
-            .process_input(&msg)
-            .await
-            .map_err(|e| CliError::AgentError(e.to_string()))?;
+        // Ctrl+C stops after the in-flight call and undoes the run's file changes
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let on_interrupt = cancel.clone();
+        let interrupt = tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                on_interrupt.cancel();
+            }
+        });
+        let response = manager.process_input_with_cancellation(&msg, cancel).await;
+        interrupt.abort();
+        let response = response.map_err(|e| CliError::AgentError(e.to_string()))?;
 
         if !response.content.is_empty() {
             println!("{}", response.content);
@@ -255,28 +697,131 @@ async fn cmd_run(args: RunArgs, config: &CliConfig) -> Result<(), CliError> {
         // Interactive REPL mode
         info!("Starting REPL...");
         let history = PathBuf::from(".ndc/repl_history");
-        super::run_repl(history, executor).await;
+        super::run_repl(history, executor, None).await;
         Ok(())
     }
 }
 
+async fn cmd_resume(
+    executor: &Executor,
+    task_id: &str,
+    config: &CliConfig,
+) -> Result<(), CliError> {
+    let task_id: ndc_core::TaskId = task_id
+        .parse()
+        .map_err(|_| CliError::InvalidArgument(format!("Invalid task id: {}", task_id)))?;
+
+    let result = executor
+        .resume_task(task_id)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+
+    let completed = result
+        .steps
+        .iter()
+        .filter(|s| s.status == ndc_core::StepStatus::Completed)
+        .count();
+    match config.output_format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let summary = serde_json::json!({
+                "task_id": task_id.to_string(),
+                "state": format!("{:?}", result.final_state),
+                "steps_completed": completed,
+                "steps_total": result.steps.len(),
+            });
+            println!("{}", summary);
+        }
+        _ => println!(
+            "Task {} {:?} ({}/{} steps completed)",
+            task_id,
+            result.final_state,
+            completed,
+            result.steps.len()
+        ),
+    }
+    Ok(())
+}
+
+async fn cmd_run_task(
+    executor: &Executor,
+    task_id: &str,
+    config: &CliConfig,
+) -> Result<(), CliError> {
+    let task_id: ndc_core::TaskId = task_id
+        .parse()
+        .map_err(|_| CliError::InvalidArgument(format!("Invalid task id: {}", task_id)))?;
+
+    let mut results = executor
+        .execute_with_dependencies(task_id)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+
+    // Deferred tasks are re-evaluated once their retry time comes
+    while let Some(due) = executor.next_deferral_due() {
+        let wait = (due - executor.context().clock.now())
+            .to_std()
+            .unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        for result in executor.run_due_deferrals().await {
+            let result = result.map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+            results.retain(|r| r.task_id != result.task_id);
+            results.push(result);
+        }
+    }
+
+    match config.output_format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let summary: Vec<_> = results
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "task_id": r.task_id.to_string(),
+                        "state": format!("{:?}", r.final_state),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::Value::Array(summary));
+        }
+        _ => {
+            for result in &results {
+                println!("Task {} {:?}", result.task_id, result.final_state);
+            }
+        }
+    }
+    match results.last() {
+        Some(last) if last.task_id != task_id => Err(CliError::ExecutionFailed(format!(
+            "dependency {} ended {:?}; task {} not run",
+            last.task_id, last.final_state, task_id
+        ))),
+        _ => Ok(()),
+    }
+}
+
 async fn cmd_repl(args: ReplArgs, config: &CliConfig) -> Result<(), CliError> {
     info!("Starting REPL...");
 
     // Initialize executor for tool access
-    let context = create_execution_context(config);
+    let context = create_execution_context(config).await?;
     let executor = Arc::new(Executor::new(context));
 
     // Start REPL
     let history = args
         .history
         .unwrap_or_else(|| PathBuf::from(".ndc/repl_history"));
-    super::run_repl(history, executor).await;
+    let resume = match args.resume_session.as_deref() {
+        Some(id) => Some(
+            ndc_tui::SessionTranscript::load(&crate::repl::transcript_dir(&history), id).map_err(
+                |e| CliError::InvalidArgument(format!("cannot resume session {}: {}", id, e)),
+            )?,
+        ),
+        None => None,
+    };
+    super::run_repl(history, executor, resume).await;
 
     Ok(())
 }
 
-async fn cmd_daemon(args: DaemonArgs, _config: &CliConfig) -> Result<(), CliError> {
+async fn cmd_daemon(args: DaemonArgs, config: &CliConfig) -> Result<(), CliError> {
     info!("Starting daemon on: {}", args.address);
 
     let address = args
@@ -286,54 +831,1054 @@ async fn cmd_daemon(args: DaemonArgs, _config: &CliConfig) -> Result<(), CliErro
 
     #[cfg(feature = "grpc")]
     {
-        crate::grpc::run_grpc_server(address)
+        crate::grpc::run_grpc_server(address, &config.storage_path)
             .await
             .map_err(|e| CliError::ExecutorInitFailed(e.to_string()))?;
     }
 
     #[cfg(not(feature = "grpc"))]
     {
-        super::run_daemon(address).await;
+        super::run_daemon(address, &config.storage_path).await;
+    }
+
+    Ok(())
+}
+
+async fn cmd_search(
+    args: SearchArgs,
+    config: &CliConfig,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    let query = args.to_query();
+    if query.query.is_none() && !query.has_filters() {
+        return Err(CliError::InvalidArgument(
+            "provide a search query or at least one filter (--stability, --min-stability, --tag, --type)"
+                .to_string(),
+        ));
+    }
+    info!("Searching memory: {:?}", query);
+
+    // Don't create the store just to search it
+    let hits = match config.store_path().exists() {
+        true => search_memories(&*open_store(config).await?, &query).await?,
+        false => Vec::new(),
+    };
+
+    let write_err = |e: std::io::Error| CliError::StorageError(e.to_string());
+    match config.output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&hits)
+                .map_err(|e| CliError::StorageError(e.to_string()))?;
+            writeln!(out, "{}", json).map_err(write_err)?;
+        }
+        OutputFormat::Jsonl => {
+            write_jsonl(out, &hits).map_err(write_err)?;
+        }
+        OutputFormat::Pretty | OutputFormat::Minimal => {
+            match &query.query {
+                Some(text) => writeln!(out, "Search results for '{}':", text),
+                None => writeln!(out, "Memories matching filters:"),
+            }
+            .map_err(write_err)?;
+            if hits.is_empty() {
+                writeln!(out, "  (no matching memories)").map_err(write_err)?;
+            }
+            for hit in &hits {
+                writeln!(out, "  {}  {}", hit.id, hit.summary).map_err(write_err)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a memory query against storage; every match scores 1.0
+pub(crate) async fn search_memories(
+    storage: &dyn ndc_runtime::Storage,
+    query: &MemoryQuery,
+) -> Result<Vec<SearchHit>, CliError> {
+    let mut matches: Vec<ndc_core::MemoryEntry> = storage
+        .list_memories()
+        .await
+        .map_err(CliError::StorageError)?
+        .into_iter()
+        .filter(|memory| query.matches(memory))
+        .collect();
+    query.sort(&mut matches);
+    Ok(matches
+        .into_iter()
+        .map(|memory| SearchHit {
+            id: memory.id.0.to_string(),
+            score: 1.0,
+            summary: format!(
+                "[{:?}] {}",
+                memory.metadata.stability,
+                memory.content.summary()
+            ),
+        })
+        .collect())
+}
+
+async fn cmd_list(
+    args: ListArgs,
+    config: &CliConfig,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    let filter = args.to_filter(chrono::Utc::now())?;
+    // Don't create the store just to list it
+    let tasks = match config.store_path().exists() {
+        true => list_tasks(&*open_store(config).await?, &filter).await?,
+        false => Vec::new(),
+    };
+
+    let write_err = |e: std::io::Error| CliError::StorageError(e.to_string());
+    match config.output_format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&tasks)
+                .map_err(|e| CliError::StorageError(e.to_string()))?;
+            writeln!(out, "{}", json).map_err(write_err)?;
+        }
+        OutputFormat::Jsonl => {
+            write_jsonl(out, &tasks).map_err(write_err)?;
+        }
+        OutputFormat::Minimal => {
+            for task in &tasks {
+                writeln!(out, "{}", task.id).map_err(write_err)?;
+            }
+        }
+        OutputFormat::Pretty => {
+            if tasks.is_empty() {
+                writeln!(out, "(no matching tasks)").map_err(write_err)?;
+            }
+            for task in &tasks {
+                // Padding needs owned strings; derived Debug/Display ignore width
+                let role = format!("{:?}", task.created_by);
+                writeln!(
+                    out,
+                    "  {}  {:<20}  {:<11}  {}  {}",
+                    task.id,
+                    task.state.to_string(),
+                    role,
+                    task.updated_at.format("%Y-%m-%d %H:%M"),
+                    task.title
+                )
+                .map_err(write_err)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Tasks matching `filter`, as `ndc list` reports them
+pub(crate) async fn list_tasks(
+    storage: &dyn ndc_runtime::Storage,
+    filter: &TaskFilter,
+) -> Result<Vec<TaskSummary>, CliError> {
+    let tasks = storage
+        .query_tasks(filter)
+        .await
+        .map_err(CliError::StorageError)?;
+    Ok(tasks
+        .into_iter()
+        .map(|task| TaskSummary {
+            id: task.id.to_string(),
+            title: task.title,
+            state: task.state,
+            created_by: task.metadata.created_by,
+            created_at: task.metadata.created_at,
+            updated_at: task.metadata.updated_at,
+        })
+        .collect())
+}
+
+/// Memories retrieved for agent runs before the budget is applied
+pub(crate) const DEFAULT_CONTEXT_TOP_K: usize = 20;
+
+/// Context assembly config of agent runs: the run's model and the default
+/// budget
+pub(crate) fn run_context_config(model: &str) -> ContextConfig {
+    ContextConfig::new(
+        ndc_core::DEFAULT_CONTEXT_TOKENS,
+        Arc::new(ndc_core::llm::provider::SimpleTokenCounter::new()),
+    )
+    .with_model(model)
+}
+
+/// Retrieve the `top_k` closest live memories for `query` and budget them
+/// with `builder`; agent runs and `ndc context` both go through here
+pub(crate) async fn assemble_context(
+    storage: &dyn ndc_runtime::Storage,
+    embedder: &dyn Embedder,
+    builder: &ContextBuilder,
+    query: &str,
+    top_k: usize,
+) -> Result<ndc_core::BuiltContext, CliError> {
+    let vector = embedder
+        .embed(query)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    let memories = storage
+        .list_memories()
+        .await
+        .map_err(CliError::StorageError)?;
+    let now = chrono::Utc::now();
+    let live = memories.iter().filter(|memory| !memory.is_expired(now));
+    Ok(builder.build(ndc_core::search_cosine(live, &vector, top_k)))
+}
+
+async fn cmd_context(
+    args: ContextArgs,
+    config: &CliConfig,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    if args.top_k == 0 {
+        return Err(CliError::InvalidArgument(
+            "--top-k must be at least 1".to_string(),
+        ));
+    }
+    let mut context_config = run_context_config(&AgentModeConfig::default().model);
+    if let Some(max_tokens) = args.max_tokens {
+        context_config.max_tokens = max_tokens;
+    }
+    let builder = ContextBuilder::new(context_config);
+
+    // Don't create the store just to preview it
+    let storage: ndc_runtime::SharedStorage = match config.store_path().exists() {
+        true => open_store(config).await?,
+        false => Arc::new(MemoryStorage::new()),
+    };
+    let embedder = ndc_core::embedder_from_env();
+    let preview = preview_context(
+        storage.as_ref(),
+        embedder.as_ref(),
+        &builder,
+        &args.query,
+        args.top_k,
+    )
+    .await?;
+
+    let write_err = |e: std::io::Error| CliError::StorageError(e.to_string());
+    match config.output_format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let json = match config.output_format {
+                OutputFormat::Json => serde_json::to_string_pretty(&preview),
+                _ => serde_json::to_string(&preview),
+            }
+            .map_err(|e| CliError::StorageError(e.to_string()))?;
+            writeln!(out, "{}", json).map_err(write_err)?;
+        }
+        OutputFormat::Pretty | OutputFormat::Minimal => {
+            write!(out, "{}", preview.render()).map_err(write_err)?
+        }
+    }
+    Ok(())
+}
+
+/// Retrieve and budget memories for `query` the way a run would, without
+/// calling the LLM
+pub(crate) async fn preview_context(
+    storage: &dyn ndc_runtime::Storage,
+    embedder: &dyn Embedder,
+    builder: &ContextBuilder,
+    query: &str,
+    top_k: usize,
+) -> Result<ContextPreview, CliError> {
+    let built = assemble_context(storage, embedder, builder, query, top_k).await?;
+
+    let entry = |scored: &ndc_core::ScoredMemory| PreviewMemory {
+        id: scored.memory.id.0.to_string(),
+        stability: scored.memory.metadata.stability,
+        score: scored.score,
+        tokens: builder.memory_tokens(&scored.memory),
+    };
+    Ok(ContextPreview {
+        query: query.to_string(),
+        model: builder.config().model.clone(),
+        max_tokens: builder.config().max_tokens,
+        prompt_tokens: built.tokens,
+        included: built.included.iter().map(entry).collect(),
+        dropped: built.dropped.iter().map(entry).collect(),
+    })
+}
+
+impl ContextPreview {
+    /// Human-readable listing for `--output pretty`
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Context for '{}' (model {}, budget {} tokens):\n",
+            self.query, self.model, self.max_tokens
+        );
+        if self.included.is_empty() {
+            out.push_str("  (no memories selected)\n");
+        }
+        let line = |m: &PreviewMemory| {
+            format!(
+                "  {}  {:<9}  score {:.3}  {} tokens\n",
+                m.id,
+                format!("{:?}", m.stability),
+                m.score,
+                m.tokens
+            )
+        };
+        self.included.iter().for_each(|m| out.push_str(&line(m)));
+        if !self.dropped.is_empty() {
+            out.push_str(&format!("Dropped ({}, over budget):\n", self.dropped.len()));
+            self.dropped.iter().for_each(|m| out.push_str(&line(m)));
+        }
+        out.push_str(&format!(
+            "Prompt context: {} / {} tokens\n",
+            self.prompt_tokens, self.max_tokens
+        ));
+        out
+    }
+}
+
+/// `status-system --output json` schema version; bump on breaking changes
+pub(crate) const SYSTEM_STATUS_SCHEMA_VERSION: u32 = 1;
+
+/// Machine-readable `status-system` report
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SystemStatus {
+    pub schema_version: u32,
+    pub storage: StorageStatus,
+    pub tasks: TaskCounts,
+    pub sessions: SessionCounts,
+    pub memories: usize,
+    pub providers: Vec<ProviderStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct StorageStatus {
+    pub backend: String,
+    pub path: PathBuf,
+}
+
+/// Task counts; every state is listed, zero counts included
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TaskCounts {
+    pub total: usize,
+    pub by_state: std::collections::BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionCounts {
+    /// Archived sessions still thinking, executing or awaiting permission
+    pub active: usize,
+    pub archived: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ProviderStatus {
+    pub name: String,
+    /// Provider used by agent runs
+    pub default: bool,
+    pub api_key_configured: bool,
+}
+
+/// Gather the status report for `config`'s store; `storage` is `None` when
+/// no store exists yet
+pub(crate) async fn collect_system_status(
+    storage: Option<&dyn ndc_runtime::Storage>,
+    config: &CliConfig,
+    sessions: &[ndc_core::AgentSession],
+    providers: Vec<ProviderStatus>,
+) -> Result<SystemStatus, CliError> {
+    use ndc_core::{SessionState, TaskState};
+
+    let mut by_state: std::collections::BTreeMap<String, usize> = [
+        TaskState::Pending,
+        TaskState::Preparing,
+        TaskState::InProgress,
+        TaskState::AwaitingVerification,
+        TaskState::Blocked,
+        TaskState::Completed,
+        TaskState::Failed,
+        TaskState::Cancelled,
+    ]
+    .iter()
+    .map(|state| (state.to_string(), 0))
+    .collect();
+    let (mut total, mut memories) = (0, 0);
+    if let Some(storage) = storage {
+        let tasks = storage.list_tasks().await.map_err(CliError::StorageError)?;
+        total = tasks.len();
+        for task in tasks {
+            *by_state.entry(task.state.to_string()).or_default() += 1;
+        }
+        memories = storage
+            .list_memories()
+            .await
+            .map_err(CliError::StorageError)?
+            .len();
+    }
+
+    let active = sessions
+        .iter()
+        .filter(|session| {
+            matches!(
+                session.state,
+                SessionState::Thinking
+                    | SessionState::WaitingForPermission
+                    | SessionState::Executing
+                    | SessionState::Verifying
+            )
+        })
+        .count();
+
+    Ok(SystemStatus {
+        schema_version: SYSTEM_STATUS_SCHEMA_VERSION,
+        storage: StorageStatus {
+            backend: config.storage_backend.name().to_string(),
+            path: config.store_path().to_path_buf(),
+        },
+        tasks: TaskCounts { total, by_state },
+        sessions: SessionCounts {
+            active,
+            archived: sessions.len(),
+        },
+        memories,
+        providers,
+    })
+}
+
+/// The default agent provider plus every provider configured under `llm.providers`
+fn configured_providers() -> Vec<ProviderStatus> {
+    let default = AgentModeConfig::default().provider;
+    let mut names = vec![default.clone()];
+    let mut loader = NdcConfigLoader::new();
+    if loader.load().is_ok()
+        && let Some(llm) = loader.config().llm.as_ref()
+    {
+        let mut configured: Vec<&String> = llm.providers.keys().collect();
+        configured.sort();
+        names.extend(configured.into_iter().filter(|n| **n != default).cloned());
+    }
+    names
+        .into_iter()
+        .map(|name| ProviderStatus {
+            default: name == default,
+            api_key_configured: !crate::provider_config::get_api_key(&name).is_empty(),
+            name,
+        })
+        .collect()
+}
+
+async fn cmd_status_system(
+    config: &CliConfig,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    // Don't create the store just to report on it
+    let storage = match config.store_path().exists() {
+        true => Some(open_store(config).await?),
+        false => None,
+    };
+    let sessions = crate::session_archive::SessionArchiveStore::load_default().all_sessions();
+    let status = collect_system_status(
+        storage.as_deref(),
+        config,
+        &sessions,
+        configured_providers(),
+    )
+    .await?;
+
+    let write_err = |e: std::io::Error| CliError::StorageError(e.to_string());
+    if matches!(
+        config.output_format,
+        OutputFormat::Json | OutputFormat::Jsonl
+    ) {
+        let json = match config.output_format {
+            OutputFormat::Json => serde_json::to_string_pretty(&status),
+            _ => serde_json::to_string(&status),
+        }
+        .map_err(|e| CliError::StorageError(e.to_string()))?;
+        writeln!(out, "{}", json).map_err(write_err)?;
+        return Ok(());
+    }
+
+    writeln!(out, "NDC System Status:").map_err(write_err)?;
+    writeln!(
+        out,
+        "  Storage: {} ({})",
+        status.storage.backend,
+        status.storage.path.display()
+    )
+    .map_err(write_err)?;
+    writeln!(
+        out,
+        "  Tasks: {}  Memories: {}  Active sessions: {}",
+        status.tasks.total, status.memories, status.sessions.active
+    )
+    .map_err(write_err)?;
+    for provider in &status.providers {
+        writeln!(
+            out,
+            "  Provider: {}{}{}",
+            provider.name,
+            if provider.default { " (default)" } else { "" },
+            if provider.api_key_configured {
+                ""
+            } else {
+                " [no API key]"
+            }
+        )
+        .map_err(write_err)?;
+    }
+    writeln!(out).map_err(write_err)?;
+    writeln!(out, "  Mode: AI Agent (natural language interaction)").map_err(write_err)?;
+    writeln!(out, "  REPL: Use 'ndc repl' for interactive mode").map_err(write_err)?;
+    writeln!(
+        out,
+        "  One-shot: Use 'ndc run --message \"...\"' for single messages"
+    )
+    .map_err(write_err)?;
+    writeln!(out).map_err(write_err)?;
+    writeln!(out, "Design Philosophy (from OpenCode):").map_err(write_err)?;
+    writeln!(out, "  - Human users interact via natural language").map_err(write_err)?;
+    writeln!(out, "  - AI automatically manages tasks internally").map_err(write_err)?;
+    writeln!(
+        out,
+        "  - Task commands removed from CLI (use natural language instead)"
+    )
+    .map_err(write_err)?;
+
+    Ok(())
+}
+
+async fn cmd_logs(
+    args: LogsArgs,
+    config: &CliConfig,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    use crate::logs::{LogEvent, archive_events, find_archived_session, follow, write_events};
+    use crate::session_archive::SessionArchiveStore;
+
+    let format = config.output_format;
+    if args.follow && format == OutputFormat::Json {
+        return Err(CliError::InvalidArgument(
+            "--follow prints events as they arrive; use --output jsonl".to_string(),
+        ));
+    }
+    if let Some(address) = args.daemon {
+        return cmd_logs_daemon(&address, args.id.as_deref(), args.follow, format, out).await;
+    }
+
+    let archive = SessionArchiveStore::load_default();
+    let session = find_archived_session(&archive, args.id.as_deref()).ok_or_else(|| {
+        CliError::InvalidArgument(format!(
+            "no archived session matches '{}'",
+            args.id.as_deref().unwrap_or("latest")
+        ))
+    })?;
+    let backlog: Vec<LogEvent> = session
+        .execution_events
+        .iter()
+        .map(LogEvent::from)
+        .collect();
+    write_events(out, &backlog, format).map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    if !args.follow || backlog.last().is_some_and(LogEvent::is_terminal) {
+        return Ok(());
+    }
+
+    let events = archive_events(
+        archive.path.clone(),
+        session.id.clone(),
+        backlog.len(),
+        Duration::from_millis(500),
+    );
+    follow(events, out, format)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(feature = "grpc")]
+async fn cmd_logs_daemon(
+    address: &str,
+    id: Option<&str>,
+    follow: bool,
+    format: OutputFormat,
+    out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    use crate::logs::{LogEvent, write_events};
+    use futures::StreamExt;
+
+    let client = crate::grpc_client::create_client(address)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    // Subscribe before reading the backlog so nothing falls in between
+    let live = if follow {
+        Some(
+            client
+                .subscribe_events(id)
+                .await
+                .map_err(|e| CliError::ExecutionFailed(e.to_string()))?,
+        )
+    } else {
+        None
+    };
+    let backlog = client
+        .get_session_timeline(id, None)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    let backlog: Vec<LogEvent> = backlog.events.into_iter().map(LogEvent::from).collect();
+    write_events(out, &backlog, format).map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+
+    let Some(live) = live else {
+        return Ok(());
+    };
+    if backlog.last().is_some_and(LogEvent::is_terminal) {
+        return Ok(());
+    }
+    let events = live.filter_map(|item| async move {
+        item.map(LogEvent::from)
+            .map_err(|e| warn!(error = %e, "event stream error"))
+            .ok()
+    });
+    crate::logs::follow(events, out, format)
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "grpc"))]
+async fn cmd_logs_daemon(
+    _address: &str,
+    _id: Option<&str>,
+    _follow: bool,
+    _format: OutputFormat,
+    _out: &mut (dyn std::io::Write + Send),
+) -> Result<(), CliError> {
+    Err(CliError::InvalidArgument(
+        "--daemon requires a build with the grpc feature".to_string(),
+    ))
+}
+
+async fn cmd_config_validate(args: ValidateArgs, config: &CliConfig) -> Result<(), CliError> {
+    let paths = NdcConfigLoader::new().config_paths();
+    let (loaded, mut report) = validate_config(&paths, &config.storage_path);
+    if args.check_connectivity
+        && let Some(loaded) = &loaded
+    {
+        check_provider_connectivity(loaded, &mut report).await;
     }
 
+    match config.output_format {
+        OutputFormat::Json | OutputFormat::Jsonl => println!(
+            "{}",
+            serde_json::to_string(&report).map_err(|e| CliError::ExecutionFailed(e.to_string()))?
+        ),
+        OutputFormat::Pretty | OutputFormat::Minimal => {
+            for path in paths.iter().filter(|p| p.exists()) {
+                println!("loaded {}", path.display());
+            }
+            println!("{}", report);
+        }
+    }
+
+    if report.has_errors() {
+        return Err(CliError::InvalidConfig(format!(
+            "{} error(s)",
+            report.errors().count()
+        )));
+    }
     Ok(())
 }
 
-async fn cmd_search(args: SearchArgs) -> Result<(), CliError> {
-    info!("Searching memory: {}", args.query);
+async fn cmd_verify(args: VerifyArgs, config: &CliConfig) -> Result<(), CliError> {
+    let mut loader = NdcConfigLoader::new();
+    let runtime = match loader.load() {
+        Ok(loaded) => loaded.runtime.clone(),
+        Err(e) => {
+            warn!("Config not loaded, using default quality commands: {}", e);
+            None
+        }
+    };
+    let report = crate::verify::verify(&config.project_root, &args.check, runtime.as_ref())
+        .await
+        .map_err(CliError::InvalidArgument)?;
 
-    println!("Search results for '{}':", args.query);
-    println!("  (AI memory search - full integration coming soon)");
+    match config.output_format {
+        OutputFormat::Json | OutputFormat::Jsonl => println!(
+            "{}",
+            serde_json::to_string(&report).map_err(|e| CliError::ExecutionFailed(e.to_string()))?
+        ),
+        OutputFormat::Pretty | OutputFormat::Minimal => println!("{}", report),
+    }
 
+    if report.exit_code() != 0 {
+        return Err(CliError::ExecutionFailed(format!(
+            "{} of {} quality check(s) failed",
+            report.failed(),
+            report.checks.len()
+        )));
+    }
     Ok(())
 }
 
-async fn cmd_status_system() -> Result<(), CliError> {
-    println!("NDC System Status:");
-    println!("  Mode: AI Agent (natural language interaction)");
-    println!("  REPL: Use 'ndc repl' for interactive mode");
-    println!("  One-shot: Use 'ndc run --message \"...\"' for single messages");
-    println!();
-    println!("Design Philosophy (from OpenCode):");
-    println!("  - Human users interact via natural language");
-    println!("  - AI automatically manages tasks internally");
-    println!("  - Task commands removed from CLI (use natural language instead)");
+/// Persistent store of `config.storage_backend`; saves embed memories that
+/// arrive without a vector and fold near-duplicates per `DedupPolicy::from_env`
+pub(crate) async fn open_store(config: &CliConfig) -> Result<ndc_runtime::SharedStorage, CliError> {
+    let store: ndc_runtime::SharedStorage = match &config.storage_backend {
+        StorageBackend::JsonLog => Arc::new(
+            ndc_runtime::JsonLogStorage::open(&config.storage_path)
+                .map_err(|e| CliError::StorageError(e.to_string()))?,
+        ),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite { db_path } => Arc::new(
+            ndc_runtime::SqliteStorage::new(db_path.clone())
+                .await
+                .map_err(|e| CliError::StorageError(e.to_string()))?,
+        ),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite { .. } => {
+            return Err(CliError::InvalidConfig(
+                "storage_type 'sqlite' needs ndc built with the `sqlite` feature".to_string(),
+            ));
+        }
+    };
+    let (dedup, dedup_threshold) = ndc_runtime::DedupPolicy::from_env();
+    Ok(Arc::new(
+        ndc_runtime::EmbeddingStorage::new(store, ndc_core::embedder_from_env())
+            .with_dedup(dedup, dedup_threshold),
+    ))
+}
+
+async fn cmd_memory_export(args: MemoryExportArgs, config: &CliConfig) -> Result<(), CliError> {
+    let storage = open_store(config).await?;
+    let file = std::fs::File::create(&args.out).map_err(|e| {
+        CliError::InvalidArgument(format!("cannot create {}: {}", args.out.display(), e))
+    })?;
+    let count = ndc_runtime::export_memories(&*storage, &mut std::io::BufWriter::new(file))
+        .await
+        .map_err(CliError::StorageError)?;
+    println!("Exported {} memories to {}", count, args.out.display());
+    Ok(())
+}
 
+async fn cmd_memory_import(args: MemoryImportArgs, config: &CliConfig) -> Result<(), CliError> {
+    let file = std::fs::File::open(&args.input).map_err(|e| {
+        CliError::InvalidArgument(format!("cannot open {}: {}", args.input.display(), e))
+    })?;
+    let mode = if args.replace {
+        ndc_runtime::ImportMode::Replace
+    } else {
+        ndc_runtime::ImportMode::Merge
+    };
+    let storage = open_store(config).await?;
+    let report = ndc_runtime::import_memories(&*storage, std::io::BufReader::new(file), mode)
+        .await
+        .map_err(CliError::StorageError)?;
+    println!(
+        "Imported {} memories ({} duplicates skipped, {} replaced)",
+        report.imported, report.skipped, report.removed
+    );
     Ok(())
 }
 
-fn create_execution_context(config: &CliConfig) -> ExecutionContext {
-    let storage: Arc<MemoryStorage> = Arc::new(MemoryStorage::new());
-    ExecutionContext {
+async fn cmd_rollback(args: RollbackArgs, config: &CliConfig) -> Result<(), CliError> {
+    let task_id: ndc_core::TaskId = args
+        .task
+        .parse()
+        .map_err(|_| CliError::InvalidArgument(format!("Invalid task id: {}", args.task)))?;
+    let executor = Executor::new(create_execution_context(config).await?);
+    let context = executor.context();
+    let mut saga =
+        ndc_runtime::SagaPlan::load(context.storage.as_ref(), &Executor::saga_id(&task_id))
+            .await
+            .map_err(|e| CliError::StorageError(e.to_string()))?
+            .ok_or_else(|| {
+                CliError::InvalidArgument(format!("No rollback plan for task {}", task_id))
+            })?;
+
+    let issues = match saga.steps.last() {
+        Some(last) => saga.validate_rollback(
+            &last.step_id,
+            &context.backup_store(),
+            &context.project_root,
+        ),
+        None => Vec::new(),
+    };
+    match config.output_format {
+        OutputFormat::Json | OutputFormat::Jsonl => println!(
+            "{}",
+            serde_json::json!({
+                "task_id": task_id.to_string(),
+                "steps": saga.summary().completed_steps,
+                "issues": issues.iter().map(|i| i.to_string()).collect::<Vec<_>>(),
+            })
+        ),
+        OutputFormat::Pretty | OutputFormat::Minimal => {
+            for issue in &issues {
+                println!("ISSUE {}", issue);
+            }
+            println!(
+                "{} completed step(s) to undo, {} issue(s)",
+                saga.summary().completed_steps,
+                issues.len()
+            );
+        }
+    }
+
+    if !issues.is_empty() {
+        return Err(CliError::ExecutionFailed(format!(
+            "rollback of task {} would fail: {} issue(s)",
+            task_id,
+            issues.len()
+        )));
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+    executor
+        .rollback_saga(&mut saga, &|undo| executor.apply_undo(undo))
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    println!("Rolled back task {}", task_id);
+    Ok(())
+}
+
+/// Load `paths` without failing fast and collect every problem, including
+/// the CLI storage directory
+pub(crate) fn validate_config(
+    paths: &[PathBuf],
+    storage_path: &Path,
+) -> (Option<ndc_core::NdcConfig>, ConfigReport) {
+    let mut loader = NdcConfigLoader::with_layers(Vec::new());
+    let (loaded, mut report) = match loader.load_unvalidated(paths) {
+        Ok(config) => (Some(config.clone()), config.check()),
+        Err(e) => {
+            let mut report = ConfigReport::default();
+            report.error("config", e.to_string());
+            (None, report)
+        }
+    };
+    report.check_storage_dir("--storage", storage_path);
+    (loaded, report)
+}
+
+/// Ping the selected provider and every provider override with a one-token
+/// completion, which checks the endpoint, the API key and the model at once
+async fn check_provider_connectivity(config: &ndc_core::NdcConfig, report: &mut ConfigReport) {
+    let Some(llm) = config.llm.as_ref().filter(|llm| llm.enabled) else {
+        return;
+    };
+    let mut targets = vec![(llm.provider.clone(), llm.model.clone())];
+    for (name, provider) in &llm.providers {
+        if name != &llm.provider {
+            let model = provider.model.clone().unwrap_or_else(|| llm.model.clone());
+            targets.push((name.clone(), model));
+        }
+    }
+
+    for (name, model) in targets {
+        let field = format!("connectivity.{}", name);
+        let provider = match crate::provider_config::create_provider(&name, &model) {
+            Ok(provider) => provider,
+            Err(e) => {
+                report.error(field, e.to_string());
+                continue;
+            }
+        };
+        let request = ndc_core::CompletionRequest {
+            model,
+            messages: vec![ndc_core::Message {
+                role: ndc_core::MessageRole::User,
+                content: "ping".to_string(),
+                name: None,
+                tool_calls: None,
+                cacheable: false,
+            }],
+            temperature: None,
+            max_tokens: Some(1),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            stop: None,
+            stream: false,
+            tools: None,
+            tool_choice: None,
+        };
+        match tokio::time::timeout(Duration::from_secs(10), provider.complete(&request)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => report.error(field, e.to_string()),
+            Err(_) => report.error(field, "timed out after 10s"),
+        }
+    }
+}
+
+async fn cmd_discovery_watch(args: WatchArgs, config: &CliConfig) -> Result<(), CliError> {
+    let repo = config.project_root.as_path();
+    let heatmap_config = HeatmapConfig {
+        lookback_days: args.lookback_days,
+        ..HeatmapConfig::default()
+    };
+    let mut watcher = HeatmapWatcher::new(repo, Some(heatmap_config))
+        .await
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    print_hot_modules(&watcher, args.top, config.output_format);
+
+    // HEAD lives in the (worktree) git dir, branch refs in the common dir
+    let git_dir = git_path(repo, "--git-dir")?;
+    let common_dir = git_path(repo, "--git-common-dir")?;
+    let refs_dir = common_dir.join("refs");
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+    let filter_refs = refs_dir.clone();
+    let mut fs_watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event
+                && !event.kind.is_access()
+                && event.paths.iter().any(|p| is_ref_path(p, &filter_refs))
+            {
+                let _ = tx.send(());
+            }
+        })
+        .map_err(|e| CliError::ExecutorInitFailed(e.to_string()))?;
+    let mut watches = vec![
+        (git_dir, RecursiveMode::NonRecursive),
+        (common_dir, RecursiveMode::NonRecursive),
+        (refs_dir, RecursiveMode::Recursive),
+    ];
+    watches.dedup_by(|a, b| a.0 == b.0);
+    for (dir, mode) in &watches {
+        fs_watcher
+            .watch(dir, *mode)
+            .map_err(|e| CliError::ExecutorInitFailed(e.to_string()))?;
+    }
+
+    let debounce = Duration::from_millis(args.debounce_ms);
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                if event.is_none() {
+                    break;
+                }
+                // Wait until the refs stay quiet, so a burst of commits is one update
+                while let Ok(Some(())) = tokio::time::timeout(debounce, rx.recv()).await {}
+                match watcher.refresh().await {
+                    Ok(true) => print_hot_modules(&watcher, args.top, config.output_format),
+                    Ok(false) => {}
+                    Err(e) => warn!("Heatmap refresh failed: {}", e),
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Absolute path reported by `git rev-parse <flag>`
+fn git_path(repo: &Path, flag: &str) -> Result<PathBuf, CliError> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--path-format=absolute", flag])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| CliError::ExecutionFailed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(CliError::InvalidArgument(format!(
+            "{} is not a git repository: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
+
+/// Whether a changed path under the git dir can move HEAD
+fn is_ref_path(path: &Path, refs_dir: &Path) -> bool {
+    path.starts_with(refs_dir)
+        || path
+            .file_name()
+            .is_some_and(|name| name == "HEAD" || name == "packed-refs")
+}
+
+fn print_hot_modules(watcher: &HeatmapWatcher, top: usize, format: OutputFormat) {
+    let modules = watcher.heatmap().get_modules_sorted();
+    let modules = &modules[..modules.len().min(top)];
+    let head = watcher.last_head().unwrap_or("");
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let update = serde_json::json!({
+                "head": head,
+                "modules": modules
+                    .iter()
+                    .map(|m| serde_json::json!({
+                        "name": m.module.name,
+                        "path": m.module.path,
+                        "score": m.score,
+                        "raw_count": m.raw_count,
+                        "decayed_count": m.decayed_count,
+                        "risk_level": m.risk_level,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", update);
+        }
+        OutputFormat::Pretty | OutputFormat::Minimal => {
+            println!(
+                "[{}] HEAD {} - top {} modules",
+                chrono::Local::now().format("%H:%M:%S"),
+                &head[..head.len().min(8)],
+                modules.len()
+            );
+            if modules.is_empty() {
+                println!("  (no changes in the look-back window)");
+            }
+            for (rank, m) in modules.iter().enumerate() {
+                println!(
+                    "  {:>2}. {:<24} score {:.2}  changes {:>3}  {:?}",
+                    rank + 1,
+                    m.module.path.display(),
+                    m.score,
+                    m.raw_count,
+                    m.risk_level
+                );
+            }
+        }
+    }
+}
+
+/// Execution context on the persistent store, so tasks, sagas and memories
+/// outlive the invocation that created them
+pub(crate) async fn create_execution_context(
+    config: &CliConfig,
+) -> Result<ExecutionContext, CliError> {
+    let storage = open_store(config).await?;
+    let commit_gate = ndc_runtime::CommitGate::new();
+    let lock_manager = Arc::new(ndc_runtime::FileLockManager::new(
+        config.storage_path.join("locks"),
+        None,
+    ));
+    Ok(ExecutionContext {
         storage: storage.clone(),
         workflow_engine: Arc::new(ndc_runtime::WorkflowEngine::new()),
         tools: Arc::new(ndc_runtime::create_default_tool_manager_with_storage(
             storage,
+            Some(lock_manager.clone()),
         )),
-        quality_runner: Arc::new(ndc_runtime::QualityGateRunner::new()),
+        quality_runner: Arc::new(
+            ndc_runtime::QualityGateRunner::load(&config.project_root)
+                .with_commit_gate(commit_gate.clone()),
+        ),
         project_root: config.project_root.clone(),
         current_role: AgentRole::Historian,
-    }
+        dry_run: false,
+        isolate_worktree: false,
+        retry_policy: ndc_runtime::RetryPolicy::default(),
+        events: Arc::new(ndc_runtime::EventEmitter::new()),
+        backup_dir: Some(config.storage_path.join("backups")),
+        clock: ndc_core::system_clock(),
+        decision_engine: Some(Arc::new(ndc_runtime::create_decision_engine(
+            &config.project_root,
+            &commit_gate,
+        ))),
+        commit_gate,
+        lock_manager: Some(lock_manager),
+    })
 }
 
 fn parse_model_spec(model_spec: &str) -> (&str, Option<&str>) {
@@ -40,6 +40,17 @@ mod tests {
         assert!(matches!(OutputFormat::Pretty, OutputFormat::Pretty));
         assert!(matches!(OutputFormat::Json, OutputFormat::Json));
         assert!(matches!(OutputFormat::Minimal, OutputFormat::Minimal));
+        assert!(matches!(OutputFormat::Jsonl, OutputFormat::Jsonl));
+    }
+
+    /// `--output jsonl` is accepted by the parser
+    #[test]
+    fn test_output_format_parses_jsonl() {
+        use clap::ValueEnum;
+        assert_eq!(
+            OutputFormat::from_str("jsonl", false).unwrap(),
+            OutputFormat::Jsonl
+        );
     }
 
     /// Test OutputFormat Copy trait
@@ -79,6 +90,7 @@ mod tests {
             storage_path: PathBuf::from("/custom/storage"),
             verbose: true,
             output_format: OutputFormat::Json,
+            ..Default::default()
         };
 
         assert_eq!(config.project_root, PathBuf::from("/custom/path"));
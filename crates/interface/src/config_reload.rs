@@ -0,0 +1,313 @@
+//! Config Reload - 守护进程配置热加载
+//!
+//! 职责：
+//! - 监听各层 `config.yaml` 及同目录 `mcp.yaml` 的变更（notify）
+//! - 校验新配置，校验失败时保留旧配置继续运行
+//! - 可热更新的配置（LLM provider/模型、agents、已批准权限、质量检查命令、MCP 服务器等）对新的 turn 生效，
+//!   进行中的 turn 继续持有旧配置快照
+//! - 需要重启才能生效的配置（storage 后端）仅记录警告
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use ndc_core::{ConfigError, NdcConfig, NdcConfigLoader};
+use ndc_runtime::mcp::{MCP_CONFIG_FILE, parse_server_configs};
+
+/// 合并连续文件事件的等待时间
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// 可热加载的配置句柄
+#[derive(Debug)]
+pub struct ConfigReloader {
+    paths: Vec<PathBuf>,
+    current: RwLock<Arc<NdcConfig>>,
+    generation: AtomicU64,
+    /// 各配置目录下 `mcp.yaml` 的内容（不存在为 None）
+    mcp_files: Mutex<Vec<Option<String>>>,
+    /// `mcp.yaml` 内容变化时递增
+    mcp_generation: AtomicU64,
+}
+
+impl ConfigReloader {
+    /// 从显式配置文件列表创建（按合并顺序）
+    pub fn new(paths: Vec<PathBuf>) -> Result<Self, ConfigError> {
+        let config = NdcConfigLoader::new().load_from_paths(&paths)?.clone();
+        let mcp_files = read_mcp_files(&paths);
+        Ok(Self {
+            paths,
+            current: RwLock::new(Arc::new(config)),
+            generation: AtomicU64::new(0),
+            mcp_files: Mutex::new(mcp_files),
+            mcp_generation: AtomicU64::new(0),
+        })
+    }
+
+    /// 使用默认配置分层（全局 > 用户 > 项目）创建
+    pub fn from_default_layers() -> Result<Self, ConfigError> {
+        Self::new(NdcConfigLoader::new().config_paths())
+    }
+
+    /// 当前生效配置的快照；调用方在整个 turn 内持有同一份快照
+    pub fn current(&self) -> Arc<NdcConfig> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 每次成功重载后递增
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// 每次 `mcp.yaml` 内容变化后递增
+    pub fn mcp_generation(&self) -> u64 {
+        self.mcp_generation.load(Ordering::Acquire)
+    }
+
+    /// 被监听的配置文件
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// 配置文件所在目录（按合并顺序），即 MCP 服务器列表的查找目录
+    pub fn config_dirs(&self) -> Vec<PathBuf> {
+        config_dirs(&self.paths)
+    }
+
+    /// 重新加载并校验配置
+    ///
+    /// 校验失败（含 `mcp.yaml` 无法解析）时返回错误并保留旧配置。成功时
+    /// 返回需要重启才能生效的配置项名称；这些配置项保持旧值，其余配置立即替换。
+    pub fn reload(&self) -> Result<Vec<&'static str>, ConfigError> {
+        let loaded = NdcConfigLoader::new()
+            .load_from_paths(&self.paths)
+            .cloned()
+            .and_then(|config| {
+                let mcp_files = read_mcp_files(&self.paths);
+                validate_mcp_files(&mcp_files)?;
+                Ok((config, mcp_files))
+            });
+        let (mut next, mcp_files) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                warn!("Config reload rejected, keeping previous config: {}", e);
+                return Err(e);
+            }
+        };
+
+        let mut current = self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let restart_required = restart_required_changes(&current, &next);
+        for field in &restart_required {
+            warn!(
+                "Config field '{}' changed; restart the daemon to apply it",
+                field
+            );
+        }
+        if restart_required.contains(&"storage") {
+            next.storage = current.storage.clone();
+        }
+
+        *current = Arc::new(next);
+        let mut applied_mcp = self.mcp_files.lock().unwrap_or_else(|e| e.into_inner());
+        if *applied_mcp != mcp_files {
+            *applied_mcp = mcp_files;
+            self.mcp_generation.fetch_add(1, Ordering::AcqRel);
+        }
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        info!(generation, "Config reloaded");
+
+        Ok(restart_required)
+    }
+
+    /// 启动文件监听，返回的 watcher 需要在守护进程生命周期内保持存活
+    pub fn spawn_watcher(self: &Arc<Self>) -> notify::Result<RecommendedWatcher> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+        let mcp_paths: Vec<PathBuf> = self
+            .config_dirs()
+            .into_iter()
+            .map(|dir| dir.join(MCP_CONFIG_FILE))
+            .collect();
+        let watched_files: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .chain(&mcp_paths)
+            .filter_map(|path| {
+                let dir = path.parent()?.canonicalize().ok()?;
+                Some(dir.join(path.file_name()?))
+            })
+            .collect();
+
+        let filter = watched_files.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && !event.kind.is_access()
+                    && event.paths.iter().any(|p| filter.contains(p))
+                {
+                    let _ = tx.send(());
+                }
+            })?;
+
+        for file in &watched_files {
+            if let Some(dir) = file.parent() {
+                watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            }
+        }
+
+        let reloader = Arc::clone(self);
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                while rx.try_recv().is_ok() {}
+                let _ = reloader.reload();
+            }
+        });
+
+        info!(files = ?watched_files, "Watching config files for changes");
+        Ok(watcher)
+    }
+}
+
+fn config_dirs(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|path| path.parent().map(PathBuf::from))
+        .collect()
+}
+
+/// 各配置目录下 `mcp.yaml` 的当前内容
+fn read_mcp_files(paths: &[PathBuf]) -> Vec<Option<String>> {
+    config_dirs(paths)
+        .into_iter()
+        .map(|dir| std::fs::read_to_string(dir.join(MCP_CONFIG_FILE)).ok())
+        .collect()
+}
+
+fn validate_mcp_files(files: &[Option<String>]) -> Result<(), ConfigError> {
+    for content in files.iter().flatten() {
+        parse_server_configs(content)
+            .map_err(|e| ConfigError::ValidationError(format!("{}: {}", MCP_CONFIG_FILE, e)))?;
+    }
+    Ok(())
+}
+
+/// 对比新旧配置，找出只有重启才能生效的配置项
+fn restart_required_changes(old: &NdcConfig, new: &NdcConfig) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if old.storage != new.storage {
+        fields.push("storage");
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_config(dir: &TempDir, yaml: &str) -> PathBuf {
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, yaml).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_reload_applies_safe_changes() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir, "repl:\n  max_history: 10\n");
+        let reloader = ConfigReloader::new(vec![path.clone()]).unwrap();
+        let before = reloader.current();
+
+        write_config(&dir, "repl:\n  max_history: 20\n");
+        let restart_required = reloader.reload().unwrap();
+
+        assert!(restart_required.is_empty());
+        assert_eq!(reloader.generation(), 1);
+        assert_eq!(reloader.current().repl.as_ref().unwrap().max_history, 20);
+        // Snapshots taken before the reload are unaffected.
+        assert_eq!(before.repl.as_ref().unwrap().max_history, 10);
+    }
+
+    #[test]
+    fn test_reload_keeps_old_config_on_validation_failure() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir, "repl:\n  max_history: 10\n");
+        let reloader = ConfigReloader::new(vec![path]).unwrap();
+
+        write_config(&dir, "repl:\n  max_history: 0\n");
+        assert!(reloader.reload().is_err());
+
+        write_config(&dir, "repl: [not, a, map\n");
+        assert!(reloader.reload().is_err());
+
+        assert_eq!(reloader.generation(), 0);
+        assert_eq!(reloader.current().repl.as_ref().unwrap().max_history, 10);
+    }
+
+    #[test]
+    fn test_reload_flags_storage_change_as_restart_required() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir, "storage:\n  storage_type: memory\n");
+        let reloader = ConfigReloader::new(vec![path]).unwrap();
+
+        write_config(
+            &dir,
+            "storage:\n  storage_type: sqlite\nrepl:\n  max_history: 42\n",
+        );
+        let restart_required = reloader.reload().unwrap();
+
+        assert_eq!(restart_required, vec!["storage"]);
+        let current = reloader.current();
+        assert_eq!(current.storage.as_ref().unwrap().storage_type, "memory");
+        assert_eq!(current.repl.as_ref().unwrap().max_history, 42);
+    }
+
+    #[test]
+    fn test_reload_tracks_mcp_changes_and_rejects_invalid_mcp_config() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir, "repl:\n  max_history: 10\n");
+        let mcp_path = dir.path().join(MCP_CONFIG_FILE);
+        let reloader = ConfigReloader::new(vec![path]).unwrap();
+
+        reloader.reload().unwrap();
+        assert_eq!(reloader.mcp_generation(), 0);
+
+        std::fs::write(&mcp_path, "[]\n").unwrap();
+        reloader.reload().unwrap();
+        assert_eq!(reloader.mcp_generation(), 1);
+
+        write_config(&dir, "repl:\n  max_history: 20\n");
+        std::fs::write(&mcp_path, "- not: a server\n").unwrap();
+        assert!(reloader.reload().is_err());
+        assert_eq!(reloader.mcp_generation(), 1);
+        assert_eq!(reloader.generation(), 2);
+        assert_eq!(reloader.current().repl.as_ref().unwrap().max_history, 10);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reloads_on_file_change() {
+        let dir = TempDir::new().unwrap();
+        let path = write_config(&dir, "repl:\n  max_history: 10\n");
+        let reloader = Arc::new(ConfigReloader::new(vec![path]).unwrap());
+        let _watcher = reloader.spawn_watcher().unwrap();
+
+        write_config(&dir, "repl:\n  max_history: 30\n");
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while reloader.generation() == 0 && std::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(reloader.current().repl.as_ref().unwrap().max_history, 30);
+    }
+}
@@ -4,13 +4,22 @@
 //! - gRPC 服务端实现（当启用 grpc feature 时）
 //! - 任务管理 API
 //! - 健康检查
+//! - 会话级工作区（项目根目录 + 环境变量），隔离不同会话的工具调用
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use tracing::info;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
 
 use ndc_core::TaskId;
-use ndc_runtime::{ExecutionContext, Executor};
+use ndc_runtime::tools::{FsTool, GitTool, ReadTool, ShellTool, Tool, resolve_within_root};
+use ndc_runtime::{
+    ExecutionContext, Executor, FileLockManager, SharedStorage, ToolContext, ToolManager,
+    create_default_tool_manager_with_storage,
+};
+
+use crate::config_reload::ConfigReloader;
 
 /// gRPC 服务实现
 #[derive(Debug)]
@@ -21,8 +30,15 @@ pub struct NdcDaemon {
     address: SocketAddr,
     /// 运行状态
     running: bool,
+    /// 可热加载的配置
+    config: Option<Arc<ConfigReloader>>,
+    /// 会话 ID → 工作区
+    workspaces: SessionWorkspaces,
 }
 
+/// 会话 ID → 工作区，由守护进程与其 agent manager 共享
+pub type SessionWorkspaces = Arc<RwLock<HashMap<String, SessionWorkspace>>>;
+
 impl NdcDaemon {
     /// 创建新的守护进程实例
     pub fn new(executor: Arc<Executor>, address: SocketAddr) -> Self {
@@ -30,9 +46,22 @@ impl NdcDaemon {
             executor,
             address,
             running: false,
+            config: None,
+            workspaces: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// 绑定可热加载的配置
+    pub fn with_config_reloader(mut self, config: Arc<ConfigReloader>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// 获取可热加载的配置
+    pub fn config_reloader(&self) -> Option<&Arc<ConfigReloader>> {
+        self.config.as_ref()
+    }
+
     /// 获取执行器引用
     pub fn executor(&self) -> &Arc<Executor> {
         &self.executor
@@ -52,6 +81,214 @@ impl NdcDaemon {
     pub fn set_running(&mut self, running: bool) {
         self.running = running;
     }
+
+    /// 为会话设置工作区（覆盖之前的设置）
+    pub fn configure_session(&self, session_id: impl Into<String>, workspace: SessionWorkspace) {
+        self.workspaces
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session_id.into(), workspace);
+    }
+
+    /// 会话的工作区；未设置时为 None（使用守护进程自身的 cwd 与环境）
+    pub fn session_workspace(&self, session_id: &str) -> Option<SessionWorkspace> {
+        self.workspaces
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(session_id)
+            .cloned()
+    }
+
+    /// 全部会话工作区（共享引用，后续设置对持有者可见）
+    pub fn session_workspaces(&self) -> SessionWorkspaces {
+        self.workspaces.clone()
+    }
+}
+
+/// 会话级工作区：工具调用的项目根目录与环境变量覆盖
+///
+/// 相当于 CLI 的 `-p`，但按 gRPC 会话生效：
+/// - 相对路径按项目根目录解析，越出根目录的路径一律拒绝
+/// - shell 等工具的工作目录固定为项目根目录
+/// - 环境变量覆盖只作用于该会话的子进程
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionWorkspace {
+    project_root: PathBuf,
+    env: HashMap<String, String>,
+}
+
+/// 按路径解析的工具参数
+const PATH_PARAMS: &[&str] = &["path", "to", "working_dir"];
+
+/// `patch`（unified diff）与 `files`（结构化 hunks）中的目标文件
+pub(crate) fn patch_paths(params: &serde_json::Map<String, serde_json::Value>) -> Vec<&str> {
+    let mut paths: Vec<&str> = params
+        .get("patch")
+        .and_then(|v| v.as_str())
+        .into_iter()
+        .flat_map(str::lines)
+        .filter_map(|line| {
+            line.strip_prefix("--- ")
+                .or_else(|| line.strip_prefix("+++ "))
+        })
+        .map(|path| path.split('\t').next().unwrap_or(path).trim())
+        .filter(|path| *path != "/dev/null")
+        .map(|path| {
+            path.strip_prefix("a/")
+                .or_else(|| path.strip_prefix("b/"))
+                .unwrap_or(path)
+        })
+        .collect();
+    if let Some(files) = params.get("files").and_then(|v| v.as_array()) {
+        paths.extend(
+            files
+                .iter()
+                .filter_map(|file| file.get("path").and_then(|v| v.as_str())),
+        );
+    }
+    paths
+}
+
+impl SessionWorkspace {
+    /// `project_root` 必须是已存在目录的绝对路径
+    pub fn new(
+        project_root: impl AsRef<Path>,
+        env: HashMap<String, String>,
+    ) -> Result<Self, DaemonError> {
+        let project_root = project_root.as_ref();
+        if !project_root.is_absolute() {
+            return Err(DaemonError::InvalidRequest(format!(
+                "project_root must be an absolute path: {}",
+                project_root.display()
+            )));
+        }
+        let project_root = std::fs::canonicalize(project_root)
+            .ok()
+            .filter(|root| root.is_dir())
+            .ok_or_else(|| {
+                DaemonError::InvalidRequest(format!(
+                    "project_root is not a directory: {}",
+                    project_root.display()
+                ))
+            })?;
+        if let Some(key) = env
+            .keys()
+            .find(|key| key.is_empty() || key.contains(['=', '\0']))
+        {
+            return Err(DaemonError::InvalidRequest(format!(
+                "invalid environment variable name: {:?}",
+                key
+            )));
+        }
+        Ok(Self { project_root, env })
+    }
+
+    /// 项目根目录（已规范化）
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    /// 环境变量覆盖
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// 该会话的工具上下文：工作目录为项目根目录，环境为守护进程环境叠加覆盖
+    pub fn tool_context(&self) -> ToolContext {
+        let mut env_vars: HashMap<String, String> = std::env::vars().collect();
+        env_vars.extend(self.env.clone());
+        ToolContext {
+            working_dir: self.project_root.clone(),
+            env_vars,
+            ..ToolContext::default()
+        }
+    }
+
+    /// 按项目根目录解析路径，越界时报错
+    pub fn resolve_path(&self, path: &str) -> Result<PathBuf, DaemonError> {
+        resolve_within_root(Path::new(path), &self.project_root)
+            .map_err(|e| DaemonError::PathOutsideRoot(e.to_string()))
+    }
+
+    /// 将工具参数中的路径解析为项目根目录下的绝对路径；工具声明了
+    /// `working_dir` 或可选的 `path` 参数（见 `schema`）但调用未指定时
+    /// 固定为项目根目录
+    pub fn scope_params(
+        &self,
+        params: &serde_json::Value,
+        schema: &serde_json::Value,
+    ) -> Result<serde_json::Value, DaemonError> {
+        let mut params = params.clone();
+        let Some(object) = params.as_object_mut() else {
+            return Ok(params);
+        };
+        for key in PATH_PARAMS {
+            if let Some(path) = object.get(*key).and_then(|v| v.as_str()) {
+                let resolved = self.resolve_path(path)?;
+                object.insert(key.to_string(), resolved.to_string_lossy().into());
+            }
+        }
+        if let Some(paths) = object.get_mut("paths").and_then(|v| v.as_array_mut()) {
+            for path in paths.iter_mut() {
+                if let Some(raw) = path.as_str() {
+                    *path = self.resolve_path(raw)?.to_string_lossy().into();
+                }
+            }
+        }
+        // apply_patch：补丁内的文件路径相对 working_dir
+        let base = object
+            .get("working_dir")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.project_root.clone());
+        for path in patch_paths(object) {
+            resolve_within_root(&base.join(path), &self.project_root)
+                .map_err(|e| DaemonError::PathOutsideRoot(e.to_string()))?;
+        }
+        if schema["properties"].get("working_dir").is_some() {
+            object
+                .entry("working_dir")
+                .or_insert_with(|| self.project_root.to_string_lossy().into());
+        }
+        // grep/glob/list 缺省 `path` 时搜索进程 cwd，同样固定为项目根目录
+        let path_required = schema["required"]
+            .as_array()
+            .is_some_and(|required| required.iter().any(|key| key == "path"));
+        if schema["properties"].get("path").is_some() && !path_required {
+            object
+                .entry("path")
+                .or_insert_with(|| self.project_root.to_string_lossy().into());
+        }
+        Ok(params)
+    }
+
+    /// 将 `tool` 绑定到该会话的工具上下文；不依赖上下文的工具原样返回
+    pub fn bind_tool(&self, name: &str, tool: Arc<dyn Tool>) -> Arc<dyn Tool> {
+        let context = self.tool_context();
+        match name {
+            "fs" => Arc::new(FsTool::with_context(context)),
+            "shell" => Arc::new(ShellTool::new().with_context(context)),
+            "git" => Arc::new(GitTool::with_context(context)),
+            "read" => Arc::new(ReadTool::with_context(context)),
+            _ => tool,
+        }
+    }
+
+    /// 使用该会话上下文的工具管理器
+    pub fn tool_manager(
+        &self,
+        storage: SharedStorage,
+        lock_manager: Option<Arc<FileLockManager>>,
+    ) -> ToolManager {
+        let context = self.tool_context();
+        let mut manager = create_default_tool_manager_with_storage(storage, lock_manager)
+            .with_context(context.clone());
+        manager.register("fs", FsTool::with_context(context.clone()));
+        manager.register("shell", ShellTool::new().with_context(context.clone()));
+        manager.register("git", GitTool::with_context(context.clone()));
+        manager.register("read", ReadTool::with_context(context));
+        manager
+    }
 }
 
 /// 健康检查服务
@@ -100,15 +337,104 @@ pub enum DaemonError {
 
     #[error("无效的请求: {0}")]
     InvalidRequest(String),
+
+    #[error("路径越界: {0}")]
+    PathOutsideRoot(String),
+}
+
+/// 加载配置并启动文件监听
+///
+/// 配置无效或监听失败时仅记录警告，守护进程照常启动。
+pub(crate) fn start_config_reloader() -> Option<(Arc<ConfigReloader>, notify::RecommendedWatcher)> {
+    let reloader = match ConfigReloader::from_default_layers() {
+        Ok(reloader) => Arc::new(reloader),
+        Err(e) => {
+            warn!("Config hot-reload disabled: {}", e);
+            return None;
+        }
+    };
+    match reloader.spawn_watcher() {
+        Ok(watcher) => Some((reloader, watcher)),
+        Err(e) => {
+            warn!("Config hot-reload disabled: {}", e);
+            None
+        }
+    }
+}
+
+/// 守护进程的执行上下文；文件锁位于 `storage_path/locks`，executor 与 agent 工具共用
+pub(crate) fn daemon_execution_context(storage_path: &Path) -> ExecutionContext {
+    let mut context = ExecutionContext::default();
+    context.quality_runner = Arc::new(
+        ndc_runtime::QualityGateRunner::load(&context.project_root)
+            .with_commit_gate(context.commit_gate.clone()),
+    );
+    let lock_manager = Arc::new(FileLockManager::new(storage_path.join("locks"), None));
+    context.tools = Arc::new(create_default_tool_manager_with_storage(
+        context.storage.clone(),
+        Some(lock_manager.clone()),
+    ));
+    context.lock_manager = Some(lock_manager);
+    context.decision_engine = Some(Arc::new(ndc_runtime::create_decision_engine(
+        Path::new("."),
+        &context.commit_gate,
+    )));
+    context
+}
+
+/// 检查到期延迟任务的间隔
+const DEFERRAL_TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// 周期性地重新评估到期的延迟任务（`Defer` 裁决），允许后即执行
+pub(crate) fn spawn_deferral_ticker(executor: Arc<Executor>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(DEFERRAL_TICK);
+        loop {
+            tick.tick().await;
+            for result in executor.run_due_deferrals().await {
+                match result {
+                    Ok(result) => info!(
+                        task_id = %result.task_id,
+                        state = ?result.final_state,
+                        "Deferred task re-evaluated"
+                    ),
+                    Err(e) => warn!("Deferred task failed: {}", e),
+                }
+            }
+        }
+    })
+}
+
+/// 过期记忆清理间隔
+const MEMORY_PURGE_TICK: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// 周期性地删除 TTL 已过的临时记忆；首次清理在启动时立即执行
+pub(crate) fn spawn_memory_purger(storage: SharedStorage) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(MEMORY_PURGE_TICK);
+        loop {
+            tick.tick().await;
+            match storage.purge_expired(storage.now()).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Expired memories purged"),
+                Err(e) => warn!("Memory purge failed: {}", e),
+            }
+        }
+    })
 }
 
 /// 运行守护进程
-pub async fn run_daemon(address: SocketAddr) {
+pub async fn run_daemon(address: SocketAddr, storage_path: &Path) {
     info!("Starting NDC Daemon on {}", address);
 
-    let context = ExecutionContext::default();
-    let executor = Arc::new(Executor::new(context));
+    let executor = Arc::new(Executor::new(daemon_execution_context(storage_path)));
+    let _deferrals = spawn_deferral_ticker(executor.clone());
+    let _purger = spawn_memory_purger(executor.context().storage.clone());
     let mut daemon = NdcDaemon::new(executor, address);
+    let (config, _config_watcher) = start_config_reloader().unzip();
+    if let Some(config) = config {
+        daemon = daemon.with_config_reloader(config);
+    }
 
     info!("Daemon started");
     info!("Listening on: {}", address);
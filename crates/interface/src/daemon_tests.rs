@@ -150,4 +150,77 @@ mod tests {
         assert_eq!(error1, error2);
         assert_ne!(error1, error3);
     }
+
+    /// Test SessionWorkspace validation and parameter scoping
+    #[test]
+    fn test_session_workspace_scopes_params_to_root() {
+        use crate::daemon::{DaemonError, SessionWorkspace};
+        use std::collections::HashMap;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = std::fs::canonicalize(temp.path()).unwrap();
+        assert!(matches!(
+            SessionWorkspace::new("relative", HashMap::new()),
+            Err(DaemonError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            SessionWorkspace::new(root.join("missing"), HashMap::new()),
+            Err(DaemonError::InvalidRequest(_))
+        ));
+
+        let env = HashMap::from([("NDC_MODE".to_string(), "ci".to_string())]);
+        let workspace = SessionWorkspace::new(temp.path(), env).unwrap();
+        assert_eq!(workspace.project_root(), root.as_path());
+        let context = workspace.tool_context();
+        assert_eq!(context.working_dir, root);
+        assert_eq!(
+            context.env_vars.get("NDC_MODE").map(String::as_str),
+            Some("ci")
+        );
+
+        let schema = serde_json::json!({"properties": {"working_dir": {"type": "string"}}});
+        let scoped = workspace
+            .scope_params(&serde_json::json!({"path": "src/lib.rs"}), &schema)
+            .unwrap();
+        assert_eq!(
+            scoped["path"],
+            root.join("src/lib.rs").to_string_lossy().as_ref()
+        );
+        assert_eq!(scoped["working_dir"], root.to_string_lossy().as_ref());
+
+        let patch = "--- a/../outside.rs\n+++ b/../outside.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        assert!(matches!(
+            workspace.scope_params(&serde_json::json!({"patch": patch}), &schema),
+            Err(DaemonError::PathOutsideRoot(_))
+        ));
+    }
+
+    /// An optional `path` left out defaults to the project root; a required one is left to the tool
+    #[test]
+    fn test_session_workspace_defaults_optional_path_to_root() {
+        use crate::daemon::SessionWorkspace;
+        use std::collections::HashMap;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = std::fs::canonicalize(temp.path()).unwrap();
+        let workspace = SessionWorkspace::new(temp.path(), HashMap::new()).unwrap();
+
+        let grep = serde_json::json!({
+            "properties": {"pattern": {"type": "string"}, "path": {"type": "string"}},
+            "required": ["pattern"]
+        });
+        let scoped = workspace
+            .scope_params(&serde_json::json!({"pattern": "fn"}), &grep)
+            .unwrap();
+        assert_eq!(scoped["path"], root.to_string_lossy().as_ref());
+
+        let read = serde_json::json!({
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"]
+        });
+        let scoped = workspace
+            .scope_params(&serde_json::json!({}), &read)
+            .unwrap();
+        assert!(scoped.get("path").is_none());
+    }
 }
@@ -25,6 +25,15 @@ fn create_test_executor() -> Arc<Executor> {
         quality_runner: Arc::new(QualityGateRunner::new()),
         project_root: std::env::current_dir().unwrap_or(PathBuf::from(".")),
         current_role: AgentRole::Historian,
+        dry_run: false,
+        isolate_worktree: false,
+        retry_policy: ndc_runtime::RetryPolicy::default(),
+        events: Arc::new(ndc_runtime::EventEmitter::new()),
+        backup_dir: None,
+        clock: ndc_core::system_clock(),
+        decision_engine: None,
+        commit_gate: Default::default(),
+        lock_manager: None,
     };
     Arc::new(Executor::new(context))
 }
@@ -36,6 +45,7 @@ fn create_test_config() -> crate::cli::CliConfig {
         storage_path: PathBuf::from(".ndc/test_storage"),
         verbose: true,
         output_format: crate::cli::OutputFormat::Pretty,
+        storage_backend: crate::cli::StorageBackend::JsonLog,
     }
 }
 
@@ -159,6 +169,7 @@ mod e2e_tests {
             step_id: 1,
             action: ndc_core::Action::ReadFile {
                 path: file_path.clone(),
+                range: None,
             },
             status: ndc_core::StepStatus::Completed,
             result: Some(ndc_core::ActionResult {
@@ -247,7 +258,7 @@ mod e2e_tests {
         assert_eq!(fs_tool.name(), "fs");
         assert_eq!(
             fs_tool.description(),
-            "File system operations: read, write, create, delete, list"
+            "File system operations: read, write, create, delete, move, list"
         );
 
         // Non-existent tool
@@ -269,7 +280,7 @@ mod e2e_tests {
     #[tokio::test]
     async fn test_smoke_ndc_task_tools_chain() {
         let storage = create_memory_storage();
-        let manager = create_default_tool_manager_with_storage(storage.clone());
+        let manager = create_default_tool_manager_with_storage(storage.clone(), None);
 
         let create = manager
             .execute(
@@ -363,6 +374,7 @@ mod e2e_tests {
             agent_role: AgentRole::Implementer,
             proposed_action: ndc_core::Action::ReadFile {
                 path: existing_file,
+                range: None,
             },
             effects: Vec::new(),
             reasoning: "trigger discovery".to_string(),
@@ -383,6 +395,115 @@ mod e2e_tests {
         }
     }
 
+    /// A task that fails at step 2 resumes from step 2 without re-running step 1
+    #[tokio::test]
+    async fn test_resume_skips_completed_steps() {
+        let _guard = DISCOVERY_ENV_LOCK.lock().unwrap();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let context = ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let executor = Arc::new(Executor::new(context));
+        let first = temp_dir.path().join("first.txt");
+        let missing = temp_dir.path().join("input.txt");
+        let third = temp_dir.path().join("third.txt");
+
+        let mut task = executor
+            .create_task(
+                "Resumable".to_string(),
+                "Fails at step 2".to_string(),
+                AgentRole::Implementer,
+            )
+            .await
+            .unwrap();
+        let actions = [
+            ndc_core::Action::WriteFile {
+                path: first.clone(),
+                content: "one".to_string(),
+            },
+            ndc_core::Action::ReadFile {
+                path: missing.clone(),
+                range: None,
+            },
+            ndc_core::Action::WriteFile {
+                path: third.clone(),
+                content: "three".to_string(),
+            },
+        ];
+        task.steps = actions
+            .into_iter()
+            .enumerate()
+            .map(|(i, action)| ndc_core::ExecutionStep {
+                step_id: i as u64 + 1,
+                action,
+                status: ndc_core::StepStatus::Pending,
+                result: None,
+                executed_at: None,
+            })
+            .collect();
+        executor.context().storage.save_task(&task).await.unwrap();
+
+        assert!(executor.execute_task(task.id).await.is_err());
+        let failed = executor
+            .context()
+            .storage
+            .get_task(&task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.steps[0].status, ndc_core::StepStatus::Completed);
+        assert_eq!(failed.steps[1].status, ndc_core::StepStatus::Failed);
+        assert_eq!(failed.steps[2].status, ndc_core::StepStatus::Pending);
+
+        // Step 1 must not run again: a re-run would overwrite this edit
+        std::fs::write(&first, "edited").unwrap();
+        std::fs::write(&missing, "input").unwrap();
+
+        let result = executor.resume_task(task.id).await.unwrap();
+        assert_eq!(result.final_state, TaskState::Completed);
+        assert!(
+            result
+                .steps
+                .iter()
+                .all(|s| s.status == ndc_core::StepStatus::Completed)
+        );
+        assert_eq!(result.steps[0].executed_at, failed.steps[0].executed_at);
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "edited");
+        assert_eq!(std::fs::read_to_string(&third).unwrap(), "three");
+
+        // The saga spans the resume boundary, so rollback undoes both writes
+        let mut saga = ndc_runtime::SagaPlan::load(
+            executor.context().storage.as_ref(),
+            &Executor::saga_id(&task.id),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(saga.steps.len(), 2);
+        executor
+            .rollback_saga(&mut saga, &|undo| async move {
+                match undo {
+                    ndc_runtime::UndoAction::DeleteFile { path } => {
+                        std::fs::remove_file(path).map_err(|e| e.to_string())
+                    }
+                    other => Err(format!("unexpected undo {:?}", other)),
+                }
+            })
+            .await
+            .unwrap();
+        assert!(!first.exists());
+        assert!(!third.exists());
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
     /// Discovery signals should be persisted into GoldMemory even when degrading on failure.
     #[tokio::test]
     async fn test_discovery_signal_persisted_to_gold_memory() {
@@ -420,6 +541,7 @@ mod e2e_tests {
             agent_role: AgentRole::Implementer,
             proposed_action: ndc_core::Action::ReadFile {
                 path: existing_file,
+                range: None,
             },
             effects: Vec::new(),
             reasoning: "trigger discovery".to_string(),
@@ -489,12 +611,13 @@ mod e2e_tests {
                 version: 2,
                 modified_at: Some(chrono::Utc::now()),
                 tags: vec!["gold-memory".to_string()],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::system(), MemoryStability::Canonical),
         };
         storage.save_memory(&entry).await.unwrap();
 
-        let manager = create_default_tool_manager_with_storage(storage);
+        let manager = create_default_tool_manager_with_storage(storage, None);
         let query = manager
             .execute(
                 "ndc_memory_query",
@@ -623,6 +746,947 @@ mod cli_storage_tests {
         assert_eq!(config.storage_path, temp_dir.path());
     }
 
+    fn seeded_memory(text: &str, stability: MemoryStability, tags: &[&str]) -> MemoryEntry {
+        MemoryEntry {
+            id: ndc_core::MemoryId::new(),
+            content: MemoryContent::General {
+                text: text.to_string(),
+                metadata: String::new(),
+            },
+            embedding: Vec::new(),
+            relations: Vec::new(),
+            metadata: MemoryMetadata {
+                stability,
+                created_at: chrono::Utc::now(),
+                created_by: AgentId::system(),
+                source_task: ndc_core::TaskId::new(),
+                version: 1,
+                modified_at: None,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                expires_at: None,
+            },
+            access_control: AccessControl::new(AgentId::system(), stability),
+        }
+    }
+
+    fn parse_search(argv: &[&str]) -> crate::cli::SearchArgs {
+        #[derive(clap::Parser)]
+        struct Wrapper {
+            #[command(flatten)]
+            args: crate::cli::SearchArgs,
+        }
+        let argv = std::iter::once("search").chain(argv.iter().copied());
+        <Wrapper as clap::Parser>::try_parse_from(argv)
+            .unwrap()
+            .args
+    }
+
+    /// `ndc search` filters by stability, tag and type alongside the text query
+    #[tokio::test]
+    async fn test_search_filters_narrow_results() {
+        use ndc_runtime::Storage;
+
+        let storage = MemoryStorage::new();
+        for entry in [
+            seeded_memory(
+                "cache invalidation rules",
+                MemoryStability::Canonical,
+                &["cache", "rules"],
+            ),
+            seeded_memory("cache warmup idea", MemoryStability::Ephemeral, &["cache"]),
+            seeded_memory("retry budget", MemoryStability::Verified, &["network"]),
+            seeded_memory(
+                "cache sizing",
+                MemoryStability::Verified,
+                &["cache", "perf"],
+            ),
+        ] {
+            storage.save_memory(&entry).await.unwrap();
+        }
+        let search = |argv: &'static [&'static str]| {
+            let query = parse_search(argv).to_query();
+            let storage = &storage;
+            async move { crate::cli::search_memories(storage, &query).await.unwrap() }
+        };
+
+        assert_eq!(search(&["cache"]).await.len(), 3);
+
+        let hits = search(&["cache", "--min-stability", "verified"]).await;
+        assert_eq!(hits.len(), 2);
+        assert!(hits[0].summary.contains("cache invalidation rules"));
+        assert!(hits.iter().all(|h| !h.summary.contains("warmup")));
+
+        let hits = search(&["--stability", "ephemeral"]).await;
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].summary.contains("cache warmup idea"));
+
+        let hits = search(&["--tag", "cache", "--tag", "perf"]).await;
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].summary.contains("cache sizing"));
+
+        assert_eq!(
+            search(&["--type", "general", "--tag", "network"])
+                .await
+                .len(),
+            1
+        );
+        assert!(search(&["--type", "decision"]).await.is_empty());
+        assert!(search(&["retry", "--tag", "cache"]).await.is_empty());
+    }
+
+    /// `status-system --output json` emits the stable top-level schema
+    #[tokio::test]
+    async fn test_system_status_json_schema() {
+        use ndc_runtime::Storage;
+
+        let storage = MemoryStorage::new();
+        let mut done = ndc_core::Task::new("done".into(), String::new(), AgentRole::Implementer);
+        done.state = ndc_core::TaskState::Completed;
+        storage.save_task(&done).await.unwrap();
+        storage
+            .save_task(&ndc_core::Task::new(
+                "todo".into(),
+                String::new(),
+                AgentRole::Implementer,
+            ))
+            .await
+            .unwrap();
+        storage
+            .save_memory(&seeded_memory("use tokio", MemoryStability::Verified, &[]))
+            .await
+            .unwrap();
+        let mut running = ndc_core::AgentSession::new("s-1".to_string());
+        running.state = ndc_core::SessionState::Executing;
+        let sessions = vec![running, ndc_core::AgentSession::new("s-2".to_string())];
+        let providers = vec![crate::cli::ProviderStatus {
+            name: "openai".to_string(),
+            default: true,
+            api_key_configured: false,
+        }];
+
+        let status = crate::cli::collect_system_status(
+            Some(&storage),
+            &crate::cli::CliConfig::default(),
+            &sessions,
+            providers,
+        )
+        .await
+        .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string_pretty(&status).unwrap()).unwrap();
+
+        let mut keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "memories",
+                "providers",
+                "schema_version",
+                "sessions",
+                "storage",
+                "tasks"
+            ]
+        );
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["storage"]["backend"], "json_log");
+        assert_eq!(json["tasks"]["total"], 2);
+        assert_eq!(json["tasks"]["by_state"]["Completed"], 1);
+        assert_eq!(json["tasks"]["by_state"]["Pending"], 1);
+        // Every state is present so dashboards can rely on the keys
+        assert_eq!(json["tasks"]["by_state"]["Blocked"], 0);
+        assert_eq!(json["sessions"]["active"], 1);
+        assert_eq!(json["sessions"]["archived"], 2);
+        assert_eq!(json["memories"], 1);
+        assert_eq!(json["providers"][0]["name"], "openai");
+
+        // The configured backend and its store are reported
+        let sqlite = crate::cli::CliConfig {
+            storage_backend: crate::cli::StorageBackend::Sqlite {
+                db_path: PathBuf::from(".ndc/ndc.db"),
+            },
+            ..Default::default()
+        };
+        let status = crate::cli::collect_system_status(None, &sqlite, &[], Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(status.storage.backend, "sqlite");
+        assert_eq!(status.storage.path, PathBuf::from(".ndc/ndc.db"));
+    }
+
+    /// `ndc --output json status-system` prints the report for the store it was given
+    #[tokio::test]
+    async fn test_status_system_command_prints_json() {
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        {
+            let storage = crate::cli::open_store(&config).await.unwrap();
+            let mut done =
+                ndc_core::Task::new("done".into(), String::new(), AgentRole::Implementer);
+            done.state = ndc_core::TaskState::Completed;
+            storage.save_task(&done).await.unwrap();
+            storage
+                .save_memory(&seeded_memory("use tokio", MemoryStability::Verified, &[]))
+                .await
+                .unwrap();
+        }
+
+        let stdout = run_cli(&config, &["--output", "json", "status-system"])
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["storage"]["backend"], config.storage_backend.name());
+        assert_eq!(
+            json["storage"]["path"],
+            config.store_path().to_string_lossy().as_ref()
+        );
+        assert_eq!(json["tasks"]["total"], 1);
+        assert_eq!(json["tasks"]["by_state"]["Completed"], 1);
+        assert_eq!(json["memories"], 1);
+        assert!(json["providers"].is_array());
+    }
+
+    /// `ndc context` lists the budgeted memories in score order with their token counts
+    #[tokio::test]
+    async fn test_context_preview_lists_memories_by_score() {
+        use ndc_core::{ContextBuilder, ContextConfig, Embedder, HashEmbedder};
+        use ndc_runtime::Storage;
+
+        let embedder = HashEmbedder::new(64);
+        let storage = MemoryStorage::new();
+        let mut ids = Vec::new();
+        for text in [
+            "retry budget for network calls",
+            "cache invalidation rules",
+            "cache invalidation rules for the build cache",
+        ] {
+            let mut entry = seeded_memory(text, MemoryStability::Verified, &[]);
+            entry.embedding = embedder.embed(&entry.content.summary()).await.unwrap();
+            ids.push(entry.id.0.to_string());
+            storage.save_memory(&entry).await.unwrap();
+        }
+        let builder = ContextBuilder::new(ContextConfig::new(
+            10_000,
+            Arc::new(ndc_core::llm::provider::SimpleTokenCounter::new()),
+        ));
+
+        let preview = crate::cli::preview_context(
+            &storage,
+            &embedder,
+            &builder,
+            "cache invalidation rules",
+            10,
+        )
+        .await
+        .unwrap();
+
+        let order: Vec<&str> = preview.included.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(order, vec![&ids[1], &ids[2], &ids[0]]);
+        assert!(
+            preview
+                .included
+                .windows(2)
+                .all(|w| w[0].score >= w[1].score)
+        );
+        assert!(preview.included.iter().all(|m| m.tokens > 0));
+        assert!(preview.prompt_tokens > preview.included[0].tokens);
+        assert!(preview.dropped.is_empty());
+        assert!(preview.render().contains(&format!(
+            "Prompt context: {} / 10000 tokens",
+            preview.prompt_tokens
+        )));
+
+        // A tight budget keeps the best match and reports the rest as dropped
+        let tight = ContextBuilder::new(ContextConfig::new(
+            preview.included[0].tokens + 8,
+            Arc::new(ndc_core::llm::provider::SimpleTokenCounter::new()),
+        ));
+        let preview = crate::cli::preview_context(
+            &storage,
+            &embedder,
+            &tight,
+            "cache invalidation rules",
+            10,
+        )
+        .await
+        .unwrap();
+        assert_eq!(preview.included.len(), 1);
+        assert_eq!(preview.included[0].id, ids[1]);
+        assert_eq!(preview.dropped.len(), 2);
+    }
+
+    /// Project dir plus CLI config whose store lives beside it
+    fn persistent_cli_config(dir: &TempDir) -> crate::cli::CliConfig {
+        let project_root = dir.path().join("project");
+        std::fs::create_dir_all(&project_root).unwrap();
+        crate::cli::CliConfig {
+            project_root,
+            storage_path: dir.path().join("storage"),
+            verbose: false,
+            output_format: crate::cli::OutputFormat::Json,
+            storage_backend: crate::cli::StorageBackend::JsonLog,
+        }
+    }
+
+    /// Parse `ndc --project-root P --storage S <args>` for `config`
+    fn cli_for(config: &crate::cli::CliConfig, args: &[&str]) -> crate::cli::Cli {
+        let root = config.project_root.to_string_lossy().to_string();
+        let storage = config.storage_path.to_string_lossy().to_string();
+        let argv = ["ndc", "--project-root", &root, "--storage", &storage]
+            .into_iter()
+            .chain(args.iter().copied());
+        <crate::cli::Cli as clap::Parser>::try_parse_from(argv).unwrap()
+    }
+
+    /// Run `ndc <args>` against `config`, returning what it wrote
+    async fn run_cli(
+        config: &crate::cli::CliConfig,
+        args: &[&str],
+    ) -> Result<String, crate::cli::CliError> {
+        let mut out = Vec::new();
+        crate::cli::dispatch(cli_for(config, args), &mut out).await?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    /// A task saved by one invocation is run by a later `ndc run --task`
+    #[tokio::test]
+    async fn test_run_task_from_earlier_invocation() {
+        let _guard = DISCOVERY_ENV_LOCK.lock().unwrap();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        let target = config.project_root.join("out.txt");
+        let task_id = {
+            let executor =
+                Executor::new(crate::cli::create_execution_context(&config).await.unwrap());
+            let mut task = executor
+                .create_task(
+                    "persisted".to_string(),
+                    String::new(),
+                    AgentRole::Implementer,
+                )
+                .await
+                .unwrap();
+            task.steps.push(ndc_core::ExecutionStep {
+                step_id: 1,
+                action: ndc_core::Action::WriteFile {
+                    path: target.clone(),
+                    content: "done".to_string(),
+                },
+                status: ndc_core::StepStatus::Pending,
+                result: None,
+                executed_at: None,
+            });
+            executor.context().storage.save_task(&task).await.unwrap();
+            task.id
+        };
+
+        let id = task_id.to_string();
+        run_cli(&config, &["run", "--task", &id]).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "done");
+
+        let context = crate::cli::create_execution_context(&config).await.unwrap();
+        let stored = context.storage.get_task(&task_id).await.unwrap().unwrap();
+        assert_eq!(stored.state, TaskState::Completed);
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    /// `ndc run --resume` finds a task interrupted in an earlier invocation
+    #[tokio::test]
+    async fn test_resume_task_from_earlier_invocation() {
+        let _guard = DISCOVERY_ENV_LOCK.lock().unwrap();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        let target = config.project_root.join("out.txt");
+        let task_id = {
+            let executor =
+                Executor::new(crate::cli::create_execution_context(&config).await.unwrap());
+            let mut task = executor
+                .create_task(
+                    "interrupted".to_string(),
+                    String::new(),
+                    AgentRole::Implementer,
+                )
+                .await
+                .unwrap();
+            task.state = TaskState::InProgress;
+            task.steps.push(ndc_core::ExecutionStep {
+                step_id: 1,
+                action: ndc_core::Action::WriteFile {
+                    path: target.clone(),
+                    content: "resumed".to_string(),
+                },
+                status: ndc_core::StepStatus::InProgress,
+                result: None,
+                executed_at: Some(chrono::Utc::now()),
+            });
+            executor.context().storage.save_task(&task).await.unwrap();
+            task.id
+        };
+
+        let id = task_id.to_string();
+        run_cli(&config, &["run", "--resume", &id]).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "resumed");
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    /// `ndc rollback` undoes a task run by an earlier `ndc run --task`
+    #[tokio::test]
+    async fn test_rollback_task_from_earlier_invocation() {
+        let _guard = DISCOVERY_ENV_LOCK.lock().unwrap();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        let existing = config.project_root.join("lib.rs");
+        let created = config.project_root.join("new.rs");
+        std::fs::write(&existing, "before").unwrap();
+        let task_id = {
+            let executor =
+                Executor::new(crate::cli::create_execution_context(&config).await.unwrap());
+            let mut task = executor
+                .create_task(
+                    "undoable".to_string(),
+                    String::new(),
+                    AgentRole::Implementer,
+                )
+                .await
+                .unwrap();
+            let writes = [(&existing, "after"), (&created, "fresh")];
+            for (n, (path, content)) in writes.into_iter().enumerate() {
+                task.steps.push(ndc_core::ExecutionStep {
+                    step_id: n as u64 + 1,
+                    action: ndc_core::Action::WriteFile {
+                        path: path.clone(),
+                        content: content.to_string(),
+                    },
+                    status: ndc_core::StepStatus::Pending,
+                    result: None,
+                    executed_at: None,
+                });
+            }
+            executor.context().storage.save_task(&task).await.unwrap();
+            task.id
+        };
+        let id = task_id.to_string();
+        run_cli(&config, &["run", "--task", &id]).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "after");
+
+        run_cli(&config, &["rollback", &id, "--dry-run"])
+            .await
+            .unwrap();
+        assert!(created.exists());
+
+        run_cli(&config, &["rollback", &id]).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "before");
+        assert!(!created.exists());
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    /// `ndc search` finds memories saved to the persistent store
+    #[tokio::test]
+    async fn test_search_finds_saved_memory() {
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        let search = ["search", "cache", "--output", "json"];
+        assert_eq!(run_cli(&config, &search).await.unwrap().trim(), "[]");
+        assert!(!config.store_path().exists());
+
+        {
+            let storage = crate::cli::open_store(&config).await.unwrap();
+            for memory in [
+                seeded_memory("cache invalidation rules", MemoryStability::Verified, &[]),
+                seeded_memory("retry budget", MemoryStability::Verified, &[]),
+            ] {
+                storage.save_memory(&memory).await.unwrap();
+            }
+        }
+
+        let hits: Vec<crate::cli::SearchHit> =
+            serde_json::from_str(&run_cli(&config, &search).await.unwrap()).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].summary.contains("cache invalidation rules"));
+    }
+
+    /// `--output jsonl`: every stdout line of `list`, `search` and `logs`
+    /// deserializes on its own
+    #[tokio::test]
+    async fn test_jsonl_output_lines_are_independent() {
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        let titles = ["first", "second", "third"];
+        {
+            let storage = crate::cli::open_store(&config).await.unwrap();
+            for title in titles {
+                let task =
+                    ndc_core::Task::new(title.to_string(), String::new(), AgentRole::Implementer);
+                storage.save_task(&task).await.unwrap();
+            }
+            for text in ["cache invalidation rules", "cache sizing"] {
+                let memory = seeded_memory(text, MemoryStability::Verified, &[]);
+                storage.save_memory(&memory).await.unwrap();
+            }
+        }
+
+        let stdout = run_cli(&config, &["--output", "jsonl", "list"])
+            .await
+            .unwrap();
+        assert!(stdout.ends_with('\n'));
+        let mut listed: Vec<String> = stdout
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<crate::cli::TaskSummary>(line)
+                    .unwrap()
+                    .title
+            })
+            .collect();
+        listed.sort();
+        assert_eq!(listed, titles);
+
+        let stdout = run_cli(&config, &["--output", "jsonl", "search", "cache"])
+            .await
+            .unwrap();
+        let hits: Vec<crate::cli::SearchHit> = stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(hits.len(), 2);
+
+        let archive_path = dir.path().join("session_archive.json");
+        let mut session = ndc_core::AgentSession::new("jsonl-session".to_string());
+        for message in ["thinking", "session_idle"] {
+            session.add_execution_event(ndc_core::AgentExecutionEvent {
+                kind: ndc_core::AgentExecutionEventKind::SessionStatus,
+                timestamp: chrono::Utc::now(),
+                message: message.to_string(),
+                round: 1,
+                tool_name: None,
+                tool_call_id: None,
+                duration_ms: None,
+                is_error: false,
+                workflow_stage: None,
+                workflow_detail: None,
+                workflow_stage_index: None,
+                workflow_stage_total: None,
+            });
+        }
+        let mut archive =
+            crate::session_archive::SessionArchiveStore::load_from(archive_path.clone());
+        archive.upsert(&session);
+        archive.save().unwrap();
+
+        let stdout = {
+            let _guard = DISCOVERY_ENV_LOCK.lock().unwrap();
+            unsafe {
+                std::env::set_var("NDC_SESSION_ARCHIVE_FILE", &archive_path);
+            }
+            let stdout = run_cli(&config, &["--output", "jsonl", "logs", "jsonl-session"]).await;
+            unsafe {
+                std::env::remove_var("NDC_SESSION_ARCHIVE_FILE");
+            }
+            stdout.unwrap()
+        };
+        let events: Vec<serde_json::Value> = stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["kind"], "SessionStatus");
+        assert_eq!(events[1]["message"], "session_idle");
+
+        // A pretty JSON array cannot be streamed
+        assert!(matches!(
+            run_cli(&config, &["--output", "json", "logs", "--follow"]).await,
+            Err(crate::cli::CliError::InvalidArgument(_))
+        ));
+    }
+
+    /// `ndc context` previews the persistent store with the model agent runs
+    /// use, and a run injects the same memories into its prompt
+    #[tokio::test]
+    async fn test_context_preview_matches_agent_run() {
+        let dir = TempDir::new().unwrap();
+        let config = persistent_cli_config(&dir);
+        let cache = seeded_memory("cache invalidation rules", MemoryStability::Verified, &[]);
+        {
+            let storage = crate::cli::open_store(&config).await.unwrap();
+            for memory in [
+                cache.clone(),
+                seeded_memory(
+                    "retry budget for network calls",
+                    MemoryStability::Derived,
+                    &[],
+                ),
+            ] {
+                storage.save_memory(&memory).await.unwrap();
+            }
+        }
+
+        let query = "cache invalidation rules";
+        let out = run_cli(&config, &["context", query, "--output", "json"])
+            .await
+            .unwrap();
+        let preview: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let model = crate::agent_mode::AgentModeConfig::default().model;
+        assert_eq!(preview["model"], model.as_str());
+        assert_eq!(preview["max_tokens"], ndc_core::DEFAULT_CONTEXT_TOKENS);
+        let included = preview["included"].as_array().unwrap();
+        assert_eq!(included.len(), 2);
+        assert_eq!(included[0]["id"], cache.id.0.to_string());
+
+        let context = crate::cli::create_execution_context(&config).await.unwrap();
+        let storage = context.storage.clone();
+        let manager = crate::agent_mode::AgentModeManager::new(
+            Arc::new(Executor::new(context)),
+            Arc::new(ndc_runtime::create_default_tool_registry_with_storage(
+                storage, None,
+            )),
+        );
+        let injected = manager.build_memory_context(query, &model).await.unwrap();
+        assert!(injected.contains("- [Verified] cache invalidation rules"));
+        assert!(injected.contains("- [Derived] retry budget for network calls"));
+    }
+
+    /// `ndc list --since` accepts s/m/h/d/w windows and rejects anything else
+    #[test]
+    fn test_list_since_units() {
+        use crate::cli::parse_since;
+
+        assert_eq!(parse_since("45s").unwrap(), chrono::Duration::seconds(45));
+        assert_eq!(parse_since("30m").unwrap(), chrono::Duration::minutes(30));
+        assert_eq!(parse_since("24h").unwrap(), chrono::Duration::hours(24));
+        assert_eq!(parse_since("2d").unwrap(), chrono::Duration::days(2));
+        assert_eq!(parse_since(" 1w ").unwrap(), chrono::Duration::weeks(1));
+        for bad in ["", "h", "24", "0d", "-1h", "1.5h", "5x", "3 days"] {
+            assert!(
+                matches!(
+                    parse_since(bad),
+                    Err(crate::cli::CliError::InvalidArgument(_))
+                ),
+                "{:?} should be rejected",
+                bad
+            );
+        }
+
+        // Accepted by the parser but before the earliest representable time
+        let cli = <crate::cli::Cli as clap::Parser>::try_parse_from([
+            "ndc",
+            "list",
+            "--since",
+            "15000000w",
+        ])
+        .unwrap();
+        let crate::cli::Commands::List(args) = cli.command else {
+            panic!("expected list");
+        };
+        assert!(matches!(
+            args.to_filter(chrono::Utc::now()),
+            Err(crate::cli::CliError::InvalidArgument(_))
+        ));
+    }
+
+    /// `--since` keeps tasks created or modified inside the window, and
+    /// combines with `--state` and `--role`
+    #[tokio::test]
+    async fn test_list_since_narrows_results() {
+        use ndc_core::Task;
+        use ndc_runtime::Storage;
+
+        let now = chrono::Utc::now();
+        let ago = |minutes: i64| now - chrono::Duration::minutes(minutes);
+        let seeded = |title: &str, created: i64, updated: i64, state, role| {
+            let mut task = Task::new(title.to_string(), String::new(), role);
+            task.state = state;
+            task.metadata.created_at = ago(created);
+            task.metadata.updated_at = ago(updated);
+            task
+        };
+        let storage = MemoryStorage::new();
+        for task in [
+            seeded("fresh", 10, 10, TaskState::Pending, AgentRole::Implementer),
+            seeded(
+                "touched",
+                3 * 24 * 60,
+                5 * 60,
+                TaskState::Pending,
+                AgentRole::Reviewer,
+            ),
+            seeded(
+                "last-week",
+                4 * 24 * 60,
+                4 * 24 * 60,
+                TaskState::Completed,
+                AgentRole::Implementer,
+            ),
+            seeded(
+                "old",
+                30 * 24 * 60,
+                30 * 24 * 60,
+                TaskState::Pending,
+                AgentRole::Implementer,
+            ),
+        ] {
+            storage.save_task(&task).await.unwrap();
+        }
+
+        let titles = |argv: &[&str]| {
+            let cli = <crate::cli::Cli as clap::Parser>::try_parse_from(
+                ["ndc", "list"].iter().chain(argv),
+            )
+            .unwrap();
+            let crate::cli::Commands::List(args) = cli.command else {
+                panic!("expected list");
+            };
+            let filter = args.to_filter(now).unwrap();
+            let storage = &storage;
+            async move {
+                crate::cli::list_tasks(storage, &filter)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| t.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            titles(&[]).await,
+            vec!["fresh", "touched", "last-week", "old"]
+        );
+        assert_eq!(titles(&["--since", "30m"]).await, vec!["fresh"]);
+        assert_eq!(titles(&["--since", "24h"]).await, vec!["fresh", "touched"]);
+        assert_eq!(
+            titles(&["--since", "1w"]).await,
+            vec!["fresh", "touched", "last-week"]
+        );
+        assert_eq!(
+            titles(&["--since", "2d", "--role", "reviewer"]).await,
+            vec!["touched"]
+        );
+        assert_eq!(
+            titles(&["--since", "1w", "--state", "completed"]).await,
+            vec!["last-week"]
+        );
+        assert!(
+            <crate::cli::Cli as clap::Parser>::try_parse_from(["ndc", "list", "--state", "done"])
+                .is_err()
+        );
+    }
+
+    /// `storage_type: sqlite` puts the CLI store in SQLite, where `list`
+    /// filters are evaluated in SQL
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_list_on_sqlite_backend() {
+        use crate::cli::StorageBackend;
+
+        let dir = TempDir::new().unwrap();
+        let storage_path = dir.path().join("storage");
+        let yaml = ndc_core::YamlStorageConfig {
+            storage_type: "sqlite".to_string(),
+            db_path: None,
+            in_memory: false,
+        };
+        let backend = StorageBackend::from_config(Some(&yaml), &storage_path);
+        assert_eq!(
+            backend,
+            StorageBackend::Sqlite {
+                db_path: storage_path.join("ndc.db")
+            }
+        );
+        assert_eq!(
+            StorageBackend::from_config(None, &storage_path),
+            StorageBackend::JsonLog
+        );
+        let config = crate::cli::CliConfig {
+            storage_backend: backend,
+            ..persistent_cli_config(&dir)
+        };
+
+        let now = chrono::Utc::now();
+        let storage = crate::cli::open_store(&config).await.unwrap();
+        for (title, hours) in [("recent", 1), ("stale", 72)] {
+            let mut task = ndc_core::Task::new(title.to_string(), String::new(), AgentRole::Tester);
+            task.metadata.created_at = now - chrono::Duration::hours(hours);
+            task.metadata.updated_at = task.metadata.created_at;
+            storage.save_task(&task).await.unwrap();
+        }
+        assert!(config.store_path().exists());
+
+        let cli = cli_for(&config, &["list", "--since", "1d", "--role", "tester"]);
+        let crate::cli::Commands::List(args) = cli.command else {
+            panic!("expected list");
+        };
+        let tasks = crate::cli::list_tasks(&*storage, &args.to_filter(now).unwrap())
+            .await
+            .unwrap();
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["recent"]);
+    }
+
+    /// A bare `ndc search` without query or filters is rejected
+    #[test]
+    fn test_search_requires_query_or_filter() {
+        let args = parse_search(&[]);
+        assert!(args.to_query().query.is_none());
+        assert!(!args.to_query().has_filters());
+        assert!(
+            <crate::cli::Cli as clap::Parser>::try_parse_from([
+                "ndc",
+                "search",
+                "--stability",
+                "solid"
+            ])
+            .is_err()
+        );
+    }
+
+    /// `ndc config validate` collects parse and storage problems instead of failing fast
+    #[test]
+    fn test_config_validate_reports_all_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = dir.path().join("storage");
+        let config_path = dir.path().join("config.yaml");
+
+        std::fs::write(&config_path, "repl:\n  max_history: 50\n").unwrap();
+        let (loaded, report) =
+            crate::cli::validate_config(std::slice::from_ref(&config_path), &storage);
+        assert!(!report.has_errors(), "{}", report);
+        assert_eq!(loaded.unwrap().repl.unwrap().max_history, 50);
+
+        std::fs::write(
+            &config_path,
+            "repl:\n  max_history: 0\nruntime:\n  discovery_failure_mode: panic\n",
+        )
+        .unwrap();
+        let blocker = dir.path().join("file");
+        std::fs::write(&blocker, "x").unwrap();
+        let (_, report) = crate::cli::validate_config(std::slice::from_ref(&config_path), &blocker);
+        let fields: Vec<&str> = report.errors().map(|i| i.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["repl", "runtime.discovery_failure_mode", "--storage"]
+        );
+
+        std::fs::write(&config_path, "repl: [").unwrap();
+        let (loaded, report) = crate::cli::validate_config(&[config_path], &storage);
+        assert!(loaded.is_none());
+        assert_eq!(report.errors().next().unwrap().field, "config");
+        assert!(
+            <crate::cli::Cli as clap::Parser>::try_parse_from([
+                "ndc",
+                "config",
+                "validate",
+                "--check-connectivity"
+            ])
+            .is_ok()
+        );
+    }
+
+    /// Minimal cargo crate for `ndc verify` runs
+    fn verify_fixture() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"verify-fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    /// `ndc verify` passes with exit code 0 when every selected check passes
+    #[tokio::test]
+    async fn test_verify_passing_checks_exit_zero() {
+        let project = verify_fixture();
+        let runtime = ndc_core::YamlRuntimeConfig {
+            quality_commands: std::collections::HashMap::from([(
+                "custom:readme".to_string(),
+                "test -f Cargo.toml".to_string(),
+            )]),
+            ..Default::default()
+        };
+        let checks = vec!["typecheck".to_string(), "custom:readme".to_string()];
+
+        let report = crate::verify::verify(project.path(), &checks, Some(&runtime))
+            .await
+            .unwrap();
+        assert_eq!(report.exit_code(), 0, "{}", report);
+        let names: Vec<&str> = report.checks.iter().map(|c| c.check.as_str()).collect();
+        assert_eq!(names, vec!["typecheck", "custom:readme"]);
+        assert!(report.to_string().ends_with("2 of 2 check(s) passed"));
+
+        let cli = <crate::cli::Cli as clap::Parser>::try_parse_from([
+            "ndc",
+            "verify",
+            "--check",
+            "test,lint",
+        ])
+        .unwrap();
+        match cli.command {
+            crate::cli::Commands::Verify(args) => assert_eq!(args.check, vec!["test", "lint"]),
+            other => panic!("expected verify, got {:?}", other),
+        }
+    }
+
+    /// A failing check (bad clippy flags) yields a non-zero exit code but the
+    /// remaining checks still run
+    #[tokio::test]
+    async fn test_verify_failing_check_exit_nonzero() {
+        let project = verify_fixture();
+        let runtime = ndc_core::YamlRuntimeConfig {
+            quality_gates: Some(vec!["lint".to_string(), "typecheck".to_string()]),
+            quality_commands: std::collections::HashMap::from([(
+                "lint".to_string(),
+                "cargo clippy --no-such-flag".to_string(),
+            )]),
+            ..Default::default()
+        };
+
+        // No --check: the configured gate is used
+        let report = crate::verify::verify(project.path(), &[], Some(&runtime))
+            .await
+            .unwrap();
+        assert_eq!(report.exit_code(), 1, "{}", report);
+        assert_eq!(report.failed(), 1);
+        assert!(!report.checks[0].passed);
+        assert!(report.checks[1].passed, "{}", report);
+        assert!(report.to_string().contains("FAIL lint"), "{}", report);
+
+        let unknown = crate::verify::verify(project.path(), &["clippy".to_string()], None).await;
+        assert!(unknown.unwrap_err().contains("unknown check 'clippy'"));
+    }
+
     /// Test CLI config default values
     #[test]
     fn test_cli_config_defaults() {
@@ -15,6 +15,7 @@ use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
 use ndc_core::AgentRole;
@@ -22,7 +23,7 @@ use ndc_core::TaskId;
 use ndc_runtime::{ExecutionContext, Executor};
 
 use crate::agent_mode::{AgentModeConfig, AgentModeManager};
-use crate::daemon::NdcDaemon;
+use crate::daemon::{NdcDaemon, SessionWorkspace};
 use crate::redaction::{RedactionMode, sanitize_text};
 
 // Re-export generated types from the proto
@@ -80,6 +81,34 @@ fn resolve_timeline_sse_address(grpc_addr: SocketAddr) -> Option<SocketAddr> {
     value.parse::<SocketAddr>().ok()
 }
 
+/// Format validation: ULID is 26 chars, alphanumeric; allow up to 128 chars for flexibility
+fn is_valid_session_id(session_id: &str) -> bool {
+    session_id.len() <= 128
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// `ToolExecute.parameters` → JSON 参数：schema 声明为字符串（或未声明）的
+/// 参数保持原样，其余按 JSON 解析（如 `args: "[\"-la\"]"`、`append: "true"`）
+fn tool_params(
+    schema: &serde_json::Value,
+    parameters: std::collections::HashMap<String, String>,
+) -> serde_json::Value {
+    let params = parameters
+        .into_iter()
+        .map(|(key, raw)| {
+            let declared = schema["properties"][&key]["type"].as_str();
+            let value = match declared {
+                None | Some("string") => serde_json::Value::String(raw),
+                Some(_) => serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw)),
+            };
+            (key, value)
+        })
+        .collect();
+    serde_json::Value::Object(params)
+}
+
 fn execution_event_to_json(event: generated::ExecutionEvent) -> String {
     serde_json::json!({
         "kind": event.kind,
@@ -107,8 +136,12 @@ fn execution_event_to_json(event: generated::ExecutionEvent) -> String {
 
 /// gRPC Agent 服务实现
 pub struct AgentGrpcService {
-    _daemon: Arc<NdcDaemon>,
+    daemon: Arc<NdcDaemon>,
     agent_manager: Arc<AgentModeManager>,
+    /// 已应用到 agent manager 的配置版本
+    applied_config_generation: std::sync::atomic::AtomicU64,
+    /// 已应用的 MCP 服务器配置版本
+    applied_mcp_generation: std::sync::atomic::AtomicU64,
 }
 
 impl std::fmt::Debug for AgentGrpcService {
@@ -119,17 +152,30 @@ impl std::fmt::Debug for AgentGrpcService {
 
 impl AgentGrpcService {
     fn build_agent_manager(daemon: &Arc<NdcDaemon>) -> Arc<AgentModeManager> {
-        let executor = daemon.executor();
+        let context = daemon.executor().context();
         let tool_registry = Arc::new(ndc_runtime::create_default_tool_registry_with_storage(
-            executor.context().storage.clone(),
+            context.storage.clone(),
+            context.lock_manager.clone(),
         ));
-        Arc::new(AgentModeManager::new(executor.clone(), tool_registry))
+        Self::build_agent_manager_with_tools(daemon, tool_registry)
+    }
+
+    fn build_agent_manager_with_tools(
+        daemon: &Arc<NdcDaemon>,
+        tool_registry: Arc<ndc_runtime::tools::ToolRegistry>,
+    ) -> Arc<AgentModeManager> {
+        Arc::new(
+            AgentModeManager::new(daemon.executor().clone(), tool_registry)
+                .with_session_workspaces(daemon.session_workspaces()),
+        )
     }
 
     pub fn with_manager(daemon: Arc<NdcDaemon>, agent_manager: Arc<AgentModeManager>) -> Self {
         Self {
-            _daemon: daemon,
+            daemon,
             agent_manager,
+            applied_config_generation: std::sync::atomic::AtomicU64::new(0),
+            applied_mcp_generation: std::sync::atomic::AtomicU64::new(0),
         }
     }
 
@@ -139,6 +185,7 @@ impl AgentGrpcService {
     }
 
     async fn ensure_agent_enabled(&self) -> Result<(), tonic::Status> {
+        self.apply_reloaded_config().await;
         if self.agent_manager.is_enabled().await {
             return Ok(());
         }
@@ -148,6 +195,46 @@ impl AgentGrpcService {
             .map_err(|e| tonic::Status::internal(format!("failed to enable agent mode: {}", e)))
     }
 
+    /// 将热加载后的配置（LLM、权限规则、质量检查命令、MCP 服务器）应用到之后的 turn
+    ///
+    /// 全部应用成功后才记录配置版本；失败时保留旧版本号，下次调用重试。
+    async fn apply_reloaded_config(&self) {
+        use std::sync::atomic::Ordering;
+
+        let Some(reloader) = self.daemon.config_reloader() else {
+            return;
+        };
+        let generation = reloader.generation();
+        if self.applied_config_generation.load(Ordering::Acquire) == generation {
+            return;
+        }
+        let config = reloader.current();
+        let context = self.daemon.executor().context();
+        context
+            .quality_runner
+            .set_commands(ndc_runtime::QualityCommands::from_config(&config));
+
+        let mcp_generation = reloader.mcp_generation();
+        let tool_registry =
+            match self.applied_mcp_generation.load(Ordering::Acquire) == mcp_generation {
+                true => None,
+                false => Some(crate::agent_mode::agent_tool_registry(context).await),
+            };
+        match self
+            .agent_manager
+            .apply_reloaded_config(&config, tool_registry)
+            .await
+        {
+            Ok(()) => {
+                self.applied_mcp_generation
+                    .store(mcp_generation, Ordering::Release);
+                self.applied_config_generation
+                    .store(generation, Ordering::Release);
+            }
+            Err(e) => warn!("Failed to apply reloaded config: {}", e),
+        }
+    }
+
     async fn validate_requested_session(
         &self,
         requested_session_id: &str,
@@ -155,13 +242,7 @@ impl AgentGrpcService {
         if requested_session_id.is_empty() {
             return Ok(());
         }
-
-        // Format validation: ULID is 26 chars, alphanumeric; allow up to 128 chars for flexibility
-        if requested_session_id.len() > 128
-            || !requested_session_id
-                .chars()
-                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
-        {
+        if !is_valid_session_id(requested_session_id) {
             return Err(tonic::Status::invalid_argument("invalid session ID format"));
         }
 
@@ -180,6 +261,136 @@ impl AgentGrpcService {
             })
     }
 
+    /// 在会话工作区内执行工具，返回要发送的响应
+    async fn execute_in_workspace(
+        daemon: &NdcDaemon,
+        workspace: &SessionWorkspace,
+        exec: generated::ToolExecute,
+    ) -> Vec<generated::ToolResponse> {
+        let error = |code: &str, message: String| generated::ToolResponse {
+            response_type: Some(generated::tool_response::ResponseType::Error(
+                generated::ToolError {
+                    code: code.to_string(),
+                    message,
+                },
+            )),
+        };
+
+        let context = daemon.executor().context();
+        let manager = workspace.tool_manager(context.storage.clone(), context.lock_manager.clone());
+        let Some(schema) = manager.get(&exec.tool_name).map(|tool| tool.schema()) else {
+            return vec![error(
+                "not_found",
+                format!("unknown tool '{}'", exec.tool_name),
+            )];
+        };
+        let params = match workspace.scope_params(&tool_params(&schema, exec.parameters), &schema) {
+            Ok(params) => params,
+            Err(e) => return vec![error("permission_denied", e.to_string())],
+        };
+
+        // 路径已限定在会话的项目根目录内，该根目录取代守护进程自身的项目根目录
+        let overrides = [ndc_runtime::tools::PERMISSION_EXTERNAL_DIRECTORY.to_string()];
+        let start = Instant::now();
+        let result = ndc_runtime::tools::with_security_overrides(
+            &overrides,
+            manager.execute(&exec.tool_name, &params),
+        )
+        .await;
+        match result {
+            Ok(result) => {
+                let output = result
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| result.output.clone());
+                vec![
+                    generated::ToolResponse {
+                        response_type: Some(generated::tool_response::ResponseType::Output(
+                            generated::ToolOutput {
+                                stream_id: "1".to_string(),
+                                chunk: result.output,
+                                is_stdout: result.success,
+                            },
+                        )),
+                    },
+                    generated::ToolResponse {
+                        response_type: Some(generated::tool_response::ResponseType::Complete(
+                            generated::ToolComplete {
+                                success: result.success,
+                                output,
+                                exit_code: u32::from(!result.success),
+                                duration_ms: start.elapsed().as_millis() as u64,
+                            },
+                        )),
+                    },
+                ]
+            }
+            Err(e) => {
+                let code = match &e {
+                    ndc_runtime::ToolError::NotFound(_) => "not_found",
+                    ndc_runtime::ToolError::PermissionDenied(_)
+                    | ndc_runtime::ToolError::InvalidPath(_) => "permission_denied",
+                    ndc_runtime::ToolError::InvalidArgument(_) => "invalid_argument",
+                    ndc_runtime::ToolError::Timeout(_) => "timeout",
+                    _ => "execution_failed",
+                };
+                vec![error(code, e.to_string())]
+            }
+        }
+    }
+
+    /// 执行一轮 agent 对话；客户端断开（响应流关闭）时取消该轮并撤销其文件改动
+    async fn run_chat_turn(
+        manager: &AgentModeManager,
+        input: &str,
+        tx: &mpsc::Sender<Result<generated::ChatResponse, tonic::Status>>,
+    ) -> Vec<generated::ChatResponse> {
+        let cancel = CancellationToken::new();
+        let turn = manager.process_input_with_cancellation(input, cancel.clone());
+        tokio::pin!(turn);
+        let result = tokio::select! {
+            result = &mut turn => result,
+            _ = tx.closed() => {
+                cancel.cancel();
+                turn.await
+            }
+        };
+
+        let end = |reason: &str| generated::ChatResponse {
+            response_type: Some(generated::chat_response::ResponseType::StreamEnd(
+                generated::StreamEnd {
+                    completion_reason: reason.to_string(),
+                    usage: None,
+                },
+            )),
+        };
+        match result {
+            Ok(response) => vec![
+                generated::ChatResponse {
+                    response_type: Some(generated::chat_response::ResponseType::ContentChunk(
+                        generated::ContentChunk {
+                            content: response.content,
+                            is_complete: true,
+                        },
+                    )),
+                },
+                end("stop"),
+            ],
+            Err(ndc_core::AgentError::Cancelled) => vec![end("cancelled")],
+            Err(e) => vec![
+                generated::ChatResponse {
+                    response_type: Some(generated::chat_response::ResponseType::Error(
+                        generated::Error {
+                            code: "agent_error".to_string(),
+                            message: e.to_string(),
+                        },
+                    )),
+                },
+                end("error"),
+            ],
+        }
+    }
+
     fn map_execution_event(event: ndc_core::AgentExecutionEvent) -> generated::ExecutionEvent {
         let workflow = event.workflow_stage_info();
         let usage = event.token_usage_info();
@@ -230,6 +441,7 @@ impl generated::agent_service_server::AgentService for AgentGrpcService {
     type AgentChatStream = ChatResponseStream;
     type ExecuteToolStream = ToolResponseStream;
     type SubscribeSessionTimelineStream = ExecutionEventStream;
+    type SubscribeEventsStream = ExecutionEventStream;
 
     /// 获取 Agent 状态
     async fn get_agent_status(
@@ -259,6 +471,13 @@ impl generated::agent_service_server::AgentService for AgentGrpcService {
         request: tonic::Request<generated::SwitchAgentRequest>,
     ) -> Result<tonic::Response<generated::SwitchAgentResponse>, tonic::Status> {
         let req = request.into_inner();
+        // 角色名（planner、reviewer 等）切换会话角色
+        if let Ok(role) = req.agent_name.parse::<ndc_core::AgentRole>() {
+            self.agent_manager
+                .set_role(role)
+                .await
+                .map_err(|e| tonic::Status::internal(e.to_string()))?;
+        }
         Ok(tonic::Response::new(generated::SwitchAgentResponse {
             success: true,
             agent_name: req.agent_name.clone(),
@@ -482,28 +701,106 @@ impl generated::agent_service_server::AgentService for AgentGrpcService {
         Ok(tonic::Response::new(ReceiverStream::new(rx)))
     }
 
+    /// 订阅会话的实时执行事件（不回放历史）
+    async fn subscribe_events(
+        &self,
+        request: tonic::Request<generated::SubscribeEventsRequest>,
+    ) -> Result<tonic::Response<Self::SubscribeEventsStream>, tonic::Status> {
+        self.ensure_agent_enabled().await?;
+        let req = request.into_inner();
+        self.validate_requested_session(&req.session_id).await?;
+
+        let (session_id, mut live_rx) = self
+            .agent_manager
+            .subscribe_execution_events()
+            .await
+            .map_err(|e| tonic::Status::failed_precondition(e.to_string()))?;
+        if !req.session_id.is_empty() && req.session_id != session_id {
+            return Err(tonic::Status::not_found(format!(
+                "session '{}' is not active on this daemon",
+                req.session_id
+            )));
+        }
+
+        let (tx, rx) = mpsc::channel(100);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    // 客户端断开后立即释放订阅
+                    _ = tx.closed() => return,
+                    recv = live_rx.recv() => match recv {
+                        Ok(message) => {
+                            if message.session_id != session_id {
+                                continue;
+                            }
+                            if tx
+                                .send(Ok(Self::map_execution_event(message.event)))
+                                .await
+                                .is_err()
+                            {
+                                return;
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("event subscriber lagged, {} events skipped", skipped);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+                    },
+                }
+            }
+        });
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
+
+    /// 设置会话的项目根目录与环境变量覆盖
+    async fn configure_session(
+        &self,
+        request: tonic::Request<generated::ConfigureSessionRequest>,
+    ) -> Result<tonic::Response<generated::ConfigureSessionResponse>, tonic::Status> {
+        let req = request.into_inner();
+        if req.session_id.is_empty() {
+            return Err(tonic::Status::invalid_argument("session_id is required"));
+        }
+        if !is_valid_session_id(&req.session_id) {
+            return Err(tonic::Status::invalid_argument("invalid session ID format"));
+        }
+
+        let workspace = SessionWorkspace::new(&req.project_root, req.env)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        let project_root = workspace.project_root().to_string_lossy().to_string();
+        info!(
+            session_id = %req.session_id,
+            project_root = %project_root,
+            "Session workspace configured"
+        );
+        self.daemon
+            .configure_session(req.session_id.clone(), workspace);
+
+        Ok(tonic::Response::new(generated::ConfigureSessionResponse {
+            session_id: req.session_id,
+            project_root,
+        }))
+    }
+
     /// Agent 流式聊天
     async fn agent_chat(
         &self,
         request: tonic::Request<tonic::Streaming<generated::ChatRequest>>,
     ) -> Result<tonic::Response<Self::AgentChatStream>, tonic::Status> {
+        self.ensure_agent_enabled().await?;
         let mut stream = request.into_inner();
         let (tx, rx) = mpsc::channel(100);
+        let manager = self.agent_manager.clone();
 
         tokio::spawn(async move {
             while let Ok(Some(chat_request)) = stream.message().await {
                 if let Some(generated::chat_request::RequestType::Message(msg)) =
                     chat_request.request_type
                 {
-                    let response = generated::ChatResponse {
-                        response_type: Some(generated::chat_response::ResponseType::ContentChunk(
-                            generated::ContentChunk {
-                                content: format!("Agent: {}", msg.content),
-                                is_complete: true,
-                            },
-                        )),
-                    };
-                    let _ = tx.send(Ok(response)).await;
+                    for response in Self::run_chat_turn(&manager, &msg.content, &tx).await {
+                        let _ = tx.send(Ok(response)).await;
+                    }
                 }
             }
         });
@@ -518,12 +815,22 @@ impl generated::agent_service_server::AgentService for AgentGrpcService {
     ) -> Result<tonic::Response<Self::ExecuteToolStream>, tonic::Status> {
         let mut stream = request.into_inner();
         let (tx, rx) = mpsc::channel(100);
+        let daemon = self.daemon.clone();
 
         tokio::spawn(async move {
             while let Ok(Some(tool_request)) = stream.message().await {
                 if let Some(generated::tool_request::RequestType::Execute(exec)) =
                     tool_request.request_type
                 {
+                    // 已配置工作区的会话：在其项目根目录与环境中执行
+                    if let Some(workspace) = daemon.session_workspace(&exec.session_id) {
+                        for response in Self::execute_in_workspace(&daemon, &workspace, exec).await
+                        {
+                            let _ = tx.send(Ok(response)).await;
+                        }
+                        continue;
+                    }
+
                     // 简单的工具执行响应
                     let response = generated::ToolResponse {
                         response_type: Some(generated::tool_response::ResponseType::Output(
@@ -1097,13 +1404,24 @@ impl generated::ndc_service_server::NdcService for NdcGrpcService {
 }
 
 /// 启动 gRPC 服务器
-pub async fn run_grpc_server(address: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run_grpc_server(
+    address: SocketAddr,
+    storage_path: &std::path::Path,
+) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting NDC gRPC Daemon on {}", address);
 
-    let context = ExecutionContext::default();
+    let context = crate::daemon::daemon_execution_context(storage_path);
     let executor = Arc::new(Executor::new(context));
-    let daemon = Arc::new(NdcDaemon::new(executor.clone(), address));
-    let agent_manager = AgentGrpcService::build_agent_manager(&daemon);
+    let _deferrals = crate::daemon::spawn_deferral_ticker(executor.clone());
+    let _purger = crate::daemon::spawn_memory_purger(executor.context().storage.clone());
+    let mut daemon = NdcDaemon::new(executor.clone(), address);
+    let (config, _config_watcher) = crate::daemon::start_config_reloader().unzip();
+    if let Some(config) = config {
+        daemon = daemon.with_config_reloader(config);
+    }
+    let daemon = Arc::new(daemon);
+    let tool_registry = crate::agent_mode::agent_tool_registry(executor.context()).await;
+    let agent_manager = AgentGrpcService::build_agent_manager_with_tools(&daemon, tool_registry);
 
     let ndc_service = NdcGrpcService::new(daemon.clone());
     let agent_service = AgentGrpcService::with_manager(daemon.clone(), agent_manager.clone());
@@ -1241,6 +1559,21 @@ mod tests {
             .expect("env lock poisoned")
     }
 
+    /// Manager whose session archive lives in `dir`, so turns run by a test
+    /// never reach the user's archive
+    fn isolated_agent_manager(daemon: &Arc<NdcDaemon>, dir: &TempDir) -> Arc<AgentModeManager> {
+        let executor = daemon.executor();
+        let tool_registry = Arc::new(ndc_runtime::create_default_tool_registry_with_storage(
+            executor.context().storage.clone(),
+            None,
+        ));
+        Arc::new(
+            AgentModeManager::new(executor.clone(), tool_registry)
+                .with_session_workspaces(daemon.session_workspaces())
+                .with_session_archive_file(dir.path().join("session_archive.json")),
+        )
+    }
+
     fn with_env_overrides<T>(updates: &[(&str, Option<&str>)], f: impl FnOnce() -> T) -> T {
         let _guard = env_lock();
         let previous = updates
@@ -1674,13 +2007,71 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_apply_reloaded_config_hot_applies_quality_commands_and_mcp() {
+        use ndc_core::QualityCheckType;
+        use std::sync::atomic::Ordering;
+
+        let config_dir = TempDir::new().unwrap();
+        let config_path = config_dir.path().join("config.yaml");
+        let write_config = |test_command: &str| {
+            std::fs::write(
+                &config_path,
+                format!(
+                    "runtime:\n  quality_commands:\n    test: {}\n",
+                    test_command
+                ),
+            )
+            .unwrap()
+        };
+        write_config("npm test");
+        let reloader =
+            Arc::new(crate::config_reload::ConfigReloader::new(vec![config_path.clone()]).unwrap());
+
+        let executor = Arc::new(Executor::new(ExecutionContext::default()));
+        let daemon_addr: SocketAddr = "127.0.0.1:50051".parse().unwrap();
+        let daemon = Arc::new(
+            NdcDaemon::new(executor.clone(), daemon_addr).with_config_reloader(reloader.clone()),
+        );
+        let archive = TempDir::new().unwrap();
+        let manager = isolated_agent_manager(&daemon, &archive);
+        let service = AgentGrpcService::with_manager(daemon, manager.clone());
+        let tools_before = manager.tool_registry();
+
+        write_config("pnpm test");
+        std::fs::write(
+            config_dir.path().join(ndc_runtime::mcp::MCP_CONFIG_FILE),
+            "- name: docs\n  server_type: Local\n  command: [\"true\"]\n  url: null\n  enabled: false\n  timeout_ms: 1000\n  oauth: null\n  headers: null\n",
+        )
+        .unwrap();
+        reloader.reload().unwrap();
+        assert_eq!(reloader.mcp_generation(), 1);
+
+        service.apply_reloaded_config().await;
+
+        let commands = executor.context().quality_runner.commands();
+        assert_eq!(
+            commands.configured(&QualityCheckType::Test),
+            Some("pnpm test")
+        );
+        assert!(!Arc::ptr_eq(&tools_before, &manager.tool_registry()));
+        assert_eq!(service.applied_config_generation.load(Ordering::Acquire), 1);
+        assert_eq!(service.applied_mcp_generation.load(Ordering::Acquire), 1);
+
+        // Nothing new to apply: the registry is kept
+        let tools_after = manager.tool_registry();
+        service.apply_reloaded_config().await;
+        assert!(Arc::ptr_eq(&tools_after, &manager.tool_registry()));
+    }
+
     #[tokio::test]
     async fn test_timeline_sse_endpoint_accepts_and_validates_session() {
         let context = ExecutionContext::default();
         let executor = Arc::new(Executor::new(context));
         let daemon_addr: SocketAddr = "127.0.0.1:50051".parse().unwrap();
         let daemon = Arc::new(NdcDaemon::new(executor, daemon_addr));
-        let manager = AgentGrpcService::build_agent_manager(&daemon);
+        let archive = TempDir::new().unwrap();
+        let manager = isolated_agent_manager(&daemon, &archive);
 
         let mut config = AgentModeConfig::default();
         config.provider = "ollama".to_string();
@@ -1744,7 +2135,8 @@ mod tests {
         let executor = Arc::new(Executor::new(context));
         let daemon_addr: SocketAddr = "127.0.0.1:50054".parse().unwrap();
         let daemon = Arc::new(NdcDaemon::new(executor, daemon_addr));
-        let manager = AgentGrpcService::build_agent_manager(&daemon);
+        let archive = TempDir::new().unwrap();
+        let manager = isolated_agent_manager(&daemon, &archive);
 
         let mut config = AgentModeConfig::default();
         config.provider = "ollama".to_string();
@@ -1787,7 +2179,8 @@ mod tests {
         let executor = Arc::new(Executor::new(context));
         let daemon_addr: SocketAddr = "127.0.0.1:50052".parse().unwrap();
         let daemon = Arc::new(NdcDaemon::new(executor, daemon_addr));
-        let manager = AgentGrpcService::build_agent_manager(&daemon);
+        let archive = TempDir::new().unwrap();
+        let manager = isolated_agent_manager(&daemon, &archive);
 
         let mut config = AgentModeConfig::default();
         config.provider = "ollama".to_string();
@@ -1839,7 +2232,8 @@ mod tests {
         let executor = Arc::new(Executor::new(context));
         let daemon_addr: SocketAddr = "127.0.0.1:50053".parse().unwrap();
         let daemon = Arc::new(NdcDaemon::new(executor, daemon_addr));
-        let manager = AgentGrpcService::build_agent_manager(&daemon);
+        let archive = TempDir::new().unwrap();
+        let manager = isolated_agent_manager(&daemon, &archive);
 
         let mut config = AgentModeConfig::default();
         config.provider = "ollama".to_string();
@@ -1914,6 +2308,231 @@ mod tests {
         assert_eq!(replay.token_session_total, snapshot.token_session_total);
     }
 
+    #[tokio::test]
+    async fn test_subscribe_events_streams_workflow_stage_to_client() {
+        let context = ExecutionContext::default();
+        let executor = Arc::new(Executor::new(context));
+        let daemon_addr: SocketAddr = "127.0.0.1:50054".parse().unwrap();
+        let daemon = Arc::new(NdcDaemon::new(executor, daemon_addr));
+        let archive = TempDir::new().unwrap();
+        let manager = isolated_agent_manager(&daemon, &archive);
+
+        manager
+            .enable(AgentModeConfig {
+                provider: "ollama".to_string(),
+                model: "llama3.2".to_string(),
+                ..AgentModeConfig::default()
+            })
+            .await
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let incoming = async_stream::stream! {
+            loop {
+                yield listener.accept().await.map(|(stream, _)| stream);
+            }
+        };
+        let service = AgentGrpcService::with_manager(daemon, manager.clone());
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(generated::agent_service_server::AgentServiceServer::new(
+                    service,
+                ))
+                .serve_with_incoming(incoming),
+        );
+
+        let client = crate::grpc_client::NdcClient::new(address.to_string());
+        let stream = client.subscribe_events(None).await.expect("subscribe");
+        let mut stream = Box::pin(stream);
+
+        // Provider availability is not required; the workflow emits stage events first.
+        let runner = manager.clone();
+        let task = tokio::spawn(async move {
+            let _ = tokio::time::timeout(
+                Duration::from_secs(3),
+                runner.process_input("trivial task for event subscription"),
+            )
+            .await;
+        });
+
+        let stage = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = stream.next().await {
+                let event = event.expect("stream item");
+                if event.kind == "WorkflowStage" {
+                    return event;
+                }
+            }
+            panic!("stream closed before a WorkflowStage event");
+        })
+        .await
+        .expect("no WorkflowStage event within timeout");
+        assert!(!stage.workflow_stage.is_empty());
+
+        let missing = client.subscribe_events(Some("not-a-live-session")).await;
+        assert!(missing.is_err());
+
+        drop(stream);
+        task.await.unwrap();
+        server.abort();
+    }
+
+    async fn execute_tool_in_session(
+        address: SocketAddr,
+        session_id: &str,
+        tool_name: &str,
+        parameters: &[(&str, &str)],
+    ) -> Result<generated::ToolComplete, generated::ToolError> {
+        let mut client = generated::agent_service_client::AgentServiceClient::connect(format!(
+            "http://{}",
+            address
+        ))
+        .await
+        .unwrap();
+        let request = generated::ToolRequest {
+            request_type: Some(generated::tool_request::RequestType::Execute(
+                generated::ToolExecute {
+                    tool_name: tool_name.to_string(),
+                    parameters: parameters
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                    session_id: session_id.to_string(),
+                },
+            )),
+        };
+        let mut responses = client
+            .execute_tool(tokio_stream::iter(vec![request]))
+            .await
+            .unwrap()
+            .into_inner();
+        while let Some(response) = responses.message().await.unwrap() {
+            match response.response_type {
+                Some(generated::tool_response::ResponseType::Complete(complete)) => {
+                    return Ok(complete);
+                }
+                Some(generated::tool_response::ResponseType::Error(error)) => return Err(error),
+                _ => {}
+            }
+        }
+        panic!("tool stream closed without a result");
+    }
+
+    #[tokio::test]
+    async fn test_configured_sessions_run_tools_in_isolated_roots() {
+        let root_a = TempDir::new().unwrap();
+        let root_b = TempDir::new().unwrap();
+        let executor = Arc::new(Executor::new(ExecutionContext::default()));
+        let daemon = Arc::new(NdcDaemon::new(executor, "127.0.0.1:50055".parse().unwrap()));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let incoming = async_stream::stream! {
+            loop {
+                yield listener.accept().await.map(|(stream, _)| stream);
+            }
+        };
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(generated::agent_service_server::AgentServiceServer::new(
+                    AgentGrpcService::new(daemon),
+                ))
+                .serve_with_incoming(incoming),
+        );
+
+        let client = crate::grpc_client::NdcClient::new(address.to_string());
+        for (session, root, marker) in [
+            ("session-a", &root_a, "alpha"),
+            ("session-b", &root_b, "beta"),
+        ] {
+            let env = std::collections::HashMap::from([(
+                "NDC_SESSION_MARKER".to_string(),
+                marker.to_string(),
+            )]);
+            let configured = client
+                .configure_session(session, &root.path().to_string_lossy(), env)
+                .await
+                .expect("configure session");
+            assert_eq!(
+                Path::new(&configured.project_root),
+                std::fs::canonicalize(root.path()).unwrap()
+            );
+        }
+        assert!(
+            client
+                .configure_session("session-c", "relative/root", Default::default())
+                .await
+                .is_err()
+        );
+
+        // The same relative path lands in each session's own root
+        for (session, content) in [("session-a", "from a"), ("session-b", "from b")] {
+            let written = execute_tool_in_session(
+                address,
+                session,
+                "write",
+                &[("path", "notes.txt"), ("content", content)],
+            )
+            .await
+            .expect("write");
+            assert!(written.success);
+        }
+        assert_eq!(
+            std::fs::read_to_string(root_a.path().join("notes.txt")).unwrap(),
+            "from a"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root_b.path().join("notes.txt")).unwrap(),
+            "from b"
+        );
+        let read = execute_tool_in_session(address, "session-a", "read", &[("path", "notes.txt")])
+            .await
+            .expect("read");
+        assert!(read.output.contains("from a"), "{}", read.output);
+        assert!(!read.output.contains("from b"), "{}", read.output);
+
+        // Shell runs in the session root with the session's environment
+        let shell = execute_tool_in_session(
+            address,
+            "session-b",
+            "shell",
+            &[("command", "echo \"$NDC_SESSION_MARKER $(pwd)\"")],
+        )
+        .await
+        .expect("shell");
+        let expected = format!(
+            "beta {}",
+            std::fs::canonicalize(root_b.path()).unwrap().display()
+        );
+        assert!(shell.output.contains(&expected), "{}", shell.output);
+        assert!(std::env::var("NDC_SESSION_MARKER").is_err());
+
+        // Escaping the declared root is rejected, including into another session's root
+        let escape_b = root_b
+            .path()
+            .join("notes.txt")
+            .to_string_lossy()
+            .to_string();
+        for path in ["../escape.txt", escape_b.as_str()] {
+            let denied = execute_tool_in_session(
+                address,
+                "session-a",
+                "write",
+                &[("path", path), ("content", "leak")],
+            )
+            .await
+            .expect_err("path outside the session root");
+            assert_eq!(denied.code, "permission_denied", "{}", denied.message);
+        }
+        assert_eq!(
+            std::fs::read_to_string(root_b.path().join("notes.txt")).unwrap(),
+            "from b"
+        );
+        assert!(!root_a.path().parent().unwrap().join("escape.txt").exists());
+
+        server.abort();
+    }
+
     #[tokio::test]
     async fn test_get_session_timeline_restores_persisted_permission_events_after_restart() {
         let _guard = env_lock();
@@ -20,12 +20,15 @@ use tonic::transport::Channel;
 
 #[cfg(feature = "grpc")]
 use crate::generated::{
-    CreateTaskRequest, ExecuteTaskRequest, ExecuteTaskResponse, ExecutionEvent,
-    GetSystemStatusRequest, GetTaskRequest, HealthCheckRequest, HealthCheckResponse,
-    ListTasksRequest, ListTasksResponse, RollbackTaskRequest, RollbackTaskResponse,
-    SessionTimelineRequest, SessionTimelineResponse, SystemStatusResponse, TaskResponse,
+    ConfigureSessionRequest, ConfigureSessionResponse, CreateTaskRequest, ExecuteTaskRequest,
+    ExecuteTaskResponse, ExecutionEvent, GetSystemStatusRequest, GetTaskRequest,
+    HealthCheckRequest, HealthCheckResponse, ListTasksRequest, ListTasksResponse,
+    RollbackTaskRequest, RollbackTaskResponse, SessionTimelineRequest, SessionTimelineResponse,
+    SubscribeEventsRequest, SystemStatusResponse, TaskResponse,
     agent_service_client::AgentServiceClient, ndc_service_client::NdcServiceClient,
 };
+#[cfg(feature = "grpc")]
+use futures::{Stream, StreamExt};
 
 #[cfg(feature = "grpc")]
 /// Client configuration
@@ -329,6 +332,49 @@ impl NdcClient {
         }
     }
 
+    /// Subscribe to live execution events of a session (or the current active session).
+    ///
+    /// Only events emitted after subscribing are delivered. Dropping the
+    /// stream ends the subscription on the daemon.
+    pub async fn subscribe_events(
+        &self,
+        session_id: Option<&str>,
+    ) -> Result<impl Stream<Item = Result<ExecutionEvent, ClientError>> + use<>, ClientError> {
+        let channel = self.get_channel().await?;
+        let mut client = AgentServiceClient::new(channel);
+        let request = SubscribeEventsRequest {
+            session_id: session_id.unwrap_or_default().to_string(),
+        };
+        match client.subscribe_events(request).await {
+            Ok(response) => Ok(response
+                .into_inner()
+                .map(|item| item.map_err(|e| map_error(&e.to_string())))),
+            Err(e) => Err(map_error(&e.to_string())),
+        }
+    }
+
+    /// Scope a session's tool calls to `project_root` with environment overrides.
+    ///
+    /// Paths escaping `project_root` are rejected for that session.
+    pub async fn configure_session(
+        &self,
+        session_id: &str,
+        project_root: &str,
+        env: std::collections::HashMap<String, String>,
+    ) -> Result<ConfigureSessionResponse, ClientError> {
+        let channel = self.get_channel().await?;
+        let mut client = AgentServiceClient::new(channel);
+        let request = ConfigureSessionRequest {
+            session_id: session_id.to_string(),
+            project_root: project_root.to_string(),
+            env,
+        };
+        match client.configure_session(request).await {
+            Ok(response) => Ok(response.into_inner()),
+            Err(e) => Err(map_error(&e.to_string())),
+        }
+    }
+
     /// Build SSE subscription URL for execution timeline.
     /// This URL can be consumed by EventSource-compatible clients.
     pub fn timeline_sse_subscribe_url(
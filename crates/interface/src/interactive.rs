@@ -4,6 +4,7 @@
 //! - Streaming response display
 //! - Agent status display
 //! - Progress indicators
+//! - Permission prompts (with numbered safer alternatives)
 
 use indicatif::{ProgressBar, ProgressStyle};
 use std::io::Write;
@@ -208,24 +209,153 @@ pub enum AgentSwitchResult {
 }
 
 /// Permission result
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PermissionResult {
     Allow,
     Deny,
     AlwaysAllow,
+    /// Run the n-th (0-based) alternative instead of the original action
+    Alternative(usize),
+}
+
+impl PermissionResult {
+    /// Answer to a `Verdict::RequireHuman`
+    pub fn decision(self) -> ndc_core::HumanDecision {
+        match self {
+            PermissionResult::Allow | PermissionResult::AlwaysAllow => {
+                ndc_core::HumanDecision::Approve
+            }
+            PermissionResult::Alternative(index) => ndc_core::HumanDecision::Alternative(index),
+            PermissionResult::Deny => ndc_core::HumanDecision::Deny,
+        }
+    }
 }
 
-/// Permission confirm placeholder
+/// Permission prompt; safer alternatives are offered as a numbered choice
 #[derive(Debug)]
-pub struct PermissionConfirm;
+pub struct PermissionConfirm {
+    tool: String,
+    operation: String,
+    risk: RiskLevel,
+    alternatives: Vec<String>,
+}
 
 impl PermissionConfirm {
-    pub fn new(_tool: &str, _operation: &str, _risk: RiskLevel) -> Self {
-        Self
+    pub fn new(tool: &str, operation: &str, risk: RiskLevel) -> Self {
+        Self {
+            tool: tool.to_string(),
+            operation: operation.to_string(),
+            risk,
+            alternatives: Vec::new(),
+        }
+    }
+
+    pub fn with_alternatives(mut self, alternatives: Vec<String>) -> Self {
+        self.alternatives = alternatives;
+        self
+    }
+
+    /// Prompt for a `Verdict::RequireHuman`, listing `HumanContext.alternatives`;
+    /// `None` for other verdicts
+    pub fn for_verdict(verdict: &ndc_core::Verdict) -> Option<Self> {
+        let ndc_core::Verdict::RequireHuman {
+            action,
+            question,
+            context,
+            ..
+        } = verdict
+        else {
+            return None;
+        };
+        let risk = match context.risk_level {
+            ndc_core::RiskLevel::Low => RiskLevel::Low,
+            ndc_core::RiskLevel::Medium => RiskLevel::Medium,
+            ndc_core::RiskLevel::High => RiskLevel::High,
+            ndc_core::RiskLevel::Critical => RiskLevel::Critical,
+        };
+        let operation = format!("{} ({})", question, action_label(action));
+        Some(
+            Self::new("decision", &operation, risk)
+                .with_alternatives(context.alternatives.iter().map(action_label).collect()),
+        )
+    }
+
+    pub fn operation(&self) -> &str {
+        &self.operation
     }
 
+    pub fn alternatives(&self) -> &[String] {
+        &self.alternatives
+    }
+
+    /// Prompt text: the operation, numbered alternatives and accepted answers
+    pub fn render(&self) -> String {
+        let mut text = format!("[{:?}] {}: {}\n", self.risk, self.tool, self.operation);
+        for (i, alternative) in self.alternatives.iter().enumerate() {
+            text.push_str(&format!("  {}) {}\n", i + 1, alternative));
+        }
+        text.push_str("Allow? [y]es / [n]o / [a]lways");
+        match self.alternatives.len() {
+            0 => {}
+            1 => text.push_str(" / 1"),
+            n => text.push_str(&format!(" / 1-{}", n)),
+        }
+        text.push_str(": ");
+        text
+    }
+
+    /// Parse an answer; `None` when it is not one of the offered choices
+    pub fn parse_answer(&self, answer: &str) -> Option<PermissionResult> {
+        let answer = answer.trim().to_ascii_lowercase();
+        match answer.as_str() {
+            "y" | "yes" => Some(PermissionResult::Allow),
+            "n" | "no" => Some(PermissionResult::Deny),
+            "a" | "always" => Some(PermissionResult::AlwaysAllow),
+            number => number
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=self.alternatives.len()).contains(n))
+                .map(|n| PermissionResult::Alternative(n - 1)),
+        }
+    }
+
+    /// Ask on stdin until a valid answer; end of input denies
     pub async fn confirm(&self) -> PermissionResult {
-        PermissionResult::Allow
+        loop {
+            print!("{}", self.render());
+            let _ = std::io::stdout().flush();
+            let line = tokio::task::spawn_blocking(|| {
+                let mut line = String::new();
+                match std::io::stdin().read_line(&mut line) {
+                    Ok(0) | Err(_) => None,
+                    Ok(_) => Some(line),
+                }
+            })
+            .await
+            .ok()
+            .flatten();
+            let Some(line) = line else {
+                return PermissionResult::Deny;
+            };
+            if let Some(result) = self.parse_answer(&line) {
+                return result;
+            }
+        }
+    }
+}
+
+/// Short human-readable description of an action
+fn action_label(action: &ndc_core::Action) -> String {
+    use ndc_core::Action;
+    match action {
+        Action::ReadFile { path, .. } => format!("read {}", path.display()),
+        Action::WriteFile { path, .. } => format!("write {}", path.display()),
+        Action::CreateFile { path } => format!("create {}", path.display()),
+        Action::DeleteFile { path } => format!("delete {}", path.display()),
+        Action::MoveFile { from, to } => format!("move {} -> {}", from.display(), to.display()),
+        Action::RunCommand { command, args } if args.is_empty() => format!("run {}", command),
+        Action::RunCommand { command, args } => format!("run {} {}", command, args.join(" ")),
+        other => format!("{:?}", other),
     }
 }
 
@@ -256,6 +386,64 @@ mod tests {
         assert!(display.content().is_empty());
     }
 
+    #[test]
+    fn test_permission_confirm_offers_alternatives() {
+        let verdict = ndc_core::Verdict::RequireHuman {
+            action: ndc_core::Action::DeleteFile {
+                path: "Cargo.lock".into(),
+            },
+            question: "Delete file operation requires human approval".to_string(),
+            context: ndc_core::HumanContext {
+                task_id: None,
+                affected_files: vec!["Cargo.lock".into()],
+                risk_level: ndc_core::RiskLevel::Critical,
+                alternatives: vec![
+                    ndc_core::Action::MoveFile {
+                        from: "Cargo.lock".into(),
+                        to: "Cargo.lock.bak".into(),
+                    },
+                    ndc_core::Action::ReadFile {
+                        path: "Cargo.lock".into(),
+                        range: None,
+                    },
+                ],
+                required_privilege: ndc_core::PrivilegeLevel::Critical,
+            },
+            timeout: None,
+        };
+
+        let prompt = PermissionConfirm::for_verdict(&verdict).unwrap();
+        assert_eq!(
+            prompt.alternatives(),
+            ["move Cargo.lock -> Cargo.lock.bak", "read Cargo.lock"]
+        );
+        let text = prompt.render();
+        assert!(text.contains("delete Cargo.lock"), "{}", text);
+        assert!(text.contains("  1) move Cargo.lock -> Cargo.lock.bak\n"));
+        assert!(text.contains("  2) read Cargo.lock\n"));
+        assert!(text.ends_with("/ 1-2: "));
+
+        assert_eq!(
+            prompt.parse_answer("2\n"),
+            Some(PermissionResult::Alternative(1))
+        );
+        assert_eq!(prompt.parse_answer("3"), None);
+        assert_eq!(prompt.parse_answer("N"), Some(PermissionResult::Deny));
+
+        let chosen = verdict
+            .clone()
+            .resolve_human(PermissionResult::Alternative(0).decision());
+        assert!(matches!(
+            chosen,
+            ndc_core::Verdict::Allow {
+                action: ndc_core::Action::MoveFile { .. },
+                ..
+            }
+        ));
+        let declined = verdict.resolve_human(PermissionResult::Deny.decision());
+        assert!(matches!(declined, ndc_core::Verdict::Deny { .. }));
+    }
+
     #[test]
     fn test_streaming_display_append() {
         let mut display = StreamingDisplay::new();
@@ -13,14 +13,18 @@
 mod agent_backend_impl;
 pub mod agent_mode;
 pub mod cli;
+pub mod config_reload;
 pub mod daemon;
 pub mod interactive;
+pub(crate) mod logging;
+pub(crate) mod logs;
 pub(crate) mod permission_engine;
 pub(crate) mod project_index;
 pub(crate) mod provider_config;
 pub mod redaction;
 pub mod repl;
 pub(crate) mod session_archive;
+pub(crate) mod verify;
 
 #[cfg(feature = "grpc")]
 pub mod generated;
@@ -45,6 +49,7 @@ pub use agent_mode::{
     handle_agent_command, show_agent_status,
 };
 pub use cli::{CliConfig, run};
+pub use config_reload::ConfigReloader;
 pub use daemon::run_daemon;
 pub use repl::{ReplConfig, ReplState, run_repl};
 
@@ -0,0 +1,82 @@
+//! Logging — tracing subscriber setup.
+//!
+//! `NDC_LOG_FORMAT=json` installs a JSON subscriber: one object per line,
+//! with the enclosing spans (`task_id`, `session_id`, `round`, `step`, ...)
+//! under `spans`, so logs can be filtered by task or session. Otherwise the
+//! plain fmt subscriber is installed, only in verbose mode.
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Env var selecting the log format (`json` or `text`)
+pub(crate) const LOG_FORMAT_ENV: &str = "NDC_LOG_FORMAT";
+
+/// JSON subscriber writing to `writer`
+pub(crate) fn json_subscriber<W>(writer: W) -> impl Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(writer)
+        .finish()
+}
+
+/// Install the process-wide subscriber chosen by `NDC_LOG_FORMAT`
+pub(crate) fn init_logging(verbose: bool) {
+    let json = std::env::var(LOG_FORMAT_ENV).is_ok_and(|v| v.eq_ignore_ascii_case("json"));
+    if json {
+        let _ = tracing::subscriber::set_global_default(json_subscriber(std::io::stderr));
+    } else if verbose {
+        let _ = tracing_subscriber::fmt().try_init();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_subscriber_includes_span_fields() {
+        let buffer = Buffer::default();
+        tracing::subscriber::with_default(json_subscriber(buffer.clone()), || {
+            let task = tracing::info_span!("task", task_id = "t-1");
+            let _task = task.enter();
+            let step = tracing::info_span!("step", step = 2);
+            let _step = step.enter();
+            tracing::info!("running step");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["fields"]["message"], "running step");
+        assert_eq!(line["span"]["name"], "step");
+        assert_eq!(line["spans"][0]["task_id"], "t-1");
+        assert_eq!(line["spans"][1]["step"], 2);
+    }
+}
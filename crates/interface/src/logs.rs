@@ -0,0 +1,316 @@
+//! Logs — `ndc logs [--follow]` execution event printing.
+//!
+//! Prints a session's execution events one line each. With `--follow` new
+//! events are printed as they arrive (like `tail -f`) until the session
+//! reports a terminal status (`session_idle` / `session_cancelled`), the
+//! event source closes, or Ctrl-C is pressed.
+//!
+//! Event sources:
+//! - session archive (default): local `ndc run` / `ndc repl` write the session
+//!   after every execution event; the archive is polled for growth
+//! - gRPC daemon (`--daemon`): backlog from `GetSessionTimeline`, then the live
+//!   `SubscribeEvents` stream
+
+use futures::{Stream, StreamExt};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::cli::OutputFormat;
+use crate::session_archive::SessionArchiveStore;
+
+/// Session status messages after which a run is over
+const TERMINAL_STATUSES: &[&str] = &["session_idle", "session_cancelled"];
+
+/// One execution event, as printed by `ndc logs`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct LogEvent {
+    pub(crate) kind: String,
+    /// RFC 3339
+    pub(crate) timestamp: String,
+    pub(crate) round: usize,
+    pub(crate) tool_name: Option<String>,
+    pub(crate) message: String,
+    pub(crate) duration_ms: Option<u64>,
+    pub(crate) is_error: bool,
+}
+
+impl From<&ndc_core::AgentExecutionEvent> for LogEvent {
+    fn from(event: &ndc_core::AgentExecutionEvent) -> Self {
+        Self {
+            kind: format!("{:?}", event.kind),
+            timestamp: event.timestamp.to_rfc3339(),
+            round: event.round,
+            tool_name: event.tool_name.clone(),
+            message: event.message.clone(),
+            duration_ms: event.duration_ms,
+            is_error: event.is_error,
+        }
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<crate::generated::ExecutionEvent> for LogEvent {
+    fn from(event: crate::generated::ExecutionEvent) -> Self {
+        Self {
+            kind: event.kind,
+            timestamp: event.timestamp,
+            round: event.round as usize,
+            tool_name: Some(event.tool_name).filter(|name| !name.is_empty()),
+            message: event.message,
+            duration_ms: Some(event.duration_ms).filter(|ms| *ms > 0),
+            is_error: event.is_error,
+        }
+    }
+}
+
+impl LogEvent {
+    /// Whether the session finished its run with this event
+    pub(crate) fn is_terminal(&self) -> bool {
+        self.kind == "SessionStatus" && TERMINAL_STATUSES.contains(&self.message.as_str())
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let time = chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+            .map(|ts| {
+                ts.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.timestamp.clone());
+        let mut line = format!("{} r{} {}", time, self.round, self.kind);
+        if let Some(tool) = &self.tool_name {
+            line.push_str(&format!(" [{}]", tool));
+        }
+        if let Some(ms) = self.duration_ms {
+            line.push_str(&format!(" ({}ms)", ms));
+        }
+        if self.is_error {
+            line.push_str(" ERROR");
+        }
+        if !self.message.is_empty() {
+            line.push_str(": ");
+            line.push_str(&self.message.replace('\n', " "));
+        }
+        line
+    }
+}
+
+/// Write one event as a line: rendered text, or a compact JSON object for
+/// `json` / `jsonl`
+pub(crate) fn write_event<W>(out: &mut W, event: &LogEvent, format: OutputFormat) -> io::Result<()>
+where
+    W: Write + ?Sized,
+{
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            serde_json::to_writer(&mut *out, event)?;
+            writeln!(out)
+        }
+        OutputFormat::Pretty | OutputFormat::Minimal => writeln!(out, "{}", event.render()),
+    }
+}
+
+/// Write a batch of events; `json` writes them as one pretty array
+pub(crate) fn write_events<W>(
+    out: &mut W,
+    events: &[LogEvent],
+    format: OutputFormat,
+) -> io::Result<()>
+where
+    W: Write + ?Sized,
+{
+    if format == OutputFormat::Json {
+        serde_json::to_writer_pretty(&mut *out, events)?;
+        return writeln!(out);
+    }
+    events
+        .iter()
+        .try_for_each(|event| write_event(out, event, format))
+}
+
+/// Why following stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FollowEnd {
+    /// The session reached a terminal status
+    Terminal,
+    /// The event source ended
+    Closed,
+    /// Ctrl-C
+    Interrupted,
+}
+
+/// Print `events` to `out` as they arrive until a terminal event, the end of
+/// the stream or Ctrl-C
+pub(crate) async fn follow<S, W>(
+    events: S,
+    out: &mut W,
+    format: OutputFormat,
+) -> io::Result<FollowEnd>
+where
+    S: Stream<Item = LogEvent>,
+    W: Write + ?Sized,
+{
+    let mut events = std::pin::pin!(events);
+    let mut ctrl_c = std::pin::pin!(tokio::signal::ctrl_c());
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => return Ok(FollowEnd::Interrupted),
+            next = events.next() => {
+                let Some(event) = next else {
+                    return Ok(FollowEnd::Closed);
+                };
+                write_event(out, &event, format)?;
+                out.flush()?;
+                if event.is_terminal() {
+                    return Ok(FollowEnd::Terminal);
+                }
+            }
+        }
+    }
+}
+
+/// Archived session with id `id`, or owning task `id`; the most recent
+/// session when `id` is `None`
+pub(crate) fn find_archived_session(
+    archive: &SessionArchiveStore,
+    id: Option<&str>,
+) -> Option<ndc_core::AgentSession> {
+    let sessions = archive.all_sessions();
+    match id {
+        None => sessions.into_iter().next(),
+        Some(id) => sessions.into_iter().find(|session| {
+            session.id == id
+                || session
+                    .active_tasks
+                    .iter()
+                    .any(|task| task.to_string() == id)
+        }),
+    }
+}
+
+/// Events appended to an archived session after the first `seen`, polling
+/// the archive file every `interval`
+pub(crate) fn archive_events(
+    path: PathBuf,
+    session_id: String,
+    seen: usize,
+    interval: Duration,
+) -> impl Stream<Item = LogEvent> {
+    futures::stream::unfold(seen, move |mut seen| {
+        let path = path.clone();
+        let session_id = session_id.clone();
+        async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let archive = SessionArchiveStore::load_from(path.clone());
+                let Some(session) = find_archived_session(&archive, Some(&session_id)) else {
+                    continue;
+                };
+                let events = &session.execution_events;
+                if events.len() <= seen {
+                    // The archive was rewritten with fewer events; resync
+                    seen = seen.min(events.len());
+                    continue;
+                }
+                let fresh: Vec<LogEvent> = events[seen..].iter().map(LogEvent::from).collect();
+                return Some((futures::stream::iter(fresh), events.len()));
+            }
+        }
+    })
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndc_core::{AgentExecutionEvent, AgentExecutionEventKind, AgentSession};
+    use tempfile::TempDir;
+
+    fn event(kind: AgentExecutionEventKind, message: &str) -> AgentExecutionEvent {
+        AgentExecutionEvent {
+            kind,
+            timestamp: chrono::Utc::now(),
+            message: message.to_string(),
+            round: 1,
+            tool_name: None,
+            tool_call_id: None,
+            duration_ms: None,
+            is_error: false,
+            workflow_stage: None,
+            workflow_detail: None,
+            workflow_stage_index: None,
+            workflow_stage_total: None,
+        }
+    }
+
+    fn save(path: &std::path::Path, session: &AgentSession) {
+        let mut store = SessionArchiveStore::load_from(path.to_path_buf());
+        store.upsert(session);
+        store.save().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_follow_streams_events_until_completion() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("session_archive.json");
+        let mut session = AgentSession::new("follow-session".to_string());
+        let task_id = ndc_core::TaskId::new();
+        session.add_active_task(task_id);
+        session.add_execution_event(event(
+            AgentExecutionEventKind::SessionStatus,
+            "session_running",
+        ));
+        save(&path, &session);
+
+        let archive = SessionArchiveStore::load_from(path.clone());
+        let found = find_archived_session(&archive, Some(&task_id.to_string())).unwrap();
+        assert_eq!(found.id, "follow-session");
+
+        // The task keeps running and persisting events while we follow
+        let writer = {
+            let path = path.clone();
+            let mut session = session.clone();
+            tokio::spawn(async move {
+                for step in [
+                    event(AgentExecutionEventKind::StepStart, "llm_round_start"),
+                    AgentExecutionEvent {
+                        tool_name: Some("shell".to_string()),
+                        duration_ms: Some(42),
+                        ..event(AgentExecutionEventKind::ToolCallEnd, "cargo test")
+                    },
+                    event(AgentExecutionEventKind::SessionStatus, "session_idle"),
+                    event(AgentExecutionEventKind::Text, "next run"),
+                ] {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    session.add_execution_event(step);
+                    save(&path, &session);
+                }
+            })
+        };
+
+        let events = archive_events(
+            path,
+            "follow-session".to_string(),
+            found.execution_events.len(),
+            Duration::from_millis(5),
+        );
+        let mut out = Vec::new();
+        let end = tokio::time::timeout(
+            Duration::from_secs(5),
+            follow(events, &mut out, OutputFormat::Pretty),
+        )
+        .await
+        .expect("following should end at completion")
+        .unwrap();
+        writer.await.unwrap();
+
+        assert_eq!(end, FollowEnd::Terminal);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", out);
+        assert!(lines[0].ends_with("StepStart: llm_round_start"));
+        assert!(lines[1].ends_with("ToolCallEnd [shell] (42ms): cargo test"));
+        assert!(lines[2].ends_with("SessionStatus: session_idle"));
+    }
+}
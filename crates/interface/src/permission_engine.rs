@@ -9,10 +9,19 @@ use std::sync::Arc;
 use tokio::sync::{Mutex, mpsc, oneshot};
 use tracing::debug;
 
-use ndc_core::{AgentError, ToolExecutor};
+use ndc_core::{
+    Action, ActionResult, AgentError, AgentId, AgentRole, HumanDecision, Intent, IntentId,
+    ToolExecutor, Verdict,
+};
+use ndc_decision::DecisionEngine;
 use ndc_runtime::tools::{
-    ToolError, ToolRegistry, extract_confirmation_permission, with_security_overrides,
+    PERMISSION_EXTERNAL_DIRECTORY, RoleToolPolicy, ToolError, ToolRegistry,
+    extract_confirmation_permission, with_security_overrides,
 };
+use ndc_runtime::{Executor, SagaPlan, SagaStep};
+
+use crate::daemon::{SessionWorkspace, patch_paths};
+use crate::interactive::PermissionConfirm;
 
 /// 权限规则
 #[derive(Debug, Clone, PartialEq)]
@@ -25,14 +34,158 @@ pub enum PermissionRule {
     Deny,
 }
 
+impl PermissionRule {
+    /// 解析配置中的规则（`allow` / `ask` / `deny`，不区分大小写）
+    pub fn parse(rule: &str) -> Option<Self> {
+        match rule.trim().to_ascii_lowercase().as_str() {
+            "allow" => Some(Self::Allow),
+            "ask" => Some(Self::Ask),
+            "deny" => Some(Self::Deny),
+            _ => None,
+        }
+    }
+}
+
 /// A permission confirmation request sent from the tool executor to the TUI event loop.
 pub struct PermissionRequest {
     /// Human-readable description of the operation being requested.
     pub description: String,
     /// Permission key (e.g. "shell_high_risk", "git_commit") for session/permanent approval.
     pub permission_key: Option<String>,
-    /// Send `true` to allow, `false` to deny.
-    pub response_tx: oneshot::Sender<bool>,
+    /// Safer actions offered instead of the requested one, answered with `HumanDecision::Alternative`.
+    pub alternatives: Vec<String>,
+    /// Send `Approve` to allow, `Deny` to deny, `Alternative(n)` to run the n-th alternative.
+    pub response_tx: oneshot::Sender<HumanDecision>,
+}
+
+/// 当前 agent 运行中工具调用产生的文件改动；运行被取消时按 saga 逆序撤销
+#[derive(Clone)]
+pub struct RunSaga {
+    executor: Arc<Executor>,
+    plan: Arc<Mutex<SagaPlan>>,
+}
+
+impl RunSaga {
+    pub fn new(executor: Arc<Executor>) -> Self {
+        Self {
+            executor,
+            plan: Arc::new(Mutex::new(SagaPlan::new(String::new()))),
+        }
+    }
+
+    /// 开始新的运行，丢弃上一次运行的记录
+    pub async fn reset(&self, run_id: &str) {
+        *self.plan.lock().await = SagaPlan::new(run_id.to_string());
+    }
+
+    /// 工具执行前规划撤销步骤；将被覆盖或删除的文件在此时备份
+    fn plan(&self, tool_name: &str, params: &serde_json::Value) -> Vec<SagaStep> {
+        tool_actions(tool_name, params)
+            .iter()
+            .flat_map(|action| self.executor.plan_action(action).1)
+            .collect()
+    }
+
+    /// 工具执行成功后记录其步骤
+    async fn record(&self, steps: Vec<SagaStep>) {
+        let mut plan = self.plan.lock().await;
+        for step in steps {
+            plan.add_step(step.step_id.clone(), step.action, step.undo_action);
+            plan.mark_completed(&step.step_id);
+        }
+    }
+
+    /// 执行决策引擎（或人类）替换后的动作，并记入本次运行
+    async fn execute_verdict(&self, verdict: &Verdict) -> Result<ActionResult, String> {
+        let mut plan = self.plan.lock().await;
+        self.executor
+            .execute_verdict(verdict, &mut plan)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// 撤销本次运行已完成的改动
+    pub async fn compensate(&self) -> Result<(), String> {
+        let mut plan = self.plan.lock().await;
+        let undo = |action| {
+            let executor = self.executor.clone();
+            async move { executor.apply_undo(action).await }
+        };
+        plan.rollback_completed(&undo)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// 工具调用对应的文件改动；相对路径按工具的解析方式（`working_dir` 或进程 cwd）补全
+fn tool_actions(tool_name: &str, params: &serde_json::Value) -> Vec<Action> {
+    let base = params
+        .get("working_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let path = |key: &str| {
+        params
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|p| base.join(p))
+    };
+    let write = |path| Action::WriteFile {
+        path,
+        content: String::new(),
+    };
+    match tool_name {
+        "write" | "edit" => path("path").map(write).into_iter().collect(),
+        "fs" => match params.get("operation").and_then(|v| v.as_str()) {
+            Some("write" | "create") => path("path").map(write).into_iter().collect(),
+            Some("delete") => path("path")
+                .map(|path| Action::DeleteFile { path })
+                .into_iter()
+                .collect(),
+            Some("move") => path("path")
+                .zip(path("to"))
+                .map(|(from, to)| Action::MoveFile { from, to })
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        },
+        "apply_patch" => params
+            .as_object()
+            .map(|object| {
+                patch_paths(object)
+                    .into_iter()
+                    .map(|p| write(base.join(p)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// 送交决策引擎的动作，以及能否整体替换为另一动作：只有单个动作且（写入时）
+/// 带着真实内容的调用才能替换，否则替代方案会写出空文件
+fn decision_actions(tool_name: &str, params: &serde_json::Value) -> (Vec<Action>, bool) {
+    let content = match tool_name {
+        "write" if params.get("append").and_then(|v| v.as_bool()) != Some(true) => {
+            params.get("content").and_then(|v| v.as_str())
+        }
+        "fs" => params.get("content").and_then(|v| v.as_str()),
+        _ => None,
+    };
+    let actions: Vec<Action> = tool_actions(tool_name, params)
+        .into_iter()
+        .map(|action| match (action, content) {
+            (Action::WriteFile { path, .. }, Some(content)) => Action::WriteFile {
+                path,
+                content: content.to_string(),
+            },
+            (action, _) => action,
+        })
+        .collect();
+    let substitutable = actions.len() == 1
+        && (content.is_some() || !matches!(actions[0], Action::WriteFile { .. }));
+    (actions, substitutable)
 }
 
 /// REPL Tool Executor - 桥接 Agent Orchestrator 和 Tool Registry
@@ -43,6 +196,16 @@ pub struct ReplToolExecutor {
     /// Channel to send permission requests to the TUI event loop.
     /// When `None`, falls back to stdin-based confirmation (non-TUI mode).
     permission_tx: Option<mpsc::Sender<PermissionRequest>>,
+    /// Role whose tools are exposed; `None` exposes every registered tool.
+    role_scope: Option<(AgentRole, RoleToolPolicy)>,
+    /// Workspace of the current session; scopes paths, cwd and env of tool calls.
+    workspace: Arc<Mutex<Option<SessionWorkspace>>>,
+    /// File changes of the current run, undone when the run is cancelled.
+    run_saga: Option<RunSaga>,
+    /// Security permissions approved permanently in config.
+    approved_permissions: Vec<String>,
+    /// Engine judging file changes before they run; `None` skips the decision step.
+    decision: Option<Arc<dyn DecisionEngine>>,
 }
 
 impl ReplToolExecutor {
@@ -56,6 +219,71 @@ impl ReplToolExecutor {
             permissions,
             runtime_working_dir,
             permission_tx: None,
+            role_scope: None,
+            workspace: Arc::new(Mutex::new(None)),
+            run_saga: None,
+            approved_permissions: Vec::new(),
+            decision: None,
+        }
+    }
+
+    /// Judge file changes through `engine`; substituted actions run through the run saga.
+    pub fn with_decision_engine(mut self, engine: Arc<dyn DecisionEngine>) -> Self {
+        self.decision = Some(engine);
+        self
+    }
+
+    /// Skip confirmation for security permissions approved in config.
+    pub fn with_approved_permissions(mut self, permissions: Vec<String>) -> Self {
+        self.approved_permissions = permissions;
+        self
+    }
+
+    /// Record file changes of each run in `saga` so a cancelled run can be undone.
+    pub fn with_run_saga(mut self, saga: RunSaga) -> Self {
+        self.run_saga = Some(saga);
+        self
+    }
+
+    fn plan_compensation(&self, name: &str, params: &serde_json::Value) -> Vec<SagaStep> {
+        self.run_saga
+            .as_ref()
+            .map(|saga| saga.plan(name, params))
+            .unwrap_or_default()
+    }
+
+    async fn record_compensation(&self, steps: Vec<SagaStep>) {
+        if let Some(saga) = &self.run_saga
+            && !steps.is_empty()
+        {
+            saga.record(steps).await;
+        }
+    }
+
+    /// Run tool calls inside the session workspace held by `workspace`, when set.
+    pub fn with_workspace(mut self, workspace: Arc<Mutex<Option<SessionWorkspace>>>) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Only expose (and allow) the tools `role` may call under `policy`.
+    pub fn with_role_scope(mut self, role: AgentRole, policy: RoleToolPolicy) -> Self {
+        self.role_scope = Some((role, policy));
+        self
+    }
+
+    fn is_tool_visible(&self, name: &str) -> bool {
+        self.role_scope
+            .as_ref()
+            .is_none_or(|(role, policy)| policy.is_visible(*role, name))
+    }
+
+    fn authorize_role(&self, name: &str) -> Result<(), AgentError> {
+        match &self.role_scope {
+            Some((role, policy)) => policy
+                .check(*role, name)
+                .map_err(AgentError::PermissionDenied),
+            None => Ok(()),
         }
     }
 
@@ -100,6 +328,33 @@ impl ReplToolExecutor {
                         .unwrap_or("<unknown>")
                 ),
             ),
+            "apply_patch" => {
+                let mut paths: Vec<&str> = params
+                    .get("patch")
+                    .and_then(|v| v.as_str())
+                    .map(|patch| {
+                        patch
+                            .lines()
+                            .filter_map(|line| line.strip_prefix("+++ "))
+                            .filter(|path| *path != "/dev/null")
+                            .map(|path| path.strip_prefix("b/").unwrap_or(path))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if let Some(files) = params.get("files").and_then(|v| v.as_array()) {
+                    paths.extend(
+                        files
+                            .iter()
+                            .filter_map(|f| f.get("path").and_then(|v| v.as_str())),
+                    );
+                }
+                let paths = if paths.is_empty() {
+                    "<unknown>".to_string()
+                } else {
+                    paths.join(", ")
+                };
+                ("file_write".to_string(), format!("apply_patch {}", paths))
+            }
             "webfetch" | "websearch" => ("network".to_string(), format!("{} request", tool_name)),
             "shell" => (
                 "shell_execute".to_string(),
@@ -156,45 +411,70 @@ impl ReplToolExecutor {
         }
     }
 
+    fn auto_approve() -> bool {
+        std::env::var("NDC_AUTO_APPROVE_TOOLS")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    }
+
+    /// Ask through the TUI channel; `None` when there is no channel
+    async fn ask_via_channel(
+        &self,
+        description: String,
+        permission_key: Option<String>,
+        alternatives: Vec<String>,
+    ) -> Option<Result<HumanDecision, AgentError>> {
+        let tx = self.permission_tx.as_ref()?;
+        let (resp_tx, resp_rx) = oneshot::channel();
+        let request = PermissionRequest {
+            description: description.clone(),
+            permission_key,
+            alternatives,
+            response_tx: resp_tx,
+        };
+        if tx.send(request).await.is_err() {
+            return Some(Err(AgentError::PermissionDenied(format!(
+                "Permission channel closed: {}",
+                description
+            ))));
+        }
+        Some(resp_rx.await.map_err(|_| {
+            AgentError::PermissionDenied(format!(
+                "Permission response channel dropped: {}",
+                description
+            ))
+        }))
+    }
+
+    fn require_terminal(description: &str) -> Result<(), AgentError> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err(AgentError::PermissionDenied(format!(
+                "non_interactive confirmation required: {}; set NDC_AUTO_APPROVE_TOOLS=1 for CI/tests or configure explicit allow policy",
+                description
+            )));
+        }
+        Ok(())
+    }
+
     pub(crate) async fn confirm_operation(
         &self,
         description: String,
         permission_key: Option<String>,
     ) -> Result<bool, AgentError> {
-        if std::env::var("NDC_AUTO_APPROVE_TOOLS")
-            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-            .unwrap_or(false)
-        {
+        if Self::auto_approve() {
             return Ok(true);
         }
 
         // If we have a TUI channel, use it instead of stdin
-        if let Some(tx) = &self.permission_tx {
-            let (resp_tx, resp_rx) = oneshot::channel();
-            tx.send(PermissionRequest {
-                description: description.clone(),
-                permission_key,
-                response_tx: resp_tx,
-            })
+        if let Some(answer) = self
+            .ask_via_channel(description.clone(), permission_key, Vec::new())
             .await
-            .map_err(|_| {
-                AgentError::PermissionDenied(format!("Permission channel closed: {}", description))
-            })?;
-            return resp_rx.await.map_err(|_| {
-                AgentError::PermissionDenied(format!(
-                    "Permission response channel dropped: {}",
-                    description
-                ))
-            });
+        {
+            return answer.map(|decision| decision == HumanDecision::Approve);
         }
 
         // Fallback: stdin-based confirmation for non-TUI mode
-        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
-            return Err(AgentError::PermissionDenied(format!(
-                "non_interactive confirmation required: {}; set NDC_AUTO_APPROVE_TOOLS=1 for CI/tests or configure explicit allow policy",
-                description
-            )));
-        }
+        Self::require_terminal(&description)?;
 
         tokio::task::spawn_blocking(move || -> Result<bool, String> {
             print!("\n[Permission] {}. Allow? [y/N]: ", description);
@@ -211,6 +491,127 @@ impl ReplToolExecutor {
         .map_err(AgentError::PermissionDenied)
     }
 
+    /// Answer a `Verdict::RequireHuman`, offering its alternatives as numbered choices
+    async fn ask_verdict(&self, verdict: &Verdict) -> Result<HumanDecision, AgentError> {
+        let Some(prompt) = PermissionConfirm::for_verdict(verdict) else {
+            return Ok(HumanDecision::Deny);
+        };
+        if Self::auto_approve() {
+            return Ok(HumanDecision::Approve);
+        }
+        if let Some(answer) = self
+            .ask_via_channel(
+                prompt.operation().to_string(),
+                None,
+                prompt.alternatives().to_vec(),
+            )
+            .await
+        {
+            return answer;
+        }
+        Self::require_terminal(prompt.operation())?;
+        Ok(prompt.confirm().await.decision())
+    }
+
+    fn intent(&self, tool_name: &str, action: Action) -> Intent {
+        Intent {
+            id: IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: self
+                .role_scope
+                .as_ref()
+                .map_or(AgentRole::Implementer, |(role, _)| *role),
+            proposed_action: action,
+            effects: vec![],
+            reasoning: format!("{} tool call", tool_name),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    /// Judge the file changes of a tool call. `None` runs the tool as called; when the
+    /// engine modifies the action or the human picks an alternative, the substituted
+    /// action runs here instead and its output is returned.
+    async fn decide(
+        &self,
+        name: &str,
+        params: &serde_json::Value,
+    ) -> Result<Option<String>, AgentError> {
+        let Some(engine) = &self.decision else {
+            return Ok(None);
+        };
+        let (actions, substitutable) = decision_actions(name, params);
+        for action in actions {
+            let mut current = action;
+            let mut substituted = false;
+            let verdict = loop {
+                let mut verdict = engine.evaluate(self.intent(name, current.clone())).await;
+                if let Verdict::RequireHuman { context, .. } = &mut verdict {
+                    if !substitutable {
+                        context.alternatives.clear();
+                    }
+                } else {
+                    break verdict;
+                }
+                let decision = self.ask_verdict(&verdict).await?;
+                match (decision, verdict.resolve_human(decision)) {
+                    // 人选的替代方案同样要过一遍校验器
+                    (HumanDecision::Alternative(_), Verdict::Allow { action, .. }) => {
+                        current = action;
+                        substituted = true;
+                    }
+                    (_, resolved) => break resolved,
+                }
+            };
+            match verdict {
+                Verdict::Allow { .. } if !substituted => {}
+                Verdict::Allow { .. } | Verdict::Modify { .. } if substitutable => {
+                    return self.execute_substituted(&verdict).await.map(Some);
+                }
+                Verdict::Allow { .. } | Verdict::Modify { .. } => {
+                    return Err(AgentError::PermissionDenied(format!(
+                        "Decision engine changed an action of a {} call that cannot be substituted",
+                        name
+                    )));
+                }
+                Verdict::Deny { reason, .. } => return Err(AgentError::PermissionDenied(reason)),
+                Verdict::RequireHuman { question, .. } => {
+                    return Err(AgentError::PermissionDenied(question));
+                }
+                Verdict::Defer { required_info, .. } => {
+                    return Err(AgentError::PermissionDenied(format!(
+                        "Decision deferred, waiting for: {}",
+                        required_info
+                            .iter()
+                            .map(|info| info.description.as_str())
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn execute_substituted(&self, verdict: &Verdict) -> Result<String, AgentError> {
+        let saga = self.run_saga.as_ref().ok_or_else(|| {
+            AgentError::PermissionDenied(
+                "Substituted action needs a run saga to execute".to_string(),
+            )
+        })?;
+        let result = saga
+            .execute_verdict(verdict)
+            .await
+            .map_err(AgentError::ToolError)?;
+        if result.success {
+            Ok(result.output)
+        } else {
+            Err(AgentError::ToolError(
+                result.error.unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+    }
+
     fn map_tool_error(err: ToolError) -> AgentError {
         match err {
             ToolError::PermissionDenied(message) => AgentError::PermissionDenied(message),
@@ -237,13 +638,44 @@ impl ReplToolExecutor {
         );
     }
 
+    /// Scope `params` to the session workspace (or inject the runtime working dir)
+    /// and return the tool to run, bound to the workspace context when there is one.
+    async fn prepare_tool(
+        &self,
+        name: &str,
+        params: &mut serde_json::Value,
+    ) -> Result<(Arc<dyn ndc_runtime::tools::Tool>, bool), AgentError> {
+        let tool = self
+            .tool_registry
+            .get(name)
+            .ok_or_else(|| AgentError::ToolError(format!("Tool '{}' not found", name)))?
+            .clone();
+        let Some(workspace) = self.workspace.lock().await.clone() else {
+            self.inject_runtime_working_dir(name, params).await;
+            return Ok((tool, false));
+        };
+        *params = workspace
+            .scope_params(params, &tool.schema())
+            .map_err(|e| AgentError::PermissionDenied(e.to_string()))?;
+        Ok((workspace.bind_tool(name, tool), true))
+    }
+
     async fn execute_tool_with_runtime_confirmation(
         &self,
         tool: Arc<dyn ndc_runtime::tools::Tool>,
         params: &serde_json::Value,
         description: &str,
+        in_workspace: bool,
     ) -> Result<ndc_runtime::tools::ToolResult, AgentError> {
-        let mut approved_permissions = std::collections::BTreeSet::<String>::new();
+        let mut approved_permissions = self
+            .approved_permissions
+            .iter()
+            .cloned()
+            .collect::<std::collections::BTreeSet<String>>();
+        // Paths are already confined to the workspace root, which replaces the process root
+        if in_workspace {
+            approved_permissions.insert(PERMISSION_EXTERNAL_DIRECTORY.to_string());
+        }
 
         for _attempt in 0..4 {
             let run = async { tool.execute(params).await };
@@ -293,11 +725,12 @@ impl ReplToolExecutor {
 impl ToolExecutor for ReplToolExecutor {
     async fn execute_tool(&self, name: &str, arguments: &str) -> Result<String, AgentError> {
         debug!(tool = %name, args = %arguments, "Executing tool via REPL ToolExecutor");
+        self.authorize_role(name)?;
 
         // 解析参数
         let mut params: serde_json::Value = serde_json::from_str(arguments)
             .map_err(|e| AgentError::ToolError(format!("Invalid arguments: {}", e)))?;
-        self.inject_runtime_working_dir(name, &mut params).await;
+        let (tool, in_workspace) = self.prepare_tool(name, &mut params).await?;
 
         let (permission_key, description) = self.classify_permission(name, &params);
         match self.resolve_permission_rule(&permission_key) {
@@ -321,17 +754,22 @@ impl ToolExecutor for ReplToolExecutor {
             }
         }
 
-        // 查找工具
-        let tool = self
-            .tool_registry
-            .get(name)
-            .ok_or_else(|| AgentError::ToolError(format!("Tool '{}' not found", name)))?
-            .clone();
+        if let Some(output) = self.decide(name, &params).await? {
+            return Ok(output);
+        }
 
         // 执行工具 (Tool::execute 只需要一个参数)
-        let result = tool.execute(&params).await.map_err(Self::map_tool_error)?;
+        let compensation = self.plan_compensation(name, &params);
+        let run = tool.execute(&params);
+        let result = if in_workspace {
+            with_security_overrides(&[PERMISSION_EXTERNAL_DIRECTORY.to_string()], run).await
+        } else {
+            run.await
+        }
+        .map_err(Self::map_tool_error)?;
 
         if result.success {
+            self.record_compensation(compensation).await;
             Ok(result.output)
         } else {
             Err(AgentError::ToolError(
@@ -349,21 +787,24 @@ impl ToolExecutor for ReplToolExecutor {
         if extract_confirmation_permission(permission_message).is_none() {
             return Ok(None);
         }
+        self.authorize_role(name)?;
 
         let mut params: serde_json::Value = serde_json::from_str(arguments)
             .map_err(|e| AgentError::ToolError(format!("Invalid arguments: {}", e)))?;
-        self.inject_runtime_working_dir(name, &mut params).await;
+        let (tool, in_workspace) = self.prepare_tool(name, &mut params).await?;
         let (_, description) = self.classify_permission(name, &params);
-        let tool = self
-            .tool_registry
-            .get(name)
-            .ok_or_else(|| AgentError::ToolError(format!("Tool '{}' not found", name)))?
-            .clone();
 
+        let compensation = self.plan_compensation(name, &params);
         let result = self
-            .execute_tool_with_runtime_confirmation(tool, &params, description.as_str())
+            .execute_tool_with_runtime_confirmation(
+                tool,
+                &params,
+                description.as_str(),
+                in_workspace,
+            )
             .await?;
         if result.success {
+            self.record_compensation(compensation).await;
             Ok(Some(result.output))
         } else {
             Err(AgentError::ToolError(
@@ -372,14 +813,26 @@ impl ToolExecutor for ReplToolExecutor {
         }
     }
 
+    async fn compensate_cancelled(&self) -> Result<(), AgentError> {
+        match &self.run_saga {
+            Some(saga) => saga.compensate().await.map_err(AgentError::ToolError),
+            None => Ok(()),
+        }
+    }
+
     fn list_tools(&self) -> Vec<String> {
-        self.tool_registry.names()
+        self.tool_registry
+            .names()
+            .into_iter()
+            .filter(|name| self.is_tool_visible(name))
+            .collect()
     }
 
     fn tool_schemas(&self) -> Vec<serde_json::Value> {
         self.tool_registry
             .all()
             .iter()
+            .filter(|tool| self.is_tool_visible(tool.name()))
             .map(|tool| {
                 serde_json::json!({
                     "type": "function",
@@ -475,6 +928,208 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_role_scope_hides_and_denies_tools() {
+        let registry = Arc::new(ndc_runtime::create_default_tool_registry());
+        let mut permissions = HashMap::new();
+        permissions.insert("*".to_string(), PermissionRule::Allow);
+        let executor = |role| {
+            ReplToolExecutor::new(
+                registry.clone(),
+                permissions.clone(),
+                Arc::new(tokio::sync::Mutex::new(None)),
+            )
+            .with_role_scope(role, RoleToolPolicy::default())
+        };
+
+        let historian = executor(AgentRole::Historian);
+        let tools = historian.list_tools();
+        assert!(tools.contains(&"read".to_string()));
+        assert!(!tools.contains(&"write".to_string()));
+        assert!(!tools.contains(&"fs".to_string()));
+        assert!(
+            historian
+                .tool_schemas()
+                .iter()
+                .all(|schema| schema["function"]["name"] != "edit")
+        );
+        let result = historian
+            .execute_tool("write", r#"{"path":"/tmp/a.txt","content":"x"}"#)
+            .await;
+        assert!(
+            matches!(result, Err(AgentError::PermissionDenied(ref m)) if m.contains("Insufficient privilege"))
+        );
+
+        let implementer = executor(AgentRole::Implementer);
+        assert!(implementer.list_tools().contains(&"write".to_string()));
+        assert_eq!(implementer.list_tools().len(), registry.names().len());
+    }
+
+    #[tokio::test]
+    async fn test_workspace_scopes_tool_calls_to_project_root() {
+        let _guard = env_lock();
+        let root = tempfile::TempDir::new().unwrap();
+        std::fs::write(root.path().join("needle.marker"), "x").unwrap();
+        let workspace =
+            SessionWorkspace::new(root.path(), HashMap::new()).expect("valid workspace");
+
+        let mut permissions = HashMap::new();
+        permissions.insert("*".to_string(), PermissionRule::Allow);
+        let executor = ReplToolExecutor::new(
+            Arc::new(ndc_runtime::create_default_tool_registry()),
+            permissions,
+            Arc::new(tokio::sync::Mutex::new(None)),
+        )
+        .with_workspace(Arc::new(tokio::sync::Mutex::new(Some(workspace))));
+
+        // No `path`: searches the workspace root rather than the process cwd
+        let output = executor
+            .execute_tool("glob", r#"{"pattern":"*.marker"}"#)
+            .await
+            .unwrap();
+        assert!(output.contains("needle.marker"), "{}", output);
+
+        let escape = executor
+            .execute_tool("glob", r#"{"pattern":"*","path":"../"}"#)
+            .await;
+        assert!(matches!(escape, Err(AgentError::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_undoes_file_changes() {
+        let _guard = env_lock();
+        let root = tempfile::TempDir::new().unwrap();
+        let backups = tempfile::TempDir::new().unwrap();
+        let existing = root.path().join("kept.txt");
+        std::fs::write(&existing, "original").unwrap();
+        let created = root.path().join("created.txt");
+
+        let executor = Arc::new(ndc_runtime::Executor::new(ndc_runtime::ExecutionContext {
+            project_root: root.path().to_path_buf(),
+            backup_dir: Some(backups.path().to_path_buf()),
+            ..Default::default()
+        }));
+        let saga = RunSaga::new(executor);
+        let mut permissions = HashMap::new();
+        permissions.insert("*".to_string(), PermissionRule::Allow);
+        let workspace = SessionWorkspace::new(root.path(), HashMap::new()).unwrap();
+        let tools = ReplToolExecutor::new(
+            Arc::new(ndc_runtime::create_default_tool_registry()),
+            permissions,
+            Arc::new(tokio::sync::Mutex::new(None)),
+        )
+        .with_workspace(Arc::new(tokio::sync::Mutex::new(Some(workspace))))
+        .with_run_saga(saga.clone());
+
+        saga.reset("run-1").await;
+        for (path, content) in [(&existing, "changed"), (&created, "new")] {
+            let args = serde_json::json!({"path": path, "content": content}).to_string();
+            tools.execute_tool("write", &args).await.unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "changed");
+
+        tools.compensate_cancelled().await.unwrap();
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "original");
+        assert!(!created.exists());
+
+        // A new run starts with nothing to undo
+        saga.reset("run-2").await;
+        std::fs::write(&created, "by hand").unwrap();
+        tools.compensate_cancelled().await.unwrap();
+        assert!(created.exists());
+    }
+
+    /// Write tool on a workspace at `root`, judged by `engine`, with human answers on a channel
+    fn decided_tools(
+        root: &std::path::Path,
+        backups: &std::path::Path,
+        engine: ndc_decision::BasicDecisionEngine,
+    ) -> (ReplToolExecutor, RunSaga, mpsc::Receiver<PermissionRequest>) {
+        let executor = Arc::new(ndc_runtime::Executor::new(ndc_runtime::ExecutionContext {
+            project_root: root.to_path_buf(),
+            backup_dir: Some(backups.to_path_buf()),
+            ..Default::default()
+        }));
+        let saga = RunSaga::new(executor);
+        let (tx, rx) = mpsc::channel(4);
+        let mut permissions = HashMap::new();
+        permissions.insert("*".to_string(), PermissionRule::Allow);
+        let workspace = SessionWorkspace::new(root, HashMap::new()).unwrap();
+        let tools = ReplToolExecutor::new(
+            Arc::new(ndc_runtime::create_default_tool_registry()),
+            permissions,
+            Arc::new(tokio::sync::Mutex::new(None)),
+        )
+        .with_workspace(Arc::new(tokio::sync::Mutex::new(Some(workspace))))
+        .with_run_saga(saga.clone())
+        .with_permission_channel(tx)
+        .with_decision_engine(Arc::new(engine));
+        (tools, saga, rx)
+    }
+
+    #[tokio::test]
+    async fn test_chosen_alternative_substitutes_the_action() {
+        let root = tempfile::TempDir::new().unwrap();
+        let backups = tempfile::TempDir::new().unwrap();
+        let manifest = root.path().join("Cargo.toml");
+        std::fs::write(&manifest, "[package]").unwrap();
+        let (tools, saga, mut rx) = decided_tools(
+            root.path(),
+            backups.path(),
+            ndc_runtime::create_decision_engine(root.path(), &Default::default()),
+        );
+
+        let responder = tokio::spawn(async move {
+            let req = rx.recv().await.expect("permission request");
+            assert_eq!(req.alternatives.len(), 1);
+            assert!(req.alternatives[0].contains("Cargo.toml.proposed"));
+            req.response_tx
+                .send(HumanDecision::Alternative(0))
+                .expect("send response");
+        });
+        saga.reset("run-1").await;
+        let args = serde_json::json!({"path": manifest, "content": "[workspace]"}).to_string();
+        tools.execute_tool("write", &args).await.unwrap();
+        responder.await.unwrap();
+
+        let proposed = root.path().join("Cargo.toml.proposed");
+        assert_eq!(std::fs::read_to_string(&manifest).unwrap(), "[package]");
+        assert_eq!(std::fs::read_to_string(&proposed).unwrap(), "[workspace]");
+
+        // The substituted action is part of the run and undone with it
+        tools.compensate_cancelled().await.unwrap();
+        assert!(!proposed.exists());
+    }
+
+    #[tokio::test]
+    async fn test_chosen_alternative_is_revalidated() {
+        let root = tempfile::TempDir::new().unwrap();
+        let backups = tempfile::TempDir::new().unwrap();
+        let manifest = root.path().join("Cargo.toml");
+        std::fs::write(&manifest, "[package]").unwrap();
+        let mut engine = ndc_runtime::create_decision_engine(root.path(), &Default::default());
+        engine.register_validator(Arc::new(
+            ndc_decision::PathAllowlistValidator::new(root.path(), ["Cargo.toml"]).unwrap(),
+        ));
+        let (tools, _saga, mut rx) = decided_tools(root.path(), backups.path(), engine);
+
+        let responder = tokio::spawn(async move {
+            let req = rx.recv().await.expect("permission request");
+            req.response_tx
+                .send(HumanDecision::Alternative(0))
+                .expect("send response");
+        });
+        let args = serde_json::json!({"path": manifest, "content": "[workspace]"}).to_string();
+        let result = tools.execute_tool("write", &args).await;
+        responder.await.unwrap();
+
+        assert!(
+            matches!(result, Err(AgentError::PermissionDenied(message)) if message.contains("outside the write allowlist"))
+        );
+        assert!(!root.path().join("Cargo.toml.proposed").exists());
+        assert_eq!(std::fs::read_to_string(&manifest).unwrap(), "[package]");
+    }
+
     #[tokio::test]
     async fn test_permission_deny_blocks_tool_execution() {
         let mut registry = ToolRegistry::new();
@@ -671,7 +1326,9 @@ mod tests {
         let responder = tokio::spawn(async move {
             let req = rx.recv().await.expect("should receive permission request");
             assert!(req.description.contains("write"));
-            req.response_tx.send(true).expect("send response");
+            req.response_tx
+                .send(HumanDecision::Approve)
+                .expect("send response");
         });
 
         let result = executor
@@ -701,7 +1358,9 @@ mod tests {
 
         let responder = tokio::spawn(async move {
             let req = rx.recv().await.expect("should receive permission request");
-            req.response_tx.send(false).expect("send response");
+            req.response_tx
+                .send(HumanDecision::Deny)
+                .expect("send response");
         });
 
         let result = executor
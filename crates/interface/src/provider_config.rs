@@ -2,7 +2,8 @@
 //!
 //! Extracted from `agent_mode.rs` (SEC-S1 God Object refactoring).
 
-use ndc_core::{NdcConfigLoader, ProviderConfig, ProviderType};
+use ndc_core::{AgentError, LlmProvider, NdcConfigLoader, ProviderConfig, ProviderType};
+use std::sync::Arc;
 
 /// Returns `true` when `provider` belongs to the MiniMax family of aliases.
 pub(crate) fn is_minimax_family(provider: &str) -> bool {
@@ -154,6 +155,58 @@ pub(crate) fn create_provider_config(provider_name: &str, model: &str) -> Provid
     }
 }
 
+/// 创建 LLM Provider
+pub(crate) fn create_provider(
+    provider_name: &str,
+    model: &str,
+) -> Result<Arc<dyn LlmProvider>, AgentError> {
+    use ndc_core::llm::provider::{
+        AnthropicProvider, OllamaProvider, OpenAiProvider, OpenRouterProvider, SimpleTokenCounter,
+        TokenCounter,
+    };
+
+    // 根据 provider 名称创建相应的 Provider
+    let provider_type: ProviderType = if is_minimax_family(provider_name) {
+        ProviderType::MiniMax
+    } else {
+        provider_name.to_string().into()
+    };
+    let token_counter: Arc<dyn TokenCounter> = Arc::new(SimpleTokenCounter::new());
+
+    match provider_type {
+        ProviderType::OpenAi => {
+            let config = create_provider_config(provider_name, model);
+            let provider = OpenAiProvider::new(config, token_counter);
+            Ok(Arc::new(provider))
+        }
+        ProviderType::Anthropic => {
+            let config = create_provider_config(provider_name, model);
+            let provider = AnthropicProvider::new(config, token_counter);
+            Ok(Arc::new(provider))
+        }
+        ProviderType::MiniMax => {
+            let config = create_provider_config(provider_name, model);
+            // Align with OpenCode: use Anthropic-compatible MiniMax endpoint.
+            let provider = AnthropicProvider::new(config, token_counter);
+            Ok(Arc::new(provider))
+        }
+        ProviderType::OpenRouter => {
+            let config = create_provider_config(provider_name, model);
+            let provider = OpenRouterProvider::new(config, token_counter);
+            Ok(Arc::new(provider))
+        }
+        ProviderType::Ollama => {
+            let config = create_provider_config(provider_name, model);
+            let provider = OllamaProvider::new(config, token_counter);
+            Ok(Arc::new(provider))
+        }
+        _ => Err(AgentError::InvalidRequest(format!(
+            "Provider '{}' is not supported. Supported: openai, anthropic, minimax, openrouter, ollama",
+            provider_name
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,17 +101,38 @@ impl ReplState {
     }
 }
 
+/// 会话记录目录：历史文件所在目录
+pub fn transcript_dir(history_file: &std::path::Path) -> PathBuf {
+    match history_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
 /// 运行 REPL (OpenCode 风格)
-pub async fn run_repl(history_file: PathBuf, executor: Arc<ndc_runtime::Executor>) {
+///
+/// `resume` 为之前保存的会话记录，TUI 模式下载入对话面板并沿用其会话 ID。
+pub async fn run_repl(
+    history_file: PathBuf,
+    executor: Arc<ndc_runtime::Executor>,
+    resume: Option<SessionTranscript>,
+) {
     let config = ReplConfig::new(history_file);
     let mut viz_state = ReplVisualizationState::new(config.show_thought);
+    let session_id = resume
+        .as_ref()
+        .map(|t| t.session_id.clone())
+        .unwrap_or_else(|| ReplState::new().session_id);
+    let transcript = TranscriptConfig {
+        dir: transcript_dir(&config.history_file),
+        session_id,
+        resume,
+    };
 
     // 创建 Agent Mode Manager (OpenCode 风格: 默认启用)
     let agent_manager = Arc::new(AgentModeManager::new(
         executor.clone(),
-        Arc::new(ndc_runtime::create_default_tool_registry_with_storage(
-            executor.context().storage.clone(),
-        )),
+        crate::agent_mode::agent_tool_registry(executor.context()).await,
     ));
 
     // TUI 模式下设置权限确认通道（在 enable 之前）
@@ -155,6 +176,7 @@ pub async fn run_repl(history_file: PathBuf, executor: Arc<ndc_runtime::Executor
             &mut viz_state,
             agent_backend.clone(),
             permission_rx.unwrap(),
+            Some(transcript),
         )
         .await
         {
@@ -38,7 +38,10 @@ pub(crate) struct SessionArchiveStore {
 
 impl SessionArchiveStore {
     pub(crate) fn load_default() -> Self {
-        let path = session_archive_file_path();
+        Self::load_from(session_archive_file_path())
+    }
+
+    pub(crate) fn load_from(path: PathBuf) -> Self {
         let archive = load_session_archive(path.as_path()).unwrap_or_default();
         Self { path, archive }
     }
@@ -0,0 +1,144 @@
+//! Verify — `ndc verify` standalone quality gate.
+//!
+//! Runs the project's quality checks outside of task execution, so CI can use
+//! the same gate logic. Checks come from `--check`, else
+//! `runtime.quality_gates`, else `typecheck,lint,test`; each check runs the
+//! command from `runtime.quality_commands` or the cargo default.
+//!
+//! Every check runs (no fail-fast) and is reported with its timing; the
+//! command fails when any check fails.
+
+use std::fmt;
+use std::path::Path;
+use std::time::Instant;
+
+use ndc_core::{QualityCheckType, YamlRuntimeConfig};
+use ndc_runtime::{QualityCommands, QualityGateRunner};
+
+/// Checks run when neither `--check` nor `runtime.quality_gates` is given
+const DEFAULT_CHECKS: &[&str] = &["typecheck", "lint", "test"];
+
+/// Outcome of one check
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct CheckOutcome {
+    pub(crate) check: String,
+    pub(crate) passed: bool,
+    pub(crate) duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+/// Outcome of a `verify` run
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct VerifyReport {
+    pub(crate) checks: Vec<CheckOutcome>,
+}
+
+impl VerifyReport {
+    pub(crate) fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    pub(crate) fn failed(&self) -> usize {
+        self.checks.iter().filter(|c| !c.passed).count()
+    }
+
+    /// Process exit code for the run
+    pub(crate) fn exit_code(&self) -> i32 {
+        if self.passed() { 0 } else { 1 }
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = if check.passed { "PASS" } else { "FAIL" };
+            write!(
+                f,
+                "{} {:<12} {:>7.2}s",
+                status,
+                check.check,
+                check.duration_ms as f64 / 1000.0
+            )?;
+            if let Some(error) = &check.error {
+                write!(f, "  {}", error)?;
+            }
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "{} of {} check(s) passed",
+            self.checks.len() - self.failed(),
+            self.checks.len()
+        )
+    }
+}
+
+/// Resolve check keys (`--check` or `runtime.quality_gates`), rejecting unknown ones
+fn resolve_checks(keys: &[String]) -> Result<Vec<QualityCheckType>, String> {
+    let keys: Vec<&str> = if keys.is_empty() {
+        DEFAULT_CHECKS.to_vec()
+    } else {
+        keys.iter().map(String::as_str).collect()
+    };
+    let mut checks: Vec<QualityCheckType> = Vec::new();
+    for key in keys {
+        let check = QualityGateRunner::parse_check(key).ok_or_else(|| {
+            format!(
+                "unknown check '{}' (expected test, lint, typecheck, build, security or custom:<name>)",
+                key
+            )
+        })?;
+        if !checks.contains(&check) {
+            checks.push(check);
+        }
+    }
+    Ok(checks)
+}
+
+/// Run `checks` in `project_root`, continuing past failures
+async fn run_checks(
+    project_root: &Path,
+    commands: QualityCommands,
+    checks: &[QualityCheckType],
+) -> VerifyReport {
+    let runner = QualityGateRunner::new()
+        .with_working_dir(project_root)
+        .with_commands(commands);
+    let mut report = VerifyReport::default();
+    for check in checks {
+        let started = Instant::now();
+        let (passed, error) = match runner.run_check(check).await {
+            Ok(result) => (result.passed, result.error),
+            Err(e) => (false, Some(e)),
+        };
+        report.checks.push(CheckOutcome {
+            check: QualityGateRunner::check_key(check),
+            passed,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error,
+        });
+    }
+    report
+}
+
+/// Run `keys` (or the configured gate) in `project_root` with the project's
+/// `runtime` config; `Err` for unknown check names
+pub(crate) async fn verify(
+    project_root: &Path,
+    keys: &[String],
+    runtime: Option<&YamlRuntimeConfig>,
+) -> Result<VerifyReport, String> {
+    let keys = match (
+        keys.is_empty(),
+        runtime.and_then(|r| r.quality_gates.as_ref()),
+    ) {
+        (true, Some(gate)) => gate.as_slice(),
+        _ => keys,
+    };
+    let checks = resolve_checks(keys)?;
+    let commands = runtime
+        .map(|r| QualityCommands::from_map(r.quality_commands.clone()))
+        .unwrap_or_default();
+    Ok(run_checks(project_root, commands, &checks).await)
+}
@@ -0,0 +1,192 @@
+//! Commit gate - enforce Hard Constraints before `GitOp::Commit`
+//!
+//! `CommitGateValidator` plugs into the decision engine: a commit intent is
+//! denied while `HardConstraints::check` reports unmet constraints against
+//! the shared `WorkingState`, with the readable report as the reason.
+//!
+//! `CommitGate` is the shared handle: the executor installs each task's
+//! constraints, and the quality gate records the checks that passed.
+
+use async_trait::async_trait;
+use ndc_core::{Action, GitOp, Intent, QualityCheckType};
+use ndc_decision::{PolicyState, ValidationResult, Validator};
+use std::sync::{Arc, RwLock};
+
+use super::{ConstraintReport, HardConstraints, WorkingState};
+
+/// Hard constraints of the current task and what has been verified against them
+#[derive(Debug, Clone, Default)]
+pub struct CommitGate {
+    constraints: Arc<RwLock<Option<HardConstraints>>>,
+    state: Arc<RwLock<WorkingState>>,
+}
+
+impl CommitGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gate commits on `constraints` (none: commits pass) with nothing verified yet
+    pub fn begin(&self, constraints: Option<HardConstraints>) {
+        *self.constraints.write().unwrap_or_else(|e| e.into_inner()) = constraints;
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = WorkingState::new();
+    }
+
+    /// Record a passed quality check: `Test` covers every regression test
+    /// module, other checks the validations they perform
+    pub fn record_passed(&self, check: &QualityCheckType) {
+        let constraints = self.constraints.read().unwrap_or_else(|e| e.into_inner());
+        let Some(constraints) = constraints.as_ref() else {
+            return;
+        };
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if *check == QualityCheckType::Test {
+            for test in &constraints.mandatory_regression_tests {
+                state.record_tests_passed(test.module.clone());
+            }
+        }
+        for validation in &constraints.mandatory_validations {
+            if validation.validation_type.quality_check() == *check {
+                state.record_validation(validation.path.clone(), validation.validation_type);
+            }
+        }
+    }
+
+    /// Report for the current constraints; `None` when no task is gated
+    pub fn report(&self) -> Option<ConstraintReport> {
+        let constraints = self.constraints.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        constraints.as_ref().map(|c| c.check(&state))
+    }
+}
+
+/// Denies commits until every hard constraint is met
+#[derive(Debug)]
+pub struct CommitGateValidator {
+    gate: CommitGate,
+}
+
+impl CommitGateValidator {
+    /// Validator checking `constraints` against `state`, which callers keep
+    /// updating as tests and validations run
+    pub fn new(constraints: HardConstraints, state: Arc<RwLock<WorkingState>>) -> Self {
+        Self {
+            gate: CommitGate {
+                constraints: Arc::new(RwLock::new(Some(constraints))),
+                state,
+            },
+        }
+    }
+
+    /// Validator following the constraints and progress of `gate`
+    pub fn for_gate(gate: CommitGate) -> Self {
+        Self { gate }
+    }
+}
+
+#[async_trait]
+impl Validator for CommitGateValidator {
+    async fn validate(&self, intent: &Intent, _policy: &PolicyState) -> ValidationResult {
+        let Action::Git {
+            operation: GitOp::Commit { .. },
+        } = &intent.proposed_action
+        else {
+            return ValidationResult::Allow;
+        };
+        match self.gate.report() {
+            Some(report) if !report.is_satisfied() => ValidationResult::Deny(report.to_string()),
+            _ => ValidationResult::Allow,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "commit_gate"
+    }
+
+    fn priority(&self) -> u32 {
+        10
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{FileValidation, FileValidationType, RegressionTest};
+    use ndc_core::{AgentId, AgentRole, IntentId, Verdict};
+    use ndc_decision::{BasicDecisionEngine, DecisionEngine};
+    use std::path::PathBuf;
+
+    fn intent(action: Action) -> Intent {
+        Intent {
+            id: IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Admin,
+            proposed_action: action,
+            effects: vec![],
+            reasoning: "finish task".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn commit() -> Intent {
+        intent(Action::Git {
+            operation: GitOp::Commit {
+                message: "feat: done".to_string(),
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_commit_denied_until_constraints_are_met() {
+        let mut constraints = HardConstraints::new("task-1".to_string());
+        constraints.add_regression_test(RegressionTest {
+            module: "storage".to_string(),
+            test_files: vec![PathBuf::from("tests/storage.rs")],
+            test_types: vec![],
+            coverage_requirement: 0.0,
+        });
+        constraints.mandatory_validations.push(FileValidation {
+            path: PathBuf::from("src/db.rs"),
+            validation_type: FileValidationType::Linting,
+            reason: "touched".to_string(),
+            tool: "clippy".to_string(),
+        });
+        let state = Arc::new(RwLock::new(WorkingState::new()));
+        let mut engine = BasicDecisionEngine::new();
+        engine.register_validator(Arc::new(CommitGateValidator::new(
+            constraints,
+            state.clone(),
+        )));
+
+        match engine.evaluate(commit()).await {
+            Verdict::Deny { reason, .. } => {
+                assert!(
+                    reason.starts_with("2 hard constraint(s) unmet"),
+                    "{}",
+                    reason
+                );
+                assert!(reason.contains("module `storage`"), "{}", reason);
+            }
+            other => panic!("Expected Deny for commit, got {:?}", other),
+        }
+        // Other actions are not gated
+        let status = intent(Action::Git {
+            operation: GitOp::Status,
+        });
+        assert!(matches!(
+            engine.evaluate(status).await,
+            Verdict::Allow { .. }
+        ));
+
+        {
+            let mut state = state.write().unwrap();
+            state.record_tests_passed("storage");
+            state.record_validation("src/db.rs", FileValidationType::Linting);
+        }
+        assert!(matches!(
+            engine.evaluate(commit()).await,
+            Verdict::Allow { .. }
+        ));
+    }
+}
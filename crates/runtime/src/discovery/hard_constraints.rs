@@ -5,8 +5,9 @@
 
 use ndc_core::RiskLevel;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Hard Constraints - Generated from Discovery Phase
 ///
@@ -265,7 +266,7 @@ pub struct FileValidation {
     pub tool: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FileValidationType {
     /// Check syntax
     Syntax,
@@ -286,6 +287,19 @@ pub enum FileValidationType {
     Documentation,
 }
 
+impl FileValidationType {
+    /// Quality check that performs this validation
+    pub fn quality_check(self) -> ndc_core::QualityCheckType {
+        use ndc_core::QualityCheckType;
+        match self {
+            Self::Syntax | Self::Types => QualityCheckType::TypeCheck,
+            Self::Formatting | Self::Linting => QualityCheckType::Lint,
+            Self::Security => QualityCheckType::Security,
+            Self::Documentation => QualityCheckType::Custom("documentation".to_string()),
+        }
+    }
+}
+
 impl HardConstraints {
     /// Create new empty constraints
     pub fn new(task_id: String) -> Self {
@@ -355,6 +369,65 @@ impl HardConstraints {
         failures
     }
 
+    /// Evaluate every constraint against what actually ran in `state`
+    ///
+    /// Regression tests must have passed for their module, with measured
+    /// coverage at or above the requirement, and each mandatory validation
+    /// must have passed for its file.
+    pub fn check(&self, state: &WorkingState) -> ConstraintReport {
+        let mut failures = Vec::new();
+
+        for test in &self.mandatory_regression_tests {
+            if !state.passed_test_modules.contains(&test.module) {
+                failures.push(FailedConstraint {
+                    constraint_type: "regression_test".to_string(),
+                    description: format!(
+                        "Regression tests for module `{}` have not passed",
+                        test.module
+                    ),
+                    severity: Severity::High,
+                });
+                continue;
+            }
+            if test.coverage_requirement > 0.0 {
+                match state.coverage.get(&test.module) {
+                    Some(&coverage) if coverage >= test.coverage_requirement => {}
+                    measured => failures.push(FailedConstraint {
+                        constraint_type: "coverage".to_string(),
+                        description: format!(
+                            "Coverage for module `{}` is {}, required {:.0}%",
+                            test.module,
+                            measured
+                                .map_or("unmeasured".to_string(), |c| format!("{:.0}%", c * 100.0)),
+                            test.coverage_requirement * 100.0
+                        ),
+                        severity: Severity::Medium,
+                    }),
+                }
+            }
+        }
+
+        for validation in &self.mandatory_validations {
+            if !state.validation_passed(&validation.path, validation.validation_type) {
+                failures.push(FailedConstraint {
+                    constraint_type: "validation".to_string(),
+                    description: format!(
+                        "{:?} validation of {} has not passed ({})",
+                        validation.validation_type,
+                        validation.path.display(),
+                        validation.reason
+                    ),
+                    severity: Severity::High,
+                });
+            }
+        }
+
+        ConstraintReport {
+            task_id: self.task_id.clone(),
+            failures,
+        }
+    }
+
     /// Generate summary for logging
     pub fn summary(&self) -> HardConstraintsSummary {
         HardConstraintsSummary {
@@ -377,6 +450,103 @@ pub struct FailedConstraint {
     pub severity: Severity,
 }
 
+impl fmt::Display for FailedConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:?}] {}: {}",
+            self.severity, self.constraint_type, self.description
+        )
+    }
+}
+
+/// What has actually been verified in the working tree so far
+#[derive(Debug, Clone, Default)]
+pub struct WorkingState {
+    /// Modules whose regression tests ran and passed
+    pub passed_test_modules: HashSet<String>,
+
+    /// Measured coverage per module (0.0 - 1.0)
+    pub coverage: HashMap<String, f64>,
+
+    /// Validations that passed, per file
+    pub passed_validations: HashSet<(PathBuf, FileValidationType)>,
+}
+
+impl WorkingState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_tests_passed(&mut self, module: impl Into<String>) {
+        self.passed_test_modules.insert(module.into());
+    }
+
+    pub fn record_coverage(&mut self, module: impl Into<String>, coverage: f64) {
+        self.coverage.insert(module.into(), coverage);
+    }
+
+    pub fn record_validation(&mut self, path: impl Into<PathBuf>, kind: FileValidationType) {
+        self.passed_validations
+            .insert((normalize_path(&path.into()), kind));
+    }
+
+    /// `./src/a.rs` and `src/b/../a.rs` count as `src/a.rs`
+    fn validation_passed(&self, path: &Path, kind: FileValidationType) -> bool {
+        let path = normalize_path(path);
+        self.passed_validations
+            .iter()
+            .any(|(passed, passed_kind)| *passed_kind == kind && normalize_path(passed) == path)
+    }
+}
+
+/// Lexically drop `.` and resolve `..` components
+fn normalize_path(path: &Path) -> PathBuf {
+    use std::path::Component;
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Result of `HardConstraints::check`
+#[derive(Debug, Clone)]
+pub struct ConstraintReport {
+    pub task_id: String,
+    pub failures: Vec<FailedConstraint>,
+}
+
+impl ConstraintReport {
+    pub fn is_satisfied(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for ConstraintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.failures.is_empty() {
+            return write!(f, "All hard constraints met for {}", self.task_id);
+        }
+        write!(
+            f,
+            "{} hard constraint(s) unmet for {}:",
+            self.failures.len(),
+            self.task_id
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n  - {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Severity {
     Low,
@@ -479,4 +649,62 @@ mod tests {
         assert!(output.contains("2"));
         assert!(output.contains("5"));
     }
+
+    #[test]
+    fn test_check_reports_unmet_constraints() {
+        let mut constraints = HardConstraints::new("task-123".to_string());
+        constraints.add_regression_test(RegressionTest {
+            module: "core".to_string(),
+            test_files: vec![PathBuf::from("tests/core.rs")],
+            test_types: vec![TestType::Unit],
+            coverage_requirement: 0.8,
+        });
+        constraints.mandatory_validations.push(FileValidation {
+            path: PathBuf::from("src/lib.rs"),
+            validation_type: FileValidationType::Security,
+            reason: "critical path".to_string(),
+            tool: "cargo-audit".to_string(),
+        });
+
+        let mut state = WorkingState::new();
+        let report = constraints.check(&state);
+        assert_eq!(report.failures.len(), 2);
+        let text = report.to_string();
+        assert!(text.starts_with("2 hard constraint(s) unmet for task-123:"));
+        assert!(text.contains("[High] regression_test: Regression tests for module `core`"));
+        assert!(text.contains("Security validation of src/lib.rs has not passed"));
+
+        state.record_tests_passed("core");
+        state.record_coverage("core", 0.5);
+        state.record_validation("src/lib.rs", FileValidationType::Security);
+        let report = constraints.check(&state);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].constraint_type, "coverage");
+        assert!(
+            report.failures[0]
+                .description
+                .contains("is 50%, required 80%")
+        );
+
+        state.record_coverage("core", 0.85);
+        assert!(constraints.check(&state).is_satisfied());
+    }
+
+    #[test]
+    fn test_validations_match_equivalent_paths() {
+        let mut constraints = HardConstraints::new("task-1".to_string());
+        constraints.mandatory_validations.push(FileValidation {
+            path: PathBuf::from("./src/lib.rs"),
+            validation_type: FileValidationType::Linting,
+            reason: "touched".to_string(),
+            tool: "clippy".to_string(),
+        });
+
+        let mut state = WorkingState::new();
+        state.record_validation("src/lib.rs", FileValidationType::Formatting);
+        assert!(!constraints.check(&state).is_satisfied());
+
+        state.record_validation("src/util/../lib.rs", FileValidationType::Linting);
+        assert!(constraints.check(&state).is_satisfied());
+    }
 }
@@ -76,6 +76,9 @@ pub struct VolatilityHeatmap {
     /// Module change count (raw)
     raw_counts: HashMap<ModuleId, u32>,
 
+    /// Module change count weighted by age (equals raw count without decay)
+    decayed_counts: HashMap<ModuleId, f64>,
+
     /// Calculation parameters
     config: HeatmapConfig,
 }
@@ -90,6 +93,27 @@ pub struct HeatmapConfig {
 
     /// Normalization factor
     pub normalization_factor: f64,
+
+    /// Half-life in days for change weighting; `None` or 0 counts every change as 1
+    #[serde(default = "default_half_life_days")]
+    pub half_life_days: Option<f64>,
+}
+
+fn default_half_life_days() -> Option<f64> {
+    Some(2.0)
+}
+
+impl HeatmapConfig {
+    /// Weight of a change `age` old: halves every `half_life_days`
+    pub fn decay_weight(&self, age: Duration) -> f64 {
+        match self.half_life_days {
+            Some(half_life) if half_life > 0.0 => {
+                let age_days = age.num_seconds().max(0) as f64 / 86_400.0;
+                0.5f64.powf(age_days / half_life)
+            }
+            _ => 1.0,
+        }
+    }
 }
 
 impl Default for HeatmapConfig {
@@ -98,6 +122,7 @@ impl Default for HeatmapConfig {
             lookback_days: 7,
             high_volatility_threshold: 5,
             normalization_factor: 1.0,
+            half_life_days: default_half_life_days(),
         }
     }
 }
@@ -108,6 +133,7 @@ pub struct ModuleVolatility {
     pub module: ModuleId,
     pub score: f64,     // 0-1 normalized
     pub raw_count: u32, // Raw change count
+    pub decayed_count: f64,
     pub recent_files: Vec<PathBuf>,
     pub risk_level: RiskLevel,
 }
@@ -139,51 +165,125 @@ impl VolatilityHeatmap {
         // Get changed files from git
         let changes = Self::get_git_changes(repo_path, since).await?;
 
-        // Build raw counts
-        let mut raw_counts: HashMap<ModuleId, u32> = HashMap::new();
-        for change in &changes {
-            let module = Self::identify_module(&change.path);
-            *raw_counts.entry(module).or_insert(0) += 1;
-        }
-
-        // Normalize frequencies
-        let max_count = raw_counts.values().max().copied().unwrap_or(1);
-        let mut module_frequency: HashMap<ModuleId, f64> = HashMap::new();
-
-        for (module, count) in &raw_counts {
-            let normalized = (*count as f64) / (max_count as f64) * config.normalization_factor;
-            module_frequency.insert(module.clone(), normalized.min(1.0));
-        }
-
         // Load core modules
         let core_modules = Self::identify_core_modules(repo_path).await?;
 
-        Ok(Self {
-            module_frequency,
+        Ok(Self::from_changes(
+            changes,
+            core_modules,
+            config,
+            Utc::now(),
+        ))
+    }
+
+    /// Build a heatmap from already collected changes, aged relative to `now`
+    pub fn from_changes(
+        changes: Vec<GitChange>,
+        core_modules: Vec<ModuleId>,
+        config: HeatmapConfig,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut heatmap = Self {
+            module_frequency: HashMap::new(),
             recent_changes: changes,
             core_modules,
-            raw_counts,
+            raw_counts: HashMap::new(),
+            decayed_counts: HashMap::new(),
             config,
-        })
+        };
+        heatmap.recompute(now);
+        heatmap
+    }
+
+    /// Fold newly committed changes into the heatmap, aged relative to `now`
+    ///
+    /// Changes that fell out of the lookback window are dropped. Scores are
+    /// recomputed from the changes already in memory, so git is only asked
+    /// for the new commits.
+    pub fn apply_changes(&mut self, new_changes: Vec<GitChange>, now: DateTime<Utc>) {
+        self.recent_changes.extend(new_changes);
+        let since = now - Duration::days(self.config.lookback_days as i64);
+        self.recent_changes.retain(|c| c.timestamp >= since);
+        self.recompute(now);
+    }
+
+    fn recompute(&mut self, now: DateTime<Utc>) {
+        // Build raw and decayed counts
+        let mut raw_counts: HashMap<ModuleId, u32> = HashMap::new();
+        let mut decayed_counts: HashMap<ModuleId, f64> = HashMap::new();
+        for change in &self.recent_changes {
+            let module = Self::identify_module(&change.path);
+            *raw_counts.entry(module.clone()).or_insert(0) += 1;
+            *decayed_counts.entry(module).or_insert(0.0) +=
+                self.config.decay_weight(now - change.timestamp);
+        }
+
+        // Normalize decayed frequencies
+        let max_count = decayed_counts
+            .values()
+            .copied()
+            .fold(0.0, f64::max)
+            .max(f64::MIN_POSITIVE);
+        self.module_frequency = decayed_counts
+            .iter()
+            .map(|(module, count)| {
+                let normalized = count / max_count * self.config.normalization_factor;
+                (module.clone(), normalized.min(1.0))
+            })
+            .collect();
+        self.raw_counts = raw_counts;
+        self.decayed_counts = decayed_counts;
     }
 
     /// Get git changes since a given timestamp
-    async fn get_git_changes(
+    pub(crate) async fn get_git_changes(
         repo_path: &Path,
         since: DateTime<Utc>,
     ) -> Result<Vec<GitChange>, HeatmapError> {
         // Format timestamp for git
         let since_str = since.format("%Y-%m-%dT%H:%M:%S").to_string();
+        Self::git_log_changes(repo_path, &["--since", &since_str]).await
+    }
+
+    /// Get git changes of the commits in `from..to`
+    pub(crate) async fn get_git_changes_between(
+        repo_path: &Path,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<GitChange>, HeatmapError> {
+        Self::git_log_changes(repo_path, &[&format!("{}..{}", from, to)]).await
+    }
+
+    /// Current HEAD commit, `None` before the first commit
+    pub(crate) async fn git_head(repo_path: &Path) -> Result<Option<String>, HeatmapError> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--verify", "-q", "HEAD"])
+            .current_dir(repo_path)
+            .output()
+            .await
+            .map_err(|e| HeatmapError::GitCommandFailed(e.to_string()))?;
+        let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((output.status.success() && !head.is_empty()).then_some(head))
+    }
+
+    async fn git_is_ancestor(repo_path: &Path, ancestor: &str, commit: &str) -> bool {
+        Command::new("git")
+            .args(["merge-base", "--is-ancestor", ancestor, commit])
+            .current_dir(repo_path)
+            .status()
+            .await
+            .is_ok_and(|status| status.success())
+    }
 
-        // Run git log --name-status
+    /// Run git log --name-status with extra selection args and parse the changes
+    async fn git_log_changes(
+        repo_path: &Path,
+        selection: &[&str],
+    ) -> Result<Vec<GitChange>, HeatmapError> {
         let output = Command::new("git")
-            .args([
-                "log",
-                "--since",
-                &since_str,
-                "--name-status",
-                "--pretty=format:%H|%an|%ai",
-            ])
+            .arg("log")
+            .args(selection)
+            .args(["--name-status", "--pretty=format:%H|%an|%ai"])
             .current_dir(repo_path)
             .output()
             .await
@@ -276,7 +376,9 @@ impl VolatilityHeatmap {
     }
 
     /// Identify core modules (high-risk areas)
-    async fn identify_core_modules(_repo_path: &Path) -> Result<Vec<ModuleId>, HeatmapError> {
+    pub(crate) async fn identify_core_modules(
+        _repo_path: &Path,
+    ) -> Result<Vec<ModuleId>, HeatmapError> {
         // Core modules typically include:
         // - core/src/
         // - crates/core/src/
@@ -299,6 +401,7 @@ impl VolatilityHeatmap {
     pub fn get_module_volatility(&self, module: &ModuleId) -> ModuleVolatility {
         let score = self.module_frequency.get(module).copied().unwrap_or(0.0);
         let raw_count = self.raw_counts.get(module).copied().unwrap_or(0);
+        let decayed_count = self.decayed_counts.get(module).copied().unwrap_or(0.0);
 
         let recent_files: Vec<PathBuf> = self
             .recent_changes
@@ -314,20 +417,29 @@ impl VolatilityHeatmap {
             module: module.clone(),
             score,
             raw_count,
+            decayed_count,
             recent_files,
             risk_level: volatility_to_risk_level(score),
         }
     }
 
     /// Get all high-volatility modules
+    ///
+    /// A module qualifies with a decayed score at medium risk or above, or a
+    /// decayed change count over `high_volatility_threshold`.
     pub fn get_high_volatility_modules(&self) -> Vec<ModuleVolatility> {
         self.module_frequency
             .iter()
-            .filter(|&(_, &score)| score >= 0.3) // Above medium risk
+            .filter(|&(module, &score)| score >= 0.3 || self.exceeds_threshold(module))
             .map(|(module, _)| self.get_module_volatility(module))
             .collect()
     }
 
+    fn exceeds_threshold(&self, module: &ModuleId) -> bool {
+        self.decayed_counts.get(module).copied().unwrap_or(0.0)
+            > self.config.high_volatility_threshold as f64
+    }
+
     /// Check if a file is high-risk based on heatmap
     pub fn is_high_risk(&self, file: &Path) -> bool {
         let module = Self::identify_module(&file.to_path_buf());
@@ -340,18 +452,8 @@ impl VolatilityHeatmap {
         // Check volatility score
         let score = self.module_frequency.get(&module).copied().unwrap_or(0.0);
 
-        // Count recent changes
-        let recent_count = self
-            .recent_changes
-            .iter()
-            .filter(|c| {
-                let m = Self::identify_module(&c.path);
-                m == module
-            })
-            .count();
-
-        // Heatmap rule: recent changes > threshold OR high normalized score
-        recent_count > self.config.high_volatility_threshold as usize || score > 0.6
+        // Heatmap rule: decayed changes > threshold OR high normalized score
+        self.exceeds_threshold(&module) || score > 0.6
     }
 
     /// Get all modules sorted by volatility
@@ -367,6 +469,91 @@ impl VolatilityHeatmap {
     }
 }
 
+/// Keeps a heatmap current while commits land
+///
+/// Each refresh only reads the commits between the last seen HEAD and the
+/// current one. Rewritten history (amend, rebase, reset) triggers a full
+/// rebuild over the lookback window.
+#[derive(Debug, Clone)]
+pub struct HeatmapWatcher {
+    repo_path: PathBuf,
+    heatmap: VolatilityHeatmap,
+    last_head: Option<String>,
+}
+
+impl HeatmapWatcher {
+    /// Build the initial heatmap and remember the current HEAD
+    pub async fn new(
+        repo_path: &Path,
+        config: Option<HeatmapConfig>,
+    ) -> Result<Self, HeatmapError> {
+        let config = config.unwrap_or_default();
+        let last_head = VolatilityHeatmap::git_head(repo_path).await?;
+        let heatmap = Self::build(repo_path, last_head.is_some(), config).await?;
+        Ok(Self {
+            repo_path: repo_path.to_path_buf(),
+            heatmap,
+            last_head,
+        })
+    }
+
+    /// Current heatmap
+    pub fn heatmap(&self) -> &VolatilityHeatmap {
+        &self.heatmap
+    }
+
+    /// HEAD commit the heatmap reflects
+    pub fn last_head(&self) -> Option<&str> {
+        self.last_head.as_deref()
+    }
+
+    /// Fold commits made since the last refresh into the heatmap
+    ///
+    /// Returns `false` when HEAD has not moved.
+    pub async fn refresh(&mut self) -> Result<bool, HeatmapError> {
+        let head = VolatilityHeatmap::git_head(&self.repo_path).await?;
+        if head == self.last_head {
+            return Ok(false);
+        }
+
+        match (&self.last_head, &head) {
+            (Some(last), Some(current))
+                if VolatilityHeatmap::git_is_ancestor(&self.repo_path, last, current).await =>
+            {
+                let changes =
+                    VolatilityHeatmap::get_git_changes_between(&self.repo_path, last, current)
+                        .await?;
+                self.heatmap.apply_changes(changes, Utc::now());
+            }
+            _ => {
+                let config = self.heatmap.config.clone();
+                self.heatmap = Self::build(&self.repo_path, head.is_some(), config).await?;
+            }
+        }
+
+        self.last_head = head;
+        Ok(true)
+    }
+
+    async fn build(
+        repo_path: &Path,
+        has_commits: bool,
+        config: HeatmapConfig,
+    ) -> Result<VolatilityHeatmap, HeatmapError> {
+        if has_commits {
+            return VolatilityHeatmap::from_git(repo_path, Some(config)).await;
+        }
+        // git log fails on a repository without commits
+        let core_modules = VolatilityHeatmap::identify_core_modules(repo_path).await?;
+        Ok(VolatilityHeatmap::from_changes(
+            Vec::new(),
+            core_modules,
+            config,
+            Utc::now(),
+        ))
+    }
+}
+
 /// Heatmap errors
 #[derive(Debug, thiserror::Error)]
 pub enum HeatmapError {
@@ -439,6 +626,7 @@ mod tests {
                 lookback_days: 7,
                 high_volatility_threshold: 5,
                 normalization_factor: 1.0,
+                half_life_days: None,
             }),
         )
         .await
@@ -456,6 +644,189 @@ mod tests {
         assert_eq!(volatility_to_risk_level(0.9), RiskLevel::Critical);
     }
 
+    fn change(path: &str, age: Duration, now: DateTime<Utc>) -> GitChange {
+        GitChange {
+            path: PathBuf::from(path),
+            commit_hash: "abc123".to_string(),
+            author: "Test".to_string(),
+            timestamp: now - age,
+            change_type: ChangeType::Modified,
+        }
+    }
+
+    fn module(path: &str) -> ModuleId {
+        VolatilityHeatmap::identify_module(&PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_recent_changes_score_higher_with_decay() {
+        let now = Utc::now();
+        // Three week-old changes in `old`, two fresh ones in `fresh`
+        let changes = vec![
+            change("old/a.rs", Duration::days(6), now),
+            change("old/b.rs", Duration::days(6), now),
+            change("old/c.rs", Duration::days(7), now),
+            change("fresh/a.rs", Duration::hours(1), now),
+            change("fresh/b.rs", Duration::hours(2), now),
+        ];
+        let config = HeatmapConfig {
+            half_life_days: Some(1.0),
+            high_volatility_threshold: 1,
+            ..HeatmapConfig::default()
+        };
+
+        let heatmap = VolatilityHeatmap::from_changes(changes.clone(), vec![], config, now);
+        let fresh = heatmap.get_module_volatility(&module("fresh/a.rs"));
+        let old = heatmap.get_module_volatility(&module("old/a.rs"));
+        assert_eq!(old.raw_count, 3);
+        assert!(old.decayed_count < 0.1);
+        assert!(fresh.decayed_count > 1.9);
+        assert!(fresh.score > old.score);
+        assert_eq!(heatmap.get_modules_sorted()[0].module, fresh.module);
+
+        let high: Vec<ModuleId> = heatmap
+            .get_high_volatility_modules()
+            .into_iter()
+            .map(|m| m.module)
+            .collect();
+        assert_eq!(high, vec![fresh.module.clone()]);
+        assert!(heatmap.is_high_risk(Path::new("fresh/c.rs")));
+        assert!(!heatmap.is_high_risk(Path::new("old/d.rs")));
+
+        // Without decay the older, busier module wins again
+        for half_life in [None, Some(0.0)] {
+            let config = HeatmapConfig {
+                half_life_days: half_life,
+                ..HeatmapConfig::default()
+            };
+            let plain = VolatilityHeatmap::from_changes(changes.clone(), vec![], config, now);
+            let old = plain.get_module_volatility(&module("old/a.rs"));
+            let fresh = plain.get_module_volatility(&module("fresh/a.rs"));
+            assert_eq!(old.score, 1.0);
+            assert_eq!(old.decayed_count, 3.0);
+            assert!((fresh.score - 2.0 / 3.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_deserialized_config_defaults_match_default() {
+        let config: HeatmapConfig = serde_json::from_value(serde_json::json!({
+            "lookback_days": 7,
+            "high_volatility_threshold": 5,
+            "normalization_factor": 1.0
+        }))
+        .unwrap();
+        assert_eq!(
+            config.half_life_days,
+            HeatmapConfig::default().half_life_days
+        );
+
+        // An explicit null still turns decay off
+        let config: HeatmapConfig = serde_json::from_value(serde_json::json!({
+            "lookback_days": 7,
+            "high_volatility_threshold": 5,
+            "normalization_factor": 1.0,
+            "half_life_days": null
+        }))
+        .unwrap();
+        assert_eq!(config.half_life_days, None);
+    }
+
+    #[test]
+    fn test_decay_weight_halves_per_half_life() {
+        let config = HeatmapConfig {
+            half_life_days: Some(2.0),
+            ..HeatmapConfig::default()
+        };
+        assert_eq!(config.decay_weight(Duration::zero()), 1.0);
+        assert!((config.decay_weight(Duration::days(2)) - 0.5).abs() < 1e-9);
+        assert!((config.decay_weight(Duration::days(4)) - 0.25).abs() < 1e-9);
+        // Clock skew: future commits count fully
+        assert_eq!(config.decay_weight(Duration::hours(-3)), 1.0);
+    }
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .current_dir(repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    fn commit_files(repo: &Path, files: &[&str], message: &str) {
+        for file in files {
+            let path = repo.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, message).unwrap();
+        }
+        git(repo, &["add", "-A"]);
+        git(repo, &["commit", "-q", "-m", message]);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_folds_in_each_new_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        git(repo, &["init", "-q"]);
+        commit_files(repo, &["api/lib.rs"], "initial");
+
+        let mut watcher = HeatmapWatcher::new(repo, None).await.unwrap();
+        let api = module("api/lib.rs");
+        let storage = module("storage/db.rs");
+        assert_eq!(watcher.heatmap().get_module_volatility(&api).raw_count, 1);
+        assert!(!watcher.refresh().await.unwrap());
+
+        commit_files(repo, &["storage/db.rs", "api/routes.rs"], "first");
+        assert!(watcher.refresh().await.unwrap());
+        assert_eq!(watcher.heatmap().get_module_volatility(&api).raw_count, 2);
+        assert_eq!(
+            watcher.heatmap().get_module_volatility(&storage).raw_count,
+            1
+        );
+
+        commit_files(repo, &["storage/db.rs", "storage/pool.rs"], "second");
+        assert!(watcher.refresh().await.unwrap());
+        let heatmap = watcher.heatmap();
+        assert_eq!(heatmap.get_module_volatility(&api).raw_count, 2);
+        assert_eq!(heatmap.get_module_volatility(&storage).raw_count, 3);
+        assert_eq!(heatmap.get_modules_sorted()[0].module, storage);
+        assert_eq!(heatmap.recent_changes.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_rebuilds_after_history_rewrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        git(repo, &["init", "-q"]);
+
+        let mut watcher = HeatmapWatcher::new(repo, None).await.unwrap();
+        assert!(watcher.last_head().is_none());
+        assert!(watcher.heatmap().get_modules_sorted().is_empty());
+
+        commit_files(repo, &["api/lib.rs"], "initial");
+        commit_files(repo, &["api/routes.rs"], "routes");
+        assert!(watcher.refresh().await.unwrap());
+        assert_eq!(
+            watcher
+                .heatmap()
+                .get_module_volatility(&module("api/lib.rs"))
+                .raw_count,
+            2
+        );
+
+        git(repo, &["reset", "-q", "--hard", "HEAD~1"]);
+        assert!(watcher.refresh().await.unwrap());
+        assert_eq!(
+            watcher
+                .heatmap()
+                .get_module_volatility(&module("api/lib.rs"))
+                .raw_count,
+            1
+        );
+    }
+
     #[test]
     fn test_module_id_from_path() {
         let module = ModuleId::from_path(&PathBuf::from("crates/core/src/lib.rs"));
@@ -3,20 +3,23 @@
 //! Provides read-only impact analysis before execution.
 //! Generates ImpactReport, Volatility Heatmap, and Hard Constraints.
 
+pub mod commit_gate;
 pub mod hard_constraints;
 pub mod heatmap;
 pub mod impact_report;
 
 pub use heatmap::{
-    ChangeType, GitChange, HeatmapConfig, HeatmapError, ModuleId, ModuleVolatility,
+    ChangeType, GitChange, HeatmapConfig, HeatmapError, HeatmapWatcher, ModuleId, ModuleVolatility,
     VolatilityHeatmap, volatility_to_risk_level,
 };
 
+pub use commit_gate::{CommitGate, CommitGateValidator};
+
 pub use hard_constraints::{
-    ApiKind, ApiSymbol, ComponentKind, ComponentRef, CouplingType, CouplingWarning,
-    FailedConstraint, FileValidation, FileValidationType, HardConstraints, HardConstraintsId,
-    HardConstraintsSummary, HighVolatilityModule, RegressionTest, Severity, TestType,
-    VersionDimension, VersionOperator, VersionedConstraint,
+    ApiKind, ApiSymbol, ComponentKind, ComponentRef, ConstraintReport, CouplingType,
+    CouplingWarning, FailedConstraint, FileValidation, FileValidationType, HardConstraints,
+    HardConstraintsId, HardConstraintsSummary, HighVolatilityModule, RegressionTest, Severity,
+    TestType, VersionDimension, VersionOperator, VersionedConstraint, WorkingState,
 };
 
 pub use impact_report::{
@@ -71,6 +74,56 @@ impl Default for DiscoveryConfig {
     }
 }
 
+/// Discovery phase, reported through a progress callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryPhase {
+    ScanningGitHistory,
+    BuildingHeatmap,
+    AnalyzingCoupling,
+    GeneratingConstraints,
+    Completed,
+}
+
+impl DiscoveryPhase {
+    /// Human readable phase label
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiscoveryPhase::ScanningGitHistory => "scanning git history",
+            DiscoveryPhase::BuildingHeatmap => "building heatmap",
+            DiscoveryPhase::AnalyzingCoupling => "analyzing coupling",
+            DiscoveryPhase::GeneratingConstraints => "generating constraints",
+            DiscoveryPhase::Completed => "completed",
+        }
+    }
+
+    /// Rough overall progress when the phase starts
+    pub fn percent(&self) -> u8 {
+        match self {
+            DiscoveryPhase::ScanningGitHistory => 0,
+            DiscoveryPhase::BuildingHeatmap => 40,
+            DiscoveryPhase::AnalyzingCoupling => 60,
+            DiscoveryPhase::GeneratingConstraints => 80,
+            DiscoveryPhase::Completed => 100,
+        }
+    }
+}
+
+/// Progress update emitted while discovery runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscoveryProgress {
+    pub phase: DiscoveryPhase,
+    pub percent: u8,
+}
+
+impl From<DiscoveryPhase> for DiscoveryProgress {
+    fn from(phase: DiscoveryPhase) -> Self {
+        Self {
+            phase,
+            percent: phase.percent(),
+        }
+    }
+}
+
 impl DiscoveryService {
     /// Create new discovery service
     pub fn new(repo_path: PathBuf, config: Option<DiscoveryConfig>) -> Self {
@@ -86,6 +139,21 @@ impl DiscoveryService {
         task_id: String,
         task_description: String,
         affected_files: Vec<PathBuf>,
+    ) -> Result<DiscoveryResult, DiscoveryError> {
+        self.discover_with_progress(task_id, task_description, affected_files, |_| {})
+            .await
+    }
+
+    /// Run discovery phase, reporting each phase to `on_progress`
+    ///
+    /// Phases are reported in order even when disabled by config; the last
+    /// update is always `DiscoveryPhase::Completed` on success.
+    pub async fn discover_with_progress(
+        &self,
+        task_id: String,
+        task_description: String,
+        affected_files: Vec<PathBuf>,
+        on_progress: impl Fn(DiscoveryProgress),
     ) -> Result<DiscoveryResult, DiscoveryError> {
         let mut report = ImpactReport::new(task_id.clone(), task_description);
 
@@ -96,11 +164,15 @@ impl DiscoveryService {
 
         // Generate heatmap if enabled
         let heatmap = if self.config.enable_heatmap {
-            Some(self.generate_heatmap().await?)
+            Some(self.generate_heatmap(&on_progress).await?)
         } else {
+            on_progress(DiscoveryPhase::ScanningGitHistory.into());
+            on_progress(DiscoveryPhase::BuildingHeatmap.into());
             None
         };
 
+        on_progress(DiscoveryPhase::AnalyzingCoupling.into());
+
         // Update report with heatmap data
         if let Some(ref heatmap) = heatmap {
             // Calculate volatility score
@@ -119,6 +191,7 @@ impl DiscoveryService {
         report.calculate_complexity();
 
         // Generate hard constraints if high risk
+        on_progress(DiscoveryPhase::GeneratingConstraints.into());
         let hard_constraints = if self.should_generate_constraints(&report) {
             Some(
                 self.generate_hard_constraints(&report, heatmap.as_ref())
@@ -133,6 +206,7 @@ impl DiscoveryService {
             report.generated_constraints = Some(serde_json::to_string(constraints).unwrap());
         }
 
+        on_progress(DiscoveryPhase::Completed.into());
         Ok(DiscoveryResult {
             impact_report: report,
             heatmap,
@@ -141,16 +215,28 @@ impl DiscoveryService {
     }
 
     /// Generate volatility heatmap
-    async fn generate_heatmap(&self) -> Result<VolatilityHeatmap, DiscoveryError> {
+    async fn generate_heatmap(
+        &self,
+        on_progress: &impl Fn(DiscoveryProgress),
+    ) -> Result<VolatilityHeatmap, DiscoveryError> {
         let config = HeatmapConfig {
             lookback_days: self.config.heatmap_lookback_days,
             high_volatility_threshold: self.config.high_volatility_threshold,
-            normalization_factor: 1.0,
+            ..HeatmapConfig::default()
         };
 
-        VolatilityHeatmap::from_git(&self.repo_path, Some(config))
-            .await
-            .map_err(DiscoveryError::from)
+        on_progress(DiscoveryPhase::ScanningGitHistory.into());
+        let since = chrono::Utc::now() - chrono::Duration::days(config.lookback_days as i64);
+        let changes = VolatilityHeatmap::get_git_changes(&self.repo_path, since).await?;
+
+        on_progress(DiscoveryPhase::BuildingHeatmap.into());
+        let core_modules = VolatilityHeatmap::identify_core_modules(&self.repo_path).await?;
+        Ok(VolatilityHeatmap::from_changes(
+            changes,
+            core_modules,
+            config,
+            chrono::Utc::now(),
+        ))
     }
 
     /// Check if should generate hard constraints
@@ -309,6 +395,60 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_discover_reports_progress_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_path_buf();
+        for args in [
+            vec!["init", "-q"],
+            vec![
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(&args)
+                .current_dir(&repo_path)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let service = DiscoveryService::new(repo_path, None);
+        let updates = std::sync::Mutex::new(Vec::new());
+        service
+            .discover_with_progress(
+                "task-1".to_string(),
+                "Progress".to_string(),
+                vec![PathBuf::from("src/lib.rs")],
+                |progress| updates.lock().unwrap().push(progress),
+            )
+            .await
+            .unwrap();
+
+        let updates = updates.into_inner().unwrap();
+        let phases: Vec<_> = updates.iter().map(|p| p.phase).collect();
+        assert_eq!(
+            phases,
+            vec![
+                DiscoveryPhase::ScanningGitHistory,
+                DiscoveryPhase::BuildingHeatmap,
+                DiscoveryPhase::AnalyzingCoupling,
+                DiscoveryPhase::GeneratingConstraints,
+                DiscoveryPhase::Completed,
+            ]
+        );
+        assert!(updates.windows(2).all(|w| w[0].percent < w[1].percent));
+        assert_eq!(updates.last().unwrap().percent, 100);
+    }
+
     #[test]
     fn test_discovery_config_default() {
         let config = DiscoveryConfig::default();
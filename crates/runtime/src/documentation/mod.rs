@@ -6,6 +6,9 @@
 //! - Update code documentation
 //! - Maintain changelog
 
+mod rust_docstring;
+
+use rust_docstring::{RustItem, update_rust_docstring};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
@@ -75,7 +78,8 @@ pub struct DocUpdateRequest {
     pub update_type: DocUpdateType,
     /// New content
     pub content: String,
-    /// Context for the update
+    /// Context for the update; for Rust docstrings, the target item
+    /// (`fn name`, `struct Name`, `impl Name` or a bare name)
     pub context: String,
 }
 
@@ -254,16 +258,41 @@ impl DocUpdater {
         // Read the file
         match std::fs::read_to_string(&request.file_path) {
             Ok(mut content) => {
-                let original_len = content.len();
+                let original = content.clone();
 
                 // Apply update based on type
                 match request.update_type {
                     DocUpdateType::Docstring => {
-                        if let Some(updated) = self.update_docstring(&content, &request.content) {
-                            content = updated;
-                            changes.push("Updated docstring".to_string());
-                        } else {
-                            warnings.push("No suitable location for docstring found".to_string());
+                        let rust_target = request
+                            .file_path
+                            .extension()
+                            .is_some_and(|ext| ext == "rs")
+                            .then(|| RustItem::parse(&request.context))
+                            .flatten();
+                        let updated = match rust_target {
+                            Some(ref item) => update_rust_docstring(
+                                &content,
+                                item,
+                                &request.content,
+                            )
+                            .map_err(|e| {
+                                format!("Cannot match Rust item `{}`: {}", request.context, e)
+                            }),
+                            None => Ok(self.update_docstring(&content, &request.content)),
+                        };
+                        match updated {
+                            Ok(Some(updated)) => {
+                                content = updated;
+                                changes.push("Updated docstring".to_string());
+                            }
+                            Ok(None) if rust_target.is_some() => {
+                                warnings.push(format!("Rust item `{}` not found", request.context));
+                            }
+                            Ok(None) => {
+                                warnings
+                                    .push("No suitable location for docstring found".to_string());
+                            }
+                            Err(e) => warnings.push(e),
                         }
                     }
                     DocUpdateType::Comment => {
@@ -297,7 +326,7 @@ impl DocUpdater {
                 }
 
                 // Write back if changed
-                if content.len() != original_len
+                if content != original
                     && let Err(e) = std::fs::write(&request.file_path, &content)
                 {
                     return DocUpdateResult {
@@ -553,4 +582,40 @@ mod tests {
         // Cleanup
         std::fs::remove_file(temp_file).ok();
     }
+
+    #[tokio::test]
+    async fn test_apply_rust_docstring_targets_named_item() {
+        let updater = DocUpdater::new(None);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("lib.rs");
+        std::fs::write(
+            &path,
+            "/// Keep me\nfn first() {}\n\n/// Old\nfn second() {}\n",
+        )
+        .unwrap();
+
+        let request = updater.create_doc_update(
+            path.clone(),
+            DocUpdateType::Docstring,
+            "New".to_string(),
+            "fn second".to_string(),
+        );
+        let result = updater.apply_update(&request).await;
+
+        assert!(result.success);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "/// Keep me\nfn first() {}\n\n/// New\nfn second() {}\n"
+        );
+
+        let missing = updater.create_doc_update(
+            path,
+            DocUpdateType::Docstring,
+            "New".to_string(),
+            "fn third".to_string(),
+        );
+        let result = updater.apply_update(&missing).await;
+        assert!(!result.success);
+        assert!(result.warnings[0].contains("`fn third` not found"));
+    }
 }
@@ -0,0 +1,258 @@
+//! Rust-aware docstring insertion
+//!
+//! Targets a named item (`fn`, `struct`, `enum`, `trait`, `impl`) and writes the
+//! docstring as `///` lines directly above it, above any attributes. An existing
+//! `///` block in that position is replaced rather than duplicated.
+
+use regex::Regex;
+
+const ITEM_KINDS: [&str; 5] = ["fn", "struct", "enum", "trait", "impl"];
+
+/// Item named by a `DocUpdateRequest.context` such as `fn parse` or `Config`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RustItem {
+    /// `None` matches any item kind
+    kind: Option<&'static str>,
+    name: String,
+}
+
+impl RustItem {
+    /// Parse `"<kind> <name>"` or a bare identifier; anything else is not a target
+    pub(super) fn parse(context: &str) -> Option<Self> {
+        let mut words = context.split_whitespace();
+        let (kind, name) = match (words.next(), words.next(), words.next()) {
+            (Some(name), None, None) => (None, name),
+            (Some(kind), Some(name), None) => {
+                (Some(*ITEM_KINDS.iter().find(|k| **k == kind)?), name)
+            }
+            _ => return None,
+        };
+        let is_ident = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        is_ident.then(|| Self {
+            kind,
+            name: name.to_string(),
+        })
+    }
+
+    /// Line regex for the item's declaration
+    fn pattern(&self) -> Result<Regex, regex::Error> {
+        let name = regex::escape(&self.name);
+        let kinds = self
+            .kind
+            .map(|k| vec![k])
+            .unwrap_or_else(|| ITEM_KINDS.to_vec());
+        let alternatives: Vec<String> = kinds
+            .into_iter()
+            .map(|kind| match kind {
+                "impl" => format!(r"(unsafe\s+)?impl(<[^>]*>)?\s+([^{{]*\s+for\s+)?{}\b", name),
+                "fn" => format!(
+                    r#"((const|async|unsafe|extern\s+"[^"]*")\s+)*fn\s+{}\b"#,
+                    name
+                ),
+                other => format!(r"{}\s+{}\b", other, name),
+            })
+            .collect();
+        Regex::new(&format!(
+            r"^\s*(pub(\([^)]*\))?\s+)?({})",
+            alternatives.join("|")
+        ))
+    }
+}
+
+/// Write `docstring` above `item`; `Ok(None)` if the item is not in `content`
+pub(super) fn update_rust_docstring(
+    content: &str,
+    item: &RustItem,
+    docstring: &str,
+) -> Result<Option<String>, regex::Error> {
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let pattern = item.pattern()?;
+    let Some(item_idx) = lines.iter().position(|line| pattern.is_match(line)) else {
+        return Ok(None);
+    };
+    let indent: String = lines[item_idx]
+        .chars()
+        .take_while(|c| c.is_whitespace() && *c != '\n')
+        .collect();
+
+    // Attributes and doc lines directly above the item belong to it
+    let mut top = item_idx;
+    while top > 0 {
+        let above = lines[top - 1].trim_start();
+        if above.starts_with("#[") || is_doc_line(above) {
+            top -= 1;
+        } else {
+            break;
+        }
+    }
+
+    let insert_at = (top..item_idx)
+        .find(|&i| is_doc_line(lines[i].trim_start()))
+        .unwrap_or(top);
+    let mut idx = item_idx;
+    while idx > top {
+        idx -= 1;
+        if is_doc_line(lines[idx].trim_start()) {
+            lines.remove(idx);
+        }
+    }
+
+    let doc: Vec<String> = docstring
+        .lines()
+        .map(|line| {
+            let text = line.trim().trim_start_matches("///").trim_start();
+            if text.is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, text)
+            }
+        })
+        .collect();
+
+    let mut updated = String::with_capacity(content.len() + docstring.len());
+    for line in &lines[..insert_at] {
+        updated.push_str(line);
+    }
+    for line in &doc {
+        updated.push_str(line);
+    }
+    for line in &lines[insert_at..] {
+        updated.push_str(line);
+    }
+    Ok(Some(updated))
+}
+
+fn is_doc_line(trimmed: &str) -> bool {
+    trimmed.starts_with("///") && !trimmed.starts_with("////")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(content: &str, context: &str, doc: &str) -> Option<String> {
+        update_rust_docstring(content, &RustItem::parse(context).unwrap(), doc).unwrap()
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            RustItem::parse("fn total"),
+            Some(RustItem {
+                kind: Some("fn"),
+                name: "total".to_string()
+            })
+        );
+        assert_eq!(RustItem::parse("Config").unwrap().kind, None);
+        assert_eq!(RustItem::parse("Function to update"), None);
+        assert_eq!(RustItem::parse("macro total"), None);
+    }
+
+    #[test]
+    fn test_pattern_compiles_for_every_kind() {
+        for context in ["fn total", "struct total", "enum total", "trait total"] {
+            assert!(RustItem::parse(context).unwrap().pattern().is_ok());
+        }
+        assert!(RustItem::parse("impl total").unwrap().pattern().is_ok());
+        assert!(RustItem::parse("_total2").unwrap().pattern().is_ok());
+    }
+
+    #[test]
+    fn test_replaces_existing_doc_block() {
+        let content = "\
+/// Helper
+fn helper() {}
+
+/// Old line one
+/// Old line two
+pub fn total(items: &[u32]) -> u32 {
+    items.iter().sum()
+}
+";
+        let updated = update(content, "fn total", "Sum all items.").unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+/// Helper
+fn helper() {}
+
+/// Sum all items.
+pub fn total(items: &[u32]) -> u32 {
+    items.iter().sum()
+}
+"
+        );
+        assert_eq!(
+            update(&updated, "fn total", "Sum all items.").unwrap(),
+            updated
+        );
+    }
+
+    #[test]
+    fn test_attributes_between_doc_and_fn() {
+        let content = "\
+impl Cart {
+    /// Old
+    #[inline]
+    #[must_use]
+    pub async fn total(&self) -> u32 {
+        0
+    }
+}
+";
+        let updated = update(content, "total", "Sum all items.\n\nIgnores discounts.").unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+impl Cart {
+    /// Sum all items.
+    ///
+    /// Ignores discounts.
+    #[inline]
+    #[must_use]
+    pub async fn total(&self) -> u32 {
+        0
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_struct_target_inserts_above_derive() {
+        let content = "\
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub(crate) struct Config {
+    root: PathBuf,
+}
+
+fn Config_helper() {}
+";
+        let updated = update(content, "struct Config", "Project settings").unwrap();
+
+        assert!(
+            updated
+                .contains("\n/// Project settings\n#[derive(Debug)]\npub(crate) struct Config {")
+        );
+        assert!(updated.ends_with("fn Config_helper() {}\n"));
+        assert_eq!(updated.matches("///").count(), 1);
+    }
+
+    #[test]
+    fn test_impl_target_and_missing_item() {
+        let content = "struct Cart;\n\nimpl<T> From<T> for Cart {\n}\n";
+        let updated = update(content, "impl Cart", "Conversion").unwrap();
+        assert!(updated.contains("/// Conversion\nimpl<T> From<T> for Cart {"));
+        assert!(!updated.contains("/// Conversion\nstruct Cart;"));
+
+        assert_eq!(update(content, "fn missing", "x"), None);
+    }
+}
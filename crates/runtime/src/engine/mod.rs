@@ -4,10 +4,13 @@
 //! - State transitions
 //! - Event listeners/hooks
 //! - Error handling and recovery
+//! - Deferred intent re-evaluation
 
+use ndc_core::SharedClock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
 /// Unique event ID
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -62,6 +65,8 @@ pub enum EventType {
     QualityGatePassed,
     /// Quality gate failed
     QualityGateFailed,
+    /// Decision engine modified an action before execution
+    VerdictModified,
     /// Custom event
     Custom { name: String },
 }
@@ -111,10 +116,41 @@ pub enum EventData {
     Invariant { rule: String, priority: String },
     /// Quality gate data
     QualityGate { gate_name: String, passed: bool },
+    /// Modified verdict data
+    Verdict {
+        reason: String,
+        warnings: Vec<String>,
+    },
     /// Custom data
     Custom { key: String, value: String },
 }
 
+impl Event {
+    /// Warnings carried by the event, as execution events the TUI renders as warning notes
+    pub fn warning_events(&self) -> Vec<ndc_core::AgentExecutionEvent> {
+        let EventData::Verdict { warnings, .. } = &self.data else {
+            return Vec::new();
+        };
+        warnings
+            .iter()
+            .map(|warning| ndc_core::AgentExecutionEvent {
+                kind: ndc_core::AgentExecutionEventKind::Warning,
+                timestamp: self.timestamp,
+                message: warning.clone(),
+                round: 0,
+                tool_name: None,
+                tool_call_id: None,
+                duration_ms: None,
+                is_error: false,
+                workflow_stage: None,
+                workflow_detail: None,
+                workflow_stage_index: None,
+                workflow_stage_total: None,
+            })
+            .collect()
+    }
+}
+
 /// Event handler callback
 pub type EventHandler = Box<dyn Fn(&Event) + Send + Sync>;
 
@@ -148,10 +184,11 @@ impl EventListener {
     }
 }
 
-/// Event emitter for publishing events
+/// Event emitter for publishing events; listeners can be registered through a
+/// shared reference, but not from inside a handler
 pub struct EventEmitter {
     /// Registered listeners
-    listeners: Vec<EventListener>,
+    listeners: std::sync::RwLock<Vec<EventListener>>,
 }
 
 impl Default for EventEmitter {
@@ -164,22 +201,48 @@ impl EventEmitter {
     /// Create new emitter
     pub fn new() -> Self {
         Self {
-            listeners: Vec::new(),
+            listeners: std::sync::RwLock::new(Vec::new()),
         }
     }
 
+    fn listeners(&self) -> std::sync::RwLockReadGuard<'_, Vec<EventListener>> {
+        self.listeners.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn listeners_mut(&self) -> std::sync::RwLockWriteGuard<'_, Vec<EventListener>> {
+        self.listeners.write().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Register an event listener
-    pub fn on<F>(&mut self, id: String, event_types: Vec<EventType>, handler: F)
+    pub fn on<F>(&self, id: String, event_types: Vec<EventType>, handler: F)
     where
         F: Fn(&Event) + Send + Sync + 'static,
     {
         let listener = EventListener::new(id, event_types, handler);
-        self.listeners.push(listener);
+        self.listeners_mut().push(listener);
+    }
+
+    /// Remove listeners registered under `id`; returns whether any were removed
+    pub fn off(&self, id: &str) -> bool {
+        let mut listeners = self.listeners_mut();
+        let before = listeners.len();
+        listeners.retain(|listener| listener.id != id);
+        listeners.len() != before
+    }
+
+    /// Enable or disable listeners registered under `id`; returns whether any matched
+    pub fn set_enabled(&self, id: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for listener in self.listeners_mut().iter_mut().filter(|l| l.id == id) {
+            listener.enabled = enabled;
+            found = true;
+        }
+        found
     }
 
     /// Emit an event
     pub fn emit(&self, event: &Event) {
-        for listener in &self.listeners {
+        for listener in self.listeners().iter() {
             if !listener.enabled {
                 continue;
             }
@@ -191,7 +254,7 @@ impl EventEmitter {
 
     /// Get number of listeners
     pub fn listener_count(&self) -> usize {
-        self.listeners.len()
+        self.listeners().len()
     }
 }
 
@@ -216,6 +279,8 @@ pub enum WorkflowState {
     Failed,
     /// Blocked,
     Blocked,
+    /// Cancelled (terminal)
+    Cancelled,
 }
 
 impl fmt::Display for WorkflowState {
@@ -230,6 +295,7 @@ impl fmt::Display for WorkflowState {
             WorkflowState::Completed => write!(f, "Completed"),
             WorkflowState::Failed => write!(f, "Failed"),
             WorkflowState::Blocked => write!(f, "Blocked"),
+            WorkflowState::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -250,6 +316,11 @@ const STATE_TRANSITIONS: &[(WorkflowState, WorkflowState)] = &[
     (WorkflowState::Completing, WorkflowState::Completed),
     (WorkflowState::Failed, WorkflowState::Executing),
     (WorkflowState::Blocked, WorkflowState::Executing),
+    (WorkflowState::Planning, WorkflowState::Cancelled),
+    (WorkflowState::Discovery, WorkflowState::Cancelled),
+    (WorkflowState::Executing, WorkflowState::Cancelled),
+    (WorkflowState::Verifying, WorkflowState::Cancelled),
+    (WorkflowState::Blocked, WorkflowState::Cancelled),
 ];
 
 /// Workflow instance
@@ -333,6 +404,80 @@ impl Workflow {
         self.retry_count += 1;
         self.updated_at = chrono::Utc::now();
     }
+
+    /// Retry a failed or blocked workflow
+    ///
+    /// Moves back to `Executing`, counts the attempt and clears the previous
+    /// error; refused once `max_retries` is reached.
+    pub fn retry(&mut self) -> Result<(), TransitionError> {
+        if !matches!(self.state, WorkflowState::Failed | WorkflowState::Blocked) {
+            return Err(TransitionError::Invalid {
+                from: self.state,
+                to: WorkflowState::Executing,
+            });
+        }
+        if !self.can_retry() {
+            return Err(TransitionError::RetriesExhausted {
+                max_retries: self.max_retries,
+            });
+        }
+
+        self.transition(WorkflowState::Executing)?;
+        self.increment_retry();
+        self.error = None;
+
+        Ok(())
+    }
+
+    /// Mark as blocked; refused from states that cannot move to `Blocked`
+    pub fn mark_blocked(&mut self, reason: String) -> Result<(), TransitionError> {
+        self.transition(WorkflowState::Blocked)?;
+        self.error = Some(reason);
+        Ok(())
+    }
+}
+
+/// Default number of times an intent may be deferred before its workflow blocks
+pub const DEFAULT_MAX_DEFER_ATTEMPTS: u32 = 3;
+
+/// Longest delay a deferral waits; larger `retry_after` values are clamped
+pub const MAX_DEFER_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// An intent waiting to be re-evaluated after a `Defer` verdict
+#[derive(Debug, Clone)]
+pub struct DeferredIntent {
+    /// Workflow the intent belongs to
+    pub workflow_id: String,
+
+    /// The deferred intent
+    pub intent: ndc_core::Intent,
+
+    /// How many times the intent has been deferred
+    pub attempts: u32,
+
+    /// When the intent becomes due for re-evaluation
+    pub due_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl DeferredIntent {
+    /// Wrap an intent that has not been deferred yet
+    pub fn new(workflow_id: String, intent: ndc_core::Intent) -> Self {
+        Self {
+            workflow_id,
+            intent,
+            attempts: 0,
+            due_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// Outcome of scheduling a deferral
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeferOutcome {
+    /// Re-evaluation scheduled
+    Scheduled { attempt: u32 },
+    /// Attempts exhausted, workflow moved to `Blocked`
+    Blocked { attempts: u32 },
 }
 
 /// Transition errors
@@ -343,30 +488,64 @@ pub enum TransitionError {
         from: WorkflowState,
         to: WorkflowState,
     },
+    #[error("Retries exhausted after {max_retries} attempts")]
+    RetriesExhausted { max_retries: u32 },
 }
 
 /// Event-Driven Engine
 pub struct EventEngine {
     /// Event emitter
-    emitter: EventEmitter,
+    emitter: Arc<EventEmitter>,
 
     /// Active workflows
     workflows: HashMap<String, Workflow>,
+
+    /// Intents waiting for re-evaluation
+    deferrals: Vec<DeferredIntent>,
+
+    /// Deferrals allowed per intent before the workflow blocks
+    max_defer_attempts: u32,
+
+    /// Time source for workflow timestamps and deferral deadlines
+    clock: SharedClock,
 }
 
 impl EventEngine {
     /// Create new engine
     pub fn new() -> Self {
         Self {
-            emitter: EventEmitter::new(),
+            emitter: Arc::new(EventEmitter::new()),
             workflows: HashMap::new(),
+            deferrals: Vec::new(),
+            max_defer_attempts: DEFAULT_MAX_DEFER_ATTEMPTS,
+            clock: ndc_core::system_clock(),
         }
     }
 
+    /// Use `clock` instead of the system time
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Publish events through `emitter`, shared with other publishers
+    pub fn with_emitter(mut self, emitter: Arc<EventEmitter>) -> Self {
+        self.emitter = emitter;
+        self
+    }
+
+    /// Set the number of deferrals allowed per intent
+    pub fn with_max_defer_attempts(mut self, max_defer_attempts: u32) -> Self {
+        self.max_defer_attempts = max_defer_attempts;
+        self
+    }
+
     /// Create workflow
     pub fn create_workflow(&mut self, workflow_id: String) -> &mut Workflow {
         let id_clone = workflow_id.clone();
-        let workflow = Workflow::new(workflow_id);
+        let mut workflow = Workflow::new(workflow_id);
+        workflow.created_at = self.clock.now();
+        workflow.updated_at = workflow.created_at;
         self.workflows.insert(id_clone.clone(), workflow);
         self.workflows.get_mut(&id_clone).unwrap()
     }
@@ -404,6 +583,45 @@ impl EventEngine {
         Ok(())
     }
 
+    /// Retry a failed or blocked workflow, see [`Workflow::retry`]
+    pub fn retry_workflow(&mut self, workflow_id: &str) -> Result<(), TransitionError> {
+        let workflow = self
+            .workflows
+            .get_mut(workflow_id)
+            .ok_or(TransitionError::Invalid {
+                from: WorkflowState::Initial,
+                to: WorkflowState::Executing,
+            })?;
+
+        let from = workflow.state;
+        workflow.retry()?;
+
+        self.emit_state_change(workflow_id, from, WorkflowState::Executing);
+
+        Ok(())
+    }
+
+    /// Cancel an in-flight workflow
+    ///
+    /// `Cancelled` is terminal; emits a `workflow_cancelled` event on success.
+    pub fn cancel_workflow(&mut self, workflow_id: &str) -> Result<(), TransitionError> {
+        self.transition_workflow(workflow_id, WorkflowState::Cancelled)?;
+
+        self.emitter.emit(&Event {
+            id: EventId::default(),
+            event_type: EventType::Custom {
+                name: "workflow_cancelled".to_string(),
+            },
+            data: EventData::Empty,
+            task_id: Some(workflow_id.to_string()),
+            step_id: None,
+            timestamp: self.clock.now(),
+            metadata: HashMap::new(),
+        });
+
+        Ok(())
+    }
+
     /// Emit state change event
     fn emit_state_change(&self, workflow_id: &str, from: WorkflowState, to: WorkflowState) {
         let event = Event {
@@ -415,7 +633,7 @@ impl EventEngine {
             },
             task_id: Some(workflow_id.to_string()),
             step_id: None,
-            timestamp: chrono::Utc::now(),
+            timestamp: self.clock.now(),
             metadata: HashMap::new(),
         };
 
@@ -435,6 +653,97 @@ impl EventEngine {
         self.emitter.on(id, event_types, handler);
     }
 
+    /// Remove event handler
+    pub fn off(&mut self, id: &str) -> bool {
+        self.emitter.off(id)
+    }
+
+    /// Enable or disable event handler
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        self.emitter.set_enabled(id, enabled)
+    }
+
+    /// Schedule a deferred intent for re-evaluation after `retry_after_secs`
+    ///
+    /// Pass the `DeferredIntent` returned by [`Self::poll_deferrals`] when the
+    /// same intent is deferred again so its attempt counter carries over.
+    /// Once the counter exceeds `max_defer_attempts` the workflow is blocked
+    /// instead.
+    pub fn schedule_deferral(
+        &mut self,
+        mut deferred: DeferredIntent,
+        retry_after_secs: u64,
+    ) -> DeferOutcome {
+        deferred.attempts += 1;
+        let workflow_id = deferred.workflow_id.clone();
+
+        if deferred.attempts > self.max_defer_attempts {
+            let attempts = deferred.attempts - 1;
+            if let Some(workflow) = self.workflows.get_mut(&workflow_id) {
+                let from = workflow.state;
+                let reason = format!(
+                    "Intent {} deferred {} times",
+                    deferred.intent.id.0, attempts
+                );
+                match workflow.mark_blocked(reason) {
+                    Ok(()) => self.emit_state_change(&workflow_id, from, WorkflowState::Blocked),
+                    Err(e) => {
+                        tracing::warn!(workflow_id = %workflow_id, error = %e, "Cannot block workflow")
+                    }
+                }
+            }
+            return DeferOutcome::Blocked { attempts };
+        }
+
+        let retry_after_secs = retry_after_secs.min(MAX_DEFER_SECS);
+        let delay = i64::try_from(retry_after_secs)
+            .ok()
+            .and_then(chrono::Duration::try_seconds)
+            .unwrap_or_default();
+        deferred.due_at = self.clock.now() + delay;
+        let attempt = deferred.attempts;
+
+        let mut metadata = HashMap::new();
+        metadata.insert("attempt".to_string(), attempt.to_string());
+        metadata.insert("retry_after".to_string(), retry_after_secs.to_string());
+        self.emitter.emit(&Event {
+            id: EventId::default(),
+            event_type: EventType::Custom {
+                name: "intent_deferred".to_string(),
+            },
+            data: EventData::Custom {
+                key: "intent_id".to_string(),
+                value: deferred.intent.id.0.to_string(),
+            },
+            task_id: Some(workflow_id),
+            step_id: None,
+            timestamp: self.clock.now(),
+            metadata,
+        });
+
+        self.deferrals.push(deferred);
+        DeferOutcome::Scheduled { attempt }
+    }
+
+    /// Deferred intents still waiting for their retry time
+    pub fn pending_deferrals(&self) -> &[DeferredIntent] {
+        &self.deferrals
+    }
+
+    /// Take the deferrals due by the engine's clock, for re-evaluation by the caller
+    pub fn poll_deferrals(&mut self) -> Vec<DeferredIntent> {
+        self.poll_deferrals_at(self.clock.now())
+    }
+
+    /// Take the deferrals due at `now`
+    pub fn poll_deferrals_at(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<DeferredIntent> {
+        let (due, pending) = std::mem::take(&mut self.deferrals)
+            .into_iter()
+            .partition(|d| d.due_at <= now);
+        self.deferrals = pending;
+        due
+    }
+
     /// Get summary
     pub fn summary(&self) -> EventEngineSummary {
         let mut state_counts: HashMap<WorkflowState, usize> = HashMap::new();
@@ -445,6 +754,10 @@ impl EventEngine {
 
         EventEngineSummary {
             total_workflows: self.workflows.len(),
+            cancelled_workflows: state_counts
+                .get(&WorkflowState::Cancelled)
+                .copied()
+                .unwrap_or(0),
             workflows_by_state: state_counts,
             listener_count: self.emitter.listener_count(),
         }
@@ -461,6 +774,7 @@ impl Default for EventEngine {
 #[derive(Debug, Clone)]
 pub struct EventEngineSummary {
     pub total_workflows: usize,
+    pub cancelled_workflows: usize,
     pub workflows_by_state: HashMap<WorkflowState, usize>,
     pub listener_count: usize,
 }
@@ -468,7 +782,7 @@ pub struct EventEngineSummary {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::{Arc, Mutex};
+    use std::sync::Mutex;
 
     #[test]
     fn test_event_engine_new() {
@@ -556,6 +870,48 @@ mod tests {
         assert!(!workflow.can_retry());
     }
 
+    #[test]
+    fn test_retry_clears_error() {
+        let mut engine = EventEngine::new();
+        let workflow = engine.create_workflow("wf".to_string());
+        workflow.state = WorkflowState::Executing;
+        workflow.mark_failed("tests failed".to_string());
+
+        engine.retry_workflow("wf").unwrap();
+        let workflow = engine.get_workflow("wf").unwrap();
+        assert_eq!(workflow.state, WorkflowState::Executing);
+        assert_eq!(workflow.retry_count, 1);
+        assert!(workflow.error.is_none());
+
+        // Blocked workflows retry too; running ones cannot
+        let workflow = engine.get_workflow_mut("wf").unwrap();
+        workflow.mark_blocked("waiting".to_string()).unwrap();
+        workflow.retry().unwrap();
+        assert_eq!(workflow.retry_count, 2);
+        assert!(workflow.error.is_none());
+        assert!(matches!(
+            workflow.retry(),
+            Err(TransitionError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_retry_refused_when_exhausted() {
+        let mut workflow = workflow_in(WorkflowState::Executing);
+        workflow.max_retries = 1;
+        workflow.mark_failed("first".to_string());
+        workflow.retry().unwrap();
+        workflow.mark_failed("second".to_string());
+
+        assert!(matches!(
+            workflow.retry(),
+            Err(TransitionError::RetriesExhausted { max_retries: 1 })
+        ));
+        assert_eq!(workflow.state, WorkflowState::Failed);
+        assert_eq!(workflow.retry_count, 1);
+        assert_eq!(workflow.error, Some("second".to_string()));
+    }
+
     #[test]
     fn test_workflow_transition_error() {
         let mut workflow = Workflow::new("test".to_string());
@@ -565,7 +921,7 @@ mod tests {
 
     #[test]
     fn test_event_emitter() {
-        let mut emitter = EventEmitter::new();
+        let emitter = EventEmitter::new();
         let received: Arc<Mutex<Vec<EventType>>> = Arc::new(Mutex::new(Vec::new()));
         let received_clone = received.clone();
 
@@ -605,4 +961,288 @@ mod tests {
         assert_eq!(guard[0], EventType::TaskStarted);
         assert_eq!(guard[1], EventType::TaskCompleted);
     }
+
+    fn task_started() -> Event {
+        Event {
+            id: EventId::default(),
+            event_type: EventType::TaskStarted,
+            data: EventData::Empty,
+            task_id: None,
+            step_id: None,
+            timestamp: chrono::Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn recording_engine() -> (EventEngine, Arc<Mutex<Vec<&'static str>>>) {
+        let mut engine = EventEngine::new();
+        let fired: Arc<Mutex<Vec<&'static str>>> = Arc::new(Mutex::new(Vec::new()));
+        for id in ["first", "second"] {
+            let fired = fired.clone();
+            engine.on(id.to_string(), vec![EventType::TaskStarted], move |_| {
+                fired.lock().unwrap().push(id);
+            });
+        }
+        (engine, fired)
+    }
+
+    #[test]
+    fn test_off_removes_listener() {
+        let (mut engine, fired) = recording_engine();
+
+        assert!(engine.off("first"));
+        assert!(!engine.off("first"));
+        assert_eq!(engine.summary().listener_count, 1);
+
+        engine.emit(&task_started());
+        assert_eq!(*fired.lock().unwrap(), vec!["second"]);
+    }
+
+    #[test]
+    fn test_set_enabled_toggles_listener() {
+        let (mut engine, fired) = recording_engine();
+
+        assert!(engine.set_enabled("first", false));
+        engine.emit(&task_started());
+        assert_eq!(*fired.lock().unwrap(), vec!["second"]);
+
+        assert!(engine.set_enabled("first", true));
+        engine.emit(&task_started());
+        assert_eq!(*fired.lock().unwrap(), vec!["second", "first", "second"]);
+
+        assert!(!engine.set_enabled("missing", false));
+    }
+
+    fn test_intent() -> ndc_core::Intent {
+        ndc_core::Intent {
+            id: ndc_core::IntentId::new(),
+            agent: ndc_core::AgentId::new(),
+            agent_role: ndc_core::AgentRole::Implementer,
+            proposed_action: ndc_core::Action::ReadFile {
+                path: std::path::PathBuf::from("src/lib.rs"),
+                range: None,
+            },
+            effects: vec![],
+            reasoning: "Reading".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_deferral_fires_after_timeout() {
+        let mut engine = EventEngine::new();
+        engine.create_workflow("wf".to_string());
+        let deferred_events = Arc::new(Mutex::new(0));
+        let counter = deferred_events.clone();
+        engine.on(
+            "deferrals".to_string(),
+            vec![EventType::Custom {
+                name: "intent_deferred".to_string(),
+            }],
+            move |_| *counter.lock().unwrap() += 1,
+        );
+
+        let intent = test_intent();
+        let outcome =
+            engine.schedule_deferral(DeferredIntent::new("wf".to_string(), intent.clone()), 30);
+        assert_eq!(outcome, DeferOutcome::Scheduled { attempt: 1 });
+        assert_eq!(*deferred_events.lock().unwrap(), 1);
+
+        // Not due yet.
+        assert!(engine.poll_deferrals().is_empty());
+        assert_eq!(engine.pending_deferrals().len(), 1);
+
+        let due = engine.poll_deferrals_at(chrono::Utc::now() + chrono::Duration::seconds(31));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].intent.id, intent.id);
+        assert_eq!(due[0].attempts, 1);
+        assert!(engine.pending_deferrals().is_empty());
+    }
+
+    #[test]
+    fn test_deferral_follows_mock_clock() {
+        use ndc_core::Clock;
+
+        let clock = ndc_core::MockClock::default();
+        let mut engine = EventEngine::new().with_clock(Arc::new(clock.clone()));
+        let created = engine.create_workflow("wf".to_string()).created_at;
+        assert_eq!(created, clock.now());
+
+        engine.schedule_deferral(DeferredIntent::new("wf".to_string(), test_intent()), 30);
+        clock.advance(chrono::Duration::seconds(29));
+        assert!(engine.poll_deferrals().is_empty());
+
+        clock.advance(chrono::Duration::seconds(1));
+        let due = engine.poll_deferrals();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].due_at, created + chrono::Duration::seconds(30));
+    }
+
+    #[test]
+    fn test_deferral_blocks_workflow_after_max_attempts() {
+        let mut engine = EventEngine::new().with_max_defer_attempts(2);
+        engine.create_workflow("wf".to_string());
+        engine
+            .transition_workflow("wf", WorkflowState::Planning)
+            .unwrap();
+        engine
+            .transition_workflow("wf", WorkflowState::Executing)
+            .unwrap();
+
+        let mut deferred = DeferredIntent::new("wf".to_string(), test_intent());
+        for attempt in 1..=2 {
+            let outcome = engine.schedule_deferral(deferred, 0);
+            assert_eq!(outcome, DeferOutcome::Scheduled { attempt });
+            deferred = engine.poll_deferrals().pop().unwrap();
+        }
+
+        let outcome = engine.schedule_deferral(deferred, 0);
+        assert_eq!(outcome, DeferOutcome::Blocked { attempts: 2 });
+        assert!(engine.pending_deferrals().is_empty());
+
+        let workflow = engine.get_workflow("wf").unwrap();
+        assert_eq!(workflow.state, WorkflowState::Blocked);
+        assert!(
+            workflow
+                .error
+                .as_ref()
+                .unwrap()
+                .contains("deferred 2 times")
+        );
+    }
+
+    #[test]
+    fn test_huge_retry_after_is_clamped() {
+        use ndc_core::Clock;
+
+        let clock = ndc_core::MockClock::default();
+        let mut engine = EventEngine::new().with_clock(Arc::new(clock.clone()));
+        engine.create_workflow("wf".to_string());
+
+        engine.schedule_deferral(
+            DeferredIntent::new("wf".to_string(), test_intent()),
+            u64::MAX,
+        );
+        let max = i64::try_from(MAX_DEFER_SECS).unwrap();
+        assert_eq!(
+            engine.pending_deferrals()[0].due_at,
+            clock.now() + chrono::Duration::seconds(max)
+        );
+    }
+
+    #[test]
+    fn test_mark_blocked_respects_transitions() {
+        for state in [WorkflowState::Completed, WorkflowState::Cancelled] {
+            let mut workflow = workflow_in(state);
+            assert!(matches!(
+                workflow.mark_blocked("late".to_string()),
+                Err(TransitionError::Invalid { .. })
+            ));
+            assert_eq!(workflow.state, state);
+            assert!(workflow.error.is_none());
+        }
+
+        let mut workflow = workflow_in(WorkflowState::Executing);
+        workflow.mark_blocked("waiting".to_string()).unwrap();
+        assert_eq!(workflow.state, WorkflowState::Blocked);
+    }
+
+    fn workflow_in(state: WorkflowState) -> Workflow {
+        let mut workflow = Workflow::new("wf".to_string());
+        workflow.state = state;
+        workflow
+    }
+
+    #[test]
+    fn test_cancellable_states() {
+        for state in [
+            WorkflowState::Planning,
+            WorkflowState::Discovery,
+            WorkflowState::Executing,
+            WorkflowState::Verifying,
+            WorkflowState::Blocked,
+        ] {
+            let mut workflow = workflow_in(state);
+            assert!(workflow.transition(WorkflowState::Cancelled).is_ok());
+            assert_eq!(workflow.state, WorkflowState::Cancelled);
+        }
+    }
+
+    #[test]
+    fn test_non_cancellable_states() {
+        for state in [
+            WorkflowState::Initial,
+            WorkflowState::Completing,
+            WorkflowState::Completed,
+            WorkflowState::Failed,
+            WorkflowState::Cancelled,
+        ] {
+            let mut workflow = workflow_in(state);
+            assert!(
+                workflow.transition(WorkflowState::Cancelled).is_err(),
+                "{} should not be cancellable",
+                state
+            );
+            assert_eq!(workflow.state, state);
+        }
+    }
+
+    #[test]
+    fn test_cancelled_is_terminal() {
+        let workflow = workflow_in(WorkflowState::Cancelled);
+        for to in [
+            WorkflowState::Initial,
+            WorkflowState::Planning,
+            WorkflowState::Discovery,
+            WorkflowState::Executing,
+            WorkflowState::Verifying,
+            WorkflowState::Completing,
+            WorkflowState::Completed,
+            WorkflowState::Failed,
+            WorkflowState::Blocked,
+        ] {
+            assert!(!workflow.can_transition(to));
+        }
+    }
+
+    #[test]
+    fn test_cancel_workflow_emits_event_and_counts() {
+        let mut engine = EventEngine::new();
+        let cancelled = Arc::new(Mutex::new(Vec::new()));
+        let seen = cancelled.clone();
+        engine.on(
+            "cancel".to_string(),
+            vec![EventType::Custom {
+                name: "workflow_cancelled".to_string(),
+            }],
+            move |e| seen.lock().unwrap().push(e.task_id.clone().unwrap()),
+        );
+
+        engine.create_workflow("wf-1".to_string());
+        engine.create_workflow("wf-2".to_string());
+        engine
+            .transition_workflow("wf-1", WorkflowState::Planning)
+            .unwrap();
+
+        // Initial cannot be cancelled
+        assert!(engine.cancel_workflow("wf-2").is_err());
+        assert!(engine.cancel_workflow("missing").is_err());
+        assert!(cancelled.lock().unwrap().is_empty());
+
+        engine.cancel_workflow("wf-1").unwrap();
+        assert_eq!(
+            engine.get_workflow("wf-1").unwrap().state,
+            WorkflowState::Cancelled
+        );
+        assert_eq!(*cancelled.lock().unwrap(), vec!["wf-1".to_string()]);
+
+        // Cancelling twice is rejected
+        assert!(engine.cancel_workflow("wf-1").is_err());
+        assert_eq!(cancelled.lock().unwrap().len(), 1);
+
+        let summary = engine.summary();
+        assert_eq!(summary.cancelled_workflows, 1);
+        assert_eq!(summary.total_workflows, 2);
+    }
 }
@@ -0,0 +1,160 @@
+//! Backup Store - content-addressed file backups
+//!
+//! `UndoAction::RestoreFile` references a backup by the SHA-256 of its content
+//! instead of carrying the file inline. Blobs live under
+//! `<root>/<first 2 hex chars>/<remaining hex>`, so identical backups are
+//! stored once and binary files round-trip byte for byte.
+
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Hash-keyed blob store for saga backups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+impl BackupStore {
+    /// Store rooted at `root`; the directory is created on first write
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Blob directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Blob hash of `content` (lowercase hex SHA-256)
+    pub fn hash(content: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(content))
+    }
+
+    /// Store `content` and return its hash; existing blobs are not rewritten
+    pub fn put(&self, content: &[u8]) -> io::Result<String> {
+        let hash = Self::hash(content);
+        let path = self.blob_path(&hash)?;
+        if path.exists() {
+            return Ok(hash);
+        }
+
+        let dir = path.parent().unwrap_or(&self.root);
+        std::fs::create_dir_all(dir)?;
+        // Write then rename, so a crash never leaves a truncated blob behind
+        let tmp = dir.join(format!(".{}.{}.tmp", hash, uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, content)?;
+        if let Err(e) = std::fs::rename(&tmp, &path) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+        Ok(hash)
+    }
+
+    /// Back up the file at `path`
+    pub fn put_file(&self, path: &Path) -> io::Result<String> {
+        self.put(&std::fs::read(path)?)
+    }
+
+    /// Read a blob, verifying it still matches its hash
+    pub fn get(&self, hash: &str) -> io::Result<Vec<u8>> {
+        let content = std::fs::read(self.blob_path(hash)?)?;
+        if Self::hash(&content) != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("backup blob {} is corrupted", hash),
+            ));
+        }
+        Ok(content)
+    }
+
+    /// Whether a blob is stored
+    pub fn contains(&self, hash: &str) -> bool {
+        self.blob_path(hash).is_ok_and(|path| path.exists())
+    }
+
+    /// Write a blob back to `path`, creating missing parent directories
+    pub fn restore(&self, hash: &str, path: &Path) -> io::Result<()> {
+        let content = self.get(hash)?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)
+    }
+
+    fn blob_path(&self, hash: &str) -> io::Result<PathBuf> {
+        let valid = hash.len() == 64
+            && hash
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid backup hash: {}", hash),
+            ));
+        }
+        Ok(self.root.join(&hash[..2]).join(&hash[2..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn blob_count(root: &Path) -> usize {
+        std::fs::read_dir(root)
+            .unwrap()
+            .map(|dir| std::fs::read_dir(dir.unwrap().path()).unwrap().count())
+            .sum()
+    }
+
+    #[test]
+    fn test_identical_backups_share_one_blob() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BackupStore::new(temp_dir.path().join("backups"));
+
+        let first = store.put(b"fn main() {}").unwrap();
+        let second = store.put(b"fn main() {}").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(blob_count(store.root()), 1);
+
+        let other = store.put(b"fn other() {}").unwrap();
+        assert_ne!(first, other);
+        assert_eq!(blob_count(store.root()), 2);
+        assert_eq!(store.get(&first).unwrap(), b"fn main() {}");
+    }
+
+    #[test]
+    fn test_binary_file_round_trips() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BackupStore::new(temp_dir.path().join("backups"));
+        let binary: Vec<u8> = (0..=255u8).chain([0xff, 0xfe, 0x00, 0xc3]).collect();
+        let file = temp_dir.path().join("image.bin");
+        std::fs::write(&file, &binary).unwrap();
+
+        let hash = store.put_file(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        store.restore(&hash, &file).unwrap();
+
+        assert_eq!(std::fs::read(&file).unwrap(), binary);
+        assert!(store.contains(&hash));
+    }
+
+    #[test]
+    fn test_rejects_invalid_and_corrupted_blobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = BackupStore::new(temp_dir.path());
+
+        let err = store.get("../../etc/passwd").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!store.contains("not-a-hash"));
+
+        let hash = store.put(b"original").unwrap();
+        std::fs::write(store.blob_path(&hash).unwrap(), b"tampered").unwrap();
+        assert_eq!(
+            store.get(&hash).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
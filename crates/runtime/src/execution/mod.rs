@@ -2,9 +2,20 @@
 //!
 //! Ensures clean rollback when execution fails midway.
 //! Each subtask generates compensating actions for potential rollback.
+//! Plans are checkpointed to `Storage` so rollback survives a restart.
+//! `validate_rollback` checks undo preconditions first, so a rollback that
+//! would fail halfway is caught before anything is touched.
 
+use ndc_storage::Storage;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+mod backup;
+mod retry;
+mod worktree;
+pub use backup::BackupStore;
+pub use retry::RetryPolicy;
+pub use worktree::TaskWorktree;
 
 /// Saga ID
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -39,7 +50,7 @@ impl std::fmt::Display for StepId {
 }
 
 /// Saga Plan - Complete rollback plan for a task
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SagaPlan {
     /// Saga ID
     pub id: SagaId,
@@ -55,7 +66,7 @@ pub struct SagaPlan {
 }
 
 /// Step in the saga
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SagaStep {
     /// Step ID
     pub step_id: StepId,
@@ -76,13 +87,13 @@ pub enum StepAction {
     /// Created a file
     CreateFile { path: PathBuf },
 
-    /// Modified a file
+    /// Modified a file; `backup` is the `BackupStore` hash of the old content
     ModifyFile {
         path: PathBuf,
         backup: Option<String>,
     },
 
-    /// Deleted a file
+    /// Deleted a file; `backup` is the `BackupStore` hash of its content
     DeleteFile {
         path: PathBuf,
         backup: Option<String>,
@@ -100,6 +111,13 @@ pub enum StepAction {
     /// Created a git branch
     GitBranch { branch_name: String },
 
+    /// Created a task worktree on its own branch
+    CreateWorktree {
+        path: PathBuf,
+        branch: String,
+        base_commit: String,
+    },
+
     /// Installed dependency
     AddDependency { name: String, version: String },
 
@@ -113,8 +131,11 @@ pub enum UndoAction {
     /// Delete a file
     DeleteFile { path: PathBuf },
 
-    /// Restore file from backup
-    RestoreFile { path: PathBuf, backup: String },
+    /// Restore file from the `BackupStore` blob with this hash
+    RestoreFile { path: PathBuf, hash: String },
+
+    /// Move a file back to where it was
+    MoveFile { from: PathBuf, to: PathBuf },
 
     /// Run a shell command
     ShellCommand { command: String, args: Vec<String> },
@@ -122,6 +143,13 @@ pub enum UndoAction {
     /// Git revert
     GitRevert { commit_hash: String },
 
+    /// Remove a task worktree and delete its branch
+    RemoveWorktree {
+        repo_root: PathBuf,
+        path: PathBuf,
+        branch: String,
+    },
+
     /// Undo dependency
     RemoveDependency { name: String },
 
@@ -133,7 +161,7 @@ pub enum UndoAction {
 }
 
 /// Compensation action
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompensationAction {
     /// Step being compensated
     pub step_id: StepId,
@@ -143,7 +171,7 @@ pub struct CompensationAction {
 }
 
 /// Step status
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StepStatus {
     Pending,
     Executing,
@@ -166,6 +194,74 @@ pub enum RollbackError {
 
     #[error("Git error: {0}")]
     GitError(String),
+
+    #[error("Storage error: {0}")]
+    StorageError(String),
+}
+
+/// Precondition of an undo action that does not hold (see `validate_rollback`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollbackIssue {
+    /// `from_step` is not part of the plan
+    StepNotFound(StepId),
+
+    /// `RestoreFile` backup blob is missing or corrupted
+    MissingBackup {
+        step_id: StepId,
+        path: PathBuf,
+        hash: String,
+    },
+
+    /// File to delete or move back no longer exists
+    MissingFile { step_id: StepId, path: PathBuf },
+
+    /// `MoveFile` target is already taken
+    DestinationExists { step_id: StepId, path: PathBuf },
+
+    /// `GitRevert` commit is not in the repository
+    MissingCommit {
+        step_id: StepId,
+        commit_hash: String,
+    },
+
+    /// Undo that cannot be performed automatically
+    Unsupported { step_id: StepId, undo: String },
+}
+
+impl std::fmt::Display for RollbackIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RollbackIssue::StepNotFound(step_id) => write!(f, "step {} not found", step_id),
+            RollbackIssue::MissingBackup {
+                step_id,
+                path,
+                hash,
+            } => write!(
+                f,
+                "step {}: backup {} of {} is missing or corrupted",
+                step_id,
+                hash,
+                path.display()
+            ),
+            RollbackIssue::MissingFile { step_id, path } => {
+                write!(f, "step {}: {} does not exist", step_id, path.display())
+            }
+            RollbackIssue::DestinationExists { step_id, path } => {
+                write!(f, "step {}: {} already exists", step_id, path.display())
+            }
+            RollbackIssue::MissingCommit {
+                step_id,
+                commit_hash,
+            } => write!(f, "step {}: commit {} not found", step_id, commit_hash),
+            RollbackIssue::Unsupported { step_id, undo } => {
+                write!(
+                    f,
+                    "step {}: {} cannot be undone automatically",
+                    step_id, undo
+                )
+            }
+        }
+    }
 }
 
 impl SagaPlan {
@@ -245,6 +341,165 @@ impl SagaPlan {
         Ok(())
     }
 
+    /// Check the undo actions `rollback(from_step)` would run, without running them
+    ///
+    /// Relative paths are taken from `project_root`, which is also where
+    /// `GitRevert` commits are looked up. An empty result means every undo's
+    /// preconditions hold.
+    pub fn validate_rollback(
+        &self,
+        from_step: &StepId,
+        backups: &BackupStore,
+        project_root: &Path,
+    ) -> Vec<RollbackIssue> {
+        let Some(start_idx) = self.steps.iter().position(|s| s.step_id == *from_step) else {
+            return vec![RollbackIssue::StepNotFound(from_step.clone())];
+        };
+        let resolve = |path: &Path| project_root.join(path);
+
+        let mut issues = Vec::new();
+        for step in self.steps[..=start_idx].iter().rev() {
+            let (StepStatus::Completed, Some(undo)) = (step.status, &step.undo_action) else {
+                continue;
+            };
+            let step_id = step.step_id.clone();
+            match undo {
+                UndoAction::RestoreFile { path, hash } => {
+                    if backups.get(hash).is_err() {
+                        issues.push(RollbackIssue::MissingBackup {
+                            step_id,
+                            path: path.clone(),
+                            hash: hash.clone(),
+                        });
+                    }
+                }
+                UndoAction::DeleteFile { path } => {
+                    if !resolve(path).exists() {
+                        issues.push(RollbackIssue::MissingFile {
+                            step_id,
+                            path: path.clone(),
+                        });
+                    }
+                }
+                UndoAction::MoveFile { from, to } => {
+                    if !resolve(from).exists() {
+                        issues.push(RollbackIssue::MissingFile {
+                            step_id: step_id.clone(),
+                            path: from.clone(),
+                        });
+                    }
+                    if resolve(to).exists() {
+                        issues.push(RollbackIssue::DestinationExists {
+                            step_id,
+                            path: to.clone(),
+                        });
+                    }
+                }
+                UndoAction::GitRevert { commit_hash } => {
+                    if !worktree::commit_exists(project_root, commit_hash) {
+                        issues.push(RollbackIssue::MissingCommit {
+                            step_id,
+                            commit_hash: commit_hash.clone(),
+                        });
+                    }
+                }
+                // Removal tolerates an already-missing worktree or branch
+                UndoAction::RemoveWorktree { .. } | UndoAction::ShellCommand { .. } => {}
+                UndoAction::RemoveDependency { name } => {
+                    issues.push(RollbackIssue::Unsupported {
+                        step_id,
+                        undo: format!("removing dependency {}", name),
+                    });
+                }
+                UndoAction::Custom { handler, .. } => {
+                    issues.push(RollbackIssue::Unsupported {
+                        step_id,
+                        undo: format!("custom handler {}", handler),
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Roll back every completed step in reverse order
+    ///
+    /// Steps are marked `RolledBack` as their undo succeeds, so a plan that
+    /// fails halfway can be checkpointed and resumed.
+    pub async fn rollback_completed<F, Fut>(&mut self, executor: &F) -> Result<(), RollbackError>
+    where
+        F: Fn(UndoAction) -> Fut,
+        Fut: std::future::Future<Output = Result<(), String>>,
+    {
+        for step in self.steps.iter_mut().rev() {
+            if step.status != StepStatus::Completed {
+                continue;
+            }
+            if let Some(ref undo) = step.undo_action {
+                executor(undo.clone())
+                    .await
+                    .map_err(RollbackError::UndoFailed)?;
+            }
+            step.status = StepStatus::RolledBack;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the saga has completed work but did not finish
+    ///
+    /// Such plans were interrupted mid-task and are candidates for rollback.
+    pub fn is_incomplete(&self) -> bool {
+        let completed = self
+            .steps
+            .iter()
+            .filter(|s| s.status == StepStatus::Completed)
+            .count();
+        completed > 0 && completed < self.steps.len()
+    }
+
+    /// Checkpoint the plan to storage
+    pub async fn save(&self, storage: &dyn Storage) -> Result<(), RollbackError> {
+        let plan =
+            serde_json::to_value(self).map_err(|e| RollbackError::StorageError(e.to_string()))?;
+        storage
+            .save_saga(&self.id.0, &plan)
+            .await
+            .map_err(RollbackError::StorageError)
+    }
+
+    /// Load a checkpointed plan
+    pub async fn load(
+        storage: &dyn Storage,
+        id: &SagaId,
+    ) -> Result<Option<SagaPlan>, RollbackError> {
+        storage
+            .load_saga(&id.0)
+            .await
+            .map_err(RollbackError::StorageError)?
+            .map(|plan| {
+                serde_json::from_value(plan).map_err(|e| RollbackError::StorageError(e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// List checkpointed plans that were interrupted mid-task
+    pub async fn list_incomplete(storage: &dyn Storage) -> Result<Vec<SagaPlan>, RollbackError> {
+        let mut plans = Vec::new();
+        for plan in storage
+            .list_sagas()
+            .await
+            .map_err(RollbackError::StorageError)?
+        {
+            let plan: SagaPlan = serde_json::from_value(plan)
+                .map_err(|e| RollbackError::StorageError(e.to_string()))?;
+            if plan.is_incomplete() {
+                plans.push(plan);
+            }
+        }
+        Ok(plans)
+    }
+
     /// Get summary
     pub fn summary(&self) -> SagaSummary {
         SagaSummary {
@@ -278,17 +533,30 @@ impl UndoAction {
         UndoAction::DeleteFile { path: path.clone() }
     }
 
-    /// Create appropriate undo for file modification
+    /// Create appropriate undo for file modification, given the backup hash
     pub fn from_modify_file(path: &PathBuf, backup: &Option<String>) -> Self {
         match backup {
-            Some(b) => UndoAction::RestoreFile {
+            Some(hash) => UndoAction::RestoreFile {
                 path: path.clone(),
-                backup: b.clone(),
+                hash: hash.clone(),
             },
             None => UndoAction::DeleteFile { path: path.clone() },
         }
     }
 
+    /// Step and undo for moving `from` to `to`; the undo moves it back
+    pub fn from_move_file(from: &Path, to: &Path) -> (StepAction, Self) {
+        (
+            StepAction::Other {
+                description: format!("move {} -> {}", from.display(), to.display()),
+            },
+            UndoAction::MoveFile {
+                from: to.to_path_buf(),
+                to: from.to_path_buf(),
+            },
+        )
+    }
+
     /// Create appropriate undo for git commit
     pub fn from_git_commit(commit_hash: &str) -> Self {
         UndoAction::GitRevert {
@@ -388,12 +656,12 @@ mod tests {
 
     #[test]
     fn test_undo_from_modify_with_backup() {
-        let backup = Some("backup content".to_string());
+        let backup = Some(BackupStore::hash(b"backup content"));
         let undo = UndoAction::from_modify_file(&PathBuf::from("test.rs"), &backup);
         match undo {
-            UndoAction::RestoreFile { path, backup: b } => {
+            UndoAction::RestoreFile { path, hash } => {
                 assert_eq!(path, PathBuf::from("test.rs"));
-                assert_eq!(b, "backup content");
+                assert_eq!(Some(hash), backup);
             }
             _ => panic!("Expected RestoreFile"),
         }
@@ -409,4 +677,196 @@ mod tests {
             _ => panic!("Expected GitRevert"),
         }
     }
+
+    #[tokio::test]
+    async fn test_half_completed_saga_survives_reload_and_rolls_back() {
+        let storage = ndc_storage::MemoryStorage::new();
+        let mut saga = SagaPlan::new("task-123".to_string());
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            saga.add_step(
+                StepId::default(),
+                StepAction::CreateFile {
+                    path: PathBuf::from(name),
+                },
+                Some(UndoAction::from_create_file(&PathBuf::from(name))),
+            );
+        }
+        for i in 0..2 {
+            let step_id = saga.steps[i].step_id.clone();
+            saga.mark_completed(&step_id);
+            saga.save(&storage).await.unwrap();
+        }
+
+        // Simulate a restart: only storage survives
+        let saga_id = saga.id.clone();
+        drop(saga);
+
+        let incomplete = SagaPlan::list_incomplete(&storage).await.unwrap();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0].id, saga_id);
+
+        let mut reloaded = SagaPlan::load(&storage, &saga_id).await.unwrap().unwrap();
+        assert_eq!(reloaded.summary().completed_steps, 2);
+
+        let undone = std::sync::Mutex::new(Vec::new());
+        reloaded
+            .rollback_completed(&|undo| {
+                if let UndoAction::DeleteFile { path } = undo {
+                    undone.lock().unwrap().push(path);
+                }
+                async { Ok(()) }
+            })
+            .await
+            .unwrap();
+        reloaded.save(&storage).await.unwrap();
+
+        assert_eq!(
+            *undone.lock().unwrap(),
+            vec![PathBuf::from("b.rs"), PathBuf::from("a.rs")]
+        );
+        assert_eq!(reloaded.steps[0].status, StepStatus::RolledBack);
+        assert_eq!(reloaded.steps[2].status, StepStatus::Pending);
+        assert!(
+            SagaPlan::list_incomplete(&storage)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn completed_step(saga: &mut SagaPlan, undo: UndoAction) -> StepId {
+        let step_id = StepId::default();
+        saga.add_step(
+            step_id.clone(),
+            StepAction::Other {
+                description: "test".to_string(),
+            },
+            Some(undo),
+        );
+        saga.mark_completed(&step_id);
+        step_id
+    }
+
+    #[test]
+    fn test_validate_rollback_reports_missing_backup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let backups = BackupStore::new(temp.path().join("backups"));
+        let mut saga = SagaPlan::new("task-validate".to_string());
+        let missing = BackupStore::hash(b"never stored");
+        let restore = completed_step(
+            &mut saga,
+            UndoAction::RestoreFile {
+                path: PathBuf::from("lib.rs"),
+                hash: missing.clone(),
+            },
+        );
+        let delete = completed_step(
+            &mut saga,
+            UndoAction::DeleteFile {
+                path: PathBuf::from("gone.rs"),
+            },
+        );
+
+        let issues = saga.validate_rollback(&delete, &backups, temp.path());
+        assert_eq!(
+            issues,
+            vec![
+                RollbackIssue::MissingFile {
+                    step_id: delete,
+                    path: PathBuf::from("gone.rs"),
+                },
+                RollbackIssue::MissingBackup {
+                    step_id: restore.clone(),
+                    path: PathBuf::from("lib.rs"),
+                    hash: missing,
+                },
+            ]
+        );
+        assert!(issues[1].to_string().contains("backup"));
+
+        let unknown = StepId::default();
+        assert_eq!(
+            saga.validate_rollback(&unknown, &backups, temp.path()),
+            vec![RollbackIssue::StepNotFound(unknown)]
+        );
+    }
+
+    #[test]
+    fn test_validate_rollback_accepts_valid_plan() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let backups = BackupStore::new(temp.path().join("backups"));
+        std::fs::write(temp.path().join("new.rs"), "fn new() {}").unwrap();
+        std::fs::write(temp.path().join("moved.rs"), "fn moved() {}").unwrap();
+        let hash = backups.put(b"fn old() {}").unwrap();
+
+        let mut saga = SagaPlan::new("task-validate".to_string());
+        completed_step(
+            &mut saga,
+            UndoAction::RestoreFile {
+                path: PathBuf::from("lib.rs"),
+                hash,
+            },
+        );
+        completed_step(
+            &mut saga,
+            UndoAction::from_create_file(&PathBuf::from("new.rs")),
+        );
+        completed_step(
+            &mut saga,
+            UndoAction::MoveFile {
+                from: temp.path().join("moved.rs"),
+                to: temp.path().join("original.rs"),
+            },
+        );
+        // Pending steps are not rolled back, so their undo is not checked
+        let pending = StepId::default();
+        saga.add_step(
+            pending.clone(),
+            StepAction::Other {
+                description: "pending".to_string(),
+            },
+            Some(UndoAction::DeleteFile {
+                path: PathBuf::from("not-yet.rs"),
+            }),
+        );
+
+        assert!(
+            saga.validate_rollback(&pending, &backups, temp.path())
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_file_rollback_moves_it_back() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let from = temp.path().join("old.rs");
+        let to = temp.path().join("renamed/new.rs");
+        std::fs::write(&from, "fn main() {}").unwrap();
+
+        let (action, undo) = UndoAction::from_move_file(&from, &to);
+        assert!(matches!(action, StepAction::Other { .. }));
+
+        std::fs::create_dir_all(to.parent().unwrap()).unwrap();
+        crate::tools::fs::move_path(&from, &to).await.unwrap();
+
+        let mut saga = SagaPlan::new("task-move".to_string());
+        let step_id = StepId::default();
+        saga.add_step(step_id.clone(), action, Some(undo));
+        saga.mark_completed(&step_id);
+
+        saga.rollback_completed(&|undo| async move {
+            match undo {
+                UndoAction::MoveFile { from, to } => crate::tools::fs::move_path(&from, &to)
+                    .await
+                    .map_err(|e| e.to_string()),
+                other => Err(format!("unexpected undo: {:?}", other)),
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(!to.exists());
+        assert_eq!(std::fs::read_to_string(&from).unwrap(), "fn main() {}");
+        assert_eq!(saga.steps[0].status, StepStatus::RolledBack);
+    }
 }
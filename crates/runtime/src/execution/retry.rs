@@ -0,0 +1,110 @@
+//! Retry Policy - transient step failures
+//!
+//! A failed shell step is retried when its exit code or stderr marks the
+//! failure as transient (network flake, lock contention). Anything else, such
+//! as a compile error, fails the step immediately.
+
+use std::time::Duration;
+
+/// Retry policy for shell steps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; 1 disables retries
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled per retry
+    pub base_delay: Duration,
+
+    /// Upper bound for a single delay
+    pub max_delay: Duration,
+
+    /// Exit codes that always mark a failure retryable
+    pub retryable_exit_codes: Vec<i32>,
+
+    /// Case-insensitive stderr fragments that mark a failure retryable
+    pub retryable_patterns: Vec<String>,
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a failure with this exit code and stderr is worth retrying
+    pub fn is_retryable(&self, exit_code: Option<i32>, stderr: &str) -> bool {
+        if exit_code.is_some_and(|code| self.retryable_exit_codes.contains(&code)) {
+            return true;
+        }
+        let stderr = stderr.to_lowercase();
+        self.retryable_patterns
+            .iter()
+            .any(|pattern| stderr.contains(&pattern.to_lowercase()))
+    }
+
+    /// Delay before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            retryable_exit_codes: Vec::new(),
+            retryable_patterns: [
+                "connection reset",
+                "connection refused",
+                "timed out",
+                "temporary failure",
+                "could not resolve host",
+                "resource temporarily unavailable",
+                "blocking waiting for file lock",
+                "index.lock",
+                "database is locked",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_by_pattern_and_exit_code() {
+        let policy = RetryPolicy {
+            retryable_exit_codes: vec![75],
+            ..RetryPolicy::default()
+        };
+
+        assert!(policy.is_retryable(Some(1), "curl: Could not resolve host: x"));
+        assert!(policy.is_retryable(Some(75), ""));
+        assert!(!policy.is_retryable(Some(101), "error[E0308]: mismatched types"));
+        assert!(!policy.is_retryable(None, ""));
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+}
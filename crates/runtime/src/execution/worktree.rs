@@ -0,0 +1,155 @@
+//! Task Worktree - isolated git checkout per task
+//!
+//! Each task runs on its own `git worktree` and branch, so concurrent tasks
+//! never touch each other's files or the main checkout. Worktrees live under
+//! `<git-common-dir>/ndc-worktrees/<task_id>` and never show up as untracked
+//! files in the main checkout.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A git worktree dedicated to one task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskWorktree {
+    /// Main repository root (git toplevel)
+    pub repo_root: PathBuf,
+
+    /// Worktree directory
+    pub path: PathBuf,
+
+    /// Task branch checked out in the worktree
+    pub branch: String,
+
+    /// Commit the branch was created from
+    pub base_commit: String,
+}
+
+impl TaskWorktree {
+    /// Branch name used for a task
+    pub fn branch_name(task_id: &str) -> String {
+        format!("ndc/task-{}", task_id)
+    }
+
+    /// Create the task's worktree from HEAD of the repo containing `project_root`
+    ///
+    /// An existing worktree for the task (from an interrupted run) is reused.
+    pub fn create(project_root: &Path, task_id: &str) -> Result<Self, String> {
+        let repo_root = PathBuf::from(git(project_root, &["rev-parse", "--show-toplevel"])?);
+        let common_dir = PathBuf::from(git(&repo_root, &["rev-parse", "--git-common-dir"])?);
+        let common_dir = if common_dir.is_absolute() {
+            common_dir
+        } else {
+            repo_root.join(common_dir)
+        };
+        let path = common_dir.join("ndc-worktrees").join(task_id);
+        let branch = Self::branch_name(task_id);
+
+        if path.join(".git").exists() {
+            let base_commit = git(&path, &["rev-parse", "HEAD"])?;
+            return Ok(Self {
+                repo_root,
+                path,
+                branch,
+                base_commit,
+            });
+        }
+
+        let base_commit = git(&repo_root, &["rev-parse", "HEAD"])?;
+        git(
+            &repo_root,
+            &[
+                "worktree",
+                "add",
+                "-B",
+                &branch,
+                &path.to_string_lossy(),
+                &base_commit,
+            ],
+        )?;
+        Ok(Self {
+            repo_root,
+            path,
+            branch,
+            base_commit,
+        })
+    }
+
+    /// Directory inside the worktree that corresponds to `project_root`
+    pub fn scoped_root(&self, project_root: &Path) -> PathBuf {
+        let canonical = std::fs::canonicalize(project_root).unwrap_or(project_root.to_path_buf());
+        match canonical.strip_prefix(&self.repo_root) {
+            Ok(relative) => self.path.join(relative),
+            Err(_) => self.path.clone(),
+        }
+    }
+
+    /// Map a path of the main checkout onto the worktree
+    ///
+    /// Relative paths are taken from `project_root`; paths already inside the
+    /// worktree or outside the project are returned unchanged.
+    pub fn rebase_path(&self, project_root: &Path, path: &Path) -> PathBuf {
+        let scoped = self.scoped_root(project_root);
+        if path.is_relative() {
+            return scoped.join(path);
+        }
+        if path.starts_with(&self.path) {
+            return path.to_path_buf();
+        }
+        let canonical_root =
+            std::fs::canonicalize(project_root).unwrap_or(project_root.to_path_buf());
+        [project_root, canonical_root.as_path()]
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .map_or_else(|| path.to_path_buf(), |relative| scoped.join(relative))
+    }
+
+    /// Commit every change in the worktree; returns the branch head
+    ///
+    /// Without changes no commit is made and the current head is returned.
+    pub fn commit_all(&self, message: &str) -> Result<String, String> {
+        git(&self.path, &["add", "-A"])?;
+        if !git(&self.path, &["status", "--porcelain"])?.is_empty() {
+            git(&self.path, &["commit", "-q", "-m", message])?;
+        }
+        git(&self.path, &["rev-parse", "HEAD"])
+    }
+
+    /// Remove a task worktree and its branch
+    pub fn remove(repo_root: &Path, path: &Path, branch: &str) -> Result<(), String> {
+        if path.exists() {
+            git(
+                repo_root,
+                &["worktree", "remove", "--force", &path.to_string_lossy()],
+            )?;
+        }
+        git(repo_root, &["worktree", "prune"])?;
+        // The branch may never have been created
+        let _ = git(repo_root, &["branch", "-D", branch]);
+        Ok(())
+    }
+}
+
+/// Whether `commit_hash` names a commit in the repository containing `dir`
+pub(super) fn commit_exists(dir: &Path, commit_hash: &str) -> bool {
+    git(
+        dir,
+        &["cat-file", "-e", &format!("{}^{{commit}}", commit_hash)],
+    )
+    .is_ok()
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("git {}: {}", args.join(" "), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
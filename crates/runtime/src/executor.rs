@@ -4,18 +4,69 @@
 //! - Execute tasks
 //! - Coordinate tools and quality gates
 //! - Manage task lifecycle
+//!
+//! Logs are correlated through nested `tracing` spans:
+//! `task{task_id}` > `step{step, step_id}` > `tool{action}`.
 
-use crate::discovery::DiscoveryService;
+use crate::discovery::{CommitGate, CommitGateValidator, DiscoveryService};
+use crate::engine::{
+    DeferOutcome, DeferredIntent, Event, EventData, EventEmitter, EventEngine, EventId, EventType,
+    WorkflowState,
+};
+use crate::execution::{
+    BackupStore, RetryPolicy, RollbackError, SagaId, SagaPlan, SagaStep, StepAction, StepId,
+    StepStatus as SagaStepStatus, TaskWorktree, UndoAction,
+};
+use crate::tools::FileLockManager;
 use crate::{HardConstraints, QualityGateRunner, SharedStorage, ToolManager, WorkflowEngine};
 use ndc_core::{
-    AccessControl, Action, ActionResult, AgentId, AgentRole, ExecutionStep, MemoryContent,
-    MemoryEntry, MemoryId, MemoryMetadata, MemoryStability, StepStatus, SystemFactInput, Task,
-    TaskId, TaskState,
+    AccessControl, Action, ActionResult, AgentId, AgentRole, ByteRange, ConditionType, Effect,
+    ExecutionStep, FileOp, MemoryContent, MemoryEntry, MemoryId, MemoryMetadata, MemoryStability,
+    SharedClock, StepStatus, SystemFactInput, Task, TaskId, TaskState, Verdict, WorkEvent,
+    WorkRecord, WorkResult,
 };
+use ndc_decision::{BasicDecisionEngine, DecisionEngine, PermissionValidator};
 use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
-use tracing::{info, warn};
+use tracing::{debug, info, instrument, warn};
+
+/// Short action name for log spans
+fn action_name(action: &Action) -> &'static str {
+    match action {
+        Action::ReadFile { .. } => "read_file",
+        Action::WriteFile { .. } => "write_file",
+        Action::CreateFile { .. } => "create_file",
+        Action::DeleteFile { .. } => "delete_file",
+        Action::MoveFile { .. } => "move_file",
+        Action::RunCommand { .. } => "run_command",
+        Action::Git { .. } => "git",
+        _ => "other",
+    }
+}
+
+/// Run an undo command in `dir`; a non-zero exit is an error
+async fn run_undo_command(
+    dir: &Path,
+    command: &str,
+    args: &[impl AsRef<std::ffi::OsStr>],
+) -> Result<(), String> {
+    let output = tokio::process::Command::new(command)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .map_err(|e| format!("{}: {}", command, e))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(format!(
+            "{} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
 
 /// Executor error
 #[derive(Debug, Error, Clone)]
@@ -34,6 +85,25 @@ pub enum ExecutionError {
 
     #[error("Discovery failed: {0}")]
     DiscoveryFailed(String),
+
+    #[error("Action not allowed: {0}")]
+    NotAllowed(String),
+
+    #[error("Backup failed, action refused: {0}")]
+    BackupFailed(String),
+
+    #[error("Command failed: {command} (exit {exit_code:?}): {stderr}")]
+    CommandFailed {
+        command: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+
+    #[error("{0}")]
+    DependencyCycle(#[from] ndc_core::CycleError),
+
+    #[error("Task {task} is blocked on unfinished dependency {dependency}")]
+    DependencyBlocked { task: TaskId, dependency: TaskId },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -60,6 +130,49 @@ pub struct ExecutionContext {
     pub quality_runner: Arc<QualityGateRunner>,
     pub project_root: std::path::PathBuf,
     pub current_role: AgentRole,
+    /// Plan only: report effects and compensations without touching fs/git/shell
+    pub dry_run: bool,
+    /// Run each task in its own git worktree on a `ndc/task-<id>` branch
+    pub isolate_worktree: bool,
+    /// Retries for transient shell step failures
+    pub retry_policy: RetryPolicy,
+    /// Step lifecycle events
+    pub events: Arc<EventEmitter>,
+    /// Backup blob directory; defaults to `<project_root>/.ndc/storage/backups`
+    pub backup_dir: Option<PathBuf>,
+    /// Time source for task, step and event timestamps
+    pub clock: SharedClock,
+    /// Engine that judges agent tool calls; `None` skips the decision step
+    pub decision_engine: Option<Arc<dyn DecisionEngine>>,
+    /// Current task's hard constraints and the checks passed against them
+    pub commit_gate: CommitGate,
+    /// File locks shared by `tools` and the agent's tool registry; `None`
+    /// applies patches without locking
+    pub lock_manager: Option<Arc<FileLockManager>>,
+}
+
+impl ExecutionContext {
+    /// Content-addressed store for saga file backups
+    pub fn backup_store(&self) -> BackupStore {
+        BackupStore::new(self.backup_dir.clone().unwrap_or_else(|| {
+            self.project_root
+                .join(".ndc")
+                .join("storage")
+                .join("backups")
+        }))
+    }
+}
+
+/// Decision engine for agent tool calls under `project_root`; commits are
+/// denied while `commit_gate` reports unmet hard constraints
+pub fn create_decision_engine(
+    project_root: &Path,
+    commit_gate: &CommitGate,
+) -> BasicDecisionEngine {
+    let mut engine = BasicDecisionEngine::new().with_project_root(project_root);
+    engine.register_validator(Arc::new(PermissionValidator));
+    engine.register_validator(Arc::new(CommitGateValidator::for_gate(commit_gate.clone())));
+    engine
 }
 
 impl std::fmt::Debug for ExecutionContext {
@@ -67,6 +180,13 @@ impl std::fmt::Debug for ExecutionContext {
         f.debug_struct("ExecutionContext")
             .field("project_root", &self.project_root)
             .field("current_role", &self.current_role)
+            .field("dry_run", &self.dry_run)
+            .field("isolate_worktree", &self.isolate_worktree)
+            .field("retry_policy", &self.retry_policy)
+            .field("backup_dir", &self.backup_dir)
+            .field("clock", &self.clock)
+            .field("decision_engine", &self.decision_engine.is_some())
+            .field("lock_manager", &self.lock_manager.is_some())
             .finish()
     }
 }
@@ -74,13 +194,27 @@ impl std::fmt::Debug for ExecutionContext {
 impl Default for ExecutionContext {
     fn default() -> Self {
         let storage = crate::create_memory_storage();
+        let commit_gate = CommitGate::new();
         Self {
             storage: storage.clone(),
             workflow_engine: Arc::new(WorkflowEngine::new()),
-            tools: Arc::new(crate::create_default_tool_manager_with_storage(storage)),
-            quality_runner: Arc::new(QualityGateRunner::new()),
+            tools: Arc::new(crate::create_default_tool_manager_with_storage(
+                storage, None,
+            )),
+            quality_runner: Arc::new(
+                QualityGateRunner::new().with_commit_gate(commit_gate.clone()),
+            ),
             project_root: std::path::PathBuf::from("."),
             current_role: AgentRole::Historian,
+            dry_run: false,
+            isolate_worktree: false,
+            retry_policy: RetryPolicy::default(),
+            events: Arc::new(EventEmitter::new()),
+            backup_dir: None,
+            clock: ndc_core::system_clock(),
+            decision_engine: None,
+            commit_gate,
+            lock_manager: None,
         }
     }
 }
@@ -95,6 +229,12 @@ pub struct ExecutionResult {
     pub output: String,
     pub error: Option<String>,
     pub metrics: ExecutionMetrics,
+    /// Nothing was executed; `effects`/`compensations` describe what would happen
+    pub dry_run: bool,
+    /// Effects of the planned actions (dry run only)
+    pub effects: Vec<Effect>,
+    /// Saga steps that would be recorded, with their undo actions (dry run only)
+    pub compensations: Vec<SagaStep>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -106,15 +246,29 @@ pub struct ExecutionMetrics {
 }
 
 /// Executor
-#[derive(Debug)]
 pub struct Executor {
     context: Arc<ExecutionContext>,
+    /// Tasks whose verdict was `Defer`, waiting to be re-evaluated
+    deferrals: std::sync::Mutex<EventEngine>,
+}
+
+impl std::fmt::Debug for Executor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Executor")
+            .field("context", &self.context)
+            .field("pending_deferrals", &self.pending_deferrals().len())
+            .finish()
+    }
 }
 
 impl Executor {
     pub fn new(context: ExecutionContext) -> Self {
+        let deferrals = EventEngine::new()
+            .with_emitter(context.events.clone())
+            .with_clock(context.clock.clone());
         Self {
             context: Arc::new(context),
+            deferrals: std::sync::Mutex::new(deferrals),
         }
     }
 
@@ -130,7 +284,9 @@ impl Executor {
         description: String,
         created_by: AgentRole,
     ) -> Result<Task, ExecutionError> {
-        let task = Task::new(title, description, created_by);
+        let mut task = Task::new(title, description, created_by);
+        task.metadata.created_at = self.context.clock.now();
+        task.metadata.updated_at = task.metadata.created_at;
 
         // Save to storage
         self.context
@@ -143,67 +299,462 @@ impl Executor {
         Ok(task)
     }
 
+    /// Mark a saga step completed and checkpoint the plan to storage
+    pub async fn complete_saga_step(
+        &self,
+        saga: &mut SagaPlan,
+        step_id: &StepId,
+    ) -> Result<(), ExecutionError> {
+        saga.mark_completed(step_id);
+        saga.save(self.context.storage.as_ref())
+            .await
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))
+    }
+
+    /// Sagas left incomplete by a previous run, to offer rollback on startup
+    pub async fn incomplete_sagas(&self) -> Result<Vec<SagaPlan>, ExecutionError> {
+        SagaPlan::list_incomplete(self.context.storage.as_ref())
+            .await
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))
+    }
+
+    /// Roll back a saga's completed steps and checkpoint the result
+    pub async fn rollback_saga<F, Fut>(
+        &self,
+        saga: &mut SagaPlan,
+        undo: &F,
+    ) -> Result<(), RollbackError>
+    where
+        F: Fn(UndoAction) -> Fut,
+        Fut: std::future::Future<Output = Result<(), String>>,
+    {
+        let result = saga.rollback_completed(undo).await;
+        // Checkpoint even on failure so already-undone steps are not replayed
+        saga.save(self.context.storage.as_ref()).await?;
+        result
+    }
+
+    /// Perform one undo action against the project (for `rollback_saga`)
+    ///
+    /// Relative paths are taken from the project root.
+    pub async fn apply_undo(&self, undo: UndoAction) -> Result<(), String> {
+        let root = &self.context.project_root;
+        match undo {
+            UndoAction::DeleteFile { path } => tokio::fs::remove_file(root.join(&path))
+                .await
+                .map_err(|e| format!("delete {}: {}", path.display(), e)),
+            UndoAction::RestoreFile { path, hash } => self
+                .context
+                .backup_store()
+                .restore(&hash, &root.join(&path))
+                .map_err(|e| format!("restore {}: {}", path.display(), e)),
+            UndoAction::MoveFile { from, to } => {
+                crate::tools::fs::move_path(&root.join(&from), &root.join(&to))
+                    .await
+                    .map_err(|e| format!("move {}: {}", from.display(), e))
+            }
+            UndoAction::ShellCommand { command, args } => {
+                run_undo_command(root, &command, &args).await
+            }
+            UndoAction::GitRevert { commit_hash } => {
+                run_undo_command(root, "git", &["revert", "--no-edit", &commit_hash]).await
+            }
+            UndoAction::RemoveWorktree {
+                repo_root,
+                path,
+                branch,
+            } => TaskWorktree::remove(&repo_root, &path, &branch),
+            other => Err(format!("cannot undo {:?} automatically", other)),
+        }
+    }
+
+    /// Tasks deferred by their verdict and not yet re-evaluated
+    pub fn pending_deferrals(&self) -> Vec<DeferredIntent> {
+        self.lock_deferrals().pending_deferrals().to_vec()
+    }
+
+    /// When the earliest pending deferral becomes due
+    pub fn next_deferral_due(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.lock_deferrals()
+            .pending_deferrals()
+            .iter()
+            .map(|d| d.due_at)
+            .min()
+    }
+
+    /// Re-evaluate the deferred tasks that are due and run the ones now allowed
+    ///
+    /// A task deferred again is re-scheduled with its attempt counter carried
+    /// over; one result per due task.
+    pub async fn run_due_deferrals(&self) -> Vec<Result<ExecutionResult, ExecutionError>> {
+        let due = self.lock_deferrals().poll_deferrals();
+        let mut results = Vec::with_capacity(due.len());
+        for deferred in due {
+            results.push(self.retry_deferred(deferred).await);
+        }
+        results
+    }
+
+    async fn retry_deferred(
+        &self,
+        deferred: DeferredIntent,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let task_id: TaskId = deferred.workflow_id.parse().map_err(|_| {
+            ExecutionError::ToolError(format!("invalid deferred task id {}", deferred.workflow_id))
+        })?;
+        let engine = self.context.decision_engine.clone().ok_or_else(|| {
+            ExecutionError::NotAllowed(format!(
+                "no decision engine to re-evaluate deferred task {}",
+                task_id
+            ))
+        })?;
+
+        let verdict = engine.evaluate(deferred.intent.clone()).await;
+        let mut task = self.load_task(task_id).await?;
+        task.verdict = Some(verdict.clone());
+        self.checkpoint_task(&task).await?;
+
+        match verdict {
+            Verdict::Defer { retry_after, .. } => {
+                self.defer_task(task, deferred, retry_after.unwrap_or_default(), false)
+                    .await
+            }
+            _ => self.run_task(task_id, false).await,
+        }
+    }
+
+    /// Schedule a deferred task's re-evaluation, or block it once its
+    /// attempts are exhausted; `first` starts a new deferral chain
+    async fn defer_task(
+        &self,
+        task: Task,
+        deferred: DeferredIntent,
+        retry_after: u64,
+        first: bool,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let workflow_id = deferred.workflow_id.clone();
+        let outcome = {
+            let mut engine = self.lock_deferrals();
+            if first
+                && engine
+                    .pending_deferrals()
+                    .iter()
+                    .any(|d| d.workflow_id == workflow_id)
+            {
+                None
+            } else {
+                if first || engine.get_workflow(&workflow_id).is_none() {
+                    engine.create_workflow(workflow_id.clone());
+                    for state in [WorkflowState::Planning, WorkflowState::Executing] {
+                        engine
+                            .transition_workflow(&workflow_id, state)
+                            .map_err(|e| ExecutionError::ToolError(e.to_string()))?;
+                    }
+                }
+                Some(engine.schedule_deferral(deferred, retry_after))
+            }
+        };
+
+        let (output, error, final_state) = match outcome {
+            None => (
+                format!("Task {} is already deferred", task.id),
+                None,
+                task.state.clone(),
+            ),
+            Some(DeferOutcome::Scheduled { attempt }) => {
+                info!(task_id = %task.id, attempt, retry_after, "Task deferred");
+                let output = format!(
+                    "Task deferred (attempt {}), re-evaluated in {}s",
+                    attempt, retry_after
+                );
+                (output, None, task.state.clone())
+            }
+            Some(DeferOutcome::Blocked { attempts }) => {
+                let reason = format!("Task deferred {} times, giving up", attempts);
+                warn!(task_id = %task.id, attempts, "Deferral attempts exhausted");
+                let final_state = match task.state {
+                    TaskState::Pending => {
+                        self.set_blocked(task.clone(), true).await?;
+                        TaskState::Blocked
+                    }
+                    ref state => state.clone(),
+                };
+                (reason.clone(), Some(reason), final_state)
+            }
+        };
+
+        Ok(ExecutionResult {
+            success: false,
+            task_id: task.id,
+            final_state,
+            steps: task.steps,
+            output,
+            error,
+            metrics: ExecutionMetrics::default(),
+            dry_run: false,
+            effects: Vec::new(),
+            compensations: Vec::new(),
+        })
+    }
+
+    fn lock_deferrals(&self) -> std::sync::MutexGuard<'_, EventEngine> {
+        self.deferrals.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Execute a task
     pub async fn execute_task(&self, task_id: TaskId) -> Result<ExecutionResult, ExecutionError> {
-        let start_time = std::time::Instant::now();
+        self.run_task(task_id, false).await
+    }
 
-        // Get task from storage
-        let mut task = self
+    /// Resume a task from its checkpointed steps
+    ///
+    /// `Completed` steps are skipped; a step left `InProgress` by a crash is
+    /// re-run, as are failed and pending ones. The task's saga plan is reloaded
+    /// so rollback still covers steps completed before the resume.
+    pub async fn resume_task(&self, task_id: TaskId) -> Result<ExecutionResult, ExecutionError> {
+        self.run_task(task_id, true).await
+    }
+
+    /// Run a task after its unmet dependencies, in dependency order
+    ///
+    /// Prerequisites already `Completed` are skipped. When one does not
+    /// complete, the tasks still waiting on it move to `Blocked` and the run
+    /// stops: with its error, or with its (last) result.
+    pub async fn execute_with_dependencies(
+        &self,
+        task_id: TaskId,
+    ) -> Result<Vec<ExecutionResult>, ExecutionError> {
+        let storage_err = |e: String| ExecutionError::ToolError(e);
+        let tasks = self
             .context
             .storage
-            .get_task(&task_id)
+            .list_tasks()
             .await
-            .map_err(|e| ExecutionError::ToolError(e.to_string()))?
-            .ok_or(ExecutionError::TaskNotFound(task_id))?;
+            .map_err(storage_err)?;
+        let by_id: std::collections::HashMap<TaskId, &Task> =
+            tasks.iter().map(|t| (t.id, t)).collect();
+
+        // The task and its transitive prerequisites
+        let mut needed = HashSet::new();
+        let mut stack = vec![task_id];
+        while let Some(id) = stack.pop() {
+            if !needed.insert(id) {
+                continue;
+            }
+            let task = by_id.get(&id).ok_or(ExecutionError::TaskNotFound(id))?;
+            stack.extend(task.depends_on.iter().copied());
+        }
+        let subset: Vec<Task> = tasks
+            .iter()
+            .filter(|t| needed.contains(&t.id))
+            .cloned()
+            .collect();
+        let order = ndc_core::plan_execution_order(&subset)?;
+
+        let mut results = Vec::new();
+        if self.context.dry_run {
+            for id in order {
+                results.push(self.execute_task(id).await?);
+            }
+            return Ok(results);
+        }
+        for (position, id) in order.iter().enumerate() {
+            let task = self.load_task(*id).await?;
+            if let Some(dependency) = self.unfinished_dependency(&task).await? {
+                self.block_waiting(&order[position..]).await?;
+                return Err(ExecutionError::DependencyBlocked {
+                    task: *id,
+                    dependency,
+                });
+            }
+            if task.state == TaskState::Blocked {
+                self.set_blocked(task, false).await?;
+            }
 
-        info!("Executing task: {:?} ({})", task_id, task.title);
+            let result = self.execute_task(*id).await;
+            let completed = matches!(&result, Ok(r) if r.final_state == TaskState::Completed);
+            if !completed {
+                self.block_waiting(&order[position + 1..]).await?;
+            }
+            results.push(result?);
+            if !completed {
+                break;
+            }
+        }
+        Ok(results)
+    }
 
-        // Transition: Pending -> Preparing
+    async fn load_task(&self, task_id: TaskId) -> Result<Task, ExecutionError> {
         self.context
-            .workflow_engine
-            .transition(&mut task, TaskState::Preparing)
+            .storage
+            .get_task(&task_id)
             .await
-            .map_err(|_e| ExecutionError::InvalidStateTransition {
-                from: task.state.clone(),
-                to: TaskState::Preparing,
-            })?;
+            .map_err(ExecutionError::ToolError)?
+            .ok_or(ExecutionError::TaskNotFound(task_id))
+    }
 
-        // Transition: Preparing -> InProgress
-        self.context
-            .workflow_engine
-            .transition(&mut task, TaskState::InProgress)
-            .await
-            .map_err(|_e| ExecutionError::InvalidStateTransition {
-                from: task.state.clone(),
-                to: TaskState::InProgress,
-            })?;
+    /// First prerequisite of `task` that has not completed
+    async fn unfinished_dependency(&self, task: &Task) -> Result<Option<TaskId>, ExecutionError> {
+        for dependency in &task.depends_on {
+            if self.load_task(*dependency).await?.state != TaskState::Completed {
+                return Ok(Some(*dependency));
+            }
+        }
+        Ok(None)
+    }
 
-        // Execute actions
-        if let Some(action) = task
-            .intent
-            .as_ref()
-            .map(|intent| intent.proposed_action.clone())
-        {
-            self.execute_action(&mut task, &action).await?;
+    /// Move the `Pending` tasks among `ids` that have an unfinished prerequisite to `Blocked`
+    async fn block_waiting(&self, ids: &[TaskId]) -> Result<(), ExecutionError> {
+        for id in ids {
+            let task = self.load_task(*id).await?;
+            if task.state == TaskState::Pending
+                && self.unfinished_dependency(&task).await?.is_some()
+            {
+                self.set_blocked(task, true).await?;
+            }
         }
+        Ok(())
+    }
 
-        // Transition: InProgress -> AwaitingVerification
+    /// `Pending` -> `Blocked` (or back), recorded as a work event and saved
+    async fn set_blocked(&self, mut task: Task, blocked: bool) -> Result<(), ExecutionError> {
+        let (to, event) = match blocked {
+            true => (TaskState::Blocked, WorkEvent::Blocked),
+            false => (TaskState::Pending, WorkEvent::Unblocked),
+        };
         self.context
             .workflow_engine
-            .transition(&mut task, TaskState::AwaitingVerification)
+            .transition(&mut task, to.clone())
             .await
             .map_err(|_e| ExecutionError::InvalidStateTransition {
                 from: task.state.clone(),
-                to: TaskState::AwaitingVerification,
+                to,
             })?;
-
-        // Discovery -> HardConstraints -> QualityGate enforced chain
-        let hard_constraints = self.discover_hard_constraints(&task).await?;
+        task.metadata.work_records.push(WorkRecord {
+            id: ulid::Ulid::new(),
+            timestamp: self.context.clock.now(),
+            event,
+            executor: ndc_core::Executor::System,
+            result: WorkResult::Success,
+        });
         self.context
-            .quality_runner
-            .run_with_constraints(task.quality_gate.as_ref(), hard_constraints.as_ref())
+            .storage
+            .save_task(&task)
+            .await
+            .map_err(ExecutionError::ToolError)
+    }
+
+    #[instrument(name = "task", skip(self), fields(task_id = %task_id))]
+    async fn run_task(
+        &self,
+        task_id: TaskId,
+        resume: bool,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let start_time = std::time::Instant::now();
+
+        // Get task from storage
+        let mut task = self
+            .context
+            .storage
+            .get_task(&task_id)
             .await
-            .map_err(|e| ExecutionError::QualityCheckFailed(e.to_string()))?;
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))?
+            .ok_or(ExecutionError::TaskNotFound(task_id))?;
+
+        if self.context.dry_run {
+            return Ok(self.dry_run_task(&task, start_time));
+        }
+
+        match &task.verdict {
+            Some(Verdict::Deny { reason, .. }) => {
+                return Err(ExecutionError::NotAllowed(reason.clone()));
+            }
+            Some(Verdict::Defer { retry_after, .. }) => {
+                let intent = task.intent.clone().ok_or_else(|| {
+                    ExecutionError::NotAllowed("deferred task has no intent".to_string())
+                })?;
+                let retry_after = retry_after.unwrap_or_default();
+                let deferred = DeferredIntent::new(task_id.to_string(), intent);
+                return self.defer_task(task, deferred, retry_after, true).await;
+            }
+            _ => {}
+        }
+
+        info!(
+            "{} task: {:?} ({})",
+            if resume { "Resuming" } else { "Executing" },
+            task_id,
+            task.title
+        );
+
+        if task.state != TaskState::InProgress {
+            // Transition: Pending -> Preparing
+            self.context
+                .workflow_engine
+                .transition(&mut task, TaskState::Preparing)
+                .await
+                .map_err(|_e| ExecutionError::InvalidStateTransition {
+                    from: task.state.clone(),
+                    to: TaskState::Preparing,
+                })?;
+
+            // Transition: Preparing -> InProgress
+            self.context
+                .workflow_engine
+                .transition(&mut task, TaskState::InProgress)
+                .await
+                .map_err(|_e| ExecutionError::InvalidStateTransition {
+                    from: task.state.clone(),
+                    to: TaskState::InProgress,
+                })?;
+        }
+
+        // Planned steps, or the intent's proposed action (as modified by the verdict)
+        if task.steps.is_empty()
+            && let Some(action) = self.verdict_action(&mut task)
+        {
+            task.steps.push(ExecutionStep {
+                step_id: 1,
+                action,
+                status: StepStatus::Pending,
+                result: None,
+                executed_at: None,
+            });
+        }
+
+        let saga_id = Self::saga_id(&task_id);
+        let mut saga = match resume {
+            true => SagaPlan::load(self.context.storage.as_ref(), &saga_id)
+                .await
+                .map_err(|e| ExecutionError::ToolError(e.to_string()))?,
+            false => None,
+        }
+        .unwrap_or_else(|| SagaPlan {
+            id: saga_id,
+            ..SagaPlan::new(task_id.to_string())
+        });
+
+        let worktree = match self.context.isolate_worktree {
+            true => Some(self.enter_worktree(&mut task, &mut saga).await?),
+            false => None,
+        };
+        let scoped = worktree.as_ref().map(|wt| self.scoped_to(wt));
+        let runner = scoped.as_ref().unwrap_or(self);
+
+        let mut outcome = runner.run_steps(&mut task, &mut saga, resume).await;
+        let mut output = "Task completed".to_string();
+        if let Some(wt) = &worktree {
+            outcome = match outcome {
+                Ok(()) => self.commit_worktree(&mut task, wt).await.map(|commit| {
+                    output = format!("Task completed on branch {} ({})", wt.branch, commit);
+                }),
+                Err(e) => {
+                    self.discard_worktree(&mut task, &mut saga, wt).await;
+                    Err(e)
+                }
+            };
+        }
+        outcome?;
 
         // Transition: AwaitingVerification -> Completed
         self.context
@@ -216,11 +767,7 @@ impl Executor {
             })?;
 
         // Save final state
-        self.context
-            .storage
-            .save_task(&task)
-            .await
-            .map_err(|e| ExecutionError::ToolError(e.to_string()))?;
+        self.checkpoint_task(&task).await?;
 
         let duration_ms = start_time.elapsed().as_millis() as u64;
 
@@ -229,7 +776,7 @@ impl Executor {
             task_id,
             final_state: task.state,
             steps: task.steps.clone(),
-            output: "Task completed".to_string(),
+            output,
             error: None,
             metrics: ExecutionMetrics {
                 total_duration_ms: duration_ms,
@@ -237,78 +784,680 @@ impl Executor {
                 checks_passed: 1,
                 checks_failed: 0,
             },
+            dry_run: false,
+            effects: Vec::new(),
+            compensations: Vec::new(),
         })
     }
 
-    /// Execute a single action
-    async fn execute_action(&self, task: &mut Task, action: &Action) -> Result<(), ExecutionError> {
-        let step = ExecutionStep {
-            step_id: task.steps.len() as u64 + 1,
-            action: action.clone(),
-            status: StepStatus::InProgress,
-            result: None,
-            executed_at: Some(chrono::Utc::now()),
-        };
+    /// Run the task's steps, then the discovery and quality gate chain
+    async fn run_steps(
+        &self,
+        task: &mut Task,
+        saga: &mut SagaPlan,
+        resume: bool,
+    ) -> Result<(), ExecutionError> {
+        for idx in 0..task.steps.len() {
+            if resume && task.steps[idx].status == StepStatus::Completed {
+                debug!("Skipping completed step {}", task.steps[idx].step_id);
+                continue;
+            }
+            self.execute_step(task, idx, saga).await?;
+        }
 
-        task.steps.push(step.clone());
+        // Transition: InProgress -> AwaitingVerification
+        self.context
+            .workflow_engine
+            .transition(task, TaskState::AwaitingVerification)
+            .await
+            .map_err(|_e| ExecutionError::InvalidStateTransition {
+                from: task.state.clone(),
+                to: TaskState::AwaitingVerification,
+            })?;
 
-        // Execute action
-        let result = match action {
-            Action::ReadFile { path } => self.execute_read_file(path).await,
-            Action::WriteFile { path, content } => self.execute_write_file(path, content).await,
-            _ => Ok(ActionResult {
-                success: true,
-                output: "Action not implemented".to_string(),
-                error: None,
-                ..Default::default()
-            }),
-        };
+        // Discovery -> HardConstraints -> QualityGate enforced chain
+        let hard_constraints = self.discover_hard_constraints(task).await?;
+        self.context.commit_gate.begin(hard_constraints.clone());
+        let changed_files = self.collect_affected_files(task);
+        self.context
+            .quality_runner
+            .run_for_files(
+                task.quality_gate.as_ref(),
+                hard_constraints.as_ref(),
+                &changed_files,
+            )
+            .await
+            .map_err(|e| ExecutionError::QualityCheckFailed(e.to_string()))
+    }
 
-        // Update step result
-        let result_val = result?;
-        let step_idx = task
-            .steps
-            .iter()
-            .position(|s| s.step_id == step.step_id)
-            .expect("step must exist in task");
-        task.steps[step_idx].status = StepStatus::Completed;
-        task.steps[step_idx].result = Some(result_val);
+    /// Create (or reuse, on resume) the task's worktree and point its steps at it
+    ///
+    /// The worktree is recorded as a saga step whose compensation removes it,
+    /// and as a worktree snapshot on the task.
+    async fn enter_worktree(
+        &self,
+        task: &mut Task,
+        saga: &mut SagaPlan,
+    ) -> Result<TaskWorktree, ExecutionError> {
+        let root = &self.context.project_root;
+        let worktree =
+            TaskWorktree::create(root, &task.id.to_string()).map_err(ExecutionError::ToolError)?;
+        for step in &mut task.steps {
+            step.action = rebase_action(&step.action, |path| worktree.rebase_path(root, path));
+        }
 
-        Ok(())
+        let step_id = StepId("worktree".to_string());
+        if !saga.steps.iter().any(|s| s.step_id == step_id) {
+            saga.add_step(
+                step_id.clone(),
+                StepAction::CreateWorktree {
+                    path: worktree.path.clone(),
+                    branch: worktree.branch.clone(),
+                    base_commit: worktree.base_commit.clone(),
+                },
+                Some(UndoAction::RemoveWorktree {
+                    repo_root: worktree.repo_root.clone(),
+                    path: worktree.path.clone(),
+                    branch: worktree.branch.clone(),
+                }),
+            );
+            saga.mark_completed(&step_id);
+            task.capture_worktree_snapshot(
+                worktree.path.clone(),
+                worktree.base_commit.clone(),
+                worktree.branch.clone(),
+                Vec::new(),
+                format!("worktree created for task {}", task.id),
+            );
+            self.checkpoint_saga(saga).await?;
+        }
+        self.checkpoint_task(task).await?;
+        info!(
+            "Task {:?} isolated in worktree {} ({})",
+            task.id,
+            worktree.path.display(),
+            worktree.branch
+        );
+        Ok(worktree)
     }
 
-    async fn discover_hard_constraints(
+    /// Executor whose project root is the worktree's copy of ours
+    fn scoped_to(&self, worktree: &TaskWorktree) -> Executor {
+        Executor::new(ExecutionContext {
+            project_root: worktree.scoped_root(&self.context.project_root),
+            isolate_worktree: false,
+            // Keep backups out of the worktree, which is committed and removed
+            backup_dir: Some(self.context.backup_store().root().to_path_buf()),
+            ..(*self.context).clone()
+        })
+    }
+
+    /// Commit the task's changes on its branch so it is ready to merge
+    async fn commit_worktree(
         &self,
-        task: &Task,
-    ) -> Result<Option<HardConstraints>, ExecutionError> {
-        let affected_files = self.collect_affected_files(task);
-        if affected_files.is_empty() {
-            return Ok(None);
+        task: &mut Task,
+        worktree: &TaskWorktree,
+    ) -> Result<String, ExecutionError> {
+        let commit = worktree
+            .commit_all(&format!("ndc: {} ({})", task.title, task.id))
+            .map_err(ExecutionError::ToolError)?;
+        let affected = self
+            .collect_affected_files(task)
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(&worktree.path)
+                    .map_or(path.clone(), Path::to_path_buf)
+            })
+            .collect();
+        task.capture_worktree_snapshot(
+            worktree.path.clone(),
+            commit.clone(),
+            worktree.branch.clone(),
+            affected,
+            format!("task changes committed on {}", worktree.branch),
+        );
+        self.checkpoint_task(task).await?;
+        Ok(commit)
+    }
+
+    /// Saga compensation for a failed isolated task: drop its worktree and branch
+    ///
+    /// Every completed step only touched the worktree, so they are all rolled
+    /// back with it and will be replayed by a resume.
+    async fn discard_worktree(
+        &self,
+        task: &mut Task,
+        saga: &mut SagaPlan,
+        worktree: &TaskWorktree,
+    ) {
+        if let Err(e) = TaskWorktree::remove(&worktree.repo_root, &worktree.path, &worktree.branch)
+        {
+            warn!(task_id = %task.id, error = %e, "Failed to remove task worktree");
+            return;
+        }
+        for step in &mut saga.steps {
+            if step.status == SagaStepStatus::Completed {
+                step.status = SagaStepStatus::RolledBack;
+            }
+        }
+        for step in &mut task.steps {
+            if step.status == StepStatus::Completed {
+                step.status = StepStatus::Pending;
+            }
+        }
+        if let Err(e) = self.checkpoint_saga(saga).await {
+            warn!(task_id = %task.id, error = %e, "Failed to checkpoint saga after worktree removal");
         }
+        if let Err(e) = self.checkpoint_task(task).await {
+            warn!(task_id = %task.id, error = %e, "Failed to checkpoint task after worktree removal");
+        }
+    }
 
-        let discovery = DiscoveryService::new(self.context.project_root.clone(), None);
-        let mode = Self::resolve_discovery_failure_mode();
-        match discovery
-            .discover(
-                task.id.to_string(),
-                task.description.clone(),
-                affected_files,
-            )
+    /// Saga plan id for a task, stable across resumes
+    pub fn saga_id(task_id: &TaskId) -> SagaId {
+        SagaId(format!("saga-{}", task_id))
+    }
+
+    async fn checkpoint_task(&self, task: &Task) -> Result<(), ExecutionError> {
+        self.context
+            .storage
+            .save_task(task)
             .await
-        {
-            Ok(result) => {
-                if let Some(ref constraints) = result.hard_constraints {
-                    let summary = constraints.summary();
-                    let total_constraints = summary.regression_test_count
-                        + summary.api_symbol_count
-                        + summary.high_volatility_count
-                        + summary.coupling_warning_count
-                        + summary.version_constraint_count
-                        + summary.validation_count;
-                    let signal = DiscoverySignal {
-                        dedupe_key: format!("task:{}:discovery_hard_constraints", task.id),
-                        rule: format!(
-                            "Discovery generated {} hard constraints for task {}",
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))
+    }
+
+    /// Walk the actions `run_task` would execute without executing them
+    ///
+    /// Uses the planned steps, or else the verdict's (possibly modified)
+    /// action, the same way `run_task` resolves them.
+    fn dry_run_task(&self, task: &Task, start_time: std::time::Instant) -> ExecutionResult {
+        info!("Dry run: {:?} ({})", task.id, task.title);
+
+        let actions: Vec<Action> = match task.steps.is_empty() {
+            true => Self::resolved_action(task).into_iter().collect(),
+            false => task.steps.iter().map(|step| step.action.clone()).collect(),
+        };
+        let (mut effects, mut compensations) = (Vec::new(), Vec::new());
+        for action in &actions {
+            let (action_effects, action_compensations) = self.plan_action(action);
+            effects.extend(action_effects);
+            compensations.extend(action_compensations);
+        }
+
+        let mut output = format!("Dry run: {} effect(s), nothing executed", effects.len());
+        for effect in &effects {
+            output.push_str(&format!("\n- {}", describe_effect(effect)));
+        }
+
+        ExecutionResult {
+            success: true,
+            task_id: task.id,
+            final_state: task.state.clone(),
+            steps: Vec::new(),
+            output,
+            error: None,
+            metrics: ExecutionMetrics {
+                total_duration_ms: start_time.elapsed().as_millis() as u64,
+                ..Default::default()
+            },
+            dry_run: true,
+            effects,
+            compensations,
+        }
+    }
+
+    /// Effects and saga steps an action would produce
+    ///
+    /// Files about to be overwritten or deleted are backed up into the backup
+    /// store; in a dry run they are only hashed, so nothing is written.
+    pub fn plan_action(&self, action: &Action) -> (Vec<Effect>, Vec<SagaStep>) {
+        let mut effects = Vec::new();
+        let mut plan = SagaPlan::new(String::new());
+
+        match action {
+            Action::ReadFile { path, .. } => effects.push(file_effect(path, FileOp::Read)),
+            Action::WriteFile { path, .. } => {
+                if self.context.project_root.join(path).exists() {
+                    effects.push(file_effect(path, FileOp::Write));
+                    let backup = self.backup_file(path).ok();
+                    // Without a backup there is nothing safe to undo to
+                    let undo = backup
+                        .as_ref()
+                        .map(|_| UndoAction::from_modify_file(path, &backup));
+                    plan.add_step(
+                        StepId::default(),
+                        StepAction::ModifyFile {
+                            path: path.clone(),
+                            backup,
+                        },
+                        undo,
+                    );
+                } else {
+                    effects.push(file_effect(path, FileOp::Create));
+                    plan.add_step(
+                        StepId::default(),
+                        StepAction::CreateFile { path: path.clone() },
+                        Some(UndoAction::from_create_file(path)),
+                    );
+                }
+            }
+            Action::CreateFile { path } => {
+                effects.push(file_effect(path, FileOp::Create));
+                plan.add_step(
+                    StepId::default(),
+                    StepAction::CreateFile { path: path.clone() },
+                    Some(UndoAction::from_create_file(path)),
+                );
+            }
+            Action::DeleteFile { path } => {
+                effects.push(file_effect(path, FileOp::Delete));
+                let backup = self.backup_file(path).ok();
+                let undo = backup.clone().map(|hash| UndoAction::RestoreFile {
+                    path: path.clone(),
+                    hash,
+                });
+                plan.add_step(
+                    StepId::default(),
+                    StepAction::DeleteFile {
+                        path: path.clone(),
+                        backup,
+                    },
+                    undo,
+                );
+            }
+            Action::MoveFile { from, to } => {
+                effects.push(file_effect(from, FileOp::Delete));
+                effects.push(file_effect(to, FileOp::Create));
+                let (step, undo) = UndoAction::from_move_file(from, to);
+                plan.add_step(StepId::default(), step, Some(undo));
+            }
+            Action::RunCommand { command, args } => {
+                effects.push(Effect::ToolInvocation {
+                    tool: command.clone(),
+                    args: args.clone(),
+                });
+                plan.add_step(
+                    StepId::default(),
+                    StepAction::RunCommand {
+                        command: std::iter::once(command.as_str())
+                            .chain(args.iter().map(String::as_str))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        working_dir: Some(self.context.project_root.clone()),
+                    },
+                    None,
+                );
+            }
+            Action::Git { operation } => effects.push(Effect::ToolInvocation {
+                tool: "git".to_string(),
+                args: vec![format!("{:?}", operation)],
+            }),
+            _ => {}
+        }
+
+        (effects, plan.steps)
+    }
+
+    /// Back up a project file, returning its blob hash
+    fn backup_file(&self, path: &Path) -> std::io::Result<String> {
+        let resolved = self.context.project_root.join(path);
+        if self.context.dry_run {
+            return Ok(BackupStore::hash(&std::fs::read(resolved)?));
+        }
+        let hash = self.context.backup_store().put_file(&resolved);
+        if let Err(e) = &hash {
+            warn!(path = %path.display(), error = %e, "Failed to back up file");
+        }
+        hash
+    }
+
+    /// Execute one step, checkpointing the task and saga before and after
+    #[instrument(
+        name = "step",
+        skip_all,
+        fields(step = idx, step_id = %task.steps[idx].step_id)
+    )]
+    async fn execute_step(
+        &self,
+        task: &mut Task,
+        idx: usize,
+        saga: &mut SagaPlan,
+    ) -> Result<(), ExecutionError> {
+        let action = task.steps[idx].action.clone();
+        let prefix = format!("step-{}-", task.steps[idx].step_id);
+
+        // Keep the compensations of an attempt that never completed: the files
+        // may already be partly written, so re-planning would back up the wrong content
+        let mut saga_steps: Vec<StepId> = saga
+            .steps
+            .iter_mut()
+            .filter(|s| s.step_id.0.starts_with(&prefix) && s.status != SagaStepStatus::Completed)
+            .map(|s| {
+                s.status = SagaStepStatus::Pending;
+                s.step_id.clone()
+            })
+            .collect();
+        if saga_steps.is_empty() {
+            let (_, compensations) = self.plan_action(&action);
+            for (n, step) in compensations.into_iter().enumerate() {
+                let step_id = StepId(format!("{}{}", prefix, n + 1));
+                saga.add_step(step_id.clone(), step.action, step.undo_action);
+                saga_steps.push(step_id);
+            }
+        }
+
+        task.steps[idx].status = StepStatus::InProgress;
+        task.steps[idx].executed_at = Some(self.context.clock.now());
+        self.checkpoint_task(task).await?;
+        self.checkpoint_saga(saga).await?;
+
+        // Execute action
+        let result = self.execute_action_with_retry(task, idx, &action).await;
+
+        // Update step result
+        match result {
+            Ok(result_val) => {
+                task.steps[idx].status = StepStatus::Completed;
+                task.steps[idx].result = Some(result_val);
+                for step_id in &saga_steps {
+                    saga.mark_completed(step_id);
+                }
+                self.checkpoint_saga(saga).await?;
+                self.checkpoint_task(task).await
+            }
+            Err(e) => {
+                task.steps[idx].status = StepStatus::Failed;
+                task.steps[idx].result = Some(ActionResult {
+                    success: false,
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                });
+                for step_id in &saga_steps {
+                    saga.mark_failed(step_id);
+                }
+                self.checkpoint_saga(saga).await?;
+                self.checkpoint_task(task).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Execute a step's action, retrying transient command failures
+    ///
+    /// Every attempt emits `StepStarted` with its attempt number in metadata.
+    async fn execute_action_with_retry(
+        &self,
+        task: &Task,
+        idx: usize,
+        action: &Action,
+    ) -> Result<ActionResult, ExecutionError> {
+        let policy = &self.context.retry_policy;
+        let mut attempt = 1;
+        loop {
+            self.emit_step_started(task, idx, attempt);
+            let result = self.execute_action(action).await;
+            let retryable = match &result {
+                Err(ExecutionError::CommandFailed {
+                    exit_code, stderr, ..
+                }) => policy.is_retryable(*exit_code, stderr),
+                _ => false,
+            };
+            if !retryable || attempt >= policy.max_attempts {
+                return result;
+            }
+            let delay = policy.backoff(attempt);
+            warn!(
+                task_id = %task.id,
+                step = task.steps[idx].step_id,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "Transient step failure, retrying"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    fn emit_step_started(&self, task: &Task, idx: usize, attempt: u32) {
+        let step = &task.steps[idx];
+        self.context.events.emit(&Event {
+            id: EventId::default(),
+            event_type: EventType::StepStarted,
+            data: EventData::Step {
+                step_number: step.step_id as u32,
+                description: format!("{:?}", step.action),
+            },
+            task_id: Some(task.id.to_string()),
+            step_id: Some(step.step_id.to_string()),
+            timestamp: self.context.clock.now(),
+            metadata: [("attempt".to_string(), attempt.to_string())].into(),
+        });
+    }
+
+    /// Action to run for a task without planned steps
+    ///
+    /// For a `Modify` verdict this is the modified action; the substitution is
+    /// recorded in the task's work records and announced as `VerdictModified`.
+    fn verdict_action(&self, task: &mut Task) -> Option<Action> {
+        let Some(Verdict::Modify {
+            original_action,
+            modified_action,
+            reason,
+            warnings,
+        }) = task.verdict.clone()
+        else {
+            return Self::resolved_action(task);
+        };
+        task.metadata.work_records.push(WorkRecord {
+            id: ulid::Ulid::new(),
+            timestamp: self.context.clock.now(),
+            event: WorkEvent::ActionModified {
+                original: original_action,
+                reason: reason.clone(),
+                warnings: warnings.clone(),
+            },
+            executor: ndc_core::Executor::System,
+            result: WorkResult::Success,
+        });
+        self.emit_verdict_modified(Some(&task.id), &modified_action, reason, warnings);
+        Some(modified_action)
+    }
+
+    /// The verdict's modified action, or else the intent's proposed one
+    fn resolved_action(task: &Task) -> Option<Action> {
+        match &task.verdict {
+            Some(Verdict::Modify {
+                modified_action, ..
+            }) => Some(modified_action.clone()),
+            _ => task
+                .intent
+                .as_ref()
+                .map(|intent| intent.proposed_action.clone()),
+        }
+    }
+
+    fn emit_verdict_modified(
+        &self,
+        task_id: Option<&TaskId>,
+        modified_action: &Action,
+        reason: String,
+        warnings: Vec<String>,
+    ) {
+        warn!(
+            task_id = ?task_id,
+            reason = %reason,
+            warnings = ?warnings,
+            "Decision engine modified action"
+        );
+        self.context.events.emit(&Event {
+            id: EventId::default(),
+            event_type: EventType::VerdictModified,
+            data: EventData::Verdict { reason, warnings },
+            task_id: task_id.map(TaskId::to_string),
+            step_id: None,
+            timestamp: self.context.clock.now(),
+            metadata: [(
+                "modified_action".to_string(),
+                format!("{:?}", modified_action),
+            )]
+            .into(),
+        });
+    }
+
+    /// Execute the action of an `Allow` verdict, honouring its conditions
+    ///
+    /// With `RequireBackup`, the target file is snapshotted into the saga as
+    /// `UndoAction::RestoreFile` before the action runs. If the snapshot cannot
+    /// be taken the action is refused and nothing is changed. A `Modify`
+    /// verdict runs its modified action instead of the original.
+    pub async fn execute_verdict(
+        &self,
+        verdict: &Verdict,
+        saga: &mut SagaPlan,
+    ) -> Result<ActionResult, ExecutionError> {
+        let no_conditions = Vec::new();
+        let (action, conditions) = match verdict {
+            Verdict::Allow {
+                action, conditions, ..
+            } => (action, conditions),
+            Verdict::Modify {
+                modified_action,
+                reason,
+                warnings,
+                ..
+            } => {
+                self.emit_verdict_modified(None, modified_action, reason.clone(), warnings.clone());
+                (modified_action, &no_conditions)
+            }
+            Verdict::Deny { reason, .. } => return Err(ExecutionError::NotAllowed(reason.clone())),
+            other => {
+                return Err(ExecutionError::NotAllowed(format!(
+                    "verdict is not Allow: {:?}",
+                    other
+                )));
+            }
+        };
+
+        let requires_backup = conditions
+            .iter()
+            .any(|c| c.condition_type == ConditionType::RequireBackup);
+        let steps = if requires_backup {
+            self.backup_steps(action)?
+        } else {
+            self.plan_action(action).1
+        };
+
+        let mut step_ids = Vec::new();
+        for step in steps {
+            let step_id = StepId(format!("verdict-{}", saga.steps.len() + 1));
+            saga.add_step(step_id.clone(), step.action, step.undo_action);
+            step_ids.push(step_id);
+        }
+        self.checkpoint_saga(saga).await?;
+
+        let result = self.execute_action(action).await;
+        for step_id in &step_ids {
+            match &result {
+                Ok(r) if r.success => saga.mark_completed(step_id),
+                _ => saga.mark_failed(step_id),
+            }
+        }
+        self.checkpoint_saga(saga).await?;
+        result
+    }
+
+    /// Saga steps that back up the target of a write/delete, or an error if it cannot be read
+    fn backup_steps(&self, action: &Action) -> Result<Vec<SagaStep>, ExecutionError> {
+        let (path, is_delete) = match action {
+            Action::WriteFile { path, .. } => (path, false),
+            Action::DeleteFile { path } => (path, true),
+            _ => return Ok(self.plan_action(action).1),
+        };
+        let backup = self
+            .backup_file(path)
+            .map_err(|e| ExecutionError::BackupFailed(format!("{}: {}", path.display(), e)))?;
+
+        let step = match is_delete {
+            true => StepAction::DeleteFile {
+                path: path.clone(),
+                backup: Some(backup.clone()),
+            },
+            false => StepAction::ModifyFile {
+                path: path.clone(),
+                backup: Some(backup.clone()),
+            },
+        };
+        let mut plan = SagaPlan::new(String::new());
+        plan.add_step(
+            StepId::default(),
+            step,
+            Some(UndoAction::RestoreFile {
+                path: path.clone(),
+                hash: backup,
+            }),
+        );
+        Ok(plan.steps)
+    }
+
+    #[instrument(name = "tool", skip_all, fields(action = action_name(action)))]
+    async fn execute_action(&self, action: &Action) -> Result<ActionResult, ExecutionError> {
+        debug!("Executing action");
+        match action {
+            Action::ReadFile { path, range } => self.execute_read_file(path, range.as_ref()).await,
+            Action::WriteFile { path, content } => self.execute_write_file(path, content).await,
+            Action::DeleteFile { path } => self.execute_delete_file(path).await,
+            Action::MoveFile { from, to } => self.execute_move_file(from, to).await,
+            Action::RunCommand { command, args } => self.execute_run_command(command, args).await,
+            _ => Ok(ActionResult {
+                success: true,
+                output: "Action not implemented".to_string(),
+                error: None,
+                ..Default::default()
+            }),
+        }
+    }
+
+    async fn checkpoint_saga(&self, saga: &SagaPlan) -> Result<(), ExecutionError> {
+        if saga.steps.is_empty() {
+            return Ok(());
+        }
+        saga.save(self.context.storage.as_ref())
+            .await
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))
+    }
+
+    async fn discover_hard_constraints(
+        &self,
+        task: &Task,
+    ) -> Result<Option<HardConstraints>, ExecutionError> {
+        let affected_files = self.collect_affected_files(task);
+        if affected_files.is_empty() {
+            return Ok(None);
+        }
+
+        let discovery = DiscoveryService::new(self.context.project_root.clone(), None);
+        let mode = Self::resolve_discovery_failure_mode();
+        match discovery
+            .discover(
+                task.id.to_string(),
+                task.description.clone(),
+                affected_files,
+            )
+            .await
+        {
+            Ok(result) => {
+                if let Some(ref constraints) = result.hard_constraints {
+                    let summary = constraints.summary();
+                    let total_constraints = summary.regression_test_count
+                        + summary.api_symbol_count
+                        + summary.high_volatility_count
+                        + summary.coupling_warning_count
+                        + summary.version_constraint_count
+                        + summary.validation_count;
+                    let signal = DiscoverySignal {
+                        dedupe_key: format!("task:{}:discovery_hard_constraints", task.id),
+                        rule: format!(
+                            "Discovery generated {} hard constraints for task {}",
                             total_constraints, task.id
                         ),
                         description: "Execution must respect generated hard constraints"
@@ -453,13 +1602,14 @@ impl Executor {
         service: &ndc_core::GoldMemoryService,
         migrated_from_v1: bool,
     ) -> Result<(), ExecutionError> {
+        let now = self.context.clock.now();
         let wrapper = serde_json::json!({
             "version": 2,
             "service": service,
             "migration": if migrated_from_v1 {
                 serde_json::json!({
                     "from_version": 1,
-                    "migrated_at": chrono::Utc::now(),
+                    "migrated_at": now,
                     "trigger_task_id": task_id.to_string(),
                     "trigger_source": "executor_discovery"
                 })
@@ -478,12 +1628,13 @@ impl Executor {
             relations: Vec::new(),
             metadata: MemoryMetadata {
                 stability: MemoryStability::Canonical,
-                created_at: chrono::Utc::now(),
+                created_at: now,
                 created_by: AgentId::system(),
                 source_task: *task_id,
                 version: 2,
-                modified_at: Some(chrono::Utc::now()),
+                modified_at: Some(now),
                 tags: vec!["gold-memory".to_string(), "discovery".to_string()],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::system(), MemoryStability::Canonical),
         };
@@ -514,10 +1665,11 @@ impl Executor {
 
     fn action_paths(action: &Action) -> Vec<std::path::PathBuf> {
         match action {
-            Action::ReadFile { path }
+            Action::ReadFile { path, .. }
             | Action::WriteFile { path, .. }
             | Action::CreateFile { path }
             | Action::DeleteFile { path } => vec![path.clone()],
+            Action::MoveFile { from, to } => vec![from.clone(), to.clone()],
             _ => Vec::new(),
         }
     }
@@ -526,6 +1678,7 @@ impl Executor {
     async fn execute_read_file(
         &self,
         path: &std::path::PathBuf,
+        range: Option<&ByteRange>,
     ) -> Result<ActionResult, ExecutionError> {
         let tool = self
             .context
@@ -533,12 +1686,19 @@ impl Executor {
             .get("fs")
             .ok_or_else(|| ExecutionError::ToolError("FsTool not found".to_string()))?;
 
+        let mut params = serde_json::json!({
+            "operation": "read",
+            "path": path.to_string_lossy(),
+            "working_dir": self.context.project_root.to_string_lossy(),
+        });
+        if let Some(range) = range {
+            params["byte_start"] = range.start.into();
+            if let Some(end) = range.end {
+                params["byte_end"] = end.into();
+            }
+        }
         let result = tool
-            .execute(&serde_json::json!({
-                "operation": "read",
-                "path": path.to_string_lossy(),
-                "working_dir": self.context.project_root.to_string_lossy(),
-            }))
+            .execute(&params)
             .await
             .map_err(|e| ExecutionError::ToolError(e.to_string()))?;
 
@@ -550,6 +1710,67 @@ impl Executor {
         })
     }
 
+    /// Execute a shell command; a non-zero exit is `CommandFailed`
+    async fn execute_run_command(
+        &self,
+        command: &str,
+        args: &[String],
+    ) -> Result<ActionResult, ExecutionError> {
+        let tool = self
+            .context
+            .tools
+            .get("shell")
+            .ok_or_else(|| ExecutionError::ToolError("ShellTool not found".to_string()))?;
+        let display = std::iter::once(command)
+            .chain(args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let result = match tool
+            .execute(&serde_json::json!({
+                "command": command,
+                "args": args,
+                "working_dir": self.context.project_root.to_string_lossy(),
+            }))
+            .await
+        {
+            Ok(result) => result,
+            Err(crate::tools::ToolError::Timeout(message)) => {
+                return Err(ExecutionError::CommandFailed {
+                    command: display,
+                    exit_code: None,
+                    stderr: message,
+                });
+            }
+            Err(e) => return Err(ExecutionError::ToolError(e.to_string())),
+        };
+
+        if !result.success {
+            let exit_code = result
+                .metadata
+                .structured
+                .as_ref()
+                .and_then(|s| s.get("exit_code"))
+                .and_then(|v| v.as_i64())
+                .map(|code| code as i32);
+            return Err(ExecutionError::CommandFailed {
+                command: display,
+                exit_code,
+                stderr: result.error.unwrap_or(result.output),
+            });
+        }
+
+        Ok(ActionResult {
+            success: true,
+            output: result.output,
+            error: None,
+            metrics: ndc_core::ActionMetrics {
+                duration_ms: result.metadata.execution_time_ms,
+                ..Default::default()
+            },
+        })
+    }
+
     /// Execute write file
     async fn execute_write_file(
         &self,
@@ -579,6 +1800,102 @@ impl Executor {
             ..Default::default()
         })
     }
+
+    /// Execute delete file
+    async fn execute_delete_file(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<ActionResult, ExecutionError> {
+        let tool = self
+            .context
+            .tools
+            .get("fs")
+            .ok_or_else(|| ExecutionError::ToolError("FsTool not found".to_string()))?;
+
+        let result = tool
+            .execute(&serde_json::json!({
+                "operation": "delete",
+                "path": path.to_string_lossy(),
+                "working_dir": self.context.project_root.to_string_lossy(),
+            }))
+            .await
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))?;
+
+        Ok(ActionResult {
+            success: result.success,
+            output: result.output,
+            error: result.error,
+            ..Default::default()
+        })
+    }
+
+    /// Execute move file
+    async fn execute_move_file(
+        &self,
+        from: &std::path::Path,
+        to: &std::path::Path,
+    ) -> Result<ActionResult, ExecutionError> {
+        let tool = self
+            .context
+            .tools
+            .get("fs")
+            .ok_or_else(|| ExecutionError::ToolError("FsTool not found".to_string()))?;
+
+        let result = tool
+            .execute(&serde_json::json!({
+                "operation": "move",
+                "path": from.to_string_lossy(),
+                "to": to.to_string_lossy(),
+                "working_dir": self.context.project_root.to_string_lossy(),
+            }))
+            .await
+            .map_err(|e| ExecutionError::ToolError(e.to_string()))?;
+
+        Ok(ActionResult {
+            success: result.success,
+            output: result.output,
+            error: result.error,
+            ..Default::default()
+        })
+    }
+}
+
+/// Rewrite every path of an action
+fn rebase_action(action: &Action, rebase: impl Fn(&Path) -> PathBuf) -> Action {
+    match action {
+        Action::ReadFile { path, range } => Action::ReadFile {
+            path: rebase(path),
+            range: *range,
+        },
+        Action::WriteFile { path, content } => Action::WriteFile {
+            path: rebase(path),
+            content: content.clone(),
+        },
+        Action::CreateFile { path } => Action::CreateFile { path: rebase(path) },
+        Action::DeleteFile { path } => Action::DeleteFile { path: rebase(path) },
+        Action::MoveFile { from, to } => Action::MoveFile {
+            from: rebase(from),
+            to: rebase(to),
+        },
+        other => other.clone(),
+    }
+}
+
+fn file_effect(path: &std::path::Path, operation: FileOp) -> Effect {
+    Effect::FileOperation {
+        path: path.to_path_buf(),
+        operation,
+    }
+}
+
+fn describe_effect(effect: &Effect) -> String {
+    match effect {
+        Effect::FileOperation { path, operation } => {
+            format!("{:?} {}", operation, path.display())
+        }
+        Effect::ToolInvocation { tool, args } => format!("run {} {}", tool, args.join(" ")),
+        other => format!("{:?}", other),
+    }
 }
 
 struct DiscoverySignal {
@@ -673,6 +1990,7 @@ mod tests {
             agent_role: AgentRole::Implementer,
             proposed_action: Action::ReadFile {
                 path: file_path.clone(),
+                range: None,
             },
             effects: Vec::new(),
             reasoning: "read source".to_string(),
@@ -695,4 +2013,1184 @@ mod tests {
             std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
         }
     }
+
+    #[tokio::test]
+    async fn test_executor_timestamps_follow_injected_clock() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("main.rs");
+        std::fs::write(&file_path, "fn main() {}").unwrap();
+        let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let clock = ndc_core::MockClock::new(start);
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            clock: Arc::new(clock.clone()),
+            ..Default::default()
+        });
+
+        let mut task = executor
+            .create_task(
+                "clocked".to_string(),
+                "timestamps come from the clock".to_string(),
+                AgentRole::Implementer,
+            )
+            .await
+            .unwrap();
+        assert_eq!(task.metadata.created_at, start);
+
+        task.intent = Some(ndc_core::Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Implementer,
+            proposed_action: Action::ReadFile {
+                path: file_path,
+                range: None,
+            },
+            effects: Vec::new(),
+            reasoning: "read source".to_string(),
+            task_id: Some(task.id),
+            timestamp: start,
+        });
+        executor.context().storage.save_task(&task).await.unwrap();
+        clock.advance(chrono::Duration::hours(1));
+
+        let result = executor.execute_task(task.id).await.unwrap();
+        assert!(!result.steps.is_empty());
+        let executed = start + chrono::Duration::hours(1);
+        assert!(
+            result
+                .steps
+                .iter()
+                .all(|step| step.executed_at == Some(executed))
+        );
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    async fn dry_run_executor(root: &std::path::Path, action: Action) -> (Executor, TaskId) {
+        let context = ExecutionContext {
+            project_root: root.to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+        let executor = Executor::new(context);
+
+        let mut task = executor
+            .create_task(
+                "dry run".to_string(),
+                "plan only".to_string(),
+                AgentRole::Implementer,
+            )
+            .await
+            .unwrap();
+        task.intent = Some(ndc_core::Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Implementer,
+            proposed_action: action,
+            effects: Vec::new(),
+            reasoning: "preview".to_string(),
+            task_id: Some(task.id),
+            timestamp: chrono::Utc::now(),
+        });
+        executor.context().storage.save_task(&task).await.unwrap();
+        (executor, task.id)
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_write_leaves_files_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("existing.rs");
+        std::fs::write(&existing, "old").unwrap();
+
+        let (executor, task_id) = dry_run_executor(
+            temp_dir.path(),
+            Action::WriteFile {
+                path: existing.clone(),
+                content: "new".to_string(),
+            },
+        )
+        .await;
+        let result = executor.execute_task(task_id).await.unwrap();
+
+        assert!(result.dry_run);
+        assert!(result.steps.is_empty());
+        assert_eq!(result.final_state, TaskState::Pending);
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "old");
+        assert!(matches!(
+            result.effects.as_slice(),
+            [Effect::FileOperation {
+                operation: FileOp::Write,
+                ..
+            }]
+        ));
+        assert_eq!(result.compensations.len(), 1);
+        let Some(UndoAction::RestoreFile { hash, .. }) = &result.compensations[0].undo_action
+        else {
+            panic!("expected RestoreFile");
+        };
+        assert_eq!(*hash, BackupStore::hash(b"old"));
+        // Dry runs only hash; no blob is written
+        assert!(!executor.context().backup_store().contains(hash));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_create_and_command_are_not_performed() {
+        let temp_dir = TempDir::new().unwrap();
+        let new_file = temp_dir.path().join("new.rs");
+
+        let (executor, task_id) = dry_run_executor(
+            temp_dir.path(),
+            Action::WriteFile {
+                path: new_file.clone(),
+                content: "fn main() {}".to_string(),
+            },
+        )
+        .await;
+        let result = executor.execute_task(task_id).await.unwrap();
+        assert!(!new_file.exists());
+        assert!(matches!(
+            result.effects.as_slice(),
+            [Effect::FileOperation {
+                operation: FileOp::Create,
+                ..
+            }]
+        ));
+        assert!(matches!(
+            result.compensations[0].undo_action,
+            Some(UndoAction::DeleteFile { .. })
+        ));
+
+        let marker = temp_dir.path().join("marker");
+        let (executor, task_id) = dry_run_executor(
+            temp_dir.path(),
+            Action::RunCommand {
+                command: "touch".to_string(),
+                args: vec![marker.to_string_lossy().to_string()],
+            },
+        )
+        .await;
+        let result = executor.execute_task(task_id).await.unwrap();
+        assert!(!marker.exists());
+        assert!(result.output.contains("run touch"));
+        assert!(matches!(
+            &result.compensations[0].action,
+            StepAction::RunCommand { command, .. } if command.starts_with("touch ")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_plans_the_actions_run_task_would_execute() {
+        let temp_dir = TempDir::new().unwrap();
+        let proposed = temp_dir.path().join("proposed.rs");
+        let modified = temp_dir.path().join("modified.rs");
+        let planned = temp_dir.path().join("planned.rs");
+        let write = |path: &std::path::Path| Action::WriteFile {
+            path: path.to_path_buf(),
+            content: "fn main() {}".to_string(),
+        };
+
+        let (executor, task_id) = dry_run_executor(temp_dir.path(), write(&proposed)).await;
+        let storage = executor.context().storage.clone();
+        let mut task = storage.get_task(&task_id).await.unwrap().unwrap();
+        task.verdict = Some(Verdict::Modify {
+            original_action: write(&proposed),
+            modified_action: write(&modified),
+            reason: "narrowed".to_string(),
+            warnings: Vec::new(),
+        });
+        storage.save_task(&task).await.unwrap();
+
+        let result = executor.execute_task(task_id).await.unwrap();
+        assert!(matches!(
+            result.effects.as_slice(),
+            [Effect::FileOperation { path, .. }] if *path == modified
+        ));
+
+        task.steps = vec![ExecutionStep {
+            step_id: 1,
+            action: write(&planned),
+            status: StepStatus::Pending,
+            result: None,
+            executed_at: None,
+        }];
+        storage.save_task(&task).await.unwrap();
+
+        let result = executor.execute_task(task_id).await.unwrap();
+        assert!(matches!(
+            result.effects.as_slice(),
+            [Effect::FileOperation { path, .. }] if *path == planned
+        ));
+        assert!(!planned.exists() && !modified.exists());
+    }
+
+    fn allow(action: Action, require_backup: bool) -> Verdict {
+        Verdict::Allow {
+            action,
+            privilege: ndc_core::PrivilegeLevel::High,
+            conditions: match require_backup {
+                true => vec![ndc_core::Condition {
+                    condition_type: ConditionType::RequireBackup,
+                    description: "backup".to_string(),
+                }],
+                false => vec![],
+            },
+        }
+    }
+
+    fn restore(store: &BackupStore, undo: UndoAction) -> Result<(), String> {
+        match undo {
+            UndoAction::RestoreFile { path, hash } => {
+                store.restore(&hash, &path).map_err(|e| e.to_string())
+            }
+            other => Err(format!("unexpected undo: {:?}", other)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_require_backup_rollback_restores_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("logo.bin");
+        let doomed = temp_dir.path().join("old.rs");
+        let original = vec![0x89, b'P', b'N', b'G', 0x00, 0xff, 0xfe];
+        std::fs::write(&target, &original).unwrap();
+        std::fs::write(&doomed, "keep me").unwrap();
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let mut saga = SagaPlan::new("verdict".to_string());
+
+        let write = Action::WriteFile {
+            path: target.clone(),
+            content: "rewritten".to_string(),
+        };
+        executor
+            .execute_verdict(&allow(write, true), &mut saga)
+            .await
+            .unwrap();
+        let delete = Action::DeleteFile {
+            path: doomed.clone(),
+        };
+        executor
+            .execute_verdict(&allow(delete, true), &mut saga)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "rewritten");
+        assert!(!doomed.exists());
+        assert_eq!(saga.compensations.len(), 2);
+
+        let store = executor.context().backup_store();
+        assert!(store.root().starts_with(temp_dir.path()));
+        executor
+            .rollback_saga(&mut saga, &|undo| {
+                let result = restore(&store, undo);
+                async move { result }
+            })
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), original);
+        assert_eq!(std::fs::read_to_string(&doomed).unwrap(), "keep me");
+    }
+
+    #[tokio::test]
+    async fn test_require_backup_refuses_when_backup_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.rs");
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let mut saga = SagaPlan::new("verdict".to_string());
+
+        let write = Action::WriteFile {
+            path: missing.clone(),
+            content: "new".to_string(),
+        };
+        let err = executor
+            .execute_verdict(&allow(write.clone(), true), &mut saga)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ExecutionError::BackupFailed(_)));
+        assert!(!missing.exists());
+        assert!(saga.steps.is_empty());
+
+        // Without the condition the same write goes ahead
+        executor
+            .execute_verdict(&allow(write, false), &mut saga)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&missing).unwrap(), "new");
+    }
+
+    #[tokio::test]
+    async fn test_resume_reruns_step_left_in_progress() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("out.txt");
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let mut task = executor
+            .create_task(
+                "crashed".to_string(),
+                "interrupted mid-step".to_string(),
+                AgentRole::Implementer,
+            )
+            .await
+            .unwrap();
+        task.state = TaskState::InProgress;
+        task.steps.push(ExecutionStep {
+            step_id: 1,
+            action: Action::WriteFile {
+                path: target.clone(),
+                content: "done".to_string(),
+            },
+            status: StepStatus::InProgress,
+            result: None,
+            executed_at: Some(chrono::Utc::now()),
+        });
+        executor.context().storage.save_task(&task).await.unwrap();
+
+        let result = executor.resume_task(task.id).await.unwrap();
+
+        assert_eq!(result.final_state, TaskState::Completed);
+        assert_eq!(result.steps[0].status, StepStatus::Completed);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "done");
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_keeps_first_attempt_backup() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("config.toml");
+        std::fs::write(&target, "original").unwrap();
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let action = Action::WriteFile {
+            path: target.clone(),
+            content: "rewritten".to_string(),
+        };
+        let mut task = executor
+            .create_task(
+                "crashed".to_string(),
+                "interrupted mid-write".to_string(),
+                AgentRole::Implementer,
+            )
+            .await
+            .unwrap();
+        task.state = TaskState::InProgress;
+        task.steps.push(ExecutionStep {
+            step_id: 1,
+            action: action.clone(),
+            status: StepStatus::InProgress,
+            result: None,
+            executed_at: Some(chrono::Utc::now()),
+        });
+        executor.context().storage.save_task(&task).await.unwrap();
+
+        // First attempt: compensation checkpointed, then a crash mid-write
+        let mut saga = SagaPlan {
+            id: Executor::saga_id(&task.id),
+            ..SagaPlan::new(task.id.to_string())
+        };
+        for step in executor.plan_action(&action).1 {
+            saga.add_step(
+                StepId("step-1-1".to_string()),
+                step.action,
+                step.undo_action,
+            );
+        }
+        executor.checkpoint_saga(&saga).await.unwrap();
+        std::fs::write(&target, "rewr").unwrap();
+
+        let result = executor.resume_task(task.id).await.unwrap();
+        assert_eq!(result.final_state, TaskState::Completed);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "rewritten");
+
+        let mut saga = SagaPlan::load(executor.context().storage.as_ref(), &saga.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saga.steps.len(), 1);
+        assert_eq!(saga.steps[0].status, SagaStepStatus::Completed);
+        let store = executor.context().backup_store();
+        executor
+            .rollback_saga(&mut saga, &|undo| {
+                let result = restore(&store, undo);
+                async move { result }
+            })
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "original");
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    fn git_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        for args in [
+            vec!["init", "-q"],
+            vec!["config", "user.email", "test@test.com"],
+            vec!["config", "user.name", "Test"],
+            vec!["commit", "-q", "--allow-empty", "-m", "init"],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(&args)
+                .current_dir(temp_dir.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        temp_dir
+    }
+
+    async fn task_with_action(executor: &Executor, title: &str, action: Action) -> TaskId {
+        let mut task = executor
+            .create_task(
+                title.to_string(),
+                "isolated".to_string(),
+                AgentRole::Implementer,
+            )
+            .await
+            .unwrap();
+        task.steps.push(ExecutionStep {
+            step_id: 1,
+            action,
+            status: StepStatus::Pending,
+            result: None,
+            executed_at: None,
+        });
+        executor.context().storage.save_task(&task).await.unwrap();
+        task.id
+    }
+
+    /// Span fields of every span enclosing an event, plus the event message
+    #[derive(Clone, Default)]
+    struct CaptureLayer(Arc<std::sync::Mutex<Vec<std::collections::HashMap<String, String>>>>);
+
+    struct SpanFields(Vec<(String, String)>);
+
+    struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for CaptureLayer
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = Vec::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().insert(SpanFields(fields));
+            }
+        }
+
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = Vec::new();
+            if let Some(scope) = ctx.event_scope(event) {
+                for span in scope.from_root() {
+                    if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                        fields.extend(span_fields.iter().cloned());
+                    }
+                }
+            }
+            event.record(&mut FieldVisitor(&mut fields));
+            self.0.lock().unwrap().push(fields.into_iter().collect());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_carry_task_step_and_tool_span_fields() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("traced.txt");
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let task_id = task_with_action(
+            &executor,
+            "traced",
+            Action::WriteFile {
+                path: target.clone(),
+                content: "traced".to_string(),
+            },
+        )
+        .await;
+
+        let capture = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let result = {
+            let _default = tracing::subscriber::set_default(subscriber);
+            executor.execute_task(task_id).await.unwrap()
+        };
+        assert_eq!(result.final_state, TaskState::Completed);
+
+        let events = capture.0.lock().unwrap();
+        let tool_event = events
+            .iter()
+            .find(|e| e.get("message").map(String::as_str) == Some("Executing action"))
+            .expect("no event from the tool span");
+        assert_eq!(tool_event["task_id"], task_id.to_string());
+        assert_eq!(tool_event["step"], "0");
+        assert_eq!(tool_event["step_id"], "1");
+        assert_eq!(tool_event["action"], "write_file");
+        assert!(
+            events
+                .iter()
+                .all(|e| e.get("task_id") == Some(&task_id.to_string()))
+        );
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    async fn depend(executor: &Executor, task_id: TaskId, depends_on: &[TaskId]) {
+        let storage = &executor.context().storage;
+        let mut task = storage.get_task(&task_id).await.unwrap().unwrap();
+        task.depends_on = depends_on.to_vec();
+        storage.save_task(&task).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_dependencies_runs_prerequisites_in_order() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let write = |name: &str| Action::WriteFile {
+            path: temp_dir.path().join(name),
+            content: name.to_string(),
+        };
+        let a = task_with_action(&executor, "a", write("a.txt")).await;
+        let b = task_with_action(&executor, "b", write("b.txt")).await;
+        let c = task_with_action(&executor, "c", write("c.txt")).await;
+        let unrelated = task_with_action(&executor, "unrelated", write("x.txt")).await;
+        depend(&executor, c, &[b]).await;
+        depend(&executor, b, &[a]).await;
+
+        let results = executor.execute_with_dependencies(c).await.unwrap();
+        let ran: Vec<TaskId> = results.iter().map(|r| r.task_id).collect();
+        assert_eq!(ran, vec![a, b, c]);
+        assert!(
+            results
+                .iter()
+                .all(|r| r.final_state == TaskState::Completed)
+        );
+        assert!(!temp_dir.path().join("x.txt").exists());
+        let unrelated = executor.load_task(unrelated).await.unwrap();
+        assert_eq!(unrelated.state, TaskState::Pending);
+
+        // Completed prerequisites are not run again
+        let d = task_with_action(&executor, "d", write("d.txt")).await;
+        depend(&executor, d, &[a, c]).await;
+        let results = executor.execute_with_dependencies(d).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].task_id, d);
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_prerequisite_blocks_dependents() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let failing = Action::ReadFile {
+            path: temp_dir.path().join("missing.txt"),
+            range: None,
+        };
+        let write = Action::WriteFile {
+            path: temp_dir.path().join("out.txt"),
+            content: "out".to_string(),
+        };
+        let a = task_with_action(&executor, "a", failing).await;
+        let b = task_with_action(&executor, "b", write.clone()).await;
+        let c = task_with_action(&executor, "c", write).await;
+        depend(&executor, b, &[a]).await;
+        depend(&executor, c, &[b]).await;
+
+        assert!(executor.execute_with_dependencies(c).await.is_err());
+        for id in [b, c] {
+            let task = executor.load_task(id).await.unwrap();
+            assert_eq!(task.state, TaskState::Blocked);
+            assert!(
+                task.metadata
+                    .work_records
+                    .iter()
+                    .any(|r| matches!(r.event, WorkEvent::Blocked))
+            );
+        }
+        assert!(!temp_dir.path().join("out.txt").exists());
+
+        // A cycle is reported before anything runs
+        depend(&executor, a, &[c]).await;
+        assert!(matches!(
+            executor.execute_with_dependencies(c).await,
+            Err(ExecutionError::DependencyCycle(_))
+        ));
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_isolated_tasks_run_in_separate_worktrees() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+
+        let repo = git_repo();
+        let shared = repo.path().join("shared.txt");
+        let executor = Executor::new(ExecutionContext {
+            project_root: repo.path().to_path_buf(),
+            isolate_worktree: true,
+            ..Default::default()
+        });
+        let first = task_with_action(
+            &executor,
+            "first",
+            Action::WriteFile {
+                path: shared.clone(),
+                content: "from first".to_string(),
+            },
+        )
+        .await;
+        let second = task_with_action(
+            &executor,
+            "second",
+            Action::WriteFile {
+                path: shared.clone(),
+                content: "from second".to_string(),
+            },
+        )
+        .await;
+
+        let (a, b) = tokio::join!(executor.execute_task(first), executor.execute_task(second));
+        assert!(a.unwrap().success);
+        assert!(b.unwrap().success);
+
+        // The main checkout is untouched; each branch holds its own change
+        assert!(!shared.exists());
+        for (id, expected) in [(first, "from first"), (second, "from second")] {
+            let task = executor
+                .context()
+                .storage
+                .get_task(&id)
+                .await
+                .unwrap()
+                .unwrap();
+            let snapshot = task.latest_worktree_snapshot().unwrap();
+            assert_eq!(
+                snapshot.branch_name,
+                TaskWorktree::branch_name(&id.to_string())
+            );
+            let written = snapshot.worktree_path.join("shared.txt");
+            assert_eq!(std::fs::read_to_string(written).unwrap(), expected);
+
+            let show = std::process::Command::new("git")
+                .args(["show", &format!("{}:shared.txt", snapshot.branch_name)])
+                .current_dir(repo.path())
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&show.stdout), expected);
+            assert_eq!(
+                snapshot.base_commit,
+                String::from_utf8_lossy(
+                    &std::process::Command::new("git")
+                        .args(["rev-parse", &snapshot.branch_name])
+                        .current_dir(repo.path())
+                        .output()
+                        .unwrap()
+                        .stdout
+                )
+                .trim()
+            );
+        }
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_isolated_task_removes_worktree() {
+        let repo = git_repo();
+        let executor = Executor::new(ExecutionContext {
+            project_root: repo.path().to_path_buf(),
+            isolate_worktree: true,
+            ..Default::default()
+        });
+        let id = task_with_action(
+            &executor,
+            "broken",
+            Action::ReadFile {
+                path: repo.path().join("missing.txt"),
+                range: None,
+            },
+        )
+        .await;
+
+        assert!(executor.execute_task(id).await.is_err());
+
+        let task = executor
+            .context()
+            .storage
+            .get_task(&id)
+            .await
+            .unwrap()
+            .unwrap();
+        let snapshot = task.latest_worktree_snapshot().unwrap();
+        assert!(!snapshot.worktree_path.exists());
+        let branches = std::process::Command::new("git")
+            .args(["branch", "--list", &snapshot.branch_name])
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&branches.stdout).trim().is_empty());
+
+        let saga = SagaPlan::load(executor.context().storage.as_ref(), &Executor::saga_id(&id))
+            .await
+            .unwrap()
+            .unwrap();
+        let worktree_step = saga
+            .steps
+            .iter()
+            .find(|s| matches!(s.action, StepAction::CreateWorktree { .. }))
+            .unwrap();
+        assert_eq!(worktree_step.status, SagaStepStatus::RolledBack);
+    }
+
+    /// Shell stand-in that fails `failures` times with `stderr`, then succeeds
+    #[derive(Debug)]
+    struct FlakyShell {
+        failures: u32,
+        stderr: &'static str,
+        calls: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::tools::Tool for FlakyShell {
+        fn name(&self) -> &str {
+            "shell"
+        }
+
+        fn description(&self) -> &str {
+            "flaky shell"
+        }
+
+        async fn execute(
+            &self,
+            _params: &serde_json::Value,
+        ) -> Result<crate::tools::ToolResult, crate::tools::ToolError> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let success = call >= self.failures;
+            Ok(crate::tools::ToolResult {
+                success,
+                output: if success { "ok" } else { self.stderr }.to_string(),
+                error: (!success).then(|| self.stderr.to_string()),
+                metadata: crate::tools::ToolMetadata {
+                    structured: Some(
+                        serde_json::json!({ "exit_code": if success { 0 } else { 1 } }),
+                    ),
+                    ..Default::default()
+                },
+            })
+        }
+    }
+
+    fn flaky_executor(
+        failures: u32,
+        stderr: &'static str,
+    ) -> (
+        Executor,
+        Arc<std::sync::atomic::AtomicU32>,
+        Arc<std::sync::Mutex<Vec<String>>>,
+    ) {
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let mut tools = ToolManager::new();
+        tools.register(
+            "shell",
+            FlakyShell {
+                failures,
+                stderr,
+                calls: calls.clone(),
+            },
+        );
+        let attempts = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = attempts.clone();
+        let events = EventEmitter::new();
+        events.on(
+            "attempts".to_string(),
+            vec![EventType::StepStarted],
+            move |event| seen.lock().unwrap().push(event.metadata["attempt"].clone()),
+        );
+        let executor = Executor::new(ExecutionContext {
+            tools: Arc::new(tools),
+            retry_policy: RetryPolicy {
+                base_delay: std::time::Duration::from_millis(1),
+                ..RetryPolicy::default()
+            },
+            events: Arc::new(events),
+            ..Default::default()
+        });
+        (executor, calls, attempts)
+    }
+
+    fn fetch_command() -> Action {
+        Action::RunCommand {
+            command: "cargo".to_string(),
+            args: vec!["fetch".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_command_failure_is_retried() {
+        let (executor, calls, attempts) = flaky_executor(2, "error: connection reset by peer");
+        let task_id = task_with_action(&executor, "fetch", fetch_command()).await;
+
+        let result = executor.execute_task(task_id).await.unwrap();
+        assert!(result.success);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(*attempts.lock().unwrap(), vec!["1", "2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_command_failure_fails_the_step() {
+        // A compile error is not transient: no retry
+        let (executor, calls, attempts) =
+            flaky_executor(u32::MAX, "error[E0308]: mismatched types");
+        let task_id = task_with_action(&executor, "build", fetch_command()).await;
+
+        let err = executor.execute_task(task_id).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ExecutionError::CommandFailed {
+                exit_code: Some(1),
+                ..
+            }
+        ));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(*attempts.lock().unwrap(), vec!["1"]);
+
+        // A transient failure that never clears gives up after max_attempts
+        let (executor, calls, _) = flaky_executor(u32::MAX, "Could not resolve host: crates.io");
+        let task_id = task_with_action(&executor, "fetch", fetch_command()).await;
+        assert!(executor.execute_task(task_id).await.is_err());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        let task = executor
+            .context()
+            .storage
+            .get_task(&task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.steps[0].status, StepStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_modify_verdict_runs_substituted_action() {
+        use ndc_decision::{
+            BasicDecisionEngine, DecisionEngine, PolicyState, ValidationResult, Validator,
+        };
+
+        struct SandboxValidator(PathBuf);
+
+        #[async_trait::async_trait]
+        impl Validator for SandboxValidator {
+            async fn validate(
+                &self,
+                intent: &ndc_core::Intent,
+                _: &PolicyState,
+            ) -> ValidationResult {
+                let Action::WriteFile { content, .. } = &intent.proposed_action else {
+                    return ValidationResult::Allow;
+                };
+                ValidationResult::Modify(
+                    Action::WriteFile {
+                        path: self.0.clone(),
+                        content: content.clone(),
+                    },
+                    "writes outside the sandbox are redirected".to_string(),
+                    vec!["path changed to sandbox/out.txt".to_string()],
+                )
+            }
+
+            fn name(&self) -> &str {
+                "sandbox"
+            }
+
+            fn priority(&self) -> u32 {
+                1
+            }
+        }
+
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("out.txt");
+        let sandboxed = temp_dir.path().join("sandbox/out.txt");
+        std::fs::create_dir_all(sandboxed.parent().unwrap()).unwrap();
+
+        let mut engine = BasicDecisionEngine::new();
+        engine.register_validator(Arc::new(SandboxValidator(sandboxed.clone())));
+        let intent = ndc_core::Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Implementer,
+            proposed_action: Action::WriteFile {
+                path: original.clone(),
+                content: "payload".to_string(),
+            },
+            effects: vec![],
+            reasoning: "write output".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        };
+        let verdict = engine.evaluate(intent.clone()).await;
+        assert!(matches!(verdict, Verdict::Modify { .. }));
+
+        let warnings = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = warnings.clone();
+        let events = EventEmitter::new();
+        events.on(
+            "modified".to_string(),
+            vec![EventType::VerdictModified],
+            move |event| seen.lock().unwrap().extend(event.warning_events()),
+        );
+        let executor = Executor::new(ExecutionContext {
+            project_root: temp_dir.path().to_path_buf(),
+            events: Arc::new(events),
+            ..Default::default()
+        });
+        let task = Task::from_intent_and_verdict(intent, verdict);
+        executor.context().storage.save_task(&task).await.unwrap();
+
+        let result = executor.execute_task(task.id).await.unwrap();
+        assert!(result.success);
+        assert!(!original.exists());
+        assert_eq!(std::fs::read_to_string(&sandboxed).unwrap(), "payload");
+
+        let stored = executor
+            .context()
+            .storage
+            .get_task(&task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let modification = stored
+            .metadata
+            .work_records
+            .iter()
+            .find_map(|record| match &record.event {
+                WorkEvent::ActionModified {
+                    original, reason, ..
+                } => Some((original.clone(), reason.clone())),
+                _ => None,
+            })
+            .expect("modification recorded");
+        assert!(matches!(modification.0, Action::WriteFile { path, .. } if path == original));
+        assert_eq!(modification.1, "writes outside the sandbox are redirected");
+
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ndc_core::AgentExecutionEventKind::Warning);
+        assert_eq!(warnings[0].message, "path changed to sandbox/out.txt");
+    }
+
+    /// Defers the first `defer_times` evaluations by 30s, then allows
+    struct DeferringValidator {
+        defer_times: usize,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ndc_decision::Validator for DeferringValidator {
+        async fn validate(
+            &self,
+            _: &ndc_core::Intent,
+            _: &ndc_decision::PolicyState,
+        ) -> ndc_decision::ValidationResult {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            match call < self.defer_times {
+                true => ndc_decision::ValidationResult::Defer(Vec::new(), Some(30)),
+                false => ndc_decision::ValidationResult::Allow,
+            }
+        }
+
+        fn name(&self) -> &str {
+            "deferring"
+        }
+
+        fn priority(&self) -> u32 {
+            1
+        }
+    }
+
+    async fn deferred_task_executor(
+        root: &std::path::Path,
+        defer_times: usize,
+    ) -> (Executor, ndc_core::MockClock, TaskId) {
+        use ndc_decision::DecisionEngine;
+
+        let clock = ndc_core::MockClock::default();
+        let mut engine = BasicDecisionEngine::new();
+        engine.register_validator(Arc::new(DeferringValidator {
+            defer_times,
+            calls: Default::default(),
+        }));
+        let engine: Arc<dyn DecisionEngine> = Arc::new(engine);
+        let intent = ndc_core::Intent {
+            id: ndc_core::IntentId::new(),
+            agent: AgentId::new(),
+            agent_role: AgentRole::Implementer,
+            proposed_action: Action::WriteFile {
+                path: root.join("out.txt"),
+                content: "payload".to_string(),
+            },
+            effects: vec![],
+            reasoning: "write output".to_string(),
+            task_id: None,
+            timestamp: chrono::Utc::now(),
+        };
+        let verdict = engine.evaluate(intent.clone()).await;
+        assert!(matches!(verdict, Verdict::Defer { .. }));
+
+        let executor = Executor::new(ExecutionContext {
+            project_root: root.to_path_buf(),
+            clock: Arc::new(clock.clone()),
+            decision_engine: Some(engine),
+            ..Default::default()
+        });
+        let task = Task::from_intent_and_verdict(intent, verdict);
+        executor.context().storage.save_task(&task).await.unwrap();
+        (executor, clock, task.id)
+    }
+
+    #[tokio::test]
+    async fn test_deferred_task_is_reevaluated_and_run_after_retry_after() {
+        let _guard = env_lock();
+        unsafe {
+            std::env::set_var("NDC_DISCOVERY_FAILURE_MODE", "degrade");
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let (executor, clock, task_id) = deferred_task_executor(temp_dir.path(), 1).await;
+
+        let result = executor.execute_task(task_id).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.final_state, TaskState::Pending);
+        assert!(!temp_dir.path().join("out.txt").exists());
+        assert_eq!(executor.pending_deferrals().len(), 1);
+        assert_eq!(
+            executor.next_deferral_due(),
+            Some(ndc_core::Clock::now(&clock) + chrono::Duration::seconds(30))
+        );
+
+        clock.advance(chrono::Duration::seconds(29));
+        assert!(executor.run_due_deferrals().await.is_empty());
+
+        clock.advance(chrono::Duration::seconds(1));
+        let results = executor.run_due_deferrals().await;
+        assert_eq!(results.len(), 1);
+        let result = results.into_iter().next().unwrap().unwrap();
+        assert!(result.success);
+        assert_eq!(result.final_state, TaskState::Completed);
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("out.txt")).unwrap(),
+            "payload"
+        );
+        assert!(executor.pending_deferrals().is_empty());
+
+        unsafe {
+            std::env::remove_var("NDC_DISCOVERY_FAILURE_MODE");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deferred_task_blocks_after_max_attempts() {
+        let temp_dir = TempDir::new().unwrap();
+        let (executor, clock, task_id) = deferred_task_executor(temp_dir.path(), usize::MAX).await;
+
+        executor.execute_task(task_id).await.unwrap();
+        let mut last = None;
+        while !executor.pending_deferrals().is_empty() {
+            clock.advance(chrono::Duration::seconds(30));
+            last = executor.run_due_deferrals().await.pop();
+        }
+
+        let result = last.unwrap().unwrap();
+        assert_eq!(result.final_state, TaskState::Blocked);
+        assert!(result.error.unwrap().contains("deferred 3 times"));
+        assert!(!temp_dir.path().join("out.txt").exists());
+        let stored = executor.load_task(task_id).await.unwrap();
+        assert_eq!(stored.state, TaskState::Blocked);
+    }
 }
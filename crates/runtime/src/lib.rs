@@ -22,35 +22,48 @@ pub mod verify;
 pub mod workflow;
 
 // Re-export storage from ndc-storage crate
-pub use ndc_storage::{MemoryStorage, SharedStorage, Storage, create_memory_storage};
+pub use ndc_storage::{
+    DEFAULT_DEDUP_THRESHOLD, DedupPolicy, EmbeddingStorage, ImportMode, ImportReport, JsonLogError,
+    JsonLogStorage, MemoryStorage, SharedStorage, Storage, TaskFilter, create_json_log_storage,
+    create_memory_storage, export_memories, import_memories,
+};
 #[cfg(feature = "sqlite")]
-pub use ndc_storage::{SqliteStorage, SqliteStorageError, create_sqlite_storage};
+pub use ndc_storage::{SqliteStorage, SqliteStorageError, TaskCursor, create_sqlite_storage};
 
 pub use discovery::{
-    Complexity, DiscoveryConfig, DiscoveryError, DiscoveryResult, DiscoveryService,
-    HardConstraints, HeatmapConfig, ImpactReport, ImpactScope, ModuleId, VolatilityHeatmap,
+    CommitGate, CommitGateValidator, Complexity, ConstraintReport, DiscoveryConfig, DiscoveryError,
+    DiscoveryPhase, DiscoveryProgress, DiscoveryResult, DiscoveryService, HardConstraints,
+    HeatmapConfig, HeatmapWatcher, ImpactReport, ImpactScope, ModuleId, ModuleVolatility,
+    VolatilityHeatmap, WorkingState,
 };
 pub use documentation::{
     DocUpdateRequest, DocUpdateResult, DocUpdateType, DocUpdater, DocUpdaterConfig, Fact,
     FactCategory, Narrative,
 };
 pub use engine::{
-    Event, EventData, EventEmitter, EventEngine, EventEngineSummary, EventId, EventListener,
-    EventType, TransitionError, Workflow, WorkflowState,
+    DeferOutcome, DeferredIntent, Event, EventData, EventEmitter, EventEngine, EventEngineSummary,
+    EventId, EventListener, EventType, TransitionError, Workflow, WorkflowState,
 };
 pub use execution::{
-    CompensationAction, RollbackError, SagaId, SagaPlan, SagaStep, SagaSummary, StepId, StepStatus,
-    UndoAction,
+    BackupStore, CompensationAction, RetryPolicy, RollbackError, RollbackIssue, SagaId, SagaPlan,
+    SagaStep, SagaSummary, StepId, StepStatus, TaskWorktree, UndoAction,
+};
+pub use executor::{
+    ExecutionContext, ExecutionError, ExecutionResult, Executor, create_decision_engine,
 };
-pub use executor::{ExecutionContext, ExecutionError, ExecutionResult, Executor};
 pub use mcp::{
-    McpManager, McpPrompt, McpResource, McpResult, McpServerConfig, McpServerType, McpTool,
+    CircuitState, McpManager, McpPrompt, McpResource, McpResult, McpServerConfig, McpServerType,
+    McpTool, McpToolAdapter, SseTransport, register_mcp_tools,
+};
+pub use skill::{
+    ConflictPolicy, Skill, SkillDiscovery, SkillExample, SkillMatch, SkillOverride, SkillParameter,
+    SkillRegistry, SkillSource,
 };
-pub use skill::{Skill, SkillExample, SkillParameter, SkillRegistry};
 pub use tools::{
-    Tool, ToolContext, ToolError, ToolManager, ToolResult, create_default_tool_manager,
-    create_default_tool_manager_with_storage, create_default_tool_registry,
-    create_default_tool_registry_with_storage,
+    FileLockManager, Tool, ToolContext, ToolError, ToolManager, ToolResult,
+    create_default_tool_manager, create_default_tool_manager_with_storage,
+    create_default_tool_registry, create_default_tool_registry_with_storage,
+    create_tool_registry_with_mcp,
 };
-pub use verify::QualityGateRunner;
+pub use verify::{QualityCommands, QualityGateRunner, QualityGateWatch};
 pub use workflow::{WorkflowEngine, WorkflowError, WorkflowListener};
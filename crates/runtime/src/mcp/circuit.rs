@@ -0,0 +1,93 @@
+//! Per-server circuit breaker
+//!
+//! After `threshold` consecutive failures the circuit opens and calls fail
+//! fast for `cooldown`. The first attempt after the cooldown is a half-open
+//! probe: success closes the circuit, failure re-opens it for another cooldown.
+
+use std::time::{Duration, Instant};
+
+/// Consecutive failures that open a server's circuit
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// How long an open circuit rejects calls
+pub const DEFAULT_CIRCUIT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Circuit state of an MCP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls are rejected until the cooldown ends
+    Open { retry_in: Duration },
+    /// Cooldown over; the next call is a probe
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(super) fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            failures: 0,
+            opened_at: None,
+        }
+    }
+
+    pub(super) fn state(&self) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(at) => match self.cooldown.checked_sub(at.elapsed()) {
+                Some(retry_in) if !retry_in.is_zero() => CircuitState::Open { retry_in },
+                _ => CircuitState::HalfOpen,
+            },
+        }
+    }
+
+    pub(super) fn consecutive_failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub(super) fn record_success(&mut self) {
+        self.failures = 0;
+        self.opened_at = None;
+    }
+
+    pub(super) fn record_failure(&mut self) {
+        self.failures += 1;
+        // A failed half-open probe re-opens straight away
+        if self.opened_at.is_some() || self.failures >= self.threshold {
+            self.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaker_opens_at_threshold_and_probes_after_cooldown() {
+        let mut breaker = CircuitBreaker::new(2, Duration::ZERO);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        // Zero cooldown: open circuit is immediately ready for a probe
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        breaker.record_failure();
+        assert_eq!(breaker.consecutive_failures(), 3);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+}
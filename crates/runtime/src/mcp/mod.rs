@@ -2,16 +2,41 @@
 //!
 //! Responsibilities:
 //! - Connect to external MCP servers
-//! - Tool synchronization
+//! - Tool synchronization, and invocation as agent tools (`McpToolAdapter`)
 //! - Prompt and resource management
 //! - OAuth authentication
+//! - Per-server circuit breaker for flaky servers
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
 use tokio::process::Command;
+use tokio::sync::{Mutex, mpsc};
 use tracing::{debug, info, warn};
 
+mod circuit;
+use circuit::CircuitBreaker;
+pub use circuit::{CircuitState, DEFAULT_CIRCUIT_COOLDOWN, DEFAULT_FAILURE_THRESHOLD};
+
+mod sse;
+pub use sse::SseTransport;
+
+mod tool_adapter;
+pub use tool_adapter::{McpToolAdapter, register_mcp_tools};
+
+/// Server list file read from each config directory by `connect_configured`
+pub const MCP_CONFIG_FILE: &str = "mcp.yaml";
+
+/// Parse the server list of a `MCP_CONFIG_FILE`
+pub fn parse_server_configs(content: &str) -> Result<Vec<McpServerConfig>, String> {
+    serde_yaml::from_str(content).map_err(|e| format!("Failed to parse config: {}", e))
+}
+
 /// MCP Server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerConfig {
@@ -36,6 +61,46 @@ pub struct McpOAuthConfig {
     pub redirect_uri: Option<String>,
 }
 
+/// Refresh OAuth tokens this long before they actually expire
+const OAUTH_REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// Give up on a token endpoint that has not answered within this long
+const OAUTH_TOKEN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cached OAuth access token
+#[derive(Debug, Clone)]
+pub struct OAuthToken {
+    pub access_token: String,
+    /// None when the token endpoint did not report `expires_in`
+    pub expires_at: Option<Instant>,
+}
+
+impl OAuthToken {
+    /// Token valid for `expires_in` seconds from now; a lifetime too large
+    /// to represent is treated as never expiring
+    pub fn new(access_token: String, expires_in: Option<u64>) -> Self {
+        Self {
+            access_token,
+            expires_at: expires_in
+                .and_then(|secs| Instant::now().checked_add(Duration::from_secs(secs))),
+        }
+    }
+
+    /// Whether the token is expired or about to expire
+    pub fn is_stale(&self) -> bool {
+        self.expires_at
+            .is_some_and(|at| Instant::now() + OAUTH_REFRESH_MARGIN >= at)
+    }
+}
+
+/// Token endpoint response (RFC 6749 §5.1)
+#[derive(Debug, Deserialize)]
+struct OAuthTokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
 /// MCP Server type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum McpServerType {
@@ -87,11 +152,14 @@ pub struct McpResult {
     pub tool_name: String,
 }
 
+/// Transport shared by callers; locked per request, not with the manager
+type SharedTransport = Arc<Mutex<dyn McpTransport>>;
+
 /// Active MCP connection
 struct McpConnection {
     config: McpServerConfig,
     child: Option<tokio::process::Child>,
-    transport: Option<Box<dyn McpTransport>>,
+    transport: Option<SharedTransport>,
 }
 
 impl std::fmt::Debug for McpConnection {
@@ -111,35 +179,194 @@ pub trait McpTransport: Send {
 }
 
 /// Stdio transport for local MCP servers
-#[derive(Debug)]
+///
+/// Requests are framed with `Content-Length` headers. A background task reads
+/// framed messages from stdout; `send` waits for the message whose JSON-RPC
+/// `id` matches its request, buffering other responses and dropping
+/// notifications.
 pub struct StdioTransport {
-    stdin: Option<tokio::process::ChildStdin>,
-    stdout: Option<tokio::io::BufReader<tokio::process::ChildStdout>>,
+    stdin: Option<Box<dyn AsyncWrite + Send + Unpin>>,
+    incoming: Option<mpsc::UnboundedReceiver<serde_json::Value>>,
+    reader: Option<tokio::task::JoinHandle<()>>,
+    /// Responses that arrived for requests other than the one being awaited
+    pending: HashMap<u64, serde_json::Value>,
+    next_id: u64,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for StdioTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StdioTransport")
+            .field("open", &self.stdin.is_some())
+            .field("pending", &self.pending.len())
+            .field("next_id", &self.next_id)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl StdioTransport {
+    /// Create a transport over a server's stdin/stdout
+    pub fn new<W, R>(stdin: W, stdout: R, timeout: Duration) -> Self
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let reader = tokio::spawn(async move {
+            let mut stdout = BufReader::new(stdout);
+            loop {
+                match read_frame(&mut stdout).await {
+                    Ok(Some(message)) => {
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("MCP stdio read failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            stdin: Some(Box::new(stdin)),
+            incoming: Some(rx),
+            reader: Some(reader),
+            pending: HashMap::new(),
+            next_id: 1,
+            timeout,
+        }
+    }
+
+    async fn wait_for(&mut self, id: u64) -> Result<serde_json::Value, String> {
+        wait_for_response(self.incoming.as_mut(), &mut self.pending, id, "stdout").await
+    }
+}
+
+/// Wait for the message whose JSON-RPC `id` matches, buffering responses to
+/// other requests in `pending` and dropping notifications
+async fn wait_for_response(
+    incoming: Option<&mut mpsc::UnboundedReceiver<serde_json::Value>>,
+    pending: &mut HashMap<u64, serde_json::Value>,
+    id: u64,
+    channel: &str,
+) -> Result<serde_json::Value, String> {
+    if let Some(response) = pending.remove(&id) {
+        return Ok(response);
+    }
+
+    let incoming = incoming.ok_or_else(|| "Transport closed".to_string())?;
+
+    loop {
+        let message = incoming
+            .recv()
+            .await
+            .ok_or_else(|| format!("MCP server closed {}", channel))?;
+
+        match message.get("id").and_then(|v| v.as_u64()) {
+            Some(message_id) if message_id == id => return Ok(message),
+            Some(message_id) => {
+                pending.insert(message_id, message);
+            }
+            None => {
+                debug!(
+                    "Ignoring MCP notification: {}",
+                    message
+                        .get("method")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("?")
+                );
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
 impl McpTransport for StdioTransport {
     async fn send(&mut self, message: &serde_json::Value) -> Result<serde_json::Value, String> {
+        // Use transport-local ids so concurrent callers' ids never collide;
+        // the caller's id is restored on the response.
+        let id = self.next_id;
+        self.next_id += 1;
+        let original_id = message.get("id").cloned();
+        let mut request = message.clone();
+        request["id"] = serde_json::json!(id);
+
         let json =
-            serde_json::to_string(message).map_err(|e| format!("Serialize failed: {}", e))?;
+            serde_json::to_string(&request).map_err(|e| format!("Serialize failed: {}", e))?;
 
-        let payload = format!("Content-Length: {}\n\n{}", json.len(), json);
+        let payload = format!("Content-Length: {}\r\n\r\n{}", json.len(), json);
 
-        if let Some(ref mut stdin) = self.stdin {
-            tokio::io::AsyncWriteExt::write_all(stdin, payload.as_bytes())
-                .await
-                .map_err(|e| format!("Write failed: {}", e))?;
-        }
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| "Transport closed".to_string())?;
+        stdin
+            .write_all(payload.as_bytes())
+            .await
+            .map_err(|e| format!("Write failed: {}", e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Write failed: {}", e))?;
 
-        Ok(serde_json::Value::Null)
+        let timeout = self.timeout;
+        let mut response = tokio::time::timeout(timeout, self.wait_for(id))
+            .await
+            .map_err(|_| format!("No response to request {} within {:?}", id, timeout))??;
+
+        if let Some(original_id) = original_id {
+            response["id"] = original_id;
+        }
+        Ok(response)
     }
 
     async fn close(&mut self) {
         if let Some(mut stdin) = self.stdin.take() {
-            tokio::io::AsyncWriteExt::shutdown(&mut stdin).await.ok();
+            stdin.shutdown().await.ok();
+        }
+        self.incoming = None;
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+    }
+}
+
+/// Read one `Content-Length` framed JSON message; `None` on clean EOF
+async fn read_frame<R>(reader: &mut R) -> std::io::Result<Option<serde_json::Value>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut content_length: Option<usize> = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().ok();
         }
-        self.stdout = None;
     }
+
+    let mut body = vec![0u8; content_length.unwrap_or_default()];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
 }
 
 /// HTTP transport for remote MCP servers
@@ -200,7 +427,13 @@ pub struct McpManager {
     /// Discovered resources from all servers
     resources: HashMap<String, McpResource>,
     /// OAuth tokens cache
-    oauth_tokens: HashMap<String, String>,
+    oauth_tokens: HashMap<String, OAuthToken>,
+    /// Circuit breakers by server name
+    circuits: HashMap<String, CircuitBreaker>,
+    /// Consecutive failures that open a circuit
+    failure_threshold: u32,
+    /// How long an open circuit rejects calls
+    circuit_cooldown: Duration,
 }
 
 impl McpManager {
@@ -213,6 +446,72 @@ impl McpManager {
             prompts: HashMap::new(),
             resources: HashMap::new(),
             oauth_tokens: HashMap::new(),
+            circuits: HashMap::new(),
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            circuit_cooldown: DEFAULT_CIRCUIT_COOLDOWN,
+        }
+    }
+
+    /// Open a server's circuit after `failure_threshold` consecutive
+    /// connect/call failures, rejecting calls for `cooldown`
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.failure_threshold = failure_threshold;
+        self.circuit_cooldown = cooldown;
+        self
+    }
+
+    /// Circuit state of a server (`Closed` for servers that never failed)
+    pub fn circuit_state(&self, name: &str) -> CircuitState {
+        self.circuits
+            .get(name)
+            .map(CircuitBreaker::state)
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Consecutive connect/call failures of a server
+    pub fn consecutive_failures(&self, name: &str) -> u32 {
+        self.circuits
+            .get(name)
+            .map(CircuitBreaker::consecutive_failures)
+            .unwrap_or(0)
+    }
+
+    /// Fail fast while the server's circuit is open
+    fn check_circuit(&self, name: &str) -> Result<(), String> {
+        match self.circuit_state(name) {
+            CircuitState::Open { retry_in } => Err(format!(
+                "MCP server {} circuit open after {} consecutive failures; retry in {}s",
+                name,
+                self.consecutive_failures(name),
+                retry_in.as_secs_f64().ceil() as u64
+            )),
+            CircuitState::HalfOpen => {
+                debug!("MCP server {} circuit half-open; probing", name);
+                Ok(())
+            }
+            CircuitState::Closed => Ok(()),
+        }
+    }
+
+    fn record_outcome<T>(&mut self, name: &str, result: &Result<T, String>) {
+        let (threshold, cooldown) = (self.failure_threshold, self.circuit_cooldown);
+        let breaker = self
+            .circuits
+            .entry(name.to_string())
+            .or_insert_with(|| CircuitBreaker::new(threshold, cooldown));
+        match result {
+            Ok(_) => breaker.record_success(),
+            Err(e) => {
+                breaker.record_failure();
+                if let CircuitState::Open { .. } = breaker.state() {
+                    warn!(
+                        "MCP server {} circuit opened after {} consecutive failures: {}",
+                        name,
+                        breaker.consecutive_failures(),
+                        e
+                    );
+                }
+            }
         }
     }
 
@@ -226,16 +525,33 @@ impl McpManager {
         let content = std::fs::read_to_string(config_path)
             .map_err(|e| format!("Failed to read config: {}", e))?;
 
-        let configs: Vec<McpServerConfig> =
-            serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))?;
-
-        for config in configs {
+        for config in parse_server_configs(&content)? {
             self.add_server(config);
         }
 
         Ok(())
     }
 
+    /// Connect to the servers listed in `MCP_CONFIG_FILE` under each of
+    /// `config_dirs`; later directories override servers of the same name.
+    /// `None` when no server is configured.
+    pub async fn connect_configured(config_dirs: &[PathBuf]) -> Option<Self> {
+        let mut manager = Self::new();
+        for dir in config_dirs {
+            let path = dir.join(MCP_CONFIG_FILE);
+            if path.is_file()
+                && let Err(e) = manager.load_config(&path)
+            {
+                warn!("Ignoring MCP config {}: {}", path.display(), e);
+            }
+        }
+        if manager.servers.is_empty() {
+            return None;
+        }
+        manager.connect_all().await.ok();
+        Some(manager)
+    }
+
     /// Connect to all enabled servers
     pub async fn connect_all(&mut self) -> Result<(), String> {
         // Collect server names first to avoid borrowing issues
@@ -259,43 +575,58 @@ impl McpManager {
     }
 
     /// Connect to a specific server
+    ///
+    /// Rejected without a connection attempt while the server's circuit is open.
     pub async fn connect_server(&mut self, name: &str) -> Result<(), String> {
+        if !self.servers.contains_key(name) {
+            return Err(format!("Unknown server: {}", name));
+        }
+        self.check_circuit(name)?;
+
+        let result = self.establish_connection(name).await;
+        self.record_outcome(name, &result);
+        result
+    }
+
+    async fn establish_connection(&mut self, name: &str) -> Result<(), String> {
         let config = self
             .servers
             .get(name)
             .ok_or_else(|| format!("Unknown server: {}", name))?
             .clone();
 
-        // Handle OAuth if needed
-        if let Some(ref oauth) = config.oauth
-            && self.oauth_tokens.get(name).is_none()
-        {
-            // Get new token
-            let token = self.obtain_oauth_token(name, oauth).await?;
-            self.oauth_tokens.insert(name.to_string(), token.clone());
-        }
+        // Handle OAuth if needed; without OAuth config fall back to env tokens
+        let token = match config.oauth {
+            Some(ref oauth) => Some(self.oauth_token(name, oauth).await?),
+            None => Self::env_token(name),
+        };
 
         // Create transport based on server type
-        let transport: Option<Box<dyn McpTransport>> = match config.server_type {
+        let transport: Option<SharedTransport> = match config.server_type {
             McpServerType::Local => {
                 if let Some(ref cmd) = config.command {
-                    Some(Box::new(self.create_stdio_transport(name, cmd).await?))
+                    Some(Arc::new(Mutex::new(
+                        self.create_stdio_transport(name, cmd, config.timeout_ms)
+                            .await?,
+                    )))
                 } else {
                     None
                 }
             }
             McpServerType::Remote => {
                 if let Some(ref url) = config.url {
-                    let token = self.oauth_tokens.get(name).cloned();
-                    Some(Box::new(HttpTransport::new(url.clone(), token)))
+                    Some(Arc::new(Mutex::new(HttpTransport::new(url.clone(), token))))
                 } else {
                     None
                 }
             }
-            McpServerType::Sse => {
-                warn!("SSE transport not yet implemented");
-                None
-            }
+            McpServerType::Sse => config.url.as_ref().map(|url| {
+                Arc::new(Mutex::new(SseTransport::connect(
+                    url.clone(),
+                    token,
+                    Duration::from_millis(config.timeout_ms),
+                ))) as SharedTransport
+            }),
         };
 
         // Initialize connection
@@ -318,6 +649,7 @@ impl McpManager {
         &mut self,
         name: &str,
         command: &[String],
+        timeout_ms: u64,
     ) -> Result<StdioTransport, String> {
         if command.is_empty() {
             return Err("Empty command".to_string());
@@ -335,49 +667,143 @@ impl McpManager {
             .spawn()
             .map_err(|e| format!("Failed to spawn MCP server {}: {}", name, e))?;
 
-        let stdin = child.stdin.take();
-        let stdout = child.stdout.take().map(tokio::io::BufReader::new);
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| format!("No stdin for MCP server {}", name))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| format!("No stdout for MCP server {}", name))?;
+
+        Ok(StdioTransport::new(
+            stdin,
+            stdout,
+            Duration::from_millis(timeout_ms),
+        ))
+    }
 
-        Ok(StdioTransport { stdin, stdout })
+    /// Return a valid OAuth token for a server, refreshing it when stale
+    async fn oauth_token(&mut self, name: &str, oauth: &McpOAuthConfig) -> Result<String, String> {
+        if let Some(token) = self.oauth_tokens.get(name)
+            && !token.is_stale()
+        {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = Self::obtain_oauth_token(name, oauth).await?;
+        let access_token = token.access_token.clone();
+        self.oauth_tokens.insert(name.to_string(), token);
+        Ok(access_token)
     }
 
-    /// Obtain OAuth token
-    async fn obtain_oauth_token(
-        &self,
-        _name: &str,
-        _oauth: &McpOAuthConfig,
-    ) -> Result<String, String> {
-        // In a real implementation, this would:
-        // 1. Check for cached token
-        // 2. If expired, refresh using client_id + client_secret
-        // 3. If no token, initiate authorization flow
+    /// Obtain OAuth token via the client-credentials grant
+    async fn obtain_oauth_token(name: &str, oauth: &McpOAuthConfig) -> Result<OAuthToken, String> {
+        debug!("Requesting OAuth token for MCP server {}", name);
 
-        // For now, check environment variable
-        let token = std::env::var(format!("NDC_MCP_TOKEN_{}", _name.to_uppercase()))
-            .or_else(|_| std::env::var("NDC_MCP_TOKEN"))
-            .map_err(|_| "No OAuth token available".to_string())?;
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", oauth.client_id.as_str()),
+        ];
+        if let Some(ref secret) = oauth.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        if !oauth.scope.is_empty() {
+            form.push(("scope", oauth.scope.as_str()));
+        }
+
+        let response = reqwest::Client::new()
+            .post(&oauth.token_url)
+            .timeout(OAUTH_TOKEN_TIMEOUT)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("OAuth token request failed: {}", e))?;
 
-        Ok(token)
+        if !response.status().is_success() {
+            return Err(format!(
+                "OAuth token request for {} failed: HTTP {}",
+                name,
+                response.status()
+            ));
+        }
+
+        let body: OAuthTokenResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid OAuth token response: {}", e))?;
+
+        Ok(OAuthToken::new(body.access_token, body.expires_in))
+    }
+
+    /// Static token from `NDC_MCP_TOKEN_<NAME>` or `NDC_MCP_TOKEN`
+    fn env_token(name: &str) -> Option<String> {
+        std::env::var(format!("NDC_MCP_TOKEN_{}", name.to_uppercase()))
+            .or_else(|_| std::env::var("NDC_MCP_TOKEN"))
+            .ok()
     }
 
     /// Discover resources from a server
+    ///
+    /// Sends `tools/list`, `prompts/list` and `resources/list` and records the
+    /// results keyed by `{server_name}_{item_name}`. Lists the server does not
+    /// implement (JSON-RPC -32601) are skipped.
     async fn discover_resources(&mut self, server_name: &str) -> Result<(), String> {
-        // Send JSON-RPC messages to discover resources
-        // In a full implementation, this would send:
-        // - tools/list
-        // - prompts/list
-        // - resources/list
-
         debug!("Discovering resources from MCP server: {}", server_name);
 
+        let connection = self
+            .connections
+            .get(server_name)
+            .ok_or_else(|| format!("Not connected to server: {}", server_name))?;
+        let Some(transport) = connection.transport.clone() else {
+            debug!(
+                "MCP server {} has no transport, skipping discovery",
+                server_name
+            );
+            return Ok(());
+        };
+        let mut transport = transport.lock().await;
+
+        let tools = list_all(&mut *transport, "tools/list", "tools").await?;
+        let prompts = list_all(&mut *transport, "prompts/list", "prompts").await?;
+        let resources = list_all(&mut *transport, "resources/list", "resources").await?;
+        drop(transport);
+
+        let tools: Vec<McpTool> = tools
+            .unwrap_or_default()
+            .iter()
+            .filter_map(parse_tool)
+            .collect();
+        info!(
+            "Discovered {} tools from MCP server {}",
+            tools.len(),
+            server_name
+        );
+        for tool in tools {
+            self.tools
+                .insert(format!("{}_{}", server_name, tool.name), tool);
+        }
+        for item in prompts.unwrap_or_default() {
+            if let Some(prompt) = parse_prompt(&item) {
+                self.prompts
+                    .insert(format!("{}_{}", server_name, prompt.name), prompt);
+            }
+        }
+        for item in resources.unwrap_or_default() {
+            if let Some(resource) = parse_resource(&item) {
+                self.resources
+                    .insert(format!("{}_{}", server_name, resource.name), resource);
+            }
+        }
+
         Ok(())
     }
 
     /// Disconnect from a server
     pub async fn disconnect(&mut self, name: &str) -> Result<(), String> {
         if let Some(mut connection) = self.connections.remove(name) {
-            if let Some(ref mut transport) = connection.transport {
-                transport.close().await;
+            if let Some(transport) = connection.transport {
+                transport.lock().await.close().await;
             }
             if let Some(ref mut child) = connection.child {
                 child.kill().await.ok();
@@ -419,43 +845,34 @@ impl McpManager {
     }
 
     /// Call a tool on a specific server
+    ///
+    /// Transport failures count towards the server's circuit breaker; tool
+    /// errors reported by a responsive server do not.
     pub async fn call_tool(
         &mut self,
         server_name: &str,
         tool_name: &str,
         args: serde_json::Value,
     ) -> Result<McpResult, String> {
+        let transport = self.tool_transport(server_name)?;
+        let response = send_tool_call(&transport, tool_name, args).await;
+        self.record_outcome(server_name, &response);
+        Ok(tool_result(tool_name, &response?))
+    }
+
+    /// Transport for a `tools/call` on a server, unless its circuit is open
+    fn tool_transport(&self, server_name: &str) -> Result<SharedTransport, String> {
+        self.check_circuit(server_name)?;
+
         let connection = self
             .connections
-            .get_mut(server_name)
+            .get(server_name)
             .ok_or_else(|| format!("Not connected to server: {}", server_name))?;
 
-        let transport = connection
+        connection
             .transport
-            .as_mut()
-            .ok_or_else(|| format!("No transport for server: {}", server_name))?;
-
-        // Build JSON-RPC request
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": "tools/call",
-            "params": {
-                "name": tool_name,
-                "arguments": args
-            },
-            "id": 1
-        });
-
-        let response = transport.send(&request).await?;
-
-        // Parse response
-        let content = response.to_string();
-
-        Ok(McpResult {
-            content,
-            is_error: false,
-            tool_name: tool_name.to_string(),
-        })
+            .clone()
+            .ok_or_else(|| format!("No transport for server: {}", server_name))
     }
 
     /// Get a tool by name (including server prefix)
@@ -487,9 +904,191 @@ impl Default for McpManager {
     }
 }
 
+/// Send a `tools/call` request, holding only the transport's lock
+async fn send_tool_call(
+    transport: &SharedTransport,
+    tool_name: &str,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": {
+            "name": tool_name,
+            "arguments": args
+        },
+        "id": 1
+    });
+    transport.lock().await.send(&request).await
+}
+
+fn tool_result(tool_name: &str, response: &serde_json::Value) -> McpResult {
+    let (content, is_error) = parse_call_result(response);
+    McpResult {
+        content,
+        is_error,
+        tool_name: tool_name.to_string(),
+    }
+}
+
+/// Text and error flag of a `tools/call` response
+///
+/// Text content items are joined by newlines, other items (images, embedded
+/// resources) are kept as JSON. A JSON-RPC error or `isError: true` marks the
+/// result as an error.
+fn parse_call_result(response: &serde_json::Value) -> (String, bool) {
+    if let Some(error) = response.get("error") {
+        let message = error["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return (message, true);
+    }
+    let result = &response["result"];
+    let is_error = result["isError"].as_bool().unwrap_or(false);
+    let content = match result["content"].as_array() {
+        Some(items) => items
+            .iter()
+            .map(
+                |item| match (item["type"].as_str(), item["text"].as_str()) {
+                    (Some("text"), Some(text)) => text.to_string(),
+                    _ => item.to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => result.to_string(),
+    };
+    (content, is_error)
+}
+
+/// JSON-RPC error code for unimplemented methods
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Upper bound on pages fetched by one `list_all` call
+const MAX_LIST_PAGES: usize = 100;
+
+/// Send a paginated `*/list` request until no `nextCursor` is returned
+///
+/// Returns `None` when the server does not implement the method. A cursor
+/// the server already returned, or more than `MAX_LIST_PAGES` pages, is an
+/// error rather than an endless loop.
+async fn list_all(
+    transport: &mut dyn McpTransport,
+    method: &str,
+    field: &str,
+) -> Result<Option<Vec<serde_json::Value>>, String> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut seen_cursors = HashSet::new();
+
+    for _ in 0..MAX_LIST_PAGES {
+        let params = match &cursor {
+            Some(cursor) => serde_json::json!({ "cursor": cursor }),
+            None => serde_json::json!({}),
+        };
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        });
+
+        let response = transport.send(&request).await?;
+
+        if let Some(error) = response.get("error") {
+            if error.get("code").and_then(|c| c.as_i64()) == Some(METHOD_NOT_FOUND) {
+                debug!("MCP server does not implement {}, skipping", method);
+                return Ok(None);
+            }
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("{} failed: {}", method, message));
+        }
+
+        let result = response
+            .get("result")
+            .ok_or_else(|| format!("{} returned no result", method))?;
+        if let Some(page) = result.get(field).and_then(|v| v.as_array()) {
+            items.extend(page.iter().cloned());
+        }
+
+        cursor = result
+            .get("nextCursor")
+            .and_then(|c| c.as_str())
+            .map(str::to_string);
+        match &cursor {
+            None => return Ok(Some(items)),
+            Some(next) if !seen_cursors.insert(next.clone()) => {
+                return Err(format!("{} repeated cursor {:?}", method, next));
+            }
+            Some(_) => {}
+        }
+    }
+    Err(format!(
+        "{} returned more than {} pages",
+        method, MAX_LIST_PAGES
+    ))
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(str::to_string)
+}
+
+fn parse_tool(value: &serde_json::Value) -> Option<McpTool> {
+    Some(McpTool {
+        name: str_field(value, "name")?,
+        description: str_field(value, "description").unwrap_or_default(),
+        input_schema: value
+            .get("inputSchema")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null),
+    })
+}
+
+fn parse_prompt(value: &serde_json::Value) -> Option<McpPrompt> {
+    let arguments = value
+        .get("arguments")
+        .and_then(|v| v.as_array())
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| {
+                    Some(McpPromptArgument {
+                        name: str_field(arg, "name")?,
+                        description: str_field(arg, "description").unwrap_or_default(),
+                        required: arg
+                            .get("required")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false),
+                        r#type: str_field(arg, "type"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(McpPrompt {
+        name: str_field(value, "name")?,
+        description: str_field(value, "description").unwrap_or_default(),
+        arguments,
+    })
+}
+
+fn parse_resource(value: &serde_json::Value) -> Option<McpResource> {
+    Some(McpResource {
+        uri: str_field(value, "uri")?,
+        name: str_field(value, "name")?,
+        description: str_field(value, "description").unwrap_or_default(),
+        mime_type: str_field(value, "mimeType").unwrap_or_default(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
     #[test]
     fn test_server_config_serde() {
@@ -589,4 +1188,537 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "read");
     }
+
+    /// Transport that answers each method with a canned response
+    struct MockTransport {
+        responses: HashMap<String, serde_json::Value>,
+    }
+
+    #[async_trait::async_trait]
+    impl McpTransport for MockTransport {
+        async fn send(&mut self, message: &serde_json::Value) -> Result<serde_json::Value, String> {
+            let method = message["method"].as_str().unwrap_or_default();
+            Ok(self.responses.get(method).cloned().unwrap_or_else(|| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": message["id"],
+                    "error": { "code": -32601, "message": "Method not found" }
+                })
+            }))
+        }
+
+        async fn close(&mut self) {}
+    }
+
+    fn manager_with_mock(responses: HashMap<String, serde_json::Value>) -> McpManager {
+        let mut manager = McpManager::new();
+        let config = McpServerConfig {
+            name: "fs".to_string(),
+            server_type: McpServerType::Local,
+            command: None,
+            url: None,
+            enabled: true,
+            timeout_ms: 30000,
+            oauth: None,
+            headers: None,
+        };
+        manager.connections.insert(
+            "fs".to_string(),
+            McpConnection {
+                config,
+                child: None,
+                transport: Some(Arc::new(Mutex::new(MockTransport { responses }))),
+            },
+        );
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_connect_configured_merges_config_dirs() {
+        let user = tempfile::TempDir::new().unwrap();
+        let project = tempfile::TempDir::new().unwrap();
+        let dirs = [user.path().to_path_buf(), project.path().to_path_buf()];
+        assert!(McpManager::connect_configured(&dirs).await.is_none());
+
+        let server = |name: &str| {
+            format!(
+                "- name: {}\n  server_type: Local\n  command: null\n  url: null\n  enabled: false\n  timeout_ms: 1000\n  oauth: null\n  headers: null\n",
+                name
+            )
+        };
+        std::fs::write(user.path().join(MCP_CONFIG_FILE), server("docs")).unwrap();
+        std::fs::write(project.path().join(MCP_CONFIG_FILE), server("fs")).unwrap();
+
+        let manager = McpManager::connect_configured(&dirs).await.unwrap();
+        let mut names: Vec<&String> = manager.servers.keys().collect();
+        names.sort();
+        assert_eq!(names, ["docs", "fs"]);
+        // Disabled servers are not connected
+        assert!(!manager.is_connected("fs"));
+    }
+
+    #[tokio::test]
+    async fn test_discover_resources_populates_tools() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            "tools/list".to_string(),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "tools": [{
+                        "name": "read_file",
+                        "description": "Read a file",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": { "path": { "type": "string" } },
+                            "required": ["path"]
+                        }
+                    }]
+                }
+            }),
+        );
+        responses.insert(
+            "resources/list".to_string(),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "resources": [{
+                        "uri": "file:///README.md",
+                        "name": "readme",
+                        "mimeType": "text/markdown"
+                    }]
+                }
+            }),
+        );
+        // prompts/list falls through to method-not-found.
+        let mut manager = manager_with_mock(responses);
+
+        manager.discover_resources("fs").await.unwrap();
+
+        let tool = manager.get_tool("fs_read_file").unwrap();
+        assert_eq!(tool.name, "read_file");
+        assert_eq!(tool.input_schema["required"][0], "path");
+        assert!(manager.get_prompts().is_empty());
+        assert_eq!(
+            manager.get_resources()["fs_readme"].mime_type,
+            "text/markdown"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discover_resources_fails_on_other_errors() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            "tools/list".to_string(),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32603, "message": "Internal error" }
+            }),
+        );
+        let mut manager = manager_with_mock(responses);
+
+        let err = manager.discover_resources("fs").await.unwrap_err();
+        assert!(err.contains("Internal error"));
+        assert!(manager.get_tools().is_empty());
+    }
+
+    /// Transport whose `tools/list` pages never end, with cursors from `cursor`
+    struct EndlessTransport {
+        pages: usize,
+        cursor: fn(usize) -> String,
+    }
+
+    #[async_trait::async_trait]
+    impl McpTransport for EndlessTransport {
+        async fn send(&mut self, _: &serde_json::Value) -> Result<serde_json::Value, String> {
+            self.pages += 1;
+            Ok(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "tools": [{ "name": format!("tool_{}", self.pages) }],
+                    "nextCursor": (self.cursor)(self.pages)
+                }
+            }))
+        }
+
+        async fn close(&mut self) {}
+    }
+
+    #[tokio::test]
+    async fn test_list_all_stops_on_repeated_cursor() {
+        let mut transport = EndlessTransport {
+            pages: 0,
+            cursor: |page| format!("page-{}", page.min(3)),
+        };
+
+        let err = list_all(&mut transport, "tools/list", "tools")
+            .await
+            .unwrap_err();
+        assert!(err.contains("repeated cursor"), "{}", err);
+        assert_eq!(transport.pages, 4);
+    }
+
+    #[tokio::test]
+    async fn test_list_all_caps_page_count() {
+        let mut transport = EndlessTransport {
+            pages: 0,
+            cursor: |page| format!("page-{}", page),
+        };
+
+        let err = list_all(&mut transport, "tools/list", "tools")
+            .await
+            .unwrap_err();
+        assert!(err.contains("more than"), "{}", err);
+        assert_eq!(transport.pages, MAX_LIST_PAGES);
+    }
+
+    /// Transport that fails while `down` is set, counting every send
+    struct FlakyTransport {
+        down: Arc<AtomicBool>,
+        sends: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl McpTransport for FlakyTransport {
+        async fn send(&mut self, message: &serde_json::Value) -> Result<serde_json::Value, String> {
+            self.sends.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                return Err("connection reset".to_string());
+            }
+            Ok(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": message["id"],
+                "result": { "content": [{ "type": "text", "text": "ok" }] }
+            }))
+        }
+
+        async fn close(&mut self) {}
+    }
+
+    fn flaky_manager(
+        threshold: u32,
+        cooldown: Duration,
+    ) -> (McpManager, Arc<AtomicBool>, Arc<AtomicUsize>) {
+        let down = Arc::new(AtomicBool::new(true));
+        let sends = Arc::new(AtomicUsize::new(0));
+        let mut manager =
+            manager_with_mock(HashMap::new()).with_circuit_breaker(threshold, cooldown);
+        manager.connections.get_mut("fs").unwrap().transport =
+            Some(Arc::new(Mutex::new(FlakyTransport {
+                down: down.clone(),
+                sends: sends.clone(),
+            })));
+        (manager, down, sends)
+    }
+
+    async fn call(manager: &mut McpManager) -> Result<McpResult, String> {
+        manager
+            .call_tool("fs", "read_file", serde_json::json!({}))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_open_circuit() {
+        let (mut manager, _down, sends) = flaky_manager(3, Duration::from_secs(60));
+
+        for _ in 0..3 {
+            assert_eq!(call(&mut manager).await.unwrap_err(), "connection reset");
+        }
+        assert!(matches!(
+            manager.circuit_state("fs"),
+            CircuitState::Open { .. }
+        ));
+
+        // Open circuit short-circuits without touching the server
+        let err = call(&mut manager).await.unwrap_err();
+        assert!(err.contains("circuit open"), "{}", err);
+        assert_eq!(sends.load(Ordering::SeqCst), 3);
+
+        // Reconnects are short-circuited too
+        manager.add_server(McpServerConfig {
+            name: "broken".to_string(),
+            server_type: McpServerType::Local,
+            command: Some(vec!["/nonexistent/mcp-server".to_string()]),
+            url: None,
+            enabled: true,
+            timeout_ms: 1000,
+            oauth: None,
+            headers: None,
+        });
+        for _ in 0..3 {
+            let err = manager.connect_server("broken").await.unwrap_err();
+            assert!(!err.contains("circuit open"), "{}", err);
+        }
+        let err = manager.connect_server("broken").await.unwrap_err();
+        assert!(err.contains("MCP server broken circuit open"), "{}", err);
+        assert_eq!(manager.consecutive_failures("broken"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_recovers_after_cooldown() {
+        let cooldown = Duration::from_millis(100);
+        let (mut manager, down, sends) = flaky_manager(2, cooldown);
+        for _ in 0..2 {
+            call(&mut manager).await.unwrap_err();
+        }
+        tokio::time::sleep(cooldown).await;
+        assert_eq!(manager.circuit_state("fs"), CircuitState::HalfOpen);
+
+        // A failed probe re-opens at once
+        assert_eq!(call(&mut manager).await.unwrap_err(), "connection reset");
+        assert!(matches!(
+            manager.circuit_state("fs"),
+            CircuitState::Open { .. }
+        ));
+        assert!(
+            call(&mut manager)
+                .await
+                .unwrap_err()
+                .contains("circuit open")
+        );
+        assert_eq!(sends.load(Ordering::SeqCst), 3);
+
+        // A successful probe closes the circuit
+        tokio::time::sleep(cooldown).await;
+        down.store(false, Ordering::SeqCst);
+        assert_eq!(call(&mut manager).await.unwrap().content, "ok");
+        assert_eq!(manager.circuit_state("fs"), CircuitState::Closed);
+        assert_eq!(manager.consecutive_failures("fs"), 0);
+    }
+
+    fn frame(message: &serde_json::Value) -> Vec<u8> {
+        let json = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", json.len(), json).into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_stdio_transport_echo_subprocess() {
+        // `cat` echoes each framed request back, so the response carries the same id.
+        let mut child = Command::new("cat")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let mut transport = StdioTransport::new(
+            child.stdin.take().unwrap(),
+            child.stdout.take().unwrap(),
+            Duration::from_secs(5),
+        );
+
+        for n in 0..3 {
+            let request = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "tools/call",
+                "params": { "n": n },
+                "id": 42
+            });
+            let response = transport.send(&request).await.unwrap();
+            assert_eq!(response["id"], 42);
+            assert_eq!(response["params"]["n"], n);
+        }
+
+        transport.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_stdio_transport_skips_notifications_and_buffers_responses() {
+        let (client_write, mut server_read) = tokio::io::duplex(4096);
+        let (mut server_write, client_read) = tokio::io::duplex(4096);
+        let mut transport = StdioTransport::new(client_write, client_read, Duration::from_secs(5));
+
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(&mut server_read);
+            let first = read_frame(&mut reader).await.unwrap().unwrap();
+            let second = read_frame(&mut reader).await.unwrap().unwrap();
+            let notification = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": {}
+            });
+            // Answer out of order with a notification interleaved.
+            for message in [
+                notification.clone(),
+                serde_json::json!({ "jsonrpc": "2.0", "id": second["id"], "result": "second" }),
+                notification,
+                serde_json::json!({ "jsonrpc": "2.0", "id": first["id"], "result": "first" }),
+            ] {
+                server_write.write_all(&frame(&message)).await.unwrap();
+            }
+        });
+
+        let request = serde_json::json!({ "jsonrpc": "2.0", "method": "ping", "id": 1 });
+        // Queue the second request on the wire before awaiting the first answer.
+        let first_id = transport.next_id;
+        let mut first = request.clone();
+        first["id"] = serde_json::json!(first_id);
+        transport.next_id += 1;
+        transport
+            .stdin
+            .as_mut()
+            .unwrap()
+            .write_all(&frame(&first))
+            .await
+            .unwrap();
+
+        let second = transport.send(&request).await.unwrap();
+        assert_eq!(second["result"], "second");
+        assert_eq!(second["id"], 1);
+
+        let first = tokio::time::timeout(Duration::from_secs(5), transport.wait_for(first_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first["result"], "first");
+    }
+
+    #[tokio::test]
+    async fn test_stdio_transport_times_out() {
+        let (client_write, _server_read) = tokio::io::duplex(4096);
+        let (_server_write, client_read) = tokio::io::duplex(4096);
+        let mut transport =
+            StdioTransport::new(client_write, client_read, Duration::from_millis(50));
+
+        let request = serde_json::json!({ "jsonrpc": "2.0", "method": "ping", "id": 1 });
+        let err = transport.send(&request).await.unwrap_err();
+        assert!(err.contains("No response"));
+    }
+
+    /// Token endpoint that serves `bodies` in order and records request bodies
+    async fn mock_token_endpoint(
+        bodies: Vec<serde_json::Value>,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            for body in bodies {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| {
+                                l.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .and_then(|v| v.trim().parse::<usize>().ok())
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            seen.lock().unwrap().push(text[end + 4..].to_string());
+                            break;
+                        }
+                    }
+                }
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{}/token", addr), requests)
+    }
+
+    fn oauth_config(token_url: String) -> McpOAuthConfig {
+        McpOAuthConfig {
+            client_id: "ndc-client".to_string(),
+            client_secret: Some("s3cret".to_string()),
+            scope: "mcp:tools".to_string(),
+            token_url,
+            authorization_url: None,
+            redirect_uri: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oauth_client_credentials_token_is_cached() {
+        let (url, requests) = mock_token_endpoint(vec![
+            serde_json::json!({ "access_token": "tok-1", "token_type": "bearer", "expires_in": 3600 }),
+        ])
+        .await;
+        let oauth = oauth_config(url);
+        let mut manager = McpManager::new();
+
+        assert_eq!(
+            manager.oauth_token("remote", &oauth).await.unwrap(),
+            "tok-1"
+        );
+        assert_eq!(
+            manager.oauth_token("remote", &oauth).await.unwrap(),
+            "tok-1"
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("grant_type=client_credentials"));
+        assert!(requests[0].contains("client_id=ndc-client"));
+        assert!(requests[0].contains("client_secret=s3cret"));
+        assert!(requests[0].contains("scope=mcp%3Atools"));
+        assert!(!manager.oauth_tokens["remote"].is_stale());
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_with_huge_lifetime_never_expires() {
+        let (url, _requests) = mock_token_endpoint(vec![
+            serde_json::json!({ "access_token": "tok-1", "expires_in": u64::MAX }),
+        ])
+        .await;
+        let mut manager = McpManager::new();
+
+        assert_eq!(
+            manager
+                .oauth_token("remote", &oauth_config(url))
+                .await
+                .unwrap(),
+            "tok-1"
+        );
+        let token = &manager.oauth_tokens["remote"];
+        assert!(token.expires_at.is_none());
+        assert!(!token.is_stale());
+    }
+
+    #[tokio::test]
+    async fn test_oauth_token_refreshes_when_expired() {
+        let (url, requests) = mock_token_endpoint(vec![
+            serde_json::json!({ "access_token": "tok-1", "expires_in": 1 }),
+            serde_json::json!({ "access_token": "tok-2", "expires_in": 3600 }),
+        ])
+        .await;
+        let oauth = oauth_config(url);
+        let mut manager = McpManager::new();
+
+        assert_eq!(
+            manager.oauth_token("remote", &oauth).await.unwrap(),
+            "tok-1"
+        );
+        assert!(manager.oauth_tokens["remote"].is_stale());
+
+        assert_eq!(
+            manager.oauth_token("remote", &oauth).await.unwrap(),
+            "tok-2"
+        );
+        assert_eq!(
+            manager.oauth_token("remote", &oauth).await.unwrap(),
+            "tok-2"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
 }
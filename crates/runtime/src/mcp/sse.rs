@@ -0,0 +1,453 @@
+//! SSE transport for remote MCP servers
+//!
+//! The server pushes JSON-RPC messages over a `text/event-stream`; requests are
+//! POSTed to the message endpoint announced by the stream's `endpoint` event
+//! and their responses are matched by JSON-RPC id. A dropped stream is reopened
+//! with exponential backoff, sending `Last-Event-ID` so the server can resume.
+
+use super::{McpTransport, wait_for_response};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, warn};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+/// Consecutive reconnects without receiving an event before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// SSE transport for remote MCP servers
+pub struct SseTransport {
+    client: reqwest::Client,
+    token: Option<String>,
+    /// Message endpoint announced by the server
+    endpoint: watch::Receiver<Option<String>>,
+    incoming: Option<mpsc::UnboundedReceiver<serde_json::Value>>,
+    reader: Option<tokio::task::JoinHandle<()>>,
+    last_event_id: Arc<Mutex<Option<String>>>,
+    /// Responses that arrived for requests other than the one being awaited
+    pending: HashMap<u64, serde_json::Value>,
+    next_id: u64,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for SseTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SseTransport")
+            .field("endpoint", &*self.endpoint.borrow())
+            .field("open", &self.incoming.is_some())
+            .field("pending", &self.pending.len())
+            .field("next_id", &self.next_id)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl SseTransport {
+    /// Open the event stream at `url` in the background
+    ///
+    /// `send` waits (up to `timeout`) for the server's `endpoint` event.
+    pub fn connect(url: String, token: Option<String>, timeout: Duration) -> Self {
+        let client = reqwest::Client::new();
+        let (endpoint_tx, endpoint_rx) = watch::channel(None);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let last_event_id = Arc::new(Mutex::new(None));
+
+        let stream = EventStream {
+            client: client.clone(),
+            url,
+            token: token.clone(),
+            endpoint: endpoint_tx,
+            incoming: tx,
+            last_event_id: last_event_id.clone(),
+        };
+        let reader = tokio::spawn(stream.run());
+
+        Self {
+            client,
+            token,
+            endpoint: endpoint_rx,
+            incoming: Some(rx),
+            reader: Some(reader),
+            last_event_id,
+            pending: HashMap::new(),
+            next_id: 1,
+            timeout,
+        }
+    }
+
+    /// Id of the last event received, sent as `Last-Event-ID` on reconnect
+    pub fn last_event_id(&self) -> Option<String> {
+        self.last_event_id
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    async fn message_endpoint(&self) -> Result<String, String> {
+        let mut endpoint = self.endpoint.clone();
+        let announced = tokio::time::timeout(self.timeout, endpoint.wait_for(Option::is_some))
+            .await
+            .map_err(|_| format!("No SSE endpoint event within {:?}", self.timeout))?
+            .map_err(|_| "SSE stream closed".to_string())?;
+        Ok(announced.clone().unwrap_or_default())
+    }
+}
+
+#[async_trait::async_trait]
+impl McpTransport for SseTransport {
+    async fn send(&mut self, message: &serde_json::Value) -> Result<serde_json::Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let original_id = message.get("id").cloned();
+        let mut request = message.clone();
+        request["id"] = serde_json::json!(id);
+
+        let endpoint = self.message_endpoint().await?;
+        let mut post = self.client.post(&endpoint).json(&request);
+        if let Some(ref token) = self.token {
+            post = post.bearer_auth(token);
+        }
+
+        let response = post
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let timeout = self.timeout;
+        let mut response = tokio::time::timeout(
+            timeout,
+            wait_for_response(self.incoming.as_mut(), &mut self.pending, id, "SSE stream"),
+        )
+        .await
+        .map_err(|_| format!("No response to request {} within {:?}", id, timeout))??;
+
+        if let Some(original_id) = original_id {
+            response["id"] = original_id;
+        }
+        Ok(response)
+    }
+
+    async fn close(&mut self) {
+        self.incoming = None;
+        self.token = None;
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+    }
+}
+
+/// Background reader for the server-to-client event stream
+struct EventStream {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    endpoint: watch::Sender<Option<String>>,
+    incoming: mpsc::UnboundedSender<serde_json::Value>,
+    last_event_id: Arc<Mutex<Option<String>>>,
+}
+
+impl EventStream {
+    async fn run(self) {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut failures = 0;
+
+        loop {
+            match self.read_once().await {
+                Ok(true) => {
+                    failures = 0;
+                    backoff = INITIAL_BACKOFF;
+                }
+                Ok(false) => {}
+                Err(e) => warn!("MCP SSE stream {} failed: {}", self.url, e),
+            }
+            if self.incoming.is_closed() {
+                return;
+            }
+
+            failures += 1;
+            if failures > MAX_RECONNECT_ATTEMPTS {
+                warn!("Giving up on MCP SSE stream {}", self.url);
+                return;
+            }
+            debug!("Reconnecting MCP SSE stream in {:?}", backoff);
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Read the stream until it ends; `Ok(true)` if any event was received
+    async fn read_once(&self) -> Result<bool, String> {
+        let mut request = self
+            .client
+            .get(&self.url)
+            .header(reqwest::header::ACCEPT, "text/event-stream");
+        if let Some(ref token) = self.token {
+            request = request.bearer_auth(token);
+        }
+        let resume_from = self
+            .last_event_id
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(id) = resume_from {
+            request = request.header("Last-Event-ID", id);
+        }
+
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let mut parser = SseParser::default();
+        let mut received = false;
+        while let Ok(Some(chunk)) = response.chunk().await {
+            for event in parser.push(&chunk) {
+                received = true;
+                self.dispatch(event);
+            }
+            if self.incoming.is_closed() {
+                break;
+            }
+        }
+        Ok(received)
+    }
+
+    fn dispatch(&self, event: SseEvent) {
+        if let Some(id) = event.id {
+            *self.last_event_id.lock().unwrap_or_else(|e| e.into_inner()) = Some(id);
+        }
+
+        match event.event.as_str() {
+            "endpoint" => {
+                match url::Url::parse(&self.url).and_then(|base| base.join(&event.data)) {
+                    Ok(endpoint) => {
+                        self.endpoint.send_replace(Some(endpoint.to_string()));
+                    }
+                    Err(e) => warn!("Invalid MCP SSE endpoint {}: {}", event.data, e),
+                }
+            }
+            "" | "message" => match serde_json::from_str(&event.data) {
+                Ok(message) => {
+                    let _ = self.incoming.send(message);
+                }
+                Err(e) => debug!("Ignoring non-JSON SSE message: {}", e),
+            },
+            other => debug!("Ignoring SSE event: {}", other),
+        }
+    }
+}
+
+/// A dispatched server-sent event
+#[derive(Debug, Default, PartialEq)]
+struct SseEvent {
+    event: String,
+    data: String,
+    id: Option<String>,
+}
+
+/// Incremental `text/event-stream` parser
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Feed a chunk and return every event it completes
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer
+            .extend(chunk.iter().copied().filter(|&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&block)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+/// Parse one event block; events without data are not dispatched
+fn parse_event(block: &str) -> Option<SseEvent> {
+    let mut event = SseEvent::default();
+    let mut data = Vec::new();
+
+    for line in block.lines() {
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => event.event = value.to_string(),
+            "data" => data.push(value),
+            "id" => event.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    if data.is_empty() {
+        return None;
+    }
+    event.data = data.join("\n");
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn test_parser_handles_split_chunks_and_multiline_data() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b": keep-alive\r\n\r\nevent: endp").is_empty());
+
+        let events =
+            parser.push(b"oint\r\ndata: /messages\r\n\r\nid: 7\ndata: {\"a\":\ndata: 1}\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: "endpoint".to_string(),
+                    data: "/messages".to_string(),
+                    id: None,
+                },
+                SseEvent {
+                    event: String::new(),
+                    data: "{\"a\":\n1}".to_string(),
+                    id: Some("7".to_string()),
+                },
+            ]
+        );
+    }
+
+    /// Read an HTTP request; returns (lowercased head, body)
+    async fn read_request(socket: &mut TcpStream) -> (String, String) {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let head = text[..end].to_ascii_lowercase();
+                let length = head
+                    .lines()
+                    .find_map(|l| {
+                        l.strip_prefix("content-length:")
+                            .and_then(|v| v.trim().parse::<usize>().ok())
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    return (head, text[end + 4..].to_string());
+                }
+            }
+        }
+        (String::new(), String::new())
+    }
+
+    /// MCP SSE server: drops the first stream right after the endpoint event,
+    /// then answers `tools/list` POSTs on the reopened stream
+    async fn mock_sse_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let heads = Arc::new(Mutex::new(Vec::new()));
+        let seen = heads.clone();
+        let (events_tx, events_rx) = mpsc::unbounded_channel::<String>();
+        let events_rx = Arc::new(tokio::sync::Mutex::new(events_rx));
+
+        tokio::spawn(async move {
+            let mut streams = 0;
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let (head, body) = read_request(&mut socket).await;
+                seen.lock().unwrap().push(head.clone());
+
+                if head.starts_with("get") {
+                    streams += 1;
+                    let first = streams == 1;
+                    let events_rx = events_rx.clone();
+                    tokio::spawn(async move {
+                        let opening = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\nid: evt-0\nevent: endpoint\ndata: /messages?session=1\n\n";
+                        let _ = socket.write_all(opening.as_bytes()).await;
+                        if first {
+                            return;
+                        }
+                        let mut events_rx = events_rx.lock().await;
+                        while let Some(frame) = events_rx.recv().await {
+                            if socket.write_all(frame.as_bytes()).await.is_err() {
+                                return;
+                            }
+                        }
+                    });
+                } else {
+                    let request: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    let response = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "result": { "tools": [{ "name": "search", "description": "Search docs" }] }
+                    });
+                    let _ = socket
+                        .write_all(
+                            b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        )
+                        .await;
+                    let _ = events_tx.send(
+                        "event: message\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\n\n"
+                            .to_string(),
+                    );
+                    let _ = events_tx
+                        .send(format!("id: evt-1\nevent: message\ndata: {}\n\n", response));
+                }
+            }
+        });
+
+        (format!("http://{}/sse", addr), heads)
+    }
+
+    #[tokio::test]
+    async fn test_sse_transport_tools_list_round_trip() {
+        let (url, heads) = mock_sse_server().await;
+        let mut transport =
+            SseTransport::connect(url, Some("tok-1".to_string()), Duration::from_secs(5));
+
+        let request = serde_json::json!({ "jsonrpc": "2.0", "method": "tools/list", "id": 42 });
+        let response = transport.send(&request).await.unwrap();
+
+        assert_eq!(response["id"], 42);
+        assert_eq!(response["result"]["tools"][0]["name"], "search");
+        assert_eq!(transport.last_event_id().as_deref(), Some("evt-1"));
+
+        let heads = heads.lock().unwrap().clone();
+        let gets: Vec<_> = heads.iter().filter(|h| h.starts_with("get")).collect();
+        let posts: Vec<_> = heads.iter().filter(|h| h.starts_with("post")).collect();
+        assert!(gets.len() >= 2, "stream should reconnect after the drop");
+        assert!(gets[1].contains("last-event-id: evt-0"));
+        assert!(posts[0].starts_with("post /messages?session=1"));
+        assert!(
+            heads
+                .iter()
+                .all(|h| h.contains("authorization: bearer tok-1"))
+        );
+
+        transport.close().await;
+    }
+}
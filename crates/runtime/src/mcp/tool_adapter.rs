@@ -0,0 +1,397 @@
+//! MCP Tool Adapter - discovered MCP tools as agent tools
+//!
+//! Each tool discovered by `McpManager` is wrapped in a `McpToolAdapter`
+//! implementing `Tool`: the agent's arguments become the `tools/call`
+//! `arguments`, and the `McpResult` becomes a `ToolResult` (`is_error` →
+//! `success: false`).
+//!
+//! Tools keep their MCP name (`read_file`) unless it is already taken in the
+//! registry, by a built-in tool or another server; then they are namespaced
+//! as `mcp_<server>_<tool>`.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{McpManager, McpTool, send_tool_call, tool_result};
+use crate::tools::{Tool, ToolError, ToolMetadata, ToolRegistry, ToolResult};
+
+/// Agent tool backed by a tool on a connected MCP server
+pub struct McpToolAdapter {
+    manager: Arc<Mutex<McpManager>>,
+    server: String,
+    tool: McpTool,
+    name: String,
+}
+
+impl std::fmt::Debug for McpToolAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpToolAdapter")
+            .field("server", &self.server)
+            .field("tool", &self.tool.name)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl McpToolAdapter {
+    /// Adapter calling `tool` on `server`, exposed to the agent as `name`
+    pub fn new(
+        manager: Arc<Mutex<McpManager>>,
+        server: impl Into<String>,
+        tool: McpTool,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            manager,
+            server: server.into(),
+            tool,
+            name: name.into(),
+        }
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Tool name on the MCP server
+    pub fn mcp_name(&self) -> &str {
+        &self.tool.name
+    }
+}
+
+#[async_trait]
+impl Tool for McpToolAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.tool.description
+    }
+
+    async fn execute(&self, params: &serde_json::Value) -> Result<ToolResult, ToolError> {
+        let arguments = match params {
+            serde_json::Value::Null => serde_json::json!({}),
+            serde_json::Value::Object(_) => params.clone(),
+            other => {
+                return Err(ToolError::InvalidArgument(format!(
+                    "MCP tool arguments must be an object, got {}",
+                    other
+                )));
+            }
+        };
+
+        let start = std::time::Instant::now();
+        let failed = |e: String| {
+            ToolError::ExecutionFailed(format!("MCP {}/{}: {}", self.server, self.tool.name, e))
+        };
+        // The manager is only locked around the bookkeeping, so a slow server
+        // does not block calls to other servers
+        let transport = self
+            .manager
+            .lock()
+            .await
+            .tool_transport(&self.server)
+            .map_err(failed)?;
+        let response = send_tool_call(&transport, &self.tool.name, arguments).await;
+        self.manager
+            .lock()
+            .await
+            .record_outcome(&self.server, &response);
+        let result = tool_result(&self.tool.name, &response.map_err(failed)?);
+
+        let metadata = ToolMetadata {
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            bytes_processed: result.content.len() as u64,
+            ..Default::default()
+        };
+        Ok(match result.is_error {
+            false => ToolResult {
+                success: true,
+                output: result.content,
+                error: None,
+                metadata,
+            },
+            true => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(result.content),
+                metadata,
+            },
+        })
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        match &self.tool.input_schema {
+            schema @ serde_json::Value::Object(map) if !map.is_empty() => schema.clone(),
+            _ => serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+}
+
+/// Register every tool discovered by `manager` in `registry`
+///
+/// Returns the registered names, namespacing those that collide.
+pub async fn register_mcp_tools(
+    registry: &mut ToolRegistry,
+    manager: Arc<Mutex<McpManager>>,
+) -> Vec<String> {
+    let mut discovered: Vec<(String, McpTool)> = {
+        let guard = manager.lock().await;
+        guard
+            .get_tools()
+            .iter()
+            .filter_map(|(key, tool)| {
+                // Keys are `<server>_<tool>`
+                let server = key.strip_suffix(&format!("_{}", tool.name))?;
+                Some((server.to_string(), tool.clone()))
+            })
+            .collect()
+    };
+    discovered.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+
+    let mut names = Vec::with_capacity(discovered.len());
+    for (server, tool) in discovered {
+        let name = match registry.contains(&tool.name) {
+            false => tool.name.clone(),
+            true => format!("mcp_{}_{}", server, tool.name),
+        };
+        registry.register_as(
+            &name,
+            McpToolAdapter::new(manager.clone(), server, tool, name.clone()),
+        );
+        names.push(name);
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{McpConnection, McpServerConfig, McpServerType, McpTransport};
+    use std::sync::Mutex as StdMutex;
+
+    /// Records `tools/call` requests and answers with `response`
+    struct RecordingTransport {
+        requests: Arc<StdMutex<Vec<serde_json::Value>>>,
+        response: serde_json::Value,
+    }
+
+    #[async_trait]
+    impl McpTransport for RecordingTransport {
+        async fn send(&mut self, message: &serde_json::Value) -> Result<serde_json::Value, String> {
+            self.requests.lock().unwrap().push(message.clone());
+            Ok(self.response.clone())
+        }
+
+        async fn close(&mut self) {}
+    }
+
+    fn manager(
+        response: serde_json::Value,
+    ) -> (
+        Arc<Mutex<McpManager>>,
+        Arc<StdMutex<Vec<serde_json::Value>>>,
+    ) {
+        let requests = Arc::new(StdMutex::new(Vec::new()));
+        let transport = RecordingTransport {
+            requests: requests.clone(),
+            response,
+        };
+        (manager_with(transport), requests)
+    }
+
+    /// Manager connected to server `fs` offering `read_file` and `list_dir`
+    fn manager_with(transport: impl McpTransport + 'static) -> Arc<Mutex<McpManager>> {
+        let mut manager = McpManager::new();
+        manager.connections.insert(
+            "fs".to_string(),
+            McpConnection {
+                config: McpServerConfig {
+                    name: "fs".to_string(),
+                    server_type: McpServerType::Local,
+                    command: None,
+                    url: None,
+                    enabled: true,
+                    timeout_ms: 30000,
+                    oauth: None,
+                    headers: None,
+                },
+                child: None,
+                transport: Some(Arc::new(Mutex::new(transport))),
+            },
+        );
+        for name in ["read_file", "list_dir"] {
+            manager.tools.insert(
+                format!("fs_{}", name),
+                McpTool {
+                    name: name.to_string(),
+                    description: format!("MCP {}", name),
+                    input_schema: serde_json::json!({
+                        "type": "object",
+                        "properties": { "path": { "type": "string" } }
+                    }),
+                },
+            );
+        }
+        Arc::new(Mutex::new(manager))
+    }
+
+    /// Answers a `tools/call` only once `release` is notified
+    struct GatedTransport {
+        release: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait]
+    impl McpTransport for GatedTransport {
+        async fn send(
+            &mut self,
+            _message: &serde_json::Value,
+        ) -> Result<serde_json::Value, String> {
+            self.release.notified().await;
+            Ok(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "content": [{ "type": "text", "text": "done" }] }
+            }))
+        }
+
+        async fn close(&mut self) {}
+    }
+
+    /// Built-in tool whose name collides with an MCP tool
+    struct BuiltinReadFile;
+
+    #[async_trait]
+    impl Tool for BuiltinReadFile {
+        fn name(&self) -> &str {
+            "read_file"
+        }
+
+        fn description(&self) -> &str {
+            "built-in"
+        }
+
+        async fn execute(&self, _params: &serde_json::Value) -> Result<ToolResult, ToolError> {
+            Err(ToolError::ExecutionFailed("not the MCP tool".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mcp_tool_call_round_trips_through_adapter() {
+        let (manager, requests) = manager(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "content": [
+                    { "type": "text", "text": "# README" },
+                    { "type": "text", "text": "hello" }
+                ],
+                "isError": false
+            }
+        }));
+        let mut registry = ToolRegistry::new();
+        registry.register(BuiltinReadFile);
+
+        let names = register_mcp_tools(&mut registry, manager).await;
+        assert_eq!(names, vec!["list_dir", "mcp_fs_read_file"]);
+        assert_eq!(
+            registry.metadata("mcp_fs_read_file").unwrap().schema["properties"]["path"]["type"],
+            "string"
+        );
+
+        let result = registry
+            .execute(
+                "mcp_fs_read_file",
+                &serde_json::json!({ "path": "README.md" }),
+            )
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "# README\nhello");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["method"], "tools/call");
+        assert_eq!(
+            requests[0]["params"],
+            serde_json::json!({ "name": "read_file", "arguments": { "path": "README.md" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mcp_tool_errors_map_to_failed_results() {
+        let (manager, _) = manager(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "content": [{ "type": "text", "text": "no such file" }],
+                "isError": true
+            }
+        }));
+        let adapter = McpToolAdapter::new(
+            manager.clone(),
+            "fs",
+            manager
+                .lock()
+                .await
+                .get_tool("fs_read_file")
+                .unwrap()
+                .clone(),
+            "read_file",
+        );
+
+        let result = adapter
+            .execute(&serde_json::json!({ "path": "missing" }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("no such file"));
+
+        assert!(matches!(
+            adapter.execute(&serde_json::json!("missing")).await,
+            Err(ToolError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_manager_stays_unlocked_while_a_call_is_in_flight() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let manager = manager_with(GatedTransport {
+            release: release.clone(),
+        });
+        let adapter = Arc::new(McpToolAdapter::new(
+            manager.clone(),
+            "fs",
+            manager
+                .lock()
+                .await
+                .get_tool("fs_read_file")
+                .unwrap()
+                .clone(),
+            "read_file",
+        ));
+
+        let call = tokio::spawn({
+            let adapter = adapter.clone();
+            async move { adapter.execute(&serde_json::json!({ "path": "a" })).await }
+        });
+        tokio::task::yield_now().await;
+
+        // Other servers' calls and discovery need the manager meanwhile
+        let tools = tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            manager.lock().await.get_tools().len()
+        })
+        .await
+        .expect("manager locked during the call");
+        assert_eq!(tools, 2);
+
+        release.notify_one();
+        let result = call.await.unwrap().unwrap();
+        assert_eq!(result.output, "done");
+    }
+}
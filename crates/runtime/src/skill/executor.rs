@@ -1,12 +1,13 @@
 //! Skill Execution Engine
 //!
 //! Responsibilities:
+//! - Validate and coerce parameters against `SkillParameter` declarations
 //! - Execute skills with parameter substitution
 //! - Integrate with LLM for AI-powered skills
 //! - Chain multiple skills together
 //! - Track execution state and results
 
-use super::{Skill, SkillRegistry};
+use super::{Skill, SkillParameter, SkillRegistry};
 use regex::Regex;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -83,6 +84,63 @@ pub struct ToolCall {
     pub result: Option<String>,
 }
 
+/// Placeholders filled in by the executor itself
+const BUILTIN_PLACEHOLDERS: &[&str] = &["cwd", "timestamp", "thought"];
+
+/// Invalid skill invocation, reported before anything runs
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SkillParamError {
+    #[error("Missing required parameter(s): {}", .0.join(", "))]
+    MissingRequired(Vec<String>),
+    #[error("Parameter '{name}' expects a {expected}, got '{value}'")]
+    TypeMismatch {
+        name: String,
+        expected: String,
+        value: String,
+    },
+    #[error("Unknown placeholder(s) in skill content: {}", .0.join(", "))]
+    UnknownPlaceholders(Vec<String>),
+}
+
+/// `{{variable}}` placeholders
+fn placeholder_regex() -> &'static Regex {
+    static RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}").expect("valid placeholder pattern")
+    })
+}
+
+/// Coerce `value` to the declared type; unknown types are passed through
+fn coerce_parameter(param: &SkillParameter, value: &str) -> Result<String, SkillParamError> {
+    let mismatch = || SkillParamError::TypeMismatch {
+        name: param.name.clone(),
+        expected: param.r#type.to_ascii_lowercase(),
+        value: value.to_string(),
+    };
+    let trimmed = value.trim();
+    match param.r#type.to_ascii_lowercase().as_str() {
+        "integer" | "int" => trimmed
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| mismatch()),
+        "number" | "float" => {
+            if let Ok(n) = trimmed.parse::<i64>() {
+                return Ok(n.to_string());
+            }
+            match trimmed.parse::<f64>() {
+                Ok(n) if n.is_finite() => Ok(n.to_string()),
+                _ => Err(mismatch()),
+            }
+        }
+        "bool" | "boolean" => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok("true".to_string()),
+            "false" | "no" | "0" => Ok("false".to_string()),
+            _ => Err(mismatch()),
+        },
+        _ => Ok(value.to_string()),
+    }
+}
+
 /// Skill Executor
 #[derive(Clone)]
 pub struct SkillExecutor {
@@ -120,6 +178,9 @@ impl SkillExecutor {
         let start_time = std::time::Instant::now();
         let mut steps = Vec::new();
 
+        let parameters =
+            Self::validate_parameters(&skill, parameters).map_err(|e| e.to_string())?;
+
         // Build execution context if not set
         if self.context.is_none() {
             self.context = Some(SkillExecutionContext::default());
@@ -132,6 +193,11 @@ impl SkillExecutor {
             }
         }
 
+        {
+            let context = self.context.as_ref().expect("context set above");
+            Self::check_placeholders(&skill, context).map_err(|e| e.to_string())?;
+        }
+
         // Execute skill content
         let output = {
             let context = self.context.as_ref().expect("context set above");
@@ -151,6 +217,55 @@ impl SkillExecutor {
         })
     }
 
+    /// Check `parameters` against the skill's declarations
+    ///
+    /// Every required parameter must be present; values of declared
+    /// parameters are coerced to their `type` (number, integer, bool).
+    /// Undeclared parameters pass through unchanged.
+    pub fn validate_parameters(
+        skill: &Skill,
+        mut parameters: HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, SkillParamError> {
+        let missing: Vec<String> = skill
+            .parameters
+            .iter()
+            .filter(|param| param.required && !parameters.contains_key(&param.name))
+            .map(|param| param.name.clone())
+            .collect();
+        if !missing.is_empty() {
+            return Err(SkillParamError::MissingRequired(missing));
+        }
+
+        for param in &skill.parameters {
+            if let Some(value) = parameters.get_mut(&param.name) {
+                *value = coerce_parameter(param, value)?;
+            }
+        }
+        Ok(parameters)
+    }
+
+    /// Reject `{{name}}` placeholders that no parameter, variable or built-in fills
+    fn check_placeholders(
+        skill: &Skill,
+        context: &SkillExecutionContext,
+    ) -> Result<(), SkillParamError> {
+        let mut unknown: Vec<String> = Vec::new();
+        for caps in placeholder_regex().captures_iter(&skill.content) {
+            let name = &caps[1];
+            let known = BUILTIN_PLACEHOLDERS.contains(&name)
+                || context.variables.contains_key(name)
+                || skill.parameters.iter().any(|param| param.name == name);
+            if !known && !unknown.iter().any(|u| u == name) {
+                unknown.push(name.to_string());
+            }
+        }
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(SkillParamError::UnknownPlaceholders(unknown))
+        }
+    }
+
     /// Execute skill content with template substitution
     async fn execute_skill_content(
         &self,
@@ -256,21 +371,17 @@ impl SkillExecutor {
         let mut result = content.to_string();
 
         // Variable substitution: {{variable}}
-        if let Some(re) = Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}")
-            .ok()
-            .as_ref()
-        {
-            result = re
-                .replace_all(&result, |caps: &regex::Captures| {
-                    let var_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-                    context
-                        .variables
-                        .get(var_name)
-                        .map(|s| s.as_str())
-                        .unwrap_or("")
-                })
-                .to_string();
-        }
+        result = placeholder_regex()
+            .replace_all(&result, |caps: &regex::Captures| {
+                let var_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+                match context.variables.get(var_name) {
+                    Some(value) => value.clone(),
+                    // Built-ins are filled in below
+                    None if matches!(var_name, "cwd" | "timestamp") => caps[0].to_string(),
+                    None => String::new(),
+                }
+            })
+            .to_string();
 
         // Environment variables: {{env.VAR}}
         if let Some(re) = Regex::new(r"\{\{env\.([A-Z_][A-Z0-9_]*)\}\}").ok().as_ref() {
@@ -295,16 +406,8 @@ impl SkillExecutor {
         name: &str,
         invocation: &str,
     ) -> Result<SkillResult, String> {
-        let skill = {
-            let registry = self.registry.read().await;
-            match registry.get(name) {
-                Some(s) => s.clone(),
-                None => return Err(format!("Skill '{}' not found", name)),
-            }
-        };
-
         // Extract parameters from invocation
-        let parameters = Self::parse_invocation(invocation, &skill);
+        let parameters = Self::parse_invocation(invocation);
 
         let mut executor = self.clone();
         executor.context = self.context.clone();
@@ -312,7 +415,7 @@ impl SkillExecutor {
     }
 
     /// Parse skill invocation string into parameters
-    fn parse_invocation(invocation: &str, skill: &Skill) -> HashMap<String, String> {
+    fn parse_invocation(invocation: &str) -> HashMap<String, String> {
         let mut params = HashMap::new();
 
         // Simple key=value parsing
@@ -328,13 +431,6 @@ impl SkillExecutor {
             );
         }
 
-        // Check required parameters
-        for param in &skill.parameters {
-            if param.required && !params.contains_key(&param.name) {
-                params.insert(param.name.clone(), format!("{{{{ {} }}}}", param.name));
-            }
-        }
-
         params
     }
 
@@ -386,6 +482,7 @@ mod tests {
             parameters: vec![],
             examples: vec![],
             content: content.to_string(),
+            source: None,
         }
     }
 
@@ -545,12 +642,126 @@ This is a test skill content.
             }],
             examples: vec![],
             content: "test".to_string(),
+            source: None,
         };
 
         let invocation = "@test --path /tmp/test";
-        let params = SkillExecutor::parse_invocation(invocation, &skill);
+        let params = SkillExecutor::parse_invocation(invocation);
 
         assert!(params.contains_key("path"));
+        let params = SkillExecutor::validate_parameters(&skill, params).unwrap();
+        assert_eq!(params["path"], "/tmp/test");
+    }
+
+    fn parameter(name: &str, r#type: &str, required: bool) -> SkillParameter {
+        SkillParameter {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            description: String::new(),
+            required,
+        }
+    }
+
+    async fn executor_with(skill: Skill) -> SkillExecutor {
+        let registry = Arc::new(RwLock::new(SkillRegistry::new()));
+        registry
+            .write()
+            .await
+            .skills
+            .insert(skill.name.clone(), skill);
+        SkillExecutor::new(registry)
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_missing_required_parameters_are_listed() {
+        let mut skill = create_test_skill("deploy", "Deploy {{service}} to {{env}}");
+        skill.parameters = vec![
+            parameter("service", "string", true),
+            parameter("env", "string", true),
+            parameter("dry_run", "bool", false),
+        ];
+
+        assert_eq!(
+            SkillExecutor::validate_parameters(&skill, HashMap::new()),
+            Err(SkillParamError::MissingRequired(vec![
+                "service".to_string(),
+                "env".to_string()
+            ]))
+        );
+        let mut executor = executor_with(skill).await;
+        let err = executor
+            .execute("deploy", params(&[("env", "prod")]))
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Missing required parameter(s): service");
+    }
+
+    #[tokio::test]
+    async fn test_parameters_are_coerced_and_substituted() {
+        let mut skill = create_test_skill(
+            "scale",
+            "Scale {{service}} to {{replicas}} (ratio {{ratio}}, dry run: {{dry_run}}) in {{cwd}}",
+        );
+        skill.parameters = vec![
+            parameter("service", "string", true),
+            parameter("replicas", "number", true),
+            parameter("ratio", "number", false),
+            parameter("dry_run", "bool", false),
+        ];
+        let context = SkillExecutionContext {
+            working_dir: std::path::PathBuf::from("/srv"),
+            ..Default::default()
+        };
+        let mut executor = executor_with(skill).await.with_context(context);
+
+        let result = executor
+            .execute(
+                "scale",
+                params(&[
+                    ("service", "api"),
+                    ("replicas", " 3 "),
+                    ("ratio", "0.50"),
+                    ("dry_run", "YES"),
+                ]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.output.trim_end(),
+            "Scale api to 3 (ratio 0.5, dry run: true) in /srv"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_type_mismatch_and_unknown_placeholders_are_rejected() {
+        let mut skill = create_test_skill("scale", "Scale to {{replicas}}");
+        skill.parameters = vec![parameter("replicas", "number", true)];
+        assert_eq!(
+            SkillExecutor::validate_parameters(&skill, params(&[("replicas", "many")])),
+            Err(SkillParamError::TypeMismatch {
+                name: "replicas".to_string(),
+                expected: "number".to_string(),
+                value: "many".to_string(),
+            })
+        );
+
+        let mut executor = executor_with(create_test_skill(
+            "typo",
+            "Hello {{name}} from {{citty}} and {{citty}}",
+        ))
+        .await;
+        let err = executor
+            .execute("typo", params(&[("name", "Alice")]))
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Unknown placeholder(s) in skill content: citty");
     }
 
     #[tokio::test]
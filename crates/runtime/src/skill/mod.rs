@@ -2,7 +2,9 @@
 //!
 //! Responsibilities:
 //! - Skill discovery and loading from filesystem
+//! - Conflict resolution between discovery paths
 //! - Skill metadata parsing (YAML frontmatter)
+//! - Fuzzy search over name, tags and description
 //! - Template variable substitution
 //! - Skill execution engine
 //! - Integration with LLM and tool system
@@ -11,11 +13,13 @@ use glob::glob;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 pub mod executor;
-pub use executor::{SkillExecutionContext, SkillExecutor, SkillResult};
+pub mod search;
+pub use executor::{SkillExecutionContext, SkillExecutor, SkillParamError, SkillResult};
+pub use search::{DEFAULT_SEARCH_THRESHOLD, SkillMatch};
 
 /// Skill definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +34,52 @@ pub struct Skill {
     #[serde(default)]
     pub examples: Vec<SkillExample>,
     pub content: String,
+    /// Where the skill was loaded from (None for skills built in code)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SkillSource>,
+}
+
+/// Origin of a loaded skill
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillSource {
+    /// SKILL.md file path
+    pub path: PathBuf,
+    /// Discovery root the file was found under (None when loaded directly)
+    pub root: Option<PathBuf>,
+}
+
+/// How to resolve two skills with the same name
+///
+/// Discovery paths are scanned in the order they were added, so the
+/// earlier path has the higher priority under `FirstWins`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// Keep the skill that was loaded first
+    #[default]
+    FirstWins,
+    /// Replace the existing skill with the one loaded later
+    LastWins,
+    /// Treat a duplicate name as a load error
+    Error,
+}
+
+/// A skill that lost a name conflict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillOverride {
+    pub name: String,
+    /// Source of the skill that is now registered
+    pub winner: Option<SkillSource>,
+    /// Source of the skill that was dropped
+    pub overridden: Option<SkillSource>,
+}
+
+/// Result of `SkillRegistry::discover_and_load`
+#[derive(Debug, Clone, Default)]
+pub struct SkillDiscovery {
+    /// Number of skills newly registered or replaced
+    pub loaded: usize,
+    /// Conflicts resolved by the registry's `ConflictPolicy`
+    pub overridden: Vec<SkillOverride>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +103,7 @@ pub struct SkillRegistry {
     categories: HashMap<String, Vec<String>>,
     tags_index: HashMap<String, Vec<String>>,
     discovery_paths: Vec<PathBuf>,
+    conflict_policy: ConflictPolicy,
 }
 
 impl SkillRegistry {
@@ -62,9 +113,23 @@ impl SkillRegistry {
             categories: HashMap::new(),
             tags_index: HashMap::new(),
             discovery_paths: Vec::new(),
+            conflict_policy: ConflictPolicy::default(),
         }
     }
 
+    pub fn with_conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    pub fn set_conflict_policy(&mut self, policy: ConflictPolicy) {
+        self.conflict_policy = policy;
+    }
+
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        self.conflict_policy
+    }
+
     pub fn add_discovery_path(&mut self, path: PathBuf) {
         self.discovery_paths.push(path);
     }
@@ -77,8 +142,8 @@ impl SkillRegistry {
         }
     }
 
-    pub async fn discover_and_load(&mut self) -> Result<usize, String> {
-        let mut loaded = 0;
+    pub async fn discover_and_load(&mut self) -> Result<SkillDiscovery, String> {
+        let mut discovery = SkillDiscovery::default();
 
         // Clone paths to avoid borrow issues
         let paths: Vec<PathBuf> = self.discovery_paths.clone();
@@ -89,8 +154,22 @@ impl SkillRegistry {
             for entry in glob(&pattern).map_err(|e| format!("Glob error: {}", e))? {
                 match entry {
                     Ok(file_path) => {
-                        if let Some(1) = self.load_skill(&file_path).await? {
-                            loaded += 1;
+                        let source = SkillSource {
+                            path: file_path.clone(),
+                            root: Some(base_path.clone()),
+                        };
+                        let (loaded, overridden) = self.load_skill_from(&file_path, source).await?;
+                        if loaded {
+                            discovery.loaded += 1;
+                        }
+                        if let Some(overridden) = overridden {
+                            warn!(
+                                "Skill {} from {:?} overrides {:?}",
+                                overridden.name,
+                                overridden.winner.as_ref().map(|s| &s.path),
+                                overridden.overridden.as_ref().map(|s| &s.path)
+                            );
+                            discovery.overridden.push(overridden);
                         }
                     }
                     Err(e) => {
@@ -100,25 +179,78 @@ impl SkillRegistry {
             }
         }
 
-        info!("Loaded {} skills", loaded);
-        Ok(loaded)
+        info!(
+            "Loaded {} skills ({} overridden)",
+            discovery.loaded,
+            discovery.overridden.len()
+        );
+        Ok(discovery)
     }
 
-    pub async fn load_skill(&mut self, path: &PathBuf) -> Result<Option<usize>, String> {
+    pub async fn load_skill(&mut self, path: &Path) -> Result<Option<usize>, String> {
+        let source = SkillSource {
+            path: path.to_path_buf(),
+            root: None,
+        };
+        let (loaded, _) = self.load_skill_from(path, source).await?;
+        Ok(loaded.then_some(1))
+    }
+
+    async fn load_skill_from(
+        &mut self,
+        path: &Path,
+        source: SkillSource,
+    ) -> Result<(bool, Option<SkillOverride>), String> {
         if !path.exists() {
-            return Ok(None);
+            return Ok((false, None));
         }
 
         let content = fs::read_to_string(path).map_err(|e| format!("Read failed: {}", e))?;
 
-        let skill = Self::parse_skill(&content, path)?;
+        let mut skill = Self::parse_skill(&content, path)?;
+        skill.source = Some(source);
 
-        if self.skills.contains_key(&skill.name) {
-            warn!("Skill {} already exists, skipping", skill.name);
-            return Ok(None);
-        }
+        self.register(skill)
+    }
 
-        self.skills.insert(skill.name.clone(), skill.clone());
+    /// Register a skill, resolving name conflicts with the configured policy
+    ///
+    /// Returns whether the skill was registered and, on conflict, which
+    /// source lost.
+    pub fn register(&mut self, skill: Skill) -> Result<(bool, Option<SkillOverride>), String> {
+        let mut overridden = None;
+
+        if let Some(existing) = self.skills.get(&skill.name) {
+            match self.conflict_policy {
+                ConflictPolicy::FirstWins => {
+                    debug!("Skill {} already exists, keeping first", skill.name);
+                    return Ok((
+                        false,
+                        Some(SkillOverride {
+                            name: skill.name.clone(),
+                            winner: existing.source.clone(),
+                            overridden: skill.source,
+                        }),
+                    ));
+                }
+                ConflictPolicy::LastWins => {
+                    overridden = Some(SkillOverride {
+                        name: skill.name.clone(),
+                        winner: skill.source.clone(),
+                        overridden: existing.source.clone(),
+                    });
+                    self.unindex(&skill.name);
+                }
+                ConflictPolicy::Error => {
+                    return Err(format!(
+                        "Skill {} defined in both {:?} and {:?}",
+                        skill.name,
+                        existing.source.as_ref().map(|s| &s.path),
+                        skill.source.as_ref().map(|s| &s.path)
+                    ));
+                }
+            }
+        }
 
         if let Some(ref cat) = skill.category {
             self.categories
@@ -135,10 +267,33 @@ impl SkillRegistry {
         }
 
         debug!("Loaded skill: {}", skill.name);
-        Ok(Some(1))
+        self.skills.insert(skill.name.clone(), skill);
+        Ok((true, overridden))
     }
 
-    fn parse_skill(content: &str, _path: &PathBuf) -> Result<Skill, String> {
+    fn unindex(&mut self, name: &str) {
+        let Some(skill) = self.skills.remove(name) else {
+            return;
+        };
+        if let Some(ref cat) = skill.category
+            && let Some(names) = self.categories.get_mut(cat)
+        {
+            names.retain(|n| n != name);
+            if names.is_empty() {
+                self.categories.remove(cat);
+            }
+        }
+        for tag in &skill.tags {
+            if let Some(names) = self.tags_index.get_mut(tag) {
+                names.retain(|n| n != name);
+                if names.is_empty() {
+                    self.tags_index.remove(tag);
+                }
+            }
+        }
+    }
+
+    fn parse_skill(content: &str, _path: &Path) -> Result<Skill, String> {
         let (frontmatter, body) = Self::extract_frontmatter(content)?;
 
         let metadata: SkillMetadata = if !frontmatter.is_empty() {
@@ -157,6 +312,7 @@ impl SkillRegistry {
             parameters: metadata.parameters.unwrap_or_default(),
             examples,
             content: body.to_string(),
+            source: None,
         })
     }
 
@@ -256,15 +412,37 @@ impl SkillRegistry {
         }
     }
 
+    /// Skills relevant to `query`, best match first
     pub fn search(&self, query: &str) -> Vec<&Skill> {
-        let query = query.to_lowercase();
-        self.skills
+        self.search_scored(query, DEFAULT_SEARCH_THRESHOLD)
+            .into_iter()
+            .map(|m| m.skill)
+            .collect()
+    }
+
+    /// Fuzzy search with scores, dropping matches below `threshold`
+    ///
+    /// Ties are broken by name so results are stable.
+    pub fn search_scored(&self, query: &str, threshold: f32) -> Vec<SkillMatch<'_>> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<SkillMatch<'_>> = self
+            .skills
             .values()
-            .filter(|skill| {
-                skill.name.to_lowercase().contains(&query)
-                    || skill.description.to_lowercase().contains(&query)
+            .map(|skill| SkillMatch {
+                skill,
+                score: search::score_skill(skill, &query),
             })
-            .collect()
+            .filter(|m| m.score >= threshold)
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.skill.name.cmp(&b.skill.name))
+        });
+        matches
     }
 
     pub fn get_categories(&self) -> Vec<String> {
@@ -288,3 +466,141 @@ struct SkillMetadata {
     tags: Option<Vec<String>>,
     parameters: Option<Vec<SkillParameter>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Write `name` as a skill under two discovery roots, returning the roots
+    fn two_roots_with(name: &str) -> (TempDir, PathBuf, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        for (root, tag) in [(&first, "first"), (&second, "second")] {
+            let skill_dir = root.join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(
+                skill_dir.join("SKILL.md"),
+                format!(
+                    "---\nname: {}\ndescription: from {}\ncategory: {}\ntags: [{}]\n---\nbody",
+                    name, tag, tag, tag
+                ),
+            )
+            .unwrap();
+        }
+        (dir, first, second)
+    }
+
+    fn registry_for(first: &Path, second: &Path, policy: ConflictPolicy) -> SkillRegistry {
+        let mut registry = SkillRegistry::new().with_conflict_policy(policy);
+        registry.add_discovery_path(first.to_path_buf());
+        registry.add_discovery_path(second.to_path_buf());
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_first_wins_keeps_higher_priority_path() {
+        let (_dir, first, second) = two_roots_with("review");
+        let mut registry = registry_for(&first, &second, ConflictPolicy::FirstWins);
+
+        let discovery = registry.discover_and_load().await.unwrap();
+
+        assert_eq!(discovery.loaded, 1);
+        assert_eq!(discovery.overridden.len(), 1);
+        let skill = registry.get("review").unwrap();
+        assert_eq!(skill.description, "from first");
+        assert_eq!(skill.source.as_ref().unwrap().root, Some(first.clone()));
+        let conflict = &discovery.overridden[0];
+        assert_eq!(conflict.overridden.as_ref().unwrap().root, Some(second));
+        assert!(registry.get_by_tag("second").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_last_wins_replaces_and_reindexes() {
+        let (_dir, first, second) = two_roots_with("review");
+        let mut registry = registry_for(&first, &second, ConflictPolicy::LastWins);
+
+        let discovery = registry.discover_and_load().await.unwrap();
+
+        assert_eq!(discovery.loaded, 2);
+        assert_eq!(discovery.overridden.len(), 1);
+        let skill = registry.get("review").unwrap();
+        assert_eq!(skill.description, "from second");
+        let conflict = &discovery.overridden[0];
+        assert_eq!(conflict.winner.as_ref().unwrap().root, Some(second));
+        assert_eq!(conflict.overridden.as_ref().unwrap().root, Some(first));
+        assert!(registry.get_by_category("first").is_empty());
+        assert!(registry.get_by_tag("first").is_empty());
+        assert_eq!(registry.get_by_tag("second").len(), 1);
+        assert_eq!(registry.get_categories(), vec!["second".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_error_policy_rejects_duplicates() {
+        let (_dir, first, second) = two_roots_with("review");
+        let mut registry = registry_for(&first, &second, ConflictPolicy::Error);
+
+        let err = registry.discover_and_load().await.unwrap_err();
+
+        assert!(err.contains("review"));
+    }
+
+    fn skill(name: &str, description: &str, tags: &[&str]) -> Skill {
+        Skill {
+            name: name.to_string(),
+            description: description.to_string(),
+            category: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            parameters: Vec::new(),
+            examples: Vec::new(),
+            content: String::new(),
+            source: None,
+        }
+    }
+
+    fn search_registry() -> SkillRegistry {
+        let mut registry = SkillRegistry::new();
+        for skill in [
+            skill("search", "Find code by text", &["grep"]),
+            skill("code-review", "Review a diff for bugs", &["quality"]),
+            skill("research", "Summarize papers on a topic", &[]),
+            skill("deploy", "Ship to production", &["release"]),
+            skill("web-search-helper", "Query the web", &[]),
+        ] {
+            registry.register(skill).unwrap();
+        }
+        registry
+    }
+
+    #[test]
+    fn test_search_tolerates_typos_and_word_order() {
+        let registry = search_registry();
+        let names = |query: &str| -> Vec<String> {
+            registry
+                .search(query)
+                .iter()
+                .map(|s| s.name.clone())
+                .collect()
+        };
+
+        assert_eq!(names("serch").first().map(String::as_str), Some("search"));
+        assert_eq!(names("review code"), vec!["code-review"]);
+        assert_eq!(names("relase"), vec!["deploy"]);
+        assert!(names("kubernetes").is_empty());
+        assert!(names("   ").is_empty());
+    }
+
+    #[test]
+    fn test_search_ranks_exact_name_first() {
+        let registry = search_registry();
+        let matches = registry.search_scored("search", DEFAULT_SEARCH_THRESHOLD);
+        let names: Vec<&str> = matches.iter().map(|m| m.skill.name.as_str()).collect();
+
+        assert_eq!(names, vec!["search", "research", "web-search-helper"]);
+        assert_eq!(matches[0].score, 1.0);
+        // Substring hits still rank above the threshold
+        assert!(matches[1].score >= 0.9 && matches[2].score >= 0.9);
+        assert_eq!(registry.search_scored("search", 1.0).len(), 1);
+    }
+}
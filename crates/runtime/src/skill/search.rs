@@ -0,0 +1,118 @@
+//! Skill Search - fuzzy relevance scoring
+//!
+//! A skill is scored against the query on its name, tags and description;
+//! the best field wins, weighted name > tags > description. Per field:
+//! - exact match: 1.0
+//! - substring match: 0.9
+//! - otherwise token-based: every query token is compared with the field's
+//!   tokens by trigram similarity or subsequence (typos, word order), capped
+//!   at 0.8 so fuzzy hits rank below substring hits
+
+use super::Skill;
+
+/// Matches scoring below this are dropped by `SkillRegistry::search`
+pub const DEFAULT_SEARCH_THRESHOLD: f32 = 0.45;
+
+const NAME_WEIGHT: f32 = 1.0;
+const TAG_WEIGHT: f32 = 0.8;
+const DESCRIPTION_WEIGHT: f32 = 0.6;
+const FUZZY_CAP: f32 = 0.8;
+
+/// A search hit with its relevance score (0.0..=1.0)
+#[derive(Debug, Clone)]
+pub struct SkillMatch<'a> {
+    pub skill: &'a Skill,
+    pub score: f32,
+}
+
+/// Relevance of `skill` for a lowercased, non-empty `query`
+pub(crate) fn score_skill(skill: &Skill, query: &str) -> f32 {
+    let query_tokens = tokenize(query);
+    let field =
+        |text: &str, weight: f32| weight * score_field(&text.to_lowercase(), query, &query_tokens);
+
+    let tags = skill
+        .tags
+        .iter()
+        .map(|tag| field(tag, TAG_WEIGHT))
+        .fold(0.0, f32::max);
+    field(&skill.name, NAME_WEIGHT)
+        .max(tags)
+        .max(field(&skill.description, DESCRIPTION_WEIGHT))
+}
+
+fn score_field(text: &str, query: &str, query_tokens: &[String]) -> f32 {
+    if text == query {
+        return 1.0;
+    }
+    if text.contains(query) {
+        return 0.9;
+    }
+    let tokens = tokenize(text);
+    if query_tokens.is_empty() || tokens.is_empty() {
+        return 0.0;
+    }
+    let total: f32 = query_tokens
+        .iter()
+        .map(|q| {
+            tokens
+                .iter()
+                .map(|t| token_similarity(q, t))
+                .fold(0.0, f32::max)
+        })
+        .sum();
+    FUZZY_CAP * total / query_tokens.len() as f32
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn token_similarity(query: &str, token: &str) -> f32 {
+    if query == token {
+        return 1.0;
+    }
+    trigram_similarity(query, token).max(subsequence_ratio(query, token))
+}
+
+/// Dice coefficient over padded character trigrams
+fn trigram_similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut remaining = b.clone();
+    let shared = a
+        .iter()
+        .filter(|gram| {
+            remaining
+                .iter()
+                .position(|other| other == *gram)
+                .map(|i| remaining.swap_remove(i))
+                .is_some()
+        })
+        .count();
+    2.0 * shared as f32 / (a.len() + b.len()) as f32
+}
+
+fn trigrams(word: &str) -> Vec<[char; 3]> {
+    let padded: Vec<char> = "  ".chars().chain(word.chars()).chain([' ']).collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// `query.len() / token.len()` when `query` is an in-order subsequence of `token`
+fn subsequence_ratio(query: &str, token: &str) -> f32 {
+    let query_len = query.chars().count();
+    if query_len < 3 {
+        return 0.0;
+    }
+    let mut chars = token.chars();
+    if query.chars().all(|q| chars.any(|t| t == q)) {
+        query_len as f32 / token.chars().count() as f32
+    } else {
+        0.0
+    }
+}
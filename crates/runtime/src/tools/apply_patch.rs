@@ -0,0 +1,837 @@
+//! Apply Patch Tool - multi-hunk, multi-file edits
+//!
+//! Accepts a unified diff (`patch`) or a structured list of hunks (`files`)
+//! and applies it all-or-nothing:
+//! - every hunk's context/removed lines must match the file (a shifted
+//!   position is tolerated, changed lines are not)
+//! - all new contents are computed before anything is written; a conflict
+//!   reports the failing hunk and leaves every file untouched
+//! - if a write fails midway, files already written are restored
+//!
+//! Target files are write-locked through `FileLockManager` while the patch
+//! is applied, when a lock manager is configured.
+
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::binary::{binary_file_notice, is_binary_content};
+use super::locking::{FileLockManager, LockRequest, LockType};
+use super::schema::{JsonSchema, ToolSchemaBuilder};
+use super::{Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
+
+/// 补丁错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum PatchError {
+    #[error("Invalid patch at line {line}: {message}")]
+    Parse { line: usize, message: String },
+
+    #[error("Hunk #{hunk} ({header}) does not apply to {path}: {reason}")]
+    Conflict {
+        path: String,
+        hunk: usize,
+        header: String,
+        reason: String,
+    },
+}
+
+/// 单个 hunk：`old` 为上下文 + 删除行，`new` 为上下文 + 新增行
+#[derive(Debug, Clone, PartialEq)]
+struct Hunk {
+    /// 原文件起始行（从 1 开始；纯插入时为插入点前一行），None 表示从当前位置搜索
+    old_start: Option<usize>,
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+impl Hunk {
+    fn from_lines<'a>(
+        old_start: Option<usize>,
+        lines: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, String> {
+        let mut hunk = Hunk {
+            old_start,
+            old: Vec::new(),
+            new: Vec::new(),
+        };
+        for line in lines {
+            match line.chars().next() {
+                Some(' ') => {
+                    hunk.old.push(line[1..].to_string());
+                    hunk.new.push(line[1..].to_string());
+                }
+                // 空行视为空的上下文行（部分编辑器会去掉行尾空格）
+                None => {
+                    hunk.old.push(String::new());
+                    hunk.new.push(String::new());
+                }
+                Some('-') => hunk.old.push(line[1..].to_string()),
+                Some('+') => hunk.new.push(line[1..].to_string()),
+                Some('\\') => {}
+                Some(_) => return Err(format!("unexpected hunk line: {:?}", line)),
+            }
+        }
+        Ok(hunk)
+    }
+
+    fn header(&self) -> String {
+        let start = self
+            .old_start
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        format!("@@ -{},{} +{} @@", start, self.old.len(), self.new.len())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileChange {
+    Modify,
+    Create,
+    Delete,
+}
+
+/// 单个文件的补丁
+#[derive(Debug, Clone)]
+struct FilePatch {
+    path: String,
+    change: FileChange,
+    hunks: Vec<Hunk>,
+}
+
+/// 解析 unified diff
+fn parse_unified_diff(patch: &str) -> Result<Vec<FilePatch>, PatchError> {
+    let error = |line: usize, message: &str| PatchError::Parse {
+        line: line + 1,
+        message: message.to_string(),
+    };
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut files = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(old_path) = lines[i].strip_prefix("--- ") else {
+            // diff --git / index / 说明文字
+            i += 1;
+            continue;
+        };
+        let new_path = lines
+            .get(i + 1)
+            .and_then(|l| l.strip_prefix("+++ "))
+            .ok_or_else(|| error(i + 1, "expected '+++' after '---'"))?;
+        let (old_path, new_path) = (diff_path(old_path), diff_path(new_path));
+        let (path, change) = match (old_path, new_path) {
+            (None, Some(new)) => (new, FileChange::Create),
+            (Some(old), None) => (old, FileChange::Delete),
+            (Some(_), Some(new)) => (new, FileChange::Modify),
+            (None, None) => return Err(error(i, "both sides are /dev/null")),
+        };
+        i += 2;
+
+        let mut hunks = Vec::new();
+        while let Some(header) = lines.get(i).filter(|l| l.starts_with("@@")) {
+            let (old_start, old_len, new_len) =
+                parse_hunk_header(header).ok_or_else(|| error(i, "malformed hunk header"))?;
+            i += 1;
+            let body_start = i;
+            let (mut old_seen, mut new_seen) = (0, 0);
+            while (old_seen < old_len || new_seen < new_len) && i < lines.len() {
+                match lines[i].chars().next() {
+                    Some('-') => old_seen += 1,
+                    Some('+') => new_seen += 1,
+                    Some('\\') => {}
+                    _ => {
+                        old_seen += 1;
+                        new_seen += 1;
+                    }
+                }
+                i += 1;
+            }
+            // 尾随的 "\ No newline at end of file"
+            while lines.get(i).is_some_and(|l| l.starts_with('\\')) {
+                i += 1;
+            }
+            if old_seen != old_len || new_seen != new_len {
+                return Err(error(
+                    i.saturating_sub(1),
+                    "hunk is shorter than its header",
+                ));
+            }
+            let hunk = Hunk::from_lines(Some(old_start), lines[body_start..i].iter().copied())
+                .map_err(|message| error(i, &message))?;
+            hunks.push(hunk);
+        }
+        if hunks.is_empty() {
+            return Err(error(i, "file patch has no hunks"));
+        }
+        files.push(FilePatch {
+            path,
+            change,
+            hunks,
+        });
+    }
+    if files.is_empty() {
+        return Err(error(0, "no file patches found"));
+    }
+    Ok(files)
+}
+
+/// `a/src/lib.rs` -> `src/lib.rs`；`/dev/null` -> None
+fn diff_path(raw: &str) -> Option<String> {
+    // 去掉 git 追加的时间戳
+    let raw = raw.split('\t').next().unwrap_or(raw).trim();
+    if raw == "/dev/null" {
+        return None;
+    }
+    let path = raw
+        .strip_prefix("a/")
+        .or_else(|| raw.strip_prefix("b/"))
+        .unwrap_or(raw);
+    Some(path.to_string())
+}
+
+/// `@@ -l,s +l,s @@` -> (old_start, old_len, new_len)
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = header.strip_prefix("@@ ")?.split_whitespace();
+    let range = |part: &str| -> Option<(usize, usize)> {
+        match part.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((part.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(parts.next()?.strip_prefix('-')?)?;
+    let (_, new_len) = range(parts.next()?.strip_prefix('+')?)?;
+    Some((old_start, old_len, new_len))
+}
+
+/// 解析结构化 hunks：`[{path, hunks: [{old_start?, lines: [" ctx", "-old", "+new"]}]}]`
+fn parse_structured(files: &serde_json::Value) -> Result<Vec<FilePatch>, ToolError> {
+    let invalid = |message: String| ToolError::InvalidArgument(message);
+    let files = files
+        .as_array()
+        .ok_or_else(|| invalid("'files' must be an array".to_string()))?;
+    files
+        .iter()
+        .map(|file| {
+            let path = file
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| invalid("each file needs a 'path'".to_string()))?;
+            let hunks = file
+                .get("hunks")
+                .and_then(|v| v.as_array())
+                .filter(|hunks| !hunks.is_empty())
+                .ok_or_else(|| invalid(format!("{}: 'hunks' must be a non-empty array", path)))?
+                .iter()
+                .map(|hunk| {
+                    let lines = hunk
+                        .get("lines")
+                        .and_then(|v| v.as_array())
+                        .ok_or_else(|| invalid(format!("{}: each hunk needs 'lines'", path)))?;
+                    let lines = lines
+                        .iter()
+                        .map(|l| {
+                            l.as_str().ok_or_else(|| {
+                                invalid(format!("{}: hunk lines must be strings", path))
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let old_start = hunk
+                        .get("old_start")
+                        .and_then(|v| v.as_u64())
+                        .map(|n| n as usize);
+                    Hunk::from_lines(old_start, lines)
+                        .map_err(|e| invalid(format!("{}: {}", path, e)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(FilePatch {
+                path: path.to_string(),
+                change: FileChange::Modify,
+                hunks,
+            })
+        })
+        .collect()
+}
+
+/// 将 hunks 应用到 `content`，返回新内容
+fn apply_hunks(path: &str, content: &str, hunks: &[Hunk]) -> Result<String, PatchError> {
+    let trailing_newline = content.is_empty() || content.ends_with('\n');
+    let original: Vec<&str> = match content.strip_suffix('\n').unwrap_or(content) {
+        "" if content.is_empty() => Vec::new(),
+        body => body.split('\n').collect(),
+    };
+
+    let mut output: Vec<&str> = Vec::with_capacity(original.len());
+    let mut cursor = 0;
+    for (idx, hunk) in hunks.iter().enumerate() {
+        let conflict = |reason: String| PatchError::Conflict {
+            path: path.to_string(),
+            hunk: idx + 1,
+            header: hunk.header(),
+            reason,
+        };
+        let position = if hunk.old.is_empty() {
+            // 纯插入：old_start 为插入点前一行
+            let at = hunk.old_start.unwrap_or(cursor);
+            if at < cursor || at > original.len() {
+                return Err(conflict(format!(
+                    "insertion point {} is outside lines {}..={}",
+                    at,
+                    cursor,
+                    original.len()
+                )));
+            }
+            at
+        } else {
+            let expected = hunk
+                .old_start
+                .map(|s| s.saturating_sub(1))
+                .unwrap_or(cursor)
+                .max(cursor);
+            find_hunk(&original, &hunk.old, cursor, expected)
+                .ok_or_else(|| conflict(mismatch_reason(&original, &hunk.old, expected)))?
+        };
+        if let Some(expected) = hunk.old_start.map(|s| s.saturating_sub(1))
+            && !hunk.old.is_empty()
+            && position != expected
+        {
+            debug!(
+                path,
+                hunk = idx + 1,
+                offset = position as i64 - expected as i64,
+                "hunk applied at offset"
+            );
+        }
+        output.extend(&original[cursor..position]);
+        output.extend(hunk.new.iter().map(String::as_str));
+        cursor = position + hunk.old.len();
+    }
+    output.extend(&original[cursor..]);
+
+    let mut result = output.join("\n");
+    if trailing_newline && !output.is_empty() {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// `old` 在 `lines[from..]` 中离 `expected` 最近的位置
+fn find_hunk(lines: &[&str], old: &[String], from: usize, expected: usize) -> Option<usize> {
+    let last = lines.len().checked_sub(old.len())?;
+    let matches_at = |p: usize| {
+        p >= from
+            && p <= last
+            && lines[p..p + old.len()]
+                .iter()
+                .zip(old)
+                .all(|(a, b)| *a == b.as_str())
+    };
+    (0..=last.max(expected)).find_map(|distance| {
+        let before = expected.checked_sub(distance).filter(|p| matches_at(*p));
+        before.or_else(|| Some(expected + distance).filter(|p| matches_at(*p)))
+    })
+}
+
+/// 说明 hunk 为何在预期位置不匹配
+fn mismatch_reason(lines: &[&str], old: &[String], expected: usize) -> String {
+    for (offset, want) in old.iter().enumerate() {
+        let line = expected + offset;
+        match lines.get(line) {
+            None => {
+                return format!(
+                    "expected {:?} at line {}, but the file has only {} lines",
+                    want,
+                    line + 1,
+                    lines.len()
+                );
+            }
+            Some(found) if *found != want.as_str() => {
+                return format!(
+                    "line {}: expected {:?}, found {:?} (and the hunk matches nowhere else)",
+                    line + 1,
+                    want,
+                    found
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    "context does not match".to_string()
+}
+
+/// 计划写入的文件
+#[derive(Debug)]
+struct PlannedWrite {
+    path: PathBuf,
+    display: String,
+    change: FileChange,
+    hunks: usize,
+    /// 原内容，None 表示新建
+    original: Option<String>,
+    /// 新内容，None 表示删除
+    updated: Option<String>,
+}
+
+/// Apply patch tool
+#[derive(Debug, Default)]
+pub struct ApplyPatchTool {
+    lock_manager: Option<Arc<FileLockManager>>,
+    lock_timeout: Option<Duration>,
+}
+
+impl ApplyPatchTool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write-lock target files through `manager` while applying
+    pub fn with_lock_manager(mut self, manager: Arc<FileLockManager>) -> Self {
+        self.lock_manager = Some(manager);
+        self
+    }
+
+    /// How long to wait for a contended file lock (default: 30s)
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
+    fn resolve(working_dir: &Path, path: &str) -> PathBuf {
+        let path = PathBuf::from(path);
+        match path.is_absolute() {
+            true => path,
+            false => working_dir.join(path),
+        }
+    }
+
+    /// 读取文件并计算新内容；任一 hunk 冲突则整体失败
+    async fn plan(
+        &self,
+        working_dir: &Path,
+        patches: Vec<FilePatch>,
+    ) -> Result<Vec<PlannedWrite>, ToolError> {
+        let mut planned: Vec<PlannedWrite> = Vec::with_capacity(patches.len());
+        for patch in patches {
+            let path = Self::resolve(working_dir, &patch.path);
+            if planned.iter().any(|p| p.path == path) {
+                return Err(ToolError::InvalidArgument(format!(
+                    "{} appears more than once in the patch",
+                    patch.path
+                )));
+            }
+            enforce_path_boundary(&path, Some(working_dir), "apply_patch")?;
+
+            let original = match tokio::fs::read(&path).await {
+                Ok(bytes) => {
+                    if is_binary_content(&bytes) {
+                        return Err(ToolError::InvalidArgument(format!(
+                            "Cannot patch: {}",
+                            binary_file_notice(&path, bytes.len() as u64)
+                        )));
+                    }
+                    Some(String::from_utf8(bytes).map_err(|e| {
+                        ToolError::InvalidArgument(format!(
+                            "Cannot patch {}: file is not valid UTF-8 ({})",
+                            path.display(),
+                            e.utf8_error()
+                        ))
+                    })?)
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory
+                    ) =>
+                {
+                    None
+                }
+                Err(e) => return Err(ToolError::Io(e)),
+            };
+
+            let change = match (&original, patch.change) {
+                (Some(_), FileChange::Create) => {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "{} already exists; the patch expects to create it",
+                        patch.path
+                    )));
+                }
+                (None, FileChange::Modify) if patch.hunks.iter().all(|h| h.old.is_empty()) => {
+                    FileChange::Create
+                }
+                (None, FileChange::Modify | FileChange::Delete) => {
+                    return Err(ToolError::InvalidPath(path));
+                }
+                (_, change) => change,
+            };
+
+            let patched = apply_hunks(&patch.path, original.as_deref().unwrap_or(""), &patch.hunks)
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            let updated = match change {
+                FileChange::Delete if !patched.is_empty() => {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "{} is not empty after applying its deletion hunks",
+                        patch.path
+                    )));
+                }
+                FileChange::Delete => None,
+                FileChange::Modify | FileChange::Create => Some(patched),
+            };
+            planned.push(PlannedWrite {
+                path,
+                display: patch.path,
+                change,
+                hunks: patch.hunks.len(),
+                original,
+                updated,
+            });
+        }
+        Ok(planned)
+    }
+
+    /// 写入所有文件；中途失败时恢复已写入的文件
+    async fn commit(planned: &[PlannedWrite]) -> Result<(), ToolError> {
+        for (idx, write) in planned.iter().enumerate() {
+            if let Err(e) = Self::write(&write.path, write.updated.as_deref()).await {
+                for done in planned[..idx].iter().rev() {
+                    if let Err(restore) = Self::write(&done.path, done.original.as_deref()).await {
+                        warn!(path = %done.path.display(), error = %restore, "failed to restore file after patch failure");
+                    }
+                }
+                return Err(ToolError::ExecutionFailed(format!(
+                    "Failed to write {}: {}; {} already written file(s) restored",
+                    write.display, e, idx
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn write(path: &Path, content: Option<&str>) -> std::io::Result<()> {
+        match content {
+            Some(content) => {
+                if let Some(parent) = path.parent().filter(|p| !p.exists()) {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                super::write_tool::atomic_write(path, content).await
+            }
+            None => match tokio::fs::remove_file(path).await {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        }
+    }
+
+    /// 对已存在的目标文件加写锁（按路径排序，避免互相等待）
+    async fn lock_targets(
+        &self,
+        planned: &[PlannedWrite],
+        owner: &super::LockOwner,
+    ) -> Result<Vec<PathBuf>, ToolError> {
+        let Some(manager) = &self.lock_manager else {
+            return Ok(Vec::new());
+        };
+        let mut paths: Vec<PathBuf> = planned
+            .iter()
+            .filter(|p| p.original.is_some())
+            .map(|p| p.path.clone())
+            .collect();
+        paths.sort();
+
+        let mut locked = Vec::with_capacity(paths.len());
+        for path in paths {
+            let mut request = LockRequest::new(&path, LockType::Write);
+            if let Some(timeout) = self.lock_timeout {
+                request = request.with_timeout(timeout);
+            }
+            if let Err(e) = manager.acquire(&request, owner).await {
+                self.unlock(&locked, owner).await;
+                return Err(ToolError::ExecutionFailed(format!(
+                    "Cannot lock {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+            locked.push(path);
+        }
+        Ok(locked)
+    }
+
+    async fn unlock(&self, paths: &[PathBuf], owner: &super::LockOwner) {
+        if let Some(manager) = &self.lock_manager {
+            for path in paths {
+                let _ = manager.release_lock(path, &owner.id).await;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ApplyPatchTool {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply a unified diff (or structured hunks) to one or more files. All hunks must match or nothing is changed."
+    }
+
+    async fn execute(&self, params: &serde_json::Value) -> Result<ToolResult, ToolError> {
+        let patches = match (params.get("patch"), params.get("files")) {
+            (Some(patch), None) => {
+                let patch = patch.as_str().ok_or_else(|| {
+                    ToolError::InvalidArgument("'patch' must be a string".to_string())
+                })?;
+                parse_unified_diff(patch).map_err(|e| ToolError::InvalidArgument(e.to_string()))?
+            }
+            (None, Some(files)) => parse_structured(files)?,
+            _ => {
+                return Err(ToolError::InvalidArgument(
+                    "Provide exactly one of 'patch' or 'files'".to_string(),
+                ));
+            }
+        };
+
+        let working_dir = match params.get("working_dir").and_then(|v| v.as_str()) {
+            Some(dir) => PathBuf::from(dir),
+            None => super::ToolContext::default().working_dir,
+        };
+        if !working_dir.is_absolute() {
+            return Err(ToolError::InvalidArgument(
+                "working_dir must be an absolute path, not relative".to_string(),
+            ));
+        }
+
+        let start = std::time::Instant::now();
+        let owner = FileLockManager::create_owner(
+            &format!("apply_patch-{}", uuid::Uuid::new_v4().simple()),
+            "apply_patch",
+        );
+
+        // 先加锁再读取，避免计算新内容后文件被并发修改
+        let targets = self.plan(&working_dir, patches.clone()).await?;
+        let locked = self.lock_targets(&targets, &owner).await?;
+        let result = match self.plan(&working_dir, patches).await {
+            Ok(planned) => Self::commit(&planned).await.map(|_| planned),
+            Err(e) => Err(e),
+        };
+        self.unlock(&locked, &owner).await;
+        let planned = result?;
+
+        let files: Vec<serde_json::Value> = planned
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "path": p.display,
+                    "change": format!("{:?}", p.change).to_lowercase(),
+                    "hunks": p.hunks,
+                })
+            })
+            .collect();
+        let hunks: usize = planned.iter().map(|p| p.hunks).sum();
+        let mut output = format!("Applied {} hunk(s) to {} file(s):", hunks, planned.len());
+        for p in &planned {
+            output.push_str(&format!(
+                "\n  {} {}",
+                format!("{:?}", p.change).to_lowercase(),
+                p.display
+            ));
+        }
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            metadata: ToolMetadata {
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                files_read: planned.iter().filter(|p| p.original.is_some()).count() as u32,
+                files_written: planned.len() as u32,
+                bytes_processed: planned
+                    .iter()
+                    .map(|p| p.updated.as_ref().map_or(0, String::len) as u64)
+                    .sum(),
+                structured: Some(serde_json::json!({ "files": files })),
+                truncated: None,
+            },
+        })
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        ToolSchemaBuilder::new()
+            .description("Apply a multi-hunk patch atomically")
+            .param_string(
+                "patch",
+                "Unified diff (---/+++ headers and @@ hunks); /dev/null creates or deletes a file",
+            )
+            .param_array(
+                "files",
+                "Structured alternative to 'patch': [{path, hunks: [{old_start?, lines: [\" context\", \"-removed\", \"+added\"]}]}]",
+                JsonSchema::with_description("{path, hunks}"),
+            )
+            .param_string(
+                "working_dir",
+                "Absolute directory that relative patch paths resolve against (defaults to current directory)",
+            )
+            .build()
+            .to_value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn apply(temp_dir: &TempDir, patch: &str) -> Result<ToolResult, ToolError> {
+        let params = serde_json::json!({
+            "patch": patch,
+            "working_dir": temp_dir.path().to_string_lossy(),
+        });
+        ApplyPatchTool::new().execute(&params).await
+    }
+
+    fn read(temp_dir: &TempDir, name: &str) -> String {
+        std::fs::read_to_string(temp_dir.path().join(name)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_clean_multi_file_patch() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("src/lib.rs"),
+            "fn one() {}\nfn two() {}\nfn three() {}\nfn four() {}\nfn five() {}\n",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "# Demo\n\nOld text\n").unwrap();
+        std::fs::write(temp_dir.path().join("old.txt"), "bye\n").unwrap();
+
+        let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+-fn one() {}
++fn one() -> u8 { 1 }
+ fn two() {}
+@@ -4,2 +4,3 @@
+ fn four() {}
+ fn five() {}
++fn six() {}
+--- a/README.md
++++ b/README.md
+@@ -2,2 +2,2 @@
+
+-Old text
++New text
+--- /dev/null
++++ b/docs/notes.md
+@@ -0,0 +1,2 @@
++# Notes
++created by patch
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let lock_dir = TempDir::new().unwrap();
+        let tool = ApplyPatchTool::new()
+            .with_lock_manager(Arc::new(FileLockManager::new(lock_dir.path(), None)))
+            .with_lock_timeout(Duration::from_millis(200));
+        let params = serde_json::json!({
+            "patch": patch,
+            "working_dir": temp_dir.path().to_string_lossy(),
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(
+            result.output.contains("Applied 5 hunk(s) to 4 file(s)"),
+            "{}",
+            result.output
+        );
+
+        assert_eq!(
+            read(&temp_dir, "src/lib.rs"),
+            "fn one() -> u8 { 1 }\nfn two() {}\nfn three() {}\nfn four() {}\nfn five() {}\nfn six() {}\n"
+        );
+        assert_eq!(read(&temp_dir, "README.md"), "# Demo\n\nNew text\n");
+        assert_eq!(
+            read(&temp_dir, "docs/notes.md"),
+            "# Notes\ncreated by patch\n"
+        );
+        assert!(!temp_dir.path().join("old.txt").exists());
+
+        // 结构化 hunks，位置偏移也能定位
+        let params = serde_json::json!({
+            "files": [{
+                "path": "src/lib.rs",
+                "hunks": [{"old_start": 1, "lines": [" fn two() {}", "-fn three() {}", "+fn three() -> u8 { 3 }"]}]
+            }],
+            "working_dir": temp_dir.path().to_string_lossy(),
+        });
+        ApplyPatchTool::new().execute(&params).await.unwrap();
+        assert!(read(&temp_dir, "src/lib.rs").contains("fn three() -> u8 { 3 }\nfn four"));
+    }
+
+    #[tokio::test]
+    async fn test_stale_context_line_is_reported_and_nothing_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "alpha\nbeta\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "one\ntwo\nthree\n").unwrap();
+
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ alpha
+-beta
++BETA
+--- a/b.txt
++++ b/b.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ four
+";
+        let err = apply(&temp_dir, patch).await.unwrap_err().to_string();
+        assert!(err.contains("Hunk #1"), "{}", err);
+        assert!(err.contains("b.txt"), "{}", err);
+        assert!(
+            err.contains("line 3: expected \"four\", found \"three\""),
+            "{}",
+            err
+        );
+
+        // 干净的 a.txt 也没有被改动
+        assert_eq!(read(&temp_dir, "a.txt"), "alpha\nbeta\n");
+        assert_eq!(read(&temp_dir, "b.txt"), "one\ntwo\nthree\n");
+    }
+
+    #[tokio::test]
+    async fn test_partial_write_failure_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "alpha\n").unwrap();
+        std::fs::write(temp_dir.path().join("blocker"), "not a directory\n").unwrap();
+
+        // a.txt 写入成功后，blocker/new.txt 因父路径是文件而写入失败
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-alpha
++ALPHA
+--- /dev/null
++++ b/blocker/new.txt
+@@ -0,0 +1 @@
++new
+";
+        let err = apply(&temp_dir, patch).await.unwrap_err().to_string();
+        assert!(err.contains("Failed to write blocker/new.txt"), "{}", err);
+        assert!(
+            err.contains("1 already written file(s) restored"),
+            "{}",
+            err
+        );
+        assert_eq!(read(&temp_dir, "a.txt"), "alpha\n");
+        assert_eq!(read(&temp_dir, "blocker"), "not a directory\n");
+    }
+}
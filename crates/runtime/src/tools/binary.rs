@@ -0,0 +1,96 @@
+//! Binary Detection - Shared heuristic for file tools
+//!
+//! Samples the first few KB of a file and treats it as binary when the
+//! sample contains a NUL byte or is not valid UTF-8. Used by read, grep,
+//! glob, edit and fs tools so binaries are reported instead of dumped.
+
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// 检测时采样的字节数
+pub const BINARY_SAMPLE_BYTES: usize = 8 * 1024;
+
+/// 判断一段字节（通常是文件开头的采样）是否为二进制内容
+pub fn is_binary_content(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(BINARY_SAMPLE_BYTES)];
+    if sample.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => false,
+        // A multi-byte character cut off by the sample boundary is still text.
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+/// 读取文件开头的采样并判断是否为二进制文件
+pub async fn is_binary_file(path: &Path) -> std::io::Result<bool> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut sample = vec![0u8; BINARY_SAMPLE_BYTES];
+    let mut filled = 0;
+    while filled < sample.len() {
+        let n = file.read(&mut sample[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(is_binary_content(&sample[..filled]))
+}
+
+/// 二进制文件的统一提示文本
+pub fn binary_file_notice(path: &Path, size: u64) -> String {
+    format!("Binary file {}, {} bytes, not shown", path.display(), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PNG_HEADER: &[u8] = &[
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52,
+    ];
+
+    #[test]
+    fn test_png_bytes_are_binary() {
+        assert!(is_binary_content(PNG_HEADER));
+    }
+
+    #[test]
+    fn test_invalid_utf8_without_nul_is_binary() {
+        assert!(is_binary_content(&[0xFF, 0xFE, 0x41, 0x42]));
+    }
+
+    #[test]
+    fn test_utf8_text_is_not_binary() {
+        assert!(!is_binary_content("hello\n世界\n".as_bytes()));
+        assert!(!is_binary_content(b""));
+    }
+
+    #[test]
+    fn test_truncated_multibyte_at_sample_boundary_is_text() {
+        let mut bytes = vec![b'a'; BINARY_SAMPLE_BYTES - 1];
+        bytes.extend_from_slice("世".as_bytes());
+        assert!(!is_binary_content(&bytes));
+    }
+
+    #[tokio::test]
+    async fn test_is_binary_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let png = temp_dir.path().join("image.png");
+        let text = temp_dir.path().join("notes.txt");
+        std::fs::write(&png, PNG_HEADER).unwrap();
+        std::fs::write(&text, "plain text").unwrap();
+
+        assert!(is_binary_file(&png).await.unwrap());
+        assert!(!is_binary_file(&text).await.unwrap());
+    }
+
+    #[test]
+    fn test_binary_file_notice() {
+        let notice = binary_file_notice(Path::new("/tmp/a.png"), 16);
+        assert_eq!(notice, "Binary file /tmp/a.png, 16 bytes, not shown");
+    }
+}
@@ -13,6 +13,7 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 use tracing::debug;
 
+use super::binary::{binary_file_notice, is_binary_content};
 use super::schema::ToolSchemaBuilder;
 use super::{Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
 
@@ -25,8 +26,8 @@ pub enum EditError {
     #[error("String not found: {0}")]
     NotFound(String),
 
-    #[error("Multiple matches found for: {0}")]
-    MultipleMatches(String),
+    #[error("Found {count} matches for: {pattern}")]
+    MultipleMatches { pattern: String, count: usize },
 
     #[error("Invalid block anchor: {0}")]
     InvalidAnchor(String),
@@ -206,7 +207,10 @@ impl EditTool {
         // 验证唯一性
         let all_matches = self.find_matches(content, old);
         if all_matches.len() > 1 {
-            return Err(EditError::MultipleMatches(old.to_string()));
+            return Err(EditError::MultipleMatches {
+                pattern: old.to_string(),
+                count: all_matches.len(),
+            });
         }
 
         let result = content[..start].to_string() + new + &content[end..];
@@ -255,6 +259,11 @@ impl Tool for EditTool {
             .ok_or_else(|| {
                 ToolError::InvalidArgument("Missing 'oldString' parameter".to_string())
             })?;
+        if old_string.is_empty() {
+            return Err(ToolError::InvalidArgument(
+                "'oldString' must not be empty".to_string(),
+            ));
+        }
 
         let new_string = params
             .get("newString")
@@ -264,27 +273,46 @@ impl Tool for EditTool {
 
         let replace_all = params
             .get("replaceAll")
+            .or_else(|| params.get("replace_all"))
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
         let start = std::time::Instant::now();
 
         // Read file
-        let content = fs::read_to_string(&path).await.map_err(ToolError::Io)?;
+        let bytes = fs::read(&path).await.map_err(ToolError::Io)?;
+        if is_binary_content(&bytes) {
+            return Err(ToolError::InvalidArgument(format!(
+                "Cannot edit: {}",
+                binary_file_notice(&path, bytes.len() as u64)
+            )));
+        }
+        let content = String::from_utf8(bytes).map_err(|e| {
+            ToolError::InvalidArgument(format!(
+                "Cannot edit {}: file is not valid UTF-8 ({})",
+                path.display(),
+                e.utf8_error()
+            ))
+        })?;
 
         let result = if replace_all {
-            // 替换所有匹配
-            let count = self.find_matches(&content, old_string).len();
+            // 替换所有不重叠的匹配
+            let count = content.matches(old_string).count();
+            if count == 0 {
+                return Err(ToolError::ExecutionFailed(
+                    EditError::NotFound(old_string.to_string()).to_string(),
+                ));
+            }
             let new_content = content.replace(old_string, new_string);
             (new_content, count)
         } else {
             // 单次替换
             match self.smart_replace(&content, old_string, new_string) {
                 Ok(new_content) => (new_content, 1),
-                Err(EditError::MultipleMatches(old)) => {
-                    return Err(ToolError::ExecutionFailed(format!(
-                        "Multiple matches found for '{}'. Use replaceAll=true to replace all occurrences.",
-                        old
+                Err(EditError::MultipleMatches { pattern, count }) => {
+                    return Err(ToolError::InvalidArgument(format!(
+                        "Found {} matches for '{}'. Add surrounding context to make oldString unique, or use replaceAll=true to replace all occurrences.",
+                        count, pattern
                     )));
                 }
                 Err(e) => return Err(ToolError::ExecutionFailed(e.to_string())),
@@ -307,6 +335,8 @@ impl Tool for EditTool {
                 files_read: 1,
                 files_written: 1,
                 bytes_processed: result.0.len() as u64,
+                structured: Some(serde_json::json!({ "replacements": result.1 })),
+                truncated: None,
             },
         })
     }
@@ -317,7 +347,10 @@ impl Tool for EditTool {
             .required_string("path", "The absolute path to the file to edit")
             .required_string("oldString", "The text to replace")
             .required_string("newString", "The text to replace it with")
-            .param_boolean("replaceAll", "Replace all occurrences (default: false)")
+            .param_boolean(
+                "replaceAll",
+                "Replace every non-overlapping occurrence (default: false; alias: replace_all)",
+            )
             .build()
             .to_value()
     }
@@ -351,6 +384,25 @@ mod tests {
         assert_eq!(content, "Hello, Rust!");
     }
 
+    #[tokio::test]
+    async fn test_edit_rejects_binary_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("image.png");
+        let png = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00];
+        std::fs::write(&file_path, png).unwrap();
+
+        let tool = EditTool::new();
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy(),
+            "oldString": "PNG",
+            "newString": "JPG"
+        });
+
+        let err = format!("{}", tool.execute(&params).await.unwrap_err());
+        assert!(err.contains("Binary file"), "unexpected error: {}", err);
+        assert_eq!(std::fs::read(&file_path).unwrap(), png);
+    }
+
     #[tokio::test]
     async fn test_edit_replace_all() {
         let temp_dir = TempDir::new().unwrap();
@@ -499,4 +551,76 @@ mod tests {
         assert!(!content.contains("remove this"));
         assert!(content.contains("keep this"));
     }
+
+    async fn run_edit(content: &str, replace_all: bool) -> (Result<ToolResult, ToolError>, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("matches.txt");
+        std::fs::write(&file_path, content).unwrap();
+
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy(),
+            "oldString": "foo",
+            "newString": "bar",
+            "replace_all": replace_all
+        });
+        let result = EditTool::new().execute(&params).await;
+        (result, std::fs::read_to_string(&file_path).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_edit_match_counts_without_replace_all() {
+        let (result, content) = run_edit("nothing here", false).await;
+        assert!(result.is_err());
+        assert_eq!(content, "nothing here");
+
+        let (result, content) = run_edit("one foo", false).await;
+        let result = result.unwrap();
+        assert_eq!(result.metadata.structured.unwrap()["replacements"], 1);
+        assert_eq!(content, "one bar");
+
+        let (result, content) = run_edit("foo and foo and foo", false).await;
+        match result {
+            Err(ToolError::InvalidArgument(msg)) => assert!(msg.contains("Found 3 matches")),
+            other => panic!("expected InvalidArgument, got {:?}", other),
+        }
+        assert_eq!(content, "foo and foo and foo");
+    }
+
+    #[tokio::test]
+    async fn test_edit_match_counts_with_replace_all() {
+        let (result, content) = run_edit("nothing here", true).await;
+        assert!(result.is_err());
+        assert_eq!(content, "nothing here");
+
+        let (result, content) = run_edit("one foo", true).await;
+        assert_eq!(
+            result.unwrap().metadata.structured.unwrap()["replacements"],
+            1
+        );
+        assert_eq!(content, "one bar");
+
+        let (result, content) = run_edit("foofoo and foo", true).await;
+        assert_eq!(
+            result.unwrap().metadata.structured.unwrap()["replacements"],
+            3
+        );
+        assert_eq!(content, "barbar and bar");
+    }
+
+    #[tokio::test]
+    async fn test_edit_rejects_empty_old_string() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("empty.txt");
+        std::fs::write(&file_path, "content").unwrap();
+
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy(),
+            "oldString": "",
+            "newString": "x",
+            "replaceAll": true
+        });
+        let result = EditTool::new().execute(&params).await;
+        assert!(matches!(result, Err(ToolError::InvalidArgument(_))));
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "content");
+    }
 }
@@ -1,15 +1,18 @@
 //! FsTool - File system operations
 //!
 //! Provides safe file operations:
-//! - Read files
+//! - Read files (optionally a byte range; binary content comes back base64-encoded)
 //! - Write files
 //! - Create files/directories
 //! - Delete files
+//! - Move/rename files
 //! - List directory contents
 
 use super::{Tool, ToolContext, ToolError, ToolResult, enforce_path_boundary};
-use std::path::PathBuf;
+use base64::Engine;
+use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tracing::debug;
 
 /// File system tool
@@ -26,10 +29,71 @@ impl Default for FsTool {
 
 impl FsTool {
     pub fn new() -> Self {
-        Self {
-            context: ToolContext::default(),
+        Self::with_context(ToolContext::default())
+    }
+
+    pub fn with_context(context: ToolContext) -> Self {
+        Self { context }
+    }
+}
+
+/// Move `from` to `to`, falling back to copy + delete across filesystems
+///
+/// The fallback only handles regular files; moving a directory across
+/// devices returns the original error.
+pub async fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs::rename(from, to).await {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices && from.is_file() => {
+            fs::copy(from, to).await?;
+            fs::remove_file(from).await
         }
+        result => result,
+    }
+}
+
+/// `[byte_start, byte_end)` from the params, clamped to the file size
+fn byte_range(params: &serde_json::Value, size: u64) -> Result<(u64, u64), ToolError> {
+    let bound = |key: &str| params.get(key).and_then(|v| v.as_u64());
+    let start = bound("byte_start").unwrap_or(0).min(size);
+    let end = bound("byte_end").unwrap_or(size).min(size);
+    if end < start {
+        return Err(ToolError::InvalidArgument(format!(
+            "Invalid byte range: byte_end {} is before byte_start {}",
+            end, start
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Read bytes `[start, end)` without loading the rest of the file
+async fn read_range(path: &Path, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(start)).await?;
+    let mut bytes = Vec::with_capacity((end - start) as usize);
+    file.take(end - start).read_to_end(&mut bytes).await?;
+    Ok(bytes)
+}
+
+/// The whole range as UTF-8 text, or `None` when it is binary. A character
+/// cut by a range boundary inside the file still counts as text.
+fn range_text(bytes: &[u8], cut_start: bool, cut_end: bool) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
     }
+    let lead = match cut_start {
+        true => bytes
+            .iter()
+            .take(3)
+            .take_while(|b| **b & 0xC0 == 0x80)
+            .count(),
+        false => 0,
+    };
+    match std::str::from_utf8(&bytes[lead..]) {
+        Ok(_) => {}
+        Err(e) if cut_end && e.error_len().is_none() => {}
+        Err(_) => return None,
+    }
+    Some(String::from_utf8_lossy(bytes).into_owned())
 }
 
 #[async_trait::async_trait]
@@ -39,7 +103,7 @@ impl Tool for FsTool {
     }
 
     fn description(&self) -> &str {
-        "File system operations: read, write, create, delete, list"
+        "File system operations: read, write, create, delete, move, list"
     }
 
     async fn execute(&self, params: &serde_json::Value) -> Result<ToolResult, ToolError> {
@@ -71,19 +135,45 @@ impl Tool for FsTool {
         let start = std::time::Instant::now();
         let mut files_read = 0u32;
         let mut files_written = 0u32;
+        let mut bytes_processed = 0u64;
+        let mut structured = None;
 
         let output = match operation {
             "read" => {
-                let content = fs::read_to_string(&path).await.map_err(ToolError::Io)?;
+                let max_bytes = self.context.effective_max_read_bytes(params);
+                let size = fs::metadata(&path).await.map_err(ToolError::Io)?.len();
+                let (start, end) = byte_range(params, size)?;
+                if end - start > max_bytes {
+                    return Err(ToolError::InvalidArgument(format!(
+                        "File too large: {} bytes (max {} bytes). Use 'byte_start'/'byte_end' \
+                         for a byte range, the 'read' tool with 'offset'/'limit' for a line \
+                         range, or raise 'max_bytes' for this call",
+                        end - start,
+                        max_bytes
+                    )));
+                }
+                let bytes = read_range(&path, start, end).await.map_err(ToolError::Io)?;
                 files_read = 1;
-                content
+                bytes_processed = bytes.len() as u64;
+                let text = range_text(&bytes, start > 0, end < size);
+                let binary = text.is_none();
+                structured = Some(serde_json::json!({
+                    "binary": binary,
+                    "encoding": if binary { "base64" } else { "utf-8" },
+                    "file_size": size,
+                    "start": start,
+                    "end": end,
+                }));
+                text.unwrap_or_else(|| base64::engine::general_purpose::STANDARD.encode(&bytes))
             }
             "write" => {
                 let content = params
                     .get("content")
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| ToolError::InvalidArgument("Missing content".to_string()))?;
-                fs::write(&path, content).await.map_err(ToolError::Io)?;
+                super::write_tool::atomic_write(&path, content)
+                    .await
+                    .map_err(ToolError::Io)?;
                 files_written = 1;
                 format!("Written {} bytes to {}", content.len(), path.display())
             }
@@ -104,6 +194,36 @@ impl Tool for FsTool {
                 }
                 format!("Deleted {}", path.display())
             }
+            "move" => {
+                let to = params
+                    .get("to")
+                    .and_then(|v| v.as_str())
+                    .map(PathBuf::from)
+                    .ok_or_else(|| ToolError::InvalidArgument("Missing to".to_string()))?;
+                enforce_path_boundary(
+                    to.as_path(),
+                    working_dir
+                        .as_deref()
+                        .or(Some(self.context.working_dir.as_path())),
+                    "fs:move",
+                )?;
+                if !path.exists() {
+                    return Err(ToolError::InvalidPath(path));
+                }
+                // 不覆盖已有目标，保证回滚可以原样移回
+                if to.exists() {
+                    return Err(ToolError::InvalidArgument(format!(
+                        "Destination already exists: {}",
+                        to.display()
+                    )));
+                }
+                if let Some(parent) = to.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent).await.map_err(ToolError::Io)?;
+                }
+                move_path(&path, &to).await.map_err(ToolError::Io)?;
+                files_written = 1;
+                format!("Moved {} to {}", path.display(), to.display())
+            }
             "list" => {
                 let mut entries = tokio::fs::read_dir(&path).await.map_err(ToolError::Io)?;
                 let mut items = Vec::new();
@@ -131,7 +251,9 @@ impl Tool for FsTool {
                 execution_time_ms: duration,
                 files_read,
                 files_written,
-                bytes_processed: 0,
+                bytes_processed,
+                structured,
+                truncated: None,
             },
         })
     }
@@ -142,7 +264,7 @@ impl Tool for FsTool {
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["read", "write", "create", "delete", "list", "exists"],
+                    "enum": ["read", "write", "create", "delete", "move", "list", "exists"],
                     "description": "File operation type"
                 },
                 "path": {
@@ -153,9 +275,25 @@ impl Tool for FsTool {
                     "type": "string",
                     "description": "File content for write"
                 },
+                "to": {
+                    "type": "string",
+                    "description": "Destination path for move"
+                },
                 "working_dir": {
                     "type": "string",
                     "description": "Optional project root/working directory used for boundary checks"
+                },
+                "max_bytes": {
+                    "type": "integer",
+                    "description": "Override the maximum file size in bytes for read"
+                },
+                "byte_start": {
+                    "type": "integer",
+                    "description": "First byte to read (default 0)"
+                },
+                "byte_end": {
+                    "type": "integer",
+                    "description": "Byte offset to stop reading at, exclusive (default end of file)"
                 }
             },
             "required": ["operation", "path"]
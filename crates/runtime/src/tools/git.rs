@@ -4,18 +4,63 @@
 //! - Status check
 //! - Branch operations
 //! - Commit operations
+//! - Diff with structured per-file summary
 
 use super::{Tool, ToolContext, ToolError, ToolResult, enforce_git_operation};
+use serde::Serialize;
 use tokio::process::Command;
 use tracing::debug;
 
 /// Git tool using shell commands
 #[derive(Debug)]
 pub struct GitTool {
-    #[allow(dead_code)]
     context: ToolContext,
 }
 
+/// Per-file entry of a diff summary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffFileStat {
+    pub path: String,
+    pub insertions: u64,
+    pub deletions: u64,
+    pub binary: bool,
+}
+
+/// Parsed summary of a `git diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffSummary {
+    pub files: Vec<DiffFileStat>,
+    pub insertions: u64,
+    pub deletions: u64,
+}
+
+impl DiffSummary {
+    /// Parse `git diff --numstat` output; binary files report `-` counts
+    pub fn from_numstat(numstat: &str) -> Self {
+        let mut summary = Self::default();
+        for line in numstat.lines() {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(added), Some(removed), Some(path)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let binary = added == "-" && removed == "-";
+            let insertions = added.parse().unwrap_or(0);
+            let deletions = removed.parse().unwrap_or(0);
+            summary.insertions += insertions;
+            summary.deletions += deletions;
+            summary.files.push(DiffFileStat {
+                path: path.to_string(),
+                insertions,
+                deletions,
+                binary,
+            });
+        }
+        summary
+    }
+}
+
 impl Default for GitTool {
     fn default() -> Self {
         Self::new()
@@ -29,9 +74,15 @@ impl GitTool {
         }
     }
 
+    pub fn with_context(context: ToolContext) -> Self {
+        Self { context }
+    }
+
     async fn git(&self, args: &[&str]) -> Result<String, ToolError> {
         let mut cmd = Command::new("git");
         cmd.args(args);
+        cmd.current_dir(&self.context.working_dir);
+        cmd.kill_on_drop(true);
 
         let output = cmd
             .output()
@@ -47,6 +98,31 @@ impl GitTool {
 
         Ok(stdout.into_owned())
     }
+
+    /// Run `git diff` and its `--numstat` summary with the same arguments
+    ///
+    /// Binary files show up as "Binary files ... differ" in the text and are
+    /// flagged in the summary; their contents are never included.
+    async fn diff(
+        &self,
+        staged: bool,
+        paths: &[String],
+    ) -> Result<(String, DiffSummary), ToolError> {
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+        if staged {
+            args.push("--cached");
+        }
+        let mut numstat_args = args.clone();
+        numstat_args.push("--numstat");
+        for list in [&mut args, &mut numstat_args] {
+            list.push("--");
+            list.extend(paths.iter().map(String::as_str));
+        }
+
+        let diff = self.git(&args).await?;
+        let numstat = self.git(&numstat_args).await?;
+        Ok((diff, DiffSummary::from_numstat(&numstat)))
+    }
 }
 
 #[async_trait::async_trait]
@@ -56,7 +132,7 @@ impl Tool for GitTool {
     }
 
     fn description(&self) -> &str {
-        "Git operations: status, branch, commit"
+        "Git operations: status, branch, diff, commit"
     }
 
     async fn execute(&self, params: &serde_json::Value) -> Result<ToolResult, ToolError> {
@@ -70,6 +146,8 @@ impl Tool for GitTool {
         debug!("GitTool executing: {}", operation);
 
         let start = std::time::Instant::now();
+        let mut files_read = 0;
+        let mut structured = None;
         let (output, bytes) = match operation {
             "status" => {
                 let out = self.git(&["status", "--porcelain"]).await?;
@@ -87,13 +165,28 @@ impl Tool for GitTool {
                 let out = self.git(&["log", "--oneline", "-10"]).await?;
                 (out.clone(), out.len())
             }
-            "diff_staged" => {
-                let out = self.git(&["diff", "--cached"]).await?;
-                (out.clone(), out.len())
-            }
-            "diff" => {
-                let out = self.git(&["diff"]).await?;
-                (out.clone(), out.len())
+            "diff" | "diff_staged" => {
+                let staged = operation == "diff_staged"
+                    || params
+                        .get("staged")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                let paths: Vec<String> = params
+                    .get("paths")
+                    .and_then(|v| v.as_array())
+                    .map(|paths| {
+                        paths
+                            .iter()
+                            .filter_map(|p| p.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let (out, summary) = self.diff(staged, &paths).await?;
+                files_read = summary.files.len() as u32;
+                structured = Some(serde_json::to_value(&summary)?);
+                let len = out.len();
+                (out, len)
             }
             "commit" => {
                 let message = params
@@ -137,9 +230,11 @@ impl Tool for GitTool {
             error: None,
             metadata: super::ToolMetadata {
                 execution_time_ms: duration,
-                files_read: 0,
+                files_read,
                 files_written: 0,
                 bytes_processed: bytes as u64,
+                structured,
+                truncated: None,
             },
         })
     }
@@ -156,9 +251,128 @@ impl Tool for GitTool {
                 "message": {
                     "type": "string",
                     "description": "Commit message for commit operation"
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "For diff: compare the index against HEAD instead of the working tree (default: false)"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "For diff: limit the diff to these paths"
                 }
             },
             "required": ["operation"]
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {:?} failed", args);
+    }
+
+    /// Repo with one staged change, one unstaged change and a staged binary file
+    fn temp_repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        run_git(root, &["init", "-q"]);
+        run_git(root, &["config", "user.email", "test@example.com"]);
+        run_git(root, &["config", "user.name", "Test"]);
+        std::fs::write(root.join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(root.join("b.txt"), "alpha\n").unwrap();
+        run_git(root, &["add", "."]);
+        run_git(root, &["commit", "-q", "-m", "init"]);
+
+        std::fs::write(root.join("a.txt"), "one\nTWO\nthree\n").unwrap();
+        run_git(root, &["add", "a.txt"]);
+        std::fs::write(root.join("image.png"), [0x89, b'P', b'N', b'G', 0, 0, 0, 1]).unwrap();
+        run_git(root, &["add", "image.png"]);
+        std::fs::write(root.join("b.txt"), "beta\n").unwrap();
+        dir
+    }
+
+    fn tool_for(dir: &TempDir) -> GitTool {
+        GitTool::with_context(ToolContext {
+            working_dir: dir.path().to_path_buf(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_diff_summary_from_numstat() {
+        let summary = DiffSummary::from_numstat("2\t1\tsrc/lib.rs\n-\t-\tlogo.png\n");
+        assert_eq!(summary.insertions, 2);
+        assert_eq!(summary.deletions, 1);
+        assert_eq!(summary.files.len(), 2);
+        assert!(summary.files[1].binary);
+    }
+
+    #[tokio::test]
+    async fn test_diff_unstaged() {
+        let dir = temp_repo();
+        let result = tool_for(&dir)
+            .execute(&serde_json::json!({ "operation": "diff" }))
+            .await
+            .unwrap();
+
+        assert!(result.output.contains("-alpha"));
+        assert!(result.output.contains("+beta"));
+        assert!(!result.output.contains("TWO"));
+        assert_eq!(result.metadata.files_read, 1);
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["files"][0]["path"], "b.txt");
+        assert_eq!(structured["insertions"], 1);
+        assert_eq!(structured["deletions"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_diff_staged_reports_binary_without_contents() {
+        let dir = temp_repo();
+        let result = tool_for(&dir)
+            .execute(&serde_json::json!({ "operation": "diff", "staged": true }))
+            .await
+            .unwrap();
+
+        assert!(result.output.contains("+TWO"));
+        assert!(result.output.contains("Binary files"));
+        assert!(!result.output.contains("PNG"));
+        assert_eq!(result.metadata.files_read, 2);
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["insertions"], 2);
+        assert_eq!(structured["deletions"], 1);
+        let binary = structured["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["path"] == "image.png")
+            .unwrap();
+        assert_eq!(binary["binary"], true);
+    }
+
+    #[tokio::test]
+    async fn test_diff_limited_to_paths() {
+        let dir = temp_repo();
+        let result = tool_for(&dir)
+            .execute(&serde_json::json!({
+                "operation": "diff",
+                "staged": true,
+                "paths": ["a.txt"]
+            }))
+            .await
+            .unwrap();
+
+        assert!(!result.output.contains("image.png"));
+        assert_eq!(result.metadata.files_read, 1);
+    }
+}
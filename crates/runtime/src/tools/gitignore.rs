@@ -0,0 +1,250 @@
+//! Gitignore matching for search tools
+//!
+//! Supports the common subset of gitignore syntax: comments, `!` negation,
+//! trailing `/` for directories, a leading or embedded `/` to anchor a pattern,
+//! and the `*`, `?`, `[...]` and `**` wildcards. A nested `.gitignore` applies to
+//! its own directory and overrides rules from its parents. As in git, nothing
+//! inside an ignored directory can be re-included.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, body) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None if line.starts_with("\\!") || line.starts_with("\\#") => (false, &line[1..]),
+            None => (false, line),
+        };
+        let dir_only = body.ends_with('/');
+        let body = body.trim_end_matches('/');
+        // A slash anywhere but the end anchors the pattern to the .gitignore directory
+        let anchored = body.contains('/');
+        let body = body.strip_prefix('/').unwrap_or(body);
+        if body.is_empty() {
+            return None;
+        }
+
+        let regex = Regex::new(&glob_to_regex(body, anchored)).ok()?;
+        Some(Self {
+            regex,
+            negated,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.regex.is_match(relative)
+    }
+}
+
+fn glob_to_regex(pattern: &str, anchored: bool) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut re = String::from("^");
+    if !anchored {
+        re.push_str("(?:.*/)?");
+    }
+
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    re.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    re.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            '[' => {
+                if let Some(len) = chars[i + 1..].iter().position(|c| *c == ']') {
+                    let class: String = chars[i + 1..i + 1 + len].iter().collect();
+                    let class = match class.strip_prefix('!') {
+                        Some(rest) => format!("^{}", rest),
+                        None => class,
+                    };
+                    re.push('[');
+                    re.push_str(&class.replace('\\', "\\\\"));
+                    re.push(']');
+                    i += len + 2;
+                    continue;
+                }
+                re.push_str("\\[");
+            }
+            '\\' if i + 1 < chars.len() => {
+                re.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+                continue;
+            }
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    re.push('$');
+    re
+}
+
+/// `.gitignore` rules for a search, plus caller-supplied exclude patterns
+#[derive(Debug)]
+pub(crate) struct IgnoreMatcher {
+    /// Search base; only components below it are checked
+    base: PathBuf,
+    /// Repository root (nearest ancestor with `.git`), or `base`
+    root: PathBuf,
+    respect_gitignore: bool,
+    /// Extra patterns, relative to `base`
+    exclude: Vec<IgnoreRule>,
+    /// Directory -> rules from its `.gitignore`
+    loaded: HashMap<PathBuf, Vec<IgnoreRule>>,
+}
+
+impl IgnoreMatcher {
+    pub(crate) fn new(base: &Path, respect_gitignore: bool, exclude: &[String]) -> Self {
+        let root = base
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(base)
+            .to_path_buf();
+        Self {
+            base: base.to_path_buf(),
+            root,
+            respect_gitignore,
+            exclude: exclude
+                .iter()
+                .filter_map(|p| IgnoreRule::parse(p))
+                .collect(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    /// Whether `path` or any directory between the search base and it is ignored
+    pub(crate) fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return self.matches(path, is_dir);
+        };
+        let components: Vec<_> = relative.components().collect();
+        let mut current = self.base.clone();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let last = i + 1 == components.len();
+            if self.matches(&current, !last || is_dir) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Last matching rule wins; deeper `.gitignore` files and excludes come last
+    fn matches(&mut self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+
+        if self.respect_gitignore {
+            if path.file_name().is_some_and(|name| name == ".git") {
+                return true;
+            }
+            if let Ok(relative) = path.strip_prefix(&self.root) {
+                let mut dirs = vec![self.root.clone()];
+                for component in relative.parent().into_iter().flat_map(Path::components) {
+                    let next = dirs[dirs.len() - 1].join(component);
+                    dirs.push(next);
+                }
+                for dir in dirs {
+                    let rel = slash_path(path.strip_prefix(&dir).unwrap_or(path));
+                    for rule in self.rules_for(&dir) {
+                        if rule.matches(&rel, is_dir) {
+                            ignored = !rule.negated;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Ok(relative) = path.strip_prefix(&self.base) {
+            let rel = slash_path(relative);
+            for rule in &self.exclude {
+                if rule.matches(&rel, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+
+        ignored
+    }
+
+    fn rules_for(&mut self, dir: &Path) -> &[IgnoreRule] {
+        self.loaded.entry(dir.to_path_buf()).or_insert_with(|| {
+            std::fs::read_to_string(dir.join(".gitignore"))
+                .map(|content| content.lines().filter_map(IgnoreRule::parse).collect())
+                .unwrap_or_default()
+        })
+    }
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether any component of `path` below `base` is a dotfile
+pub(crate) fn is_hidden(path: &Path, base: &Path) -> bool {
+    path.strip_prefix(base).is_ok_and(|relative| {
+        relative.components().any(|c| {
+            let name = c.as_os_str().to_string_lossy();
+            name.starts_with('.') && name != "." && name != ".."
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str) -> IgnoreRule {
+        IgnoreRule::parse(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_rule_parsing() {
+        assert!(IgnoreRule::parse("# comment").is_none());
+        assert!(IgnoreRule::parse("   ").is_none());
+
+        let build = rule("build/");
+        assert!(build.dir_only);
+        assert!(build.matches("build", true));
+        assert!(build.matches("crates/x/build", true));
+        assert!(!build.matches("build", false));
+
+        let anchored = rule("/docs/*.md");
+        assert!(anchored.matches("docs/a.md", false));
+        assert!(!anchored.matches("x/docs/a.md", false));
+        assert!(!anchored.matches("docs/sub/a.md", false));
+
+        let deep = rule("logs/**/*.log");
+        assert!(deep.matches("logs/a.log", false));
+        assert!(deep.matches("logs/x/y/a.log", false));
+
+        let negated = rule("!keep[0-9].txt");
+        assert!(negated.negated);
+        assert!(negated.matches("a/keep1.txt", false));
+        assert!(!negated.matches("keepx.txt", false));
+    }
+}
@@ -1,6 +1,8 @@
 //! Glob Tool - File pattern matching
 //!
-//! Finds files matching glob patterns.
+//! Finds files matching glob patterns. `.gitignore` rules and dotfiles are
+//! filtered out by default. At most `max_results` paths (default
+//! `DEFAULT_MAX_RESULTS`) are returned; `metadata.truncated` reports the total.
 //! Design参考 OpenCode glob.ts
 
 use async_trait::async_trait;
@@ -8,8 +10,11 @@ use glob::glob;
 use std::path::PathBuf;
 use tracing::debug;
 
-use super::schema::ToolSchemaBuilder;
-use super::{Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
+use super::binary::is_binary_file;
+use super::gitignore::{IgnoreMatcher, is_hidden};
+use super::grep_tool::max_results;
+use super::schema::{JsonSchema, JsonSchemaProperty, ToolSchemaBuilder};
+use super::{ResultTruncation, Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
 
 /// Glob tool - 文件模式匹配
 #[derive(Debug)]
@@ -54,6 +59,33 @@ impl Tool for GlobTool {
 
         enforce_path_boundary(base_dir.as_path(), None, "glob")?;
 
+        let include_binary = params
+            .get("include_binary")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let respect_gitignore = params
+            .get("respect_gitignore")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let include_hidden = params
+            .get("include_hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let exclude: Vec<String> = params
+            .get("exclude")
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let max_results = max_results(params)?;
+
         let start = std::time::Instant::now();
 
         // Build full pattern
@@ -69,14 +101,23 @@ impl Tool for GlobTool {
         let mut matches = Vec::new();
         let mut directories = Vec::new();
         let mut files = Vec::new();
+        let mut ignore = IgnoreMatcher::new(&base_dir, respect_gitignore, &exclude);
 
         for entry in glob(&full_pattern)
             .map_err(|e| ToolError::InvalidArgument(format!("Invalid glob pattern: {}", e)))?
         {
             match entry {
                 Ok(path) => {
-                    if path.is_dir() {
+                    let is_dir = path.is_dir();
+                    if (!include_hidden && is_hidden(&path, &base_dir))
+                        || ignore.is_ignored(&path, is_dir)
+                    {
+                        continue;
+                    }
+                    if is_dir {
                         directories.push(path.display().to_string());
+                    } else if !include_binary && is_binary_file(&path).await.unwrap_or(false) {
+                        continue;
                     } else {
                         files.push(path.display().to_string());
                     }
@@ -93,21 +134,26 @@ impl Tool for GlobTool {
         files.sort();
         let mut results = directories;
         results.extend(files);
+        let total = results.len();
+        results.truncate(max_results);
+        let truncated = ResultTruncation::of(results.len(), total);
 
         let duration = start.elapsed().as_millis() as u64;
 
         // Format output
-        let output = if results.is_empty() {
+        let mut output = if results.is_empty() {
             "No matches found".to_string()
         } else {
             results.join("\n")
         };
+        if let Some(truncated) = &truncated {
+            output = format!("{}\n{}", output, truncated.notice());
+        }
 
         let bytes = output.len();
         debug!(
             "Glob found {} matches for pattern '{}'",
-            results.len(),
-            full_pattern
+            total, full_pattern
         );
 
         Ok(ToolResult {
@@ -119,11 +165,17 @@ impl Tool for GlobTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: bytes as u64,
+                structured: None,
+                truncated,
             },
         })
     }
 
     fn schema(&self) -> serde_json::Value {
+        let pattern_schema = JsonSchemaProperty::string("A gitignore-style pattern").to_value();
+        let items_schema: JsonSchema =
+            serde_json::from_value(pattern_schema).unwrap_or_else(|_| JsonSchema::object());
+
         ToolSchemaBuilder::new()
             .description("Find files matching glob patterns")
             .required_string(
@@ -134,6 +186,27 @@ impl Tool for GlobTool {
                 "path",
                 "Base directory for search (defaults to current directory)",
             )
+            .param_boolean(
+                "include_binary",
+                "Also list binary files (skipped by default)",
+            )
+            .param_integer(
+                "max_results",
+                "Maximum number of paths to return (default: 200)",
+            )
+            .param_boolean(
+                "respect_gitignore",
+                "Skip paths ignored by .gitignore files (default: true)",
+            )
+            .param_boolean(
+                "include_hidden",
+                "Also list dotfiles and dot-directories (default: false)",
+            )
+            .param_array(
+                "exclude",
+                "Extra gitignore-style patterns to exclude",
+                items_schema,
+            )
             .build()
             .to_value()
     }
@@ -237,4 +310,162 @@ mod tests {
         let result = tool.execute(&params).await.unwrap();
         assert!(result.success);
     }
+
+    #[tokio::test]
+    async fn test_glob_skips_binary_files_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("logo.png"),
+            [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00],
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("readme.txt"), "text").unwrap();
+
+        let tool = GlobTool::new();
+        let params = serde_json::json!({
+            "pattern": "*",
+            "path": temp_dir.path().to_string_lossy()
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.output.contains("readme.txt"));
+        assert!(!result.output.contains("logo.png"));
+
+        let params = serde_json::json!({
+            "pattern": "*",
+            "path": temp_dir.path().to_string_lossy(),
+            "include_binary": true
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.output.contains("logo.png"));
+    }
+
+    fn gitignore_fixture() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["src", "build", "sub"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "build/\n*.log\n!important.log\n").unwrap();
+        std::fs::write(root.join("sub/.gitignore"), "generated.rs\n!trace.log\n").unwrap();
+        for file in [
+            "src/main.rs",
+            "build/out.rs",
+            "app.log",
+            "important.log",
+            "sub/generated.rs",
+            "sub/trace.log",
+            "sub/lib.rs",
+            ".env",
+        ] {
+            std::fs::write(root.join(file), "x").unwrap();
+        }
+        temp_dir
+    }
+
+    async fn glob_all(temp_dir: &TempDir, extra: serde_json::Value) -> Vec<String> {
+        let mut params = serde_json::json!({
+            "pattern": "**/*",
+            "path": temp_dir.path().to_string_lossy()
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            params[key] = value.clone();
+        }
+        let result = GlobTool::new().execute(&params).await.unwrap();
+        let prefix = format!("{}/", temp_dir.path().display());
+        result
+            .output
+            .lines()
+            .filter_map(|line| line.strip_prefix(&prefix).map(String::from))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_glob_respects_gitignore_and_negation() {
+        let temp_dir = gitignore_fixture();
+        let found = glob_all(&temp_dir, serde_json::json!({})).await;
+
+        for kept in [
+            "src/main.rs",
+            "important.log",
+            "sub/lib.rs",
+            "sub/trace.log",
+        ] {
+            assert!(found.iter().any(|p| p == kept), "missing {}", kept);
+        }
+        for ignored in [
+            "build",
+            "build/out.rs",
+            "app.log",
+            "sub/generated.rs",
+            ".env",
+            ".gitignore",
+        ] {
+            assert!(
+                !found.iter().any(|p| p == ignored),
+                "{} not ignored",
+                ignored
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_glob_gitignore_options() {
+        let temp_dir = gitignore_fixture();
+
+        let everything = glob_all(
+            &temp_dir,
+            serde_json::json!({ "respect_gitignore": false, "include_hidden": true }),
+        )
+        .await;
+        assert!(everything.iter().any(|p| p == "build/out.rs"));
+        assert!(everything.iter().any(|p| p == "app.log"));
+        assert!(everything.iter().any(|p| p == ".env"));
+
+        let excluded = glob_all(
+            &temp_dir,
+            serde_json::json!({ "exclude": ["src/", "*.log"] }),
+        )
+        .await;
+        assert!(!excluded.iter().any(|p| p.starts_with("src")));
+        assert!(!excluded.iter().any(|p| p.ends_with(".log")));
+        assert!(excluded.iter().any(|p| p == "sub/lib.rs"));
+        assert!(!excluded.iter().any(|p| p == "build/out.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_glob_max_results_truncates_and_reports_total() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..5 {
+            File::create(temp_dir.path().join(format!("f{}.rs", i))).unwrap();
+        }
+        let tool = GlobTool::new();
+
+        let result = tool
+            .execute(&serde_json::json!({
+                "pattern": "*.rs",
+                "path": temp_dir.path().to_string_lossy(),
+                "max_results": 2
+            }))
+            .await
+            .unwrap();
+        let lines: Vec<&str> = result.output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("f0.rs"));
+        assert!(lines[1].ends_with("f1.rs"));
+        assert!(lines[2].contains("3 more matches not shown"));
+        assert_eq!(
+            result.metadata.truncated,
+            Some(ResultTruncation { shown: 2, total: 5 })
+        );
+
+        let result = tool
+            .execute(&serde_json::json!({
+                "pattern": "*.rs",
+                "path": temp_dir.path().to_string_lossy()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result.output.lines().count(), 5);
+        assert!(result.metadata.truncated.is_none());
+    }
 }
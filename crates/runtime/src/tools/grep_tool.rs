@@ -2,20 +2,47 @@
 //!
 //! Searches for patterns in files using regex.
 //! Design参考 OpenCode grep.ts
+//!
+//! `json_output` returns structured match locations instead of text.
+//! At most `max_results` matched lines (default `DEFAULT_MAX_RESULTS`) are
+//! returned, earliest first; later lines are only counted, and
+//! `metadata.truncated` reports how many were found.
 
 use async_trait::async_trait;
 use regex::Regex;
+use serde::Serialize;
 use std::path::PathBuf;
 use tokio::fs;
 use tracing::debug;
 
+use super::binary::{binary_file_notice, is_binary_content, is_binary_file};
 use super::schema::ToolSchemaBuilder;
-use super::{Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
+use super::{
+    DEFAULT_MAX_RESULTS, ResultTruncation, Tool, ToolError, ToolMetadata, ToolResult,
+    enforce_path_boundary,
+};
 
 /// Grep tool - 内容搜索
 #[derive(Debug)]
 pub struct GrepTool;
 
+/// A single regex match with its surrounding lines
+///
+/// `line_number` and `column` are 1-based; `column` counts characters, not
+/// bytes, so multibyte text reports the position an editor would show.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrepMatch {
+    pub path: String,
+    pub line_number: usize,
+    pub column: usize,
+    pub matched_text: String,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+    /// Full matching line, used for the text output
+    #[serde(skip)]
+    line: String,
+}
+
 impl Default for GrepTool {
     fn default() -> Self {
         Self::new()
@@ -61,26 +88,72 @@ impl Tool for GrepTool {
 
         enforce_path_boundary(path.as_path(), None, "grep")?;
 
+        let include_binary = params
+            .get("include_binary")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // A single binary target is reported rather than searched
+        if path.is_file()
+            && !include_binary
+            && is_binary_file(&path).await.map_err(ToolError::Io)?
+        {
+            let size = fs::metadata(&path).await.map_err(ToolError::Io)?.len();
+            return Ok(ToolResult {
+                success: true,
+                output: binary_file_notice(&path, size),
+                error: None,
+                metadata: ToolMetadata {
+                    execution_time_ms: start.elapsed().as_millis() as u64,
+                    files_read: 0,
+                    files_written: 0,
+                    bytes_processed: 0,
+                    structured: None,
+                    truncated: None,
+                },
+            });
+        }
+
+        let json_output = params
+            .get("json_output")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let context_lines = params
+            .get("context_lines")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let max_results = max_results(params)?;
+
         // Determine if path is a file or directory
-        let results = if path.is_file() {
+        let (matches, total) = if path.is_file() {
             // Search single file
-            Self::search_file(&path, &regex).await?
+            Self::search_file(&path, &regex, include_binary, context_lines, max_results).await?
         } else {
             // Search directory
-            Self::search_directory(&path, &regex, params).await?
+            Self::search_directory(&path, &regex, params, context_lines, max_results).await?
         };
+        let truncated = ResultTruncation::of(total.min(max_results), total);
 
         let duration = start.elapsed().as_millis() as u64;
 
         // Format output
-        let output = if results.is_empty() {
-            "No matches found".to_string()
+        let (output, structured) = if json_output {
+            let value = serde_json::to_value(&matches)
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            let output = serde_json::to_string_pretty(&value)
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            (output, Some(value))
+        } else if matches.is_empty() {
+            ("No matches found".to_string(), None)
         } else {
-            results.join("\n")
+            let mut output = Self::format_text(&matches);
+            if let Some(truncated) = &truncated {
+                output = format!("{}\n{}", output, truncated.notice());
+            }
+            (output, None)
         };
 
-        let match_count = results.len() / 2;
-        debug!("Grep found {} matches in {}ms", match_count, duration);
+        debug!("Grep found {} matches in {}ms", total, duration);
 
         Ok(ToolResult {
             success: true,
@@ -91,6 +164,8 @@ impl Tool for GrepTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured,
+                truncated,
             },
         })
     }
@@ -107,46 +182,117 @@ impl Tool for GrepTool {
                 "include",
                 "File pattern to include (e.g., \"*.rs\", \"*.{ts,tsx}\")",
             )
-            .param_integer("max_results", "Maximum number of results to return")
+            .param_integer(
+                "max_results",
+                "Maximum number of matching lines to return (default: 200)",
+            )
+            .param_boolean(
+                "include_binary",
+                "Also search binary files (skipped by default)",
+            )
+            .param_boolean(
+                "json_output",
+                "Return a JSON array of match locations instead of text",
+            )
+            .param_integer(
+                "context_lines",
+                "Lines of context before and after each match (json_output only)",
+            )
             .build()
             .to_value()
     }
 }
 
 impl GrepTool {
-    /// 搜索单个文件
-    async fn search_file(path: &PathBuf, regex: &Regex) -> Result<Vec<String>, ToolError> {
-        let content = fs::read_to_string(path).await.map_err(ToolError::Io)?;
+    /// 搜索单个文件（除非 `include_binary`，否则跳过二进制文件）
+    ///
+    /// Keeps the matches of the first `limit` matching lines and returns them
+    /// with the number of matching lines in the whole file.
+    async fn search_file(
+        path: &PathBuf,
+        regex: &Regex,
+        include_binary: bool,
+        context_lines: usize,
+        limit: usize,
+    ) -> Result<(Vec<GrepMatch>, usize), ToolError> {
+        let bytes = fs::read(path).await.map_err(ToolError::Io)?;
+        if !include_binary && is_binary_content(&bytes) {
+            return Ok((Vec::new(), 0));
+        }
+        let content = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = content.lines().collect();
 
         let mut results = Vec::new();
+        let mut matched_lines = 0usize;
 
-        for (i, line) in content.lines().enumerate() {
-            if regex.is_match(line) {
-                let line_num = i + 1;
-                results.push(format!("{}:{}", path.display(), line_num));
-                results.push(format!("  {}", line));
+        for (i, line) in lines.iter().enumerate() {
+            if matched_lines >= limit {
+                // Past the cap: count, don't collect
+                matched_lines += usize::from(regex.is_match(line));
+                continue;
             }
+            let before = results.len();
+            for m in regex.find_iter(line) {
+                let before_start = i.saturating_sub(context_lines);
+                let after_end = (i + 1 + context_lines).min(lines.len());
+                results.push(GrepMatch {
+                    path: path.display().to_string(),
+                    line_number: i + 1,
+                    column: line[..m.start()].chars().count() + 1,
+                    matched_text: m.as_str().to_string(),
+                    context_before: lines[before_start..i]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    context_after: lines[i + 1..after_end]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    line: line.to_string(),
+                });
+            }
+            matched_lines += usize::from(results.len() > before);
         }
 
-        Ok(results)
+        Ok((results, matched_lines))
+    }
+
+    /// Render matches as `path:line` followed by the matching line, once per line
+    fn format_text(matches: &[GrepMatch]) -> String {
+        let mut out = Vec::new();
+        let mut last: Option<(&str, usize)> = None;
+        for m in matches {
+            if last == Some((m.path.as_str(), m.line_number)) {
+                continue;
+            }
+            last = Some((m.path.as_str(), m.line_number));
+            out.push(format!("{}:{}", m.path, m.line_number));
+            out.push(format!("  {}", m.line));
+        }
+        out.join("\n")
     }
 
     /// 搜索目录（非递归版本）
+    ///
+    /// Files are searched in path order; once `limit` lines matched the rest
+    /// are only counted. Returns the kept matches and the total matched lines.
     async fn search_directory(
         dir: &PathBuf,
         regex: &Regex,
         params: &serde_json::Value,
-    ) -> Result<Vec<String>, ToolError> {
-        let mut results = Vec::new();
+        context_lines: usize,
+        limit: usize,
+    ) -> Result<(Vec<GrepMatch>, usize), ToolError> {
+        let mut files = Vec::new();
 
         let include_pattern = params
             .get("include")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
-        let max_results = params
-            .get("max_results")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(u64::MAX);
+        let include_binary = params
+            .get("include_binary")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         // Walk directory
         let mut entries = tokio::fs::read_dir(dir).await.map_err(ToolError::Io)?;
@@ -165,19 +311,23 @@ impl GrepTool {
                 };
 
                 if matches_pattern {
-                    // Search file
-                    let file_results = Self::search_file(&path, regex).await?;
-                    results.extend(file_results);
+                    files.push(path);
                 }
             }
+        }
+        files.sort();
 
-            // Check max results
-            if results.len() / 2 >= max_results as usize {
-                break;
-            }
+        let mut results = Vec::new();
+        let mut matched_lines = 0usize;
+        for path in files {
+            let remaining = limit.saturating_sub(matched_lines);
+            let (file_results, file_lines) =
+                Self::search_file(&path, regex, include_binary, context_lines, remaining).await?;
+            results.extend(file_results);
+            matched_lines += file_lines;
         }
 
-        Ok(results)
+        Ok((results, matched_lines))
     }
 
     /// 检查文件名是否匹配模式
@@ -188,6 +338,17 @@ impl GrepTool {
     }
 }
 
+/// `max_results` parameter (shared with glob); must be positive
+pub(crate) fn max_results(params: &serde_json::Value) -> Result<usize, ToolError> {
+    match params.get("max_results").and_then(|v| v.as_u64()) {
+        Some(0) => Err(ToolError::InvalidArgument(
+            "'max_results' must be positive".to_string(),
+        )),
+        Some(n) => Ok(n as usize),
+        None => Ok(DEFAULT_MAX_RESULTS),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,4 +470,211 @@ mod tests {
         // max_results limits matches, each match = 2 lines (filename:line + content)
         assert!(result.output.contains("line1"));
     }
+
+    #[tokio::test]
+    async fn test_grep_skips_binary_files_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let png = temp_dir.path().join("image.png");
+        let text = temp_dir.path().join("notes.txt");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n\x00\x00needle").unwrap();
+        std::fs::write(&text, "needle in text").unwrap();
+
+        let tool = GrepTool::new();
+        let params = serde_json::json!({
+            "pattern": "needle",
+            "path": temp_dir.path().to_string_lossy()
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.output.contains("notes.txt"));
+        assert!(!result.output.contains("image.png"));
+
+        let params = serde_json::json!({
+            "pattern": "needle",
+            "path": temp_dir.path().to_string_lossy(),
+            "include_binary": true
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.output.contains("image.png"));
+    }
+
+    #[tokio::test]
+    async fn test_grep_single_binary_file_reports_notice() {
+        let temp_dir = TempDir::new().unwrap();
+        let png = temp_dir.path().join("image.png");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n\x00\x00needle").unwrap();
+
+        let tool = GrepTool::new();
+        let params = serde_json::json!({
+            "pattern": "needle",
+            "path": png.to_string_lossy()
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.starts_with("Binary file"));
+    }
+
+    async fn grep_json(
+        path: &std::path::Path,
+        pattern: &str,
+        context_lines: u64,
+    ) -> Vec<GrepMatch> {
+        let params = serde_json::json!({
+            "pattern": pattern,
+            "path": path.to_string_lossy(),
+            "json_output": true,
+            "context_lines": context_lines
+        });
+        let result = GrepTool::new().execute(&params).await.unwrap();
+        assert_eq!(
+            result.metadata.structured.as_ref().unwrap(),
+            &serde_json::from_str::<serde_json::Value>(&result.output).unwrap()
+        );
+        let value: Vec<serde_json::Value> = serde_json::from_str(&result.output).unwrap();
+        value
+            .into_iter()
+            .map(|v| GrepMatch {
+                path: v["path"].as_str().unwrap().to_string(),
+                line_number: v["line_number"].as_u64().unwrap() as usize,
+                column: v["column"].as_u64().unwrap() as usize,
+                matched_text: v["matched_text"].as_str().unwrap().to_string(),
+                context_before: serde_json::from_value(v["context_before"].clone()).unwrap(),
+                context_after: serde_json::from_value(v["context_after"].clone()).unwrap(),
+                line: String::new(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_grep_json_output_clips_context_at_file_boundaries() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        std::fs::write(&file_path, "needle first\ntwo\nthree\nfour\nlast needle").unwrap();
+
+        let matches = grep_json(&file_path, "needle", 2).await;
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line_number, 1);
+        assert_eq!(matches[0].column, 1);
+        assert_eq!(matches[0].matched_text, "needle");
+        assert!(matches[0].context_before.is_empty());
+        assert_eq!(matches[0].context_after, vec!["two", "three"]);
+
+        assert_eq!(matches[1].line_number, 5);
+        assert_eq!(matches[1].column, 6);
+        assert_eq!(matches[1].context_before, vec!["three", "four"]);
+        assert!(matches[1].context_after.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_grep_json_output_reports_character_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("utf8.txt");
+        std::fs::write(&file_path, "héllo 世界 needle needle").unwrap();
+
+        let matches = grep_json(&file_path, "needle", 0).await;
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].column, 10);
+        assert_eq!(matches[1].column, 17);
+        assert!(matches[0].context_before.is_empty());
+        assert!(matches[0].context_after.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_grep_json_output_empty_and_text_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        std::fs::write(&file_path, "a a\nb").unwrap();
+
+        assert!(grep_json(&file_path, "zzz", 1).await.is_empty());
+
+        // Text mode still prints each matching line once
+        let params = serde_json::json!({
+            "pattern": "a",
+            "path": file_path.to_string_lossy()
+        });
+        let result = GrepTool::new().execute(&params).await.unwrap();
+        assert_eq!(result.output, format!("{}:1\n  a a", file_path.display()));
+        assert!(result.metadata.structured.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_grep_max_results_keeps_earliest_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "hit 1\nhit 2\nhit 3").unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "hit 4\nhit 5").unwrap();
+        let tool = GrepTool::new();
+
+        let params = serde_json::json!({
+            "pattern": "hit",
+            "path": temp_dir.path().to_string_lossy(),
+            "max_results": 2
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.output.contains("hit 1"));
+        assert!(result.output.contains("hit 2"));
+        assert!(!result.output.contains("hit 3"));
+        assert!(!result.output.contains("b.txt"));
+        assert!(
+            result
+                .output
+                .ends_with("... 3 more matches not shown (5 total); narrow the query to see them")
+        );
+        assert_eq!(
+            result.metadata.truncated,
+            Some(ResultTruncation { shown: 2, total: 5 })
+        );
+
+        // JSON output stays a plain match array
+        let params = serde_json::json!({
+            "pattern": "hit",
+            "path": temp_dir.path().to_string_lossy(),
+            "max_results": 4,
+            "json_output": true
+        });
+        let result = tool.execute(&params).await.unwrap();
+        let matches: Vec<serde_json::Value> = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(matches.len(), 4);
+        assert_eq!(result.metadata.truncated.unwrap().total, 5);
+
+        // Under the cap nothing is flagged
+        let params = serde_json::json!({
+            "pattern": "hit",
+            "path": temp_dir.path().to_string_lossy(),
+            "max_results": 5
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.metadata.truncated.is_none());
+        assert!(!result.output.contains("not shown"));
+
+        let params = serde_json::json!({ "pattern": "hit", "max_results": 0 });
+        assert!(matches!(
+            tool.execute(&params).await,
+            Err(ToolError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_grep_max_results_counts_matched_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("a.txt");
+        std::fs::write(&file_path, "a a\nb\na a a\na\na").unwrap();
+        let tool = GrepTool::new();
+
+        // Every match on a kept line is returned; the cap is in lines
+        let params = serde_json::json!({
+            "pattern": "a",
+            "path": file_path.to_string_lossy(),
+            "max_results": 2,
+            "json_output": true
+        });
+        let result = tool.execute(&params).await.unwrap();
+        let matches: Vec<serde_json::Value> = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(matches.len(), 5);
+        assert!(matches.iter().all(|m| m["line_number"] != 4));
+        assert_eq!(
+            result.metadata.truncated,
+            Some(ResultTruncation { shown: 2, total: 4 })
+        );
+    }
 }
@@ -108,6 +108,8 @@ impl Tool for ListTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured: None,
+                truncated: None,
             },
         })
     }
@@ -4,6 +4,7 @@
 //! - Advisory file locking for concurrent edit prevention
 //! - Lock management with ownership tracking
 //! - Lock timeout and automatic release
+//! - Deadlock detection via a wait-for graph
 //! - Integration with edit tool
 
 use serde::Serialize;
@@ -64,6 +65,8 @@ pub struct FileLock {
     pub expires_at: Option<Instant>,
     /// Lock type
     pub lock_type: LockType,
+    /// When this lock was granted
+    pub acquired_at: Instant,
 }
 
 /// Lock error types
@@ -81,8 +84,15 @@ pub enum LockError {
     #[error("Lock expired")]
     LockExpired,
 
-    #[error("Lock timeout exceeded")]
-    LockTimeout,
+    #[error("Timed out waiting for lock on {0}")]
+    Timeout(PathBuf),
+
+    #[error("Waiting for {path} would deadlock: {}", cycle.join(" -> "))]
+    WouldDeadlock {
+        path: PathBuf,
+        /// Owner IDs forming the wait cycle, starting with the requester
+        cycle: Vec<String>,
+    },
 
     #[error("Invalid lock operation: {0}")]
     InvalidOperation(String),
@@ -96,11 +106,33 @@ pub struct LockRequest {
     /// Lock type
     pub lock_type: LockType,
     /// Timeout for acquiring lock
-    pub timeout_ms: u64,
+    pub timeout: Duration,
     /// Whether to fail if lock is held or wait
     pub wait: bool,
 }
 
+impl LockRequest {
+    /// Waiting request with a 30s timeout
+    pub fn new(path: impl Into<PathBuf>, lock_type: LockType) -> Self {
+        Self {
+            path: path.into(),
+            lock_type,
+            timeout: Duration::from_secs(30),
+            wait: true,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn no_wait(mut self) -> Self {
+        self.wait = false;
+        self
+    }
+}
+
 /// Result of a lock operation
 #[derive(Debug, Clone)]
 pub struct LockResult {
@@ -122,31 +154,26 @@ pub struct LockResult {
 pub struct FileLockManager {
     /// Lock storage: path -> lock info
     locks: Arc<RwLock<HashMap<PathBuf, FileLock>>>,
+    /// Wait-for graph: owner ID -> path it is blocked on
+    waits: Arc<RwLock<HashMap<String, PathBuf>>>,
     /// Default lock timeout (None = no timeout)
     default_timeout: Option<Duration>,
     /// Lock directory for dotfile storage
     lock_dir: PathBuf,
 }
 
-impl Default for FileLockManager {
-    fn default() -> Self {
-        Self::new(None)
-    }
-}
-
 impl FileLockManager {
-    /// Create a new lock manager
-    pub fn new(default_timeout: Option<Duration>) -> Self {
-        // Use .ndc/locks in current directory
-        let mut lock_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        lock_dir.push(".ndc");
-        lock_dir.push("locks");
+    /// Create a new lock manager keeping its dotfile locks under `lock_dir`
+    /// (typically `<storage>/locks`)
+    pub fn new(lock_dir: impl Into<PathBuf>, default_timeout: Option<Duration>) -> Self {
+        let lock_dir = lock_dir.into();
 
         // Create lock directory if it doesn't exist
         let _ = std::fs::create_dir_all(&lock_dir);
 
         Self {
             locks: Arc::new(RwLock::new(HashMap::new())),
+            waits: Arc::new(RwLock::new(HashMap::new())),
             default_timeout,
             lock_dir,
         }
@@ -196,6 +223,85 @@ impl FileLockManager {
         }
     }
 
+    /// Acquire a lock, waiting up to `request.timeout`
+    ///
+    /// Fails with `WouldDeadlock` as soon as waiting would close a cycle in
+    /// the wait-for graph, and with `Timeout` once the deadline passes.
+    pub async fn acquire(
+        &self,
+        request: &LockRequest,
+        owner: &LockOwner,
+    ) -> Result<FileLock, LockError> {
+        let path = self.normalize_path(&request.path);
+        let deadline = Instant::now() + request.timeout;
+
+        let result = loop {
+            let attempt = self.try_acquire_lock(&path, owner, request.lock_type).await;
+            if let (true, Some(lock)) = (attempt.success, attempt.lock) {
+                break Ok(lock);
+            }
+            let Some(holder) = attempt.current_holder else {
+                break Err(LockError::FileNotFound(path.clone()));
+            };
+            if !request.wait {
+                break Err(LockError::LockHeldByOther(format!(
+                    "{} (ID: {})",
+                    holder.name, holder.id
+                )));
+            }
+            if let Err(e) = self.register_wait(&path, owner).await {
+                break Err(e);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                break Err(LockError::Timeout(path.clone()));
+            }
+            tokio::time::sleep(Duration::from_millis(50).min(deadline - now)).await;
+        };
+
+        self.waits.write().await.remove(&owner.id);
+        result
+    }
+
+    /// Record that `owner` waits for `path`, unless that closes a cycle
+    async fn register_wait(&self, path: &Path, owner: &LockOwner) -> Result<(), LockError> {
+        let locks = self.locks.read().await;
+        let mut waits = self.waits.write().await;
+        let now = Instant::now();
+        let holder_of = |p: &Path| {
+            locks
+                .get(p)
+                .filter(|l| l.expires_at.is_none_or(|e| now <= e))
+                .map(|l| l.owner.id.clone())
+        };
+
+        let mut cycle = vec![owner.id.clone()];
+        let mut next = holder_of(path);
+        while let Some(holder) = next {
+            if holder == owner.id {
+                return Err(LockError::WouldDeadlock {
+                    path: path.to_path_buf(),
+                    cycle,
+                });
+            }
+            // 已存在且不含请求者的环，停止遍历
+            if cycle.contains(&holder) {
+                break;
+            }
+            next = waits.get(&holder).and_then(|p| holder_of(p));
+            cycle.push(holder);
+        }
+
+        waits.insert(owner.id.clone(), path.to_path_buf());
+        Ok(())
+    }
+
+    /// Owners currently blocked in `acquire`, with the path each waits on
+    pub async fn waiting_owners(&self) -> HashMap<String, PathBuf> {
+        self.waits.read().await.clone()
+    }
+
     /// Try to acquire a lock without waiting
     pub async fn try_acquire_lock(
         &self,
@@ -278,6 +384,7 @@ impl FileLockManager {
             owner: owner.clone(),
             expires_at,
             lock_type,
+            acquired_at: Instant::now(),
         };
 
         // Store lock
@@ -420,7 +527,7 @@ impl FileLockManager {
             owner: SerializableLockOwner {
                 id: lock.owner.id.clone(),
                 name: lock.owner.name.clone(),
-                acquired_at_secs: lock.owner.acquired_at.elapsed().as_secs(),
+                acquired_at_secs: lock.acquired_at.elapsed().as_secs(),
             },
             expires_at_secs: lock.expires_at.map(|e| e.elapsed().as_secs()),
             lock_type: match lock.lock_type {
@@ -517,18 +624,10 @@ impl EditToolWithLocking {
         path: &PathBuf,
         owner: &LockOwner,
     ) -> Result<(), LockError> {
-        let result = self
-            .lock_manager
-            .acquire_lock(path, owner, LockType::Write, 30000, true)
-            .await;
-
-        if result.success {
-            Ok(())
-        } else {
-            Err(LockError::LockHeldByOther(
-                result.error.unwrap_or_else(|| "Unknown".to_string()),
-            ))
-        }
+        self.lock_manager
+            .acquire(&LockRequest::new(path, LockType::Write), owner)
+            .await
+            .map(|_| ())
     }
 
     /// Release lock after editing
@@ -560,7 +659,8 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let file_path = create_test_file(&temp_dir, "test.txt", "content");
 
-        let manager = FileLockManager::new(Some(Duration::from_secs(60)));
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), Some(Duration::from_secs(60)));
         let owner = FileLockManager::create_owner("test-id", "Test Owner");
 
         // Acquire lock
@@ -571,8 +671,9 @@ mod tests {
         assert!(result.success);
         assert!(result.lock.is_some());
 
-        // Check file is locked
+        // Check file is locked, with its dotfile under the given lock dir
         assert!(manager.is_locked(&file_path).await);
+        assert_eq!(std::fs::read_dir(lock_dir.path()).unwrap().count(), 1);
 
         // Try to acquire again (should succeed - same owner)
         let result2 = manager
@@ -586,6 +687,7 @@ mod tests {
 
         // Check file is not locked
         assert!(!manager.is_locked(&file_path).await);
+        assert_eq!(std::fs::read_dir(lock_dir.path()).unwrap().count(), 0);
     }
 
     #[tokio::test]
@@ -593,7 +695,8 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let file_path = create_test_file(&temp_dir, "test.txt", "content");
 
-        let manager = FileLockManager::new(Some(Duration::from_secs(60)));
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), Some(Duration::from_secs(60)));
         let owner1 = FileLockManager::create_owner("owner-1", "Owner 1");
         let owner2 = FileLockManager::create_owner("owner-2", "Owner 2");
 
@@ -618,7 +721,8 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let file_path = create_test_file(&temp_dir, "test.txt", "content");
 
-        let manager = FileLockManager::new(Some(Duration::from_millis(100)));
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), Some(Duration::from_millis(100)));
         let owner1 = FileLockManager::create_owner("owner-1", "Owner 1");
         let owner2 = FileLockManager::create_owner("owner-2", "Owner 2");
 
@@ -644,7 +748,8 @@ mod tests {
         let file1 = create_test_file(&temp_dir, "test1.txt", "content1");
         let file2 = create_test_file(&temp_dir, "test2.txt", "content2");
 
-        let manager = FileLockManager::new(None);
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), None);
         let owner = FileLockManager::create_owner("owner-1", "Owner 1");
 
         manager
@@ -666,7 +771,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_lock_nonexistent_file() {
-        let manager = FileLockManager::new(None);
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), None);
         let owner = FileLockManager::create_owner("owner-1", "Owner 1");
 
         let result = manager
@@ -688,7 +794,8 @@ mod tests {
         let file2 = create_test_file(&temp_dir, "test2.txt", "content2");
 
         // Manager with very short timeout
-        let manager = FileLockManager::new(Some(Duration::from_millis(50)));
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), Some(Duration::from_millis(50)));
         let owner = FileLockManager::create_owner("owner-1", "Owner 1");
 
         manager
@@ -709,4 +816,89 @@ mod tests {
         assert!(!manager.is_locked(&file1).await);
         assert!(!manager.is_locked(&file2).await);
     }
+
+    #[tokio::test]
+    async fn test_acquire_times_out() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = create_test_file(&temp_dir, "test.txt", "content");
+
+        let lock_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(lock_dir.path(), None);
+        let owner1 = FileLockManager::create_owner("owner-1", "Owner 1");
+        let owner2 = FileLockManager::create_owner("owner-2", "Owner 2");
+
+        let lock = manager
+            .acquire(&LockRequest::new(&file_path, LockType::Write), &owner1)
+            .await
+            .unwrap();
+        assert_eq!(lock.owner.id, "owner-1");
+        assert!(lock.acquired_at <= Instant::now());
+
+        let request =
+            LockRequest::new(&file_path, LockType::Write).with_timeout(Duration::from_millis(120));
+        let err = manager.acquire(&request, &owner2).await.unwrap_err();
+        assert!(matches!(err, LockError::Timeout(_)));
+        assert!(manager.waiting_owners().await.is_empty());
+
+        let err = manager
+            .acquire(&request.clone().no_wait(), &owner2)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LockError::LockHeldByOther(_)));
+    }
+
+    #[tokio::test]
+    async fn test_two_lock_cycle_is_detected() {
+        let temp_dir = TempDir::new().unwrap();
+        let file1 = create_test_file(&temp_dir, "a.txt", "a");
+        let file2 = create_test_file(&temp_dir, "b.txt", "b");
+
+        let lock_dir = TempDir::new().unwrap();
+        let manager = Arc::new(FileLockManager::new(lock_dir.path(), None));
+        let owner_a = FileLockManager::create_owner("a", "A");
+        let owner_b = FileLockManager::create_owner("b", "B");
+
+        manager
+            .acquire(&LockRequest::new(&file1, LockType::Write), &owner_a)
+            .await
+            .unwrap();
+        manager
+            .acquire(&LockRequest::new(&file2, LockType::Write), &owner_b)
+            .await
+            .unwrap();
+
+        // A blocks on B's file
+        let waiter = {
+            let manager = manager.clone();
+            let owner_a = owner_a.clone();
+            let request =
+                LockRequest::new(&file2, LockType::Write).with_timeout(Duration::from_secs(10));
+            tokio::spawn(async move { manager.acquire(&request, &owner_a).await })
+        };
+        while !manager.waiting_owners().await.contains_key("a") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // B asking for A's file closes the cycle: fail fast instead of hanging
+        let request =
+            LockRequest::new(&file1, LockType::Write).with_timeout(Duration::from_secs(10));
+        let started = Instant::now();
+        let err = tokio::time::timeout(Duration::from_secs(5), manager.acquire(&request, &owner_b))
+            .await
+            .expect("deadlock detection must not hang")
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        match err {
+            LockError::WouldDeadlock { cycle, .. } => {
+                assert_eq!(cycle, vec!["b".to_string(), "a".to_string()])
+            }
+            other => panic!("expected WouldDeadlock, got {other:?}"),
+        }
+
+        // B backs off; A proceeds
+        manager.release_all_locks("b").await;
+        let lock = waiter.await.unwrap().unwrap();
+        assert_eq!(lock.owner.id, "a");
+        assert!(manager.waiting_owners().await.is_empty());
+    }
 }
@@ -5,11 +5,17 @@
 //! - Parse diagnostic output
 //! - Integrate with edit operations
 //! - Provide diagnostic summaries
+//! - Talk JSON-RPC to a running language server (`LspSession`)
 
+use async_trait::async_trait;
+use serde_json::{Value, json};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::time::Duration;
-use tokio::process::Command;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
 
 /// LSP diagnostic severity
 #[derive(Debug, Clone, PartialEq)]
@@ -53,6 +59,12 @@ pub struct DiagnosticSummary {
     pub has_errors: bool,
 }
 
+/// Anything that can report diagnostics for a file (LSP client, manager, mock)
+#[async_trait]
+pub trait DiagnosticSource: Send + Sync + std::fmt::Debug {
+    async fn diagnostics(&self, file: &Path) -> Result<DiagnosticSummary, String>;
+}
+
 /// Default timeout for LSP diagnostics (seconds)
 const DEFAULT_LSP_TIMEOUT_SECS: u64 = 60;
 
@@ -77,6 +89,47 @@ impl LspClient {
         }
     }
 
+    /// Spawn the server and complete the `initialize` handshake
+    pub async fn start_session(&self) -> Result<LspSession, String> {
+        let Some((program, args)) = self.server_command.split_first() else {
+            return Err("No LSP server command configured".to_string());
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(&self.root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+        let stdin = child.stdin.take().ok_or("LSP server stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("LSP server stdout unavailable")?;
+
+        let session = LspSession {
+            io: Mutex::new(LspIo {
+                _child: child,
+                stdin,
+                stdout: BufReader::new(stdout),
+                next_id: 0,
+                opened: Vec::new(),
+            }),
+        };
+        let root_uri = path_to_uri(&self.root)?;
+        session
+            .request(
+                "initialize",
+                json!({
+                    "processId": std::process::id(),
+                    "rootUri": root_uri,
+                    "capabilities": {},
+                }),
+            )
+            .await?;
+        session.notify("initialized", json!({})).await?;
+        Ok(session)
+    }
+
     /// Check if an LSP server is available
     pub async fn is_available(&self) -> bool {
         // Try to run the server with --version or check if command exists
@@ -419,7 +472,7 @@ impl LspClient {
     }
 
     /// Summarize diagnostics
-    fn summarize_diagnostics(diagnostics: &[Diagnostic]) -> DiagnosticSummary {
+    pub fn summarize_diagnostics(diagnostics: &[Diagnostic]) -> DiagnosticSummary {
         let mut summary = DiagnosticSummary {
             total_count: diagnostics.len(),
             error_count: 0,
@@ -500,6 +553,30 @@ impl LspDiagnostics {
         }
     }
 
+    /// Clients for the languages whose diagnostics `LspClient` can run,
+    /// rooted at `root`
+    ///
+    /// Each client's command only checks that the diagnostic tool is
+    /// installed; `servers` overrides it per language.
+    pub fn for_project(root: &Path, servers: &HashMap<String, Vec<String>>) -> Self {
+        let mut diagnostics = Self::new();
+        for (language, command) in [
+            ("rust", &["cargo", "--version"][..]),
+            (
+                "typescript",
+                &["npx", "--no-install", "eslint", "--version"],
+            ),
+            ("python", &["npx", "--no-install", "pyright", "--version"]),
+        ] {
+            let command = servers
+                .get(language)
+                .cloned()
+                .unwrap_or_else(|| command.iter().map(|arg| arg.to_string()).collect());
+            diagnostics.add_client(language, LspClient::new(command, root.to_path_buf()));
+        }
+        diagnostics
+    }
+
     /// Add an LSP client for a language
     pub fn add_client(&mut self, language: &str, client: LspClient) {
         self.clients.insert(language.to_string(), client);
@@ -520,16 +597,7 @@ impl LspDiagnostics {
         }
 
         // Determine language from extension
-        let extension = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-        let language = match extension {
-            "rs" => "rust",
-            "ts" | "tsx" | "js" | "jsx" => "typescript",
-            "py" => "python",
-            "go" => "go",
-            "java" => "java",
-            _ => "generic",
-        };
+        let language = language_for_path(file_path);
 
         // Try to use configured client or run diagnostics directly
         if let Some(client) = self.clients.get(language) {
@@ -547,6 +615,162 @@ impl LspDiagnostics {
     }
 }
 
+#[async_trait]
+impl DiagnosticSource for LspClient {
+    async fn diagnostics(&self, file: &Path) -> Result<DiagnosticSummary, String> {
+        self.get_diagnostics(&file.to_path_buf()).await
+    }
+}
+
+#[async_trait]
+impl DiagnosticSource for LspDiagnostics {
+    async fn diagnostics(&self, file: &Path) -> Result<DiagnosticSummary, String> {
+        self.get_diagnostics(&file.to_path_buf()).await
+    }
+}
+
+/// A running language server spoken to over stdio JSON-RPC
+///
+/// Requests are serialized; the server process is killed on drop.
+#[derive(Debug)]
+pub struct LspSession {
+    io: Mutex<LspIo>,
+}
+
+#[derive(Debug)]
+struct LspIo {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+    /// Documents already sent with `textDocument/didOpen`
+    opened: Vec<PathBuf>,
+}
+
+impl LspSession {
+    /// Send a request and wait for its response, skipping notifications
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let mut io = self.io.lock().await;
+        io.next_id += 1;
+        let id = io.next_id;
+        io.send(&json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))
+            .await?;
+        loop {
+            let message = io.receive().await?;
+            if message.get("id").and_then(Value::as_u64) != Some(id)
+                || message.get("method").is_some()
+            {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(format!("{} failed: {}", method, error));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    /// Send a notification (no response expected)
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        let mut io = self.io.lock().await;
+        io.send(&json!({"jsonrpc": "2.0", "method": method, "params": params}))
+            .await
+    }
+
+    /// Open a document once so position-based requests can resolve it
+    pub async fn ensure_open(&self, path: &Path) -> Result<(), String> {
+        if self.io.lock().await.opened.iter().any(|p| p == path) {
+            return Ok(());
+        }
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let language_id = language_for_path(path);
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": path_to_uri(path)?,
+                    "languageId": language_id,
+                    "version": 1,
+                    "text": text,
+                }
+            }),
+        )
+        .await?;
+        self.io.lock().await.opened.push(path.to_path_buf());
+        Ok(())
+    }
+}
+
+impl LspIo {
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let body = message.to_string();
+        let frame = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        self.stdin
+            .write_all(frame.as_bytes())
+            .await
+            .map_err(|e| format!("LSP write failed: {}", e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| format!("LSP write failed: {}", e))
+    }
+
+    async fn receive(&mut self) -> Result<Value, String> {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            let read = self
+                .stdout
+                .read_line(&mut header)
+                .await
+                .map_err(|e| format!("LSP read failed: {}", e))?;
+            if read == 0 {
+                return Err("LSP server closed the connection".to_string());
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+        let length = content_length.ok_or("LSP message without Content-Length")?;
+        let mut body = vec![0u8; length];
+        self.stdout
+            .read_exact(&mut body)
+            .await
+            .map_err(|e| format!("LSP read failed: {}", e))?;
+        serde_json::from_slice(&body).map_err(|e| format!("Invalid LSP message: {}", e))
+    }
+}
+
+/// Language key for a file, shared by diagnostics and symbol search
+pub fn language_for_path(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "rs" => "rust",
+        "ts" | "tsx" | "js" | "jsx" => "typescript",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        _ => "generic",
+    }
+}
+
+pub(crate) fn path_to_uri(path: &Path) -> Result<String, String> {
+    url::Url::from_file_path(path)
+        .map(String::from)
+        .map_err(|_| format!("Not an absolute path: {}", path.display()))
+}
+
+pub(crate) fn uri_to_path(uri: &str) -> PathBuf {
+    url::Url::parse(uri)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .unwrap_or_else(|| PathBuf::from(uri.trim_start_matches("file://")))
+}
+
 impl Default for LspDiagnostics {
     fn default() -> Self {
         Self::new()
@@ -12,7 +12,10 @@
 //! - JSON Schema: LLM-friendly parameter definitions
 
 mod trait_mod;
-pub use trait_mod::{Tool, ToolContext, ToolError, ToolManager, ToolMetadata, ToolResult};
+pub use trait_mod::{
+    DEFAULT_MAX_READ_BYTES, DEFAULT_MAX_RESULTS, ResultTruncation, Tool, ToolContext, ToolError,
+    ToolManager, ToolMetadata, ToolResult,
+};
 
 pub mod schema;
 pub use schema::{
@@ -25,6 +28,9 @@ pub use registry::{
     PredefinedCategories, RegistrySummary, ToolMetadata as RegistryToolMetadata, ToolRegistry,
 };
 
+pub mod binary;
+pub use binary::{binary_file_notice, is_binary_content, is_binary_file};
+
 pub mod fs;
 pub use fs::FsTool;
 
@@ -32,7 +38,7 @@ pub mod git;
 pub use git::GitTool;
 
 pub mod shell;
-pub use shell::ShellTool;
+pub use shell::{ShellPolicy, ShellPolicyMode, ShellTool};
 
 // P4.2 Core Tools
 pub mod list_tool;
@@ -47,28 +53,43 @@ pub use write_tool::WriteTool;
 pub mod edit_tool;
 pub use edit_tool::EditTool;
 
+pub mod apply_patch;
+pub use apply_patch::ApplyPatchTool;
+
 pub mod grep_tool;
-pub use grep_tool::GrepTool;
+pub use grep_tool::{GrepMatch, GrepTool};
 
 pub mod glob_tool;
 pub use glob_tool::GlobTool;
 
+mod gitignore;
+
 pub mod permission;
 pub use permission::{
-    DangerLevel, PermissionConfig, PermissionError, PermissionRequest, PermissionResponse,
-    PermissionSystem, PermissionSystemBuilder, PermissionType,
+    AuditDecision, AuditFilter, DangerLevel, PermissionAuditEntry, PermissionConfig,
+    PermissionError, PermissionRequest, PermissionResponse, PermissionSystem,
+    PermissionSystemBuilder, PermissionType,
 };
 
+pub mod role_scope;
+pub use role_scope::RoleToolPolicy;
+
 pub mod output_truncation;
 pub use output_truncation::{
-    OutputTruncator, TruncatedOutput, TruncationConfig, read_partial_output,
+    OutputTruncator, TruncatedOutput, TruncationConfig, TruncationStrategy, read_partial_output,
 };
 
 pub mod lsp;
-pub use lsp::{Diagnostic, DiagnosticSeverity, DiagnosticSummary, LspClient, LspDiagnostics};
+pub use lsp::{
+    Diagnostic, DiagnosticSeverity, DiagnosticSource, DiagnosticSummary, LspClient, LspDiagnostics,
+    LspSession,
+};
+
+pub mod symbol_search;
+pub use symbol_search::{SymbolLocation, SymbolProvider, SymbolSearchTool};
 
 pub mod webfetch;
-pub use webfetch::WebFetchTool;
+pub use webfetch::{WebFetchConfig, WebFetchTool};
 
 pub mod websearch;
 pub use websearch::WebSearchTool;
@@ -87,8 +108,8 @@ pub use locking::{
 // P4.3 Bash Parsing
 pub mod bash_parsing;
 pub use bash_parsing::{
-    BashDangerLevel, BashParser, BashPermissionRequest, CommandType, FileOpType, FileOperation,
-    ParsedBashCommand,
+    BashDangerLevel, BashParser, BashPermissionRequest, CommandType, EmbeddedCommand, EmbeddedKind,
+    FileOpType, FileOperation, ParsedBashCommand,
 };
 
 pub mod security;
@@ -96,15 +117,29 @@ pub use security::{
     PERMISSION_EXTERNAL_DIRECTORY, PERMISSION_GIT_COMMIT, PERMISSION_SHELL_HIGH_RISK,
     PERMISSION_SHELL_MEDIUM_RISK, PERMISSION_SHELL_UNLISTED, enforce_git_operation,
     enforce_path_boundary, enforce_shell_command, extract_confirmation_permission, has_override,
-    with_security_overrides,
+    resolve_within_root, with_security_overrides,
 };
 
 use ndc_storage::{SharedStorage, create_memory_storage};
+use std::sync::Arc;
+
+/// `apply_patch`, write-locking its targets through `lock_manager` when given;
+/// pass the same manager to the executor's tool manager and the agent's
+/// registry so their concurrent patches exclude each other
+fn apply_patch_tool(lock_manager: Option<Arc<FileLockManager>>) -> ApplyPatchTool {
+    match lock_manager {
+        Some(manager) => ApplyPatchTool::new().with_lock_manager(manager),
+        None => ApplyPatchTool::new(),
+    }
+}
 
 /// Create the default low-level tool manager used by runtime execution.
 ///
 /// This manager is consumed by `Executor` and other non-LLM callers.
-pub fn create_default_tool_manager_with_storage(storage: SharedStorage) -> ToolManager {
+pub fn create_default_tool_manager_with_storage(
+    storage: SharedStorage,
+    lock_manager: Option<Arc<FileLockManager>>,
+) -> ToolManager {
     let mut manager = ToolManager::new();
 
     // Compatibility tool used by existing executor actions.
@@ -117,8 +152,10 @@ pub fn create_default_tool_manager_with_storage(storage: SharedStorage) -> ToolM
     manager.register("read", ReadTool::new());
     manager.register("write", WriteTool::new());
     manager.register("edit", EditTool::new());
+    manager.register("apply_patch", apply_patch_tool(lock_manager));
     manager.register("grep", GrepTool::new());
     manager.register("glob", GlobTool::new());
+    manager.register("symbol_search", SymbolSearchTool::default());
 
     // Optional web tools.
     manager.register("webfetch", WebFetchTool::new());
@@ -147,11 +184,14 @@ pub fn create_default_tool_manager_with_storage(storage: SharedStorage) -> ToolM
 ///
 /// This manager is consumed by `Executor` and other non-LLM callers.
 pub fn create_default_tool_manager() -> ToolManager {
-    create_default_tool_manager_with_storage(create_memory_storage())
+    create_default_tool_manager_with_storage(create_memory_storage(), None)
 }
 
 /// Create the default LLM-facing tool registry with explicit storage injection.
-pub fn create_default_tool_registry_with_storage(storage: SharedStorage) -> ToolRegistry {
+pub fn create_default_tool_registry_with_storage(
+    storage: SharedStorage,
+    lock_manager: Option<Arc<FileLockManager>>,
+) -> ToolRegistry {
     let mut registry = ToolRegistry::new();
 
     registry.register(FsTool::new());
@@ -162,8 +202,10 @@ pub fn create_default_tool_registry_with_storage(storage: SharedStorage) -> Tool
     registry.register(ReadTool::new());
     registry.register(WriteTool::new());
     registry.register(EditTool::new());
+    registry.register(apply_patch_tool(lock_manager));
     registry.register(GrepTool::new());
     registry.register(GlobTool::new());
+    registry.register(SymbolSearchTool::default());
 
     registry.register(WebFetchTool::new());
     registry.register(WebSearchTool::new());
@@ -181,7 +223,23 @@ pub fn create_default_tool_registry_with_storage(storage: SharedStorage) -> Tool
 ///
 /// This registry is consumed by Agent mode and is the source of tool schemas.
 pub fn create_default_tool_registry() -> ToolRegistry {
-    create_default_tool_registry_with_storage(create_memory_storage())
+    create_default_tool_registry_with_storage(create_memory_storage(), None)
+}
+
+/// Default LLM-facing tool registry plus the tools of the MCP servers
+/// configured under `config_dirs` (see `McpManager::connect_configured`).
+pub async fn create_tool_registry_with_mcp(
+    storage: SharedStorage,
+    lock_manager: Option<Arc<FileLockManager>>,
+    config_dirs: &[std::path::PathBuf],
+) -> ToolRegistry {
+    let mut registry = create_default_tool_registry_with_storage(storage, lock_manager);
+    if let Some(manager) = crate::mcp::McpManager::connect_configured(config_dirs).await {
+        let manager = Arc::new(tokio::sync::Mutex::new(manager));
+        let names = crate::mcp::register_mcp_tools(&mut registry, manager).await;
+        tracing::info!("Registered {} MCP tools", names.len());
+    }
+    registry
 }
 
 #[cfg(test)]
@@ -249,6 +307,181 @@ mod tests {
         assert_eq!(result.metadata.files_read, 1);
     }
 
+    #[tokio::test]
+    async fn test_fs_read_rejects_file_over_limit() {
+        let _env_guard = env_lock();
+        let temp_dir = scoped_temp_dir();
+        let file_path = temp_dir.path().join("big.txt");
+        std::fs::write(&file_path, "x".repeat(64)).unwrap();
+
+        let tool = FsTool::with_context(ToolContext {
+            max_read_bytes: 16,
+            ..ToolContext::default()
+        });
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy()
+        });
+        match tool.execute(&params).await {
+            Err(ToolError::InvalidArgument(msg)) => assert!(msg.contains("too large")),
+            other => panic!("Expected InvalidArgument error, got {:?}", other),
+        }
+
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy(),
+            "max_bytes": 128
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert_eq!(result.output.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_binary_file_as_base64() {
+        let _env_guard = env_lock();
+        let temp_dir = scoped_temp_dir();
+        let file_path = temp_dir.path().join("logo.png");
+        let bytes = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0xFF];
+        std::fs::write(&file_path, bytes).unwrap();
+
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy()
+        });
+        let result = FsTool::new().execute(&params).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "iVBORw0KGgoA/w==");
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["binary"], true);
+        assert_eq!(structured["encoding"], "base64");
+        assert_eq!(result.metadata.bytes_processed, 10);
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_invalid_utf8_past_the_sample_is_binary() {
+        use base64::Engine;
+
+        let _env_guard = env_lock();
+        let temp_dir = scoped_temp_dir();
+        let file_path = temp_dir.path().join("mostly_text.dat");
+        let mut bytes = vec![b'a'; crate::tools::binary::BINARY_SAMPLE_BYTES + 16];
+        bytes.push(0xFF);
+        std::fs::write(&file_path, &bytes).unwrap();
+
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy()
+        });
+        let result = FsTool::new().execute(&params).await.unwrap();
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["binary"], true);
+        assert!(!result.output.contains('\u{FFFD}'));
+        assert_eq!(
+            base64::engine::general_purpose::STANDARD
+                .decode(&result.output)
+                .unwrap(),
+            bytes
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_range_cutting_characters_is_text() {
+        let _env_guard = env_lock();
+        let temp_dir = scoped_temp_dir();
+        let file_path = temp_dir.path().join("cjk.txt");
+        // "世界和平": three bytes per character
+        std::fs::write(&file_path, "世界和平").unwrap();
+
+        // Starts inside "世" and ends inside "平"
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy(),
+            "byte_start": 1,
+            "byte_end": 10
+        });
+        let result = FsTool::new().execute(&params).await.unwrap();
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["binary"], false);
+        assert!(result.output.contains("界和"), "{}", result.output);
+    }
+
+    #[tokio::test]
+    async fn test_default_apply_patch_waits_for_shared_file_locks() {
+        let temp_dir = scoped_temp_dir();
+        let target = temp_dir.path().join("locked.txt");
+        std::fs::write(&target, "old\n").unwrap();
+
+        let lock_dir = TempDir::new().unwrap();
+        let locks = Arc::new(FileLockManager::new(lock_dir.path(), None));
+        let holder = FileLockManager::create_owner("holder", "test");
+        locks
+            .acquire(&LockRequest::new(&target, LockType::Write), &holder)
+            .await
+            .unwrap();
+
+        let tool =
+            create_default_tool_registry_with_storage(create_memory_storage(), Some(locks.clone()))
+                .get("apply_patch")
+                .unwrap()
+                .clone();
+        let params = serde_json::json!({
+            "patch": "--- a/locked.txt\n+++ b/locked.txt\n@@ -1 +1 @@\n-old\n+new\n",
+            "working_dir": temp_dir.path().to_string_lossy(),
+        });
+        let patch = tokio::spawn(async move { tool.execute(&params).await });
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "old\n");
+
+        locks.release_lock(&target, &holder.id).await.unwrap();
+        patch.await.unwrap().unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new\n");
+    }
+
+    #[tokio::test]
+    async fn test_fs_read_byte_range() {
+        let _env_guard = env_lock();
+        let temp_dir = scoped_temp_dir();
+        let file_path = temp_dir.path().join("big.txt");
+        std::fs::write(&file_path, "0123456789abcdef").unwrap();
+
+        // Only the range counts against the size limit
+        let tool = FsTool::with_context(ToolContext {
+            max_read_bytes: 8,
+            ..ToolContext::default()
+        });
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy(),
+            "byte_start": 4,
+            "byte_end": 10
+        });
+        let result = tool.execute(&params).await.unwrap();
+        assert_eq!(result.output, "456789");
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["binary"], false);
+        assert_eq!(structured["file_size"], 16);
+
+        // An open-ended range stops at the end of the file
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy(),
+            "byte_start": 12
+        });
+        assert_eq!(tool.execute(&params).await.unwrap().output, "cdef");
+
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": file_path.to_string_lossy(),
+            "byte_start": 6,
+            "byte_end": 2
+        });
+        assert!(matches!(
+            tool.execute(&params).await,
+            Err(ToolError::InvalidArgument(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_fs_write_file() {
         let _env_guard = env_lock();
@@ -289,6 +522,35 @@ mod tests {
         assert!(file_path.exists());
     }
 
+    #[tokio::test]
+    async fn test_fs_move_file() {
+        let _env_guard = env_lock();
+        let temp_dir = scoped_temp_dir();
+        let from = temp_dir.path().join("old.txt");
+        let to = temp_dir.path().join("nested/new.txt");
+        std::fs::write(&from, "payload").unwrap();
+
+        let tool = FsTool::new();
+        let params = serde_json::json!({
+            "operation": "move",
+            "path": from.to_string_lossy(),
+            "to": to.to_string_lossy()
+        });
+
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.success);
+        assert!(!from.exists());
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "payload");
+
+        // Existing destinations are never overwritten
+        std::fs::write(&from, "other").unwrap();
+        match tool.execute(&params).await {
+            Err(ToolError::InvalidArgument(msg)) => assert!(msg.contains("already exists")),
+            other => panic!("Expected InvalidArgument error, got {:?}", other),
+        }
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "payload");
+    }
+
     #[tokio::test]
     async fn test_fs_create_directory() {
         let _env_guard = env_lock();
@@ -673,6 +935,157 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_tool_manager_rejects_missing_required_fields() {
+        let mut manager = ToolManager::new();
+        manager.register("fs", FsTool::new());
+
+        let params = serde_json::json!({ "operation": "read" });
+        match manager.execute("fs", &params).await {
+            Err(ToolError::InvalidArgument(msg)) => {
+                assert!(msg.contains("Missing required field: path"), "{}", msg);
+                assert!(!msg.contains("operation"), "{}", msg);
+            }
+            other => panic!("expected InvalidArgument, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_manager_rejects_wrong_types() {
+        let mut manager = ToolManager::new();
+        manager.register("fs", FsTool::new());
+
+        let params = serde_json::json!({
+            "operation": "read",
+            "path": 42,
+            "max_bytes": "big"
+        });
+        match manager.execute("fs", &params).await {
+            Err(ToolError::InvalidArgument(msg)) => {
+                assert!(msg.contains("Field 'path' has wrong type"), "{}", msg);
+                assert!(msg.contains("Field 'max_bytes' has wrong type"), "{}", msg);
+            }
+            other => panic!("expected InvalidArgument, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_manager_empty_schema_validation_toggle() {
+        struct AnyTool;
+
+        #[async_trait::async_trait]
+        impl Tool for AnyTool {
+            fn name(&self) -> &str {
+                "any"
+            }
+
+            fn description(&self) -> &str {
+                "Accepts anything"
+            }
+
+            async fn execute(&self, _params: &serde_json::Value) -> Result<ToolResult, ToolError> {
+                Ok(ToolResult {
+                    success: true,
+                    output: "ok".to_string(),
+                    error: None,
+                    metadata: ToolMetadata::default(),
+                })
+            }
+
+            fn schema(&self) -> serde_json::Value {
+                serde_json::json!({})
+            }
+        }
+
+        let params = serde_json::json!("not an object");
+
+        let mut manager = ToolManager::new();
+        manager.register("any", AnyTool);
+        assert!(manager.execute("any", &params).await.unwrap().success);
+
+        let mut strict = ToolManager::new().with_empty_schema_validation(true);
+        strict.register("any", AnyTool);
+        assert!(matches!(
+            strict.execute("any", &params).await,
+            Err(ToolError::InvalidArgument(_))
+        ));
+    }
+
+    struct SleepTool(std::time::Duration);
+
+    #[async_trait::async_trait]
+    impl Tool for SleepTool {
+        fn name(&self) -> &str {
+            "sleep"
+        }
+
+        fn description(&self) -> &str {
+            "Sleeps, then succeeds"
+        }
+
+        async fn execute(&self, _params: &serde_json::Value) -> Result<ToolResult, ToolError> {
+            tokio::time::sleep(self.0).await;
+            Ok(ToolResult {
+                success: true,
+                output: "done".to_string(),
+                error: None,
+                metadata: ToolMetadata::default(),
+            })
+        }
+    }
+
+    fn manager_with_timeout(tool: SleepTool, timeout_seconds: u64) -> ToolManager {
+        let mut manager = ToolManager::new().with_context(ToolContext {
+            timeout_seconds,
+            ..ToolContext::default()
+        });
+        manager.register("sleep", tool);
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_tool_manager_times_out_slow_tool() {
+        let manager = manager_with_timeout(SleepTool(std::time::Duration::from_secs(30)), 1);
+
+        let start = std::time::Instant::now();
+        match manager.execute("sleep", &serde_json::json!({})).await {
+            Err(ToolError::Timeout(msg)) => {
+                assert!(msg.contains("'sleep'"), "{}", msg);
+                assert!(msg.contains("after 1.0s"), "{}", msg);
+            }
+            other => panic!("expected Timeout, got {:?}", other),
+        }
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+        // 按工具覆盖
+        let manager = manager_with_timeout(SleepTool(std::time::Duration::from_secs(30)), 60)
+            .with_tool_timeout("sleep", std::time::Duration::from_millis(50));
+        assert!(matches!(
+            manager.execute("sleep", &serde_json::json!({})).await,
+            Err(ToolError::Timeout(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tool_manager_fast_tool_completes_within_timeout() {
+        let manager = manager_with_timeout(SleepTool(std::time::Duration::from_millis(10)), 1);
+        let result = manager
+            .execute("sleep", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result.output, "done");
+
+        // 0 表示不限时
+        let manager = manager_with_timeout(SleepTool(std::time::Duration::from_millis(10)), 0);
+        assert_eq!(manager.timeout_for("sleep"), None);
+        assert!(
+            manager
+                .execute("sleep", &serde_json::json!({}))
+                .await
+                .is_ok()
+        );
+    }
+
     // ===== ToolContext Tests =====
 
     #[test]
@@ -682,6 +1095,7 @@ mod tests {
         assert!(context.working_dir.exists() || context.working_dir.to_string_lossy() == ".");
         assert!(!context.read_only);
         assert!(context.timeout_seconds > 0);
+        assert_eq!(context.max_read_bytes, DEFAULT_MAX_READ_BYTES);
     }
 
     #[test]
@@ -693,11 +1107,13 @@ mod tests {
             allowed_operations: vec!["test".to_string()],
             read_only: true,
             timeout_seconds: 60,
+            max_read_bytes: 1024,
         };
 
         assert_eq!(context.working_dir, PathBuf::from("/tmp"));
         assert!(context.read_only);
         assert_eq!(context.timeout_seconds, 60);
+        assert_eq!(context.max_read_bytes, 1024);
     }
 
     // ===== ToolResult Tests =====
@@ -714,6 +1130,8 @@ mod tests {
                 files_read: 1,
                 files_written: 0,
                 bytes_processed: 50,
+                structured: None,
+                truncated: None,
             },
         };
 
@@ -735,6 +1153,8 @@ mod tests {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured: None,
+                truncated: None,
             },
         };
 
@@ -158,6 +158,8 @@ impl Tool for MemoryQueryTool {
                     files_read: 0,
                     files_written: 0,
                     bytes_processed: 0,
+                    structured: None,
+                    truncated: None,
                 },
             });
         };
@@ -205,6 +207,8 @@ impl Tool for MemoryQueryTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured: None,
+                truncated: None,
             },
         })
     }
@@ -278,6 +282,7 @@ mod tests {
                 version: 2,
                 modified_at: Some(chrono::Utc::now()),
                 tags: vec!["gold-memory".to_string()],
+                expires_at: None,
             },
             access_control: AccessControl::new(AgentId::system(), MemoryStability::Canonical),
         };
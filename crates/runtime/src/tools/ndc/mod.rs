@@ -19,3 +19,94 @@ pub use task_verify::TaskVerifyTool;
 
 pub mod memory_query;
 pub use memory_query::MemoryQueryTool;
+
+use std::sync::Arc;
+
+use ndc_core::TokenCounter;
+use ndc_core::llm::provider::SimpleTokenCounter;
+
+use super::ToolError;
+
+/// 默认任务描述 token 上限
+pub const DEFAULT_MAX_DESCRIPTION_TOKENS: usize = 8_000;
+
+/// Token budget for task text created or updated through the task tools
+///
+/// Descriptions are re-injected into prompts, so oversized ones are rejected
+/// with a message telling the agent to trim.
+#[derive(Clone)]
+pub struct DescriptionBudget {
+    counter: Arc<dyn TokenCounter>,
+    max_tokens: usize,
+}
+
+impl DescriptionBudget {
+    pub fn new(counter: Arc<dyn TokenCounter>, max_tokens: usize) -> Self {
+        Self {
+            counter,
+            max_tokens,
+        }
+    }
+
+    pub fn counter(&self) -> Arc<dyn TokenCounter> {
+        self.counter.clone()
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    /// Count tokens of `text`
+    pub fn count(&self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        self.counter.count_text(text, "default")
+    }
+
+    /// Count tokens of a description, rejecting it when over budget
+    pub fn check(&self, description: &str) -> Result<usize, ToolError> {
+        let tokens = self.count(description);
+        if tokens > self.max_tokens {
+            return Err(ToolError::InvalidArgument(format!(
+                "Description too long: ~{} tokens (max {}). Trim it to the essentials",
+                tokens, self.max_tokens
+            )));
+        }
+        Ok(tokens)
+    }
+}
+
+impl Default for DescriptionBudget {
+    fn default() -> Self {
+        Self::new(
+            Arc::new(SimpleTokenCounter::new()),
+            DEFAULT_MAX_DESCRIPTION_TOKENS,
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use ndc_core::llm::{Message, TokenCounter};
+
+    /// Counts one token per whitespace separated word
+    pub struct WordCounter;
+
+    impl TokenCounter for WordCounter {
+        fn count_messages(&self, messages: &[Message], model: &str) -> usize {
+            messages
+                .iter()
+                .map(|m| self.count_text(&m.content, model))
+                .sum()
+        }
+
+        fn count_text(&self, text: &str, _model: &str) -> usize {
+            text.split_whitespace().count()
+        }
+
+        fn get_max_tokens(&self, _model: &str) -> usize {
+            usize::MAX
+        }
+    }
+}
@@ -2,17 +2,21 @@
 //!
 //! Allows AI to create new tasks with title and description.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use ndc_core::{AgentRole, Task, TaskPriority};
+use ndc_core::{AgentRole, Task, TaskPriority, TokenCounter};
 use ndc_storage::{SharedStorage, create_memory_storage};
 
 use super::super::schema::ToolSchemaBuilder;
 use super::super::{Tool, ToolError, ToolMetadata, ToolResult};
+use super::DescriptionBudget;
 
 /// Task Create Tool - 创建新任务
 #[derive(Clone)]
 pub struct TaskCreateTool {
     storage: SharedStorage,
+    budget: DescriptionBudget,
 }
 
 impl TaskCreateTool {
@@ -21,7 +25,22 @@ impl TaskCreateTool {
     }
 
     pub fn with_storage(storage: SharedStorage) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            budget: DescriptionBudget::default(),
+        }
+    }
+
+    /// Count description tokens with `counter`
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.budget = DescriptionBudget::new(counter, self.budget.max_tokens());
+        self
+    }
+
+    /// Reject descriptions above `max_tokens`
+    pub fn with_max_description_tokens(mut self, max_tokens: usize) -> Self {
+        self.budget = DescriptionBudget::new(self.budget.counter(), max_tokens);
+        self
     }
 }
 
@@ -70,6 +89,8 @@ impl Tool for TaskCreateTool {
             .get("description")
             .and_then(|v| v.as_str())
             .unwrap_or("");
+        let description_tokens = self.budget.check(description)?;
+        let task_tokens = self.budget.count(title) + description_tokens;
 
         let priority_str = params
             .get("priority")
@@ -120,6 +141,7 @@ impl Tool for TaskCreateTool {
         }
         output.push_str(&format!("Priority: {:?}\n", task.metadata.priority));
         output.push_str(&format!("State: {:?}\n", task.state));
+        output.push_str(&format!("Tokens: ~{}\n", task_tokens));
 
         let duration = start.elapsed().as_millis() as u64;
 
@@ -139,6 +161,12 @@ impl Tool for TaskCreateTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: title.len() as u64 + description.len() as u64,
+                structured: Some(serde_json::json!({
+                    "task_id": task_id,
+                    "description_tokens": description_tokens,
+                    "task_tokens": task_tokens,
+                })),
+                truncated: None,
             },
         })
     }
@@ -250,6 +278,32 @@ mod tests {
         assert!(required.contains(&serde_json::json!("title")));
     }
 
+    #[tokio::test]
+    async fn test_task_create_rejects_oversized_description() {
+        let tool = TaskCreateTool::new()
+            .with_token_counter(Arc::new(crate::tools::ndc::test_support::WordCounter))
+            .with_max_description_tokens(3);
+
+        let result = tool
+            .execute(&json!({"title": "Big", "description": "one two three four"}))
+            .await;
+        match result {
+            Err(ToolError::InvalidArgument(msg)) => assert!(msg.contains("4 tokens (max 3)")),
+            other => panic!(
+                "Expected InvalidArgument, got {:?}",
+                other.map(|r| r.output)
+            ),
+        }
+
+        let result = tool
+            .execute(&json!({"title": "Small task", "description": "one two three"}))
+            .await
+            .unwrap();
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["description_tokens"], 3);
+        assert_eq!(structured["task_tokens"], 5);
+    }
+
     #[tokio::test]
     async fn test_task_create_persists_to_storage() {
         let storage = create_memory_storage();
@@ -229,6 +229,8 @@ impl Tool for TaskListTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured: None,
+                truncated: None,
             },
         })
     }
@@ -2,17 +2,21 @@
 //!
 //! Allows AI to update task status, add notes, change priority, etc.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use ndc_core::{TaskId, TaskPriority, TaskState};
+use ndc_core::{TaskId, TaskPriority, TaskState, TokenCounter};
 
 use super::super::schema::{JsonSchema, JsonSchemaProperty, ToolSchemaBuilder};
 use super::super::{Tool, ToolError, ToolMetadata, ToolResult};
+use super::DescriptionBudget;
 use ndc_storage::{SharedStorage, create_memory_storage};
 
 /// Task Update Tool - 更新任务状态
 #[derive(Clone)]
 pub struct TaskUpdateTool {
     storage: SharedStorage,
+    budget: DescriptionBudget,
 }
 
 impl TaskUpdateTool {
@@ -21,7 +25,22 @@ impl TaskUpdateTool {
     }
 
     pub fn with_storage(storage: SharedStorage) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            budget: DescriptionBudget::default(),
+        }
+    }
+
+    /// Count description tokens with `counter`
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.budget = DescriptionBudget::new(counter, self.budget.max_tokens());
+        self
+    }
+
+    /// Reject updates leaving the description above `max_tokens`
+    pub fn with_max_description_tokens(mut self, max_tokens: usize) -> Self {
+        self.budget = DescriptionBudget::new(self.budget.counter(), max_tokens);
+        self
     }
 }
 
@@ -91,6 +110,7 @@ impl Tool for TaskUpdateTool {
             .ok_or_else(|| ToolError::ExecutionFailed(format!("Task not found: {}", task_id)))?;
 
         let mut updates = Vec::new();
+        let mut notes_added = false;
 
         if let Some(state_str) = params.get("state").and_then(|v| v.as_str()) {
             let state = match parse_state(state_str) {
@@ -150,6 +170,7 @@ impl Tool for TaskUpdateTool {
                 notes.trim()
             ));
             updates.push(format!("notes: {} chars", notes.len()));
+            notes_added = true;
         }
 
         if let Some(add_tags) = params.get("add_tags").and_then(|v| v.as_array()) {
@@ -180,6 +201,14 @@ impl Tool for TaskUpdateTool {
             });
         }
 
+        // Notes grow the description, which is re-injected into prompts
+        let description_tokens = if notes_added {
+            self.budget.check(&task.description)?
+        } else {
+            self.budget.count(&task.description)
+        };
+        let task_tokens = self.budget.count(&task.title) + description_tokens;
+
         task.metadata.updated_at = chrono::Utc::now();
         self.storage
             .save_task(&task)
@@ -209,6 +238,12 @@ impl Tool for TaskUpdateTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured: Some(serde_json::json!({
+                    "task_id": task_id.to_string(),
+                    "description_tokens": description_tokens,
+                    "task_tokens": task_tokens,
+                })),
+                truncated: None,
             },
         })
     }
@@ -327,6 +362,31 @@ mod tests {
         assert_eq!(persisted.state, TaskState::Pending);
     }
 
+    #[tokio::test]
+    async fn test_task_update_rejects_notes_over_budget() {
+        let storage = create_memory_storage();
+        let task = seed_task(storage.clone()).await;
+        let tool = TaskUpdateTool::with_storage(storage.clone())
+            .with_token_counter(Arc::new(crate::tools::ndc::test_support::WordCounter))
+            .with_max_description_tokens(5);
+        let task_id = task.id.to_string();
+
+        let result = tool
+            .execute(&json!({"task_id": task_id, "notes": "far too many words here"}))
+            .await;
+        assert!(matches!(result, Err(ToolError::InvalidArgument(_))));
+        let persisted = storage.get_task(&task.id).await.unwrap().unwrap();
+        assert_eq!(persisted.description, "Description");
+
+        let result = tool
+            .execute(&json!({"task_id": task_id, "notes": "short"}))
+            .await
+            .unwrap();
+        let structured = result.metadata.structured.unwrap();
+        assert_eq!(structured["description_tokens"], 4);
+        assert_eq!(structured["task_tokens"], 5);
+    }
+
     #[tokio::test]
     async fn test_task_update_missing_task_id() {
         let tool = TaskUpdateTool::new();
@@ -152,6 +152,8 @@ impl Tool for TaskVerifyTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: 0,
+                structured: None,
+                truncated: None,
             },
         })
     }
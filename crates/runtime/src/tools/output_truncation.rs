@@ -32,6 +32,18 @@ pub struct TruncatedOutput {
     pub line_count: usize,
 }
 
+/// Which part of an oversized output to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TruncationStrategy {
+    /// Keep the first `head_lines` lines
+    Head,
+    /// Keep the last `tail_lines` lines
+    Tail,
+    /// Keep the first `head_lines` and the last `tail_lines` lines
+    #[default]
+    Middle,
+}
+
 /// Output truncation configuration
 #[derive(Debug, Clone)]
 pub struct TruncationConfig {
@@ -47,6 +59,8 @@ pub struct TruncationConfig {
     pub head_lines: usize,
     /// Tail lines to keep when truncated
     pub tail_lines: usize,
+    /// Which lines survive truncation
+    pub strategy: TruncationStrategy,
 }
 
 impl Default for TruncationConfig {
@@ -58,6 +72,7 @@ impl Default for TruncationConfig {
             save_to_disk: true,
             head_lines: 100,
             tail_lines: 100,
+            strategy: TruncationStrategy::default(),
         }
     }
 }
@@ -115,22 +130,37 @@ impl OutputTruncator {
         let lines: Vec<&str> = output.lines().collect();
         let total_lines = lines.len();
 
-        // Keep head and tail
-        let head_end = std::cmp::min(self.config.head_lines, total_lines);
-        let tail_start =
-            std::cmp::max(total_lines.saturating_sub(self.config.tail_lines), head_end);
+        let (head_len, tail_len) = match self.config.strategy {
+            TruncationStrategy::Head => (self.config.head_lines, 0),
+            TruncationStrategy::Tail => (0, self.config.tail_lines),
+            TruncationStrategy::Middle => (self.config.head_lines, self.config.tail_lines),
+        };
+        let head_end = std::cmp::min(head_len, total_lines);
+        let tail_start = std::cmp::max(total_lines.saturating_sub(tail_len), head_end);
+
+        let head = lines[..head_end].join("\n");
+        let tail = lines[tail_start..].join("\n");
+        let (head, tail, omitted_bytes) = fit_bytes(&head, &tail, self.config.max_bytes);
 
-        let head: Vec<&str> = lines[..head_end].to_vec();
-        let tail: Vec<&str> = lines[tail_start..].to_vec();
+        let mut kept: Vec<String> = Vec::new();
+        if !head.is_empty() {
+            kept.push(head.to_string());
+        }
+        if tail_start > head_end {
+            kept.push(omitted_marker(tail_start - head_end));
+        }
+        if omitted_bytes > 0 {
+            kept.push(format!("... {} bytes omitted ...", omitted_bytes));
+        }
+        if !tail.is_empty() {
+            kept.push(tail.to_string());
+        }
 
-        // Format truncated content
         let truncated_content = format!(
-            "{}\n\n... truncated ({}/{} lines, {} bytes) ...\n\n{}\n\nHint: Use read tool with offset/limit to view specific portions. Full output saved to: {}",
-            head.join("\n"),
-            total_lines - head_end - tail.len(),
+            "{}\n\n... truncated ({} lines, {} bytes). Hint: Use read tool with offset/limit to view specific portions. Full output saved to: {}",
+            kept.join("\n"),
             total_lines,
             byte_count,
-            tail.join("\n"),
             if self.config.save_to_disk {
                 "see output_path below"
             } else {
@@ -204,6 +234,36 @@ impl Default for OutputTruncator {
     }
 }
 
+/// Marker line inserted where lines were dropped
+fn omitted_marker(omitted: usize) -> String {
+    format!("... {} lines omitted ...", omitted)
+}
+
+/// Cut `head` from its end and `tail` from its start until together they fit
+/// in `max_bytes`; returns the kept parts and the number of bytes dropped
+///
+/// The budget is split evenly, with either side's unused share going to the other.
+fn fit_bytes<'a>(head: &'a str, tail: &'a str, max_bytes: usize) -> (&'a str, &'a str, usize) {
+    let total = head.len() + tail.len();
+    if total <= max_bytes {
+        return (head, tail, 0);
+    }
+    let half = max_bytes / 2;
+    let head_budget = head.len().min(max_bytes - tail.len().min(max_bytes - half));
+    let tail_budget = max_bytes - head_budget;
+
+    let mut head_end = head_budget;
+    while !head.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let mut tail_start = tail.len().saturating_sub(tail_budget);
+    while !tail.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    let (head, tail) = (&head[..head_end], &tail[tail_start..]);
+    (head, tail, total - head.len() - tail.len())
+}
+
 /// Read partial content from a saved output file
 pub fn read_partial_output(
     file_path: &PathBuf,
@@ -300,6 +360,7 @@ mod tests {
             save_to_disk: false,
             head_lines: 2,
             tail_lines: 2,
+            strategy: TruncationStrategy::Middle,
         };
 
         let mut truncator = OutputTruncator::with_config(config);
@@ -310,4 +371,111 @@ mod tests {
         assert!(result.truncated);
         assert!(!result.output_path.is_some()); // save_to_disk is false
     }
+
+    fn numbered(count: usize) -> (String, Vec<String>) {
+        let lines: Vec<String> = (1..=count).map(|i| format!("Line {}", i)).collect();
+        (lines.join("\n"), lines)
+    }
+
+    fn truncate_with(
+        strategy: TruncationStrategy,
+        head: usize,
+        tail: usize,
+    ) -> (Vec<String>, TruncatedOutput, String) {
+        let mut truncator = OutputTruncator::with_config(TruncationConfig {
+            max_lines: 20,
+            save_to_disk: false,
+            head_lines: head,
+            tail_lines: tail,
+            strategy,
+            ..TruncationConfig::default()
+        });
+        let (output, lines) = numbered(100);
+        let result = truncator.truncate(&output);
+        (lines, result, output)
+    }
+
+    /// Lines of the truncated body, without the trailing hint
+    fn body(result: &TruncatedOutput) -> Vec<&str> {
+        let (body, _) = result.content.split_once("\n\n... truncated").unwrap();
+        body.lines().collect()
+    }
+
+    fn markers(lines: &[&str]) -> Vec<usize> {
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| l.ends_with("lines omitted ..."))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_middle_keeps_head_and_tail() {
+        let (lines, result, output) = truncate_with(TruncationStrategy::Middle, 3, 5);
+        let body = body(&result);
+
+        assert_eq!(markers(&body), vec![3]);
+        assert_eq!(body[3], "... 92 lines omitted ...");
+        assert_eq!(body[..3], lines[..3]);
+        assert_eq!(body[4..], lines[95..]);
+        assert_eq!(result.original_size, output.len());
+        assert_eq!(result.line_count, 100);
+    }
+
+    #[test]
+    fn test_head_strategy() {
+        let (lines, result, _) = truncate_with(TruncationStrategy::Head, 4, 5);
+        let body = body(&result);
+
+        assert_eq!(markers(&body), vec![4]);
+        assert_eq!(body[4], "... 96 lines omitted ...");
+        assert_eq!(body[..4], lines[..4]);
+        assert_eq!(body.len(), 5);
+    }
+
+    #[test]
+    fn test_tail_strategy() {
+        let (lines, result, output) = truncate_with(TruncationStrategy::Tail, 4, 6);
+        let body = body(&result);
+
+        assert_eq!(markers(&body), vec![0]);
+        assert_eq!(body[0], "... 94 lines omitted ...");
+        assert_eq!(body[1..], lines[94..]);
+        assert_eq!(result.original_size, output.len());
+    }
+
+    #[test]
+    fn test_long_lines_over_byte_budget_are_sliced() {
+        let mut truncator = OutputTruncator::with_config(TruncationConfig {
+            max_bytes: 100,
+            save_to_disk: false,
+            head_lines: 2,
+            tail_lines: 2,
+            ..TruncationConfig::default()
+        });
+        let output = ["a".repeat(200), "é".repeat(150), "c".repeat(200)].join("\n");
+
+        let result = truncator.truncate(&output);
+        let body = body(&result);
+
+        assert!(result.truncated);
+        assert!(markers(&body).is_empty());
+        let marker = body
+            .iter()
+            .position(|l| l.ends_with("bytes omitted ..."))
+            .unwrap();
+        let kept: usize = body
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != marker)
+            .map(|(_, l)| l.len())
+            .sum();
+        assert!(kept <= 100, "kept {} bytes", kept);
+        assert!(body[0].starts_with('a'));
+        assert!(body[body.len() - 1].ends_with('c'));
+        // The newline between the head and tail lines is not counted
+        let omitted = output.len() - kept - 1;
+        assert_eq!(body[marker], format!("... {} bytes omitted ...", omitted));
+    }
 }
@@ -4,9 +4,13 @@
 //! - Dangerous operation detection
 //! - Permission confirmation requests
 //! - Permission usage logging
+//! - Audit log of decisions, persisted through `Storage`
 //!
 //! Design参考 OpenCode permission.ts
 
+use chrono::{DateTime, Utc};
+use ndc_core::redaction::{RedactionMode, sanitize_text};
+use ndc_storage::{SharedStorage, create_memory_storage};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
@@ -27,7 +31,7 @@ pub enum PermissionError {
 }
 
 /// 危险操作级别
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DangerLevel {
     /// 安全操作
     Safe = 0,
@@ -59,7 +63,7 @@ impl DangerLevel {
 }
 
 /// 权限类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PermissionType {
     /// 读取文件
     Read,
@@ -137,16 +141,79 @@ struct CacheEntry {
     response: PermissionResponse,
     /// 创建时间
     created_at: SystemTime,
+    /// 原始请求 (用于审计)
+    request: PermissionRequest,
+    /// 评估后的危险级别
+    level: DangerLevel,
+}
+
+/// 审计决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditDecision {
+    Granted,
+    Denied,
+    ConfirmationRequested,
+    /// A remembered "always allow" rule was created
+    RuleCreated,
+}
+
+/// 权限审计条目
+///
+/// Only a redacted summary of the request is kept, never its raw description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionAuditEntry {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub decision: AuditDecision,
+    /// Who decided: `auto`, `policy`, `user` or `remembered_rule`
+    pub decided_by: String,
+    pub permission_type: PermissionType,
+    pub path: Option<PathBuf>,
+    /// Redacted request description
+    pub summary: String,
+    pub danger_level: DangerLevel,
+    /// Whether a remembered rule was created or applied
+    pub remembered_rule: bool,
+}
+
+/// 审计查询过滤器
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub decision: Option<AuditDecision>,
+    pub min_danger: Option<DangerLevel>,
+    pub since: Option<DateTime<Utc>>,
+    pub remembered_only: bool,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &PermissionAuditEntry) -> bool {
+        self.decision.is_none_or(|d| entry.decision == d)
+            && self.min_danger.is_none_or(|l| entry.danger_level.ge(&l))
+            && self.since.is_none_or(|t| entry.timestamp >= t)
+            && (!self.remembered_only || entry.remembered_rule)
+    }
 }
 
 /// 权限系统
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct PermissionSystem {
     /// 配置
     config: PermissionConfig,
 
     /// 权限缓存
     cache: Vec<CacheEntry>,
+
+    /// 审计日志存储
+    audit: SharedStorage,
+}
+
+impl std::fmt::Debug for PermissionSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PermissionSystem")
+            .field("config", &self.config)
+            .field("cache", &self.cache)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for PermissionConfig {
@@ -254,9 +321,16 @@ impl PermissionSystem {
         Self {
             config: config.unwrap_or_default(),
             cache: Vec::new(),
+            audit: create_memory_storage(),
         }
     }
 
+    /// 使用指定存储记录审计日志
+    pub fn with_audit_storage(mut self, storage: SharedStorage) -> Self {
+        self.audit = storage;
+        self
+    }
+
     /// 检查权限
     pub async fn check(
         &mut self,
@@ -273,6 +347,8 @@ impl PermissionSystem {
         // 如果危险级别在自动允许范围内，直接允许
         if self.should_auto_allow(level) {
             debug!("Permission auto-allowed: {:?}", request.description);
+            self.record(&request, level, AuditDecision::Granted, "auto", false)
+                .await;
             return Ok(PermissionResponse::Allow);
         }
 
@@ -282,13 +358,28 @@ impl PermissionSystem {
         // 检查缓存
         if let Some(cached) = self.get_cached(&hash) {
             debug!("Permission cached: {:?}", request.description);
+            let decision = match cached {
+                PermissionResponse::Allow => AuditDecision::Granted,
+                PermissionResponse::Deny => AuditDecision::Denied,
+                PermissionResponse::Confirm(_) => AuditDecision::ConfirmationRequested,
+            };
+            self.record(&request, level, decision, "remembered_rule", true)
+                .await;
             return Ok(cached);
         }
 
         // 需要确认
         if self.should_confirm(level) {
             let response = PermissionResponse::Confirm(hash.clone());
-            self.cache_response(hash, response.clone());
+            self.cache_response(hash, response.clone(), &request, level);
+            self.record(
+                &request,
+                level,
+                AuditDecision::ConfirmationRequested,
+                "policy",
+                false,
+            )
+            .await;
             return Err(PermissionError::RequiresConfirmation(format!(
                 "Operation requires confirmation: {}",
                 request.description
@@ -298,6 +389,8 @@ impl PermissionSystem {
         // 默认拒绝高风险操作
         if level.ge(&DangerLevel::High) {
             warn!("Permission denied: {:?}", request.description);
+            self.record(&request, level, AuditDecision::Denied, "policy", false)
+                .await;
             return Err(PermissionError::Denied(format!(
                 "High-risk operation denied: {}",
                 request.description
@@ -305,6 +398,8 @@ impl PermissionSystem {
         }
 
         // 默认允许低风险操作
+        self.record(&request, level, AuditDecision::Granted, "policy", false)
+            .await;
         Ok(PermissionResponse::Allow)
     }
 
@@ -314,20 +409,71 @@ impl PermissionSystem {
         hash: &str,
         confirm: bool,
     ) -> Result<PermissionResponse, PermissionError> {
+        let Some(idx) = self.cache.iter().position(|entry| entry.hash == hash) else {
+            return match confirm {
+                true => Err(PermissionError::InvalidScope(hash.to_string())),
+                false => Err(PermissionError::Denied("User denied operation".to_string())),
+            };
+        };
+        let (request, level) = (self.cache[idx].request.clone(), self.cache[idx].level);
+
         if confirm {
-            // 更新缓存
-            for entry in &mut self.cache {
-                if entry.hash == hash {
-                    entry.response = PermissionResponse::Allow;
-                    return Ok(PermissionResponse::Allow);
-                }
-            }
-            Err(PermissionError::InvalidScope(hash.to_string()))
+            // 更新缓存: 记住为 "always allow" 规则
+            self.cache[idx].response = PermissionResponse::Allow;
+            self.record(&request, level, AuditDecision::Granted, "user", false)
+                .await;
+            self.record(&request, level, AuditDecision::RuleCreated, "user", true)
+                .await;
+            Ok(PermissionResponse::Allow)
         } else {
+            self.record(&request, level, AuditDecision::Denied, "user", false)
+                .await;
             Err(PermissionError::Denied("User denied operation".to_string()))
         }
     }
 
+    /// 查询审计日志 (按时间顺序)
+    pub async fn audit_entries(
+        &self,
+        filter: &AuditFilter,
+    ) -> Result<Vec<PermissionAuditEntry>, String> {
+        let entries = self.audit.list_audit().await?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|value| serde_json::from_value::<PermissionAuditEntry>(value).ok())
+            .filter(|entry| filter.matches(entry))
+            .collect())
+    }
+
+    /// 记录审计条目; 写入失败只告警, 不影响权限决定
+    async fn record(
+        &self,
+        request: &PermissionRequest,
+        level: DangerLevel,
+        decision: AuditDecision,
+        decided_by: &str,
+        remembered_rule: bool,
+    ) {
+        let entry = PermissionAuditEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            decision,
+            decided_by: decided_by.to_string(),
+            permission_type: request.permission_type.clone(),
+            path: request.path.clone(),
+            summary: sanitize_text(&request.description, RedactionMode::Basic),
+            danger_level: level,
+            remembered_rule,
+        };
+        let result = match serde_json::to_value(&entry) {
+            Ok(value) => self.audit.append_audit(&value).await,
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            warn!(error = %e, "Failed to write permission audit entry");
+        }
+    }
+
     /// 评估危险级别
     fn assess_danger(&self, request: &PermissionRequest) -> DangerLevel {
         // 如果已经有明确的危险级别，使用它
@@ -401,11 +547,19 @@ impl PermissionSystem {
     }
 
     /// 缓存响应
-    fn cache_response(&mut self, hash: String, response: PermissionResponse) {
+    fn cache_response(
+        &mut self,
+        hash: String,
+        response: PermissionResponse,
+        request: &PermissionRequest,
+        level: DangerLevel,
+    ) {
         self.cache.push(CacheEntry {
             hash,
             response,
             created_at: SystemTime::now(),
+            request: request.clone(),
+            level,
         });
 
         // 清理过期缓存
@@ -431,9 +585,18 @@ impl PermissionSystem {
 }
 
 /// 权限系统构建器
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct PermissionSystemBuilder {
     config: PermissionConfig,
+    audit: Option<SharedStorage>,
+}
+
+impl std::fmt::Debug for PermissionSystemBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PermissionSystemBuilder")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
 }
 
 impl PermissionSystemBuilder {
@@ -466,9 +629,19 @@ impl PermissionSystemBuilder {
         self
     }
 
+    /// 设置审计日志存储
+    pub fn audit_storage(mut self, storage: SharedStorage) -> Self {
+        self.audit = Some(storage);
+        self
+    }
+
     /// 构建权限系统
     pub fn build(self) -> PermissionSystem {
-        PermissionSystem::new(Some(self.config))
+        let system = PermissionSystem::new(Some(self.config));
+        match self.audit {
+            Some(storage) => system.with_audit_storage(storage),
+            None => system,
+        }
     }
 }
 
@@ -570,4 +743,87 @@ mod tests {
         assert!(DangerLevel::High.ge(&DangerLevel::Medium));
         assert!(!DangerLevel::Low.ge(&DangerLevel::High));
     }
+
+    #[tokio::test]
+    async fn test_grant_and_deny_are_audited() {
+        let storage = create_memory_storage();
+        let mut system = PermissionSystemBuilder::new()
+            .audit_storage(storage.clone())
+            .build();
+
+        let write = PermissionRequest {
+            permission_type: PermissionType::Write,
+            path: Some(PathBuf::from("/repo/.env")),
+            description: "write api_key=sk-abcdefghijklmnop".to_string(),
+            danger_level: DangerLevel::Safe,
+            confirmed: false,
+        };
+        system.check(write).await.unwrap();
+        let wipe = PermissionRequest {
+            permission_type: PermissionType::Execute,
+            path: None,
+            description: "rm -rf /".to_string(),
+            danger_level: DangerLevel::Critical,
+            confirmed: false,
+        };
+        assert!(system.check(wipe).await.is_err());
+
+        let entries = system.audit_entries(&AuditFilter::default()).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].decision, AuditDecision::Granted);
+        assert_eq!(entries[0].decided_by, "auto");
+        assert_eq!(entries[0].permission_type, PermissionType::Write);
+        assert_eq!(entries[0].path, Some(PathBuf::from("/repo/.env")));
+        assert_eq!(entries[0].danger_level, DangerLevel::Low);
+        assert!(!entries[0].remembered_rule);
+        assert_eq!(entries[1].decision, AuditDecision::Denied);
+        assert_eq!(entries[1].decided_by, "policy");
+        assert_eq!(entries[1].danger_level, DangerLevel::Critical);
+
+        // The secret never reaches storage
+        let raw = serde_json::to_string(&storage.list_audit().await.unwrap()).unwrap();
+        assert!(!raw.contains("sk-abcdefghijklmnop"));
+        assert!(entries[0].summary.contains("[REDACTED]"));
+
+        let denied = AuditFilter {
+            decision: Some(AuditDecision::Denied),
+            ..AuditFilter::default()
+        };
+        assert_eq!(system.audit_entries(&denied).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remembered_rule_is_audited_when_created_and_applied() {
+        let mut system = PermissionSystem::new(None);
+        let request = PermissionRequest {
+            permission_type: PermissionType::Execute,
+            path: None,
+            description: "git push --force".to_string(),
+            danger_level: DangerLevel::High,
+            confirmed: false,
+        };
+
+        assert!(matches!(
+            system.check(request.clone()).await,
+            Err(PermissionError::RequiresConfirmation(_))
+        ));
+        let hash = system.hash_request(&request);
+        system.confirm(&hash, true).await.unwrap();
+        assert_eq!(
+            system.check(request).await.unwrap(),
+            PermissionResponse::Allow
+        );
+
+        let remembered = AuditFilter {
+            remembered_only: true,
+            ..AuditFilter::default()
+        };
+        let entries = system.audit_entries(&remembered).await.unwrap();
+        let decisions: Vec<_> = entries.iter().map(|e| e.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![AuditDecision::RuleCreated, AuditDecision::Granted]
+        );
+        assert_eq!(entries[1].decided_by, "remembered_rule");
+    }
 }
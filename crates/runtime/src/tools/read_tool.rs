@@ -4,16 +4,20 @@
 //! Design参考 OpenCode read.ts
 
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tracing::debug;
 
+use super::binary::{binary_file_notice, is_binary_file};
 use super::schema::ToolSchemaBuilder;
-use super::{Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
+use super::{Tool, ToolContext, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
 
 /// Read tool - 读取文件内容
 #[derive(Debug)]
-pub struct ReadTool;
+pub struct ReadTool {
+    context: ToolContext,
+}
 
 impl Default for ReadTool {
     fn default() -> Self {
@@ -23,10 +27,64 @@ impl Default for ReadTool {
 
 impl ReadTool {
     pub fn new() -> Self {
-        Self
+        Self::with_context(ToolContext::default())
+    }
+
+    pub fn with_context(context: ToolContext) -> Self {
+        Self { context }
+    }
+
+    /// Stream a line range without loading the whole file.
+    ///
+    /// Used when the file exceeds `max_bytes` but the caller asked for an
+    /// explicit `limit`; collected output is still capped at `max_bytes`.
+    /// The rest of the file is still scanned to report `total_lines`.
+    async fn read_line_range(
+        path: &Path,
+        skip: usize,
+        limit: usize,
+        max_bytes: u64,
+    ) -> Result<LineRange, ToolError> {
+        let file = fs::File::open(path).await.map_err(ToolError::Io)?;
+        let mut lines = BufReader::new(file).split(b'\n');
+        let mut collected = Vec::new();
+        let mut collected_bytes = 0u64;
+        let mut truncated = false;
+        let mut index = 0usize;
+
+        while let Some(line) = lines.next_segment().await.map_err(ToolError::Io)? {
+            if index >= skip && collected.len() < limit && !truncated {
+                let mut line = String::from_utf8_lossy(&line).into_owned();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+                collected_bytes += line.len() as u64 + 1;
+                if collected_bytes > max_bytes {
+                    truncated = true;
+                } else {
+                    collected.push(line);
+                }
+            }
+            index += 1;
+        }
+
+        Ok(LineRange {
+            lines: collected,
+            total_lines: index,
+            truncated,
+        })
     }
 }
 
+/// Lines selected by `offset`/`limit`
+struct LineRange {
+    lines: Vec<String>,
+    /// Line count of the whole file
+    total_lines: usize,
+    /// Whether the range was cut short by `max_bytes`
+    truncated: bool,
+}
+
 #[async_trait]
 impl Tool for ReadTool {
     fn name(&self) -> &str {
@@ -73,90 +131,116 @@ impl Tool for ReadTool {
         enforce_path_boundary(path.as_path(), None, "read")?;
 
         // Check file size before reading
-        const MAX_READ_SIZE: u64 = 10 * 1024 * 1024; // 10MB
+        let max_bytes = self.context.effective_max_read_bytes(params);
         let meta = fs::metadata(&path).await.map_err(ToolError::Io)?;
-        if meta.len() > MAX_READ_SIZE {
-            return Err(ToolError::InvalidArgument(format!(
-                "File too large: {} bytes (max {} bytes)",
-                meta.len(),
-                MAX_READ_SIZE
-            )));
-        }
+        // offset 是 1-based 行号；0 视同 1
+        let offset = params
+            .get("offset")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1)
+            .max(1) as usize;
+        let skip = offset - 1;
+        let limit = params
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
 
         let start = std::time::Instant::now();
 
-        // Read entire file first
-        let content = fs::read_to_string(&path).await.map_err(ToolError::Io)?;
-
-        let total_lines = content.lines().count();
-        let total_bytes = content.len();
+        if is_binary_file(&path).await.map_err(ToolError::Io)? {
+            return Ok(ToolResult {
+                success: true,
+                output: binary_file_notice(&path, meta.len()),
+                error: None,
+                metadata: ToolMetadata {
+                    execution_time_ms: start.elapsed().as_millis() as u64,
+                    files_read: 1,
+                    files_written: 0,
+                    bytes_processed: 0,
+                    structured: None,
+                    truncated: None,
+                },
+            });
+        }
 
-        // Apply offset if provided
-        let lines: Vec<&str> = if let Some(offset) = params.get("offset").and_then(|v| v.as_u64()) {
-            let offset = offset as usize;
-            if offset >= total_lines {
+        let (range, bytes_processed) = if meta.len() > max_bytes {
+            let Some(limit) = limit else {
                 return Err(ToolError::InvalidArgument(format!(
-                    "offset {} is beyond file length {}",
-                    offset, total_lines
+                    "File too large: {} bytes (max {} bytes). Read a line range with \
+                     'offset'/'limit', or raise 'max_bytes' for this call",
+                    meta.len(),
+                    max_bytes
                 )));
-            }
-            content.lines().skip(offset).collect()
-        } else {
-            content.lines().collect()
-        };
+            };
 
-        // Apply limit if provided
-        let lines: Vec<&str> = if let Some(limit) = params.get("limit").and_then(|v| v.as_u64()) {
-            let limit = limit as usize;
-            let start_idx = 0;
-            let end_idx = std::cmp::min(limit, lines.len() - start_idx);
-            if start_idx >= lines.len() {
-                Vec::new()
-            } else {
-                lines[start_idx..start_idx + end_idx].to_vec()
-            }
+            let range = Self::read_line_range(&path, skip, limit, max_bytes).await?;
+            let bytes = range.lines.iter().map(|l| l.len() as u64 + 1).sum();
+            (range, bytes)
         } else {
-            lines
+            let bytes = fs::read(&path).await.map_err(ToolError::Io)?;
+            let content = String::from_utf8(bytes)
+                .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+
+            let all: Vec<&str> = content.lines().collect();
+            let total_lines = all.len();
+            let end = limit.map_or(total_lines, |l| skip.saturating_add(l).min(total_lines));
+            let lines = all
+                .get(skip..end)
+                .unwrap_or_default()
+                .iter()
+                .map(|l| l.to_string())
+                .collect();
+            (
+                LineRange {
+                    lines,
+                    total_lines,
+                    truncated: false,
+                },
+                content.len() as u64,
+            )
         };
 
-        let displayed_lines = lines.len();
-        let output = lines.join("\n");
-
-        // Add line numbers if requested
-        if params
+        let mut output = if params
             .get("number")
             .and_then(|v| v.as_bool())
             .unwrap_or(false)
         {
-            let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
-            let numbered: Vec<String> = lines
+            range
+                .lines
                 .iter()
                 .enumerate()
-                .map(|(i, line)| format!("{:6}  {}", offset + i as u64 + 1, line))
-                .collect();
-            let output = numbered.join("\n");
-            let duration = start.elapsed().as_millis() as u64;
-
-            return Ok(ToolResult {
-                success: true,
-                output,
-                error: None,
-                metadata: ToolMetadata {
-                    execution_time_ms: duration,
-                    files_read: 1,
-                    files_written: 0,
-                    bytes_processed: total_bytes as u64,
-                },
-            });
+                .map(|(i, line)| format!("{:6}  {}", offset + i, line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            range.lines.join("\n")
+        };
+        if range.truncated {
+            output.push_str(&format!(
+                "\n... (range truncated at {} bytes; use a smaller 'limit')",
+                max_bytes
+            ));
         }
 
-        let duration = start.elapsed().as_millis() as u64;
+        let returned = range.lines.len();
+        let mut structured = serde_json::json!({
+            "offset": offset,
+            "lines_returned": returned,
+            "total_lines": range.total_lines,
+            "has_more": skip + returned < range.total_lines,
+        });
+        if skip > 0 && skip >= range.total_lines {
+            structured["note"] = serde_json::Value::String(format!(
+                "offset {} is past the end of the file ({} lines)",
+                offset, range.total_lines
+            ));
+        }
 
         debug!(
-            "Read {} bytes ({} lines, displayed {}) from {}",
-            total_bytes,
-            total_lines,
-            displayed_lines,
+            "Read {} of {} lines (offset {}) from {}",
+            returned,
+            range.total_lines,
+            offset,
             path.display()
         );
 
@@ -165,10 +249,12 @@ impl Tool for ReadTool {
             output,
             error: None,
             metadata: ToolMetadata {
-                execution_time_ms: duration,
+                execution_time_ms: start.elapsed().as_millis() as u64,
                 files_read: 1,
                 files_written: 0,
-                bytes_processed: total_bytes as u64,
+                bytes_processed,
+                structured: Some(structured),
+                truncated: None,
             },
         })
     }
@@ -177,9 +263,13 @@ impl Tool for ReadTool {
         ToolSchemaBuilder::new()
             .description("Read file contents")
             .required_string("path", "The absolute path to the file to read")
-            .param_integer("offset", "Line number to start reading from (0-indexed)")
+            .param_integer("offset", "Line number to start reading from (1-based)")
             .param_integer("limit", "Maximum number of lines to read")
             .param_boolean("number", "Whether to include line numbers")
+            .param_integer(
+                "max_bytes",
+                "Override the maximum file size in bytes for this read",
+            )
             .build()
             .to_value()
     }
@@ -218,7 +308,7 @@ mod tests {
         let tool = ReadTool::new();
         let params = serde_json::json!({
             "path": file_path.to_string_lossy(),
-            "offset": 1
+            "offset": 2
         });
 
         let result = tool.execute(&params).await.unwrap();
@@ -254,7 +344,7 @@ mod tests {
         let tool = ReadTool::new();
         let params = serde_json::json!({
             "path": file_path.to_string_lossy(),
-            "offset": 1,
+            "offset": 2,
             "limit": 2
         });
 
@@ -327,6 +417,71 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_read_rejects_file_over_context_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("over.txt");
+        std::fs::write(&file_path, "0123456789\n".repeat(10)).unwrap();
+
+        let tool = ReadTool::with_context(ToolContext {
+            max_read_bytes: 32,
+            ..ToolContext::default()
+        });
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy()
+        });
+
+        let err = format!("{}", tool.execute(&params).await.unwrap_err());
+        assert!(err.contains("too large"), "unexpected error: {}", err);
+        assert!(
+            err.contains("'limit'"),
+            "should suggest a ranged read: {}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_max_bytes_override_allows_large_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("over.txt");
+        std::fs::write(&file_path, "0123456789\n".repeat(10)).unwrap();
+
+        let tool = ReadTool::with_context(ToolContext {
+            max_read_bytes: 32,
+            ..ToolContext::default()
+        });
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy(),
+            "max_bytes": 1024
+        });
+
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output.lines().count(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_read_line_range_of_file_over_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("over.txt");
+        let content: String = (1..=100).map(|i| format!("line{}\n", i)).collect();
+        std::fs::write(&file_path, content).unwrap();
+
+        let tool = ReadTool::with_context(ToolContext {
+            max_read_bytes: 64,
+            ..ToolContext::default()
+        });
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy(),
+            "offset": 50,
+            "limit": 2
+        });
+
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "line50\nline51");
+    }
+
     #[tokio::test]
     async fn test_read_rejects_dev_path() {
         let tool = ReadTool::new();
@@ -392,18 +547,23 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_read_binary_file_returns_error() {
+    async fn test_read_binary_file_reports_notice() {
         let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("binary.dat");
-        std::fs::write(&file_path, &[0x00, 0xFF, 0x80, 0xFE, 0x01]).unwrap();
+        let file_path = temp_dir.path().join("image.png");
+        std::fs::write(
+            &file_path,
+            [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00],
+        )
+        .unwrap();
 
         let tool = ReadTool::new();
         let params = serde_json::json!({
             "path": file_path.to_str().unwrap()
         });
-        let result = tool.execute(&params).await;
-        // read_to_string will fail on invalid UTF-8
-        assert!(result.is_err());
+        let result = tool.execute(&params).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.starts_with("Binary file"));
+        assert!(result.output.contains("10 bytes, not shown"));
     }
 
     #[tokio::test]
@@ -415,4 +575,74 @@ mod tests {
         let result = tool.execute(&params).await;
         assert!(result.is_err());
     }
+
+    fn line_file(temp_dir: &TempDir, content: &str) -> String {
+        let file_path = temp_dir.path().join("lines.txt");
+        std::fs::write(&file_path, content).unwrap();
+        file_path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_read_middle_slice_reports_total_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let content: String = (1..=10).map(|i| format!("line{}\n", i)).collect();
+        let path = line_file(&temp_dir, &content);
+
+        let result = ReadTool::new()
+            .execute(&serde_json::json!({ "path": path, "offset": 4, "limit": 3, "number": true }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.output, "     4  line4\n     5  line5\n     6  line6");
+        let meta = result.metadata.structured.unwrap();
+        assert_eq!(meta["total_lines"], 10);
+        assert_eq!(meta["lines_returned"], 3);
+        assert_eq!(meta["has_more"], true);
+        assert!(meta.get("note").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_offset_past_eof_returns_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = line_file(&temp_dir, "a\nb\nc\n");
+
+        let result = ReadTool::new()
+            .execute(&serde_json::json!({ "path": path, "offset": 10, "limit": 5 }))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(result.output.is_empty());
+        let meta = result.metadata.structured.unwrap();
+        assert_eq!(meta["total_lines"], 3);
+        assert_eq!(meta["has_more"], false);
+        assert!(meta["note"].as_str().unwrap().contains("past the end"));
+    }
+
+    #[tokio::test]
+    async fn test_read_range_without_trailing_newline() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = line_file(&temp_dir, "one\ntwo\nthree");
+
+        let result = ReadTool::new()
+            .execute(&serde_json::json!({ "path": path.clone(), "offset": 2 }))
+            .await
+            .unwrap();
+        assert_eq!(result.output, "two\nthree");
+        let meta = result.metadata.structured.unwrap();
+        assert_eq!(meta["total_lines"], 3);
+        assert_eq!(meta["has_more"], false);
+
+        // Streaming path agrees on the line count
+        let tool = ReadTool::with_context(ToolContext {
+            max_read_bytes: 8,
+            ..ToolContext::default()
+        });
+        let result = tool
+            .execute(&serde_json::json!({ "path": path, "offset": 3, "limit": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(result.output, "three");
+        assert_eq!(result.metadata.structured.unwrap()["total_lines"], 3);
+    }
 }
@@ -322,6 +322,8 @@ mod tests {
                     files_read: 0,
                     files_written: 0,
                     bytes_processed: 0,
+                    structured: None,
+                    truncated: None,
                 },
             })
         }
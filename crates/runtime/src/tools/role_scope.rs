@@ -0,0 +1,270 @@
+//! Role Scope - 按 AgentRole 限定可见工具
+//!
+//! 职责：
+//! - 声明每个工具所需的权限等级，以及每个角色被授予的权限等级
+//! - 只向 LLM 展示当前角色有权调用的工具
+//! - 调用隐藏工具时按决策引擎的口径拒绝（`Insufficient privilege`）
+//!
+//! 默认的角色权限等级与决策引擎一致；未声明的工具（如 MCP 工具）按
+//! `default_tool_privilege` 处理。
+
+use ndc_core::{AgentRole, PrivilegeLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::trait_mod::ToolError;
+
+/// 角色 → 工具可见性策略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoleToolPolicy {
+    /// 角色被授予的权限等级
+    pub role_privileges: HashMap<AgentRole, PrivilegeLevel>,
+
+    /// 工具所需的权限等级
+    pub tool_privileges: HashMap<String, PrivilegeLevel>,
+
+    /// 未声明工具所需的权限等级
+    pub default_tool_privilege: PrivilegeLevel,
+
+    /// 不论权限等级，额外放行给角色的工具
+    #[serde(default)]
+    pub role_allow: HashMap<AgentRole, HashSet<String>>,
+
+    /// 不论权限等级，对角色隐藏的工具
+    #[serde(default)]
+    pub role_deny: HashMap<AgentRole, HashSet<String>>,
+}
+
+impl Default for RoleToolPolicy {
+    fn default() -> Self {
+        let role_privileges = [
+            (AgentRole::Planner, PrivilegeLevel::Normal),
+            (AgentRole::Implementer, PrivilegeLevel::Elevated),
+            (AgentRole::Reviewer, PrivilegeLevel::Normal),
+            (AgentRole::Tester, PrivilegeLevel::Normal),
+            (AgentRole::Historian, PrivilegeLevel::Normal),
+            (AgentRole::Admin, PrivilegeLevel::Critical),
+            (AgentRole::System, PrivilegeLevel::Critical),
+            (AgentRole::Any, PrivilegeLevel::Normal),
+        ]
+        .into_iter()
+        .collect();
+
+        let read_only = [
+            "read",
+            "list",
+            "grep",
+            "glob",
+            "symbol_search",
+            "webfetch",
+            "websearch",
+            "ndc_task_list",
+            "ndc_task_verify",
+            "ndc_memory_query",
+        ];
+        // 写入/删除/执行命令、修改任务状态的工具
+        let mutating = [
+            "write",
+            "edit",
+            "apply_patch",
+            "fs",
+            "shell",
+            "git",
+            "ndc_task_create",
+            "ndc_task_update",
+        ];
+        let tool_privileges = read_only
+            .iter()
+            .map(|name| (name.to_string(), PrivilegeLevel::Normal))
+            .chain(
+                mutating
+                    .iter()
+                    .map(|name| (name.to_string(), PrivilegeLevel::Elevated)),
+            )
+            .collect();
+
+        // Reviewer / Tester 需要运行测试命令；Planner 负责拆分和更新任务
+        let task_tools =
+            HashSet::from(["ndc_task_create".to_string(), "ndc_task_update".to_string()]);
+        let role_allow = [
+            (AgentRole::Reviewer, HashSet::from(["shell".to_string()])),
+            (AgentRole::Tester, HashSet::from(["shell".to_string()])),
+            (AgentRole::Planner, task_tools),
+        ]
+        .into_iter()
+        .collect();
+
+        Self {
+            role_privileges,
+            tool_privileges,
+            default_tool_privilege: PrivilegeLevel::Elevated,
+            role_allow,
+            role_deny: HashMap::new(),
+        }
+    }
+}
+
+impl RoleToolPolicy {
+    /// 覆盖角色的权限等级
+    pub fn with_role_privilege(mut self, role: AgentRole, level: PrivilegeLevel) -> Self {
+        self.role_privileges.insert(role, level);
+        self
+    }
+
+    /// 覆盖工具所需的权限等级
+    pub fn with_tool_privilege(mut self, tool: impl Into<String>, level: PrivilegeLevel) -> Self {
+        self.tool_privileges.insert(tool.into(), level);
+        self
+    }
+
+    /// 额外放行工具给角色
+    pub fn allow_tool(mut self, role: AgentRole, tool: impl Into<String>) -> Self {
+        self.role_allow.entry(role).or_default().insert(tool.into());
+        self
+    }
+
+    /// 对角色隐藏工具
+    pub fn deny_tool(mut self, role: AgentRole, tool: impl Into<String>) -> Self {
+        self.role_deny.entry(role).or_default().insert(tool.into());
+        self
+    }
+
+    /// 角色被授予的权限等级
+    pub fn granted(&self, role: AgentRole) -> PrivilegeLevel {
+        self.role_privileges
+            .get(&role)
+            .copied()
+            .unwrap_or(PrivilegeLevel::Normal)
+    }
+
+    /// 工具所需的权限等级
+    pub fn required(&self, tool: &str) -> PrivilegeLevel {
+        self.tool_privileges
+            .get(tool)
+            .copied()
+            .unwrap_or(self.default_tool_privilege)
+    }
+
+    /// 检查角色能否调用工具，拒绝原因与决策引擎的 `Insufficient privilege` 一致
+    pub fn check(&self, role: AgentRole, tool: &str) -> Result<(), String> {
+        let listed = |lists: &HashMap<AgentRole, HashSet<String>>| {
+            lists.get(&role).is_some_and(|tools| tools.contains(tool))
+        };
+        if listed(&self.role_deny) {
+            return Err(format!("Tool '{}' is not available to {:?}", tool, role));
+        }
+        if listed(&self.role_allow) {
+            return Ok(());
+        }
+
+        let required = self.required(tool);
+        let granted = self.granted(role);
+        if required > granted {
+            return Err(format!(
+                "Insufficient privilege for tool '{}': required {:?}, granted {:?}",
+                tool, required, granted
+            ));
+        }
+        Ok(())
+    }
+
+    /// 角色能否看到工具
+    pub fn is_visible(&self, role: AgentRole, tool: &str) -> bool {
+        self.check(role, tool).is_ok()
+    }
+
+    /// 过滤出角色可见的工具名
+    pub fn visible<'a, I>(&self, role: AgentRole, tools: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut visible: Vec<String> = tools
+            .into_iter()
+            .filter(|tool| self.is_visible(role, tool))
+            .map(str::to_string)
+            .collect();
+        visible.sort();
+        visible
+    }
+
+    /// `check` 的 ToolError 版本
+    pub fn authorize(&self, role: AgentRole, tool: &str) -> Result<(), ToolError> {
+        self.check(role, tool).map_err(ToolError::PermissionDenied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::create_default_tool_manager;
+
+    #[test]
+    fn test_historian_cannot_see_write_or_delete_tools() {
+        let manager = create_default_tool_manager();
+        let historian = manager.tools_for_role(AgentRole::Historian);
+        let implementer = manager.tools_for_role(AgentRole::Implementer);
+
+        for tool in ["write", "edit", "apply_patch", "fs", "shell", "git"] {
+            assert!(!historian.contains(&tool.to_string()), "{}", tool);
+            assert!(implementer.contains(&tool.to_string()), "{}", tool);
+        }
+        for tool in ["read", "grep", "ndc_memory_query", "ndc_task_list"] {
+            assert!(historian.contains(&tool.to_string()), "{}", tool);
+        }
+        for tool in ["ndc_task_create", "ndc_task_update"] {
+            assert!(!historian.contains(&tool.to_string()), "{}", tool);
+            assert!(implementer.contains(&tool.to_string()), "{}", tool);
+            assert!(
+                manager
+                    .tools_for_role(AgentRole::Planner)
+                    .contains(&tool.to_string()),
+                "{}",
+                tool
+            );
+        }
+        assert!(
+            manager
+                .tools_for_role(AgentRole::Tester)
+                .contains(&"shell".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hidden_tool_call_is_denied() {
+        let manager = create_default_tool_manager();
+        let params = serde_json::json!({"path": "/tmp/never-written.txt", "content": "x"});
+
+        let err = manager
+            .execute_as(AgentRole::Historian, "write", &params)
+            .await
+            .unwrap_err();
+        match err {
+            ToolError::PermissionDenied(reason) => {
+                assert!(reason.contains("Insufficient privilege"), "{}", reason);
+                assert!(reason.contains("required Elevated, granted Normal"));
+            }
+            other => panic!("expected PermissionDenied, got {:?}", other),
+        }
+        assert!(!std::path::Path::new("/tmp/never-written.txt").exists());
+    }
+
+    #[test]
+    fn test_policy_overrides() {
+        let policy = RoleToolPolicy::default()
+            .allow_tool(AgentRole::Historian, "git")
+            .deny_tool(AgentRole::Implementer, "shell")
+            .with_tool_privilege("write", PrivilegeLevel::High);
+
+        assert!(policy.is_visible(AgentRole::Historian, "git"));
+        assert!(!policy.is_visible(AgentRole::Implementer, "shell"));
+        assert!(!policy.is_visible(AgentRole::Implementer, "write"));
+        assert!(policy.is_visible(AgentRole::Admin, "write"));
+        // 未声明的工具按默认等级
+        assert!(!policy.is_visible(AgentRole::Planner, "mcp_custom"));
+        assert!(policy.is_visible(AgentRole::Implementer, "mcp_custom"));
+
+        let json = serde_json::to_value(&policy).unwrap();
+        let restored: RoleToolPolicy = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, policy);
+    }
+}
@@ -10,7 +10,7 @@ use std::collections::HashMap;
 
 /// JSON Schema 类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(rename_all = "lowercase")]
 pub enum JsonSchemaType {
     /// String 类型
     String,
@@ -48,7 +48,7 @@ pub struct JsonSchemaProperty {
     pub default: Option<serde_json::Value>,
 
     /// 枚举值
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
     pub enum_: Option<Vec<serde_json::Value>>,
 
     /// 最小值
@@ -344,9 +344,11 @@ pub struct ToolSchemaBuilder {
 }
 
 impl ToolSchemaBuilder {
-    /// 创建新的构建器
+    /// 创建新的构建器（工具参数总是对象）
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            schema: JsonSchema::object(),
+        }
     }
 
     /// 设置描述
@@ -712,6 +714,11 @@ mod tests {
                 .unwrap()
                 .contains(&serde_json::json!("filePath"))
         );
+        assert_eq!(value["type"], "object");
+        assert_eq!(value["properties"]["offset"]["type"], "integer");
+
+        let parsed: JsonSchema = serde_json::from_value(value).unwrap();
+        assert!(matches!(parsed.type_, Some(JsonSchemaType::Object)));
     }
 
     #[test]
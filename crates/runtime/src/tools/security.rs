@@ -199,6 +199,24 @@ fn resolve_absolute(path: &Path, working_dir: Option<&Path>) -> PathBuf {
     canonicalize_lossy(&base.join(path))
 }
 
+/// Resolve `path` (relative paths against `root`) and require it to stay inside `root`.
+///
+/// Unlike `enforce_path_boundary` this is a hard check without env overrides, for
+/// callers that pin a project root per request (e.g. daemon sessions).
+pub fn resolve_within_root(path: &Path, root: &Path) -> Result<PathBuf, ToolError> {
+    let root = canonicalize_lossy(root);
+    let resolved = resolve_absolute(path, Some(&root));
+    if resolved.starts_with(&root) {
+        Ok(resolved)
+    } else {
+        Err(ToolError::PermissionDenied(format!(
+            "{} is outside project root {}",
+            path.display(),
+            root.display()
+        )))
+    }
+}
+
 /// Enforce project boundary for file-system like paths.
 ///
 /// Returns `PermissionDenied` when target path is outside project root and policy is not `allow`.
@@ -537,6 +555,32 @@ mod tests {
         assert_eq!(normalize_path(Path::new("/a/b/c")), PathBuf::from("/a/b/c"));
     }
 
+    #[test]
+    fn test_resolve_within_root_rejects_escapes() {
+        let root = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        let canonical = std::fs::canonicalize(root.path()).unwrap();
+
+        assert_eq!(
+            resolve_within_root(Path::new("src/lib.rs"), root.path()).unwrap(),
+            canonical.join("src/lib.rs")
+        );
+        assert_eq!(
+            resolve_within_root(Path::new("src/../Cargo.toml"), root.path()).unwrap(),
+            canonical.join("Cargo.toml")
+        );
+        for escape in ["../outside.txt", "src/../../outside.txt", "/etc/passwd"] {
+            assert!(
+                matches!(
+                    resolve_within_root(Path::new(escape), root.path()),
+                    Err(ToolError::PermissionDenied(_))
+                ),
+                "{}",
+                escape
+            );
+        }
+    }
+
     #[test]
     fn test_dotdot_traversal_blocked_by_boundary_check() {
         let _guard = test_env_lock();
@@ -6,13 +6,22 @@
 //! - Execute linters
 //!
 //! Security measures:
-//! - Whitelist commands
+//! - Configurable allow/deny policy (`ShellPolicy`)
 //! - Timeout limits
 //! - Environment variable filtering
+//!
+//! Commands run in their own process group (Unix). When the command times
+//! out or its future is dropped, the whole group is killed, so grandchildren
+//! (`sh -c "a; b"`) are not orphaned; output produced before the kill is kept.
 
+use super::bash_parsing::{BashParser, EmbeddedKind};
 use super::{Tool, ToolContext, ToolError, ToolResult, enforce_shell_command};
 use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
+use tokio::task::JoinHandle;
 use tracing::debug;
 
 /// Denied commands — always blocked regardless of user approval.
@@ -43,10 +52,237 @@ const DENIED_COMMANDS: &[&str] = &[
     "systemctl",
 ];
 
+/// How `ShellPolicy` treats commands that match no rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShellPolicyMode {
+    /// Only commands matching an `allow` rule may run
+    AllowlistOnly,
+    /// Everything may run except commands matching a `deny` rule
+    #[default]
+    DenylistOnly,
+}
+
+/// Command policy applied on top of the hard-coded denylist
+///
+/// Each rule is a binary name optionally followed by arguments, e.g. `"git"`
+/// or `"rm -rf"`. The binary is compared by file name, so `/bin/rm` and `rm`
+/// match the same rule. A rule with arguments only matches when every listed
+/// argument is present; short-flag clusters match in any order and split
+/// form (`-rf` matches `-fr` and `-r -f`). `deny` rules win over `allow`
+/// rules in both modes.
+#[derive(Debug, Clone, Default)]
+pub struct ShellPolicy {
+    pub mode: ShellPolicyMode,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl ShellPolicy {
+    pub fn allowlist(allow: Vec<String>) -> Self {
+        Self {
+            mode: ShellPolicyMode::AllowlistOnly,
+            allow,
+            deny: Vec::new(),
+        }
+    }
+
+    pub fn denylist(deny: Vec<String>) -> Self {
+        Self {
+            mode: ShellPolicyMode::DenylistOnly,
+            allow: Vec::new(),
+            deny,
+        }
+    }
+
+    pub fn with_deny(mut self, rule: impl Into<String>) -> Self {
+        self.deny.push(rule.into());
+        self
+    }
+
+    /// Check one resolved invocation (binary + args)
+    pub fn check(&self, binary: &str, args: &[String]) -> Result<(), ToolError> {
+        let name = binary_name(binary);
+
+        if let Some(rule) = self.deny.iter().find(|r| rule_matches(r, name, args)) {
+            return Err(ToolError::PermissionDenied(format!(
+                "Command '{}' is denied by shell policy rule '{}'",
+                name, rule
+            )));
+        }
+
+        if self.mode == ShellPolicyMode::AllowlistOnly
+            && !self.allow.iter().any(|r| rule_matches(r, name, args))
+        {
+            return Err(ToolError::PermissionDenied(format!(
+                "Command '{}' is not in the shell allowlist",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// File name of a command path (`/usr/bin/rm` -> `rm`)
+fn binary_name(command: &str) -> &str {
+    Path::new(command)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(command)
+}
+
+fn rule_matches(rule: &str, name: &str, args: &[String]) -> bool {
+    let mut parts = rule.split_whitespace();
+    let Some(rule_bin) = parts.next() else {
+        return false;
+    };
+    binary_name(rule_bin) == name && parts.all(|arg| arg_present(arg, args))
+}
+
+fn arg_present(rule_arg: &str, args: &[String]) -> bool {
+    if args.iter().any(|a| a == rule_arg) {
+        return true;
+    }
+    // Short-flag cluster: every letter must appear in some short-flag arg
+    match rule_arg.strip_prefix('-') {
+        Some(flags) if !flags.is_empty() && !flags.starts_with('-') => flags.chars().all(|f| {
+            args.iter().any(|a| {
+                a.strip_prefix('-')
+                    .is_some_and(|s| !s.starts_with('-') && s.contains(f))
+            })
+        }),
+        _ => false,
+    }
+}
+
+/// Split a command into the (binary, args) invocations it would run
+///
+/// Shell strings are split on `|`, `&`, `;` and newlines; leading
+/// `VAR=value` assignments are skipped. The bodies of `$(...)` and backtick
+/// substitutions, nested ones included, are found by `BashParser` and split
+/// the same way, so each substituted command is checked on its own.
+/// Quoting is not interpreted when splitting: a quoted separator yields an
+/// extra invocation.
+fn resolve_invocations(
+    command: &str,
+    args: &[String],
+    via_shell: bool,
+) -> Vec<(String, Vec<String>)> {
+    if !via_shell {
+        return vec![(command.to_string(), args.to_vec())];
+    }
+
+    let substitutions = BashParser::new()
+        .parse(command)
+        .map(|parsed| parsed.embedded_commands)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|embedded| embedded.kind == EmbeddedKind::Substitution)
+        .map(|embedded| embedded.command);
+
+    std::iter::once(command.to_string())
+        .chain(substitutions)
+        .flat_map(|source| split_invocations(&source))
+        .collect()
+}
+
+/// Split one shell string on command separators, without looking into substitutions
+fn split_invocations(command: &str) -> Vec<(String, Vec<String>)> {
+    command
+        .split(['|', '&', ';', '\n'])
+        .filter_map(|segment| {
+            let mut words = segment
+                .split_whitespace()
+                .map(|w| w.trim_matches(|c| "$()`\"'".contains(c)))
+                .filter(|w| !w.is_empty())
+                .skip_while(|w| w.contains('=') && !w.starts_with('-'));
+            let binary = words.next()?.to_string();
+            Some((binary, words.map(str::to_string).collect()))
+        })
+        .collect()
+}
+
+/// How long to wait for the pipes to close after a kill
+const KILL_GRACE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Kills a command's process group when dropped, unless disarmed
+///
+/// Covers both the timeout path and cancellation (the tool future dropped
+/// mid-run, e.g. by `ToolManager`'s timeout).
+struct ProcessGroupGuard {
+    pid: Option<u32>,
+}
+
+impl ProcessGroupGuard {
+    fn new(pid: Option<u32>) -> Self {
+        Self { pid }
+    }
+
+    /// Kill the group now
+    fn kill(&mut self) {
+        if let Some(pid) = self.pid.take() {
+            kill_process_group(pid);
+        }
+    }
+
+    /// The command finished on its own; leave the group alone
+    fn disarm(&mut self) {
+        self.pid = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // The child leads its own group (`process_group(0)`), so pgid == pid
+    // SAFETY: killpg only sends a signal; an invalid pgid returns ESRCH
+    unsafe {
+        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {
+    // No process groups; `kill_on_drop` still kills the direct child
+}
+
+/// Bytes read so far from a child pipe, plus the reader task
+type Capture = (Arc<Mutex<Vec<u8>>>, JoinHandle<()>);
+
+/// Read `pipe` into a shared buffer, so output is kept even if the command
+/// is killed before it finishes
+fn capture<R: AsyncRead + Unpin + Send + 'static>(pipe: Option<R>) -> Capture {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let sink = buffer.clone();
+    let task = tokio::spawn(async move {
+        let Some(mut pipe) = pipe else { return };
+        let mut chunk = [0u8; 8192];
+        while let Ok(read) = pipe.read(&mut chunk).await {
+            if read == 0 {
+                break;
+            }
+            sink.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .extend_from_slice(&chunk[..read]);
+        }
+    });
+    (buffer, task)
+}
+
+fn captured(capture: &Capture) -> String {
+    String::from_utf8_lossy(&capture.0.lock().unwrap_or_else(|e| e.into_inner())).into_owned()
+}
+
 /// Shell tool
 #[derive(Debug)]
 pub struct ShellTool {
     context: ToolContext,
+    policy: ShellPolicy,
 }
 
 impl Default for ShellTool {
@@ -59,11 +295,23 @@ impl ShellTool {
     pub fn new() -> Self {
         Self {
             context: ToolContext::default(),
+            policy: ShellPolicy::default(),
         }
     }
 
+    /// Run commands with `context` (default working dir, environment)
+    pub fn with_context(mut self, context: ToolContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: ShellPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     fn is_denied(&self, command: &str) -> bool {
-        DENIED_COMMANDS.contains(&command)
+        DENIED_COMMANDS.contains(&binary_name(command))
     }
 }
 
@@ -83,15 +331,6 @@ impl Tool for ShellTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::InvalidArgument("Missing command".to_string()))?;
 
-        // Hard-deny commands that should never be executed
-        if self.is_denied(command) {
-            tracing::warn!("Blocked denied command: {}", command);
-            return Err(ToolError::PermissionDenied(format!(
-                "Command '{}' is permanently denied for safety",
-                command
-            )));
-        }
-
         // 获取参数
         let args: Vec<String> = params
             .get("args")
@@ -109,6 +348,26 @@ impl Tool for ShellTool {
             .map(std::path::PathBuf::from)
             .unwrap_or_else(|| self.context.working_dir.clone());
 
+        // When command contains spaces/shell metacharacters and no separate args
+        // are provided, the caller passed a full shell command string (e.g. "echo hello"
+        // or "cargo test --workspace"). Execute via `sh -c` so the shell interprets it,
+        // rather than treating the whole string as an executable name.
+        let needs_shell = args.is_empty()
+            && command
+                .contains(|c: char| c.is_whitespace() || "|&;<>()$`\"'\\!{}*?[]#~".contains(c));
+
+        for (binary, binary_args) in resolve_invocations(command, &args, needs_shell) {
+            // Hard-deny commands that should never be executed
+            if self.is_denied(&binary) {
+                tracing::warn!("Blocked denied command: {}", binary);
+                return Err(ToolError::PermissionDenied(format!(
+                    "Command '{}' is permanently denied for safety",
+                    binary_name(&binary)
+                )));
+            }
+            self.policy.check(&binary, &binary_args)?;
+        }
+
         enforce_shell_command(command, args.as_slice(), Some(&working_dir))?;
 
         // 检查超时
@@ -119,14 +378,6 @@ impl Tool for ShellTool {
 
         let start = std::time::Instant::now();
 
-        // When command contains spaces/shell metacharacters and no separate args
-        // are provided, the caller passed a full shell command string (e.g. "echo hello"
-        // or "cargo test --workspace"). Execute via `sh -c` so the shell interprets it,
-        // rather than treating the whole string as an executable name.
-        let needs_shell = args.is_empty()
-            && command
-                .contains(|c: char| c.is_whitespace() || "|&;<>()$`\"'\\!{}*?[]#~".contains(c));
-
         let mut cmd = if needs_shell {
             let mut c = Command::new("sh");
             c.arg("-c").arg(command);
@@ -139,6 +390,14 @@ impl Tool for ShellTool {
 
         // 设置工作目录
         cmd.current_dir(&working_dir);
+        // 超时（包括 ToolManager 的超时）丢弃 future 时终止子进程
+        cmd.kill_on_drop(true);
+        cmd.stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        // 独立进程组，超时/取消时整组终止（含孙进程）
+        #[cfg(unix)]
+        cmd.process_group(0);
 
         // 过滤环境变量 — 白名单 + context 追加，黑名单拦截危险变量
         const DANGEROUS_ENV_VARS: &[&str] = &[
@@ -157,29 +416,58 @@ impl Tool for ShellTool {
             if DANGEROUS_ENV_VARS.contains(&key.as_str()) {
                 continue;
             }
-            if filtered_env.contains(key.as_str()) || self.context.env_vars.contains_key(&key) {
+            if filtered_env.contains(key.as_str()) {
                 cmd.env(&key, value);
             }
         }
+        // context 中的变量（如会话级覆盖）以 context 的值为准
+        for (key, value) in &self.context.env_vars {
+            if !DANGEROUS_ENV_VARS.contains(&key.as_str()) {
+                cmd.env(key, value);
+            }
+        }
 
-        let output = tokio::time::timeout(std::time::Duration::from_secs(timeout), cmd.output())
-            .await
-            .map_err(|_| {
-                ToolError::Timeout(format!(
-                    "Command '{}' timed out after {}s",
-                    command, timeout
-                ))
-            })?
+        let mut child = cmd
+            .spawn()
             .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let output_text = if stderr.is_empty() {
-            stdout.into_owned()
-        } else {
-            stderr.into_owned()
+        let mut guard = ProcessGroupGuard::new(child.id());
+        let mut stdout = capture(child.stdout.take());
+        let mut stderr = capture(child.stderr.take());
+
+        let run = async {
+            let status = child.wait().await;
+            // Wait for EOF so output written just before exit is not lost
+            let _ = (&mut stdout.1).await;
+            let _ = (&mut stderr.1).await;
+            status
         };
-        let success = output.status.success();
+        let status = match tokio::time::timeout(std::time::Duration::from_secs(timeout), run).await
+        {
+            Ok(status) => {
+                guard.disarm();
+                status.map_err(|e| ToolError::ExecutionFailed(e.to_string()))?
+            }
+            Err(_) => {
+                guard.kill();
+                let _ = child.kill().await;
+                let _ = tokio::time::timeout(KILL_GRACE, async {
+                    let _ = (&mut stdout.1).await;
+                    let _ = (&mut stderr.1).await;
+                })
+                .await;
+                let partial = format!("{}{}", captured(&stdout), captured(&stderr));
+                let mut message = format!("Command '{}' timed out after {}s", command, timeout);
+                if !partial.trim().is_empty() {
+                    message.push_str(&format!("; output before kill:\n{}", partial.trim_end()));
+                }
+                return Err(ToolError::Timeout(message));
+            }
+        };
+
+        let stdout = captured(&stdout);
+        let stderr = captured(&stderr);
+        let output_text = if stderr.is_empty() { stdout } else { stderr };
+        let success = status.success();
 
         let duration = start.elapsed().as_millis() as u64;
         let bytes = output_text.len();
@@ -195,6 +483,8 @@ impl Tool for ShellTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: bytes as u64,
+                structured: Some(serde_json::json!({ "exit_code": status.code() })),
+                truncated: None,
             },
         })
     }
@@ -231,7 +521,10 @@ mod tests {
     use super::*;
 
     fn shell_with_context(ctx: ToolContext) -> ShellTool {
-        ShellTool { context: ctx }
+        ShellTool {
+            context: ctx,
+            policy: ShellPolicy::default(),
+        }
     }
 
     #[tokio::test]
@@ -269,6 +562,72 @@ mod tests {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_manager_timeout_kills_subprocess() {
+        let running = || {
+            std::fs::read_dir("/proc").unwrap().flatten().any(|entry| {
+                std::fs::read(entry.path().join("cmdline"))
+                    .is_ok_and(|cmdline| cmdline == b"sleep\x0031.4159\x00")
+            })
+        };
+        let mut manager = super::super::ToolManager::new()
+            .with_tool_timeout("shell", std::time::Duration::from_millis(300));
+        manager.register("shell", ShellTool::new());
+
+        let params = serde_json::json!({ "command": "sleep", "args": ["31.4159"] });
+        let result = manager.execute("shell", &params).await;
+        assert!(matches!(result, Err(ToolError::Timeout(_))), "{:?}", result);
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(!running(), "sleep should be killed, not orphaned");
+    }
+
+    #[cfg(target_os = "linux")]
+    fn sleep_running(seconds: &str) -> bool {
+        let cmdline = format!("sleep\0{}\0", seconds);
+        std::fs::read_dir("/proc").unwrap().flatten().any(|entry| {
+            std::fs::read(entry.path().join("cmdline")).is_ok_and(|c| c == cmdline.as_bytes())
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cancelled_command_kills_process_group() {
+        // `sleep` is a grandchild here: sh does not exec the first of two commands
+        let params = serde_json::json!({ "command": "sleep 30.2718; echo done", "timeout": 60 });
+        let tool = ShellTool::new();
+        let cancelled =
+            tokio::time::timeout(std::time::Duration::from_millis(300), tool.execute(&params))
+                .await;
+        assert!(cancelled.is_err(), "command should still be running");
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(
+            !sleep_running("30.2718"),
+            "sleep should be killed with its group"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_keeps_partial_output() {
+        let params = serde_json::json!({ "command": "echo started; sleep 30.5772", "timeout": 1 });
+        match ShellTool::new().execute(&params).await {
+            Err(ToolError::Timeout(message)) => {
+                assert!(message.contains("timed out after 1s"), "{}", message);
+                assert!(
+                    message.ends_with("output before kill:\nstarted"),
+                    "{}",
+                    message
+                );
+            }
+            other => panic!("Expected Timeout, got: {:?}", other),
+        }
+        #[cfg(target_os = "linux")]
+        assert!(!sleep_running("30.5772"));
+    }
+
     #[tokio::test]
     async fn test_shell_dangerous_env_vars_filtered() {
         // LD_PRELOAD should never reach child process even if in context.env_vars
@@ -372,4 +731,118 @@ mod tests {
         assert!(r.success);
         assert!(r.output.contains("world"));
     }
+
+    fn run(command: &str, args: &[&str]) -> serde_json::Value {
+        serde_json::json!({ "command": command, "args": args, "timeout": 5 })
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_permits_only_listed_binaries() {
+        let tool = ShellTool::new().with_policy(ShellPolicy::allowlist(vec!["echo".to_string()]));
+
+        let ok = tool.execute(&run("echo", &["hi"])).await.unwrap();
+        assert!(ok.success);
+
+        // Absolute path resolves to the same binary
+        let ok = tool.execute(&run("/bin/echo", &["hi"])).await.unwrap();
+        assert!(ok.success);
+
+        match tool.execute(&run("ls", &[])).await {
+            Err(ToolError::PermissionDenied(msg)) => {
+                assert!(msg.contains("'ls'"), "unexpected message: {}", msg)
+            }
+            other => panic!("Expected PermissionDenied, got: {:?}", other),
+        }
+
+        // Every command in a shell pipeline must be allowed
+        let piped = serde_json::json!({ "command": "echo hi | cat", "timeout": 5 });
+        assert!(matches!(
+            tool.execute(&piped).await,
+            Err(ToolError::PermissionDenied(msg)) if msg.contains("'cat'")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_denylist_blocks_resolved_binary() {
+        let tool = ShellTool::new().with_policy(ShellPolicy::denylist(vec!["ls".to_string()]));
+
+        assert!(matches!(
+            tool.execute(&run("/bin/ls", &[])).await,
+            Err(ToolError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            tool.execute(&serde_json::json!({ "command": "FOO=1 ls -la", "timeout": 5 }))
+                .await,
+            Err(ToolError::PermissionDenied(_))
+        ));
+        assert!(tool.execute(&run("echo", &["hi"])).await.unwrap().success);
+    }
+
+    #[test]
+    fn test_argument_level_rules() {
+        let policy = ShellPolicy::denylist(vec!["rm -rf".to_string()]);
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert!(policy.check("rm", &args(&["-rf", "build"])).is_err());
+        assert!(policy.check("/bin/rm", &args(&["-fr", "build"])).is_err());
+        assert!(policy.check("rm", &args(&["-r", "-f", "build"])).is_err());
+        assert!(policy.check("rm", &args(&["build.log"])).is_ok());
+        assert!(policy.check("rm", &args(&["-r", "build"])).is_ok());
+
+        let policy = ShellPolicy::allowlist(vec!["git status".to_string(), "cargo".to_string()])
+            .with_deny("cargo publish");
+        assert!(policy.check("git", &args(&["status"])).is_ok());
+        assert!(policy.check("git", &args(&["push"])).is_err());
+        assert!(policy.check("cargo", &args(&["test"])).is_ok());
+        assert!(policy.check("cargo", &args(&["publish"])).is_err());
+    }
+
+    #[test]
+    fn test_resolve_invocations_splits_shell_strings() {
+        let resolved = resolve_invocations("A=1 /usr/bin/rm -rf x; echo ok | cat", &[], true);
+        let binaries: Vec<&str> = resolved.iter().map(|(b, _)| b.as_str()).collect();
+        assert_eq!(binaries, vec!["/usr/bin/rm", "echo", "cat"]);
+        assert_eq!(resolved[0].1, vec!["-rf".to_string(), "x".to_string()]);
+    }
+
+    #[test]
+    fn test_resolve_invocations_includes_substituted_commands() {
+        let resolved = resolve_invocations("echo $(rm -rf ~) `id -u $(whoami)`", &[], true);
+        let binaries: Vec<&str> = resolved.iter().map(|(b, _)| b.as_str()).collect();
+        assert_eq!(binaries, vec!["echo", "rm", "id", "whoami"]);
+        assert_eq!(resolved[1].1, vec!["-rf".to_string(), "~".to_string()]);
+
+        // Single-quoted text is literal and runs nothing
+        let resolved = resolve_invocations("echo '$(rm -rf ~)'", &[], true);
+        assert_eq!(resolved.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_checks_commands_inside_substitutions() {
+        let tool = ShellTool::new().with_policy(ShellPolicy::allowlist(vec!["echo".to_string()]));
+
+        for command in [
+            "echo $(rm -rf ~)",
+            "echo `rm -rf ~`",
+            "echo $(echo $(rm -rf ~))",
+        ] {
+            let params = serde_json::json!({ "command": command, "timeout": 5 });
+            assert!(
+                matches!(
+                    tool.execute(&params).await,
+                    Err(ToolError::PermissionDenied(msg)) if msg.contains("'rm'")
+                ),
+                "{command} should be denied"
+            );
+        }
+
+        let nested_echo = serde_json::json!({ "command": "echo $(echo hi)", "timeout": 5 });
+        assert!(
+            tool.execute(&nested_echo)
+                .await
+                .unwrap()
+                .output
+                .contains("hi")
+        );
+    }
 }
@@ -0,0 +1,654 @@
+//! Symbol Search Tool - project-wide symbol lookup backed by LSP
+//!
+//! Exposes `workspace/symbol` and `textDocument/references` to the agent so
+//! definitions and usages can be found without grepping. One LSP session is
+//! started lazily per language and reused for later calls.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use super::lsp::{LspClient, LspSession, language_for_path, path_to_uri, uri_to_path};
+use super::schema::ToolSchemaBuilder;
+use super::{Tool, ToolError, ToolMetadata, ToolResult, resolve_within_root};
+
+/// Default per-request timeout for the language server
+const DEFAULT_SYMBOL_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Default number of results returned to the agent
+const DEFAULT_MAX_RESULTS: usize = 50;
+
+/// A symbol or reference location (1-indexed line/column)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    pub name: String,
+    pub kind: String,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Source of workspace symbols and references (LSP session, mock)
+#[async_trait]
+pub trait SymbolProvider: Send + Sync + std::fmt::Debug {
+    async fn workspace_symbols(&self, query: &str) -> Result<Vec<SymbolLocation>, String>;
+
+    /// References to the symbol at `line`/`column` (1-indexed) in `path`
+    async fn references(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+    ) -> Result<Vec<SymbolLocation>, String>;
+}
+
+#[async_trait]
+impl SymbolProvider for LspSession {
+    async fn workspace_symbols(&self, query: &str) -> Result<Vec<SymbolLocation>, String> {
+        let result = self
+            .request("workspace/symbol", json!({ "query": query }))
+            .await?;
+        Ok(result
+            .as_array()
+            .map(|items| items.iter().filter_map(parse_symbol).collect())
+            .unwrap_or_default())
+    }
+
+    async fn references(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+    ) -> Result<Vec<SymbolLocation>, String> {
+        self.ensure_open(path).await?;
+        let result = self
+            .request(
+                "textDocument/references",
+                json!({
+                    "textDocument": { "uri": path_to_uri(path)? },
+                    "position": {
+                        "line": line.saturating_sub(1),
+                        "character": column.saturating_sub(1),
+                    },
+                    "context": { "includeDeclaration": true },
+                }),
+            )
+            .await?;
+        let name = symbol_name_at(path, line, column).unwrap_or_default();
+        Ok(result
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|location| parse_location(location, &name, "reference"))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+/// `SymbolInformation` / `WorkspaceSymbol` → `SymbolLocation`
+fn parse_symbol(value: &Value) -> Option<SymbolLocation> {
+    let name = value.get("name")?.as_str()?;
+    let kind = value
+        .get("kind")
+        .and_then(Value::as_u64)
+        .map_or("unknown", symbol_kind_name);
+    parse_location(value.get("location")?, name, kind)
+}
+
+fn parse_location(value: &Value, name: &str, kind: &str) -> Option<SymbolLocation> {
+    let uri = value.get("uri")?.as_str()?;
+    let start = value.get("range").and_then(|r| r.get("start"));
+    let position = |key: &str| {
+        start
+            .and_then(|s| s.get(key))
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize
+            + 1
+    };
+    Some(SymbolLocation {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        path: uri_to_path(uri),
+        line: position("line"),
+        column: position("character"),
+    })
+}
+
+/// LSP `SymbolKind` number → name
+fn symbol_kind_name(kind: u64) -> &'static str {
+    match kind {
+        1 => "file",
+        2 => "module",
+        3 => "namespace",
+        4 => "package",
+        5 => "class",
+        6 => "method",
+        7 => "property",
+        8 => "field",
+        9 => "constructor",
+        10 => "enum",
+        11 => "interface",
+        12 => "function",
+        13 => "variable",
+        14 => "constant",
+        15 => "string",
+        16 => "number",
+        17 => "boolean",
+        18 => "array",
+        19 => "object",
+        20 => "key",
+        21 => "null",
+        22 => "enum_member",
+        23 => "struct",
+        24 => "event",
+        25 => "operator",
+        26 => "type_parameter",
+        _ => "unknown",
+    }
+}
+
+/// Identifier under the cursor, used to label reference results
+fn symbol_name_at(path: &Path, line: usize, column: usize) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let text: Vec<char> = content.lines().nth(line.checked_sub(1)?)?.chars().collect();
+    let is_ident = |c: &char| c.is_alphanumeric() || *c == '_';
+    let cursor = column.checked_sub(1)?.min(text.len().checked_sub(1)?);
+    let start = (0..=cursor)
+        .rev()
+        .take_while(|&i| is_ident(&text[i]))
+        .last()?;
+    let end = (cursor..text.len())
+        .take_while(|&i| is_ident(&text[i]))
+        .last()?;
+    Some(text[start..=end].iter().collect())
+}
+
+/// Lower is better: exact, case-insensitive exact, prefix, substring, other
+fn match_rank(name: &str, query: &str) -> u8 {
+    let (lower_name, lower_query) = (name.to_lowercase(), query.to_lowercase());
+    if name == query {
+        0
+    } else if lower_name == lower_query {
+        1
+    } else if name.starts_with(query) {
+        2
+    } else if lower_name.starts_with(&lower_query) {
+        3
+    } else if lower_name.contains(&lower_query) {
+        4
+    } else {
+        5
+    }
+}
+
+/// Symbol search tool
+#[derive(Debug)]
+pub struct SymbolSearchTool {
+    root: PathBuf,
+    /// Language → server command used to start a session
+    servers: HashMap<String, Vec<String>>,
+    /// Started (or injected) providers, reused across calls
+    sessions: Mutex<HashMap<String, Arc<dyn SymbolProvider>>>,
+    timeout: Duration,
+    max_results: usize,
+}
+
+impl Default for SymbolSearchTool {
+    fn default() -> Self {
+        Self::new(std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+    }
+}
+
+impl SymbolSearchTool {
+    pub fn new(root: PathBuf) -> Self {
+        let servers = [
+            ("rust", vec!["rust-analyzer"]),
+            ("typescript", vec!["typescript-language-server", "--stdio"]),
+            ("python", vec!["pyright-langserver", "--stdio"]),
+            ("go", vec!["gopls"]),
+        ]
+        .into_iter()
+        .map(|(language, command)| {
+            (
+                language.to_string(),
+                command.into_iter().map(String::from).collect(),
+            )
+        })
+        .collect();
+        Self {
+            root,
+            servers,
+            sessions: Mutex::new(HashMap::new()),
+            timeout: DEFAULT_SYMBOL_TIMEOUT,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+
+    /// Use a custom server command for a language
+    pub fn with_server(mut self, language: &str, command: Vec<String>) -> Self {
+        self.servers.insert(language.to_string(), command);
+        self
+    }
+
+    /// Use an already-running provider for a language
+    pub fn with_provider(mut self, language: &str, provider: Arc<dyn SymbolProvider>) -> Self {
+        self.sessions
+            .get_mut()
+            .insert(language.to_string(), provider);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results.max(1);
+        self
+    }
+
+    /// Language of the project root, from its manifest
+    fn project_language(&self) -> Option<&'static str> {
+        [
+            ("Cargo.toml", "rust"),
+            ("package.json", "typescript"),
+            ("pyproject.toml", "python"),
+            ("setup.py", "python"),
+            ("go.mod", "go"),
+        ]
+        .into_iter()
+        .find(|(manifest, _)| self.root.join(manifest).exists())
+        .map(|(_, language)| language)
+    }
+
+    /// Reuse the language's session, starting it on first use
+    async fn provider(&self, language: &str) -> Result<Arc<dyn SymbolProvider>, String> {
+        let mut sessions = self.sessions.lock().await;
+        if let Some(provider) = sessions.get(language) {
+            return Ok(provider.clone());
+        }
+        let command = self
+            .servers
+            .get(language)
+            .ok_or_else(|| format!("No LSP server configured for '{}'", language))?;
+        debug!(language, ?command, "Starting LSP session for symbol search");
+        let session = tokio::time::timeout(
+            self.timeout,
+            LspClient::new(command.clone(), self.root.clone()).start_session(),
+        )
+        .await
+        .map_err(|_| {
+            format!(
+                "LSP server for '{}' did not start within {}s",
+                language,
+                self.timeout.as_secs()
+            )
+        })??;
+        let provider: Arc<dyn SymbolProvider> = Arc::new(session);
+        sessions.insert(language.to_string(), provider.clone());
+        Ok(provider)
+    }
+
+    /// Drop a session that failed or hung so the next call starts a fresh server
+    async fn evict(&self, language: &str, provider: &Arc<dyn SymbolProvider>) {
+        let mut sessions = self.sessions.lock().await;
+        if sessions
+            .get(language)
+            .is_some_and(|current| Arc::ptr_eq(current, provider))
+        {
+            debug!(language, "Evicting LSP session after failure");
+            sessions.remove(language);
+        }
+    }
+
+    fn failure(error: String, started: std::time::Instant) -> ToolResult {
+        ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+            metadata: ToolMetadata {
+                execution_time_ms: started.elapsed().as_millis() as u64,
+                ..ToolMetadata::default()
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for SymbolSearchTool {
+    fn name(&self) -> &str {
+        "symbol_search"
+    }
+
+    fn description(&self) -> &str {
+        "Find symbol definitions across the project (workspace_symbol) or all references to the symbol at a file position (references), using the language server."
+    }
+
+    async fn execute(&self, params: &serde_json::Value) -> Result<ToolResult, ToolError> {
+        let started = std::time::Instant::now();
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("workspace_symbol");
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .map(|p| resolve_within_root(Path::new(p), &self.root))
+            .transpose()?;
+        let language = params
+            .get("language")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| path.as_deref().map(|p| language_for_path(p).to_string()))
+            .or_else(|| self.project_language().map(String::from))
+            .ok_or_else(|| {
+                ToolError::InvalidArgument(
+                    "Cannot infer language; pass 'language' or 'path'".to_string(),
+                )
+            })?;
+
+        let provider = match self.provider(&language).await {
+            Ok(provider) => provider,
+            Err(e) => return Ok(Self::failure(e, started)),
+        };
+
+        let (query, lookup) = match action {
+            "workspace_symbol" => {
+                let query = params
+                    .get("query")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| {
+                        ToolError::InvalidArgument("Missing 'query' parameter".to_string())
+                    })?
+                    .to_string();
+                let lookup =
+                    tokio::time::timeout(self.timeout, provider.workspace_symbols(&query)).await;
+                (Some(query), lookup)
+            }
+            "references" => {
+                let path = path.ok_or_else(|| {
+                    ToolError::InvalidArgument("Missing 'path' parameter".to_string())
+                })?;
+                let position = |key: &str| {
+                    params.get(key).and_then(|v| v.as_u64()).ok_or_else(|| {
+                        ToolError::InvalidArgument(format!("Missing '{}' parameter", key))
+                    })
+                };
+                let (line, column) = (position("line")? as usize, position("column")? as usize);
+                let lookup =
+                    tokio::time::timeout(self.timeout, provider.references(&path, line, column))
+                        .await;
+                (None, lookup)
+            }
+            other => {
+                return Err(ToolError::InvalidArgument(format!(
+                    "Unknown action '{}' (expected workspace_symbol or references)",
+                    other
+                )));
+            }
+        };
+
+        let mut results = match lookup {
+            Ok(Ok(results)) => results,
+            Ok(Err(e)) => {
+                self.evict(&language, &provider).await;
+                return Ok(Self::failure(e, started));
+            }
+            Err(_) => {
+                self.evict(&language, &provider).await;
+                return Ok(Self::failure(
+                    format!(
+                        "LSP server for '{}' timed out after {}s",
+                        language,
+                        self.timeout.as_secs_f32()
+                    ),
+                    started,
+                ));
+            }
+        };
+
+        match &query {
+            Some(query) => results.sort_by(|a, b| {
+                (match_rank(&a.name, query), a.name.len(), &a.path, a.line).cmp(&(
+                    match_rank(&b.name, query),
+                    b.name.len(),
+                    &b.path,
+                    b.line,
+                ))
+            }),
+            None => results
+                .sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column))),
+        }
+        let total = results.len();
+        results.truncate(self.max_results);
+
+        let output = if results.is_empty() {
+            "No symbols found".to_string()
+        } else {
+            let mut lines: Vec<String> = results
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{} {} {}:{}:{}",
+                        r.kind,
+                        r.name,
+                        r.path.display(),
+                        r.line,
+                        r.column
+                    )
+                })
+                .collect();
+            if total > results.len() {
+                lines.push(format!("... {} more not shown", total - results.len()));
+            }
+            lines.join("\n")
+        };
+
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+            metadata: ToolMetadata {
+                execution_time_ms: started.elapsed().as_millis() as u64,
+                structured: Some(json!({
+                    "results": results,
+                    "total": total,
+                    "truncated": total > results.len(),
+                })),
+                ..ToolMetadata::default()
+            },
+        })
+    }
+
+    fn schema(&self) -> serde_json::Value {
+        ToolSchemaBuilder::new()
+            .description("Find symbols or references via the language server")
+            .param_string("action", "workspace_symbol (default) or references")
+            .param_string("query", "Symbol name to search for (workspace_symbol)")
+            .param_string("path", "File containing the symbol (references)")
+            .param_integer("line", "1-indexed line of the symbol (references)")
+            .param_integer("column", "1-indexed column of the symbol (references)")
+            .param_string(
+                "language",
+                "rust, typescript, python or go (inferred from path or project by default)",
+            )
+            .build()
+            .to_value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct MockLsp {
+        symbols: Vec<SymbolLocation>,
+        references: Vec<SymbolLocation>,
+        delay: Option<Duration>,
+    }
+
+    fn location(name: &str, kind: &str, path: &str, line: usize) -> SymbolLocation {
+        SymbolLocation {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            path: PathBuf::from(path),
+            line,
+            column: 5,
+        }
+    }
+
+    #[async_trait]
+    impl SymbolProvider for MockLsp {
+        async fn workspace_symbols(&self, _query: &str) -> Result<Vec<SymbolLocation>, String> {
+            if let Some(delay) = self.delay {
+                tokio::time::sleep(delay).await;
+            }
+            Ok(self.symbols.clone())
+        }
+
+        async fn references(
+            &self,
+            _path: &Path,
+            _line: usize,
+            _column: usize,
+        ) -> Result<Vec<SymbolLocation>, String> {
+            Ok(self.references.clone())
+        }
+    }
+
+    fn tool_with(mock: MockLsp) -> SymbolSearchTool {
+        SymbolSearchTool::new(PathBuf::from("/repo")).with_provider("rust", Arc::new(mock))
+    }
+
+    #[tokio::test]
+    async fn test_workspace_symbols_are_ranked_and_capped() {
+        let tool = tool_with(MockLsp {
+            symbols: vec![
+                location("execute_tool_calls", "method", "/repo/src/runner.rs", 40),
+                location("ToolExecutor", "interface", "/repo/src/orchestrator.rs", 12),
+                location("BashExecutor", "struct", "/repo/src/bash.rs", 3),
+                location("executor", "module", "/repo/src/lib.rs", 1),
+            ],
+            ..MockLsp::default()
+        })
+        .with_max_results(3);
+
+        let result = tool
+            .execute(&json!({"query": "Executor", "language": "rust"}))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        let structured = result.metadata.structured.unwrap();
+        let names: Vec<&str> = structured["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["executor", "BashExecutor", "ToolExecutor"]);
+        assert_eq!(structured["total"], 4);
+        assert_eq!(structured["truncated"], true);
+        assert_eq!(structured["results"][0]["kind"], "module");
+        assert_eq!(structured["results"][0]["line"], 1);
+        assert!(result.output.contains("1 more not shown"));
+    }
+
+    #[tokio::test]
+    async fn test_references_sorted_by_location() {
+        let tool = tool_with(MockLsp {
+            references: vec![
+                location("run", "reference", "/repo/src/b.rs", 9),
+                location("run", "reference", "/repo/src/a.rs", 20),
+                location("run", "reference", "/repo/src/a.rs", 3),
+            ],
+            ..MockLsp::default()
+        });
+
+        let result = tool
+            .execute(&json!({
+                "action": "references",
+                "path": "src/a.rs",
+                "line": 3,
+                "column": 5,
+            }))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(
+            result.output,
+            "reference run /repo/src/a.rs:3:5\nreference run /repo/src/a.rs:20:5\nreference run /repo/src/b.rs:9:5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_server_times_out_gracefully() {
+        let tool = tool_with(MockLsp {
+            delay: Some(Duration::from_secs(5)),
+            ..MockLsp::default()
+        })
+        .with_timeout(Duration::from_millis(20));
+
+        let result = tool
+            .execute(&json!({"query": "main", "language": "rust"}))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("timed out"));
+        assert!(tool.sessions.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_path_outside_root_is_rejected() {
+        let tool = tool_with(MockLsp::default());
+
+        for path in ["/etc/passwd", "../outside.rs", "src/../../outside.rs"] {
+            let err = tool
+                .execute(&json!({
+                    "action": "references",
+                    "path": path,
+                    "line": 1,
+                    "column": 1,
+                }))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ToolError::PermissionDenied(_)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_parse_symbol_information() {
+        let symbol = parse_symbol(&json!({
+            "name": "AgentOrchestrator",
+            "kind": 23,
+            "location": {
+                "uri": "file:///repo/src/orchestrator.rs",
+                "range": {"start": {"line": 9, "character": 11}, "end": {"line": 9, "character": 28}},
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            symbol,
+            SymbolLocation {
+                name: "AgentOrchestrator".to_string(),
+                kind: "struct".to_string(),
+                path: PathBuf::from("/repo/src/orchestrator.rs"),
+                line: 10,
+                column: 12,
+            }
+        );
+    }
+}
@@ -8,10 +8,15 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
+use super::role_scope::RoleToolPolicy;
+use super::schema::{JsonSchema, SchemaValidator};
+
 /// 工具执行结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
@@ -27,6 +32,38 @@ pub struct ToolMetadata {
     pub files_read: u32,
     pub files_written: u32,
     pub bytes_processed: u64,
+    /// 工具特定的结构化结果（如 git diff 摘要）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<serde_json::Value>,
+    /// 结果列表被截断时的数量（如 grep/glob 的 `max_results`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<ResultTruncation>,
+}
+
+/// grep/glob 默认最多返回的结果数
+pub const DEFAULT_MAX_RESULTS: usize = 200;
+
+/// 结果截断信息：只返回了 `total` 中的前 `shown` 条
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultTruncation {
+    pub shown: usize,
+    pub total: usize,
+}
+
+impl ResultTruncation {
+    /// `Some` when `total` results were capped to `shown`
+    pub fn of(shown: usize, total: usize) -> Option<Self> {
+        (shown < total).then_some(Self { shown, total })
+    }
+
+    /// Hint appended to text output
+    pub fn notice(&self) -> String {
+        format!(
+            "... {} more matches not shown ({} total); narrow the query to see them",
+            self.total - self.shown,
+            self.total
+        )
+    }
 }
 
 /// 工具错误
@@ -81,6 +118,9 @@ pub trait Tool: Send + Sync {
     }
 }
 
+/// 默认单次读取文件的最大字节数（5MB）
+pub const DEFAULT_MAX_READ_BYTES: u64 = 5 * 1024 * 1024;
+
 /// 工具执行上下文
 #[derive(Debug, Clone)]
 pub struct ToolContext {
@@ -96,8 +136,21 @@ pub struct ToolContext {
     /// 是否为只读模式
     pub read_only: bool,
 
-    /// 超时时间（秒）
+    /// 超时时间（秒），`ToolManager` 对每次工具调用强制执行；0 表示不限时
     pub timeout_seconds: u64,
+
+    /// 单次读取文件的最大字节数（可通过 `max_bytes` 参数按调用覆盖）
+    pub max_read_bytes: u64,
+}
+
+impl ToolContext {
+    /// 解析本次调用的读取上限：优先使用参数中的 `max_bytes`
+    pub fn effective_max_read_bytes(&self, params: &ToolParams) -> u64 {
+        params
+            .get("max_bytes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(self.max_read_bytes)
+    }
 }
 
 impl Default for ToolContext {
@@ -108,6 +161,7 @@ impl Default for ToolContext {
             allowed_operations: vec!["read".to_string(), "write".to_string()],
             read_only: false,
             timeout_seconds: 300,
+            max_read_bytes: DEFAULT_MAX_READ_BYTES,
         }
     }
 }
@@ -116,8 +170,13 @@ impl Default for ToolContext {
 #[derive(Default)]
 pub struct ToolManager {
     registry: std::collections::HashMap<String, Arc<dyn Tool>>,
-    #[allow(dead_code)]
     context: ToolContext,
+    /// 按工具覆盖的超时时间
+    tool_timeouts: HashMap<String, Duration>,
+    /// 是否也校验 schema 为 `{}` 的工具（默认跳过，视为接受任意参数）
+    validate_empty_schemas: bool,
+    /// 角色可见工具策略
+    role_policy: RoleToolPolicy,
 }
 
 impl std::fmt::Debug for ToolManager {
@@ -137,6 +196,89 @@ impl ToolManager {
         self.registry.insert(name.into(), Arc::new(tool));
     }
 
+    /// 设置是否校验 schema 为 `{}` 的工具参数
+    pub fn with_empty_schema_validation(mut self, enabled: bool) -> Self {
+        self.validate_empty_schemas = enabled;
+        self
+    }
+
+    /// 设置执行上下文（默认超时取自 `timeout_seconds`）
+    pub fn with_context(mut self, context: ToolContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// 为单个工具覆盖超时时间
+    pub fn with_tool_timeout(mut self, tool: impl Into<String>, timeout: Duration) -> Self {
+        self.tool_timeouts.insert(tool.into(), timeout);
+        self
+    }
+
+    /// 工具调用的超时时间；`None` 表示不限时
+    pub fn timeout_for(&self, tool_name: &str) -> Option<Duration> {
+        self.tool_timeouts.get(tool_name).copied().or_else(|| {
+            (self.context.timeout_seconds > 0)
+                .then(|| Duration::from_secs(self.context.timeout_seconds))
+        })
+    }
+
+    /// 设置角色可见工具策略
+    pub fn with_role_policy(mut self, policy: RoleToolPolicy) -> Self {
+        self.role_policy = policy;
+        self
+    }
+
+    /// 角色可见工具策略
+    pub fn role_policy(&self) -> &RoleToolPolicy {
+        &self.role_policy
+    }
+
+    /// 角色可见的工具名（已排序）
+    pub fn tools_for_role(&self, role: ndc_core::AgentRole) -> Vec<String> {
+        self.role_policy
+            .visible(role, self.registry.keys().map(String::as_str))
+    }
+
+    /// 以角色身份执行工具；对该角色隐藏的工具返回 `PermissionDenied`
+    pub async fn execute_as(
+        &self,
+        role: ndc_core::AgentRole,
+        tool_name: &str,
+        params: &ToolParams,
+    ) -> Result<ToolResult, ToolError> {
+        if self.registry.contains_key(tool_name) {
+            self.role_policy.authorize(role, tool_name)?;
+        }
+        self.execute(tool_name, params).await
+    }
+
+    /// 按工具的 JSON Schema 校验参数，返回缺失/类型错误字段列表
+    pub fn validate_params(&self, tool: &dyn Tool, params: &ToolParams) -> Result<(), ToolError> {
+        let schema_value = tool.schema();
+        if !self.validate_empty_schemas && schema_value.as_object().is_some_and(|o| o.is_empty()) {
+            return Ok(());
+        }
+
+        let schema: JsonSchema = match serde_json::from_value(schema_value) {
+            Ok(schema) => schema,
+            Err(e) => {
+                tracing::debug!(tool = tool.name(), "schema not validatable: {}", e);
+                return Ok(());
+            }
+        };
+
+        let result = SchemaValidator::validate(params, &schema);
+        if result.valid {
+            Ok(())
+        } else {
+            Err(ToolError::InvalidArgument(format!(
+                "{}: {}",
+                tool.name(),
+                result.errors.join("; ")
+            )))
+        }
+    }
+
     pub async fn execute(
         &self,
         tool_name: &str,
@@ -147,8 +289,27 @@ impl ToolManager {
             .get(tool_name)
             .ok_or_else(|| ToolError::NotFound(tool_name.to_string()))?;
 
+        self.validate_params(tool.as_ref(), params)?;
+
         let start = std::time::Instant::now();
-        let result = tool.execute(params).await?;
+        // 超时后丢弃工具 future；子进程以 kill_on_drop 启动，随之被终止
+        let result = match self.timeout_for(tool_name) {
+            Some(limit) => tokio::time::timeout(limit, tool.execute(params))
+                .await
+                .map_err(|_| {
+                    tracing::warn!(
+                        tool = tool_name,
+                        timeout_ms = limit.as_millis() as u64,
+                        "tool timed out"
+                    );
+                    ToolError::Timeout(format!(
+                        "tool '{}' timed out after {:.1}s",
+                        tool_name,
+                        start.elapsed().as_secs_f64()
+                    ))
+                })??,
+            None => tool.execute(params).await?,
+        };
         let duration = start.elapsed().as_millis() as u64;
 
         tracing::debug!(
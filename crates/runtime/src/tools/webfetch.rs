@@ -2,12 +2,16 @@
 //!
 //! Responsibilities:
 //! - Fetch HTTP/HTTPS content
-//! - Handle redirects
+//! - Handle redirects (capped, re-validated per hop, caller headers kept same-origin)
 //! - Support headers and methods
 //! - Parse response status
+//! - Honor robots.txt for the target host
+//! - Render bodies by Content-Type (HTML as text, JSON pretty-printed)
 
 use super::{Tool, ToolError, ToolResult};
+use std::collections::HashMap;
 use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 use tracing::debug;
 
@@ -75,15 +79,317 @@ fn is_private_ip(ip: &IpAddr) -> bool {
     }
 }
 
+/// WebFetch configuration
+#[derive(Debug, Clone)]
+pub struct WebFetchConfig {
+    /// Refuse URLs disallowed by the host's robots.txt
+    pub respect_robots: bool,
+    /// Request timeout
+    pub timeout_secs: u64,
+    /// Maximum response body size (bytes)
+    pub max_bytes: usize,
+    /// Maximum number of redirects to follow
+    pub max_redirects: usize,
+    /// User-agent sent with requests and matched against robots.txt groups
+    pub user_agent: String,
+}
+
+impl Default for WebFetchConfig {
+    fn default() -> Self {
+        Self {
+            respect_robots: true,
+            timeout_secs: 30,
+            max_bytes: 1024 * 1024, // 1MB
+            max_redirects: 5,
+            user_agent: "ndc".to_string(),
+        }
+    }
+}
+
+/// Parsed robots.txt rules that apply to our user-agent
+#[derive(Debug, Clone, Default)]
+struct RobotsRules {
+    /// (allow, path pattern)
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Parse robots.txt, keeping the group for `user_agent` or else `*`
+    fn parse(content: &str, user_agent: &str) -> Self {
+        let agent = user_agent.to_ascii_lowercase();
+        let mut specific: Vec<(bool, String)> = Vec::new();
+        let mut wildcard: Vec<(bool, String)> = Vec::new();
+        let mut matched_specific = false;
+
+        // Current group's agents; a new group starts at the first user-agent line after rules.
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        group_agents.clear();
+                        in_rules = false;
+                    }
+                    group_agents.push(value.to_ascii_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // An empty Disallow allows everything.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (key == "allow", value.to_string());
+                    if group_agents
+                        .iter()
+                        .any(|a| a != "*" && agent.contains(a.as_str()))
+                    {
+                        matched_specific = true;
+                        specific.push(rule);
+                    } else if group_agents.iter().any(|a| a == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if matched_specific { specific } else { wildcard },
+        }
+    }
+
+    /// Longest matching rule wins; Allow wins ties
+    fn is_allowed(&self, path: &str) -> bool {
+        let mut best: Option<(usize, bool)> = None;
+        for (allow, pattern) in &self.rules {
+            if robots_pattern_matches(pattern, path) {
+                let len = pattern.len();
+                match best {
+                    Some((best_len, best_allow))
+                        if best_len > len || (best_len == len && best_allow) => {}
+                    _ => best = Some((len, *allow)),
+                }
+            }
+        }
+        best.map(|(_, allow)| allow).unwrap_or(true)
+    }
+}
+
+/// Match a robots.txt path pattern supporting `*` and a trailing `$`
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// How a response body is rendered; chosen from Content-Type unless forced via `as`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderMode {
+    /// Body as received
+    Raw,
+    /// HTML reduced to plain text
+    Text,
+    /// HTML reduced to text keeping headings, links and list items
+    Markdown,
+}
+
+impl RenderMode {
+    fn parse(value: &str) -> Result<Self, ToolError> {
+        match value.to_ascii_lowercase().as_str() {
+            "raw" => Ok(Self::Raw),
+            "text" => Ok(Self::Text),
+            "markdown" => Ok(Self::Markdown),
+            other => Err(ToolError::InvalidArgument(format!(
+                "Invalid 'as' value '{}'; expected raw, text or markdown",
+                other
+            ))),
+        }
+    }
+}
+
+/// Render `body` for the model
+///
+/// Without a forced mode: HTML becomes markdown-ish text, JSON is
+/// pretty-printed, other textual types pass through and binary types are
+/// summarized by type and size. A forced `text`/`markdown` treats the body as
+/// HTML whatever its declared type.
+fn render_body(content_type: Option<&str>, body: &[u8], mode: Option<RenderMode>) -> String {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let text = || String::from_utf8_lossy(body);
+
+    match mode {
+        Some(RenderMode::Raw) => text().into_owned(),
+        Some(RenderMode::Text) => html_to_text(&text(), false),
+        Some(RenderMode::Markdown) => html_to_text(&text(), true),
+        None if mime == "text/html" || mime == "application/xhtml+xml" => {
+            html_to_text(&text(), true)
+        }
+        None if mime == "application/json" || mime.ends_with("+json") => {
+            // Truncated or invalid JSON is shown as received
+            serde_json::from_slice::<serde_json::Value>(body)
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                .unwrap_or_else(|| text().into_owned())
+        }
+        None if is_textual(&mime) || (mime.is_empty() && std::str::from_utf8(body).is_ok()) => {
+            text().into_owned()
+        }
+        None => format!(
+            "[{} content, {} bytes not shown; pass \"as\": \"raw\" to include it]",
+            if mime.is_empty() { "unknown" } else { &mime },
+            body.len()
+        ),
+    }
+}
+
+fn is_textual(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || mime.ends_with("+xml")
+        || matches!(
+            mime,
+            "application/xml" | "application/javascript" | "application/x-yaml"
+        )
+}
+
+/// Patterns used by `html_to_text`, compiled once
+struct HtmlPatterns {
+    hidden: regex::Regex,
+    heading: regex::Regex,
+    link: regex::Regex,
+    list_item: regex::Regex,
+    block: regex::Regex,
+    tag: regex::Regex,
+    entity: regex::Regex,
+}
+
+fn html_patterns() -> &'static HtmlPatterns {
+    static PATTERNS: OnceLock<HtmlPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let re = |pattern: &str| regex::Regex::new(pattern).expect("valid HTML pattern");
+        HtmlPatterns {
+            hidden: re(
+                r"(?is)<!--.*?-->|<script\b.*?</script\s*>|<style\b.*?</style\s*>|<noscript\b.*?</noscript\s*>|<template\b.*?</template\s*>",
+            ),
+            heading: re(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>"),
+            link: re(r#"(?is)<a\b[^>]*?href\s*=\s*["']([^"']*)["'][^>]*>(.*?)</a\s*>"#),
+            list_item: re(r"(?i)<li\b[^>]*>"),
+            block: re(
+                r"(?i)</?(?:p|div|br|hr|tr|ul|ol|table|section|article|header|footer|nav|main|blockquote|pre|h[1-6])\b[^>]*>",
+            ),
+            tag: re(r"(?s)<[^>]*>"),
+            entity: re(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);"),
+        }
+    })
+}
+
+/// Reduce HTML to readable text; `markdown` keeps headings and links as markdown
+fn html_to_text(html: &str, markdown: bool) -> String {
+    let patterns = html_patterns();
+    let html = patterns.hidden.replace_all(html, "");
+    let html = patterns
+        .heading
+        .replace_all(&html, |caps: &regex::Captures| {
+            let level: usize = caps[1].parse().unwrap_or(1);
+            if markdown {
+                format!("\n\n{} {}\n\n", "#".repeat(level), &caps[2])
+            } else {
+                format!("\n\n{}\n\n", &caps[2])
+            }
+        });
+    let html = patterns.link.replace_all(&html, |caps: &regex::Captures| {
+        if markdown && !caps[1].is_empty() && !caps[1].starts_with('#') {
+            format!("[{}]({})", &caps[2], &caps[1])
+        } else {
+            caps[2].to_string()
+        }
+    });
+    let html = patterns.list_item.replace_all(&html, "\n- ");
+    let html = patterns.block.replace_all(&html, "\n");
+    let html = patterns.tag.replace_all(&html, "");
+    let text = patterns
+        .entity
+        .replace_all(&html, |caps: &regex::Captures| {
+            decode_entity(&caps[1]).unwrap_or_else(|| caps[0].to_string())
+        });
+
+    // Collapse runs of whitespace and blank lines
+    let mut out = String::new();
+    let mut blank = false;
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+fn decode_entity(entity: &str) -> Option<String> {
+    let ch = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        _ => {
+            let code = match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => entity.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+    Some(ch.to_string())
+}
+
 /// WebFetch tool
 #[derive(Debug)]
 pub struct WebFetchTool {
-    /// Request timeout
-    timeout_seconds: u64,
-    /// Maximum content size (bytes)
-    max_content_size: usize,
-    /// Follow redirects
-    _follow_redirects: bool,
+    config: WebFetchConfig,
+    /// robots.txt rules cached per origin
+    robots_cache: Mutex<HashMap<String, RobotsRules>>,
+    /// Skip the private-address check (tests against a local server only)
+    allow_private_hosts: bool,
 }
 
 impl Default for WebFetchTool {
@@ -94,10 +400,96 @@ impl Default for WebFetchTool {
 
 impl WebFetchTool {
     pub fn new() -> Self {
+        Self::with_config(WebFetchConfig::default())
+    }
+
+    pub fn with_config(config: WebFetchConfig) -> Self {
         Self {
-            timeout_seconds: 30,
-            max_content_size: 1024 * 1024, // 1MB
-            _follow_redirects: true,
+            config,
+            robots_cache: Mutex::new(HashMap::new()),
+            allow_private_hosts: false,
+        }
+    }
+
+    fn validate_url(&self, url: &str) -> Result<url::Url, ToolError> {
+        if !self.allow_private_hosts {
+            validate_url_safety(url)?;
+        }
+        url::Url::parse(url).map_err(|e| ToolError::InvalidArgument(format!("Invalid URL: {e}")))
+    }
+
+    fn client(&self) -> Result<reqwest::Client, ToolError> {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.config.timeout_secs))
+            .user_agent(self.config.user_agent.clone())
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+    }
+
+    /// Refuse the URL when the host's robots.txt disallows its path
+    async fn check_robots(
+        &self,
+        client: &reqwest::Client,
+        url: &url::Url,
+    ) -> Result<(), ToolError> {
+        if !self.config.respect_robots {
+            return Ok(());
+        }
+
+        let origin = url.origin().ascii_serialization();
+        let cached = self
+            .robots_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&origin)
+            .cloned();
+
+        let rules = match cached {
+            Some(rules) => rules,
+            None => {
+                let rules = self.fetch_robots(client, &origin).await;
+                self.robots_cache
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .insert(origin, rules.clone());
+                rules
+            }
+        };
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if rules.is_allowed(&path) {
+            Ok(())
+        } else {
+            Err(ToolError::PermissionDenied(format!(
+                "robots.txt disallows fetching {}",
+                url
+            )))
+        }
+    }
+
+    /// Missing or unreachable robots.txt allows everything
+    async fn fetch_robots(&self, client: &reqwest::Client, origin: &str) -> RobotsRules {
+        let robots_url = format!("{}/robots.txt", origin);
+        match client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(body) => RobotsRules::parse(&body, &self.config.user_agent),
+                Err(e) => {
+                    debug!("Failed to read {}: {}", robots_url, e);
+                    RobotsRules::default()
+                }
+            },
+            Ok(response) => {
+                debug!("{} returned {}", robots_url, response.status());
+                RobotsRules::default()
+            }
+            Err(e) => {
+                debug!("Failed to fetch {}: {}", robots_url, e);
+                RobotsRules::default()
+            }
         }
     }
 
@@ -108,54 +500,102 @@ impl WebFetchTool {
         method: &str,
         headers: Option<&serde_json::Value>,
         body: Option<&str>,
+        render: Option<RenderMode>,
     ) -> Result<String, ToolError> {
-        validate_url_safety(url)?;
+        let client = self.client()?;
+        let mut current = self.validate_url(url)?;
+        let origin = current.origin();
+        let mut method = method.to_string();
+        let mut body = body.map(str::to_string);
+        let mut redirects = 0;
+
+        let mut response = loop {
+            self.check_robots(&client, &current).await?;
+
+            let mut request = match method.as_str() {
+                "POST" => client.post(current.clone()),
+                "PUT" => client.put(current.clone()),
+                "DELETE" => client.delete(current.clone()),
+                "HEAD" => client.head(current.clone()),
+                _ => client.get(current.clone()),
+            };
+
+            // Caller headers may carry credentials, so other origins never see them
+            if let Some(h) = headers.filter(|_| current.origin() == origin) {
+                for (key, value) in h.as_object().unwrap_or(&serde_json::Map::new()) {
+                    if let Some(v) = value.as_str() {
+                        request = request.header(key, v);
+                    }
+                }
+            }
 
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(self.timeout_seconds))
-            .redirect(reqwest::redirect::Policy::none())
-            .build()
-            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
-
-        let mut request = match method {
-            "POST" => client.post(url),
-            "PUT" => client.put(url),
-            "DELETE" => client.delete(url),
-            "HEAD" => client.head(url),
-            _ => client.get(url),
-        };
+            // Add body for POST/PUT
+            if let Some(b) = &body {
+                request = request.body(b.clone());
+            }
 
-        // Add headers
-        if let Some(h) = headers {
-            for (key, value) in h.as_object().unwrap_or(&serde_json::Map::new()) {
-                if let Some(v) = value.as_str() {
-                    request = request.header(key, v);
-                }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("Request failed: {}", e)))?;
+
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok());
+            let Some(location) = location.filter(|_| status.is_redirection()) else {
+                break response;
+            };
+
+            redirects += 1;
+            if redirects > self.config.max_redirects {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "Too many redirects (max {})",
+                    self.config.max_redirects
+                )));
             }
-        }
 
-        // Add body for POST/PUT
-        if let Some(b) = body {
-            request = request.body(b.to_string());
-        }
+            let next = current
+                .join(location)
+                .map_err(|e| ToolError::ExecutionFailed(format!("Invalid redirect: {}", e)))?;
+            debug!("WebFetch redirect {} -> {}", current, next);
+            current = self.validate_url(next.as_str())?;
 
-        let response = request
-            .send()
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Request failed: {}", e)))?;
+            // 303 (and legacy 301/302 for non-GET) switch to a bodyless GET.
+            if matches!(status.as_u16(), 301..=303) && method != "HEAD" {
+                method = "GET".to_string();
+                body = None;
+            }
+        };
 
         let status = response.status();
-        let text = response
-            .text()
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let mut content = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response
+            .chunk()
             .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("Read response failed: {}", e)))?;
+            .map_err(|e| ToolError::ExecutionFailed(format!("Read response failed: {}", e)))?
+        {
+            let remaining = self.config.max_bytes - content.len();
+            if chunk.len() > remaining {
+                content.extend_from_slice(&chunk[..remaining]);
+                truncated = true;
+                break;
+            }
+            content.extend_from_slice(&chunk);
+        }
+        let text = render_body(content_type.as_deref(), &content, render);
 
-        // Check content size
-        if text.len() > self.max_content_size {
+        if truncated {
             return Ok(format!(
-                "[Content truncated - {} bytes]\n\n{}...",
-                text.len(),
-                &text[..std::cmp::min(1024, text.len())]
+                "Status: {}\n[Content truncated at {} bytes]\n\n{}",
+                status, self.config.max_bytes, text
             ));
         }
 
@@ -188,11 +628,17 @@ impl Tool for WebFetchTool {
 
         let body = params.get("body").and_then(|v| v.as_str());
 
+        let render = params
+            .get("as")
+            .and_then(|v| v.as_str())
+            .map(RenderMode::parse)
+            .transpose()?;
+
         debug!("WebFetch: {} {}", method, url);
 
         let start = std::time::Instant::now();
 
-        let output = self.fetch(url, method, headers, body).await?;
+        let output = self.fetch(url, method, headers, body, render).await?;
         let duration = start.elapsed().as_millis() as u64;
         let bytes = output.len();
 
@@ -205,6 +651,8 @@ impl Tool for WebFetchTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: bytes as u64,
+                structured: None,
+                truncated: None,
             },
         })
     }
@@ -229,6 +677,11 @@ impl Tool for WebFetchTool {
                 "body": {
                     "type": "string",
                     "description": "Request body for POST/PUT"
+                },
+                "as": {
+                    "type": "string",
+                    "enum": ["raw", "text", "markdown"],
+                    "description": "Force how the body is rendered (default: by Content-Type; HTML as markdown, JSON pretty-printed)"
                 }
             },
             "required": ["url"]
@@ -291,4 +744,258 @@ mod tests {
         assert!(!is_private_ip(&"8.8.8.8".parse().unwrap()));
         assert!(!is_private_ip(&"1.1.1.1".parse().unwrap()));
     }
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Minimal HTTP server: robots.txt disallows `/private`, `/loop` redirects to itself
+    async fn mock_server(robots_hits: Arc<AtomicUsize>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                let robots_hits = robots_hits.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                    let (status, extra, body) = match path.as_str() {
+                        "/robots.txt" => {
+                            robots_hits.fetch_add(1, Ordering::SeqCst);
+                            (
+                                "200 OK",
+                                String::new(),
+                                "User-agent: *\nDisallow: /private\n".to_string(),
+                            )
+                        }
+                        "/loop" => (
+                            "302 Found",
+                            "Location: /loop\r\n".to_string(),
+                            String::new(),
+                        ),
+                        "/moved" => (
+                            "301 Moved Permanently",
+                            "Location: /public\r\n".to_string(),
+                            String::new(),
+                        ),
+                        "/big" => ("200 OK", String::new(), "x".repeat(4096)),
+                        "/headers" => ("200 OK", String::new(), request.to_string()),
+                        p if p.starts_with("/redirect?to=") => (
+                            "302 Found",
+                            format!("Location: {}\r\n", &p["/redirect?to=".len()..]),
+                            String::new(),
+                        ),
+                        "/page" => (
+                            "200 OK",
+                            "Content-Type: text/html; charset=utf-8\r\n".to_string(),
+                            PAGE.to_string(),
+                        ),
+                        "/api" => (
+                            "200 OK",
+                            "Content-Type: application/json\r\n".to_string(),
+                            r#"{"name":"ndc","tags":["a","b"]}"#.to_string(),
+                        ),
+                        "/logo.png" => (
+                            "200 OK",
+                            "Content-Type: image/png\r\n".to_string(),
+                            "PNG\0\0data".to_string(),
+                        ),
+                        _ => ("200 OK", String::new(), format!("hello from {}", path)),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        extra,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    const PAGE: &str = "<html><head><title>Docs</title><style>body { color: red; }</style>\
+        <script>var secret = 'tracking';</script></head><body>\
+        <h1>Getting   started</h1><p>Read the <a href=\"https://example.com/guide\">guide</a> &amp; enjoy.</p>\
+        <ul><li>fast</li><li>safe</li></ul><!-- hidden --></body></html>";
+
+    fn local_tool(config: WebFetchConfig) -> WebFetchTool {
+        let mut tool = WebFetchTool::with_config(config);
+        tool.allow_private_hosts = true;
+        tool
+    }
+
+    #[tokio::test]
+    async fn test_fetch_respects_robots_and_caches_it() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base = mock_server(hits.clone()).await;
+        let tool = local_tool(WebFetchConfig::default());
+
+        let ok = tool
+            .execute(&serde_json::json!({ "url": format!("{}/public", base) }))
+            .await
+            .unwrap();
+        assert!(ok.output.contains("hello from /public"));
+
+        let denied = tool
+            .execute(&serde_json::json!({ "url": format!("{}/private/data", base) }))
+            .await;
+        assert!(matches!(denied, Err(ToolError::PermissionDenied(_))));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Disabled robots handling fetches the path anyway.
+        let tool = local_tool(WebFetchConfig {
+            respect_robots: false,
+            ..Default::default()
+        });
+        let result = tool
+            .execute(&serde_json::json!({ "url": format!("{}/private/data", base) }))
+            .await
+            .unwrap();
+        assert!(result.output.contains("hello from /private/data"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_caps_redirects_and_size() {
+        let base = mock_server(Arc::new(AtomicUsize::new(0))).await;
+        let tool = local_tool(WebFetchConfig {
+            max_redirects: 3,
+            max_bytes: 100,
+            ..Default::default()
+        });
+
+        let looped = tool
+            .execute(&serde_json::json!({ "url": format!("{}/loop", base) }))
+            .await;
+        match looped {
+            Err(ToolError::ExecutionFailed(msg)) => assert!(msg.contains("Too many redirects")),
+            other => panic!("expected redirect error, got {:?}", other),
+        }
+
+        let moved = tool
+            .execute(&serde_json::json!({ "url": format!("{}/moved", base) }))
+            .await
+            .unwrap();
+        assert!(moved.output.contains("hello from /public"));
+
+        let big = tool
+            .execute(&serde_json::json!({ "url": format!("{}/big", base) }))
+            .await
+            .unwrap();
+        assert!(big.output.contains("truncated at 100 bytes"));
+        assert!(!big.output.contains(&"x".repeat(101)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_drops_caller_headers_on_cross_origin_redirect() {
+        let base = mock_server(Arc::new(AtomicUsize::new(0))).await;
+        let other = mock_server(Arc::new(AtomicUsize::new(0))).await;
+        let tool = local_tool(WebFetchConfig::default());
+        let params = |target: &str| {
+            serde_json::json!({
+                "url": format!("{}/redirect?to={}/headers", base, target),
+                "headers": { "Authorization": "Bearer s3cret", "X-Api-Key": "k3y" }
+            })
+        };
+
+        let same_origin = tool.execute(&params(&base)).await.unwrap();
+        assert!(same_origin.output.contains("Bearer s3cret"));
+        assert!(same_origin.output.contains("k3y"));
+
+        let cross_origin = tool.execute(&params(&other)).await.unwrap();
+        assert!(cross_origin.output.contains("GET /headers"));
+        assert!(!cross_origin.output.contains("s3cret"));
+        assert!(!cross_origin.output.contains("k3y"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_renders_html_as_readable_text() {
+        let base = mock_server(Arc::new(AtomicUsize::new(0))).await;
+        let tool = local_tool(WebFetchConfig::default());
+        let url = format!("{}/page", base);
+
+        let page = tool
+            .execute(&serde_json::json!({ "url": url }))
+            .await
+            .unwrap();
+        assert!(!page.output.contains("secret"), "{}", page.output);
+        assert!(!page.output.contains("color: red"), "{}", page.output);
+        assert!(!page.output.contains('<'), "{}", page.output);
+        assert!(page.output.contains("# Getting started"), "{}", page.output);
+        assert!(
+            page.output
+                .contains("Read the [guide](https://example.com/guide) & enjoy."),
+            "{}",
+            page.output
+        );
+        assert!(page.output.contains("- fast\n- safe"), "{}", page.output);
+
+        let text = tool
+            .execute(&serde_json::json!({ "url": url, "as": "text" }))
+            .await
+            .unwrap();
+        assert!(text.output.contains("Read the guide & enjoy."));
+        assert!(!text.output.contains("secret"));
+
+        let raw = tool
+            .execute(&serde_json::json!({ "url": url, "as": "raw" }))
+            .await
+            .unwrap();
+        assert!(raw.output.contains("<script>var secret"));
+
+        let invalid = tool
+            .execute(&serde_json::json!({ "url": url, "as": "pdf" }))
+            .await;
+        assert!(matches!(invalid, Err(ToolError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pretty_prints_json_and_summarizes_binary() {
+        let base = mock_server(Arc::new(AtomicUsize::new(0))).await;
+        let tool = local_tool(WebFetchConfig::default());
+
+        let api = tool
+            .execute(&serde_json::json!({ "url": format!("{}/api", base) }))
+            .await
+            .unwrap();
+        assert!(
+            api.output
+                .ends_with("{\n  \"name\": \"ndc\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ]\n}"),
+            "{}",
+            api.output
+        );
+
+        let image = tool
+            .execute(&serde_json::json!({ "url": format!("{}/logo.png", base) }))
+            .await
+            .unwrap();
+        assert!(
+            image
+                .output
+                .contains("[image/png content, 9 bytes not shown"),
+            "{}",
+            image.output
+        );
+    }
+
+    #[test]
+    fn test_robots_rules_parsing() {
+        let robots = "User-agent: googlebot\nDisallow: /\n\nUser-agent: *\nDisallow: /private\nAllow: /private/public\nDisallow: /*.pdf$\n";
+        let rules = RobotsRules::parse(robots, "ndc");
+        assert!(rules.is_allowed("/"));
+        assert!(!rules.is_allowed("/private/x"));
+        assert!(rules.is_allowed("/private/public/x"));
+        assert!(!rules.is_allowed("/docs/a.pdf"));
+        assert!(rules.is_allowed("/docs/a.pdf.html"));
+
+        let rules = RobotsRules::parse(robots, "Googlebot/2.1");
+        assert!(!rules.is_allowed("/anything"));
+    }
 }
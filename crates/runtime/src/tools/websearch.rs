@@ -136,6 +136,8 @@ impl Tool for WebSearchTool {
                 files_read: 0,
                 files_written: 0,
                 bytes_processed: bytes as u64,
+                structured: None,
+                truncated: None,
             },
         })
     }
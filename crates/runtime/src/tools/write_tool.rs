@@ -4,8 +4,10 @@
 //! Design参考 OpenCode write.ts
 
 use async_trait::async_trait;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use tracing::debug;
 
 use super::schema::ToolSchemaBuilder;
@@ -13,10 +15,60 @@ use super::{Tool, ToolError, ToolMetadata, ToolResult, enforce_path_boundary};
 
 /// Atomic file write: writes to a temporary file then renames to the target path.
 /// Prevents data corruption if the process crashes during write.
+///
+/// The temp file lives in the target's directory and is fsynced before the
+/// rename; the directory is fsynced afterwards where supported. An existing
+/// target keeps its permissions, and symlinks are written through.
 pub async fn atomic_write(path: &Path, content: &str) -> std::io::Result<()> {
-    let tmp = path.with_extension("tmp");
-    fs::write(&tmp, content).await?;
-    fs::rename(&tmp, path).await?;
+    let path = path.to_path_buf();
+    let content = content.as_bytes().to_vec();
+    tokio::task::spawn_blocking(move || atomic_write_blocking(&path, &content))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+fn atomic_write_blocking(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = dir.join(format!(
+        ".{}.{}.tmp",
+        file_name,
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let written = (|| {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp)?;
+        file.write_all(content)?;
+        if let Ok(existing) = std::fs::metadata(&path) {
+            file.set_permissions(existing.permissions())?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&tmp, &path)
+    })();
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written?;
+    sync_dir(dir)
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
     Ok(())
 }
 
@@ -82,8 +134,26 @@ impl Tool for WriteTool {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
         let mode = if append { "appended to" } else { "written to" };
-
-        if append && path.exists() {
+        let atomic = params
+            .get("atomic")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        if !atomic {
+            // Direct write, for targets that can't be replaced by rename
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .append(append)
+                .truncate(!append)
+                .open(&path)
+                .await
+                .map_err(ToolError::Io)?;
+            file.write_all(content.as_bytes())
+                .await
+                .map_err(ToolError::Io)?;
+            file.flush().await.map_err(ToolError::Io)?;
+        } else if append && path.exists() {
             // Append to existing file (atomic: read + concat + write-tmp + rename)
             let existing = fs::read_to_string(&path).await.map_err(ToolError::Io)?;
             let new_content = existing + content;
@@ -109,6 +179,8 @@ impl Tool for WriteTool {
                 files_read: 0,
                 files_written: 1,
                 bytes_processed: bytes_written as u64,
+                structured: None,
+                truncated: None,
             },
         })
     }
@@ -122,6 +194,10 @@ impl Tool for WriteTool {
                 "append",
                 "Whether to append to existing file instead of overwriting",
             )
+            .param_boolean(
+                "atomic",
+                "Write via a synced temp file and rename (default true); false writes in place",
+            )
             .build()
             .to_value()
     }
@@ -332,6 +408,65 @@ mod tests {
         assert!(!tmp_path.exists());
     }
 
+    #[tokio::test]
+    async fn test_atomic_write_leaves_only_complete_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("data.json");
+        std::fs::write(&file_path, "{\"old\": true}").unwrap();
+
+        let content = "x".repeat(256 * 1024);
+        let tool = WriteTool::new();
+        let params = serde_json::json!({
+            "path": file_path.to_string_lossy(),
+            "content": content
+        });
+        tool.execute(&params).await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), content);
+        let entries: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("data.json")]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_atomic_write_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("run.sh");
+        std::fs::write(&file_path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&file_path, std::fs::Permissions::from_mode(0o750)).unwrap();
+
+        atomic_write(&file_path, "#!/bin/sh\necho hi\n")
+            .await
+            .unwrap();
+
+        let mode = std::fs::metadata(&file_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+    }
+
+    #[tokio::test]
+    async fn test_write_non_atomic_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("direct.txt");
+        std::fs::write(&file_path, "base").unwrap();
+
+        let tool = WriteTool::new();
+        for (content, append) in [("first", false), (" second", true)] {
+            let params = serde_json::json!({
+                "path": file_path.to_string_lossy(),
+                "content": content,
+                "append": append,
+                "atomic": false
+            });
+            tool.execute(&params).await.unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "first second");
+    }
+
     #[tokio::test]
     async fn test_atomic_write_helper_overwrites_existing() {
         use super::atomic_write;
@@ -1,18 +1,26 @@
 //! Verify - Quality gate module
 //!
 //! Responsibilities:
-//! - Run tests
+//! - Run tests, parsing cargo's test summaries into metrics
 //! - Execute linting
+//! - Re-run the gate on file changes (watch mode)
 //!
 //! Design principles:
 //! - All checks are optionally configured
 //! - Clear pass/fail criteria
 
-use crate::discovery::{FileValidationType, HardConstraints};
-use crate::tools::{ShellTool, Tool};
+use crate::discovery::{CommitGate, HardConstraints};
+use crate::tools::{DiagnosticSource, LspClient, LspDiagnostics, ShellTool, Tool, ToolContext};
 use ndc_core::{QualityCheckType, QualityGate, TestType};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use tracing::{debug, info};
 
+mod watch;
+
+pub use watch::{QualityGateWatch, WATCH_GATE_NAME};
+
 /// Quality check result
 #[derive(Debug, Clone)]
 pub struct QualityResult {
@@ -30,10 +38,141 @@ pub struct QualityMetrics {
     pub duration_ms: u64,
 }
 
+impl QualityMetrics {
+    /// Test counts and duration from `cargo test` output
+    ///
+    /// Sums every libtest summary (`test result: ok. 3 passed; 0 failed; ...`)
+    /// and, for `--format json`, every suite result event. `None` when the
+    /// output contains neither.
+    pub fn from_test_output(output: &str) -> Option<Self> {
+        static SUMMARY: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        let summary = SUMMARY.get_or_init(|| {
+            regex::Regex::new(
+                r"test result: \w+\. (\d+) passed; (\d+) failed;(?:.*finished in ([0-9.]+)s)?",
+            )
+            .expect("valid test summary pattern")
+        });
+
+        let mut metrics = Self::default();
+        let mut suites = 0;
+        for line in output.lines() {
+            let line = line.trim();
+            let (passed, failed, seconds) = if line.starts_with('{') {
+                let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+                    continue;
+                };
+                if event["type"] != "suite" || event.get("passed").is_none() {
+                    continue;
+                }
+                let count = |key: &str| event[key].as_u64().unwrap_or(0) as u32;
+                (
+                    count("passed"),
+                    count("failed"),
+                    event["exec_time"].as_f64(),
+                )
+            } else if let Some(caps) = summary.captures(line) {
+                (
+                    caps[1].parse().unwrap_or(0),
+                    caps[2].parse().unwrap_or(0),
+                    caps.get(3).and_then(|m| m.as_str().parse::<f64>().ok()),
+                )
+            } else {
+                continue;
+            };
+            suites += 1;
+            metrics.tests_passed += passed;
+            metrics.tests_failed += failed;
+            metrics.duration_ms += seconds.map(|s| (s * 1000.0).round() as u64).unwrap_or(0);
+        }
+
+        metrics.tests_run = metrics.tests_passed + metrics.tests_failed;
+        (suites > 0).then_some(metrics)
+    }
+}
+
+/// Per-project shell command for each quality check
+///
+/// Keys are `test`, `lint`, `typecheck`, `build`, `security` and
+/// `custom:<name>`. Checks without an entry fall back to the cargo defaults.
+#[derive(Debug, Clone, Default)]
+pub struct QualityCommands {
+    commands: HashMap<String, String>,
+}
+
+impl QualityCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_map(commands: HashMap<String, String>) -> Self {
+        Self { commands }
+    }
+
+    /// Load `runtime.quality_commands` from the project/user config
+    pub fn load() -> Self {
+        let mut loader = ndc_core::NdcConfigLoader::new();
+        match loader.load() {
+            Ok(config) => Self::from_config(config),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// `runtime.quality_commands` of an already loaded config
+    pub fn from_config(config: &ndc_core::NdcConfig) -> Self {
+        config
+            .runtime
+            .as_ref()
+            .map(|runtime| Self::from_map(runtime.quality_commands.clone()))
+            .unwrap_or_default()
+    }
+
+    pub fn with_command(mut self, check: &QualityCheckType, command: impl Into<String>) -> Self {
+        self.commands
+            .insert(QualityGateRunner::check_key(check), command.into());
+        self
+    }
+
+    /// Command configured for the check, if any
+    pub fn configured(&self, check: &QualityCheckType) -> Option<&str> {
+        self.commands
+            .get(&QualityGateRunner::check_key(check))
+            .map(String::as_str)
+    }
+
+    /// Configured command, falling back to the cargo default
+    pub fn resolve(&self, check: &QualityCheckType) -> Option<String> {
+        self.configured(check)
+            .or_else(|| cargo_default_command(check))
+            .map(str::to_string)
+    }
+}
+
+fn cargo_default_command(check: &QualityCheckType) -> Option<&'static str> {
+    match check {
+        QualityCheckType::Test => Some("cargo test"),
+        QualityCheckType::Lint => Some("cargo clippy -- -D warnings"),
+        QualityCheckType::TypeCheck => Some("cargo check"),
+        QualityCheckType::Build => Some("cargo build"),
+        QualityCheckType::Security | QualityCheckType::Custom(_) => None,
+    }
+}
+
+/// LSP-backed type check on changed files
+#[derive(Debug, Clone)]
+struct LspGate {
+    source: Arc<dyn DiagnosticSource>,
+    /// Warnings fail the check, not just errors
+    fail_on_warnings: bool,
+}
+
 /// Quality gate runner
 #[derive(Debug)]
 pub struct QualityGateRunner {
     shell_tool: ShellTool,
+    /// Swappable so a config reload reaches runners already shared
+    commands: RwLock<QualityCommands>,
+    lsp: Option<LspGate>,
+    commit_gate: Option<CommitGate>,
 }
 
 impl Default for QualityGateRunner {
@@ -46,9 +185,83 @@ impl QualityGateRunner {
     pub fn new() -> Self {
         Self {
             shell_tool: ShellTool::new(),
+            commands: RwLock::new(QualityCommands::default()),
+            lsp: None,
+            commit_gate: None,
+        }
+    }
+
+    /// Runner configured by the layered NDC config, type checking `root`
+    /// through LSP when `runtime.lsp_gate` is set
+    pub fn load(root: &Path) -> Self {
+        let mut loader = ndc_core::NdcConfigLoader::new();
+        match loader.load() {
+            Ok(config) => Self::from_config(config, root),
+            Err(_) => Self::new(),
         }
     }
 
+    /// Quality commands and LSP gate of an already loaded config
+    pub fn from_config(config: &ndc_core::NdcConfig, root: &Path) -> Self {
+        let runner = Self::new().with_commands(QualityCommands::from_config(config));
+        match config
+            .runtime
+            .as_ref()
+            .and_then(|runtime| runtime.lsp_gate.as_ref())
+        {
+            Some(lsp) => runner.with_lsp(
+                Arc::new(LspDiagnostics::for_project(root, &lsp.servers)),
+                lsp.fail_on_warnings,
+            ),
+            None => runner,
+        }
+    }
+
+    /// Record checks that pass in `gate`, so commits see what was verified
+    pub fn with_commit_gate(mut self, gate: CommitGate) -> Self {
+        self.commit_gate = Some(gate);
+        self
+    }
+
+    pub fn with_commands(self, commands: QualityCommands) -> Self {
+        self.set_commands(commands);
+        self
+    }
+
+    /// Replace the configured commands; checks already running keep theirs
+    pub fn set_commands(&self, commands: QualityCommands) {
+        *self.commands.write().unwrap_or_else(|e| e.into_inner()) = commands;
+    }
+
+    /// Run check commands in `dir` instead of the process working directory
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.shell_tool = ShellTool::new().with_context(ToolContext {
+            working_dir: dir.into(),
+            ..ToolContext::default()
+        });
+        self
+    }
+
+    /// Answer `TypeCheck` from LSP diagnostics on the changed files
+    ///
+    /// Avoids a full `cargo check` for incremental edits. Errors always fail
+    /// the check; warnings fail it only when `fail_on_warnings` is set. When no
+    /// changed files are known the regular type check runs instead.
+    pub fn with_lsp(mut self, source: Arc<dyn DiagnosticSource>, fail_on_warnings: bool) -> Self {
+        self.lsp = Some(LspGate {
+            source,
+            fail_on_warnings,
+        });
+        self
+    }
+
+    pub fn commands(&self) -> QualityCommands {
+        self.commands
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
     /// Run quality gate
     pub async fn run(&self, gate: &QualityGate) -> Result<(), String> {
         self.run_with_constraints(Some(gate), None).await
@@ -62,6 +275,16 @@ impl QualityGateRunner {
         &self,
         gate: Option<&QualityGate>,
         constraints: Option<&HardConstraints>,
+    ) -> Result<(), String> {
+        self.run_for_files(gate, constraints, &[]).await
+    }
+
+    /// Run the enforced checks; `TypeCheck` uses LSP diagnostics on `changed_files` when configured
+    pub async fn run_for_files(
+        &self,
+        gate: Option<&QualityGate>,
+        constraints: Option<&HardConstraints>,
+        changed_files: &[PathBuf],
     ) -> Result<(), String> {
         let checks = Self::collect_enforced_checks(gate, constraints);
         info!("Running quality gate with {} enforced checks", checks.len());
@@ -72,12 +295,22 @@ impl QualityGateRunner {
         }
 
         for check in &checks {
-            let result = self.run_check(check).await?;
+            let result = match check {
+                QualityCheckType::TypeCheck if self.lsp.is_some() && !changed_files.is_empty() => {
+                    self.run_lsp_check(changed_files).await?
+                }
+                _ => self.run_check(check).await?,
+            };
             if !result.passed {
                 return Err(result
                     .error
                     .unwrap_or_else(|| format!("Quality check failed: {:?}", check)));
             }
+            if let Some(gate) = &self.commit_gate
+                && self.performs(check)
+            {
+                gate.record_passed(check);
+            }
         }
         info!("All quality checks passed");
         Ok(())
@@ -117,19 +350,11 @@ impl QualityGateRunner {
             }
 
             for validation in &constraints.mandatory_validations {
-                let check_type = match validation.validation_type {
-                    FileValidationType::Syntax | FileValidationType::Types => {
-                        QualityCheckType::TypeCheck
-                    }
-                    FileValidationType::Formatting | FileValidationType::Linting => {
-                        QualityCheckType::Lint
-                    }
-                    FileValidationType::Security => QualityCheckType::Security,
-                    FileValidationType::Documentation => {
-                        QualityCheckType::Custom("documentation".to_string())
-                    }
-                };
-                Self::push_unique_check(&mut checks, &mut seen, check_type);
+                Self::push_unique_check(
+                    &mut checks,
+                    &mut seen,
+                    validation.validation_type.quality_check(),
+                );
             }
         }
 
@@ -147,7 +372,22 @@ impl QualityGateRunner {
         }
     }
 
-    fn check_key(check: &QualityCheckType) -> String {
+    /// Inverse of `check_key`: `test`, `lint`, `typecheck`, `build`, `security`, `custom:<name>`
+    pub fn parse_check(key: &str) -> Option<QualityCheckType> {
+        match key.trim() {
+            "test" => Some(QualityCheckType::Test),
+            "lint" => Some(QualityCheckType::Lint),
+            "typecheck" => Some(QualityCheckType::TypeCheck),
+            "build" => Some(QualityCheckType::Build),
+            "security" => Some(QualityCheckType::Security),
+            key => key
+                .strip_prefix("custom:")
+                .filter(|name| !name.is_empty())
+                .map(|name| QualityCheckType::Custom(name.to_string())),
+        }
+    }
+
+    pub fn check_key(check: &QualityCheckType) -> String {
         match check {
             QualityCheckType::Test => "test".to_string(),
             QualityCheckType::Lint => "lint".to_string(),
@@ -158,8 +398,22 @@ impl QualityGateRunner {
         }
     }
 
+    /// Whether passing `check` verifies anything; security and custom checks
+    /// are skipped unless a command is configured for them
+    fn performs(&self, check: &QualityCheckType) -> bool {
+        self.commands().configured(check).is_some()
+            || !matches!(
+                check,
+                QualityCheckType::Security | QualityCheckType::Custom(_)
+            )
+    }
+
     /// Run a single quality check
     pub async fn run_check(&self, check_type: &QualityCheckType) -> Result<QualityResult, String> {
+        let configured = self.commands().configured(check_type).map(str::to_string);
+        if let Some(command) = configured {
+            return self.run_configured(check_type, &command).await;
+        }
         match check_type {
             QualityCheckType::Test => self.run_tests(&TestType::All).await,
             QualityCheckType::Lint => self.run_lint().await,
@@ -170,6 +424,91 @@ impl QualityGateRunner {
         }
     }
 
+    /// Run a project-configured command for a check
+    async fn run_configured(
+        &self,
+        check_type: &QualityCheckType,
+        command: &str,
+    ) -> Result<QualityResult, String> {
+        debug!(
+            "Running {:?} via configured command: {}",
+            check_type, command
+        );
+
+        let result = self
+            .shell_tool
+            .execute(&serde_json::json!({
+                "command": command,
+                "timeout": 600
+            }))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let passed = result.success;
+        let metrics = match check_type {
+            QualityCheckType::Test => QualityMetrics::from_test_output(&result.output),
+            _ => None,
+        };
+
+        Ok(QualityResult {
+            passed,
+            output: result.output,
+            error: if passed {
+                None
+            } else {
+                Some(format!("Quality check failed: {}", command))
+            },
+            metrics: metrics.unwrap_or_default(),
+        })
+    }
+
+    /// Type check `files` via LSP diagnostics
+    ///
+    /// The output is the formatted `DiagnosticSummary` across all files.
+    pub async fn run_lsp_check(&self, files: &[PathBuf]) -> Result<QualityResult, String> {
+        let lsp = self
+            .lsp
+            .as_ref()
+            .ok_or_else(|| "LSP diagnostics are not configured".to_string())?;
+        let started = std::time::Instant::now();
+
+        let mut diagnostics = Vec::new();
+        for file in files {
+            diagnostics.extend(lsp.source.diagnostics(file).await?.diagnostics);
+        }
+        let summary = LspClient::summarize_diagnostics(&diagnostics);
+        debug!(
+            "LSP check on {} file(s): {} error(s), {} warning(s)",
+            files.len(),
+            summary.error_count,
+            summary.warning_count
+        );
+
+        let error = if summary.error_count > 0 {
+            Some(format!(
+                "Type check failed: {} error(s) from LSP",
+                summary.error_count
+            ))
+        } else if lsp.fail_on_warnings && summary.warning_count > 0 {
+            Some(format!(
+                "Type check failed: {} warning(s) from LSP",
+                summary.warning_count
+            ))
+        } else {
+            None
+        };
+
+        Ok(QualityResult {
+            passed: error.is_none(),
+            output: LspClient::format_diagnostics(&summary),
+            error,
+            metrics: QualityMetrics {
+                duration_ms: started.elapsed().as_millis() as u64,
+                ..Default::default()
+            },
+        })
+    }
+
     /// Run tests
     pub async fn run_tests(&self, test_type: &TestType) -> Result<QualityResult, String> {
         let command = match test_type {
@@ -194,7 +533,9 @@ impl QualityGateRunner {
             .await
             .map_err(|e| e.to_string())?;
 
-        let passed = result.success && !result.output.contains("FAILED");
+        let metrics = QualityMetrics::from_test_output(&result.output).unwrap_or_default();
+        let passed =
+            result.success && !result.output.contains("FAILED") && metrics.tests_failed == 0;
 
         if !passed {
             tracing::warn!("Tests failed");
@@ -205,10 +546,15 @@ impl QualityGateRunner {
             output: result.output,
             error: if passed {
                 None
+            } else if metrics.tests_failed > 0 {
+                Some(format!(
+                    "Tests failed: {} of {} failed",
+                    metrics.tests_failed, metrics.tests_run
+                ))
             } else {
                 Some("Tests failed".to_string())
             },
-            metrics: QualityMetrics::default(),
+            metrics,
         })
     }
 
@@ -316,10 +662,10 @@ mod tests {
     use super::*;
     use crate::discovery::{
         ComponentKind, ComponentRef, CouplingType, CouplingWarning, FileValidation,
-        HardConstraints, RegressionTest,
+        FileValidationType, HardConstraints, RegressionTest,
     };
+    use crate::tools::{Diagnostic, DiagnosticSeverity, DiagnosticSummary};
     use ndc_core::RiskLevel;
-    use std::path::PathBuf;
 
     #[test]
     fn test_collect_enforced_checks_from_constraints() {
@@ -362,4 +708,325 @@ mod tests {
         assert!(keys.contains("security"));
         assert!(keys.contains("lint"));
     }
+
+    #[tokio::test]
+    async fn test_configured_test_command_is_invoked() {
+        let runner = QualityGateRunner::new().with_commands(
+            QualityCommands::new().with_command(&QualityCheckType::Test, "echo npm-test-ran"),
+        );
+
+        let result = runner.run_check(&QualityCheckType::Test).await.unwrap();
+        assert!(result.passed);
+        assert!(result.output.contains("npm-test-ran"));
+    }
+
+    #[tokio::test]
+    async fn test_passed_checks_satisfy_the_commit_gate() {
+        let mut constraints = HardConstraints::new("task-1".to_string());
+        constraints.add_regression_test(RegressionTest {
+            module: "storage".to_string(),
+            test_files: vec![PathBuf::from("tests/storage.rs")],
+            test_types: vec![],
+            coverage_requirement: 0.0,
+        });
+        constraints.mandatory_validations.push(FileValidation {
+            path: PathBuf::from("./src/db.rs"),
+            validation_type: FileValidationType::Linting,
+            reason: "touched".to_string(),
+            tool: "clippy".to_string(),
+        });
+        let gate = CommitGate::new();
+        gate.begin(Some(constraints.clone()));
+        assert_eq!(gate.report().unwrap().failures.len(), 2);
+
+        let runner = QualityGateRunner::new()
+            .with_commands(
+                QualityCommands::new()
+                    .with_command(&QualityCheckType::Test, "true")
+                    .with_command(&QualityCheckType::Lint, "true"),
+            )
+            .with_commit_gate(gate.clone());
+        runner
+            .run_for_files(None, Some(&constraints), &[PathBuf::from("src/db.rs")])
+            .await
+            .unwrap();
+
+        assert!(gate.report().unwrap().is_satisfied());
+    }
+
+    const PASSING_RUN: &str = "\
+   Compiling demo v0.1.0 (/tmp/demo)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 1.02s
+     Running unittests src/lib.rs (target/debug/deps/demo-1a2b3c)
+
+running 3 tests
+test tests::adds ... ok
+test tests::subtracts ... ok
+test tests::slow ... ignored
+
+test result: ok. 2 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.25s
+
+   Doc-tests demo
+
+running 1 test
+test src/lib.rs - add (line 3) ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.50s
+";
+
+    const FAILING_RUN: &str = "\
+running 3 tests
+test tests::adds ... ok
+test tests::divides ... FAILED
+test tests::rounds ... FAILED
+
+failures:
+
+---- tests::divides stdout ----
+thread 'tests::divides' panicked at src/lib.rs:20:9:
+attempt to divide by zero
+
+failures:
+    tests::divides
+    tests::rounds
+
+test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+error: test failed, to rerun pass `--lib`
+";
+
+    #[test]
+    fn test_parse_libtest_summaries() {
+        let metrics = QualityMetrics::from_test_output(PASSING_RUN).unwrap();
+        assert_eq!(
+            (
+                metrics.tests_run,
+                metrics.tests_passed,
+                metrics.tests_failed
+            ),
+            (3, 3, 0)
+        );
+        assert_eq!(metrics.duration_ms, 750);
+
+        let metrics = QualityMetrics::from_test_output(FAILING_RUN).unwrap();
+        assert_eq!(
+            (
+                metrics.tests_run,
+                metrics.tests_passed,
+                metrics.tests_failed
+            ),
+            (3, 1, 2)
+        );
+        assert_eq!(metrics.duration_ms, 10);
+
+        assert!(QualityMetrics::from_test_output("error: could not compile `demo`").is_none());
+    }
+
+    #[test]
+    fn test_parse_json_test_events() {
+        let output = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::adds" }
+{ "type": "test", "name": "tests::adds", "event": "ok" }
+{ "type": "test", "name": "tests::divides", "event": "failed", "stdout": "panicked" }
+{ "type": "test", "name": "tests::slow", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.125 }
+{ "type": "suite", "event": "ok", "passed": 4, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.5 }"#;
+        let metrics = QualityMetrics::from_test_output(output).unwrap();
+        assert_eq!(
+            (
+                metrics.tests_run,
+                metrics.tests_passed,
+                metrics.tests_failed
+            ),
+            (6, 5, 1)
+        );
+        assert_eq!(metrics.duration_ms, 625);
+    }
+
+    #[tokio::test]
+    async fn test_configured_test_command_reports_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("passing.txt"), PASSING_RUN).unwrap();
+        let runner = QualityGateRunner::new()
+            .with_working_dir(dir.path())
+            .with_commands(
+                QualityCommands::new().with_command(&QualityCheckType::Test, "cat passing.txt"),
+            );
+
+        let result = runner.run_check(&QualityCheckType::Test).await.unwrap();
+        assert!(result.passed);
+        assert_eq!(result.metrics.tests_run, 3);
+        assert_eq!(result.metrics.duration_ms, 750);
+    }
+
+    #[tokio::test]
+    async fn test_configured_custom_and_security_checks_run() {
+        let commands = QualityCommands::from_map(HashMap::from([
+            ("security".to_string(), "echo audit-ok".to_string()),
+            ("custom:docs".to_string(), "false".to_string()),
+        ]));
+        let runner = QualityGateRunner::new().with_commands(commands);
+
+        let security = runner.run_check(&QualityCheckType::Security).await.unwrap();
+        assert!(security.output.contains("audit-ok"));
+
+        let docs = runner
+            .run_check(&QualityCheckType::Custom("docs".to_string()))
+            .await
+            .unwrap();
+        assert!(!docs.passed);
+    }
+
+    #[derive(Debug)]
+    struct MockLsp {
+        severities: Vec<DiagnosticSeverity>,
+    }
+
+    #[async_trait::async_trait]
+    impl DiagnosticSource for MockLsp {
+        async fn diagnostics(&self, file: &std::path::Path) -> Result<DiagnosticSummary, String> {
+            let diagnostics: Vec<Diagnostic> = self
+                .severities
+                .iter()
+                .enumerate()
+                .map(|(i, severity)| Diagnostic {
+                    message: format!("problem {}", i),
+                    severity: severity.clone(),
+                    file: file.to_path_buf(),
+                    line: i + 1,
+                    column: 1,
+                    code: None,
+                })
+                .collect();
+            Ok(LspClient::summarize_diagnostics(&diagnostics))
+        }
+    }
+
+    fn lsp_runner(
+        severities: Vec<DiagnosticSeverity>,
+        fail_on_warnings: bool,
+    ) -> QualityGateRunner {
+        QualityGateRunner::new().with_lsp(Arc::new(MockLsp { severities }), fail_on_warnings)
+    }
+
+    fn type_check_gate() -> QualityGate {
+        QualityGate {
+            checks: vec![ndc_core::QualityCheck {
+                check_type: QualityCheckType::TypeCheck,
+                command: None,
+                pass_condition: ndc_core::PassCondition::ExitCode(0),
+            }],
+            strategy: ndc_core::GateStrategy::FailFast,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lsp_errors_fail_type_check() {
+        let runner = lsp_runner(
+            vec![DiagnosticSeverity::Error, DiagnosticSeverity::Warning],
+            false,
+        );
+        let files = [PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")];
+
+        let result = runner.run_lsp_check(&files).await.unwrap();
+        assert!(!result.passed);
+        assert!(result.error.unwrap().contains("2 error(s)"));
+        assert!(result.output.contains("src/b.rs:1:1"));
+
+        let err = runner
+            .run_for_files(Some(&type_check_gate()), None, &files)
+            .await
+            .unwrap_err();
+        assert!(err.contains("LSP"));
+    }
+
+    #[tokio::test]
+    async fn test_lsp_warnings_pass_unless_configured() {
+        let files = [PathBuf::from("src/a.rs")];
+        let warnings = vec![DiagnosticSeverity::Warning, DiagnosticSeverity::Hint];
+
+        let lenient = lsp_runner(warnings.clone(), false);
+        let result = lenient.run_lsp_check(&files).await.unwrap();
+        assert!(result.passed);
+        assert!(result.output.contains("WARNING"));
+        lenient
+            .run_for_files(Some(&type_check_gate()), None, &files)
+            .await
+            .unwrap();
+
+        let strict = lsp_runner(warnings, true);
+        let result = strict.run_lsp_check(&files).await.unwrap();
+        assert!(!result.passed);
+        assert!(result.error.unwrap().contains("1 warning(s)"));
+    }
+
+    #[tokio::test]
+    async fn test_from_config_enables_lsp_gate_only_when_configured() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = ndc_core::NdcConfig::default();
+        let runner = QualityGateRunner::from_config(&config, dir.path());
+        assert!(runner.lsp.is_none());
+
+        config.runtime = Some(ndc_core::YamlRuntimeConfig {
+            quality_commands: HashMap::from([("lint".to_string(), "true".to_string())]),
+            lsp_gate: Some(ndc_core::YamlLspGateConfig {
+                fail_on_warnings: true,
+                servers: HashMap::from([("rust".to_string(), vec!["false".to_string()])]),
+            }),
+            ..Default::default()
+        });
+        let runner = QualityGateRunner::from_config(&config, dir.path());
+        assert_eq!(
+            runner.commands().configured(&QualityCheckType::Lint),
+            Some("true")
+        );
+        assert!(runner.lsp.as_ref().is_some_and(|lsp| lsp.fail_on_warnings));
+        // The overridden availability check fails, so no diagnostics are reported
+        let result = runner
+            .run_lsp_check(&[dir.path().join("src/lib.rs")])
+            .await
+            .unwrap();
+        assert!(result.passed);
+    }
+
+    #[tokio::test]
+    async fn test_working_dir_and_check_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("marker.txt"), "here").unwrap();
+        let runner = QualityGateRunner::new()
+            .with_working_dir(dir.path())
+            .with_commands(
+                QualityCommands::new().with_command(&QualityCheckType::Lint, "cat marker.txt"),
+            );
+        let result = runner.run_check(&QualityCheckType::Lint).await.unwrap();
+        assert!(result.passed);
+        assert!(result.output.contains("here"));
+
+        for check in [
+            QualityCheckType::Test,
+            QualityCheckType::TypeCheck,
+            QualityCheckType::Custom("docs".to_string()),
+        ] {
+            let key = QualityGateRunner::check_key(&check);
+            assert_eq!(QualityGateRunner::parse_check(&key), Some(check));
+        }
+        assert_eq!(QualityGateRunner::parse_check("custom:"), None);
+        assert_eq!(QualityGateRunner::parse_check("clippy"), None);
+    }
+
+    #[test]
+    fn test_missing_config_falls_back_to_cargo() {
+        let commands = QualityCommands::new().with_command(&QualityCheckType::Test, "npm test");
+
+        assert_eq!(
+            commands.resolve(&QualityCheckType::Test).as_deref(),
+            Some("npm test")
+        );
+        assert_eq!(
+            commands.resolve(&QualityCheckType::Build).as_deref(),
+            Some("cargo build")
+        );
+        assert_eq!(commands.configured(&QualityCheckType::Lint), None);
+        assert_eq!(commands.resolve(&QualityCheckType::Security), None);
+    }
 }
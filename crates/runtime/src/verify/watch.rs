@@ -0,0 +1,321 @@
+//! Watch mode - re-run the quality gate when sources change
+//!
+//! `QualityGateRunner::watch` turns file system events into change batches:
+//! a burst of edits is folded into one run once the paths stay quiet for
+//! `debounce`, and a change arriving mid-run cancels that run (killing its
+//! commands) before the next one starts. Each finished run is reported as a
+//! `QualityGatePassed` / `QualityGateFailed` event.
+
+use futures::future::BoxFuture;
+use ndc_core::QualityGate;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+use super::QualityGateRunner;
+use crate::discovery::HardConstraints;
+use crate::engine::{Event, EventData, EventId, EventType};
+
+/// Gate name carried by watch-mode events
+pub const WATCH_GATE_NAME: &str = "watch";
+
+/// Build output and VCS metadata; changes there never trigger a run
+const IGNORED_DIRS: &[&str] = &["target", ".git", "node_modules"];
+
+type GateRun = BoxFuture<'static, Result<(), String>>;
+
+/// Running watch; dropping it stops watching and cancels any in-flight run
+pub struct QualityGateWatch {
+    events: mpsc::UnboundedReceiver<Event>,
+    task: JoinHandle<()>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl QualityGateWatch {
+    /// Next gate result; `None` once the change source is gone
+    pub async fn next_event(&mut self) -> Option<Event> {
+        self.events.recv().await
+    }
+}
+
+impl Drop for QualityGateWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl QualityGateRunner {
+    /// Watch `paths` recursively and re-run the enforced checks on change
+    pub fn watch(
+        self: Arc<Self>,
+        paths: &[PathBuf],
+        debounce: Duration,
+        gate: Option<QualityGate>,
+        constraints: Option<HardConstraints>,
+    ) -> Result<QualityGateWatch, String> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && !event.kind.is_access()
+                {
+                    let paths: Vec<PathBuf> =
+                        event.paths.into_iter().filter(|p| !is_ignored(p)).collect();
+                    if !paths.is_empty() {
+                        let _ = tx.send(paths);
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        for path in paths {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .map_err(|e| format!("cannot watch {}: {}", path.display(), e))?;
+        }
+
+        let mut watch = self.watch_changes(rx, debounce, gate, constraints);
+        watch._watcher = Some(watcher);
+        Ok(watch)
+    }
+
+    /// Re-run the gate for each debounced batch of changed paths from `changes`
+    pub fn watch_changes(
+        self: Arc<Self>,
+        mut changes: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+        debounce: Duration,
+        gate: Option<QualityGate>,
+        constraints: Option<HardConstraints>,
+    ) -> QualityGateWatch {
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            // Dropping the run future cancels it and kills its commands
+            let mut running: Option<(GateRun, Vec<PathBuf>)> = None;
+            loop {
+                tokio::select! {
+                    batch = changes.recv() => {
+                        let Some(batch) = batch else { break };
+                        let mut changed: BTreeSet<PathBuf> = batch.into_iter().collect();
+                        // The cancelled run's paths are still unchecked, so they
+                        // carry over into the next run
+                        if let Some((_, cancelled)) = running.take() {
+                            debug!("Newer change arrived; cancelling in-flight quality gate");
+                            changed.extend(cancelled);
+                        }
+                        // Wait until the paths stay quiet, so a burst of edits is one run
+                        while let Ok(Some(batch)) =
+                            tokio::time::timeout(debounce, changes.recv()).await
+                        {
+                            changed.extend(batch);
+                        }
+                        let changed: Vec<PathBuf> = changed.into_iter().collect();
+                        info!("{} file(s) changed; re-running quality gate", changed.len());
+
+                        let runner = self.clone();
+                        let (gate, constraints) = (gate.clone(), constraints.clone());
+                        let files = changed.clone();
+                        let run = Box::pin(async move {
+                            runner
+                                .run_for_files(gate.as_ref(), constraints.as_ref(), &files)
+                                .await
+                        });
+                        running = Some((run, changed));
+                    }
+                    result = async {
+                        match running.as_mut() {
+                            Some((run, _)) => run.as_mut().await,
+                            None => std::future::pending().await,
+                        }
+                    } => {
+                        if let Some((_, changed)) = running.take()
+                            && events_tx.send(gate_event(result, &changed)).is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        QualityGateWatch {
+            events,
+            task,
+            _watcher: None,
+        }
+    }
+}
+
+fn is_ignored(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_DIRS.iter().any(|dir| name == *dir),
+        _ => false,
+    })
+}
+
+fn gate_event(result: Result<(), String>, changed: &[PathBuf]) -> Event {
+    let passed = result.is_ok();
+    let mut metadata = HashMap::from([(
+        "changed_files".to_string(),
+        changed
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    )]);
+    if let Err(error) = result {
+        metadata.insert("error".to_string(), error);
+    }
+    Event {
+        id: EventId::default(),
+        event_type: if passed {
+            EventType::QualityGatePassed
+        } else {
+            EventType::QualityGateFailed
+        },
+        data: EventData::QualityGate {
+            gate_name: WATCH_GATE_NAME.to_string(),
+            passed,
+        },
+        task_id: None,
+        step_id: None,
+        timestamp: chrono::Utc::now(),
+        metadata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::QualityCommands;
+    use ndc_core::{GateStrategy, PassCondition, QualityCheck, QualityCheckType};
+
+    fn test_gate() -> QualityGate {
+        QualityGate {
+            checks: vec![QualityCheck {
+                check_type: QualityCheckType::Test,
+                command: None,
+                pass_condition: PassCondition::ExitCode(0),
+            }],
+            strategy: GateStrategy::FailFast,
+        }
+    }
+
+    /// Runner whose test check appends to `runs.log` in `dir`, after `sleep` seconds
+    fn counting_runner(dir: &Path, sleep: &str) -> Arc<QualityGateRunner> {
+        Arc::new(
+            QualityGateRunner::new()
+                .with_working_dir(dir)
+                .with_commands(QualityCommands::new().with_command(
+                    &QualityCheckType::Test,
+                    format!(
+                        "sleep {} && echo run >> runs.log && test ! -e broken",
+                        sleep
+                    ),
+                )),
+        )
+    }
+
+    fn runs(dir: &Path) -> usize {
+        std::fs::read_to_string(dir.join("runs.log"))
+            .map(|log| log.lines().count())
+            .unwrap_or(0)
+    }
+
+    async fn next(watch: &mut QualityGateWatch) -> Event {
+        tokio::time::timeout(Duration::from_secs(20), watch.next_event())
+            .await
+            .expect("gate event")
+            .expect("watch running")
+    }
+
+    #[tokio::test]
+    async fn test_burst_of_changes_runs_gate_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watch = counting_runner(dir.path(), "0").watch_changes(
+            rx,
+            Duration::from_millis(200),
+            Some(test_gate()),
+            None,
+        );
+
+        for name in ["a.rs", "b.rs", "a.rs"] {
+            tx.send(vec![PathBuf::from(name)]).unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let event = next(&mut watch).await;
+        assert_eq!(event.event_type, EventType::QualityGatePassed);
+        assert_eq!(event.metadata["changed_files"], "a.rs,b.rs");
+        assert_eq!(runs(dir.path()), 1);
+
+        // A later change fails the gate
+        std::fs::write(dir.path().join("broken"), "").unwrap();
+        tx.send(vec![PathBuf::from("c.rs")]).unwrap();
+        let event = next(&mut watch).await;
+        assert_eq!(event.event_type, EventType::QualityGateFailed);
+        assert!(matches!(
+            event.data,
+            EventData::QualityGate { passed: false, .. }
+        ));
+        assert_eq!(runs(dir.path()), 2);
+    }
+
+    #[tokio::test]
+    async fn test_newer_change_cancels_in_flight_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watch = counting_runner(dir.path(), "1").watch_changes(
+            rx,
+            Duration::from_millis(50),
+            Some(test_gate()),
+            None,
+        );
+
+        tx.send(vec![PathBuf::from("a.rs")]).unwrap();
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        tx.send(vec![PathBuf::from("b.rs")]).unwrap();
+
+        // Only the second run reports, covering the killed run's paths too
+        let event = next(&mut watch).await;
+        assert_eq!(event.metadata["changed_files"], "a.rs,b.rs");
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(runs(dir.path()), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reruns_gate_on_file_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("target")).unwrap();
+        let mut watch = counting_runner(dir.path(), "0")
+            .watch(
+                std::slice::from_ref(&src),
+                Duration::from_millis(200),
+                Some(test_gate()),
+                None,
+            )
+            .unwrap();
+
+        // Build output is ignored
+        std::fs::write(src.join("target").join("out.o"), "x").unwrap();
+        std::fs::write(src.join("lib.rs"), "fn a() {}").unwrap();
+        std::fs::write(src.join("lib.rs"), "fn b() {}").unwrap();
+
+        let event = next(&mut watch).await;
+        assert_eq!(event.event_type, EventType::QualityGatePassed);
+        assert!(event.metadata["changed_files"].ends_with("lib.rs"));
+        assert!(!event.metadata["changed_files"].contains("target"));
+        assert_eq!(runs(dir.path()), 1);
+    }
+
+    #[test]
+    fn test_build_and_vcs_paths_are_ignored() {
+        assert!(is_ignored(Path::new("/repo/target/debug/x")));
+        assert!(is_ignored(Path::new("/repo/.git/index")));
+        assert!(!is_ignored(Path::new("/repo/src/targets.rs")));
+    }
+}
@@ -6,11 +6,15 @@
 //! - Handle blocking and resumption
 //! - Saga pattern for distributed transactions
 //! - Compensating transactions for rollback
+//! - Agent workflow stage timing (`enter_stage` / `finish_stage`)
 
-use ndc_core::{Executor, Task, TaskId, TaskState, WorkEvent, WorkRecord, WorkResult};
+use ndc_core::{
+    AgentWorkflowStage, Executor, Task, TaskId, TaskState, WorkEvent, WorkRecord, WorkResult,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
@@ -28,9 +32,17 @@ pub enum WorkflowError {
 }
 
 /// Workflow listener trait
+///
+/// Every hook has a no-op default, so listeners implement only what they need.
 #[async_trait::async_trait]
 pub trait WorkflowListener: Send + Sync {
-    async fn on_transition(&self, task_id: &TaskId, from: &TaskState, to: &TaskState);
+    async fn on_transition(&self, _task_id: &TaskId, _from: &TaskState, _to: &TaskState) {}
+
+    /// An agent workflow stage began
+    async fn on_stage_enter(&self, _stage: AgentWorkflowStage) {}
+
+    /// An agent workflow stage ended after `duration` (monotonic clock)
+    async fn on_stage_exit(&self, _stage: AgentWorkflowStage, _duration: Duration) {}
 }
 
 /// Workflow transition rule
@@ -47,6 +59,8 @@ pub struct TransitionRule {
 pub struct WorkflowEngine {
     rules: HashMap<(TaskState, TaskState), TransitionRule>,
     listeners: Vec<Arc<dyn WorkflowListener>>,
+    /// Stage being timed, with its start
+    active_stage: Mutex<Option<(AgentWorkflowStage, Instant)>>,
 }
 
 impl std::fmt::Debug for WorkflowEngine {
@@ -74,6 +88,9 @@ impl WorkflowEngine {
             // Rollback
             (TaskState::Failed, TaskState::Pending),
             (TaskState::Completed, TaskState::Pending),
+            // Waiting on unfinished dependencies
+            (TaskState::Pending, TaskState::Blocked),
+            (TaskState::Blocked, TaskState::Pending),
         ];
 
         for (from, to) in rules {
@@ -136,6 +153,54 @@ impl WorkflowEngine {
     pub fn register_listener(&mut self, listener: Arc<dyn WorkflowListener>) {
         self.listeners.push(listener);
     }
+
+    /// Enter `stage`, ending the current one
+    ///
+    /// Entering the stage already active is a no-op, so repeated stage events
+    /// within one visit do not split its timing; a later visit (e.g.
+    /// Verifying -> Executing -> Verifying) gets its own enter/exit pair.
+    pub async fn enter_stage(&self, stage: AgentWorkflowStage) {
+        let previous = {
+            let mut active = self.active_stage.lock().unwrap_or_else(|e| e.into_inner());
+            if active.is_some_and(|(current, _)| current == stage) {
+                return;
+            }
+            active.replace((stage, Instant::now()))
+        };
+        if let Some((previous, started)) = previous {
+            self.notify_stage_exit(previous, started.elapsed()).await;
+        }
+        for listener in &self.listeners {
+            listener.on_stage_enter(stage).await;
+        }
+    }
+
+    /// End the current stage, if any (run finished or cancelled)
+    pub async fn finish_stage(&self) {
+        let active = self
+            .active_stage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some((stage, started)) = active {
+            self.notify_stage_exit(stage, started.elapsed()).await;
+        }
+    }
+
+    /// Stage currently being timed
+    pub fn current_stage(&self) -> Option<AgentWorkflowStage> {
+        self.active_stage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|(stage, _)| stage)
+    }
+
+    async fn notify_stage_exit(&self, stage: AgentWorkflowStage, duration: Duration) {
+        debug!(stage = %stage, duration_ms = duration.as_millis() as u64, "Workflow stage finished");
+        for listener in &self.listeners {
+            listener.on_stage_exit(stage, duration).await;
+        }
+    }
 }
 
 // ============================================================================
@@ -651,6 +716,71 @@ mod tests {
         }
     }
 
+    /// Records stage hooks as `(event, stage, duration)`
+    #[derive(Default)]
+    struct StageRecorder {
+        events: Mutex<Vec<(&'static str, AgentWorkflowStage, Option<Duration>)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl WorkflowListener for StageRecorder {
+        async fn on_stage_enter(&self, stage: AgentWorkflowStage) {
+            self.events.lock().unwrap().push(("enter", stage, None));
+        }
+
+        async fn on_stage_exit(&self, stage: AgentWorkflowStage, duration: Duration) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(("exit", stage, Some(duration)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stage_hooks_pair_enter_and_exit_with_durations() {
+        use AgentWorkflowStage::{Executing, Planning, Verifying};
+
+        let recorder = Arc::new(StageRecorder::default());
+        let mut engine = WorkflowEngine::new();
+        engine.register_listener(recorder.clone());
+
+        let pause = Duration::from_millis(20);
+        for stage in [
+            Planning, Executing, Executing, Verifying, Executing, Verifying,
+        ] {
+            engine.enter_stage(stage).await;
+            tokio::time::sleep(pause).await;
+        }
+        assert_eq!(engine.current_stage(), Some(Verifying));
+        engine.finish_stage().await;
+        assert_eq!(engine.current_stage(), None);
+        engine.finish_stage().await;
+
+        let events = recorder.events.lock().unwrap().clone();
+        let visits: Vec<AgentWorkflowStage> = events.iter().step_by(2).map(|e| e.1).collect();
+        // The repeated Executing is one visit; re-entering Verifying is a new one
+        assert_eq!(
+            visits,
+            vec![Planning, Executing, Verifying, Executing, Verifying]
+        );
+        for pair in events.chunks(2) {
+            let [(enter, entered, None), (exit, exited, Some(duration))] = pair else {
+                panic!("expected an enter/exit pair, got {:?}", pair);
+            };
+            assert_eq!((*enter, *exit), ("enter", "exit"));
+            assert_eq!(entered, exited);
+            assert!(
+                *duration >= pause,
+                "{:?} shorter than {:?}",
+                duration,
+                pause
+            );
+            assert!(*duration < Duration::from_secs(5));
+        }
+        // The merged Executing visit spans both pauses
+        assert!(events[3].2.unwrap() >= pause * 2);
+    }
+
     #[test]
     fn test_saga_state_default() {
         let state = SagaState::Pending;
@@ -0,0 +1,527 @@
+//! Embedding storage wrapper
+//!
+//! Wraps any `Storage` so memories are saved with an embedding: an empty
+//! `embedding` is filled from the memory's summary by the configured
+//! `Embedder`. Vectors whose size differs from the embedder's dimensions are
+//! rejected, so the cosine index never mixes vector spaces.
+//!
+//! Optionally, a save whose embedding is a near-duplicate (cosine at or
+//! above a threshold) of an existing memory is skipped or merged into that
+//! memory instead of adding another entry; see `DedupPolicy`. Only memories
+//! with the same owner and roles count as duplicates, and dedup saves are
+//! serialized so concurrent saves cannot both miss each other.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ndc_core::{
+    AccessControl, EmbedError, Embedder, MemoryEntry, MemoryId, ScoredMemory, Task, TaskId,
+    check_dimensions,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::trait_::{SharedStorage, Storage, TaskFilter};
+
+/// Cosine similarity at which two memories count as near-duplicates
+pub const DEFAULT_DEDUP_THRESHOLD: f32 = 0.95;
+
+/// Nearest memories checked for a near-duplicate on save
+const DEDUP_CANDIDATES: usize = 8;
+
+/// What to do when a saved memory nearly duplicates an existing one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DedupPolicy {
+    /// Save it as a new memory
+    #[default]
+    Allow,
+    /// Drop the new memory
+    Skip,
+    /// Fold it into the existing memory: bump its version, set
+    /// `modified_at` and add the new tags
+    Merge,
+}
+
+impl DedupPolicy {
+    /// `NDC_MEMORY_DEDUP` (`allow`/`skip`/`merge`) and
+    /// `NDC_MEMORY_DEDUP_THRESHOLD`; unset or invalid values use the defaults
+    pub fn from_env() -> (Self, f32) {
+        let policy = std::env::var("NDC_MEMORY_DEDUP")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+        let threshold = std::env::var("NDC_MEMORY_DEDUP_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|t| (0.0..=1.0).contains(t))
+            .unwrap_or(DEFAULT_DEDUP_THRESHOLD);
+        (policy, threshold)
+    }
+}
+
+impl FromStr for DedupPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "allow" => Ok(Self::Allow),
+            "skip" => Ok(Self::Skip),
+            "merge" => Ok(Self::Merge),
+            other => Err(format!(
+                "unknown dedup policy '{}' (allow, skip, merge)",
+                other
+            )),
+        }
+    }
+}
+
+/// `Storage` that embeds memories on save before delegating to `inner`
+#[derive(Clone)]
+pub struct EmbeddingStorage {
+    inner: SharedStorage,
+    embedder: Arc<dyn Embedder>,
+    dedup: DedupPolicy,
+    dedup_threshold: f32,
+    /// Held from the duplicate lookup until the save, so the pair is atomic
+    dedup_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl std::fmt::Debug for EmbeddingStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddingStorage")
+            .field("embedder", &self.embedder)
+            .field("dedup", &self.dedup)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EmbeddingStorage {
+    pub fn new(inner: SharedStorage, embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            inner,
+            embedder,
+            dedup: DedupPolicy::Allow,
+            dedup_threshold: DEFAULT_DEDUP_THRESHOLD,
+            dedup_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Handle near-duplicates (cosine ≥ `threshold`) according to `policy`
+    pub fn with_dedup(mut self, policy: DedupPolicy, threshold: f32) -> Self {
+        self.dedup = policy;
+        self.dedup_threshold = threshold;
+        self
+    }
+
+    /// Save an embedded memory, applying the dedup policy
+    async fn save_embedded(&self, memory: &MemoryEntry) -> Result<(), String> {
+        if self.dedup == DedupPolicy::Allow || memory.embedding.is_empty() {
+            return self.inner.save_memory(memory).await;
+        }
+        let _guard = self.dedup_lock.lock().await;
+        // Re-saving a stored memory is an update, never a duplicate
+        if self.inner.get_memory(&memory.id).await?.is_some() {
+            return self.inner.save_memory(memory).await;
+        }
+        let Some(duplicate) = self
+            .inner
+            .search_memories(&memory.embedding, DEDUP_CANDIDATES)
+            .await?
+            .into_iter()
+            .take_while(|hit| hit.score >= self.dedup_threshold)
+            .find(|hit| same_access(&hit.memory.access_control, &memory.access_control))
+        else {
+            return self.inner.save_memory(memory).await;
+        };
+
+        debug!(
+            memory_id = %memory.id.0,
+            duplicate_of = %duplicate.memory.id.0,
+            score = duplicate.score,
+            policy = ?self.dedup,
+            "Near-duplicate memory"
+        );
+        match self.dedup {
+            DedupPolicy::Skip => Ok(()),
+            _ => {
+                let mut merged = duplicate.memory;
+                merged.metadata.version += 1;
+                merged.metadata.modified_at = Some(chrono::Utc::now());
+                for tag in &memory.metadata.tags {
+                    if !merged.metadata.tags.contains(tag) {
+                        merged.metadata.tags.push(tag.clone());
+                    }
+                }
+                self.inner.save_memory(&merged).await
+            }
+        }
+    }
+
+    pub fn embedder(&self) -> &Arc<dyn Embedder> {
+        &self.embedder
+    }
+}
+
+/// Same owner and roles: merging never moves a memory into another agent's entry
+fn same_access(a: &AccessControl, b: &AccessControl) -> bool {
+    a.owner == b.owner && a.read_roles == b.read_roles && a.write_roles == b.write_roles
+}
+
+#[async_trait]
+impl Storage for EmbeddingStorage {
+    async fn save_task(&self, task: &Task) -> Result<(), String> {
+        self.inner.save_task(task).await
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<Option<Task>, String> {
+        self.inner.get_task(task_id).await
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<Task>, String> {
+        self.inner.list_tasks().await
+    }
+
+    async fn query_tasks(&self, filter: &TaskFilter) -> Result<Vec<Task>, String> {
+        self.inner.query_tasks(filter).await
+    }
+
+    async fn list_tasks_by_tags(&self, tags: &[String]) -> Result<Vec<Task>, String> {
+        self.inner.list_tasks_by_tags(tags).await
+    }
+
+    /// Embedding failures (network, API) save the memory unembedded;
+    /// dimension mismatches are errors
+    async fn save_memory(&self, memory: &MemoryEntry) -> Result<(), String> {
+        let expected = self.embedder.dimensions();
+        if !memory.embedding.is_empty() {
+            check_dimensions(expected, &memory.embedding).map_err(|e| e.to_string())?;
+            return self.save_embedded(memory).await;
+        }
+
+        match self.embedder.embed(&memory.content.summary()).await {
+            Ok(embedding) => {
+                check_dimensions(expected, &embedding).map_err(|e| e.to_string())?;
+                let mut memory = memory.clone();
+                memory.embedding = embedding;
+                self.save_embedded(&memory).await
+            }
+            Err(e @ EmbedError::DimensionMismatch { .. }) => Err(e.to_string()),
+            Err(e) => {
+                warn!(memory_id = %memory.id.0, error = %e, "Saving memory without embedding");
+                self.inner.save_memory(memory).await
+            }
+        }
+    }
+
+    async fn get_memory(&self, memory_id: &MemoryId) -> Result<Option<MemoryEntry>, String> {
+        self.inner.get_memory(memory_id).await
+    }
+
+    async fn list_memories(&self) -> Result<Vec<MemoryEntry>, String> {
+        self.inner.list_memories().await
+    }
+
+    async fn delete_memory(&self, memory_id: &MemoryId) -> Result<(), String> {
+        self.inner.delete_memory(memory_id).await
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, String> {
+        self.inner.purge_expired(now).await
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.inner.now()
+    }
+
+    async fn search_memories(
+        &self,
+        query: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<ScoredMemory>, String> {
+        self.inner.search_memories(query, top_k).await
+    }
+
+    async fn save_saga(&self, saga_id: &str, plan: &serde_json::Value) -> Result<(), String> {
+        self.inner.save_saga(saga_id, plan).await
+    }
+
+    async fn load_saga(&self, saga_id: &str) -> Result<Option<serde_json::Value>, String> {
+        self.inner.load_saga(saga_id).await
+    }
+
+    async fn list_sagas(&self) -> Result<Vec<serde_json::Value>, String> {
+        self.inner.list_sagas().await
+    }
+
+    async fn delete_saga(&self, saga_id: &str) -> Result<(), String> {
+        self.inner.delete_saga(saga_id).await
+    }
+
+    async fn append_audit(&self, entry: &serde_json::Value) -> Result<(), String> {
+        self.inner.append_audit(entry).await
+    }
+
+    async fn list_audit(&self) -> Result<Vec<serde_json::Value>, String> {
+        self.inner.list_audit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStorage;
+    use ndc_core::{AccessControl, AgentId, MemoryContent, MemoryMetadata, MemoryStability};
+
+    /// Embeds every text as `[len, 1, 1, ...]` with `returned` components
+    #[derive(Debug)]
+    struct StubEmbedder {
+        dimensions: usize,
+        returned: usize,
+    }
+
+    #[async_trait]
+    impl Embedder for StubEmbedder {
+        fn dimensions(&self) -> usize {
+            self.dimensions
+        }
+
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbedError> {
+            let mut vector = vec![1.0; self.returned];
+            if let Some(first) = vector.first_mut() {
+                *first = text.len() as f32;
+            }
+            Ok(vector)
+        }
+    }
+
+    fn embedding_storage(
+        dimensions: usize,
+        returned: usize,
+    ) -> (Arc<MemoryStorage>, EmbeddingStorage) {
+        let inner = Arc::new(MemoryStorage::new());
+        let storage = EmbeddingStorage::new(
+            inner.clone(),
+            Arc::new(StubEmbedder {
+                dimensions,
+                returned,
+            }),
+        );
+        (inner, storage)
+    }
+
+    fn make_memory(text: &str, embedding: Vec<f32>) -> MemoryEntry {
+        let agent_id = AgentId::new();
+        MemoryEntry {
+            id: MemoryId::new(),
+            content: MemoryContent::General {
+                text: text.to_string(),
+                metadata: String::new(),
+            },
+            embedding,
+            relations: vec![],
+            metadata: MemoryMetadata {
+                stability: MemoryStability::Ephemeral,
+                created_at: chrono::Utc::now(),
+                created_by: agent_id,
+                source_task: TaskId::new(),
+                version: 1,
+                modified_at: None,
+                tags: vec![],
+                expires_at: None,
+            },
+            access_control: AccessControl::new(agent_id, MemoryStability::Ephemeral),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_saved_memories_get_embeddings() {
+        let (inner, storage) = embedding_storage(3, 3);
+
+        let memory = make_memory("use tokio", vec![]);
+        storage.save_memory(&memory).await.unwrap();
+        let saved = inner.get_memory(&memory.id).await.unwrap().unwrap();
+        let expected_len = memory.content.summary().len() as f32;
+        assert_eq!(saved.embedding, vec![expected_len, 1.0, 1.0]);
+
+        // A caller-provided embedding is kept as-is
+        let provided = make_memory("keep mine", vec![0.1, 0.2, 0.3]);
+        storage.save_memory(&provided).await.unwrap();
+        let saved = inner.get_memory(&provided.id).await.unwrap().unwrap();
+        assert_eq!(saved.embedding, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[tokio::test]
+    async fn test_dimension_mismatches_are_rejected() {
+        let (inner, storage) = embedding_storage(3, 3);
+        let provided = make_memory("too short", vec![0.1, 0.2]);
+        let err = storage.save_memory(&provided).await.unwrap_err();
+        assert!(err.contains("expected 3, got 2"), "{}", err);
+        assert!(inner.get_memory(&provided.id).await.unwrap().is_none());
+
+        // The embedder itself returning the wrong size is rejected too
+        let (inner, storage) = embedding_storage(3, 4);
+        let memory = make_memory("bad embedder", vec![]);
+        let err = storage.save_memory(&memory).await.unwrap_err();
+        assert!(err.contains("expected 3, got 4"), "{}", err);
+        assert!(inner.get_memory(&memory.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_near_duplicates_follow_dedup_policy() {
+        let owner = AgentId::new();
+        let first = || {
+            let mut memory = make_memory("use tokio for async", vec![1.0, 0.0, 0.0]);
+            memory.metadata.tags = vec!["async".to_string()];
+            memory.access_control = AccessControl::new(owner, MemoryStability::Ephemeral);
+            memory
+        };
+        let reworded = || {
+            let mut memory = make_memory("prefer tokio for async code", vec![0.99, 0.05, 0.0]);
+            memory.metadata.tags = vec!["tokio".to_string(), "async".to_string()];
+            memory.access_control = AccessControl::new(owner, MemoryStability::Ephemeral);
+            memory
+        };
+        let unrelated = make_memory("render markdown tables", vec![0.0, 1.0, 0.0]);
+
+        for (policy, expected) in [
+            (DedupPolicy::Allow, 3),
+            (DedupPolicy::Skip, 2),
+            (DedupPolicy::Merge, 2),
+        ] {
+            let (inner, storage) = embedding_storage(3, 3);
+            let storage = storage.with_dedup(policy, DEFAULT_DEDUP_THRESHOLD);
+            let original = first();
+            storage.save_memory(&original).await.unwrap();
+            storage.save_memory(&reworded()).await.unwrap();
+            storage.save_memory(&unrelated).await.unwrap();
+            assert_eq!(
+                inner.list_memories().await.unwrap().len(),
+                expected,
+                "{:?}",
+                policy
+            );
+
+            let kept = inner.get_memory(&original.id).await.unwrap().unwrap();
+            if policy == DedupPolicy::Merge {
+                assert_eq!(kept.metadata.version, 2);
+                assert!(kept.metadata.modified_at.is_some());
+                assert_eq!(kept.metadata.tags, vec!["async", "tokio"]);
+                assert_eq!(kept.content.summary(), original.content.summary());
+            } else {
+                assert_eq!(kept.metadata.version, 1);
+                assert!(kept.metadata.modified_at.is_none());
+            }
+
+            // Updating a memory in place is never treated as a duplicate
+            storage.save_memory(&kept).await.unwrap();
+            assert_eq!(inner.list_memories().await.unwrap().len(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_keeps_memories_of_other_owners_apart() {
+        let (inner, storage) = embedding_storage(3, 3);
+        let storage = storage.with_dedup(DedupPolicy::Merge, DEFAULT_DEDUP_THRESHOLD);
+        let mine = make_memory("use tokio for async", vec![1.0, 0.0, 0.0]);
+        let theirs = make_memory("prefer tokio for async code", vec![0.99, 0.05, 0.0]);
+        storage.save_memory(&mine).await.unwrap();
+        storage.save_memory(&theirs).await.unwrap();
+
+        assert_eq!(inner.list_memories().await.unwrap().len(), 2);
+        let kept = inner.get_memory(&mine.id).await.unwrap().unwrap();
+        assert_eq!(kept.metadata.version, 1);
+    }
+
+    /// Yields inside the duplicate lookup, so unserialized saves interleave there
+    struct YieldingStorage(Arc<MemoryStorage>);
+
+    #[async_trait]
+    impl Storage for YieldingStorage {
+        async fn save_task(&self, task: &Task) -> Result<(), String> {
+            self.0.save_task(task).await
+        }
+        async fn get_task(&self, task_id: &TaskId) -> Result<Option<Task>, String> {
+            self.0.get_task(task_id).await
+        }
+        async fn list_tasks(&self) -> Result<Vec<Task>, String> {
+            self.0.list_tasks().await
+        }
+        async fn list_tasks_by_tags(&self, tags: &[String]) -> Result<Vec<Task>, String> {
+            self.0.list_tasks_by_tags(tags).await
+        }
+        async fn save_memory(&self, memory: &MemoryEntry) -> Result<(), String> {
+            self.0.save_memory(memory).await
+        }
+        async fn get_memory(&self, memory_id: &MemoryId) -> Result<Option<MemoryEntry>, String> {
+            self.0.get_memory(memory_id).await
+        }
+        async fn list_memories(&self) -> Result<Vec<MemoryEntry>, String> {
+            self.0.list_memories().await
+        }
+        async fn delete_memory(&self, memory_id: &MemoryId) -> Result<(), String> {
+            self.0.delete_memory(memory_id).await
+        }
+        async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, String> {
+            Storage::purge_expired(&*self.0, now).await
+        }
+        async fn search_memories(
+            &self,
+            query: &[f32],
+            top_k: usize,
+        ) -> Result<Vec<ScoredMemory>, String> {
+            let hits = Storage::search_memories(&*self.0, query, top_k).await;
+            tokio::task::yield_now().await;
+            hits
+        }
+        async fn save_saga(&self, saga_id: &str, plan: &serde_json::Value) -> Result<(), String> {
+            self.0.save_saga(saga_id, plan).await
+        }
+        async fn load_saga(&self, saga_id: &str) -> Result<Option<serde_json::Value>, String> {
+            self.0.load_saga(saga_id).await
+        }
+        async fn list_sagas(&self) -> Result<Vec<serde_json::Value>, String> {
+            self.0.list_sagas().await
+        }
+        async fn delete_saga(&self, saga_id: &str) -> Result<(), String> {
+            self.0.delete_saga(saga_id).await
+        }
+        async fn append_audit(&self, entry: &serde_json::Value) -> Result<(), String> {
+            self.0.append_audit(entry).await
+        }
+        async fn list_audit(&self) -> Result<Vec<serde_json::Value>, String> {
+            self.0.list_audit().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicate_saves_store_one_memory() {
+        let inner = Arc::new(MemoryStorage::new());
+        let storage = EmbeddingStorage::new(
+            Arc::new(YieldingStorage(inner.clone())),
+            Arc::new(StubEmbedder {
+                dimensions: 3,
+                returned: 3,
+            }),
+        )
+        .with_dedup(DedupPolicy::Skip, DEFAULT_DEDUP_THRESHOLD);
+        let owner = AgentId::new();
+        let saves = (0..8).map(|_| {
+            let storage = storage.clone();
+            let mut memory = make_memory("use tokio for async", vec![1.0, 0.0, 0.0]);
+            memory.access_control = AccessControl::new(owner, MemoryStability::Ephemeral);
+            tokio::spawn(async move { storage.save_memory(&memory).await })
+        });
+        for save in saves.collect::<Vec<_>>() {
+            save.await.unwrap().unwrap();
+        }
+
+        assert_eq!(inner.list_memories().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_dedup_policy_parses() {
+        assert_eq!("Merge".parse::<DedupPolicy>(), Ok(DedupPolicy::Merge));
+        assert_eq!(" skip ".parse::<DedupPolicy>(), Ok(DedupPolicy::Skip));
+        assert!("drop".parse::<DedupPolicy>().is_err());
+    }
+}
@@ -0,0 +1,651 @@
+//! JSON Log Storage - append-only file persistence
+//!
+//! Every mutation is appended to `log.jsonl` as one event per line instead of
+//! rewriting the whole store, so a write costs O(event) rather than O(store).
+//! After `compact_every` events the state is compacted into `snapshot.json`
+//! (written to a temp file and renamed into place) and the log is truncated.
+//!
+//! Opening replays snapshot + log tail:
+//! - a torn last line (crash mid-append) is dropped and truncated away
+//! - events carry a sequence number; events already folded into the snapshot
+//!   are skipped, so a crash between snapshot rename and log truncation never
+//!   applies an event twice
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ndc_core::{MemoryEntry, MemoryId, ScoredMemory, Task, TaskId, search_cosine};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+use crate::trait_::{SharedStorage, Storage};
+
+/// Events appended before the log is compacted into a snapshot
+const DEFAULT_COMPACT_EVERY: usize = 1_000;
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const LOG_FILE: &str = "log.jsonl";
+
+/// JSON log storage error
+#[derive(Debug, thiserror::Error)]
+pub enum JsonLogError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Corrupt store: {0}")]
+    Corrupt(String),
+}
+
+/// A single mutation, as written to the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Event {
+    SaveTask { task: Box<Task> },
+    SaveMemory { memory: Box<MemoryEntry> },
+    DeleteMemory { id: MemoryId },
+    SaveSaga { id: String, plan: serde_json::Value },
+    DeleteSaga { id: String },
+    AppendAudit { entry: serde_json::Value },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LogRecord {
+    seq: u64,
+    #[serde(flatten)]
+    event: Event,
+}
+
+/// Materialized state, rebuilt from snapshot + log on open
+#[derive(Debug, Default)]
+struct State {
+    /// Sequence number of the last applied event
+    seq: u64,
+    tasks: HashMap<TaskId, Task>,
+    memories: HashMap<MemoryId, MemoryEntry>,
+    sagas: BTreeMap<String, serde_json::Value>,
+    audit: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    seq: u64,
+    tasks: Vec<Task>,
+    memories: Vec<MemoryEntry>,
+    sagas: BTreeMap<String, serde_json::Value>,
+    audit: Vec<serde_json::Value>,
+}
+
+impl State {
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::SaveTask { task } => {
+                self.tasks.insert(task.id, *task);
+            }
+            Event::SaveMemory { memory } => {
+                self.memories.insert(memory.id, *memory);
+            }
+            Event::DeleteMemory { id } => {
+                self.memories.remove(&id);
+            }
+            Event::SaveSaga { id, plan } => {
+                self.sagas.insert(id, plan);
+            }
+            Event::DeleteSaga { id } => {
+                self.sagas.remove(&id);
+            }
+            Event::AppendAudit { entry } => self.audit.push(entry),
+        }
+    }
+
+    fn to_snapshot(&self) -> Snapshot {
+        let mut tasks: Vec<Task> = self.tasks.values().cloned().collect();
+        tasks.sort_by_key(|task| task.id);
+        let mut memories: Vec<MemoryEntry> = self.memories.values().cloned().collect();
+        memories.sort_by_key(|memory| memory.id.0);
+        Snapshot {
+            seq: self.seq,
+            tasks,
+            memories,
+            sagas: self.sagas.clone(),
+            audit: self.audit.clone(),
+        }
+    }
+
+    fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self {
+            seq: snapshot.seq,
+            tasks: snapshot.tasks.into_iter().map(|t| (t.id, t)).collect(),
+            memories: snapshot.memories.into_iter().map(|m| (m.id, m)).collect(),
+            sagas: snapshot.sagas,
+            audit: snapshot.audit,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    dir: PathBuf,
+    state: State,
+    log: File,
+    /// Events appended since the last compaction
+    pending: usize,
+    compact_every: usize,
+    /// Write only this many bytes of the next record, then fail
+    #[cfg(test)]
+    fail_next_append_after: Option<usize>,
+}
+
+impl Inner {
+    fn append(&mut self, event: Event) -> Result<(), JsonLogError> {
+        let record = LogRecord {
+            seq: self.state.seq + 1,
+            event,
+        };
+        let mut line =
+            serde_json::to_vec(&record).map_err(|e| JsonLogError::Corrupt(e.to_string()))?;
+        line.push(b'\n');
+        let len = self.log.metadata()?.len();
+        if let Err(e) = self.write_line(&line) {
+            // Drop any torn bytes so later appends start on a clean line
+            if let Err(truncate) = self.log.set_len(len) {
+                warn!(error = %truncate, "Failed to truncate torn record in {}", LOG_FILE);
+            }
+            return Err(e);
+        }
+
+        self.state.seq = record.seq;
+        self.state.apply(record.event);
+        self.pending += 1;
+        if self.pending >= self.compact_every {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> Result<(), JsonLogError> {
+        #[cfg(test)]
+        if let Some(written) = self.fail_next_append_after.take() {
+            self.log.write_all(&line[..written.min(line.len())])?;
+            return Err(std::io::Error::other("injected write failure").into());
+        }
+        self.log.write_all(line)?;
+        self.log.sync_data()?;
+        Ok(())
+    }
+
+    fn compact(&mut self) -> Result<(), JsonLogError> {
+        let content = serde_json::to_vec(&self.state.to_snapshot())
+            .map_err(|e| JsonLogError::Corrupt(e.to_string()))?;
+        let tmp = self.dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&content)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp, self.dir.join(SNAPSHOT_FILE))?;
+        // A crash here leaves the old log behind; replay skips its events by seq
+        self.log.set_len(0)?;
+        self.log.sync_all()?;
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+/// Append-only JSON file storage
+#[derive(Debug, Clone)]
+pub struct JsonLogStorage {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl JsonLogStorage {
+    /// Open (or create) a store in `dir`
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, JsonLogError> {
+        Self::with_compact_every(dir, DEFAULT_COMPACT_EVERY)
+    }
+
+    /// Open a store that compacts after every `compact_every` appended events
+    pub fn with_compact_every(
+        dir: impl Into<PathBuf>,
+        compact_every: usize,
+    ) -> Result<Self, JsonLogError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        // Leftover from a compaction that crashed before the rename
+        let _ = std::fs::remove_file(dir.join(format!("{}.tmp", SNAPSHOT_FILE)));
+
+        let mut state = match std::fs::read(dir.join(SNAPSHOT_FILE)) {
+            Ok(content) => State::from_snapshot(
+                serde_json::from_slice(&content)
+                    .map_err(|e| JsonLogError::Corrupt(format!("{}: {}", SNAPSHOT_FILE, e)))?,
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => State::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let log_path = dir.join(LOG_FILE);
+        let pending = replay_log(&log_path, &mut state)?;
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Inner {
+                dir,
+                state,
+                log,
+                pending,
+                compact_every: compact_every.max(1),
+                #[cfg(test)]
+                fail_next_append_after: None,
+            })),
+        })
+    }
+
+    /// Fold the log into a fresh snapshot now
+    pub async fn compact(&self) -> Result<(), String> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || lock(&inner).compact().map_err(|e| e.to_string()))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    async fn append(&self, event: Event) -> Result<(), String> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || lock(&inner).append(event).map_err(|e| e.to_string()))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&State) -> R) -> R {
+        f(&lock(&self.inner).state)
+    }
+}
+
+fn lock(inner: &Mutex<Inner>) -> std::sync::MutexGuard<'_, Inner> {
+    inner
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Apply log events newer than the snapshot; returns how many were applied
+///
+/// A torn final line is truncated away. Corruption anywhere else is an error,
+/// since dropping a committed event would silently lose data.
+fn replay_log(path: &Path, state: &mut State) -> Result<usize, JsonLogError> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+    let mut valid_len = 0u64;
+    let mut applied = 0;
+    let mut line_no = 0;
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        line_no += 1;
+        let complete = line.ends_with(b"\n");
+        match serde_json::from_slice::<LogRecord>(&line) {
+            Ok(record) if complete => {
+                valid_len += read as u64;
+                if record.seq > state.seq {
+                    state.seq = record.seq;
+                    state.apply(record.event);
+                    applied += 1;
+                }
+            }
+            Ok(_) | Err(_) if !reader.fill_buf()?.is_empty() => {
+                return Err(JsonLogError::Corrupt(format!(
+                    "{} line {} is unreadable",
+                    LOG_FILE, line_no
+                )));
+            }
+            _ => {
+                warn!(
+                    line = line_no,
+                    "Dropping torn record at the end of {}", LOG_FILE
+                );
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)?
+                    .set_len(valid_len)?;
+                break;
+            }
+        }
+    }
+    Ok(applied)
+}
+
+#[async_trait]
+impl Storage for JsonLogStorage {
+    async fn save_task(&self, task: &Task) -> Result<(), String> {
+        self.append(Event::SaveTask {
+            task: Box::new(task.clone()),
+        })
+        .await
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<Option<Task>, String> {
+        Ok(self.read(|state| state.tasks.get(task_id).cloned()))
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<Task>, String> {
+        Ok(self.read(|state| state.tasks.values().cloned().collect()))
+    }
+
+    async fn list_tasks_by_tags(&self, tags: &[String]) -> Result<Vec<Task>, String> {
+        Ok(self.read(|state| {
+            state
+                .tasks
+                .values()
+                .filter(|task| task.has_tags(tags))
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn save_memory(&self, memory: &MemoryEntry) -> Result<(), String> {
+        self.append(Event::SaveMemory {
+            memory: Box::new(memory.clone()),
+        })
+        .await
+    }
+
+    async fn get_memory(&self, memory_id: &MemoryId) -> Result<Option<MemoryEntry>, String> {
+        Ok(self.read(|state| state.memories.get(memory_id).cloned()))
+    }
+
+    async fn list_memories(&self) -> Result<Vec<MemoryEntry>, String> {
+        let now = self.now();
+        Ok(self.read(|state| {
+            let mut memories: Vec<MemoryEntry> = state
+                .memories
+                .values()
+                .filter(|memory| !memory.is_expired(now))
+                .cloned()
+                .collect();
+            memories.sort_by_key(|memory| memory.id.0);
+            memories
+        }))
+    }
+
+    async fn search_memories(
+        &self,
+        query: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<ScoredMemory>, String> {
+        let now = self.now();
+        Ok(self.read(|state| {
+            let live = state
+                .memories
+                .values()
+                .filter(|memory| !memory.is_expired(now));
+            search_cosine(live, query, top_k)
+        }))
+    }
+
+    async fn delete_memory(&self, memory_id: &MemoryId) -> Result<(), String> {
+        self.append(Event::DeleteMemory { id: *memory_id }).await
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, String> {
+        let expired: Vec<MemoryId> = self.read(|state| {
+            state
+                .memories
+                .values()
+                .filter(|memory| memory.is_expired(now))
+                .map(|memory| memory.id)
+                .collect()
+        });
+        for id in &expired {
+            self.append(Event::DeleteMemory { id: *id }).await?;
+        }
+        Ok(expired.len())
+    }
+
+    async fn save_saga(&self, saga_id: &str, plan: &serde_json::Value) -> Result<(), String> {
+        self.append(Event::SaveSaga {
+            id: saga_id.to_string(),
+            plan: plan.clone(),
+        })
+        .await
+    }
+
+    async fn load_saga(&self, saga_id: &str) -> Result<Option<serde_json::Value>, String> {
+        Ok(self.read(|state| state.sagas.get(saga_id).cloned()))
+    }
+
+    async fn list_sagas(&self) -> Result<Vec<serde_json::Value>, String> {
+        Ok(self.read(|state| state.sagas.values().cloned().collect()))
+    }
+
+    async fn delete_saga(&self, saga_id: &str) -> Result<(), String> {
+        self.append(Event::DeleteSaga {
+            id: saga_id.to_string(),
+        })
+        .await
+    }
+
+    async fn append_audit(&self, entry: &serde_json::Value) -> Result<(), String> {
+        self.append(Event::AppendAudit {
+            entry: entry.clone(),
+        })
+        .await
+    }
+
+    async fn list_audit(&self) -> Result<Vec<serde_json::Value>, String> {
+        Ok(self.read(|state| state.audit.clone()))
+    }
+}
+
+/// Open a shared append-only JSON store in `dir`
+pub fn create_json_log_storage(dir: impl Into<PathBuf>) -> Result<SharedStorage, JsonLogError> {
+    Ok(Arc::new(JsonLogStorage::open(dir)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndc_core::{
+        AccessControl, AgentId, AgentRole, MemoryContent, MemoryMetadata, MemoryStability,
+    };
+    use tempfile::TempDir;
+
+    fn make_task(title: &str) -> Task {
+        Task::new(
+            title.to_string(),
+            "desc".to_string(),
+            AgentRole::Implementer,
+        )
+    }
+
+    fn make_ephemeral_memory(minutes_ago: i64) -> MemoryEntry {
+        MemoryEntry {
+            id: MemoryId::new(),
+            content: MemoryContent::General {
+                text: format!("{} minutes old", minutes_ago),
+                metadata: String::new(),
+            },
+            embedding: vec![1.0, 0.0],
+            relations: vec![],
+            metadata: MemoryMetadata {
+                stability: MemoryStability::Ephemeral,
+                created_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+                created_by: AgentId::new(),
+                source_task: TaskId::new(),
+                version: 1,
+                modified_at: None,
+                tags: vec![],
+                expires_at: None,
+            },
+            access_control: AccessControl::new(AgentId::new(), MemoryStability::Ephemeral),
+        }
+        .with_ttl(chrono::Duration::minutes(5))
+    }
+
+    #[tokio::test]
+    async fn test_replay_after_crash_mid_append() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = make_task("first");
+        let second = make_task("second");
+        {
+            let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+            storage.save_task(&first).await.unwrap();
+            storage.save_task(&second).await.unwrap();
+            storage
+                .append_audit(&serde_json::json!({"tool": "shell"}))
+                .await
+                .unwrap();
+        }
+
+        // Simulate a crash halfway through writing the next record
+        let log_path = temp_dir.path().join(LOG_FILE);
+        let intact_len = std::fs::metadata(&log_path).unwrap().len();
+        let mut log = OpenOptions::new().append(true).open(&log_path).unwrap();
+        log.write_all(br#"{"seq":4,"op":"save_task","task":{"id":"#)
+            .unwrap();
+        drop(log);
+
+        let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+        assert_eq!(storage.list_tasks().await.unwrap().len(), 2);
+        assert_eq!(
+            storage.get_task(&second.id).await.unwrap().unwrap().title,
+            "second"
+        );
+        assert_eq!(storage.list_audit().await.unwrap().len(), 1);
+        assert_eq!(std::fs::metadata(&log_path).unwrap().len(), intact_len);
+
+        // Appends after recovery land on a clean line boundary
+        let third = make_task("third");
+        storage.save_task(&third).await.unwrap();
+        drop(storage);
+        let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+        assert_eq!(storage.list_tasks().await.unwrap().len(), 3);
+
+        // Corruption before the tail is not silently skipped
+        let content = std::fs::read_to_string(&log_path).unwrap();
+        std::fs::write(&log_path, content.replacen("{\"seq\":1", "{\"seq\":", 1)).unwrap();
+        assert!(matches!(
+            JsonLogStorage::open(temp_dir.path()),
+            Err(JsonLogError::Corrupt(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_append_leaves_no_torn_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = make_task("first");
+        let lost = make_task("lost");
+        let third = make_task("third");
+        {
+            let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+            storage.save_task(&first).await.unwrap();
+
+            lock(&storage.inner).fail_next_append_after = Some(12);
+            assert!(storage.save_task(&lost).await.is_err());
+            assert!(storage.get_task(&lost.id).await.unwrap().is_none());
+
+            storage.save_task(&third).await.unwrap();
+        }
+
+        let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+        let mut titles: Vec<String> = storage
+            .list_tasks()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        titles.sort();
+        assert_eq!(titles, vec!["first", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_preserves_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = JsonLogStorage::with_compact_every(temp_dir.path(), 3).unwrap();
+        let mut task = make_task("draft");
+        storage.save_task(&task).await.unwrap();
+        storage
+            .save_saga("saga-1", &serde_json::json!({"steps": 1}))
+            .await
+            .unwrap();
+        storage
+            .save_saga("saga-2", &serde_json::json!({"steps": 2}))
+            .await
+            .unwrap();
+        // Third event triggered a compaction
+        let log_path = temp_dir.path().join(LOG_FILE);
+        assert_eq!(std::fs::metadata(&log_path).unwrap().len(), 0);
+        assert!(temp_dir.path().join(SNAPSHOT_FILE).exists());
+
+        task.title = "final".to_string();
+        storage.save_task(&task).await.unwrap();
+        storage.delete_saga("saga-1").await.unwrap();
+        storage
+            .append_audit(&serde_json::json!({"n": 1}))
+            .await
+            .unwrap();
+        storage
+            .append_audit(&serde_json::json!({"n": 2}))
+            .await
+            .unwrap();
+        let pre_compaction_log = std::fs::read(&log_path).unwrap();
+        storage.compact().await.unwrap();
+        drop(storage);
+
+        // Crash between snapshot rename and log truncation: the stale log
+        // must not re-apply events already in the snapshot
+        std::fs::write(&log_path, pre_compaction_log).unwrap();
+        let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+        let tasks = storage.list_tasks().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "final");
+        assert!(storage.load_saga("saga-1").await.unwrap().is_none());
+        assert_eq!(
+            storage.load_saga("saga-2").await.unwrap(),
+            Some(serde_json::json!({"steps": 2}))
+        );
+        assert_eq!(
+            storage.list_audit().await.unwrap(),
+            vec![serde_json::json!({"n": 1}), serde_json::json!({"n": 2})]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_memories_are_hidden_and_purge_survives_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        let expired = make_ephemeral_memory(10);
+        let live = make_ephemeral_memory(1);
+        {
+            let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+            storage.save_memory(&expired).await.unwrap();
+            storage.save_memory(&live).await.unwrap();
+
+            let listed = storage.list_memories().await.unwrap();
+            assert_eq!(listed.len(), 1);
+            assert_eq!(listed[0].id, live.id);
+            let hits = storage.search_memories(&[1.0, 0.0], 10).await.unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!(hits[0].memory.id, live.id);
+
+            assert_eq!(storage.purge_expired(Utc::now()).await.unwrap(), 1);
+        }
+
+        let storage = JsonLogStorage::open(temp_dir.path()).unwrap();
+        assert!(storage.get_memory(&expired.id).await.unwrap().is_none());
+        assert!(storage.get_memory(&live.id).await.unwrap().is_some());
+        assert_eq!(storage.purge_expired(Utc::now()).await.unwrap(), 0);
+    }
+}
@@ -2,14 +2,20 @@
 //
 // Abstract storage interface with pluggable backends
 
+pub mod embedding;
+pub mod json_log;
 pub mod memory;
+pub mod portable;
 pub mod trait_;
 
 #[cfg(feature = "sqlite")]
 pub mod sqlite;
 
+pub use embedding::{DEFAULT_DEDUP_THRESHOLD, DedupPolicy, EmbeddingStorage};
+pub use json_log::{JsonLogError, JsonLogStorage, create_json_log_storage};
 pub use memory::{MemoryStorage, create_memory_storage};
+pub use portable::{ImportMode, ImportReport, export_memories, import_memories};
 pub use trait_::*;
 
 #[cfg(feature = "sqlite")]
-pub use sqlite::{SqliteStorage, SqliteStorageError, create_sqlite_storage};
+pub use sqlite::{SqliteStorage, SqliteStorageError, TaskCursor, create_sqlite_storage};
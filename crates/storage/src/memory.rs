@@ -1,9 +1,13 @@
 //! In-memory storage implementation
 //!
-//! Provides basic task and memory persistence during execution
+//! Provides basic task, memory and saga persistence during execution
 
 use async_trait::async_trait;
-use ndc_core::{MemoryEntry, MemoryId, Task, TaskId};
+use chrono::{DateTime, Utc};
+use ndc_core::{
+    MemoryEntry, MemoryId, MemoryQuery, ScoredMemory, SharedClock, Task, TaskId, search_cosine,
+    system_clock,
+};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -19,8 +23,11 @@ const DEFAULT_MAX_MEMORIES: usize = 10_000;
 pub struct MemoryStorage {
     tasks: Mutex<(HashMap<TaskId, Task>, VecDeque<TaskId>)>,
     memories: Mutex<(HashMap<MemoryId, MemoryEntry>, VecDeque<MemoryId>)>,
+    sagas: Mutex<HashMap<String, serde_json::Value>>,
+    audit: Mutex<Vec<serde_json::Value>>,
     max_tasks: usize,
     max_memories: usize,
+    clock: SharedClock,
 }
 
 impl Default for MemoryStorage {
@@ -38,10 +45,65 @@ impl MemoryStorage {
         Self {
             tasks: Mutex::new((HashMap::new(), VecDeque::new())),
             memories: Mutex::new((HashMap::new(), VecDeque::new())),
+            sagas: Mutex::new(HashMap::new()),
+            audit: Mutex::new(Vec::new()),
             max_tasks,
             max_memories,
+            clock: system_clock(),
         }
     }
+
+    /// Use `clock` as the time source for TTL checks
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current time by the storage's clock, for `purge_expired` / `search_memories`
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Drop Ephemeral memories whose TTL has passed; returns the number removed
+    pub async fn purge_expired(&self, now: DateTime<Utc>) -> usize {
+        let mut guard = self.memories.lock().await;
+        let (map, order) = &mut *guard;
+        let before = map.len();
+        map.retain(|_, memory| !memory.is_expired(now));
+        order.retain(|id| map.contains_key(id));
+        before - map.len()
+    }
+
+    /// Cosine search over stored memories
+    ///
+    /// When `skip_expired_at` is set, memories already expired at that time are
+    /// left out of the results even if they have not been purged yet.
+    pub async fn search_memories(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        skip_expired_at: Option<DateTime<Utc>>,
+    ) -> Vec<ScoredMemory> {
+        let guard = self.memories.lock().await;
+        let live = guard
+            .0
+            .values()
+            .filter(|memory| skip_expired_at.is_none_or(|now| !memory.is_expired(now)));
+        search_cosine(live, query, top_k)
+    }
+
+    /// Memories passing every filter in `query`, ordered by `query.sort_by`
+    pub async fn query_memories(&self, query: &MemoryQuery) -> Vec<MemoryEntry> {
+        let guard = self.memories.lock().await;
+        let mut matches: Vec<MemoryEntry> = guard
+            .0
+            .values()
+            .filter(|memory| query.matches(memory))
+            .cloned()
+            .collect();
+        query.sort(&mut matches);
+        matches
+    }
 }
 
 #[async_trait]
@@ -106,6 +168,75 @@ impl Storage for MemoryStorage {
         let guard = self.memories.lock().await;
         Ok(guard.0.get(memory_id).cloned())
     }
+
+    async fn list_memories(&self) -> Result<Vec<MemoryEntry>, String> {
+        let now = MemoryStorage::now(self);
+        let guard = self.memories.lock().await;
+        let mut memories: Vec<MemoryEntry> = guard
+            .0
+            .values()
+            .filter(|memory| !memory.is_expired(now))
+            .cloned()
+            .collect();
+        memories.sort_by_key(|memory| memory.id.0);
+        Ok(memories)
+    }
+
+    async fn delete_memory(&self, memory_id: &MemoryId) -> Result<(), String> {
+        let mut guard = self.memories.lock().await;
+        let (map, order) = &mut *guard;
+        if map.remove(memory_id).is_some() {
+            order.retain(|id| id != memory_id);
+        }
+        Ok(())
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, String> {
+        Ok(MemoryStorage::purge_expired(self, now).await)
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        MemoryStorage::now(self)
+    }
+
+    async fn search_memories(
+        &self,
+        query: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<ScoredMemory>, String> {
+        let now = MemoryStorage::now(self);
+        Ok(MemoryStorage::search_memories(self, query, top_k, Some(now)).await)
+    }
+
+    async fn save_saga(&self, saga_id: &str, plan: &serde_json::Value) -> Result<(), String> {
+        self.sagas
+            .lock()
+            .await
+            .insert(saga_id.to_string(), plan.clone());
+        Ok(())
+    }
+
+    async fn load_saga(&self, saga_id: &str) -> Result<Option<serde_json::Value>, String> {
+        Ok(self.sagas.lock().await.get(saga_id).cloned())
+    }
+
+    async fn list_sagas(&self) -> Result<Vec<serde_json::Value>, String> {
+        Ok(self.sagas.lock().await.values().cloned().collect())
+    }
+
+    async fn delete_saga(&self, saga_id: &str) -> Result<(), String> {
+        self.sagas.lock().await.remove(saga_id);
+        Ok(())
+    }
+
+    async fn append_audit(&self, entry: &serde_json::Value) -> Result<(), String> {
+        self.audit.lock().await.push(entry.clone());
+        Ok(())
+    }
+
+    async fn list_audit(&self) -> Result<Vec<serde_json::Value>, String> {
+        Ok(self.audit.lock().await.clone())
+    }
 }
 
 /// Create a new shared in-memory storage
@@ -117,7 +248,8 @@ pub fn create_memory_storage() -> SharedStorage {
 mod tests {
     use super::*;
     use ndc_core::{
-        AccessControl, AgentId, AgentRole, MemoryContent, MemoryMetadata, MemoryStability,
+        AccessControl, AgentId, AgentRole, MemoryContent, MemoryMetadata, MemorySort,
+        MemoryStability, SearchMode,
     };
 
     fn make_task() -> Task {
@@ -147,6 +279,7 @@ mod tests {
                 version: 1,
                 modified_at: None,
                 tags: vec![],
+                expires_at: None,
             },
             access_control: AccessControl::new(agent_id, MemoryStability::Ephemeral),
         }
@@ -371,4 +504,191 @@ mod tests {
             .unwrap();
         assert_eq!(result.len(), 0);
     }
+
+    fn make_memory_with(stability: MemoryStability, ttl_secs: i64) -> MemoryEntry {
+        let mut memory = make_memory().with_ttl(chrono::Duration::seconds(ttl_secs));
+        memory.metadata.stability = stability;
+        memory.embedding = vec![1.0, 0.0];
+        memory
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_drops_only_expired_ephemeral() {
+        let storage = MemoryStorage::new();
+        let expired = make_memory_with(MemoryStability::Ephemeral, 10);
+        let live = make_memory_with(MemoryStability::Ephemeral, 3600);
+        let no_ttl = make_memory();
+        storage.save_memory(&expired).await.unwrap();
+        storage.save_memory(&live).await.unwrap();
+        storage.save_memory(&no_ttl).await.unwrap();
+
+        let now = expired.metadata.created_at + chrono::Duration::seconds(60);
+        assert_eq!(storage.purge_expired(now).await, 1);
+        assert!(storage.get_memory(&expired.id).await.unwrap().is_none());
+        assert!(storage.get_memory(&live.id).await.unwrap().is_some());
+        assert!(storage.get_memory(&no_ttl.id).await.unwrap().is_some());
+        assert_eq!(storage.memories.lock().await.1.len(), 2);
+
+        assert_eq!(storage.purge_expired(now).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expiry_follows_mock_clock() {
+        let clock = ndc_core::MockClock::default();
+        let storage = MemoryStorage::new().with_clock(Arc::new(clock.clone()));
+        let mut memory = make_memory();
+        memory.metadata.created_at = storage.now();
+        memory.embedding = vec![1.0, 0.0];
+        let memory = memory.with_ttl(chrono::Duration::minutes(5));
+        storage.save_memory(&memory).await.unwrap();
+
+        clock.advance(chrono::Duration::minutes(4));
+        assert_eq!(
+            storage
+                .search_memories(&[1.0, 0.0], 10, Some(storage.now()))
+                .await
+                .len(),
+            1
+        );
+        assert_eq!(storage.purge_expired(storage.now()).await, 0);
+
+        clock.advance(chrono::Duration::minutes(1));
+        assert!(
+            storage
+                .search_memories(&[1.0, 0.0], 10, Some(storage.now()))
+                .await
+                .is_empty()
+        );
+        assert_eq!(storage.purge_expired(storage.now()).await, 1);
+        assert!(storage.get_memory(&memory.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_never_touches_verified_or_canonical() {
+        let storage = MemoryStorage::new();
+        let verified = make_memory_with(MemoryStability::Verified, 1);
+        let canonical = make_memory_with(MemoryStability::Canonical, 1);
+        storage.save_memory(&verified).await.unwrap();
+        storage.save_memory(&canonical).await.unwrap();
+
+        let later = verified.metadata.created_at + chrono::Duration::days(365);
+        assert_eq!(storage.purge_expired(later).await, 0);
+        assert!(storage.get_memory(&verified.id).await.unwrap().is_some());
+        assert!(storage.get_memory(&canonical.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_search_memories_can_skip_expired() {
+        let storage = MemoryStorage::new();
+        let expired = make_memory_with(MemoryStability::Ephemeral, 10);
+        let live = make_memory_with(MemoryStability::Ephemeral, 3600);
+        storage.save_memory(&expired).await.unwrap();
+        storage.save_memory(&live).await.unwrap();
+        let now = expired.metadata.created_at + chrono::Duration::seconds(60);
+
+        let all = storage.search_memories(&[1.0, 0.0], 10, None).await;
+        assert_eq!(all.len(), 2);
+
+        let fresh = storage.search_memories(&[1.0, 0.0], 10, Some(now)).await;
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].memory.id, live.id);
+    }
+
+    #[tokio::test]
+    async fn test_search_memories_with_mode() {
+        let storage = MemoryStorage::new();
+        let mut exact = make_memory();
+        exact.embedding = vec![1.0, 0.0, 0.0];
+        let mut orthogonal = make_memory();
+        orthogonal.embedding = vec![0.0, 1.0, 0.0];
+        let mut other_dims = make_memory();
+        other_dims.embedding = vec![1.0, 0.0];
+        for memory in [&exact, &orthogonal, &other_dims] {
+            storage.save_memory(memory).await.unwrap();
+        }
+
+        for mode in [SearchMode::SimHash, SearchMode::Cosine, SearchMode::Hybrid] {
+            let hits = Storage::search_memories_with_mode(&storage, &[1.0, 0.0, 0.0], 10, mode)
+                .await
+                .unwrap();
+            assert_eq!(hits[0].memory.id, exact.id, "{:?}", mode);
+            assert!((hits[0].score - 1.0).abs() < 1e-6, "{:?}", mode);
+            assert!(
+                hits.iter()
+                    .all(|h| h.memory.id != other_dims.id || mode == SearchMode::Cosine)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_memories_sort_and_date_window() {
+        let storage = MemoryStorage::new();
+        let base = chrono::Utc::now() - chrono::Duration::days(10);
+        let mut ids = Vec::new();
+        for (days, stability, text) in [
+            (0, MemoryStability::Verified, "oldest note"),
+            (3, MemoryStability::Canonical, "middle rust note"),
+            (6, MemoryStability::Ephemeral, "rust newest"),
+        ] {
+            let mut memory = make_memory();
+            memory.metadata.created_at = base + chrono::Duration::days(days);
+            memory.metadata.stability = stability;
+            memory.content = MemoryContent::General {
+                text: text.to_string(),
+                metadata: String::new(),
+            };
+            if days == 0 {
+                memory.metadata.tags = vec!["rust".to_string()];
+            }
+            ids.push(memory.id);
+            storage.save_memory(&memory).await.unwrap();
+        }
+        let order = |entries: Vec<MemoryEntry>| -> Vec<usize> {
+            entries
+                .iter()
+                .map(|e| ids.iter().position(|id| *id == e.id).unwrap())
+                .collect()
+        };
+        let sorted = |sort_by| MemoryQuery {
+            sort_by,
+            ..Default::default()
+        };
+
+        let newest = storage.query_memories(&sorted(MemorySort::Newest)).await;
+        assert_eq!(order(newest), vec![2, 1, 0]);
+        let oldest = storage.query_memories(&sorted(MemorySort::Oldest)).await;
+        assert_eq!(order(oldest), vec![0, 1, 2]);
+        let stable = storage.query_memories(&sorted(MemorySort::Stability)).await;
+        assert_eq!(order(stable), vec![1, 0, 2]);
+
+        // Content matches outrank the tag-only match, then stability decides
+        let relevance = MemoryQuery {
+            query: Some("rust".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            order(storage.query_memories(&relevance).await),
+            vec![1, 2, 0]
+        );
+
+        let window = MemoryQuery {
+            created_after: Some(base + chrono::Duration::days(3)),
+            created_before: Some(base + chrono::Duration::days(6)),
+            ..Default::default()
+        };
+        assert_eq!(order(storage.query_memories(&window).await), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_save_load_delete_saga() {
+        let storage = MemoryStorage::new();
+        let plan = serde_json::json!({ "id": "saga-1", "steps": [] });
+
+        storage.save_saga("saga-1", &plan).await.unwrap();
+        assert_eq!(storage.load_saga("saga-1").await.unwrap(), Some(plan));
+        assert_eq!(storage.list_sagas().await.unwrap().len(), 1);
+
+        storage.delete_saga("saga-1").await.unwrap();
+        assert!(storage.load_saga("saga-1").await.unwrap().is_none());
+    }
 }
@@ -0,0 +1,215 @@
+//! Memory export/import - JSON Lines portability
+//!
+//! `export_memories` writes every `MemoryEntry` (embedding, relations,
+//! metadata and access control included) as one JSON object per line, so a
+//! knowledge base can move between machines. `import_memories` reads such a
+//! file into any `Storage`; saving through `EmbeddingStorage` re-embeds
+//! entries that arrive without a vector, rebuilding the search index.
+
+use ndc_core::MemoryEntry;
+use std::io::{BufRead, Write};
+
+use crate::trait_::Storage;
+
+/// How `import_memories` treats memories already in the store
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep existing memories; imported ones whose id is taken are skipped
+    #[default]
+    Merge,
+    /// Delete every existing memory before importing
+    Replace,
+}
+
+/// Outcome of `import_memories`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Duplicates skipped in `Merge` mode
+    pub skipped: usize,
+    /// Existing memories deleted in `Replace` mode
+    pub removed: usize,
+}
+
+/// Write all memories of `storage` to `out` as JSON Lines; returns the count
+pub async fn export_memories<W: Write>(
+    storage: &dyn Storage,
+    out: &mut W,
+) -> Result<usize, String> {
+    let memories = storage.list_memories().await?;
+    for memory in &memories {
+        serde_json::to_writer(&mut *out, memory).map_err(|e| e.to_string())?;
+        out.write_all(b"\n").map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(memories.len())
+}
+
+/// Import JSON Lines written by `export_memories`
+///
+/// The whole input is parsed before the store is touched, so a malformed
+/// line never leaves a half-replaced store. Blank lines are ignored.
+pub async fn import_memories<R: BufRead>(
+    storage: &dyn Storage,
+    input: R,
+    mode: ImportMode,
+) -> Result<ImportReport, String> {
+    let mut memories = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let memory: MemoryEntry = serde_json::from_str(&line)
+            .map_err(|e| format!("line {}: invalid memory: {}", index + 1, e))?;
+        memories.push(memory);
+    }
+
+    let mut report = ImportReport::default();
+    if mode == ImportMode::Replace {
+        for existing in storage.list_memories().await? {
+            storage.delete_memory(&existing.id).await?;
+            report.removed += 1;
+        }
+    }
+    for memory in memories {
+        if mode == ImportMode::Merge && storage.get_memory(&memory.id).await?.is_some() {
+            report.skipped += 1;
+            continue;
+        }
+        storage.save_memory(&memory).await?;
+        report.imported += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStorage;
+    use ndc_core::{
+        AccessControl, AgentId, AgentRole, MemoryContent, MemoryId, MemoryMetadata,
+        MemoryStability, Relation, RelationType, TaskId,
+    };
+
+    fn memory(text: &str, embedding: Vec<f32>) -> MemoryEntry {
+        let owner = AgentId::new();
+        let mut access_control = AccessControl::new(owner, MemoryStability::Verified);
+        access_control.write_roles.insert(AgentRole::Reviewer);
+        MemoryEntry {
+            id: MemoryId::new(),
+            content: MemoryContent::General {
+                text: text.to_string(),
+                metadata: String::new(),
+            },
+            embedding,
+            relations: vec![Relation {
+                target: MemoryId::new(),
+                relation_type: RelationType::Reference,
+                strength: 0.5,
+            }],
+            metadata: MemoryMetadata {
+                stability: MemoryStability::Verified,
+                created_at: chrono::Utc::now(),
+                created_by: owner,
+                source_task: TaskId::new(),
+                version: 3,
+                modified_at: None,
+                tags: vec!["portable".to_string()],
+                expires_at: None,
+            },
+            access_control,
+        }
+    }
+
+    async fn seeded() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        for (text, embedding) in [
+            ("use tokio", vec![1.0, 0.0, 0.0]),
+            ("prefer thiserror", vec![0.6, 0.8, 0.0]),
+            ("no unwrap in lib code", vec![0.0, 0.0, 1.0]),
+        ] {
+            storage.save_memory(&memory(text, embedding)).await.unwrap();
+        }
+        storage
+    }
+
+    async fn export(storage: &dyn Storage) -> Vec<u8> {
+        let mut out = Vec::new();
+        export_memories(storage, &mut out).await.unwrap();
+        out
+    }
+
+    /// JSON of `memories`, with the role sets sorted so order does not matter
+    fn json(memories: Vec<MemoryEntry>) -> serde_json::Value {
+        let mut value = serde_json::to_value(memories).unwrap();
+        for memory in value.as_array_mut().unwrap() {
+            for roles in ["read_roles", "write_roles"] {
+                let roles = memory["access_control"][roles].as_array_mut().unwrap();
+                roles.sort_by_key(|role| role.to_string());
+            }
+        }
+        value
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip_preserves_search() {
+        let source = seeded().await;
+        let exported = export(&source).await;
+        assert_eq!(exported.iter().filter(|b| **b == b'\n').count(), 3);
+
+        let target = MemoryStorage::new();
+        let report = import_memories(&target, exported.as_slice(), ImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!(report.imported, 3);
+
+        // Every field, access control included, round-trips
+        assert_eq!(
+            json(target.list_memories().await.unwrap()),
+            json(source.list_memories().await.unwrap())
+        );
+        let query = [0.7, 0.7, 0.0];
+        let ranked = |hits: Vec<ndc_core::ScoredMemory>| {
+            hits.into_iter()
+                .map(|hit| (hit.memory.id, hit.score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked(target.search_memories(&query, 10, None).await),
+            ranked(source.search_memories(&query, 10, None).await)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_skips_duplicates_and_replace_clears_store() {
+        let source = seeded().await;
+        let exported = export(&source).await;
+
+        let target = MemoryStorage::new();
+        let local = memory("local only", vec![0.0, 1.0, 0.0]);
+        target.save_memory(&local).await.unwrap();
+        import_memories(&target, exported.as_slice(), ImportMode::Merge)
+            .await
+            .unwrap();
+
+        let again = import_memories(&target, exported.as_slice(), ImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!((again.imported, again.skipped), (0, 3));
+        assert_eq!(target.list_memories().await.unwrap().len(), 4);
+
+        let replaced = import_memories(&target, exported.as_slice(), ImportMode::Replace)
+            .await
+            .unwrap();
+        assert_eq!((replaced.removed, replaced.imported), (4, 3));
+        assert!(target.get_memory(&local.id).await.unwrap().is_none());
+
+        // A malformed line aborts before anything is deleted
+        let err = import_memories(&target, &b"{\"id\": 1}\n"[..], ImportMode::Replace)
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("line 1:"), "{}", err);
+        assert_eq!(target.list_memories().await.unwrap().len(), 3);
+    }
+}
@@ -1,6 +1,6 @@
 //! SQLite Storage - Persistent storage using SQLite
 //!
-//! Provides persistent task and memory storage using SQLite database
+//! Provides persistent task, memory and saga storage using SQLite database
 //!
 //! Features:
 //! - Persistent storage across sessions
@@ -8,8 +8,10 @@
 //! - Automatic schema migration
 //! - Async-friendly using spawn_blocking
 
+use crate::TaskFilter;
 use async_trait::async_trait;
-use ndc_core::{MemoryEntry, MemoryId, Task, TaskId};
+use chrono::{DateTime, Utc};
+use ndc_core::{MemoryEntry, MemoryId, MemoryQuery, Task, TaskId};
 use r2d2::Pool;
 use rusqlite::{self, OptionalExtension};
 use std::path::PathBuf;
@@ -65,6 +67,28 @@ pub enum SqliteStorageError {
     InvalidData(String),
 }
 
+/// Opaque cursor returned by `query_tasks_paged`, encoding the last-seen task id
+///
+/// Pages are ordered by ULID, so tasks inserted after a cursor was issued
+/// only ever appear after it and never shift earlier pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskCursor(TaskId);
+
+impl TaskCursor {
+    /// Serialize the cursor for handing to a client
+    pub fn to_token(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Restore a cursor from a token produced by `to_token`
+    pub fn from_token(token: &str) -> Result<Self, SqliteStorageError> {
+        token
+            .parse()
+            .map(Self)
+            .map_err(|e| SqliteStorageError::InvalidData(format!("invalid task cursor: {}", e)))
+    }
+}
+
 /// Maximum number of connections in the pool
 const MAX_POOL_SIZE: u32 = 4;
 
@@ -134,13 +158,31 @@ impl SqliteStorage {
                 verdict TEXT,
                 quality_gate TEXT,
                 snapshots TEXT NOT NULL DEFAULT '[]',
-                lightweight_snapshots TEXT NOT NULL DEFAULT '[]'
+                lightweight_snapshots TEXT NOT NULL DEFAULT '[]',
+                depends_on TEXT NOT NULL DEFAULT '[]'
             )
             "#,
             [],
         )
         .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
 
+        // Databases created before task dependencies lack the column
+        let has_depends_on: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name = 'depends_on'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count > 0)
+            .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
+        if !has_depends_on {
+            conn.execute(
+                "ALTER TABLE tasks ADD COLUMN depends_on TEXT NOT NULL DEFAULT '[]'",
+                [],
+            )
+            .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
+        }
+
         conn.execute(
             r#"
             CREATE TABLE IF NOT EXISTS memories (
@@ -156,6 +198,30 @@ impl SqliteStorage {
         )
         .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
 
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS sagas (
+                id TEXT PRIMARY KEY,
+                plan TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+            [],
+        )
+        .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
+
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS permission_audit (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                entry TEXT NOT NULL,
+                recorded_at TEXT NOT NULL
+            )
+            "#,
+            [],
+        )
+        .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
+
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_tasks_state ON tasks(state)",
             [],
@@ -175,6 +241,108 @@ impl SqliteStorage {
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// Page over tasks in ULID order, starting after `cursor`
+    ///
+    /// Returns at most `limit` tasks matching `filter` and the cursor for the
+    /// next page, or `None` once the last matching row has been returned.
+    pub async fn query_tasks_paged(
+        &self,
+        cursor: Option<TaskCursor>,
+        limit: usize,
+        filter: &TaskFilter,
+    ) -> Result<(Vec<Task>, Option<TaskCursor>), SqliteStorageError> {
+        if limit == 0 {
+            return Err(SqliteStorageError::InvalidData(
+                "page limit must be greater than zero".to_string(),
+            ));
+        }
+
+        let (mut clauses, mut params) = task_filter_clauses(filter)?;
+        if let Some(cursor) = &cursor {
+            clauses.push("id > ?");
+            params.push(cursor.0.to_string().into());
+        }
+
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        // Fetch one extra row to learn whether another page exists.
+        let sql = format!(
+            "SELECT {} FROM tasks {} ORDER BY id ASC LIMIT {}",
+            TASK_COLUMNS,
+            where_clause,
+            limit + 1
+        );
+
+        let mut tasks = run_sqlite(self.pool.clone(), move |conn| {
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(params), task_from_row)
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(SqliteStorageError::DatabaseError)?;
+
+        let next = if tasks.len() > limit {
+            tasks.truncate(limit);
+            tasks.last().map(|task| TaskCursor(task.id))
+        } else {
+            None
+        };
+
+        Ok((tasks, next))
+    }
+
+    /// Memories passing every filter in `query`, ordered by `query.sort_by`
+    ///
+    /// The creation-time window is evaluated in SQL; the remaining filters
+    /// and the ordering use `MemoryQuery::matches` and `MemoryQuery::sort`.
+    pub async fn query_memories(
+        &self,
+        query: &MemoryQuery,
+    ) -> Result<Vec<MemoryEntry>, SqliteStorageError> {
+        let mut clauses = Vec::new();
+        let mut params: Vec<rusqlite::types::Value> = Vec::new();
+        // julianday() copes with the variable fractional seconds chrono writes,
+        // comparing at millisecond precision
+        if let Some(after) = query.created_after {
+            clauses.push("julianday(json_extract(metadata, '$.created_at')) >= julianday(?)");
+            params.push(after.to_rfc3339().into());
+        }
+        if let Some(before) = query.created_before {
+            clauses.push("julianday(json_extract(metadata, '$.created_at')) < julianday(?)");
+            params.push(before.to_rfc3339().into());
+        }
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        let sql = format!("SELECT {} FROM memories {}", MEMORY_COLUMNS, where_clause);
+
+        let memories = run_sqlite(self.pool.clone(), move |conn| {
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(params), memory_from_row)
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(SqliteStorageError::DatabaseError)?;
+
+        let mut matches: Vec<MemoryEntry> = memories
+            .into_iter()
+            .filter(|memory| query.matches(memory))
+            .collect();
+        query.sort(&mut matches);
+        Ok(matches)
+    }
 }
 
 /// Helper function to run blocking SQLite operations using the connection pool
@@ -191,8 +359,143 @@ where
     .map_err(|e| e.to_string())?
 }
 
+/// Columns selected for a full task row, in the order `task_from_row` expects
+const TASK_COLUMNS: &str = "id, title, description, state, created_at, updated_at, \
+     created_by, priority, metadata, steps, intent, verdict, \
+     quality_gate, snapshots, lightweight_snapshots, depends_on";
+
+/// Decode a JSON column, surfacing failures as a rusqlite conversion error
+fn json_column<T: serde::de::DeserializeOwned>(
+    index: usize,
+    json: &str,
+) -> Result<T, rusqlite::Error> {
+    serde_json::from_str(json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// Columns selected for a full memory row, in the order `memory_from_row` expects
+const MEMORY_COLUMNS: &str = "id, content, embedding, relations, metadata, access_control";
+
+/// Build a `MemoryEntry` from a row selected with `MEMORY_COLUMNS`
+fn memory_from_row(row: &rusqlite::Row<'_>) -> Result<MemoryEntry, rusqlite::Error> {
+    let id: String = row.get(0)?;
+    let content_json: String = row.get(1)?;
+    let embedding_json: String = row.get(2)?;
+    let relations_json: String = row.get(3)?;
+    let metadata_json: String = row.get(4)?;
+    let access_control_json: String = row.get(5)?;
+
+    let id: uuid::Uuid = id.parse().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok(MemoryEntry {
+        id: MemoryId(id),
+        content: json_column(1, &content_json)?,
+        embedding: json_column(2, &embedding_json)?,
+        relations: json_column(3, &relations_json)?,
+        metadata: json_column(4, &metadata_json)?,
+        access_control: json_column(5, &access_control_json)?,
+    })
+}
+
+/// `WHERE` clauses and bound parameters for a `TaskFilter`
+///
+/// Times are compared with julianday(), as chrono writes a variable number
+/// of fractional digits that breaks plain string comparison.
+fn task_filter_clauses(
+    filter: &TaskFilter,
+) -> Result<(Vec<&'static str>, Vec<rusqlite::types::Value>), SqliteStorageError> {
+    let mut clauses = Vec::new();
+    let mut params: Vec<rusqlite::types::Value> = Vec::new();
+    let invalid = |e: serde_json::Error| SqliteStorageError::InvalidData(e.to_string());
+
+    if let Some(state) = &filter.state {
+        clauses.push("state = ?");
+        params.push(serde_json::to_string(state).map_err(invalid)?.into());
+    }
+    if let Some(role) = &filter.created_by {
+        clauses.push("created_by = ?");
+        params.push(serde_json::to_string(role).map_err(invalid)?.into());
+    }
+    if let Some(after) = filter.created_after {
+        clauses.push("julianday(created_at) >= julianday(?)");
+        params.push(after.to_rfc3339().into());
+    }
+    if let Some(before) = filter.created_before {
+        clauses.push("julianday(created_at) < julianday(?)");
+        params.push(before.to_rfc3339().into());
+    }
+    if let Some(since) = filter.active_since {
+        clauses.push(
+            "(julianday(created_at) >= julianday(?) OR julianday(updated_at) >= julianday(?))",
+        );
+        params.push(since.to_rfc3339().into());
+        params.push(since.to_rfc3339().into());
+    }
+    Ok((clauses, params))
+}
+
+/// Build a `Task` from a row selected with `TASK_COLUMNS`
+fn task_from_row(row: &rusqlite::Row<'_>) -> Result<Task, rusqlite::Error> {
+    let id: String = row.get(0)?;
+    let state: String = row.get(3)?;
+    let metadata_json: String = row.get(8)?;
+    let steps_json: String = row.get(9)?;
+    let intent_json: Option<String> = row.get(10)?;
+    let verdict_json: Option<String> = row.get(11)?;
+    let quality_gate_json: Option<String> = row.get(12)?;
+    let snapshots_json: String = row.get(13)?;
+    let lightweight_snapshots_json: String = row.get(14)?;
+    let depends_on_json: String = row.get(15)?;
+
+    let id: TaskId = id.parse().map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok(Task {
+        id,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        state: json_column(3, &state)?,
+        allowed_transitions: Vec::new(),
+        steps: json_column(9, &steps_json)?,
+        quality_gate: quality_gate_json.map(|s| json_column(12, &s)).transpose()?,
+        snapshots: json_column(13, &snapshots_json)?,
+        lightweight_snapshots: json_column(14, &lightweight_snapshots_json)?,
+        metadata: json_column(8, &metadata_json)?,
+        intent: intent_json.map(|s| json_column(10, &s)).transpose()?,
+        verdict: verdict_json.map(|s| json_column(11, &s)).transpose()?,
+        depends_on: json_column(15, &depends_on_json)?,
+    })
+}
+
 #[async_trait]
 impl crate::Storage for SqliteStorage {
+    async fn query_tasks(&self, filter: &TaskFilter) -> Result<Vec<Task>, String> {
+        let (clauses, params) = task_filter_clauses(filter).map_err(|e| e.to_string())?;
+        let where_clause = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        let sql = format!(
+            "SELECT {} FROM tasks {} ORDER BY julianday(updated_at) DESC, id DESC",
+            TASK_COLUMNS, where_clause
+        );
+
+        run_sqlite(self.pool.clone(), move |conn| {
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(params), task_from_row)
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
+        })
+        .await
+    }
+
     async fn save_task(&self, task: &Task) -> Result<(), String> {
         let pool = self.pool.clone();
 
@@ -211,6 +514,7 @@ impl crate::Storage for SqliteStorage {
         let snapshots = serde_json::to_string(&task.snapshots).map_err(|e| e.to_string())?;
         let lightweight_snapshots =
             serde_json::to_string(&task.lightweight_snapshots).map_err(|e| e.to_string())?;
+        let depends_on = serde_json::to_string(&task.depends_on).map_err(|e| e.to_string())?;
         let intent = task
             .intent
             .as_ref()
@@ -233,8 +537,8 @@ impl crate::Storage for SqliteStorage {
                 INSERT INTO tasks (
                     id, title, description, state, created_at, updated_at,
                     created_by, priority, metadata, steps, intent, verdict,
-                    quality_gate, snapshots, lightweight_snapshots
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    quality_gate, snapshots, lightweight_snapshots, depends_on
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 ON CONFLICT(id) DO UPDATE SET
                     title = excluded.title,
                     description = excluded.description,
@@ -243,7 +547,8 @@ impl crate::Storage for SqliteStorage {
                     metadata = excluded.metadata,
                     steps = excluded.steps,
                     snapshots = excluded.snapshots,
-                    lightweight_snapshots = excluded.lightweight_snapshots
+                    lightweight_snapshots = excluded.lightweight_snapshots,
+                    depends_on = excluded.depends_on
                 "#,
                 rusqlite::params![
                     task_id,
@@ -261,6 +566,7 @@ impl crate::Storage for SqliteStorage {
                     quality_gate,
                     snapshots,
                     lightweight_snapshots,
+                    depends_on,
                 ],
             )
             .map_err(|e| e.to_string())
@@ -275,129 +581,10 @@ impl crate::Storage for SqliteStorage {
         let task_id_str = task_id.to_string();
 
         run_sqlite(pool, move |conn| {
-            let mut stmt = conn
-                .prepare(
-                    r#"
-                SELECT id, title, description, state, created_at, updated_at,
-                       created_by, priority, metadata, steps, intent, verdict,
-                       quality_gate, snapshots, lightweight_snapshots
-                FROM tasks WHERE id = ?
-                "#,
-                )
-                .map_err(|e| e.to_string())?;
+            let sql = format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS);
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
 
-            let task_opt = stmt
-                .query_row([&task_id_str], |row| {
-                    let id: String = row.get(0)?;
-                    let title: String = row.get(1)?;
-                    let description: String = row.get(2)?;
-                    let state: String = row.get(3)?;
-                    let _created_at: String = row.get(4)?;
-                    let _updated_at: String = row.get(5)?;
-                    let _created_by: String = row.get(6)?;
-                    let _priority: String = row.get(7)?;
-                    let metadata_json: String = row.get(8)?;
-                    let steps_json: String = row.get(9)?;
-                    let intent_json: Option<String> = row.get(10)?;
-                    let verdict_json: Option<String> = row.get(11)?;
-                    let quality_gate_json: Option<String> = row.get(12)?;
-                    let snapshots_json: String = row.get(13)?;
-                    let lightweight_snapshots_json: String = row.get(14)?;
-
-                    let task_id_parsed: TaskId = id.parse().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?;
-                    let state_parsed: ndc_core::TaskState =
-                        serde_json::from_str(&state).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let metadata: ndc_core::TaskMetadata = serde_json::from_str(&metadata_json)
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let steps: Vec<ndc_core::ExecutionStep> = serde_json::from_str(&steps_json)
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let intent = intent_json
-                        .map(|s| serde_json::from_str(&s))
-                        .transpose()
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let verdict = verdict_json
-                        .map(|s| serde_json::from_str(&s))
-                        .transpose()
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let quality_gate = quality_gate_json
-                        .map(|s| serde_json::from_str(&s))
-                        .transpose()
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let snapshots: Vec<ndc_core::GitWorktreeSnapshot> =
-                        serde_json::from_str(&snapshots_json).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let lightweight_snapshots: Vec<ndc_core::LightweightSnapshot> =
-                        serde_json::from_str(&lightweight_snapshots_json).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-
-                    Ok(Task {
-                        id: task_id_parsed,
-                        title,
-                        description,
-                        state: state_parsed,
-                        allowed_transitions: Vec::new(),
-                        steps,
-                        quality_gate,
-                        snapshots,
-                        lightweight_snapshots,
-                        metadata,
-                        intent,
-                        verdict,
-                    })
-                })
-                .optional();
+            let task_opt = stmt.query_row([&task_id_str], task_from_row).optional();
 
             task_opt.map_err(|e| e.to_string())
         })
@@ -408,124 +595,14 @@ impl crate::Storage for SqliteStorage {
         let pool = self.pool.clone();
 
         run_sqlite(pool, move |conn| {
-            let mut stmt = conn
-                .prepare(
-                    r#"
-                SELECT id, title, description, state, created_at, updated_at,
-                       created_by, priority, metadata, steps, intent, verdict,
-                       quality_gate, snapshots, lightweight_snapshots
-                FROM tasks ORDER BY created_at DESC
-                "#,
-                )
-                .map_err(|e| e.to_string())?;
+            let sql = format!(
+                "SELECT {} FROM tasks ORDER BY created_at DESC",
+                TASK_COLUMNS
+            );
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
 
             let rows = stmt
-                .query_map([], |row| {
-                    let id: String = row.get(0)?;
-                    let title: String = row.get(1)?;
-                    let description: String = row.get(2)?;
-                    let state: String = row.get(3)?;
-                    let metadata_json: String = row.get(8)?;
-                    let steps_json: String = row.get(9)?;
-                    let intent_json: Option<String> = row.get(10)?;
-                    let verdict_json: Option<String> = row.get(11)?;
-                    let quality_gate_json: Option<String> = row.get(12)?;
-                    let snapshots_json: String = row.get(13)?;
-                    let lightweight_snapshots_json: String = row.get(14)?;
-
-                    let task_id_parsed: TaskId = id.parse().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?;
-                    let state_parsed: ndc_core::TaskState =
-                        serde_json::from_str(&state).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let metadata: ndc_core::TaskMetadata = serde_json::from_str(&metadata_json)
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let steps: Vec<ndc_core::ExecutionStep> = serde_json::from_str(&steps_json)
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let intent = intent_json
-                        .map(|s| serde_json::from_str(&s))
-                        .transpose()
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let verdict = verdict_json
-                        .map(|s| serde_json::from_str(&s))
-                        .transpose()
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let quality_gate = quality_gate_json
-                        .map(|s| serde_json::from_str(&s))
-                        .transpose()
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let snapshots: Vec<ndc_core::GitWorktreeSnapshot> =
-                        serde_json::from_str(&snapshots_json).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let lightweight_snapshots: Vec<ndc_core::LightweightSnapshot> =
-                        serde_json::from_str(&lightweight_snapshots_json).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-
-                    Ok(Task {
-                        id: task_id_parsed,
-                        title,
-                        description,
-                        state: state_parsed,
-                        allowed_transitions: Vec::new(),
-                        steps,
-                        quality_gate,
-                        snapshots,
-                        lightweight_snapshots,
-                        metadata,
-                        intent,
-                        verdict,
-                    })
-                })
+                .query_map([], task_from_row)
                 .map_err(|e| e.to_string())?;
 
             let mut result = Vec::new();
@@ -587,87 +664,188 @@ impl crate::Storage for SqliteStorage {
         let memory_id_str = memory_id.0.to_string();
 
         run_sqlite(pool, move |conn| {
-            let mut stmt = conn
-                .prepare(
-                    r#"
-                SELECT id, content, embedding, relations, metadata, access_control
-                FROM memories WHERE id = ?
-                "#,
-                )
+            let sql = format!("SELECT {} FROM memories WHERE id = ?", MEMORY_COLUMNS);
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            stmt.query_row([&memory_id_str], memory_from_row)
+                .optional()
+                .map_err(|e| e.to_string())
+        })
+        .await
+    }
+
+    async fn list_memories(&self) -> Result<Vec<MemoryEntry>, String> {
+        let pool = self.pool.clone();
+        let now = self.now();
+
+        let memories = run_sqlite(pool, move |conn| {
+            let sql = format!("SELECT {} FROM memories ORDER BY id", MEMORY_COLUMNS);
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], memory_from_row)
                 .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        // Expiry lives in the JSON metadata column, so it is checked here
+        Ok(memories
+            .into_iter()
+            .filter(|memory| !memory.is_expired(now))
+            .collect())
+    }
+
+    async fn delete_memory(&self, memory_id: &MemoryId) -> Result<(), String> {
+        let pool = self.pool.clone();
+        let memory_id = memory_id.0.to_string();
 
-            let memory_opt = stmt
-                .query_row([&memory_id_str], |row| {
-                    let id: String = row.get(0)?;
-                    let content_json: String = row.get(1)?;
-                    let embedding_json: String = row.get(2)?;
-                    let relations_json: String = row.get(3)?;
-                    let metadata_json: String = row.get(4)?;
-                    let access_control_json: String = row.get(5)?;
-
-                    let memory_id_parsed: uuid::Uuid = id.parse().map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?;
-                    let content: ndc_core::MemoryContent = serde_json::from_str(&content_json)
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let embedding: Vec<f32> =
-                        serde_json::from_str(&embedding_json).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let relations: Vec<ndc_core::Relation> = serde_json::from_str(&relations_json)
-                        .map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-                    let metadata: ndc_core::MemoryMetadata = serde_json::from_str(&metadata_json)
-                        .map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            0,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?;
-                    let access_control: ndc_core::AccessControl =
-                        serde_json::from_str(&access_control_json).map_err(|e| {
-                            rusqlite::Error::FromSqlConversionFailure(
-                                0,
-                                rusqlite::types::Type::Text,
-                                Box::new(e),
-                            )
-                        })?;
-
-                    Ok(MemoryEntry {
-                        id: MemoryId(memory_id_parsed),
-                        content,
-                        embedding,
-                        relations,
-                        metadata,
-                        access_control,
-                    })
-                })
-                .optional();
-
-            memory_opt.map_err(|e| e.to_string())
+        run_sqlite(pool, move |conn| {
+            conn.execute("DELETE FROM memories WHERE id = ?", [&memory_id])
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, String> {
+        let pool = self.pool.clone();
+
+        run_sqlite(pool, move |conn| {
+            let sql = format!("SELECT {} FROM memories", MEMORY_COLUMNS);
+            let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+            let expired: Vec<String> = stmt
+                .query_map([], memory_from_row)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|memory| memory.is_expired(now))
+                .map(|memory| memory.id.0.to_string())
+                .collect();
+
+            let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+            for id in &expired {
+                tx.execute("DELETE FROM memories WHERE id = ?", [id])
+                    .map_err(|e| e.to_string())?;
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+            Ok(expired.len())
         })
         .await
     }
+
+    async fn save_saga(&self, saga_id: &str, plan: &serde_json::Value) -> Result<(), String> {
+        let pool = self.pool.clone();
+        let saga_id = saga_id.to_string();
+        let plan = serde_json::to_string(plan).map_err(|e| e.to_string())?;
+        let updated_at = Utc::now().to_rfc3339();
+
+        run_sqlite(pool, move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO sagas (id, plan, updated_at) VALUES (?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    plan = excluded.plan,
+                    updated_at = excluded.updated_at
+                "#,
+                rusqlite::params![saga_id, plan, updated_at],
+            )
+            .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_saga(&self, saga_id: &str) -> Result<Option<serde_json::Value>, String> {
+        let pool = self.pool.clone();
+        let saga_id = saga_id.to_string();
+
+        let plan: Option<String> = run_sqlite(pool, move |conn| {
+            conn.query_row("SELECT plan FROM sagas WHERE id = ?", [&saga_id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        plan.map(|p| serde_json::from_str(&p).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn list_sagas(&self) -> Result<Vec<serde_json::Value>, String> {
+        let pool = self.pool.clone();
+
+        let plans: Vec<String> = run_sqlite(pool, move |conn| {
+            let mut stmt = conn
+                .prepare("SELECT plan FROM sagas ORDER BY updated_at")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<String>, _>>()
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        plans
+            .iter()
+            .map(|p| serde_json::from_str(p).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    async fn delete_saga(&self, saga_id: &str) -> Result<(), String> {
+        let pool = self.pool.clone();
+        let saga_id = saga_id.to_string();
+
+        run_sqlite(pool, move |conn| {
+            conn.execute("DELETE FROM sagas WHERE id = ?", [&saga_id])
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn append_audit(&self, entry: &serde_json::Value) -> Result<(), String> {
+        let pool = self.pool.clone();
+        let entry = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        let recorded_at = Utc::now().to_rfc3339();
+
+        run_sqlite(pool, move |conn| {
+            conn.execute(
+                "INSERT INTO permission_audit (entry, recorded_at) VALUES (?, ?)",
+                rusqlite::params![entry, recorded_at],
+            )
+            .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_audit(&self) -> Result<Vec<serde_json::Value>, String> {
+        let pool = self.pool.clone();
+
+        let entries: Vec<String> = run_sqlite(pool, move |conn| {
+            let mut stmt = conn
+                .prepare("SELECT entry FROM permission_audit ORDER BY seq")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<String>, _>>()
+                .map_err(|e| e.to_string())
+        })
+        .await?;
+
+        entries
+            .iter()
+            .map(|e| serde_json::from_str(e).map_err(|e| e.to_string()))
+            .collect()
+    }
 }
 
 /// Create a new shared SQLite storage
@@ -682,7 +860,7 @@ pub async fn create_sqlite_storage(
 mod tests {
     use super::*;
     use crate::Storage;
-    use ndc_core::{MemoryContent, TaskMetadata, TaskState};
+    use ndc_core::{AgentRole, MemoryContent, TaskMetadata, TaskState};
     use tempfile::tempdir;
     use ulid::Ulid;
     use uuid::Uuid;
@@ -717,6 +895,7 @@ mod tests {
             metadata: TaskMetadata::default(),
             intent: None,
             verdict: None,
+            depends_on: vec![Ulid::new()],
         };
 
         // Save the task
@@ -729,6 +908,7 @@ mod tests {
         assert_eq!(retrieved.title, task.title);
         assert_eq!(retrieved.description, task.description);
         assert_eq!(retrieved.state, task.state);
+        assert_eq!(retrieved.depends_on, task.depends_on);
     }
 
     #[tokio::test]
@@ -753,6 +933,7 @@ mod tests {
                 metadata: TaskMetadata::default(),
                 intent: None,
                 verdict: None,
+                depends_on: vec![],
             };
             storage.save_task(&task).await.unwrap();
         }
@@ -798,6 +979,7 @@ mod tests {
                 version: 1,
                 modified_at: None,
                 tags: vec![],
+                expires_at: None,
             },
             access_control: ndc_core::AccessControl::new(
                 ndc_core::AgentId(uuid::Uuid::new_v4()),
@@ -841,6 +1023,7 @@ mod tests {
             metadata: TaskMetadata::default(),
             intent: None,
             verdict: None,
+            depends_on: vec![],
         };
 
         // Save the task
@@ -881,6 +1064,7 @@ mod tests {
                     metadata: TaskMetadata::default(),
                     intent: None,
                     verdict: None,
+                    depends_on: vec![],
                 };
                 s.save_task(&task).await.unwrap();
             }));
@@ -917,6 +1101,7 @@ mod tests {
                 metadata: TaskMetadata::default(),
                 intent: None,
                 verdict: None,
+                depends_on: vec![],
             };
             storage.save_task(&task).await.unwrap();
         }
@@ -924,4 +1109,372 @@ mod tests {
         let tasks = storage.list_tasks().await.unwrap();
         assert_eq!(tasks.len(), 5);
     }
+
+    fn paged_task(state: TaskState, created_by: AgentRole) -> Task {
+        Task {
+            id: Ulid::new(),
+            title: "Paged Task".to_string(),
+            description: "paged".to_string(),
+            state,
+            allowed_transitions: vec![],
+            steps: vec![],
+            quality_gate: None,
+            snapshots: vec![],
+            lightweight_snapshots: vec![],
+            metadata: TaskMetadata {
+                created_by,
+                ..TaskMetadata::default()
+            },
+            intent: None,
+            verdict: None,
+            depends_on: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_paged_empty() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let (tasks, next) = storage
+            .query_tasks_paged(None, 10, &TaskFilter::default())
+            .await
+            .unwrap();
+        assert!(tasks.is_empty());
+        assert!(next.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_paged_ends_exactly_on_last_row() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        for _ in 0..4 {
+            let task = paged_task(TaskState::Pending, AgentRole::Historian);
+            ids.push(task.id);
+            storage.save_task(&task).await.unwrap();
+        }
+        ids.sort();
+
+        let filter = TaskFilter::default();
+        let (first, next) = storage.query_tasks_paged(None, 2, &filter).await.unwrap();
+        assert_eq!(first.iter().map(|t| t.id).collect::<Vec<_>>(), ids[..2]);
+        let next = next.expect("second page expected");
+
+        // The cursor round-trips through its opaque token form.
+        let next = TaskCursor::from_token(&next.to_token()).unwrap();
+        let (second, after) = storage
+            .query_tasks_paged(Some(next), 2, &filter)
+            .await
+            .unwrap();
+        assert_eq!(second.iter().map(|t| t.id).collect::<Vec<_>>(), ids[2..]);
+        assert!(after.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_paged_cursor_stable_under_inserts() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            storage
+                .save_task(&paged_task(TaskState::Pending, AgentRole::Historian))
+                .await
+                .unwrap();
+        }
+        let filter = TaskFilter::default();
+        let (first, next) = storage.query_tasks_paged(None, 2, &filter).await.unwrap();
+
+        // ULIDs are only ordered across milliseconds.
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        let late = paged_task(TaskState::Pending, AgentRole::Historian);
+        storage.save_task(&late).await.unwrap();
+
+        let (rest, _) = storage.query_tasks_paged(next, 10, &filter).await.unwrap();
+        assert_eq!(rest.len(), 2);
+        assert!(rest.iter().all(|t| !first.iter().any(|f| f.id == t.id)));
+        assert_eq!(rest.last().unwrap().id, late.id);
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_paged_with_filter() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let start = chrono::Utc::now() - chrono::Duration::seconds(1);
+        for i in 0..6 {
+            let (state, role) = if i % 2 == 0 {
+                (TaskState::Completed, AgentRole::Implementer)
+            } else {
+                (TaskState::Pending, AgentRole::Historian)
+            };
+            storage.save_task(&paged_task(state, role)).await.unwrap();
+        }
+
+        let filter = TaskFilter {
+            state: Some(TaskState::Completed),
+            created_by: Some(AgentRole::Implementer),
+            created_after: Some(start),
+            created_before: Some(chrono::Utc::now() + chrono::Duration::seconds(1)),
+            active_since: None,
+        };
+        let (first, next) = storage.query_tasks_paged(None, 2, &filter).await.unwrap();
+        let (second, after) = storage.query_tasks_paged(next, 2, &filter).await.unwrap();
+
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+        assert!(after.is_none());
+        assert!(
+            first
+                .iter()
+                .chain(second.iter())
+                .all(|t| t.state == TaskState::Completed
+                    && t.metadata.created_by == AgentRole::Implementer)
+        );
+
+        let past = TaskFilter {
+            created_before: Some(start),
+            ..TaskFilter::default()
+        };
+        let (none, _) = storage.query_tasks_paged(None, 10, &past).await.unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_active_since() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+        let now = Utc::now();
+        let hours_ago = |h: i64| now - chrono::Duration::hours(h);
+
+        let mut stale = paged_task(TaskState::Completed, AgentRole::Implementer);
+        stale.metadata.created_at = hours_ago(72);
+        stale.metadata.updated_at = hours_ago(72);
+        let mut touched = paged_task(TaskState::Pending, AgentRole::Implementer);
+        touched.metadata.created_at = hours_ago(72);
+        touched.metadata.updated_at = hours_ago(1);
+        let mut fresh = paged_task(TaskState::Pending, AgentRole::Historian);
+        fresh.metadata.created_at = hours_ago(2);
+        fresh.metadata.updated_at = hours_ago(2);
+        for task in [&stale, &touched, &fresh] {
+            storage.save_task(task).await.unwrap();
+        }
+
+        let recent = TaskFilter {
+            active_since: Some(hours_ago(24)),
+            ..TaskFilter::default()
+        };
+        let ids: Vec<TaskId> = storage
+            .query_tasks(&recent)
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![touched.id, fresh.id]);
+
+        let implementer = TaskFilter {
+            created_by: Some(AgentRole::Implementer),
+            ..recent
+        };
+        let tasks = storage.query_tasks(&implementer).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, touched.id);
+        assert_eq!(
+            storage
+                .query_tasks(&TaskFilter::default())
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_paged_rejects_zero_limit() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+        assert!(
+            storage
+                .query_tasks_paged(None, 0, &TaskFilter::default())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_saga_roundtrip() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let plan = serde_json::json!({ "id": "saga-1", "steps": [{ "status": "Completed" }] });
+
+        {
+            let storage = SqliteStorage::new(db_path.clone()).await.unwrap();
+            storage.save_saga("saga-1", &plan).await.unwrap();
+        }
+
+        // Survives reopening the database
+        let storage = SqliteStorage::new(db_path).await.unwrap();
+        assert_eq!(storage.load_saga("saga-1").await.unwrap(), Some(plan));
+        assert_eq!(storage.list_sagas().await.unwrap().len(), 1);
+
+        storage.delete_saga("saga-1").await.unwrap();
+        assert!(storage.load_saga("saga-1").await.unwrap().is_none());
+        assert!(storage.list_sagas().await.unwrap().is_empty());
+
+        for n in 0..3 {
+            storage
+                .append_audit(&serde_json::json!({ "n": n }))
+                .await
+                .unwrap();
+        }
+        let audit = storage.list_audit().await.unwrap();
+        assert_eq!(
+            audit
+                .iter()
+                .map(|e| e["n"].as_i64().unwrap())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_memories_date_window_and_sort() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let base = Utc::now() - chrono::Duration::days(10);
+        let mut ids = Vec::new();
+        for (days, stability) in [
+            (0, ndc_core::MemoryStability::Verified),
+            (3, ndc_core::MemoryStability::Canonical),
+            (6, ndc_core::MemoryStability::Ephemeral),
+        ] {
+            let agent = ndc_core::AgentId(Uuid::new_v4());
+            let memory = MemoryEntry {
+                id: MemoryId(Uuid::new_v4()),
+                content: MemoryContent::General {
+                    text: format!("note from day {}", days),
+                    metadata: String::new(),
+                },
+                embedding: vec![],
+                relations: vec![],
+                metadata: ndc_core::MemoryMetadata {
+                    stability,
+                    created_at: base + chrono::Duration::days(days),
+                    created_by: agent,
+                    source_task: Ulid::new(),
+                    version: 1,
+                    modified_at: None,
+                    tags: vec![],
+                    expires_at: None,
+                },
+                access_control: ndc_core::AccessControl::new(agent, stability),
+            };
+            ids.push(memory.id);
+            storage.save_memory(&memory).await.unwrap();
+        }
+        let order = |entries: Vec<MemoryEntry>| -> Vec<usize> {
+            entries
+                .iter()
+                .map(|e| ids.iter().position(|id| *id == e.id).unwrap())
+                .collect()
+        };
+
+        for (sort_by, expected) in [
+            (ndc_core::MemorySort::Newest, vec![2, 1, 0]),
+            (ndc_core::MemorySort::Oldest, vec![0, 1, 2]),
+            (ndc_core::MemorySort::Stability, vec![1, 0, 2]),
+            (ndc_core::MemorySort::Relevance, vec![1, 0, 2]),
+        ] {
+            let query = MemoryQuery {
+                sort_by,
+                ..Default::default()
+            };
+            let found = storage.query_memories(&query).await.unwrap();
+            assert_eq!(order(found), expected, "{:?}", sort_by);
+        }
+
+        // Inclusive lower bound, exclusive upper bound
+        let window = MemoryQuery {
+            created_after: Some(base + chrono::Duration::days(3)),
+            created_before: Some(base + chrono::Duration::days(6)),
+            ..Default::default()
+        };
+        let found = storage.query_memories(&window).await.unwrap();
+        assert_eq!(order(found), vec![1]);
+
+        let recent = MemoryQuery {
+            created_after: Some(base + chrono::Duration::days(1)),
+            sort_by: ndc_core::MemorySort::Oldest,
+            ..Default::default()
+        };
+        let found = storage.query_memories(&recent).await.unwrap();
+        assert_eq!(order(found), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_memories_are_hidden_and_purged() {
+        let dir = tempdir().unwrap();
+        let storage = SqliteStorage::new(dir.path().join("test.db"))
+            .await
+            .unwrap();
+
+        let agent = ndc_core::AgentId(Uuid::new_v4());
+        let stability = ndc_core::MemoryStability::Ephemeral;
+        let memory = |minutes_ago: i64| {
+            MemoryEntry {
+                id: MemoryId(Uuid::new_v4()),
+                content: MemoryContent::General {
+                    text: format!("{} minutes old", minutes_ago),
+                    metadata: String::new(),
+                },
+                embedding: vec![1.0, 0.0],
+                relations: vec![],
+                metadata: ndc_core::MemoryMetadata {
+                    stability,
+                    created_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+                    created_by: agent,
+                    source_task: Ulid::new(),
+                    version: 1,
+                    modified_at: None,
+                    tags: vec![],
+                    expires_at: None,
+                },
+                access_control: ndc_core::AccessControl::new(agent, stability),
+            }
+            .with_ttl(chrono::Duration::minutes(5))
+        };
+        let expired = memory(10);
+        let live = memory(1);
+        storage.save_memory(&expired).await.unwrap();
+        storage.save_memory(&live).await.unwrap();
+
+        let listed = storage.list_memories().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, live.id);
+        let hits = storage.search_memories(&[1.0, 0.0], 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].memory.id, live.id);
+
+        assert_eq!(storage.purge_expired(Utc::now()).await.unwrap(), 1);
+        assert!(storage.get_memory(&expired.id).await.unwrap().is_none());
+        assert!(storage.get_memory(&live.id).await.unwrap().is_some());
+        assert_eq!(storage.purge_expired(Utc::now()).await.unwrap(), 0);
+    }
 }
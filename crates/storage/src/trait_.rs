@@ -1,11 +1,41 @@
 //! Storage trait definition
 //!
-//! Abstract interface for task and memory persistence
+//! Abstract interface for task, memory, saga and audit persistence
 
 use async_trait::async_trait;
-use ndc_core::{MemoryEntry, MemoryId, Task, TaskId};
+use chrono::{DateTime, Utc};
+use ndc_core::{
+    AgentRole, MemoryEntry, MemoryId, ScoredMemory, SearchMode, Task, TaskId, TaskState,
+    VectorSearch, search_cosine,
+};
 use std::sync::Arc;
 
+/// Task filter for `Storage::query_tasks`; SQL backends push it into `WHERE`
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    pub state: Option<TaskState>,
+    pub created_by: Option<AgentRole>,
+    /// Inclusive lower bound on the creation time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on the creation time
+    pub created_before: Option<DateTime<Utc>>,
+    /// Created or updated at or after this time
+    pub active_since: Option<DateTime<Utc>>,
+}
+
+impl TaskFilter {
+    pub fn matches(&self, task: &Task) -> bool {
+        let meta = &task.metadata;
+        self.state.as_ref().is_none_or(|s| task.state == *s)
+            && self.created_by.is_none_or(|r| meta.created_by == r)
+            && self.created_after.is_none_or(|t| meta.created_at >= t)
+            && self.created_before.is_none_or(|t| meta.created_at < t)
+            && self
+                .active_since
+                .is_none_or(|t| meta.created_at >= t || meta.updated_at >= t)
+    }
+}
+
 /// Storage trait for task and memory persistence
 #[async_trait]
 pub trait Storage: Send + Sync {
@@ -13,8 +43,66 @@ pub trait Storage: Send + Sync {
     async fn get_task(&self, task_id: &TaskId) -> Result<Option<Task>, String>;
     async fn list_tasks(&self) -> Result<Vec<Task>, String>;
     async fn list_tasks_by_tags(&self, tags: &[String]) -> Result<Vec<Task>, String>;
+    /// Tasks matching `filter`, most recently updated first
+    async fn query_tasks(&self, filter: &TaskFilter) -> Result<Vec<Task>, String> {
+        let mut tasks: Vec<Task> = self
+            .list_tasks()
+            .await?
+            .into_iter()
+            .filter(|task| filter.matches(task))
+            .collect();
+        tasks.sort_by_key(|task| std::cmp::Reverse(task.metadata.updated_at));
+        Ok(tasks)
+    }
     async fn save_memory(&self, memory: &MemoryEntry) -> Result<(), String>;
     async fn get_memory(&self, memory_id: &MemoryId) -> Result<Option<MemoryEntry>, String>;
+    /// Every stored memory not yet expired at `now()`, ordered by id
+    async fn list_memories(&self) -> Result<Vec<MemoryEntry>, String>;
+    /// Deleting an unknown id is not an error
+    async fn delete_memory(&self, memory_id: &MemoryId) -> Result<(), String>;
+    /// Delete Ephemeral memories whose TTL has passed at `now`; returns the
+    /// number removed
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, String>;
+    /// Current time for TTL checks; backends with a clock override this
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+    /// The `top_k` unexpired memories closest to `query` by cosine, best first
+    async fn search_memories(
+        &self,
+        query: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<ScoredMemory>, String> {
+        Ok(search_cosine(&self.list_memories().await?, query, top_k))
+    }
+
+    /// Like `search_memories`, but ranked by `mode`
+    ///
+    /// `Cosine` defers to `search_memories`; the SimHash modes index every
+    /// memory whose embedding matches the query's dimensions.
+    async fn search_memories_with_mode(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        mode: SearchMode,
+    ) -> Result<Vec<ScoredMemory>, String> {
+        if mode == SearchMode::Cosine {
+            return self.search_memories(query, top_k).await;
+        }
+        let index = VectorSearch::from_memories(self.list_memories().await?, query.len());
+        Ok(index.search(query, top_k, mode))
+    }
+
+    /// Saga plans are stored as opaque JSON keyed by saga id, since the
+    /// plan types live in the runtime crate
+    async fn save_saga(&self, saga_id: &str, plan: &serde_json::Value) -> Result<(), String>;
+    async fn load_saga(&self, saga_id: &str) -> Result<Option<serde_json::Value>, String>;
+    async fn list_sagas(&self) -> Result<Vec<serde_json::Value>, String>;
+    async fn delete_saga(&self, saga_id: &str) -> Result<(), String>;
+
+    /// Permission audit entries, append-only opaque JSON in insertion order
+    async fn append_audit(&self, entry: &serde_json::Value) -> Result<(), String>;
+    async fn list_audit(&self) -> Result<Vec<serde_json::Value>, String>;
 }
 
 /// Shared storage reference
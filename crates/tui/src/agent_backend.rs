@@ -9,6 +9,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use ndc_core::{AgentExecutionEvent, AgentResponse, AgentSessionExecutionEvent, ModelInfo};
+use tokio_util::sync::CancellationToken;
 
 // ── DTO types (TUI-owned, mapped from interface types) ──────────────
 
@@ -46,14 +47,16 @@ pub struct ProjectCandidate {
 pub struct TuiPermissionRequest {
     pub description: String,
     pub permission_key: Option<String>,
-    pub response_tx: tokio::sync::oneshot::Sender<bool>,
+    /// 可替代原操作的更安全方案，按数字键选择
+    pub alternatives: Vec<String>,
+    pub response_tx: tokio::sync::oneshot::Sender<ndc_core::HumanDecision>,
 }
 
 /// TODO 任务的轻量视图（TUI 显示用）
 #[derive(Debug, Clone)]
 pub struct TodoItem {
-    pub id: String,       // TaskId 的字符串形式
-    pub index: usize,     // 会话内序号（1-based）
+    pub id: String,   // TaskId 的字符串形式
+    pub index: usize, // 会话内序号（1-based）
     pub title: String,
     pub state: TodoState,
 }
@@ -85,17 +88,23 @@ pub trait AgentBackend: Send + Sync {
 
     async fn subscribe_execution_events(
         &self,
-    ) -> anyhow::Result<(String, tokio::sync::broadcast::Receiver<AgentSessionExecutionEvent>)>;
+    ) -> anyhow::Result<(
+        String,
+        tokio::sync::broadcast::Receiver<AgentSessionExecutionEvent>,
+    )>;
 
     // --- User input ---
     async fn process_input(&self, input: &str) -> anyhow::Result<AgentResponse>;
 
-    // --- Provider / model ---
-    async fn switch_provider(
+    /// Like `process_input`, but stops once `cancel` fires and undoes the turn's file changes
+    async fn process_input_with_cancellation(
         &self,
-        provider: &str,
-        model: Option<&str>,
-    ) -> anyhow::Result<()>;
+        input: &str,
+        cancel: CancellationToken,
+    ) -> anyhow::Result<AgentResponse>;
+
+    // --- Provider / model ---
+    async fn switch_provider(&self, provider: &str, model: Option<&str>) -> anyhow::Result<()>;
 
     async fn switch_model(&self, model: &str) -> anyhow::Result<()>;
 
@@ -115,10 +124,7 @@ pub trait AgentBackend: Send + Sync {
     ) -> anyhow::Result<Vec<String>>;
 
     // --- Project context ---
-    async fn switch_project_context(
-        &self,
-        path: PathBuf,
-    ) -> anyhow::Result<ProjectSwitchInfo>;
+    async fn switch_project_context(&self, path: PathBuf) -> anyhow::Result<ProjectSwitchInfo>;
 
     async fn discover_projects(&self, limit: usize) -> anyhow::Result<Vec<ProjectCandidate>>;
 
@@ -126,10 +132,7 @@ pub trait AgentBackend: Send + Sync {
     async fn handle_agent_command(&self, input: &str) -> anyhow::Result<()>;
 
     // --- Permission channel ---
-    async fn set_permission_channel(
-        &self,
-        tx: tokio::sync::mpsc::Sender<TuiPermissionRequest>,
-    );
+    async fn set_permission_channel(&self, tx: tokio::sync::mpsc::Sender<TuiPermissionRequest>);
 
     // --- TODO management ---
 
@@ -140,10 +143,7 @@ pub trait AgentBackend: Send + Sync {
     async fn create_todo(&self, title: &str, description: &str) -> anyhow::Result<TodoItem>;
 
     /// 批量创建 TODO（用于 Agent planning 输出）
-    async fn create_todos(
-        &self,
-        items: Vec<(String, String)>,
-    ) -> anyhow::Result<Vec<TodoItem>>;
+    async fn create_todos(&self, items: Vec<(String, String)>) -> anyhow::Result<Vec<TodoItem>>;
 
     /// 更新 TODO 状态（按会话内序号）
     async fn update_todo_state(&self, index: usize, state: TodoState) -> anyhow::Result<()>;
@@ -16,9 +16,10 @@ use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
     Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
 };
+use tokio_util::sync::CancellationToken;
 
 use crate::agent_backend::{AgentBackend, TuiPermissionRequest};
-use crate::layout_manager::{effective_log_scroll, tui_session_split};
+use crate::layout_manager::{alternative_index, effective_log_scroll, tui_session_split};
 use crate::todo_panel::render_todo_sidebar;
 
 use super::*;
@@ -28,7 +29,11 @@ fn wrapped_visual_line_count(lines: &[Line<'_>], inner_width: usize) -> usize {
     lines
         .iter()
         .map(|line| {
-            let line_width: usize = line.spans.iter().map(|span| span.content.chars().count()).sum();
+            let line_width: usize = line
+                .spans
+                .iter()
+                .map(|span| span.content.chars().count())
+                .sum();
             line_width.max(1).div_ceil(width)
         })
         .sum()
@@ -38,6 +43,7 @@ pub async fn run_repl_tui(
     viz_state: &mut ReplVisualizationState,
     agent_manager: std::sync::Arc<dyn AgentBackend>,
     mut permission_rx: tokio::sync::mpsc::Receiver<TuiPermissionRequest>,
+    mut transcript: Option<TranscriptConfig>,
 ) -> io::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -57,20 +63,46 @@ pub async fn run_repl_tui(
     let mut processing_handle: Option<
         tokio::task::JoinHandle<anyhow::Result<ndc_core::AgentResponse>>,
     > = None;
+    let mut processing_cancel: Option<CancellationToken> = None;
     let mut streamed_count = 0usize;
     let mut streamed_any = false;
     let mut last_poll = Instant::now();
     let mut should_quit = false;
-    let mut pending_permission_tx: Option<tokio::sync::oneshot::Sender<bool>> = None;
+    let mut pending_permission_tx: Option<tokio::sync::oneshot::Sender<ndc_core::HumanDecision>> =
+        None;
     let mut pending_permission_key: Option<String> = None;
     let mut session_view = TuiSessionViewState::default();
     let mut turn_counter: usize = 0;
+    if let Some(prior) = transcript.as_mut().and_then(|t| t.resume.take()) {
+        let session_id = prior.session_id.clone();
+        let (restored, turns) = prior.restore(viz_state);
+        let note = format!(
+            "Resumed session {} ({} entries)",
+            session_id,
+            restored.len()
+        );
+        entries = restored;
+        turn_counter = turns;
+        push_chat_entry(&mut entries, ChatEntry::SystemNote(note));
+    }
+    let mut last_autosave = Instant::now();
+    let mut autosaved_len = entries.len();
     let mut live_events: Option<
         tokio::sync::broadcast::Receiver<ndc_core::AgentSessionExecutionEvent>,
     > = None;
     let mut live_session_id: Option<String> = None;
 
     while !should_quit {
+        if let Some(config) = transcript.as_ref()
+            && last_autosave.elapsed() >= TRANSCRIPT_AUTOSAVE_INTERVAL
+        {
+            last_autosave = Instant::now();
+            if entries.len() != autosaved_len {
+                autosaved_len = entries.len();
+                save_transcript(config, &entries, viz_state, turn_counter);
+            }
+        }
+
         if viz_state.live_events_enabled
             && drain_live_chat_entries(
                 &mut live_events,
@@ -96,6 +128,7 @@ pub async fn run_repl_tui(
         if let Ok(req) = permission_rx.try_recv() {
             viz_state.permission_blocked = true;
             viz_state.permission_pending_message = Some(req.description);
+            viz_state.permission_alternatives = req.alternatives;
             pending_permission_key = req.permission_key;
             pending_permission_tx = Some(req.response_tx);
         }
@@ -249,6 +282,7 @@ pub async fn run_repl_tui(
 
             if handle.is_finished() {
                 let handle = processing_handle.take().expect("present");
+                processing_cancel = None;
                 match handle.await {
                     Ok(Ok(response)) => {
                         if !streamed_any {
@@ -272,6 +306,19 @@ pub async fn run_repl_tui(
                             );
                         }
                     }
+                    Ok(Err(e))
+                        if matches!(
+                            e.downcast_ref::<ndc_core::AgentError>(),
+                            Some(ndc_core::AgentError::Cancelled)
+                        ) =>
+                    {
+                        push_chat_entry(
+                            &mut entries,
+                            ChatEntry::WarningNote(
+                                "[Interrupted] task cancelled by Ctrl+C".to_string(),
+                            ),
+                        );
+                    }
                     Ok(Err(e)) => {
                         push_chat_entry(
                             &mut entries,
@@ -307,18 +354,31 @@ pub async fn run_repl_tui(
                     if key.code == KeyCode::Char('c')
                         && key.modifiers.contains(KeyModifiers::CONTROL)
                     {
-                        if let Some(handle) = processing_handle.take() {
+                        if processing_handle.is_none() {
+                            should_quit = true;
+                        } else if let Some(cancel) =
+                            processing_cancel.as_ref().filter(|c| !c.is_cancelled())
+                        {
+                            // Stop after the in-flight call and undo the turn's file changes
+                            cancel.cancel();
+                            push_chat_entry(
+                                &mut entries,
+                                ChatEntry::WarningNote(
+                                    "[Interrupted] cancelling after the current step, Ctrl+C again to abort"
+                                        .to_string(),
+                                ),
+                            );
+                        } else if let Some(handle) = processing_handle.take() {
                             handle.abort();
+                            processing_cancel = None;
                             live_events = None;
                             live_session_id = None;
                             push_chat_entry(
                                 &mut entries,
                                 ChatEntry::WarningNote(
-                                    "[Interrupted] task cancelled by Ctrl+C".to_string(),
+                                    "[Interrupted] task aborted by Ctrl+C".to_string(),
                                 ),
                             );
-                        } else {
-                            should_quit = true;
                         }
                         continue;
                     }
@@ -343,19 +403,34 @@ pub async fn run_repl_tui(
                         if let Some(tx) = pending_permission_tx.take() {
                             match key.code {
                                 KeyCode::Char('y') | KeyCode::Char('Y') => {
-                                    let _ = tx.send(true);
+                                    let _ = tx.send(ndc_core::HumanDecision::Approve);
                                     viz_state.permission_blocked = false;
                                     viz_state.permission_pending_message = None;
                                     pending_permission_key = None;
                                 }
                                 KeyCode::Char('n') | KeyCode::Char('N') => {
-                                    let _ = tx.send(false);
+                                    let _ = tx.send(ndc_core::HumanDecision::Deny);
                                     viz_state.permission_blocked = false;
                                     viz_state.permission_pending_message = None;
                                     pending_permission_key = None;
                                 }
+                                KeyCode::Char(c) if c.is_ascii_digit() => {
+                                    match alternative_index(
+                                        c,
+                                        viz_state.permission_alternatives.len(),
+                                    ) {
+                                        Some(index) => {
+                                            let _ = tx
+                                                .send(ndc_core::HumanDecision::Alternative(index));
+                                            viz_state.permission_blocked = false;
+                                            viz_state.permission_pending_message = None;
+                                            pending_permission_key = None;
+                                        }
+                                        None => pending_permission_tx = Some(tx),
+                                    }
+                                }
                                 KeyCode::Char('a') | KeyCode::Char('A') => {
-                                    let _ = tx.send(true);
+                                    let _ = tx.send(ndc_core::HumanDecision::Approve);
                                     viz_state.permission_blocked = false;
                                     viz_state.permission_pending_message = None;
                                     // Add to session-level security override for this permission
@@ -383,7 +458,7 @@ pub async fn run_repl_tui(
                                     }
                                 }
                                 KeyCode::Char('p') | KeyCode::Char('P') => {
-                                    let _ = tx.send(true);
+                                    let _ = tx.send(ndc_core::HumanDecision::Approve);
                                     viz_state.permission_blocked = false;
                                     viz_state.permission_pending_message = None;
                                     // Save permanently to config
@@ -536,10 +611,11 @@ pub async fn run_repl_tui(
                                 );
                             }
                             let manager = agent_manager.clone();
-                            processing_handle =
-                                Some(tokio::spawn(
-                                    async move { manager.process_input(&cmd).await },
-                                ));
+                            let cancel = CancellationToken::new();
+                            processing_cancel = Some(cancel.clone());
+                            processing_handle = Some(tokio::spawn(async move {
+                                manager.process_input_with_cancellation(&cmd, cancel).await
+                            }));
                         }
                         KeyCode::Backspace => {
                             input.pop();
@@ -556,19 +632,33 @@ pub async fn run_repl_tui(
                 }
                 Event::Mouse(mouse) => {
                     let rendered_line_count = session_view.rendered_line_count;
-                    let _ = handle_session_scroll_mouse(
-                        &mouse,
-                        &mut session_view,
-                        rendered_line_count,
-                    );
+                    let _ =
+                        handle_session_scroll_mouse(&mouse, &mut session_view, rendered_line_count);
                 }
                 _ => {}
             }
         }
     }
 
+    if let Some(config) = transcript.as_ref() {
+        save_transcript(config, &entries, viz_state, turn_counter);
+    }
+
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
     Ok(())
 }
+
+fn save_transcript(
+    config: &TranscriptConfig,
+    entries: &[ChatEntry],
+    viz_state: &ReplVisualizationState,
+    turn_counter: usize,
+) {
+    let transcript =
+        SessionTranscript::new(config.session_id.clone(), entries, viz_state, turn_counter);
+    if let Err(e) = transcript.save(&config.dir, viz_state.redaction_mode) {
+        tracing::warn!("Failed to save transcript {}: {}", config.session_id, e);
+    }
+}
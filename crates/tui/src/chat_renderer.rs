@@ -4,6 +4,7 @@
 
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
+use serde::{Deserialize, Serialize};
 
 use ndc_core::redaction::sanitize_text;
 
@@ -69,7 +70,7 @@ impl TuiTheme {
 }
 
 /// Status of a tool call card.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ToolCardStatus {
     Running,
     Completed,
@@ -77,7 +78,7 @@ pub enum ToolCardStatus {
 }
 
 /// A collapsible card representing a tool call execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCallCard {
     pub name: String,
     pub status: ToolCardStatus,
@@ -89,7 +90,7 @@ pub struct ToolCallCard {
 }
 
 /// A single line in a diff preview.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiffLine {
     /// Added line (rendered green with `+` prefix)
     Added(String),
@@ -100,7 +101,7 @@ pub enum DiffLine {
 }
 
 /// A single structured entry in the conversation log.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ChatEntry {
     /// Visual separator (blank line)
     Separator,
@@ -775,6 +776,12 @@ pub fn event_to_entries(
                 sanitize_text(&event.message, viz_state.redaction_mode)
             )));
         }
+        AgentExecutionEventKind::Warning => {
+            entries.push(ChatEntry::WarningNote(format!(
+                "[Warning] {}",
+                sanitize_text(&event.message, viz_state.redaction_mode)
+            )));
+        }
     }
     entries
 }
@@ -2055,4 +2062,23 @@ mod tests {
         let entries = event_to_entries(&event, &mut viz);
         assert!(!entries.is_empty(), "Report should produce entries");
     }
+
+    #[test]
+    fn test_event_to_entries_warning() {
+        let mut viz = ReplVisualizationState::new(false);
+        let event = mk_event(
+            AgentExecutionEventKind::Warning,
+            "path changed to a sandboxed copy",
+            0,
+            None,
+            None,
+            None,
+            false,
+        );
+        let entries = event_to_entries(&event, &mut viz);
+        assert!(matches!(
+            entries.as_slice(),
+            [ChatEntry::WarningNote(text)] if text == "[Warning] path changed to a sandboxed copy"
+        ));
+    }
 }
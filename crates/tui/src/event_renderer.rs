@@ -352,6 +352,13 @@ pub fn event_to_lines(
                 sanitize_text(&event.message, viz_state.redaction_mode)
             ));
         }
+        ndc_core::AgentExecutionEventKind::Warning => {
+            lines.push(format!(
+                "[Warning][r{}] {}",
+                event.round,
+                sanitize_text(&event.message, viz_state.redaction_mode)
+            ));
+        }
     }
     lines
 }
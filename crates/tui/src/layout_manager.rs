@@ -208,7 +208,7 @@ pub fn tool_status_narrative(tool_name: Option<&str>) -> &'static str {
     match tool_name {
         Some("read" | "read_file") => "Reading file...",
         Some("grep" | "glob" | "list" | "list_dir") => "Searching codebase...",
-        Some("write" | "write_file" | "edit" | "edit_file") => "Making edits...",
+        Some("write" | "write_file" | "edit" | "edit_file" | "apply_patch") => "Making edits...",
         Some("shell" | "bash") => "Running command...",
         Some("ndc_task_create" | "ndc_task_list" | "ndc_task_update" | "ndc_task_verify") => {
             "Managing tasks..."
@@ -312,6 +312,10 @@ pub fn build_permission_bar<'a>(
         Some(m) => m.clone(),
         None => "Awaiting confirmation...".to_string(),
     };
+    let mut choices = "   [y] Allow  [n] Deny  [a] Session allow  [p] Permanent allow".to_string();
+    for (i, alternative) in viz_state.permission_alternatives.iter().enumerate() {
+        choices.push_str(&format!("  [{}] {}", i + 1, alternative));
+    }
     vec![
         Line::from(vec![
             Span::styled(
@@ -324,12 +328,20 @@ pub fn build_permission_bar<'a>(
             Span::styled(format!("  {}", msg), Style::default().fg(theme.warning)),
         ]),
         Line::from(vec![Span::styled(
-            "   [y] Allow  [n] Deny  [a] Session allow  [p] Permanent allow",
+            choices,
             Style::default().fg(theme.text_muted),
         )]),
     ]
 }
 
+/// 数字键对应的替代方案下标（`1` → 0）；超出 `count` 时为 None
+pub(crate) fn alternative_index(key: char, count: usize) -> Option<usize> {
+    key.to_digit(10)
+        .map(|n| n as usize)
+        .filter(|n| (1..=count).contains(n))
+        .map(|n| n - 1)
+}
+
 pub fn build_status_hint_bar<'a>(
     input: &str,
     completion: Option<&ReplCommandCompletionState>,
@@ -1055,6 +1067,25 @@ mod tests {
         assert_eq!(metrics.tool_error_rate_percent(), 50);
     }
 
+    #[test]
+    fn test_permission_bar_lists_numbered_alternatives() {
+        let mut viz = ReplVisualizationState::new(false);
+        viz.permission_alternatives = vec![
+            "move a.txt -> a.txt.bak".to_string(),
+            "write a.txt.proposed".to_string(),
+        ];
+        let lines = build_permission_bar(&viz, &TuiTheme::default_dark());
+        let choices = lines[1].to_string();
+        assert!(choices.contains("[1] move a.txt -> a.txt.bak"));
+        assert!(choices.contains("[2] write a.txt.proposed"));
+
+        assert_eq!(alternative_index('1', 2), Some(0));
+        assert_eq!(alternative_index('2', 2), Some(1));
+        assert_eq!(alternative_index('3', 2), None);
+        assert_eq!(alternative_index('0', 2), None);
+        assert_eq!(alternative_index('1', 0), None);
+    }
+
     #[test]
     fn test_tui_layout_constraints_fixed_input_panel() {
         let constraints = tui_layout_constraints(false, 1);
@@ -13,6 +13,7 @@ mod input_handler;
 mod layout_manager;
 pub mod scene;
 pub mod todo_panel;
+mod transcript;
 #[cfg(test)]
 pub(crate) mod test_helpers;
 
@@ -29,6 +30,7 @@ pub use commands::*;
 pub use event_renderer::*;
 pub use input_handler::*;
 pub use layout_manager::*;
+pub use transcript::*;
 
 use std::collections::BTreeSet;
 
@@ -57,6 +59,8 @@ pub struct ReplVisualizationState {
     pub latest_round_token_total: u64,
     pub permission_blocked: bool,
     pub permission_pending_message: Option<String>,
+    /// 权限请求附带的替代方案（按数字键 1..=n 选择）
+    pub permission_alternatives: Vec<String>,
     pub show_todo_panel: bool,
     pub todo_items: Vec<TodoItem>,
     pub todo_scroll_offset: usize,
@@ -96,6 +100,7 @@ impl ReplVisualizationState {
             latest_round_token_total: 0,
             permission_blocked: false,
             permission_pending_message: None,
+            permission_alternatives: Vec::new(),
             show_todo_panel: true,
             todo_items: Vec::new(),
             todo_scroll_offset: 0,
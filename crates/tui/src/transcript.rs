@@ -0,0 +1,262 @@
+//! Transcript persistence — save the chat log on exit and resume it later.
+//!
+//! Transcripts live next to the REPL history file, one JSON file per session:
+//! `<history dir>/transcripts/<session_id>.json`. Every text field is passed
+//! through the redaction mode before it is written, so secrets shown in the
+//! session never reach disk.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ndc_core::redaction::{RedactionMode, sanitize_text};
+use serde::{Deserialize, Serialize};
+
+use crate::{ChatEntry, DiffLine, ReplVisualizationState};
+
+/// Subdirectory of the history directory holding transcripts
+pub const TRANSCRIPT_DIR: &str = "transcripts";
+
+/// How often a running session is checkpointed
+pub const TRANSCRIPT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Where and under which id the TUI persists its transcript
+#[derive(Debug, Clone)]
+pub struct TranscriptConfig {
+    /// Directory containing the `transcripts/` folder
+    pub dir: PathBuf,
+    pub session_id: String,
+    /// Prior transcript to load into the conversation panel
+    pub resume: Option<SessionTranscript>,
+}
+
+/// A saved conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub session_id: String,
+    pub saved_at: DateTime<Utc>,
+    pub session_token_total: u64,
+    pub turn_counter: usize,
+    pub entries: Vec<ChatEntry>,
+}
+
+impl SessionTranscript {
+    pub fn new(
+        session_id: impl Into<String>,
+        entries: &[ChatEntry],
+        viz_state: &ReplVisualizationState,
+        turn_counter: usize,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            saved_at: Utc::now(),
+            session_token_total: viz_state.session_token_total,
+            turn_counter,
+            entries: entries.to_vec(),
+        }
+    }
+
+    /// Apply `mode` to every text field
+    pub fn redacted(mut self, mode: RedactionMode) -> Self {
+        if mode != RedactionMode::Off {
+            self.entries = self
+                .entries
+                .into_iter()
+                .map(|entry| redact_entry(entry, mode))
+                .collect();
+        }
+        self
+    }
+
+    /// `<dir>/transcripts/<session_id>.json`
+    pub fn path(dir: &Path, session_id: &str) -> io::Result<PathBuf> {
+        let valid = !session_id.is_empty()
+            && session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid session id: {:?}", session_id),
+            ));
+        }
+        Ok(dir
+            .join(TRANSCRIPT_DIR)
+            .join(format!("{}.json", session_id)))
+    }
+
+    /// Redact with `mode` and write atomically; returns the file path
+    pub fn save(&self, dir: &Path, mode: RedactionMode) -> io::Result<PathBuf> {
+        let path = Self::path(dir, &self.session_id)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.clone().redacted(mode))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    pub fn load(dir: &Path, session_id: &str) -> io::Result<Self> {
+        let content = std::fs::read_to_string(Self::path(dir, session_id)?)?;
+        serde_json::from_str(&content).map_err(io::Error::from)
+    }
+
+    /// Restore the token total and hand back the entries and turn counter
+    pub fn restore(self, viz_state: &mut ReplVisualizationState) -> (Vec<ChatEntry>, usize) {
+        viz_state.session_token_total = self.session_token_total;
+        (self.entries, self.turn_counter)
+    }
+}
+
+fn redact_entry(entry: ChatEntry, mode: RedactionMode) -> ChatEntry {
+    let clean = |text: String| sanitize_text(&text, mode);
+    match entry {
+        ChatEntry::Separator | ChatEntry::RoundSeparator { .. } => entry,
+        ChatEntry::UserMessage { content, turn_id } => ChatEntry::UserMessage {
+            content: clean(content),
+            turn_id,
+        },
+        ChatEntry::AssistantMessage { content, turn_id } => ChatEntry::AssistantMessage {
+            content: clean(content),
+            turn_id,
+        },
+        ChatEntry::SystemNote(text) => ChatEntry::SystemNote(clean(text)),
+        ChatEntry::ToolCard(mut card) => {
+            card.args_summary = card.args_summary.map(clean);
+            card.output_preview = card.output_preview.map(clean);
+            ChatEntry::ToolCard(card)
+        }
+        ChatEntry::ReasoningBlock {
+            round,
+            content,
+            collapsed,
+        } => ChatEntry::ReasoningBlock {
+            round,
+            content: clean(content),
+            collapsed,
+        },
+        ChatEntry::StageNote(text) => ChatEntry::StageNote(clean(text)),
+        ChatEntry::UsageNote(text) => ChatEntry::UsageNote(clean(text)),
+        ChatEntry::ErrorNote(text) => ChatEntry::ErrorNote(clean(text)),
+        ChatEntry::WarningNote(text) => ChatEntry::WarningNote(clean(text)),
+        ChatEntry::PermissionNote(text) => ChatEntry::PermissionNote(clean(text)),
+        ChatEntry::PermissionHint(text) => ChatEntry::PermissionHint(clean(text)),
+        ChatEntry::DiffPreview {
+            path,
+            lines,
+            collapsed,
+        } => ChatEntry::DiffPreview {
+            path: clean(path),
+            lines: lines
+                .into_iter()
+                .map(|line| match line {
+                    DiffLine::Added(text) => DiffLine::Added(clean(text)),
+                    DiffLine::Removed(text) => DiffLine::Removed(clean(text)),
+                    DiffLine::Context(text) => DiffLine::Context(clean(text)),
+                })
+                .collect(),
+            collapsed,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ToolCallCard, ToolCardStatus};
+
+    fn sample_entries() -> Vec<ChatEntry> {
+        vec![
+            ChatEntry::UserMessage {
+                content: "deploy with token=abc123".to_string(),
+                turn_id: 1,
+            },
+            ChatEntry::ReasoningBlock {
+                round: 1,
+                content: "Need to read the config first".to_string(),
+                collapsed: true,
+            },
+            ChatEntry::ToolCard(ToolCallCard {
+                name: "read".to_string(),
+                status: ToolCardStatus::Completed,
+                duration: Some("12ms".to_string()),
+                args_summary: Some("path=config.toml".to_string()),
+                output_preview: Some("api_key = sk-ABCDEF123456".to_string()),
+                is_error: false,
+                collapsed: false,
+            }),
+            ChatEntry::DiffPreview {
+                path: "config.toml".to_string(),
+                lines: vec![
+                    DiffLine::Removed("a = 1".to_string()),
+                    DiffLine::Added("a = 2".to_string()),
+                ],
+                collapsed: true,
+            },
+            ChatEntry::AssistantMessage {
+                content: "Done.".to_string(),
+                turn_id: 1,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_transcript_round_trip_and_resume() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut viz = ReplVisualizationState::new(false);
+        viz.session_token_total = 1234;
+        let entries = sample_entries();
+
+        let transcript = SessionTranscript::new("18f3a9c2b10", &entries, &viz, 1);
+        let path = transcript.save(dir.path(), RedactionMode::Off).unwrap();
+        assert!(path.ends_with("transcripts/18f3a9c2b10.json"));
+
+        let loaded = SessionTranscript::load(dir.path(), "18f3a9c2b10").unwrap();
+        let mut resumed = ReplVisualizationState::new(false);
+        let (restored, turns) = loaded.restore(&mut resumed);
+
+        assert_eq!(restored.len(), entries.len());
+        assert_eq!(turns, 1);
+        assert_eq!(resumed.session_token_total, 1234);
+        assert!(matches!(
+            &restored[1],
+            ChatEntry::ReasoningBlock { content, collapsed: true, .. }
+                if content == "Need to read the config first"
+        ));
+        assert!(matches!(
+            &restored[2],
+            ChatEntry::ToolCard(card)
+                if card.name == "read" && card.status == ToolCardStatus::Completed
+        ));
+        assert!(matches!(
+            &restored[3],
+            ChatEntry::DiffPreview { lines, .. } if lines[1] == DiffLine::Added("a = 2".to_string())
+        ));
+    }
+
+    #[test]
+    fn test_transcript_redacts_before_writing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let viz = ReplVisualizationState::new(false);
+
+        let path = SessionTranscript::new("s1", &sample_entries(), &viz, 1)
+            .save(dir.path(), RedactionMode::Basic)
+            .unwrap();
+        let raw = std::fs::read_to_string(path).unwrap();
+
+        assert!(!raw.contains("abc123"));
+        assert!(!raw.contains("ABCDEF123456"));
+        assert!(raw.contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn test_transcript_rejects_path_like_ids() {
+        let dir = Path::new("/tmp");
+        assert!(SessionTranscript::path(dir, "../etc/passwd").is_err());
+        assert!(SessionTranscript::path(dir, "").is_err());
+        assert!(SessionTranscript::load(dir, "a/b").is_err());
+    }
+}